@@ -1,7 +1,10 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{DeriveInput, Error};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, Lit, LitStr, Meta, MetaNameValue,
+    Token, Type, Variant,
+};
 
 #[proc_macro_derive(BuildEvent)]
 pub fn build_event_derive(item: TokenStream) -> TokenStream {
@@ -63,3 +66,205 @@ fn machine_derive2(item: TokenStream2) -> Result<TokenStream2, Error> {
 
     Ok(expanded)
 }
+
+/// Implements `control_core::schema::JsonSchema` following the serde attributes of the type
+///
+/// Supports the container attributes `tag` and `content` and the field attribute `default`,
+/// other serde attributes are a compile error so the schema can't silently differ from serde.
+#[proc_macro_derive(JsonSchema, attributes(serde))]
+pub fn json_schema_derive(item: TokenStream) -> TokenStream {
+    json_schema_derive2(item.into())
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct SerdeAttributes {
+    tag: Option<String>,
+    content: Option<String>,
+    default: bool,
+}
+
+fn serde_attributes(attrs: &[Attribute]) -> Result<SerdeAttributes, Error> {
+    let mut serde = SerdeAttributes::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                serde.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("content") {
+                serde.content = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<LitStr>()?;
+                }
+                serde.default = true;
+            } else {
+                return Err(meta.error("serde attribute not supported by JsonSchema"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(serde)
+}
+
+/// Doc comment lines joined with newlines
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn describe(schema: TokenStream2, attrs: &[Attribute]) -> TokenStream2 {
+    let doc = doc_comment(attrs);
+    quote! { control_core::schema::annotate(#schema, "description", #doc) }
+}
+
+/// Schema of the fields of a struct or enum variant
+fn fields_schema(fields: &Fields) -> Result<TokenStream2, Error> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let properties = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let serde = serde_attributes(&field.attrs)?;
+                    let name = field.ident.as_ref().expect("named field").to_string();
+                    let ty = &field.ty;
+                    let required = !serde.default && !is_option(ty);
+                    let schema = describe(
+                        quote! { <#ty as control_core::schema::JsonSchema>::json_schema() },
+                        &field.attrs,
+                    );
+                    Ok(quote! { (#name, #schema, #required) })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! { control_core::schema::object_schema(vec![#(#properties),*]) }
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            quote! { <#ty as control_core::schema::JsonSchema>::json_schema() }
+        }
+        Fields::Unnamed(fields) => {
+            let items = fields.unnamed.iter().map(|field| {
+                let ty = &field.ty;
+                quote! { <#ty as control_core::schema::JsonSchema>::json_schema() }
+            });
+            quote! { control_core::schema::tuple_schema(vec![#(#items),*]) }
+        }
+        Fields::Unit => quote! { <() as control_core::schema::JsonSchema>::json_schema() },
+    })
+}
+
+fn variant_schema(variant: &Variant, container: &SerdeAttributes) -> Result<TokenStream2, Error> {
+    let variant_serde = serde_attributes(&variant.attrs)?;
+    if variant_serde.default || variant_serde.tag.is_some() || variant_serde.content.is_some() {
+        return Err(Error::new_spanned(
+            variant,
+            "serde attribute not supported on variants by JsonSchema",
+        ));
+    }
+
+    let name = variant.ident.to_string();
+    let is_unit = matches!(variant.fields, Fields::Unit);
+    let schema = match (&container.tag, &container.content) {
+        // externally tagged
+        (None, _) if is_unit => quote! { control_core::schema::const_schema(#name) },
+        (None, _) => {
+            let fields = fields_schema(&variant.fields)?;
+            quote! { control_core::schema::single_property_schema(#name, #fields) }
+        }
+        // adjacently tagged
+        (Some(tag), Some(_)) if is_unit => quote! {
+            control_core::schema::object_schema(vec![
+                (#tag, control_core::schema::const_schema(#name), true),
+            ])
+        },
+        (Some(tag), Some(content)) => {
+            let fields = fields_schema(&variant.fields)?;
+            quote! {
+                control_core::schema::object_schema(vec![
+                    (#tag, control_core::schema::const_schema(#name), true),
+                    (#content, #fields, true),
+                ])
+            }
+        }
+        (Some(_), None) => {
+            return Err(Error::new_spanned(
+                variant,
+                "internally tagged enums are not supported by JsonSchema",
+            ));
+        }
+    };
+    Ok(describe(schema, &variant.attrs))
+}
+
+fn json_schema_derive2(item: TokenStream2) -> Result<TokenStream2, Error> {
+    let ast: DeriveInput = syn::parse2(item)?;
+    let ident = &ast.ident;
+    let title = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let serde = serde_attributes(&ast.attrs)?;
+
+    let schema = match &ast.data {
+        Data::Struct(data) => fields_schema(&data.fields)?,
+        Data::Enum(data)
+            if serde.tag.is_none()
+                && data
+                    .variants
+                    .iter()
+                    .all(|variant| matches!(variant.fields, Fields::Unit)) =>
+        {
+            let names = data
+                .variants
+                .iter()
+                .map(|variant| variant.ident.to_string());
+            quote! { control_core::schema::string_enum_schema(&[#(#names),*]) }
+        }
+        Data::Enum(data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| variant_schema(variant, &serde))
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote! { control_core::schema::one_of_schema(vec![#(#variants),*]) }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "unions are not supported by JsonSchema",
+            ));
+        }
+    };
+    let schema = describe(schema, &ast.attrs);
+
+    Ok(quote! {
+        impl #impl_generics control_core::schema::JsonSchema for #ident #ty_generics #where_clause {
+            fn json_schema() -> control_core::schema::Value {
+                control_core::schema::annotate(#schema, "title", #title)
+            }
+        }
+    })
+}
@@ -0,0 +1,255 @@
+use anyhow::bail;
+
+/// Position relation between a master and a slave axis.
+///
+/// Units are up to the caller, e.g. revolutions of a spool as master and
+/// millimeters of a traverse as slave.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GearProfile {
+    /// Slave moves `ratio` units per master unit
+    Ratio(f64),
+    /// Slave follows a cam table
+    Cam(CamTable),
+}
+
+impl GearProfile {
+    /// Slave position for a master position, relative to master and slave 0
+    pub fn position(&self, master: f64) -> f64 {
+        match self {
+            Self::Ratio(ratio) => ratio * master,
+            Self::Cam(cam) => cam.position(master),
+        }
+    }
+
+    /// Slave units per master unit at a master position
+    pub fn slope(&self, master: f64) -> f64 {
+        match self {
+            Self::Ratio(ratio) => *ratio,
+            Self::Cam(cam) => cam.slope(master),
+        }
+    }
+}
+
+/// Slave positions at master positions, linearly interpolated in between.
+///
+/// The table repeats every [`CamTable::period`] master units. If the last
+/// slave position differs from the first, every period adds that difference,
+/// so a cam can also feed the slave forward.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CamTable {
+    /// (master, slave) pairs, master starting at 0 and strictly increasing
+    points: Vec<(f64, f64)>,
+}
+
+impl CamTable {
+    pub fn new(points: Vec<(f64, f64)>) -> anyhow::Result<Self> {
+        if points.len() < 2 {
+            bail!("A cam table needs at least 2 points, got {}", points.len());
+        }
+        if points[0].0 != 0.0 {
+            bail!("A cam table must start at master 0, got {}", points[0].0);
+        }
+        if points.iter().any(|(m, s)| !m.is_finite() || !s.is_finite()) {
+            bail!("Cam table points must be finite");
+        }
+        if points.windows(2).any(|w| w[1].0 <= w[0].0) {
+            bail!("Cam table master positions must be strictly increasing");
+        }
+        Ok(Self { points })
+    }
+
+    /// Master travel after which the table repeats
+    pub fn period(&self) -> f64 {
+        self.points[self.points.len() - 1].0
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    pub fn position(&self, master: f64) -> f64 {
+        let (cycles, phase) = self.wrap(master);
+        let rise = self.points[self.points.len() - 1].1 - self.points[0].1;
+        let i = self.segment(phase);
+        let ((m0, s0), (m1, s1)) = (self.points[i], self.points[i + 1]);
+        cycles.mul_add(rise, s0) + (s1 - s0) * (phase - m0) / (m1 - m0)
+    }
+
+    pub fn slope(&self, master: f64) -> f64 {
+        let (_, phase) = self.wrap(master);
+        let i = self.segment(phase);
+        let ((m0, s0), (m1, s1)) = (self.points[i], self.points[i + 1]);
+        (s1 - s0) / (m1 - m0)
+    }
+
+    /// Splits a master position into whole periods and the phase within
+    fn wrap(&self, master: f64) -> (f64, f64) {
+        let period = self.period();
+        let cycles = (master / period).floor();
+        (cycles, cycles.mul_add(-period, master).clamp(0.0, period))
+    }
+
+    /// Index of the segment containing `phase`, the later one at a point
+    fn segment(&self, phase: f64) -> usize {
+        let after = self.points.partition_point(|&(m, _)| m <= phase);
+        after.clamp(1, self.points.len() - 1) - 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Engagement {
+    /// Master position at engagement
+    master_origin: f64,
+    /// Profile master position at engagement
+    phase: f64,
+    /// Added to the profile position
+    slave_offset: f64,
+}
+
+/// Electronic gearing and camming.
+///
+/// Links a slave axis to the *position* of a master axis through a
+/// [`GearProfile`]. Each cycle [`ElectronicGear::update`] returns a slave
+/// velocity: the profile slope times the master velocity as feedforward plus
+/// a proportional correction towards the target position. Speed changes of
+/// the master thus keep the slave phase-locked instead of accumulating drift
+/// like a pure velocity coupling.
+///
+/// # Example
+/// ```ignore
+/// // Slave follows 1.75 mm per master revolution
+/// let mut gear = ElectronicGear::new(GearProfile::Ratio(1.75), 10.0, 5.0);
+/// gear.engage(master_revolutions, 0.0, slave_mm);
+/// let slave_speed = gear.update(master_revolutions, master_rev_s, slave_mm);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ElectronicGear {
+    profile: GearProfile,
+    /// Correction speed per unit of position error (1/s)
+    position_gain: f64,
+    /// Largest correction speed (slave units/s)
+    max_correction: f64,
+    engagement: Option<Engagement>,
+}
+
+impl ElectronicGear {
+    pub const fn new(profile: GearProfile, position_gain: f64, max_correction: f64) -> Self {
+        Self {
+            profile,
+            position_gain,
+            max_correction,
+            engagement: None,
+        }
+    }
+
+    pub const fn profile(&self) -> &GearProfile {
+        &self.profile
+    }
+
+    /// Replaces the profile. An engaged gear keeps its phase, call
+    /// [`Self::engage`] again to re-anchor it.
+    pub fn set_profile(&mut self, profile: GearProfile) {
+        self.profile = profile;
+    }
+
+    /// Couples the slave to the master from now on: at `master_position` the
+    /// profile is at `phase` and the slave target is
+    /// `slave_offset + profile.position(phase)`.
+    pub const fn engage(&mut self, master_position: f64, phase: f64, slave_offset: f64) {
+        self.engagement = Some(Engagement {
+            master_origin: master_position,
+            phase,
+            slave_offset,
+        });
+    }
+
+    pub const fn disengage(&mut self) {
+        self.engagement = None;
+    }
+
+    pub const fn is_engaged(&self) -> bool {
+        self.engagement.is_some()
+    }
+
+    /// Profile master position, `None` if disengaged
+    pub fn phase(&self, master_position: f64) -> Option<f64> {
+        let e = self.engagement?;
+        Some(e.phase + master_position - e.master_origin)
+    }
+
+    /// Where the slave should be, `None` if disengaged
+    pub fn slave_target(&self, master_position: f64) -> Option<f64> {
+        let phase = self.phase(master_position)?;
+        Some(self.engagement?.slave_offset + self.profile.position(phase))
+    }
+
+    /// Slave velocity for this cycle, `None` if disengaged
+    pub fn update(
+        &self,
+        master_position: f64,
+        master_velocity: f64,
+        slave_position: f64,
+    ) -> Option<f64> {
+        let phase = self.phase(master_position)?;
+        let target = self.slave_target(master_position)?;
+        let correction = (self.position_gain * (target - slave_position))
+            .clamp(-self.max_correction, self.max_correction);
+        Some(
+            self.profile
+                .slope(phase)
+                .mul_add(master_velocity, correction),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn triangle() -> CamTable {
+        CamTable::new(vec![(0.0, 0.0), (10.0, -20.0), (20.0, 0.0)]).unwrap()
+    }
+
+    #[test]
+    fn test_cam_table() {
+        let cam = triangle();
+        assert_relative_eq!(cam.position(5.0), -10.0);
+        assert_relative_eq!(cam.position(15.0), -10.0);
+        assert_relative_eq!(cam.position(45.0), -10.0);
+        assert_relative_eq!(cam.position(-5.0), -10.0);
+        assert_relative_eq!(cam.slope(3.0), -2.0);
+        assert_relative_eq!(cam.slope(10.0), 2.0);
+        assert_relative_eq!(cam.slope(20.0), -2.0);
+
+        // A feeding cam rises by its last point every period
+        let feed = CamTable::new(vec![(0.0, 0.0), (1.0, 0.0), (2.0, 3.0)]).unwrap();
+        assert_relative_eq!(feed.position(5.5), 7.5);
+
+        assert!(CamTable::new(vec![(0.0, 0.0)]).is_err());
+        assert!(CamTable::new(vec![(1.0, 0.0), (2.0, 0.0)]).is_err());
+        assert!(CamTable::new(vec![(0.0, 0.0), (2.0, 0.0), (2.0, 1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_slave_stays_phase_locked() {
+        let mut gear = ElectronicGear::new(GearProfile::Cam(triangle()), 20.0, 50.0);
+        assert_eq!(gear.update(0.0, 1.0, 0.0), None);
+        gear.engage(100.0, 0.0, 80.0);
+
+        // Master speeds up and slows down, the slave integrates the output
+        let (dt, mut master, mut slave) = (0.001, 100.0, 80.0);
+        for i in 0..60_000 {
+            let master_velocity = (i as f64 * 0.0005).sin().mul_add(0.8, 1.0);
+            slave = gear
+                .update(master, master_velocity, slave)
+                .unwrap()
+                .mul_add(dt, slave);
+            master = master_velocity.mul_add(dt, master);
+        }
+        let target = gear.slave_target(master).unwrap();
+        assert_relative_eq!(target, 80.0 + triangle().position(master - 100.0));
+        assert!((slave - target).abs() < 0.1);
+    }
+}
@@ -610,10 +610,11 @@ impl AccelerationPositionController {
             self.target_position + decel_distance
         };
 
-        // Determine initial motion phase
+        // Determine initial motion phase. Signed compare: moving away from
+        // the new target at full speed must still ramp through zero
         if self.approx_equal(
-            self.current_speed.abs(),
-            self.peak_speed.abs(),
+            self.current_speed,
+            self.peak_speed,
             self.config.speed_tolerance,
         ) {
             self.motion_phase = MotionPhase::ConstantSpeed;
@@ -1184,6 +1185,21 @@ mod tests {
         assert_eq!(controller.get_target_position(), 50.0);
     }
 
+    #[test]
+    fn test_reverse_at_full_speed() {
+        let mut controller = AccelerationPositionController::new_simple(None, 10.0, 2.0).unwrap();
+
+        // Run up to full speed, then put the target behind
+        for _ in 0..100 {
+            let _ = controller.update(100.0, 0.1);
+        }
+        assert_eq!(controller.get_speed(), 10.0);
+        let _ = controller.update(0.0, 0.1);
+
+        // The speed ramps down instead of jumping to -10
+        assert!((controller.get_speed() - 9.8).abs() < 1e-9);
+    }
+
     #[test]
     fn test_emergency_stop() {
         let mut controller = AccelerationPositionController::builder()
@@ -8,6 +8,7 @@ pub mod helpers;
 pub mod irq_handling;
 pub mod modbus;
 pub mod realtime;
+pub mod schema;
 pub mod serial;
 pub mod socketio;
 pub mod transmission;
@@ -0,0 +1,140 @@
+//! JSON Schemas of the serde types of the machine APIs
+//!
+//! Derive [`JsonSchema`] with `#[derive(JsonSchema)]` from `control_core_derive`. The derive
+//! follows the serde attributes used in the machine APIs: `tag`, `content` and `default`. Doc
+//! comments become descriptions.
+
+use std::collections::{BTreeMap, HashMap};
+
+pub use serde_json::Value;
+use serde_json::{Map, json};
+
+pub mod validate;
+
+pub trait JsonSchema {
+    fn json_schema() -> Value;
+}
+
+macro_rules! impl_json_schema {
+    ($schema:expr, $($ty:ty),+) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Value {
+                    $schema
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_json_schema_integer {
+    ($($ty:ty),+) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Value {
+                    json!({ "type": "integer", "minimum": <$ty>::MIN, "maximum": <$ty>::MAX })
+                }
+            }
+        )+
+    };
+}
+
+impl_json_schema!(json!({ "type": "boolean" }), bool);
+impl_json_schema!(json!({ "type": "number" }), f32, f64);
+impl_json_schema!(json!({ "type": "string" }), String, str);
+impl_json_schema!(json!({ "type": "null" }), ());
+impl_json_schema_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<T: JsonSchema + ?Sized> JsonSchema for &T {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Box<T> {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchema, const N: usize> JsonSchema for [T; N] {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema(), "minItems": N, "maxItems": N })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for HashMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for BTreeMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+// Building blocks for the derive macro
+
+/// Adds `title` or `description` to `schema`, empty strings are left out
+pub fn annotate(mut schema: Value, keyword: &str, text: &str) -> Value {
+    if let (Some(object), false) = (schema.as_object_mut(), text.is_empty()) {
+        object.insert(keyword.to_string(), Value::String(text.to_string()));
+    }
+    schema
+}
+
+/// Object with the given `(name, schema, required)` properties
+pub fn object_schema(properties: Vec<(&str, Value, bool)>) -> Value {
+    let required: Vec<&str> = properties
+        .iter()
+        .filter(|(_, _, required)| *required)
+        .map(|(name, _, _)| *name)
+        .collect();
+    let properties: Map<String, Value> = properties
+        .into_iter()
+        .map(|(name, schema, _)| (name.to_string(), schema))
+        .collect();
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+/// Object with exactly one property, an externally tagged enum variant
+pub fn single_property_schema(name: &str, schema: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": { name: schema },
+        "required": [name],
+        "additionalProperties": false,
+    })
+}
+
+pub fn const_schema(value: &str) -> Value {
+    json!({ "const": value })
+}
+
+pub fn string_enum_schema(values: &[&str]) -> Value {
+    json!({ "type": "string", "enum": values })
+}
+
+/// Array with one item per schema, a tuple
+pub fn tuple_schema(items: Vec<Value>) -> Value {
+    let len = items.len();
+    json!({ "type": "array", "prefixItems": items, "minItems": len, "maxItems": len })
+}
+
+pub fn one_of_schema(variants: Vec<Value>) -> Value {
+    json!({ "oneOf": variants })
+}
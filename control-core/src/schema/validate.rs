@@ -0,0 +1,437 @@
+use std::fmt;
+
+use serde_json::Value;
+
+/// A value that doesn't match its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON pointer to the value, empty for the root
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Checks `value` against a schema from [`super::JsonSchema`]
+///
+/// Supports the keywords the derive produces. `oneOf` is checked like `anyOf`, the variants of
+/// a serde enum never overlap. If no variant matches, the errors are reported for the variant
+/// selected by the tag of `value`.
+pub fn validate(schema: &Value, value: &Value) -> Result<(), Vec<ValidationError>> {
+    let mut errors = vec![];
+    validate_at(schema, value, "", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let mut error = |message: String| {
+        errors.push(ValidationError {
+            path: path.to_string(),
+            message,
+        });
+    };
+
+    if let Some(types) = schema.get("type") {
+        if !type_matches(types, value) {
+            error(format!(
+                "expected {}, found {}",
+                type_names(types),
+                kind(value)
+            ));
+            return;
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            error(format!("expected {}, found {}", expected, value));
+            return;
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            error(format!(
+                "expected one of {}, found {}",
+                join(allowed),
+                value
+            ));
+            return;
+        }
+    }
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                error(format!("{} is less than the minimum {}", value, minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                error(format!("{} is greater than the maximum {}", value, maximum));
+            }
+        }
+    }
+
+    if let Some(variants) = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+    {
+        validate_variants(variants, value, path, errors);
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(ValidationError {
+                            path: path.to_string(),
+                            message: format!("missing field `{}`", name),
+                        });
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in object {
+                let field_path = format!("{}/{}", path, name.replace('~', "~0").replace('/', "~1"));
+                match (
+                    properties.and_then(|p| p.get(name)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(field_schema), _) => {
+                        validate_at(field_schema, field, &field_path, errors);
+                    }
+                    (None, Some(Value::Bool(false))) => {
+                        let expected = properties
+                            .map(|p| p.keys().map(|k| format!("`{}`", k)).collect::<Vec<_>>())
+                            .unwrap_or_default();
+                        errors.push(ValidationError {
+                            path: path.to_string(),
+                            message: format!(
+                                "unknown field `{}`, expected {}",
+                                name,
+                                expected.join(", ")
+                            ),
+                        });
+                    }
+                    (None, Some(additional)) => {
+                        validate_at(additional, field, &field_path, errors);
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if len < min {
+                    errors.push(ValidationError {
+                        path: path.to_string(),
+                        message: format!("expected at least {} items, found {}", min, len),
+                    });
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if len > max {
+                    errors.push(ValidationError {
+                        path: path.to_string(),
+                        message: format!("expected at most {} items, found {}", max, len),
+                    });
+                }
+            }
+            let prefix = schema.get("prefixItems").and_then(Value::as_array);
+            for (index, item) in items.iter().enumerate() {
+                let item_schema = prefix
+                    .and_then(|p| p.get(index))
+                    .or_else(|| schema.get("items"));
+                if let Some(item_schema) = item_schema {
+                    validate_at(item_schema, item, &format!("{}/{}", path, index), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_variants(
+    variants: &[Value],
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut variant_errors: Vec<Vec<ValidationError>> = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let mut errors = vec![];
+        validate_at(variant, value, path, &mut errors);
+        if errors.is_empty() {
+            return;
+        }
+        variant_errors.push(errors);
+    }
+
+    // report the errors of the variant the value is meant to be
+    let selected: Vec<usize> = (0..variants.len())
+        .filter(|index| tag_matches(&variants[*index], value))
+        .collect();
+    let selected = match selected.as_slice() {
+        [] => (0..variants.len())
+            .filter(|index| {
+                variants[*index]
+                    .get("type")
+                    .is_some_and(|types| type_matches(types, value))
+            })
+            .collect(),
+        _ => selected,
+    };
+    if let [index] = selected.as_slice() {
+        errors.append(&mut variant_errors[*index]);
+        return;
+    }
+
+    let names: Vec<String> = variants.iter().filter_map(variant_name).collect();
+    let types: Vec<&Value> = variants.iter().filter_map(|v| v.get("type")).collect();
+    let message = if names.len() == variants.len() {
+        format!(
+            "unknown variant {}, expected one of {}",
+            tag_summary(variants, value),
+            names.join(", ")
+        )
+    } else if types.len() == variants.len() {
+        let types: Vec<String> = types.into_iter().map(type_names).collect();
+        format!("expected {}, found {}", types.join(" or "), kind(value))
+    } else {
+        format!("{} doesn't match any variant", tag_summary(variants, value))
+    };
+    errors.push(ValidationError {
+        path: path.to_string(),
+        message,
+    });
+}
+
+/// Whether the tag of `value` selects the `variant`
+fn tag_matches(variant: &Value, value: &Value) -> bool {
+    if let Some(expected) = variant.get("const") {
+        return expected == value;
+    }
+    let (Some(properties), Some(object)) = (
+        variant.get("properties").and_then(Value::as_object),
+        value.as_object(),
+    ) else {
+        return false;
+    };
+
+    // adjacently tagged
+    let mut has_tag = false;
+    for (name, property) in properties {
+        if let Some(expected) = property.get("const") {
+            if object.get(name) != Some(expected) {
+                return false;
+            }
+            has_tag = true;
+        }
+    }
+    if has_tag {
+        return true;
+    }
+
+    // externally tagged
+    properties.len() == 1
+        && variant.get("additionalProperties") == Some(&Value::Bool(false))
+        && properties.keys().all(|name| object.contains_key(name))
+}
+
+fn variant_name(variant: &Value) -> Option<String> {
+    if let Some(name) = variant.get("const") {
+        return Some(name.to_string());
+    }
+    let properties = variant.get("properties")?.as_object()?;
+    if let Some(tag) = properties.values().find_map(|p| p.get("const")) {
+        return Some(tag.to_string());
+    }
+    match properties.keys().collect::<Vec<_>>().as_slice() {
+        [name] => Some(format!("{{\"{}\": ...}}", name)),
+        _ => None,
+    }
+}
+
+/// The tag of `value` if the variants are adjacently tagged, otherwise its keys or the value
+fn tag_summary(variants: &[Value], value: &Value) -> String {
+    let tag = variants
+        .iter()
+        .filter_map(|v| v.get("properties")?.as_object())
+        .flat_map(|properties| properties.iter())
+        .find(|(name, property)| property.get("const").is_some() && value.get(name).is_some())
+        .and_then(|(name, _)| value.get(name));
+    if let Some(tag) = tag {
+        return tag.to_string();
+    }
+
+    match value {
+        Value::Object(object) => {
+            let keys: Vec<String> = object.keys().map(|k| format!("\"{}\"", k)).collect();
+            format!("{{{}}}", keys.join(", "))
+        }
+        Value::Array(_) => "array".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn type_matches(types: &Value, value: &Value) -> bool {
+    match types {
+        Value::String(name) => is_type(name, value),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| is_type(name, value)),
+        _ => true,
+    }
+}
+
+fn is_type(name: &str, value: &Value) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn type_names(types: &Value) -> String {
+    match types {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Value::String(name) => name.clone(),
+        _ => types.to_string(),
+    }
+}
+
+const fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn join(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::schema::{
+        JsonSchema, const_schema, object_schema, one_of_schema, single_property_schema,
+    };
+
+    fn messages(schema: &Value, value: Value) -> Vec<String> {
+        validate(schema, &value)
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_adjacently_tagged() {
+        // #[serde(tag = "action", content = "value")]
+        // enum Mutation { SetOutput { index: usize, on: bool }, Reset }
+        let schema = one_of_schema(vec![
+            object_schema(vec![
+                ("action", const_schema("SetOutput"), true),
+                (
+                    "value",
+                    object_schema(vec![
+                        ("index", usize::json_schema(), true),
+                        ("on", bool::json_schema(), true),
+                    ]),
+                    true,
+                ),
+            ]),
+            object_schema(vec![("action", const_schema("Reset"), true)]),
+        ]);
+
+        assert!(validate(&schema, &json!({ "action": "Reset" })).is_ok());
+        assert!(
+            validate(
+                &schema,
+                &json!({ "action": "SetOutput", "value": { "index": 2, "on": true } })
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            messages(
+                &schema,
+                json!({ "action": "SetOutput", "value": { "index": -1 } })
+            ),
+            vec![
+                "/value: missing field `on`",
+                "/value/index: -1 is less than the minimum 0",
+            ]
+        );
+        assert_eq!(
+            messages(&schema, json!({ "action": "Explode" })),
+            vec!["unknown variant \"Explode\", expected one of \"SetOutput\", \"Reset\"",]
+        );
+    }
+
+    #[test]
+    fn test_validate_externally_tagged() {
+        // enum Mutation { SetSpeed(f64), SetLimit(Option<f64>), Home }
+        let schema = one_of_schema(vec![
+            single_property_schema("SetSpeed", f64::json_schema()),
+            single_property_schema("SetLimit", Option::<f64>::json_schema()),
+            const_schema("Home"),
+        ]);
+
+        assert!(validate(&schema, &json!("Home")).is_ok());
+        assert!(validate(&schema, &json!({ "SetLimit": null })).is_ok());
+        assert_eq!(
+            messages(&schema, json!({ "SetSpeed": "fast" })),
+            vec!["/SetSpeed: expected number, found string"]
+        );
+        assert_eq!(
+            messages(&schema, json!({ "SetLimit": true })),
+            vec!["/SetLimit: expected number or null, found boolean"]
+        );
+        assert_eq!(
+            messages(&schema, json!({ "SetSpeeed": 1.0 })),
+            vec![
+                "unknown variant {\"SetSpeeed\"}, expected one of {\"SetSpeed\": ...}, {\"SetLimit\": ...}, \"Home\"",
+            ]
+        );
+    }
+}
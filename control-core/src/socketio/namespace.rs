@@ -4,15 +4,27 @@ use socketioxide::extract::SocketRef;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tracing::instrument;
 
+/// An event for one socket, sent from a [`Namespace`] to the socket.io fan-out
+#[derive(Debug, Clone)]
+pub struct SocketQueueItem {
+    pub socket: SocketRef,
+    pub event: Arc<GenericEvent>,
+    /// Cached event re-emitted to a newly subscribed socket
+    ///
+    /// Replays are history like the power graph of the last hour, they are never coalesced or
+    /// dropped.
+    pub replay: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Namespace {
     pub sockets: Vec<SocketRef>,
     pub events: HashMap<String, Vec<Arc<GenericEvent>>>,
-    pub socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>,
+    pub socket_queue_tx: Sender<SocketQueueItem>,
 }
 
 impl Namespace {
-    pub fn new(socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>) -> Self {
+    pub fn new(socket_queue_tx: Sender<SocketQueueItem>) -> Self {
         Self {
             sockets: vec![],
             events: HashMap::new(),
@@ -81,7 +93,7 @@ impl Namespace {
         for (_event_name, events) in event_groups {
             for event in events {
                 // Send to global queue instead of per-socket queue
-                self.send_to_queue(&socket, event, "reemit");
+                self.send_to_queue(&socket, event, true);
             }
         }
     }
@@ -121,7 +133,7 @@ impl Namespace {
         // emit the event - inlined from emit function
         // Send to global queue for each socket in the namespace
         for socket in self.sockets.clone() {
-            self.send_to_queue(&socket, &event, "emit");
+            self.send_to_queue(&socket, &event, false);
         }
     }
 
@@ -131,24 +143,25 @@ impl Namespace {
     ///
     /// * `socket` - The socket to send the event to
     /// * `event` - The event to be sent
-    /// * `operation` - A description of the operation for logging purposes (e.g., "reemit", "emit")
+    /// * `replay` - Whether the event is re-emitted from the cache, see [`SocketQueueItem::replay`]
     #[instrument(skip_all)]
-    fn send_to_queue(&self, socket: &SocketRef, event: &Arc<GenericEvent>, operation: &str) {
+    fn send_to_queue(&self, socket: &SocketRef, event: &Arc<GenericEvent>, replay: bool) {
         tracing::trace!(
             socket_id = ?socket.id,
             event = %event.name,
-            operation = %operation,
+            replay = %replay,
             "Sending event to global queue"
         );
-        match self
-            .socket_queue_tx
-            .try_send((socket.clone(), event.clone()))
-        {
+        match self.socket_queue_tx.try_send(SocketQueueItem {
+            socket: socket.clone(),
+            event: event.clone(),
+            replay,
+        }) {
             Ok(_) => {
                 tracing::trace!(
                     socket_id = ?socket.id,
                     event = %event.name,
-                    operation = %operation,
+                    replay = %replay,
                     "Successfully sent event to global queue"
                 );
             }
@@ -156,7 +169,7 @@ impl Namespace {
                 tracing::error!(
                     socket_id = ?socket.id,
                     event = %event.name,
-                    operation = %operation,
+                    replay = %replay,
                     error = %e,
                     "Failed to send event to global queue"
                 );
@@ -87,11 +87,37 @@ export type EthercatInterfaceDiscoveryEvent = z.infer<
   typeof ethercatInterfaceDiscoveryEventSchema
 >;
 
+// Machines whose act took longer than their budget
+export const machineActBudgetEventDataSchema = z.object({
+  machines: z.array(
+    z.object({
+      machine_identification_unique: machineIdentificationUnique,
+      executor: z.enum(["real_time", "non_real_time"]),
+      budget_ns: z.number().int(),
+      max_act_ns: z.number().int(),
+      overruns: z.number().int(),
+    }),
+  ),
+});
+
+export type MachineActBudgetEventData = z.infer<
+  typeof machineActBudgetEventDataSchema
+>;
+
+export const machineActBudgetEventSchema = eventSchema(
+  machineActBudgetEventDataSchema,
+);
+
+export type MachineActBudgetEvent = z.infer<
+  typeof machineActBudgetEventSchema
+>;
+
 // Update the main namespace store schema
 export const mainNamespaceStoreSchema = z.object({
   ethercatDevices: ethercatDevicesEventSchema.nullable(),
   machines: machinesEventSchema.nullable(),
   ethercatInterfaceDiscovery: ethercatInterfaceDiscoveryEventSchema.nullable(),
+  machineActBudget: machineActBudgetEventSchema.nullable(),
 });
 
 export type MainNamespaceStore = z.infer<typeof mainNamespaceStoreSchema>;
@@ -101,12 +127,14 @@ export const createMainNamespaceStore = (): StoreApi<MainNamespaceStore> => {
     ethercatDevices: null,
     machines: null,
     ethercatInterfaceDiscovery: null,
+    machineActBudget: null,
   }));
 };
 
 export const eventSchemaMap = {
   EthercatDevicesEvent: ethercatDevicesEventSchema,
   MachinesEvent: machinesEventSchema,
+  MachineActBudgetEvent: machineActBudgetEventSchema,
 };
 
 export function mainMessageHandler(
@@ -135,6 +163,12 @@ export function mainMessageHandler(
           ...state,
           ethercatInterfaceDiscovery: validatedEvent,
         }));
+      } else if (eventName === "MachineActBudgetEvent") {
+        const validatedEvent = machineActBudgetEventSchema.parse(event);
+        store.setState((state) => ({
+          ...state,
+          machineActBudget: validatedEvent,
+        }));
       } else {
         // Ignore unknown events instead of throwing error
         console.warn(`mainNamespace: Unknown event "${eventName}" ignored`);
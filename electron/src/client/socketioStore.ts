@@ -252,6 +252,75 @@ export function deserializeNamespaceId(namespaceId: string): NamespaceId {
   }
 }
 
+const PATCH_SUFFIX = "Patch";
+
+const patchOperationSchema = z.object({
+  op: z.enum(["add", "remove", "replace"]),
+  path: z.string(),
+  value: z.any().optional(),
+});
+
+/**
+ * Applies JSON patch operations (RFC 6902, only add/remove/replace) to a copy of `base`
+ */
+export function applyPatch(
+  base: unknown,
+  operations: z.infer<typeof patchOperationSchema>[],
+): unknown {
+  const root = { value: structuredClone(base) };
+  for (const operation of operations) {
+    const keys = operation.path
+      .split("/")
+      .slice(1)
+      .map((key) => key.replace(/~1/g, "/").replace(/~0/g, "~"));
+    let parent: any = root;
+    let key = "value";
+    for (const next of keys) {
+      parent = parent[key];
+      key = next;
+    }
+    if (operation.op === "remove") {
+      if (Array.isArray(parent)) {
+        parent.splice(Number(key), 1);
+      } else {
+        delete parent[key];
+      }
+    } else {
+      parent[key] = operation.value;
+    }
+  }
+  return root.value;
+}
+
+/**
+ * Turns a `<Name>Patch` event into the full `<Name>` event
+ *
+ * Full events are stored in `bases`, patches are applied to them. Returns
+ * undefined for a patch without a full event before it.
+ */
+export function resolvePatchEvent(
+  event: Event<any>,
+  bases: Record<string, unknown>,
+): Event<any> | undefined {
+  if (!event.name.endsWith(PATCH_SUFFIX)) {
+    bases[event.name] = event.data;
+    return event;
+  }
+  const name = event.name.slice(0, -PATCH_SUFFIX.length);
+  const operations = z.array(patchOperationSchema).safeParse(event.data);
+  if (!operations.success) {
+    toastZodError(operations.error, `Invalid patch for ${name}`);
+    return undefined;
+  }
+  if (!(name in bases)) {
+    // the server sends the full event again with the next snapshot
+    return undefined;
+  }
+  const data = applyPatch(bases[name], operations.data);
+  bases[name] = data;
+  return { name, data, ts: event.ts };
+}
+
 type SocketioStore = {
   baseUrl: string;
   namespaces: Record<string, Namespace<unknown>>;
@@ -321,9 +390,17 @@ const useSocketioStore = create<SocketioStore>()((set, get) => ({
       );
     };
 
+    // last full data of events the server sends as patches
+    const patchBases: Record<string, unknown> = {};
+
     // add handlers
     socket.on("connect", () => {
       console.log(`Connected to ${namespace_path}`);
+      // state changes arrive as JSON patches, the cached events are sent again
+      socket.emit("subscribe", { delta: true });
+      for (const name of Object.keys(patchBases)) {
+        delete patchBases[name];
+      }
       // Fresh store on every (re)connect so cached first/last events
       // repopulate cleanly after a reconnect.
       resetStore(set);
@@ -349,8 +426,12 @@ const useSocketioStore = create<SocketioStore>()((set, get) => ({
       if (!event_parsed.success) {
         toastZodError(event_parsed.error, "Invalid event");
         return;
+      }
+      const full = resolvePatchEvent(event_parsed.data, patchBases);
+      if (full === undefined) {
+        return;
       } // handle the event
-      get().namespaces[namespace_path].handler(event_parsed.data);
+      get().namespaces[namespace_path].handler(full);
     });
     // store the namespace initally
     set(
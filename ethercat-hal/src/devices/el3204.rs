@@ -50,6 +50,8 @@ impl TemperatureInputDevice<EL3204Port> for EL3204 {
             limit1: channel.limit1,
             limit2: channel.limit2,
             error: channel.error,
+            // RTD wire breaks are reported as overvoltage
+            open_circuit: false,
             txpdo_state: channel.txpdo_state,
             txpdo_toggle: channel.txpdo_toggle,
         }
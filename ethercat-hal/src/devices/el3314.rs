@@ -0,0 +1,182 @@
+use crate::coe::{ConfigurableDevice, Configuration};
+use crate::helpers::ethercrab_types::EthercrabSubDevicePreoperational;
+use crate::pdo::TxPdo;
+use crate::shared_config::el331x::{
+    EL331X_TC_SETTINGS_BASE_INDEX, EL331XChannelConfiguration, EL331XFilterSettings,
+    is_open_circuit,
+};
+use crate::{
+    io::temperature_input::{TemperatureInputDevice, TemperatureInputInput},
+    pdo::el331x::TcInput,
+};
+use ethercat_hal_derive::{EthercatDevice, TxPdo};
+
+use super::{EthercatDeviceProcessing, NewEthercatDevice, SubDeviceIdentityTuple};
+
+/// EL3314 4-channel temperature input device
+///
+/// Thermocouple (types J, K, L, E, T, N, U, B, R, S, C) and mV measurement
+#[derive(EthercatDevice)]
+pub struct EL3314 {
+    pub txpdo: EL3314TxPdo,
+    pub configuration: EL3314Configuration,
+    is_used: bool,
+}
+
+impl EthercatDeviceProcessing for EL3314 {}
+
+impl std::fmt::Debug for EL3314 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EL3314")
+    }
+}
+
+impl NewEthercatDevice for EL3314 {
+    fn new() -> Self {
+        Self {
+            txpdo: EL3314TxPdo::default(),
+            configuration: EL3314Configuration::default(),
+            is_used: false,
+        }
+    }
+}
+
+impl TemperatureInputDevice<EL3314Port> for EL3314 {
+    fn get_input(&self, port: EL3314Port) -> TemperatureInputInput {
+        let expect_text = "All channels should be Some(_)";
+        let channel = match port {
+            EL3314Port::T1 => self.txpdo.channel1.as_ref().expect(expect_text),
+            EL3314Port::T2 => self.txpdo.channel2.as_ref().expect(expect_text),
+            EL3314Port::T3 => self.txpdo.channel3.as_ref().expect(expect_text),
+            EL3314Port::T4 => self.txpdo.channel4.as_ref().expect(expect_text),
+        };
+        TemperatureInputInput {
+            temperature: channel.temperature,
+            undervoltage: channel.underrange,
+            overvoltage: channel.overrange,
+            limit1: channel.limit1,
+            limit2: channel.limit2,
+            error: channel.error,
+            open_circuit: is_open_circuit(channel.overrange, channel.error),
+            txpdo_state: channel.txpdo_state,
+            txpdo_toggle: channel.txpdo_toggle,
+        }
+    }
+}
+
+impl ConfigurableDevice<EL3314Configuration> for EL3314 {
+    async fn write_config<'maindevice>(
+        &mut self,
+        device: &EthercrabSubDevicePreoperational<'maindevice>,
+        config: &EL3314Configuration,
+    ) -> Result<(), anyhow::Error> {
+        config.write_config(device).await?;
+        self.configuration = config.clone();
+        Ok(())
+    }
+
+    fn get_config(&self) -> EL3314Configuration {
+        self.configuration.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EL3314Configuration {
+    /// 0x8000:15, applies to all channels
+    pub filter_settings: EL331XFilterSettings,
+    pub channels: [EL331XChannelConfiguration; 4],
+}
+
+impl Default for EL3314Configuration {
+    fn default() -> Self {
+        Self {
+            filter_settings: EL331XFilterSettings::FIR50Hz,
+            channels: Default::default(),
+        }
+    }
+}
+
+impl Configuration for EL3314Configuration {
+    async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        device
+            .sdo_write(
+                EL331X_TC_SETTINGS_BASE_INDEX,
+                0x15,
+                u16::from(self.filter_settings),
+            )
+            .await?;
+        for (index, channel) in (0u16..).zip(self.channels.iter()) {
+            channel
+                .write_channel_config(device, EL331X_TC_SETTINGS_BASE_INDEX + index * 0x10)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EL3314Port {
+    T1,
+    T2,
+    T3,
+    T4,
+}
+
+#[derive(Debug, Clone, TxPdo)]
+pub struct EL3314TxPdo {
+    #[pdo_object_index(0x1A00)]
+    channel1: Option<TcInput>,
+    #[pdo_object_index(0x1A01)]
+    channel2: Option<TcInput>,
+    #[pdo_object_index(0x1A02)]
+    channel3: Option<TcInput>,
+    #[pdo_object_index(0x1A03)]
+    channel4: Option<TcInput>,
+}
+
+impl Default for EL3314TxPdo {
+    fn default() -> Self {
+        Self {
+            channel1: Some(TcInput::default()),
+            channel2: Some(TcInput::default()),
+            channel3: Some(TcInput::default()),
+            channel4: Some(TcInput::default()),
+        }
+    }
+}
+
+pub const EL3314_VENDOR_ID: u32 = 0x2;
+pub const EL3314_PRODUCT_ID: u32 = 0x0cf23052;
+pub const EL3314_REVISION_A: u32 = 0x00150000;
+
+pub const EL3314_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL3314_VENDOR_ID, EL3314_PRODUCT_ID, EL3314_REVISION_A);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec::prelude::*;
+
+    #[test]
+    fn test_open_circuit() {
+        let mut device = EL3314::new();
+        let mut buffer = [0u8; 16];
+        // channel 1: 235.6°C
+        buffer[1] = 0b1000_0000;
+        buffer[2..4].copy_from_slice(&2356i16.to_le_bytes());
+        // channel 2: overrange + error
+        buffer[4] = 0b0100_0010;
+        buffer[5] = 0b1000_0000;
+        device.txpdo.read(buffer.view_bits::<Lsb0>()).unwrap();
+
+        let input = device.get_input(EL3314Port::T1);
+        assert!(!input.open_circuit);
+        assert!((input.temperature - 235.6).abs() < 0.01);
+
+        let input = device.get_input(EL3314Port::T2);
+        assert!(input.open_circuit);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::coe::{ConfigurableDevice, Configuration};
+use crate::helpers::ethercrab_types::EthercrabSubDevicePreoperational;
+use crate::pdo::TxPdo;
+use crate::shared_config::el331x::{
+    EL331X_TC_SETTINGS_BASE_INDEX, EL331XChannelConfiguration, EL331XFilterSettings,
+    is_open_circuit,
+};
+use crate::{
+    io::temperature_input::{TemperatureInputDevice, TemperatureInputInput},
+    pdo::el331x::TcInput,
+};
+use ethercat_hal_derive::{EthercatDevice, TxPdo};
+
+use super::{EthercatDeviceProcessing, NewEthercatDevice, SubDeviceIdentityTuple};
+
+/// EL3318 8-channel temperature input device
+///
+/// Thermocouple (types J, K, L, E, T, N, U, B, R, S, C) and mV measurement
+#[derive(EthercatDevice)]
+pub struct EL3318 {
+    pub txpdo: EL3318TxPdo,
+    pub configuration: EL3318Configuration,
+    is_used: bool,
+}
+
+impl EthercatDeviceProcessing for EL3318 {}
+
+impl std::fmt::Debug for EL3318 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EL3318")
+    }
+}
+
+impl NewEthercatDevice for EL3318 {
+    fn new() -> Self {
+        Self {
+            txpdo: EL3318TxPdo::default(),
+            configuration: EL3318Configuration::default(),
+            is_used: false,
+        }
+    }
+}
+
+impl TemperatureInputDevice<EL3318Port> for EL3318 {
+    fn get_input(&self, port: EL3318Port) -> TemperatureInputInput {
+        let expect_text = "All channels should be Some(_)";
+        let channel = match port {
+            EL3318Port::T1 => self.txpdo.channel1.as_ref().expect(expect_text),
+            EL3318Port::T2 => self.txpdo.channel2.as_ref().expect(expect_text),
+            EL3318Port::T3 => self.txpdo.channel3.as_ref().expect(expect_text),
+            EL3318Port::T4 => self.txpdo.channel4.as_ref().expect(expect_text),
+            EL3318Port::T5 => self.txpdo.channel5.as_ref().expect(expect_text),
+            EL3318Port::T6 => self.txpdo.channel6.as_ref().expect(expect_text),
+            EL3318Port::T7 => self.txpdo.channel7.as_ref().expect(expect_text),
+            EL3318Port::T8 => self.txpdo.channel8.as_ref().expect(expect_text),
+        };
+        TemperatureInputInput {
+            temperature: channel.temperature,
+            undervoltage: channel.underrange,
+            overvoltage: channel.overrange,
+            limit1: channel.limit1,
+            limit2: channel.limit2,
+            error: channel.error,
+            open_circuit: is_open_circuit(channel.overrange, channel.error),
+            txpdo_state: channel.txpdo_state,
+            txpdo_toggle: channel.txpdo_toggle,
+        }
+    }
+}
+
+impl ConfigurableDevice<EL3318Configuration> for EL3318 {
+    async fn write_config<'maindevice>(
+        &mut self,
+        device: &EthercrabSubDevicePreoperational<'maindevice>,
+        config: &EL3318Configuration,
+    ) -> Result<(), anyhow::Error> {
+        config.write_config(device).await?;
+        self.configuration = config.clone();
+        Ok(())
+    }
+
+    fn get_config(&self) -> EL3318Configuration {
+        self.configuration.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EL3318Configuration {
+    /// 0x8000:15, applies to all channels
+    pub filter_settings: EL331XFilterSettings,
+    pub channels: [EL331XChannelConfiguration; 8],
+}
+
+impl Default for EL3318Configuration {
+    fn default() -> Self {
+        Self {
+            filter_settings: EL331XFilterSettings::FIR50Hz,
+            channels: Default::default(),
+        }
+    }
+}
+
+impl Configuration for EL3318Configuration {
+    async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        device
+            .sdo_write(
+                EL331X_TC_SETTINGS_BASE_INDEX,
+                0x15,
+                u16::from(self.filter_settings),
+            )
+            .await?;
+        for (index, channel) in (0u16..).zip(self.channels.iter()) {
+            channel
+                .write_channel_config(device, EL331X_TC_SETTINGS_BASE_INDEX + index * 0x10)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EL3318Port {
+    T1,
+    T2,
+    T3,
+    T4,
+    T5,
+    T6,
+    T7,
+    T8,
+}
+
+#[derive(Debug, Clone, TxPdo)]
+pub struct EL3318TxPdo {
+    #[pdo_object_index(0x1A00)]
+    channel1: Option<TcInput>,
+    #[pdo_object_index(0x1A01)]
+    channel2: Option<TcInput>,
+    #[pdo_object_index(0x1A02)]
+    channel3: Option<TcInput>,
+    #[pdo_object_index(0x1A03)]
+    channel4: Option<TcInput>,
+    #[pdo_object_index(0x1A04)]
+    channel5: Option<TcInput>,
+    #[pdo_object_index(0x1A05)]
+    channel6: Option<TcInput>,
+    #[pdo_object_index(0x1A06)]
+    channel7: Option<TcInput>,
+    #[pdo_object_index(0x1A07)]
+    channel8: Option<TcInput>,
+}
+
+impl Default for EL3318TxPdo {
+    fn default() -> Self {
+        Self {
+            channel1: Some(TcInput::default()),
+            channel2: Some(TcInput::default()),
+            channel3: Some(TcInput::default()),
+            channel4: Some(TcInput::default()),
+            channel5: Some(TcInput::default()),
+            channel6: Some(TcInput::default()),
+            channel7: Some(TcInput::default()),
+            channel8: Some(TcInput::default()),
+        }
+    }
+}
+
+pub const EL3318_VENDOR_ID: u32 = 0x2;
+pub const EL3318_PRODUCT_ID: u32 = 0x0cf63052;
+pub const EL3318_REVISION_A: u32 = 0x00120000;
+
+pub const EL3318_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL3318_VENDOR_ID, EL3318_PRODUCT_ID, EL3318_REVISION_A);
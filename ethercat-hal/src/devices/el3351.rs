@@ -0,0 +1,189 @@
+use super::{EthercatDeviceProcessing, NewEthercatDevice, SubDeviceIdentityTuple};
+use crate::{
+    coe::{ConfigurableDevice, Configuration},
+    helpers::{
+        ethercrab_types::EthercrabSubDevicePreoperational,
+        signing_converter_u16::U16SigningConverter,
+    },
+    io::load_cell::{LoadCellDevice, LoadCellInput},
+    pdo::{PredefinedPdoAssignment, RxPdo, TxPdo, analog_input::AiStandard},
+    shared_config::el30xx::{EL30XXChannelConfiguration, EL30XXPresentation},
+};
+use ethercat_hal_derive::{EthercatDevice, RxPdo, TxPdo};
+
+/// Full scale of the bridge voltage input (channel 1) in mV
+pub const EL3351_BRIDGE_VOLTAGE_RANGE_MV: f64 = 16.6;
+
+/// Full scale of the supply voltage input (channel 2) in V
+pub const EL3351_SUPPLY_VOLTAGE_RANGE_V: f64 = 12.0;
+
+/// EL3351 1-channel resistor bridge input
+///
+/// Measures the bridge voltage and the supply voltage separately, the bridge ratio in mV/V is
+/// calculated from both.
+#[derive(EthercatDevice)]
+pub struct EL3351 {
+    pub txpdo: EL3351TxPdo,
+    pub rxpdo: EL3351RxPdo,
+    pub configuration: EL3351Configuration,
+    is_used: bool,
+}
+
+impl EthercatDeviceProcessing for EL3351 {}
+
+impl std::fmt::Debug for EL3351 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EL3351")
+    }
+}
+
+impl NewEthercatDevice for EL3351 {
+    fn new() -> Self {
+        let configuration = EL3351Configuration::default();
+        Self {
+            txpdo: configuration.pdo_assignment.txpdo_assignment(),
+            rxpdo: configuration.pdo_assignment.rxpdo_assignment(),
+            configuration,
+            is_used: false,
+        }
+    }
+}
+
+/// Normalize a raw value to -1.0..1.0 according to the channel presentation
+fn normalized(raw: u16, channel: &EL30XXChannelConfiguration) -> f64 {
+    let raw = U16SigningConverter::load_raw(raw);
+    let value: i16 = match channel.presentation {
+        EL30XXPresentation::Unsigned => raw.as_unsigned() as i16,
+        EL30XXPresentation::Signed => raw.as_signed(),
+        EL30XXPresentation::SignedMagnitude => raw.as_signed_magnitude(),
+    };
+    f64::from(value) / f64::from(i16::MAX)
+}
+
+impl LoadCellDevice<EL3351Port> for EL3351 {
+    fn get_input(&self, port: EL3351Port) -> LoadCellInput {
+        match port {
+            EL3351Port::LC1 => {
+                let (Some(bridge), Some(supply)) = (
+                    &self.txpdo.ai_standard_channel1,
+                    &self.txpdo.ai_standard_channel2,
+                ) else {
+                    panic!("Invalid TxPdo assignment");
+                };
+
+                let bridge_voltage_mv = normalized(bridge.value, &self.configuration.channel_1)
+                    * EL3351_BRIDGE_VOLTAGE_RANGE_MV;
+                let supply_voltage_v = normalized(supply.value, &self.configuration.channel_2)
+                    * EL3351_SUPPLY_VOLTAGE_RANGE_V;
+
+                // without supply voltage the ratio is meaningless
+                let supply_missing = supply_voltage_v.abs() < 0.1;
+                LoadCellInput {
+                    bridge_ratio: if supply_missing {
+                        0.0
+                    } else {
+                        bridge_voltage_mv / supply_voltage_v
+                    },
+                    underrange: bridge.undervoltage,
+                    overrange: bridge.overvoltage,
+                    data_invalid: supply_missing || bridge.txpdo_state || supply.txpdo_state,
+                    error: bridge.error || supply.error,
+                    steady_state: None,
+                }
+            }
+        }
+    }
+}
+
+impl ConfigurableDevice<EL3351Configuration> for EL3351 {
+    async fn write_config<'maindevice>(
+        &mut self,
+        device: &EthercrabSubDevicePreoperational<'maindevice>,
+        config: &EL3351Configuration,
+    ) -> Result<(), anyhow::Error> {
+        config.write_config(device).await?;
+        self.configuration = config.clone();
+        self.txpdo = config.pdo_assignment.txpdo_assignment();
+        self.rxpdo = config.pdo_assignment.rxpdo_assignment();
+        Ok(())
+    }
+
+    fn get_config(&self) -> EL3351Configuration {
+        self.configuration.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EL3351Port {
+    LC1,
+}
+
+#[derive(Debug, Clone, TxPdo)]
+pub struct EL3351TxPdo {
+    /// Bridge voltage
+    #[pdo_object_index(0x1A00)]
+    pub ai_standard_channel1: Option<AiStandard>,
+    /// Supply voltage
+    #[pdo_object_index(0x1A02)]
+    pub ai_standard_channel2: Option<AiStandard>,
+}
+
+#[derive(Debug, Clone, RxPdo)]
+pub struct EL3351RxPdo {}
+
+#[derive(Debug, Clone, Default)]
+pub struct EL3351Configuration {
+    pub pdo_assignment: EL3351PredefinedPdoAssignment,
+    /// Bridge voltage
+    pub channel_1: EL30XXChannelConfiguration,
+    /// Supply voltage
+    pub channel_2: EL30XXChannelConfiguration,
+}
+
+impl Configuration for EL3351Configuration {
+    async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        self.channel_1.write_channel_config(device, 0x8000).await?;
+        self.channel_2.write_channel_config(device, 0x8010).await?;
+        self.pdo_assignment
+            .txpdo_assignment()
+            .write_config(device)
+            .await?;
+        self.pdo_assignment
+            .rxpdo_assignment()
+            .write_config(device)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum EL3351PredefinedPdoAssignment {
+    #[default]
+    Standard,
+}
+
+impl PredefinedPdoAssignment<EL3351TxPdo, EL3351RxPdo> for EL3351PredefinedPdoAssignment {
+    fn txpdo_assignment(&self) -> EL3351TxPdo {
+        match self {
+            Self::Standard => EL3351TxPdo {
+                ai_standard_channel1: Some(AiStandard::default()),
+                ai_standard_channel2: Some(AiStandard::default()),
+            },
+        }
+    }
+
+    fn rxpdo_assignment(&self) -> EL3351RxPdo {
+        match self {
+            Self::Standard => EL3351RxPdo {},
+        }
+    }
+}
+
+pub const EL3351_VENDOR_ID: u32 = 0x2;
+pub const EL3351_PRODUCT_ID: u32 = 0x0d173052;
+pub const EL3351_REVISION_A: u32 = 0x00130000;
+pub const EL3351_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL3351_VENDOR_ID, EL3351_PRODUCT_ID, EL3351_REVISION_A);
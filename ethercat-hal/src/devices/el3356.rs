@@ -0,0 +1,277 @@
+use super::{EthercatDeviceProcessing, NewEthercatDevice, SubDeviceIdentityTuple};
+use crate::{
+    coe::{ConfigurableDevice, Configuration},
+    helpers::ethercrab_types::EthercrabSubDevicePreoperational,
+    io::load_cell::{LoadCellDevice, LoadCellInput},
+    pdo::{
+        PredefinedPdoAssignment, RxPdo, TxPdo,
+        el335x::{RmbControl, RmbStatus, RmbValue},
+    },
+};
+use ethercat_hal_derive::{EthercatDevice, RxPdo, TxPdo};
+
+/// EL3356 1-channel precise load cell analysis (resistor bridge)
+///
+/// The terminal scales the bridge ratio itself, the configuration below is used to convert the
+/// value back to mV/V so tare and calibration are handled by [`crate::io::load_cell::LoadCell`].
+#[derive(EthercatDevice)]
+pub struct EL3356 {
+    pub txpdo: EL3356TxPdo,
+    pub rxpdo: EL3356RxPdo,
+    pub configuration: EL3356Configuration,
+    is_used: bool,
+}
+
+impl EthercatDeviceProcessing for EL3356 {}
+
+impl std::fmt::Debug for EL3356 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EL3356")
+    }
+}
+
+impl NewEthercatDevice for EL3356 {
+    fn new() -> Self {
+        let configuration = EL3356Configuration::default();
+        Self {
+            txpdo: configuration.pdo_assignment.txpdo_assignment(),
+            rxpdo: configuration.pdo_assignment.rxpdo_assignment(),
+            configuration,
+            is_used: false,
+        }
+    }
+}
+
+impl LoadCellDevice<EL3356Port> for EL3356 {
+    fn get_input(&self, port: EL3356Port) -> LoadCellInput {
+        match port {
+            EL3356Port::LC1 => {
+                let (Some(rmb_status), Some(rmb_value)) =
+                    (&self.txpdo.rmb_status, &self.txpdo.rmb_value)
+                else {
+                    panic!("Invalid TxPdo assignment");
+                };
+                LoadCellInput {
+                    bridge_ratio: self.configuration.value_to_bridge_ratio(rmb_value.value),
+                    underrange: rmb_status.underrange,
+                    overrange: rmb_status.overrange,
+                    data_invalid: rmb_status.data_invalid
+                        || rmb_status.calibration_in_progress
+                        || rmb_status.txpdo_state,
+                    error: rmb_status.error,
+                    steady_state: Some(rmb_status.steady_state),
+                }
+            }
+        }
+    }
+}
+
+impl ConfigurableDevice<EL3356Configuration> for EL3356 {
+    async fn write_config<'maindevice>(
+        &mut self,
+        device: &EthercrabSubDevicePreoperational<'maindevice>,
+        config: &EL3356Configuration,
+    ) -> Result<(), anyhow::Error> {
+        config.write_config(device).await?;
+        self.configuration = config.clone();
+        self.txpdo = config.pdo_assignment.txpdo_assignment();
+        self.rxpdo = config.pdo_assignment.rxpdo_assignment();
+        Ok(())
+    }
+
+    fn get_config(&self) -> EL3356Configuration {
+        self.configuration.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EL3356Port {
+    LC1,
+}
+
+#[derive(Debug, Clone, TxPdo)]
+pub struct EL3356TxPdo {
+    #[pdo_object_index(0x1A00)]
+    pub rmb_status: Option<RmbStatus>,
+    #[pdo_object_index(0x1A01)]
+    pub rmb_value: Option<RmbValue>,
+}
+
+#[derive(Debug, Clone, RxPdo)]
+pub struct EL3356RxPdo {
+    #[pdo_object_index(0x1600)]
+    pub rmb_control: Option<RmbControl>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EL3356Configuration {
+    pub pdo_assignment: EL3356PredefinedPdoAssignment,
+
+    /// # 0x8000:11
+    /// Filter of the measured value
+    ///
+    /// default: `IIR1`
+    pub filter_settings: EL3356FilterSettings,
+
+    /// # 0x8000:23
+    /// Nominal characteristic value of the sensor in mV/V
+    ///
+    /// Kept at 1.0 so the terminal value is proportional to the bridge ratio
+    pub nominal_characteristic_value: f32,
+
+    /// # 0x8000:24
+    /// Zero balance of the sensor in mV/V
+    pub zero_balance: f32,
+
+    /// # 0x8000:25
+    /// Rated load of the sensor
+    pub rated_load: f32,
+
+    /// # 0x8000:27
+    /// Scale factor of the INT32 value
+    ///
+    /// With the defaults the value is the bridge ratio in µV/V.
+    pub scale_factor: f32,
+}
+
+impl Default for EL3356Configuration {
+    fn default() -> Self {
+        Self {
+            pdo_assignment: EL3356PredefinedPdoAssignment::default(),
+            filter_settings: EL3356FilterSettings::IIR1,
+            nominal_characteristic_value: 1.0,
+            zero_balance: 0.0,
+            rated_load: 1.0,
+            scale_factor: 1000.0,
+        }
+    }
+}
+
+impl EL3356Configuration {
+    /// Convert the INT32 value of the terminal back to the bridge ratio in mV/V
+    pub fn value_to_bridge_ratio(&self, value: i32) -> f64 {
+        (f64::from(value) / f64::from(self.scale_factor) / f64::from(self.rated_load)).mul_add(
+            f64::from(self.nominal_characteristic_value),
+            f64::from(self.zero_balance),
+        )
+    }
+}
+
+impl Configuration for EL3356Configuration {
+    async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        device
+            .sdo_write(0x8000, 0x11, u16::from(self.filter_settings))
+            .await?;
+        device
+            .sdo_write(0x8000, 0x23, self.nominal_characteristic_value)
+            .await?;
+        device.sdo_write(0x8000, 0x24, self.zero_balance).await?;
+        device.sdo_write(0x8000, 0x25, self.rated_load).await?;
+        device.sdo_write(0x8000, 0x27, self.scale_factor).await?;
+        self.pdo_assignment
+            .txpdo_assignment()
+            .write_config(device)
+            .await?;
+        self.pdo_assignment
+            .rxpdo_assignment()
+            .write_config(device)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Filter settings of the EL3356 (0x8000:11)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EL3356FilterSettings {
+    /// FIR 50 Hz notch
+    FIR50Hz,
+    /// FIR 60 Hz notch
+    FIR60Hz,
+    /// IIR low pass, IIR1 is the weakest, IIR8 the strongest filter
+    IIR1,
+    IIR2,
+    IIR3,
+    IIR4,
+    IIR5,
+    IIR6,
+    IIR7,
+    IIR8,
+    /// IIR filter that adapts to the signal dynamics
+    DynamicIIR,
+    /// Averager over the PDO cycle
+    PdoFilterFrequency,
+}
+
+impl From<EL3356FilterSettings> for u16 {
+    fn from(value: EL3356FilterSettings) -> Self {
+        match value {
+            EL3356FilterSettings::FIR50Hz => 0,
+            EL3356FilterSettings::FIR60Hz => 1,
+            EL3356FilterSettings::IIR1 => 2,
+            EL3356FilterSettings::IIR2 => 3,
+            EL3356FilterSettings::IIR3 => 4,
+            EL3356FilterSettings::IIR4 => 5,
+            EL3356FilterSettings::IIR5 => 6,
+            EL3356FilterSettings::IIR6 => 7,
+            EL3356FilterSettings::IIR7 => 8,
+            EL3356FilterSettings::IIR8 => 9,
+            EL3356FilterSettings::DynamicIIR => 10,
+            EL3356FilterSettings::PdoFilterFrequency => 11,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum EL3356PredefinedPdoAssignment {
+    #[default]
+    Standard,
+}
+
+impl PredefinedPdoAssignment<EL3356TxPdo, EL3356RxPdo> for EL3356PredefinedPdoAssignment {
+    fn txpdo_assignment(&self) -> EL3356TxPdo {
+        match self {
+            Self::Standard => EL3356TxPdo {
+                rmb_status: Some(RmbStatus::default()),
+                rmb_value: Some(RmbValue::default()),
+            },
+        }
+    }
+
+    fn rxpdo_assignment(&self) -> EL3356RxPdo {
+        match self {
+            Self::Standard => EL3356RxPdo {
+                rmb_control: Some(RmbControl::default()),
+            },
+        }
+    }
+}
+
+pub const EL3356_VENDOR_ID: u32 = 0x2;
+pub const EL3356_PRODUCT_ID: u32 = 0x0d1c3052;
+pub const EL3356_REVISION_A: u32 = 0x00140000;
+pub const EL3356_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL3356_VENDOR_ID, EL3356_PRODUCT_ID, EL3356_REVISION_A);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec::prelude::*;
+
+    #[test]
+    fn test_tx_pdo() {
+        let mut device = EL3356::new();
+        let mut buffer = [0u8; 6];
+        // steady state, txpdo toggle
+        buffer[0..2].copy_from_slice(&0b1000_0001_0000_0000u16.to_le_bytes());
+        buffer[2..6].copy_from_slice(&(-1500i32).to_le_bytes());
+        device.txpdo.read(buffer.view_bits::<Lsb0>()).unwrap();
+
+        let input = device.get_input(EL3356Port::LC1);
+        assert_eq!(input.steady_state, Some(true));
+        assert!(!input.data_invalid);
+        assert!((input.bridge_ratio - -1.5).abs() < f64::EPSILON);
+    }
+}
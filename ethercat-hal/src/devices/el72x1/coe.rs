@@ -0,0 +1,126 @@
+use crate::{
+    coe::{ConfigurableDevice, Configuration},
+    helpers::{cia402::Cia402Mode, ethercrab_types::EthercrabSubDevicePreoperational},
+    pdo::PredefinedPdoAssignment,
+};
+use anyhow::anyhow;
+
+use super::{EL72x1, pdo::EL72x1PredefinedPdoAssignment};
+
+/// Configuration for EL7211/EL7221 Servo Motor Terminals
+#[derive(Debug, Clone)]
+pub struct EL72x1Configuration {
+    /// # 0x7010:03
+    /// Mode of operation used after switching to OP.
+    ///
+    /// Has to be supported by the PDO assignment, with
+    /// [`EL72x1PredefinedPdoAssignment::CyclicSynchronousPositionVelocity`] it can be switched at runtime.
+    ///
+    /// default: `CyclicSynchronousVelocity`
+    pub mode_of_operation: Cia402Mode,
+
+    /// Amplifier settings
+    pub amplifier: DrvAmplifierSettings,
+
+    pub pdo_assignment: EL72x1PredefinedPdoAssignment,
+}
+
+impl Default for EL72x1Configuration {
+    /// Defaults according to the datasheet
+    fn default() -> Self {
+        Self {
+            mode_of_operation: Cia402Mode::default(),
+            amplifier: DrvAmplifierSettings::default(),
+            pdo_assignment: EL72x1PredefinedPdoAssignment::default(),
+        }
+    }
+}
+
+/// Drive amplifier settings (0x8010)
+///
+/// Fields set to `None` keep the value stored in the terminal, these usually come from the motor
+/// electronic nameplate or the commissioning in TwinCAT.
+#[derive(Debug, Clone, Default)]
+pub struct DrvAmplifierSettings {
+    /// # 0x8010:31
+    /// Velocity limitation (unit: 1 rpm)
+    pub velocity_limitation: Option<u32>,
+
+    /// # 0x8010:50
+    /// Maximum following error before the drive faults (unit: increments)
+    pub following_error_window: Option<u32>,
+
+    /// # 0x8010:51
+    /// Time the following error has to exceed the window before the drive faults (unit: 1 ms)
+    pub following_error_timeout: Option<u16>,
+}
+
+impl DrvAmplifierSettings {
+    pub async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(velocity_limitation) = self.velocity_limitation {
+            device.sdo_write(0x8010, 0x31, velocity_limitation).await?;
+        }
+        if let Some(following_error_window) = self.following_error_window {
+            device
+                .sdo_write(0x8010, 0x50, following_error_window)
+                .await?;
+        }
+        if let Some(following_error_timeout) = self.following_error_timeout {
+            device
+                .sdo_write(0x8010, 0x51, following_error_timeout)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Configuration for EL72x1Configuration {
+    async fn write_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+    ) -> Result<(), anyhow::Error> {
+        if !self.pdo_assignment.supports_mode(self.mode_of_operation) {
+            return Err(anyhow!(
+                "Mode of operation {:?} is not supported by PDO assignment {:?}",
+                self.mode_of_operation,
+                self.pdo_assignment
+            ));
+        }
+
+        device
+            .sdo_write(0x7010, 0x03, u8::from(self.mode_of_operation))
+            .await?;
+        self.amplifier.write_config(device).await?;
+        self.pdo_assignment
+            .txpdo_assignment()
+            .write_config(device)
+            .await?;
+        self.pdo_assignment
+            .rxpdo_assignment()
+            .write_config(device)
+            .await?;
+        Ok(())
+    }
+}
+
+impl ConfigurableDevice<EL72x1Configuration> for EL72x1 {
+    async fn write_config<'maindevice>(
+        &mut self,
+        device: &EthercrabSubDevicePreoperational<'maindevice>,
+        config: &EL72x1Configuration,
+    ) -> Result<(), anyhow::Error> {
+        config.write_config(device).await?;
+        self.configuration = config.clone();
+        self.txpdo = config.pdo_assignment.txpdo_assignment();
+        self.rxpdo = config.pdo_assignment.rxpdo_assignment();
+        self.mode = config.mode_of_operation;
+        Ok(())
+    }
+
+    fn get_config(&self) -> EL72x1Configuration {
+        self.configuration.clone()
+    }
+}
@@ -0,0 +1,270 @@
+use coe::EL72x1Configuration;
+use ethercat_hal_derive::EthercatDevice;
+
+use super::{EthercatDeviceProcessing, NewEthercatDevice, SubDeviceIdentityTuple};
+use crate::{
+    helpers::cia402::{Cia402Mode, Cia402State, Cia402StateMachine},
+    io::servo_drive::{ServoDriveDevice, ServoDriveInput, ServoDriveOutput},
+    pdo::{PredefinedPdoAssignment, RxPdo, TxPdo},
+};
+use anyhow::anyhow;
+
+pub mod coe;
+pub mod pdo;
+
+/// EL7211/EL7221 Servo Motor Terminal
+///
+/// Both terminals share the object dictionary and only differ in the rated current.
+#[derive(EthercatDevice, Debug)]
+pub struct EL72x1 {
+    pub txpdo: pdo::EL72x1TxPdo,
+    pub rxpdo: pdo::EL72x1RxPdo,
+    is_used: bool,
+    pub configuration: EL72x1Configuration,
+
+    /// Drives the controlword towards the requested state
+    pub state_machine: Cia402StateMachine,
+
+    /// Requested mode of operation
+    pub mode: Cia402Mode,
+}
+
+impl NewEthercatDevice for EL72x1 {
+    fn new() -> Self {
+        let configuration = EL72x1Configuration::default();
+        Self {
+            txpdo: configuration.pdo_assignment.txpdo_assignment(),
+            rxpdo: configuration.pdo_assignment.rxpdo_assignment(),
+            is_used: false,
+            mode: configuration.mode_of_operation,
+            configuration,
+            state_machine: Cia402StateMachine::new(),
+        }
+    }
+}
+
+impl EL72x1 {
+    /// Current CiA402 state decoded from the statusword
+    pub fn state(&self) -> Result<Cia402State, anyhow::Error> {
+        self.txpdo
+            .drv_statusword
+            .as_ref()
+            .map(|value| Cia402State::from_statusword(value.statusword))
+            .ok_or_else(|| anyhow!("drv_statusword is None"))
+    }
+}
+
+impl EthercatDeviceProcessing for EL72x1 {
+    fn output_pre_process(&mut self) -> Result<(), anyhow::Error> {
+        let state = self.state()?;
+        let command = self.state_machine.next_command(state);
+
+        match &mut self.rxpdo.drv_controlword {
+            Some(drv_controlword) => drv_controlword.controlword = command.controlword(),
+            None => return Err(anyhow!("drv_controlword is None")),
+        }
+
+        if let Some(drv_modes_of_operation) = &mut self.rxpdo.drv_modes_of_operation {
+            drv_modes_of_operation.modes_of_operation = u8::from(self.mode);
+        }
+
+        // track the actual position while not enabled so the drive doesn't jump when enabling
+        if state != Cia402State::OperationEnabled {
+            if let (Some(drv_target_position), Some(fb_position)) =
+                (&mut self.rxpdo.drv_target_position, &self.txpdo.fb_position)
+            {
+                drv_target_position.target_position = fb_position.position;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ServoDriveDevice<EL72x1Port> for EL72x1 {
+    fn set_output(
+        &mut self,
+        port: EL72x1Port,
+        value: ServoDriveOutput,
+    ) -> Result<(), anyhow::Error> {
+        match port {
+            EL72x1Port::DRV1 => {
+                if !self.configuration.pdo_assignment.supports_mode(value.mode) {
+                    return Err(anyhow!(
+                        "Mode of operation {:?} is not supported by PDO assignment {:?}",
+                        value.mode,
+                        self.configuration.pdo_assignment
+                    ));
+                }
+                self.mode = value.mode;
+
+                self.state_machine.enable = value.enable;
+                if value.fault_reset {
+                    self.state_machine.reset_fault();
+                }
+
+                if let Some(drv_target_position) = &mut self.rxpdo.drv_target_position {
+                    drv_target_position.target_position = value.target_position;
+                }
+                if let Some(drv_target_velocity) = &mut self.rxpdo.drv_target_velocity {
+                    drv_target_velocity.target_velocity = value.target_velocity;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn get_output(&self, port: EL72x1Port) -> Result<ServoDriveOutput, anyhow::Error> {
+        match port {
+            EL72x1Port::DRV1 => Ok(ServoDriveOutput {
+                enable: self.state_machine.enable,
+                fault_reset: self.state_machine.fault_reset_pending(),
+                mode: self.mode,
+                target_position: self
+                    .rxpdo
+                    .drv_target_position
+                    .as_ref()
+                    .map_or(0, |value| value.target_position),
+                target_velocity: self
+                    .rxpdo
+                    .drv_target_velocity
+                    .as_ref()
+                    .map_or(0, |value| value.target_velocity),
+            }),
+        }
+    }
+
+    fn get_input(&self, port: EL72x1Port) -> Result<ServoDriveInput, anyhow::Error> {
+        match port {
+            EL72x1Port::DRV1 => {
+                let drv_statusword = match &self.txpdo.drv_statusword {
+                    Some(value) => value,
+                    None => return Err(anyhow!("drv_statusword is None")),
+                };
+                let fb_position = match &self.txpdo.fb_position {
+                    Some(value) => value,
+                    None => return Err(anyhow!("fb_position is None")),
+                };
+                let drv_velocity_actual_value = match &self.txpdo.drv_velocity_actual_value {
+                    Some(value) => value,
+                    None => return Err(anyhow!("drv_velocity_actual_value is None")),
+                };
+
+                Ok(ServoDriveInput {
+                    state: Cia402State::from_statusword(drv_statusword.statusword),
+                    mode: self
+                        .txpdo
+                        .drv_modes_of_operation_display
+                        .as_ref()
+                        .and_then(|value| {
+                            Cia402Mode::try_from(value.modes_of_operation_display).ok()
+                        }),
+                    position: fb_position.position,
+                    velocity: drv_velocity_actual_value.velocity,
+                    torque: self
+                        .txpdo
+                        .drv_torque_actual_value
+                        .as_ref()
+                        .map(|value| value.torque),
+                    following_error: self
+                        .txpdo
+                        .drv_following_error_actual_value
+                        .as_ref()
+                        .map(|value| value.following_error),
+                    warning: drv_statusword.warning(),
+                    target_reached: drv_statusword.target_reached(),
+                    internal_limit_active: drv_statusword.internal_limit_active(),
+                    drive_follows_command: drv_statusword.drive_follows_command(),
+                    following_error_exceeded: drv_statusword.following_error(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EL72x1Port {
+    DRV1,
+}
+
+pub const EL7211_VENDOR_ID: u32 = 0x2;
+pub const EL7211_PRODUCT_ID: u32 = 0x1c2b3052;
+pub const EL7211_REVISION_A: u32 = 0x00100000;
+pub const EL7211_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL7211_VENDOR_ID, EL7211_PRODUCT_ID, EL7211_REVISION_A);
+pub const EL7221_VENDOR_ID: u32 = 0x2;
+pub const EL7221_PRODUCT_ID: u32 = 0x1c353052;
+pub const EL7221_REVISION_A: u32 = 0x00100000;
+pub const EL7221_IDENTITY_A: SubDeviceIdentityTuple =
+    (EL7221_VENDOR_ID, EL7221_PRODUCT_ID, EL7221_REVISION_A);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_statusword(device: &mut EL72x1, statusword: u16) {
+        device.txpdo.drv_statusword.as_mut().unwrap().statusword = statusword;
+    }
+
+    fn controlword(device: &EL72x1) -> u16 {
+        device.rxpdo.drv_controlword.as_ref().unwrap().controlword
+    }
+
+    #[test]
+    fn test_enable_and_fault_reset() {
+        let mut device = EL72x1::new();
+        let output = ServoDriveOutput {
+            enable: true,
+            fault_reset: false,
+            mode: Cia402Mode::CyclicSynchronousVelocity,
+            target_position: 0,
+            target_velocity: 100,
+        };
+        device.set_output(EL72x1Port::DRV1, output.clone()).unwrap();
+
+        // switch on disabled -> shutdown
+        set_statusword(&mut device, 0x0250);
+        device.output_pre_process().unwrap();
+        assert_eq!(controlword(&device), 0x0006);
+
+        // ready to switch on -> enable operation
+        set_statusword(&mut device, 0x0231);
+        device.output_pre_process().unwrap();
+        assert_eq!(controlword(&device), 0x000F);
+
+        // fault stays until reset is requested
+        set_statusword(&mut device, 0x0218);
+        device.output_pre_process().unwrap();
+        assert_eq!(controlword(&device), 0x0000);
+        let input = device.get_input(EL72x1Port::DRV1).unwrap();
+        assert!(input.state.is_fault());
+
+        device
+            .set_output(
+                EL72x1Port::DRV1,
+                ServoDriveOutput {
+                    fault_reset: true,
+                    ..output
+                },
+            )
+            .unwrap();
+        device.output_pre_process().unwrap();
+        assert_eq!(controlword(&device), 0x0080);
+    }
+
+    #[test]
+    fn test_unsupported_mode() {
+        let mut device = EL72x1::new();
+        let result = device.set_output(
+            EL72x1Port::DRV1,
+            ServoDriveOutput {
+                enable: false,
+                fault_reset: false,
+                mode: Cia402Mode::CyclicSynchronousPosition,
+                target_position: 0,
+                target_velocity: 0,
+            },
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::helpers::cia402::Cia402Mode;
+use crate::helpers::ethercrab_types::EthercrabSubDevicePreoperational;
+use crate::pdo::PredefinedPdoAssignment;
+use crate::pdo::el72x1::{
+    DrvControlword, DrvFollowingErrorActualValue, DrvModesOfOperation, DrvModesOfOperationDisplay,
+    DrvStatusword, DrvTargetPosition, DrvTargetVelocity, DrvTorqueActualValue,
+    DrvVelocityActualValue, FbPosition,
+};
+use ethercat_hal_derive::{RxPdo, TxPdo};
+
+#[derive(Debug, Clone, TxPdo)]
+pub struct EL72x1TxPdo {
+    #[pdo_object_index(0x1A00)]
+    pub fb_position: Option<FbPosition>,
+
+    #[pdo_object_index(0x1A01)]
+    pub drv_statusword: Option<DrvStatusword>,
+
+    #[pdo_object_index(0x1A02)]
+    pub drv_velocity_actual_value: Option<DrvVelocityActualValue>,
+
+    #[pdo_object_index(0x1A03)]
+    pub drv_torque_actual_value: Option<DrvTorqueActualValue>,
+
+    #[pdo_object_index(0x1A06)]
+    pub drv_following_error_actual_value: Option<DrvFollowingErrorActualValue>,
+
+    #[pdo_object_index(0x1A0E)]
+    pub drv_modes_of_operation_display: Option<DrvModesOfOperationDisplay>,
+}
+
+#[derive(Debug, Clone, RxPdo)]
+pub struct EL72x1RxPdo {
+    #[pdo_object_index(0x1600)]
+    pub drv_controlword: Option<DrvControlword>,
+
+    #[pdo_object_index(0x1601)]
+    pub drv_target_velocity: Option<DrvTargetVelocity>,
+
+    #[pdo_object_index(0x1606)]
+    pub drv_target_position: Option<DrvTargetPosition>,
+
+    #[pdo_object_index(0x1608)]
+    pub drv_modes_of_operation: Option<DrvModesOfOperation>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum EL72x1PredefinedPdoAssignment {
+    /// Cyclic synchronous velocity mode (CSV)
+    #[default]
+    CyclicSynchronousVelocity,
+    /// Cyclic synchronous position mode (CSP)
+    CyclicSynchronousPosition,
+    /// CSP and CSV, switchable at runtime with the modes of operation object
+    CyclicSynchronousPositionVelocity,
+}
+
+impl EL72x1PredefinedPdoAssignment {
+    /// If the mode can be used with this assignment
+    pub const fn supports_mode(&self, mode: Cia402Mode) -> bool {
+        matches!(
+            (self, mode),
+            (
+                Self::CyclicSynchronousVelocity,
+                Cia402Mode::CyclicSynchronousVelocity
+            ) | (
+                Self::CyclicSynchronousPosition,
+                Cia402Mode::CyclicSynchronousPosition
+            ) | (
+                Self::CyclicSynchronousPositionVelocity,
+                Cia402Mode::CyclicSynchronousPosition | Cia402Mode::CyclicSynchronousVelocity
+            )
+        )
+    }
+}
+
+impl PredefinedPdoAssignment<EL72x1TxPdo, EL72x1RxPdo> for EL72x1PredefinedPdoAssignment {
+    fn txpdo_assignment(&self) -> EL72x1TxPdo {
+        match self {
+            Self::CyclicSynchronousVelocity => EL72x1TxPdo {
+                fb_position: Some(FbPosition::default()),
+                drv_statusword: Some(DrvStatusword::default()),
+                drv_velocity_actual_value: Some(DrvVelocityActualValue::default()),
+                drv_torque_actual_value: Some(DrvTorqueActualValue::default()),
+                drv_following_error_actual_value: None,
+                drv_modes_of_operation_display: None,
+            },
+            Self::CyclicSynchronousPosition => EL72x1TxPdo {
+                fb_position: Some(FbPosition::default()),
+                drv_statusword: Some(DrvStatusword::default()),
+                drv_velocity_actual_value: Some(DrvVelocityActualValue::default()),
+                drv_torque_actual_value: Some(DrvTorqueActualValue::default()),
+                drv_following_error_actual_value: Some(DrvFollowingErrorActualValue::default()),
+                drv_modes_of_operation_display: None,
+            },
+            Self::CyclicSynchronousPositionVelocity => EL72x1TxPdo {
+                fb_position: Some(FbPosition::default()),
+                drv_statusword: Some(DrvStatusword::default()),
+                drv_velocity_actual_value: Some(DrvVelocityActualValue::default()),
+                drv_torque_actual_value: Some(DrvTorqueActualValue::default()),
+                drv_following_error_actual_value: Some(DrvFollowingErrorActualValue::default()),
+                drv_modes_of_operation_display: Some(DrvModesOfOperationDisplay::default()),
+            },
+        }
+    }
+
+    fn rxpdo_assignment(&self) -> EL72x1RxPdo {
+        match self {
+            Self::CyclicSynchronousVelocity => EL72x1RxPdo {
+                drv_controlword: Some(DrvControlword::default()),
+                drv_target_velocity: Some(DrvTargetVelocity::default()),
+                drv_target_position: None,
+                drv_modes_of_operation: None,
+            },
+            Self::CyclicSynchronousPosition => EL72x1RxPdo {
+                drv_controlword: Some(DrvControlword::default()),
+                drv_target_velocity: None,
+                drv_target_position: Some(DrvTargetPosition::default()),
+                drv_modes_of_operation: None,
+            },
+            Self::CyclicSynchronousPositionVelocity => EL72x1RxPdo {
+                drv_controlword: Some(DrvControlword::default()),
+                drv_target_velocity: Some(DrvTargetVelocity::default()),
+                drv_target_position: Some(DrvTargetPosition::default()),
+                drv_modes_of_operation: Some(DrvModesOfOperation::default()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdo::{RxPdo, TxPdo};
+    use bitvec::prelude::*;
+
+    #[test]
+    fn test_rx_pdo_csp() {
+        let mut buffer = [0u8; 6];
+        let mut rxpdo = EL72x1PredefinedPdoAssignment::CyclicSynchronousPosition.rxpdo_assignment();
+        rxpdo.drv_controlword.as_mut().unwrap().controlword = 0x000F;
+        rxpdo.drv_target_position.as_mut().unwrap().target_position = 0x1234_5678;
+        assert_eq!(rxpdo.size(), 48);
+
+        rxpdo.write(buffer.view_bits_mut::<Lsb0>()).unwrap();
+        assert_eq!(u16::from_le_bytes([buffer[0], buffer[1]]), 0x000F);
+        assert_eq!(
+            u32::from_le_bytes([buffer[2], buffer[3], buffer[4], buffer[5]]),
+            0x1234_5678
+        );
+    }
+
+    #[test]
+    fn test_tx_pdo_csp() {
+        let mut txpdo = EL72x1PredefinedPdoAssignment::CyclicSynchronousPosition.txpdo_assignment();
+        assert_eq!(txpdo.size(), 128);
+
+        let mut buffer = [0u8; 16];
+        buffer[0..4].copy_from_slice(&1000u32.to_le_bytes());
+        buffer[4..6].copy_from_slice(&0x2237u16.to_le_bytes());
+        buffer[6..10].copy_from_slice(&(-500i32).to_le_bytes());
+        buffer[10..12].copy_from_slice(&(-25i16).to_le_bytes());
+        buffer[12..16].copy_from_slice(&(-3i32).to_le_bytes());
+        txpdo.read(buffer.view_bits::<Lsb0>()).unwrap();
+
+        assert_eq!(txpdo.fb_position.unwrap().position, 1000);
+        let statusword = txpdo.drv_statusword.unwrap();
+        assert_eq!(statusword.statusword, 0x2237);
+        assert!(statusword.following_error());
+        assert!(!statusword.warning());
+        assert_eq!(txpdo.drv_velocity_actual_value.unwrap().velocity, -500);
+        assert_eq!(txpdo.drv_torque_actual_value.unwrap().torque, -25);
+        assert_eq!(
+            txpdo
+                .drv_following_error_actual_value
+                .unwrap()
+                .following_error,
+            -3
+        );
+    }
+}
@@ -0,0 +1,917 @@
+use super::{
+    EthercatDevice, EthercatDeviceProcessing, EthercatDeviceUsed, Module, NewEthercatDevice,
+};
+use crate::{
+    coe::{RX_PDO_ASSIGNMENT_REG, TX_PDO_ASSIGNMENT_REG},
+    helpers::ethercrab_types::EthercrabSubDevicePreoperational,
+};
+use anyhow::anyhow;
+use bitvec::{field::BitField, order::Lsb0, slice::BitSlice, vec::BitVec};
+use ethercrab::MainDevice;
+use std::any::Any;
+
+/// SII category types as defined in ETG.2010
+const SII_CATEGORY_STRINGS: u16 = 10;
+const SII_CATEGORY_TXPDO: u16 = 50;
+const SII_CATEGORY_RXPDO: u16 = 51;
+const SII_CATEGORY_END: u16 = 0xFFFF;
+
+/// First word of the SII category area
+const SII_FIRST_CATEGORY_WORD: u16 = 0x40;
+
+/// Upper bound of categories we walk before giving up on a corrupt EEPROM
+const SII_MAX_CATEGORIES: usize = 128;
+
+/// Sync manager value of a SII PDO which is not assigned to any sync manager
+const SII_PDO_NOT_ASSIGNED: u8 = 0xFF;
+
+/// Generic device for subdevices without a dedicated driver
+///
+/// The process image layout is not known at compile time. It is read from the subdevice
+/// with [`GenericDevice::read_pdo_mapping`] in PRE-OP, either from the CoE PDO assignment
+/// (0x1C12/0x1C13) or, for subdevices without CoE, from the SII/EEPROM PDO categories.
+///
+/// The mapped entries can then be read and written by object `index:subindex`.
+pub struct GenericDevice {
+    mapping: GenericPdoMapping,
+    input_image: BitVec<u8, Lsb0>,
+    output_image: BitVec<u8, Lsb0>,
+    is_used: bool,
+}
+
+impl EthercatDeviceProcessing for GenericDevice {}
+
+impl std::fmt::Debug for GenericDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GenericDevice")
+    }
+}
+
+impl NewEthercatDevice for GenericDevice {
+    fn new() -> Self {
+        Self {
+            mapping: GenericPdoMapping::default(),
+            input_image: BitVec::new(),
+            output_image: BitVec::new(),
+            is_used: false,
+        }
+    }
+}
+
+impl EthercatDevice for GenericDevice {
+    fn input(&mut self, input: &BitSlice<u8, Lsb0>) -> Result<(), anyhow::Error> {
+        let len = input.len().min(self.input_image.len());
+        self.input_image[..len].copy_from_bitslice(&input[..len]);
+        Ok(())
+    }
+
+    fn input_len(&self) -> usize {
+        self.mapping.input_bits
+    }
+
+    fn output(&self, output: &mut BitSlice<u8, Lsb0>) -> Result<(), anyhow::Error> {
+        let len = output.len().min(self.output_image.len());
+        output[..len].copy_from_bitslice(&self.output_image[..len]);
+        Ok(())
+    }
+
+    fn output_len(&self) -> usize {
+        self.mapping.output_bits
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn is_module(&self) -> bool {
+        false
+    }
+
+    fn get_module(&self) -> Option<Module> {
+        None
+    }
+
+    fn set_module(&mut self, _module: Module) {}
+}
+
+impl EthercatDeviceUsed for GenericDevice {
+    fn is_used(&self) -> bool {
+        self.is_used
+    }
+
+    fn set_used(&mut self, used: bool) {
+        self.is_used = used;
+    }
+}
+
+impl GenericDevice {
+    /// Reads the PDO mapping of the subdevice and sizes the process image accordingly
+    ///
+    /// Must be called in PRE-OP. The CoE PDO assignment is preferred because it reflects
+    /// what ethercrab actually maps into the PDI. Subdevices without CoE fall back to the
+    /// SII PDO categories. Entry names and data types are always taken from the SII if
+    /// it describes the entry.
+    pub async fn read_pdo_mapping(
+        &mut self,
+        subdevice: &EthercrabSubDevicePreoperational<'_>,
+        maindevice: &MainDevice<'_>,
+    ) -> Result<(), anyhow::Error> {
+        let sii = match read_sii_pdos(subdevice, maindevice).await {
+            Ok(sii) => sii,
+            Err(e) => {
+                tracing::warn!(
+                    "[{}::GenericDevice::read_pdo_mapping] Failed to read SII PDOs of {}: {:?}",
+                    module_path!(),
+                    subdevice.name(),
+                    e
+                );
+                SiiPdos::default()
+            }
+        };
+
+        let mapping = match read_coe_pdos(subdevice).await {
+            Ok((inputs, outputs)) => GenericPdoMapping::from_pdos(
+                GenericPdoMappingSource::Coe,
+                &sii.describe(inputs),
+                &sii.describe(outputs),
+            ),
+            Err(_) => GenericPdoMapping::from_pdos(
+                GenericPdoMappingSource::Sii,
+                &sii.assigned(&sii.txpdos),
+                &sii.assigned(&sii.rxpdos),
+            ),
+        };
+
+        tracing::debug!(
+            "[{}::GenericDevice::read_pdo_mapping] {} mapped from {:?}: {} input bits, {} output bits",
+            module_path!(),
+            subdevice.name(),
+            mapping.source,
+            mapping.input_bits,
+            mapping.output_bits
+        );
+
+        self.set_mapping(mapping);
+        Ok(())
+    }
+
+    /// Replaces the mapping and resets the process image
+    pub fn set_mapping(&mut self, mapping: GenericPdoMapping) {
+        self.input_image = BitVec::repeat(false, mapping.input_bits);
+        self.output_image = BitVec::repeat(false, mapping.output_bits);
+        self.mapping = mapping;
+    }
+
+    pub const fn mapping(&self) -> &GenericPdoMapping {
+        &self.mapping
+    }
+
+    /// Finds a mapped entry by object index and subindex
+    pub fn find_entry(&self, index: u16, subindex: u8) -> Option<&GenericPdoEntry> {
+        self.mapping
+            .inputs
+            .iter()
+            .chain(self.mapping.outputs.iter())
+            .find(|entry| entry.index == index && entry.subindex == subindex)
+    }
+
+    /// Finds a mapped entry by its name
+    pub fn find_entry_by_name(&self, name: &str) -> Option<&GenericPdoEntry> {
+        self.mapping
+            .inputs
+            .iter()
+            .chain(self.mapping.outputs.iter())
+            .find(|entry| entry.name == name)
+    }
+
+    /// Reads the current value of a mapped input or output entry
+    pub fn get_entry(&self, index: u16, subindex: u8) -> Result<GenericPdoValue, anyhow::Error> {
+        let entry = self.find_entry(index, subindex).ok_or_else(|| {
+            anyhow!(
+                "[{}::GenericDevice::get_entry] 0x{:04X}:{:02X} is not mapped",
+                module_path!(),
+                index,
+                subindex
+            )
+        })?;
+        let image = match entry.direction {
+            GenericPdoDirection::Input => &self.input_image,
+            GenericPdoDirection::Output => &self.output_image,
+        };
+        Ok(entry.read(image))
+    }
+
+    /// Writes a mapped output entry, sent to the subdevice in the next cycle
+    ///
+    /// The value must match the data type of the entry, [`GenericPdoValue::Bits`] is
+    /// accepted for every entry and written raw.
+    pub fn set_entry(
+        &mut self,
+        index: u16,
+        subindex: u8,
+        value: GenericPdoValue,
+    ) -> Result<(), anyhow::Error> {
+        let entry = self
+            .mapping
+            .outputs
+            .iter()
+            .find(|entry| entry.index == index && entry.subindex == subindex)
+            .ok_or_else(|| {
+                anyhow!(
+                    "[{}::GenericDevice::set_entry] 0x{:04X}:{:02X} is not a mapped output",
+                    module_path!(),
+                    index,
+                    subindex
+                )
+            })?;
+        if !matches!(value, GenericPdoValue::Bits(_)) && value.data_type() != entry.data_type {
+            return Err(anyhow!(
+                "[{}::GenericDevice::set_entry] 0x{:04X}:{:02X} is {:?}, got {:?}",
+                module_path!(),
+                index,
+                subindex,
+                entry.data_type,
+                value
+            ));
+        }
+        entry.write(&mut self.output_image, value);
+        Ok(())
+    }
+
+    /// Snapshot of all mapped entries with their current values
+    pub fn values(&self) -> Vec<(GenericPdoEntry, GenericPdoValue)> {
+        self.mapping
+            .inputs
+            .iter()
+            .map(|entry| (entry.clone(), entry.read(&self.input_image)))
+            .chain(
+                self.mapping
+                    .outputs
+                    .iter()
+                    .map(|entry| (entry.clone(), entry.read(&self.output_image))),
+            )
+            .collect()
+    }
+}
+
+/// Where a [`GenericPdoMapping`] was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GenericPdoMappingSource {
+    /// Nothing has been read yet
+    #[default]
+    None,
+    /// CoE PDO assignment (0x1C12/0x1C13) and mapping objects (0x16xx/0x1Axx)
+    Coe,
+    /// SII/EEPROM TxPDO and RxPDO categories
+    Sii,
+}
+
+/// Process image layout of a [`GenericDevice`]
+#[derive(Debug, Clone, Default)]
+pub struct GenericPdoMapping {
+    pub source: GenericPdoMappingSource,
+    /// TxPDO entries (subdevice -> maindevice)
+    pub inputs: Vec<GenericPdoEntry>,
+    /// RxPDO entries (maindevice -> subdevice)
+    pub outputs: Vec<GenericPdoEntry>,
+    pub input_bits: usize,
+    pub output_bits: usize,
+}
+
+impl GenericPdoMapping {
+    /// Lays out the entries of the given PDOs back to back like the subdevice does
+    ///
+    /// Padding entries (index 0) advance the bit offset but are not exposed.
+    pub fn from_pdos(
+        source: GenericPdoMappingSource,
+        txpdos: &[PdoDescription],
+        rxpdos: &[PdoDescription],
+    ) -> Self {
+        let (inputs, input_bits) = Self::layout(txpdos, GenericPdoDirection::Input);
+        let (outputs, output_bits) = Self::layout(rxpdos, GenericPdoDirection::Output);
+        Self {
+            source,
+            inputs,
+            outputs,
+            input_bits,
+            output_bits,
+        }
+    }
+
+    fn layout(
+        pdos: &[PdoDescription],
+        direction: GenericPdoDirection,
+    ) -> (Vec<GenericPdoEntry>, usize) {
+        let mut entries = vec![];
+        let mut bit_offset = 0;
+        for pdo in pdos {
+            for entry in &pdo.entries {
+                if entry.index != 0 {
+                    let entry_name = entry
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("0x{:04X}:{:02X}", entry.index, entry.subindex));
+                    let name = match &pdo.name {
+                        Some(pdo_name) => format!("{pdo_name}.{entry_name}"),
+                        None => entry_name,
+                    };
+                    entries.push(GenericPdoEntry {
+                        name,
+                        pdo_index: pdo.index,
+                        index: entry.index,
+                        subindex: entry.subindex,
+                        bit_offset,
+                        bit_len: entry.bit_len,
+                        data_type: entry
+                            .data_type
+                            .unwrap_or_else(|| GenericPdoDataType::from_bit_len(entry.bit_len)),
+                        direction,
+                    });
+                }
+                bit_offset += usize::from(entry.bit_len);
+            }
+        }
+        (entries, bit_offset)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericPdoDirection {
+    Input,
+    Output,
+}
+
+/// A single mapped object in the process image of a [`GenericDevice`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericPdoEntry {
+    /// `"<PDO name>.<entry name>"` from the SII, `0xINDEX:SUB` if the SII has no name
+    pub name: String,
+    /// The PDO (0x16xx/0x1Axx) this entry is mapped in
+    pub pdo_index: u16,
+    pub index: u16,
+    pub subindex: u8,
+    /// Offset in bits from the start of the input or output image
+    pub bit_offset: usize,
+    pub bit_len: u8,
+    pub data_type: GenericPdoDataType,
+    pub direction: GenericPdoDirection,
+}
+
+impl GenericPdoEntry {
+    fn bits<'a>(&self, image: &'a BitSlice<u8, Lsb0>) -> Option<&'a BitSlice<u8, Lsb0>> {
+        image.get(self.bit_offset..self.bit_offset + usize::from(self.bit_len))
+    }
+
+    /// Reads the entry from an input or output image
+    ///
+    /// Entries outside of the image or longer than 64 bits read as zero.
+    pub fn read(&self, image: &BitSlice<u8, Lsb0>) -> GenericPdoValue {
+        let raw = match self.bits(image) {
+            Some(bits) if !bits.is_empty() && bits.len() <= 64 => bits.load_le::<u64>(),
+            _ => 0,
+        };
+        GenericPdoValue::from_raw(self.data_type, raw)
+    }
+
+    /// Writes the entry into an input or output image
+    pub fn write(&self, image: &mut BitSlice<u8, Lsb0>, value: GenericPdoValue) {
+        let range = self.bit_offset..self.bit_offset + usize::from(self.bit_len);
+        if let Some(bits) = image.get_mut(range) {
+            if !bits.is_empty() && bits.len() <= 64 {
+                bits.store_le::<u64>(value.to_raw());
+            }
+        }
+    }
+}
+
+/// Data type of a mapped entry, from the CoE base data types (ETG.1020)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericPdoDataType {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    /// Bit string of the given length without a more specific type
+    Bits(u8),
+}
+
+impl GenericPdoDataType {
+    /// Maps a CoE data type code, falls back to [`Self::from_bit_len`] if the code is
+    /// unknown or doesn't match the mapped length
+    pub const fn from_coe_data_type(code: u8, bit_len: u8) -> Self {
+        let data_type = match code {
+            0x01 => Self::Bool,
+            0x02 => Self::I8,
+            0x03 => Self::I16,
+            0x04 => Self::I32,
+            0x05 => Self::U8,
+            0x06 => Self::U16,
+            0x07 => Self::U32,
+            0x08 => Self::F32,
+            0x11 => Self::F64,
+            0x15 => Self::I64,
+            0x1B => Self::U64,
+            _ => return Self::from_bit_len(bit_len),
+        };
+        if data_type.bit_len() == bit_len {
+            data_type
+        } else {
+            Self::from_bit_len(bit_len)
+        }
+    }
+
+    /// Guesses an unsigned type from the mapped length
+    pub const fn from_bit_len(bit_len: u8) -> Self {
+        match bit_len {
+            1 => Self::Bool,
+            8 => Self::U8,
+            16 => Self::U16,
+            32 => Self::U32,
+            64 => Self::U64,
+            n => Self::Bits(n),
+        }
+    }
+
+    pub const fn bit_len(&self) -> u8 {
+        match self {
+            Self::Bool => 1,
+            Self::I8 | Self::U8 => 8,
+            Self::I16 | Self::U16 => 16,
+            Self::I32 | Self::U32 | Self::F32 => 32,
+            Self::I64 | Self::U64 | Self::F64 => 64,
+            Self::Bits(n) => *n,
+        }
+    }
+}
+
+/// Typed value of a [`GenericPdoEntry`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenericPdoValue {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Bits(u64),
+}
+
+impl GenericPdoValue {
+    pub const fn from_raw(data_type: GenericPdoDataType, raw: u64) -> Self {
+        match data_type {
+            GenericPdoDataType::Bool => Self::Bool(raw & 1 != 0),
+            GenericPdoDataType::I8 => Self::I8(raw as u8 as i8),
+            GenericPdoDataType::I16 => Self::I16(raw as u16 as i16),
+            GenericPdoDataType::I32 => Self::I32(raw as u32 as i32),
+            GenericPdoDataType::I64 => Self::I64(raw as i64),
+            GenericPdoDataType::U8 => Self::U8(raw as u8),
+            GenericPdoDataType::U16 => Self::U16(raw as u16),
+            GenericPdoDataType::U32 => Self::U32(raw as u32),
+            GenericPdoDataType::U64 => Self::U64(raw),
+            GenericPdoDataType::F32 => Self::F32(f32::from_bits(raw as u32)),
+            GenericPdoDataType::F64 => Self::F64(f64::from_bits(raw)),
+            GenericPdoDataType::Bits(_) => Self::Bits(raw),
+        }
+    }
+
+    pub const fn to_raw(&self) -> u64 {
+        match *self {
+            Self::Bool(v) => v as u64,
+            Self::I8(v) => v as u8 as u64,
+            Self::I16(v) => v as u16 as u64,
+            Self::I32(v) => v as u32 as u64,
+            Self::I64(v) => v as u64,
+            Self::U8(v) => v as u64,
+            Self::U16(v) => v as u64,
+            Self::U32(v) => v as u64,
+            Self::U64(v) | Self::Bits(v) => v,
+            Self::F32(v) => v.to_bits() as u64,
+            Self::F64(v) => v.to_bits(),
+        }
+    }
+
+    pub const fn data_type(&self) -> GenericPdoDataType {
+        match self {
+            Self::Bool(_) => GenericPdoDataType::Bool,
+            Self::I8(_) => GenericPdoDataType::I8,
+            Self::I16(_) => GenericPdoDataType::I16,
+            Self::I32(_) => GenericPdoDataType::I32,
+            Self::I64(_) => GenericPdoDataType::I64,
+            Self::U8(_) => GenericPdoDataType::U8,
+            Self::U16(_) => GenericPdoDataType::U16,
+            Self::U32(_) => GenericPdoDataType::U32,
+            Self::U64(_) => GenericPdoDataType::U64,
+            Self::F32(_) => GenericPdoDataType::F32,
+            Self::F64(_) => GenericPdoDataType::F64,
+            Self::Bits(_) => GenericPdoDataType::Bits(64),
+        }
+    }
+}
+
+/// A PDO as described by the SII or the CoE mapping objects
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PdoDescription {
+    pub index: u16,
+    pub name: Option<String>,
+    /// Sync manager from the SII, `None` for PDOs read via CoE
+    pub sync_manager: Option<u8>,
+    pub entries: Vec<PdoEntryDescription>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PdoEntryDescription {
+    pub index: u16,
+    pub subindex: u8,
+    pub bit_len: u8,
+    pub name: Option<String>,
+    pub data_type: Option<GenericPdoDataType>,
+}
+
+impl PdoEntryDescription {
+    /// Decodes a CoE PDO mapping entry (`index << 16 | subindex << 8 | bit length`)
+    pub const fn from_coe_mapping(raw: u32) -> Self {
+        Self {
+            index: (raw >> 16) as u16,
+            subindex: (raw >> 8) as u8,
+            bit_len: raw as u8,
+            name: None,
+            data_type: None,
+        }
+    }
+}
+
+/// PDO descriptions read from the SII
+#[derive(Debug, Clone, Default)]
+pub struct SiiPdos {
+    pub txpdos: Vec<PdoDescription>,
+    pub rxpdos: Vec<PdoDescription>,
+}
+
+impl SiiPdos {
+    /// PDOs which the SII assigns to a sync manager
+    fn assigned(&self, pdos: &[PdoDescription]) -> Vec<PdoDescription> {
+        pdos.iter()
+            .filter(|pdo| pdo.sync_manager != Some(SII_PDO_NOT_ASSIGNED))
+            .cloned()
+            .collect()
+    }
+
+    /// Fills in PDO and entry names and data types of CoE mapped PDOs from the SII
+    fn describe(&self, pdos: Vec<PdoDescription>) -> Vec<PdoDescription> {
+        let sii_pdos = || self.txpdos.iter().chain(self.rxpdos.iter());
+        pdos.into_iter()
+            .map(|mut pdo| {
+                let sii_pdo = sii_pdos().find(|sii_pdo| sii_pdo.index == pdo.index);
+                pdo.name = sii_pdo.and_then(|sii_pdo| sii_pdo.name.clone());
+                for entry in &mut pdo.entries {
+                    let sii_entry = sii_pdo
+                        .into_iter()
+                        .chain(sii_pdos())
+                        .flat_map(|sii_pdo| sii_pdo.entries.iter())
+                        .find(|sii_entry| {
+                            sii_entry.index == entry.index
+                                && sii_entry.subindex == entry.subindex
+                                && sii_entry.bit_len == entry.bit_len
+                        });
+                    if let Some(sii_entry) = sii_entry {
+                        entry.name = sii_entry.name.clone();
+                        entry.data_type = sii_entry.data_type;
+                    }
+                }
+                pdo
+            })
+            .collect()
+    }
+}
+
+/// Parses the SII strings category (count, then length prefixed strings)
+pub fn parse_sii_strings(data: &[u8]) -> Vec<String> {
+    let mut strings = vec![];
+    let Some((&count, mut rest)) = data.split_first() else {
+        return strings;
+    };
+    for _ in 0..count {
+        let Some((&len, tail)) = rest.split_first() else {
+            break;
+        };
+        let Some(bytes) = tail.get(..usize::from(len)) else {
+            break;
+        };
+        strings.push(String::from_utf8_lossy(bytes).into_owned());
+        rest = &tail[usize::from(len)..];
+    }
+    strings
+}
+
+/// Parses a SII TxPDO or RxPDO category (ETG.2010 Table 14/15)
+///
+/// `strings` are the parsed strings category, name indices into it are 1-based.
+pub fn parse_sii_pdos(data: &[u8], strings: &[String]) -> Vec<PdoDescription> {
+    let string = |idx: u8| {
+        usize::from(idx)
+            .checked_sub(1)
+            .and_then(|i| strings.get(i))
+            .filter(|s| !s.is_empty())
+            .cloned()
+    };
+
+    let mut pdos = vec![];
+    let mut rest = data;
+    while let Some(header) = rest.get(..8) {
+        let entry_count = usize::from(header[2]);
+        let mut pdo = PdoDescription {
+            index: u16::from_le_bytes([header[0], header[1]]),
+            name: string(header[5]),
+            sync_manager: Some(header[3]),
+            entries: vec![],
+        };
+        rest = &rest[8..];
+        for _ in 0..entry_count {
+            let Some(entry) = rest.get(..8) else {
+                break;
+            };
+            let bit_len = entry[5];
+            pdo.entries.push(PdoEntryDescription {
+                index: u16::from_le_bytes([entry[0], entry[1]]),
+                subindex: entry[2],
+                bit_len,
+                name: string(entry[3]),
+                data_type: Some(GenericPdoDataType::from_coe_data_type(entry[4], bit_len)),
+            });
+            rest = &rest[8..];
+        }
+        pdos.push(pdo);
+    }
+    pdos
+}
+
+/// Walks the SII categories and parses the strings and PDO categories
+async fn read_sii_pdos(
+    subdevice: &EthercrabSubDevicePreoperational<'_>,
+    maindevice: &MainDevice<'_>,
+) -> Result<SiiPdos, anyhow::Error> {
+    let mut strings = vec![];
+    let mut txpdo_data = vec![];
+    let mut rxpdo_data = vec![];
+
+    let mut word = SII_FIRST_CATEGORY_WORD;
+    for _ in 0..SII_MAX_CATEGORIES {
+        let mut header = [0u8; 4];
+        subdevice
+            .eeprom_read_raw(maindevice, word, &mut header)
+            .await
+            .map_err(|e| anyhow!("Failed to read SII category header at 0x{word:04X}: {e:?}"))?;
+        let category = u16::from_le_bytes([header[0], header[1]]);
+        let len_words = u16::from_le_bytes([header[2], header[3]]);
+        if category == SII_CATEGORY_END {
+            break;
+        }
+
+        let data_word = word + 2;
+        if matches!(
+            category,
+            SII_CATEGORY_STRINGS | SII_CATEGORY_TXPDO | SII_CATEGORY_RXPDO
+        ) {
+            let mut data = vec![0u8; usize::from(len_words) * 2];
+            subdevice
+                .eeprom_read_raw(maindevice, data_word, &mut data)
+                .await
+                .map_err(|e| {
+                    anyhow!("Failed to read SII category {category} at 0x{data_word:04X}: {e:?}")
+                })?;
+            match category {
+                SII_CATEGORY_STRINGS => strings = parse_sii_strings(&data),
+                SII_CATEGORY_TXPDO => txpdo_data.extend(data),
+                _ => rxpdo_data.extend(data),
+            }
+        }
+
+        word = match data_word.checked_add(len_words) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(SiiPdos {
+        txpdos: parse_sii_pdos(&txpdo_data, &strings),
+        rxpdos: parse_sii_pdos(&rxpdo_data, &strings),
+    })
+}
+
+/// Reads the assigned TxPDOs and RxPDOs with their entries from the CoE object dictionary
+async fn read_coe_pdos(
+    subdevice: &EthercrabSubDevicePreoperational<'_>,
+) -> Result<(Vec<PdoDescription>, Vec<PdoDescription>), anyhow::Error> {
+    let inputs = read_coe_pdo_assignment(subdevice, TX_PDO_ASSIGNMENT_REG).await?;
+    let outputs = read_coe_pdo_assignment(subdevice, RX_PDO_ASSIGNMENT_REG).await?;
+    Ok((inputs, outputs))
+}
+
+async fn read_coe_pdo_assignment(
+    subdevice: &EthercrabSubDevicePreoperational<'_>,
+    assignment_index: u16,
+) -> Result<Vec<PdoDescription>, anyhow::Error> {
+    let mut pdos = vec![];
+    let pdo_count = subdevice.sdo_read::<u8>(assignment_index, 0).await?;
+    for i in 1..=pdo_count {
+        let index = subdevice.sdo_read::<u16>(assignment_index, i).await?;
+        let entry_count = subdevice.sdo_read::<u8>(index, 0).await?;
+        let mut entries = vec![];
+        for j in 1..=entry_count {
+            let raw = subdevice.sdo_read::<u32>(index, j).await?;
+            entries.push(PdoEntryDescription::from_coe_mapping(raw));
+        }
+        pdos.push(PdoDescription {
+            index,
+            name: None,
+            sync_manager: None,
+            entries,
+        });
+    }
+    Ok(pdos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec::prelude::*;
+
+    fn sii_strings() -> Vec<u8> {
+        let mut data = vec![3];
+        for s in ["Inputs", "Status", "Value"] {
+            data.push(s.len() as u8);
+            data.extend_from_slice(s.as_bytes());
+        }
+        data
+    }
+
+    fn sii_txpdo() -> Vec<u8> {
+        vec![
+            // PDO 0x1A00, 3 entries, SM3, no dc, name "Inputs", no flags
+            0x00, 0x1A, 3, 3, 0, 1, 0, 0, //
+            // 0x6000:01, "Status", UINT16, 16 bit
+            0x00, 0x60, 0x01, 2, 0x06, 16, 0, 0, //
+            // 8 bit padding
+            0x00, 0x00, 0x00, 0, 0x00, 8, 0, 0, //
+            // 0x6000:11, "Value", INTEGER32, 32 bit
+            0x00, 0x60, 0x11, 3, 0x04, 32, 0, 0, //
+            // PDO 0x1A01, not assigned
+            0x01, 0x1A, 1, 0xFF, 0, 0, 0, 0, //
+            0x10, 0x60, 0x01, 0, 0x01, 1, 0, 0,
+        ]
+    }
+
+    #[test]
+    fn test_parse_sii_strings() {
+        let strings = parse_sii_strings(&sii_strings());
+        assert_eq!(strings, vec!["Inputs", "Status", "Value"]);
+        // truncated category
+        assert_eq!(parse_sii_strings(&[2, 3, b'a']), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_sii_pdos() {
+        let strings = parse_sii_strings(&sii_strings());
+        let pdos = parse_sii_pdos(&sii_txpdo(), &strings);
+        assert_eq!(pdos.len(), 2);
+        assert_eq!(pdos[0].index, 0x1A00);
+        assert_eq!(pdos[0].name.as_deref(), Some("Inputs"));
+        assert_eq!(pdos[0].sync_manager, Some(3));
+        assert_eq!(pdos[0].entries.len(), 3);
+        assert_eq!(pdos[0].entries[2].index, 0x6000);
+        assert_eq!(pdos[0].entries[2].subindex, 0x11);
+        assert_eq!(pdos[0].entries[2].data_type, Some(GenericPdoDataType::I32));
+        assert_eq!(pdos[1].sync_manager, Some(SII_PDO_NOT_ASSIGNED));
+    }
+
+    #[test]
+    fn test_mapping_layout_skips_padding_and_unassigned() {
+        let strings = parse_sii_strings(&sii_strings());
+        let sii = SiiPdos {
+            txpdos: parse_sii_pdos(&sii_txpdo(), &strings),
+            rxpdos: vec![],
+        };
+        let mapping = GenericPdoMapping::from_pdos(
+            GenericPdoMappingSource::Sii,
+            &sii.assigned(&sii.txpdos),
+            &[],
+        );
+        assert_eq!(mapping.input_bits, 56);
+        assert_eq!(mapping.inputs.len(), 2);
+        assert_eq!(mapping.inputs[0].name, "Inputs.Status");
+        assert_eq!(mapping.inputs[1].bit_offset, 24);
+        assert_eq!(mapping.inputs[1].data_type, GenericPdoDataType::I32);
+    }
+
+    #[test]
+    fn test_coe_mapping_described_from_sii() {
+        let strings = parse_sii_strings(&sii_strings());
+        let sii = SiiPdos {
+            txpdos: parse_sii_pdos(&sii_txpdo(), &strings),
+            rxpdos: vec![],
+        };
+        let coe = vec![PdoDescription {
+            index: 0x1A00,
+            name: None,
+            sync_manager: None,
+            entries: vec![
+                PdoEntryDescription::from_coe_mapping(0x6000_1120),
+                PdoEntryDescription::from_coe_mapping(0x7000_0108),
+            ],
+        }];
+        let mapping =
+            GenericPdoMapping::from_pdos(GenericPdoMappingSource::Coe, &sii.describe(coe), &[]);
+        assert_eq!(mapping.input_bits, 40);
+        assert_eq!(mapping.inputs[0].name, "Inputs.Value");
+        assert_eq!(mapping.inputs[0].data_type, GenericPdoDataType::I32);
+        assert_eq!(mapping.inputs[1].name, "Inputs.0x7000:01");
+        assert_eq!(mapping.inputs[1].data_type, GenericPdoDataType::U8);
+    }
+
+    #[test]
+    fn test_read_write_entries() {
+        let rxpdos = vec![PdoDescription {
+            index: 0x1600,
+            name: None,
+            sync_manager: None,
+            entries: vec![
+                PdoEntryDescription::from_coe_mapping(0x7000_0101),
+                PdoEntryDescription::from_coe_mapping(0x0000_0007),
+                PdoEntryDescription::from_coe_mapping(0x7000_1110),
+            ],
+        }];
+        let txpdos = vec![PdoDescription {
+            index: 0x1A00,
+            name: None,
+            sync_manager: None,
+            entries: vec![PdoEntryDescription::from_coe_mapping(0x6000_0110)],
+        }];
+        let mut device = GenericDevice::new();
+        device.set_mapping(GenericPdoMapping::from_pdos(
+            GenericPdoMappingSource::Coe,
+            &txpdos,
+            &rxpdos,
+        ));
+
+        device
+            .set_entry(0x7000, 0x01, GenericPdoValue::Bool(true))
+            .unwrap();
+        device
+            .set_entry(0x7000, 0x11, GenericPdoValue::U16(0xBEEF))
+            .unwrap();
+        assert!(
+            device
+                .set_entry(0x7000, 0x11, GenericPdoValue::I32(1))
+                .is_err()
+        );
+        assert!(
+            device
+                .set_entry(0x6000, 0x01, GenericPdoValue::U16(1))
+                .is_err()
+        );
+
+        let mut output = [0u8; 3];
+        device.output(output.view_bits_mut::<Lsb0>()).unwrap();
+        assert_eq!(output, [0x01, 0xEF, 0xBE]);
+
+        let input = [0x34u8, 0x12];
+        device.input(input.view_bits::<Lsb0>()).unwrap();
+        assert_eq!(
+            device.get_entry(0x6000, 0x01).unwrap(),
+            GenericPdoValue::U16(0x1234)
+        );
+        assert!(device.get_entry(0x6000, 0x02).is_err());
+    }
+
+    #[test]
+    fn test_signed_and_float_values() {
+        for value in [
+            GenericPdoValue::I8(-3),
+            GenericPdoValue::I16(-300),
+            GenericPdoValue::I32(-70000),
+            GenericPdoValue::F32(1.5),
+            GenericPdoValue::F64(-2.25),
+        ] {
+            assert_eq!(
+                GenericPdoValue::from_raw(value.data_type(), value.to_raw()),
+                value
+            );
+        }
+    }
+}
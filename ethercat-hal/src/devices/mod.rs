@@ -14,12 +14,18 @@ pub mod el3021;
 pub mod el3024;
 pub mod el3062_0030;
 pub mod el3204;
+pub mod el3314;
+pub mod el3318;
+pub mod el3351;
+pub mod el3356;
 pub mod el4002;
 pub mod el5152;
 pub mod el6021;
 pub mod el7031;
 pub mod el7031_0030;
 pub mod el7041_0052;
+pub mod el72x1;
+pub mod generic;
 pub mod wago_750_354;
 pub mod wago_modules;
 
@@ -31,6 +37,7 @@ use crate::{
 use anyhow::anyhow;
 use bitvec::{order::Lsb0, slice::BitSlice};
 use ek1100::{EK1100, EK1100_IDENTITY_A};
+use el72x1::{EL7211_IDENTITY_A, EL7221_IDENTITY_A};
 use el1002::{EL1002, EL1002_IDENTITY_A};
 use el1008::EL1008_IDENTITY_A;
 use el2002::{EL2002, EL2002_IDENTITY_A, EL2002_IDENTITY_B};
@@ -44,6 +51,10 @@ use el3024::EL3024_IDENTITY_A;
 use el3062_0030::EL3062_0030_IDENTITY_A;
 use el3204::EL3204_IDENTITY_A;
 use el3204::EL3204_IDENTITY_B;
+use el3314::EL3314_IDENTITY_A;
+use el3318::EL3318_IDENTITY_A;
+use el3351::EL3351_IDENTITY_A;
+use el3356::EL3356_IDENTITY_A;
 use el4002::EL4002_IDENTITY_A;
 use el5152::{EL5152, EL5152_IDENTITY_A};
 use el6021::{EL6021_IDENTITY_A, EL6021_IDENTITY_B, EL6021_IDENTITY_C, EL6021_IDENTITY_D};
@@ -51,6 +62,7 @@ use el7031::{EL7031_IDENTITY_A, EL7031_IDENTITY_B};
 use el7031_0030::EL7031_0030_IDENTITY_A;
 use el7041_0052::EL7041_0052_IDENTITY_A;
 use ethercrab::{MainDevice, SubDeviceIdentity};
+use generic::GenericDevice;
 use smol::lock::RwLock;
 use std::{any::Any, fmt::Debug, sync::Arc};
 use wago_750_354::{WAGO_750_354_IDENTITY_A, Wago750_354};
@@ -227,6 +239,10 @@ pub fn device_from_subdevice_identity_tuple(
         EL3021_IDENTITY_A => Ok(Arc::new(RwLock::new(el3021::EL3021::new()))),
         EL3024_IDENTITY_A => Ok(Arc::new(RwLock::new(el3024::EL3024::new()))),
         EL3062_0030_IDENTITY_A => Ok(Arc::new(RwLock::new(el3062_0030::EL3062_0030::new()))),
+        EL3314_IDENTITY_A => Ok(Arc::new(RwLock::new(el3314::EL3314::new()))),
+        EL3318_IDENTITY_A => Ok(Arc::new(RwLock::new(el3318::EL3318::new()))),
+        EL3351_IDENTITY_A => Ok(Arc::new(RwLock::new(el3351::EL3351::new()))),
+        EL3356_IDENTITY_A => Ok(Arc::new(RwLock::new(el3356::EL3356::new()))),
         EL4002_IDENTITY_A => Ok(Arc::new(RwLock::new(EL4002::new()))),
         EL5152_IDENTITY_A => Ok(Arc::new(RwLock::new(EL5152::new()))),
         EL6021_IDENTITY_A | EL6021_IDENTITY_B | EL6021_IDENTITY_C | EL6021_IDENTITY_D => {
@@ -236,6 +252,7 @@ pub fn device_from_subdevice_identity_tuple(
         EL7031_IDENTITY_A | EL7031_IDENTITY_B => Ok(Arc::new(RwLock::new(el7031::EL7031::new()))),
         EL7031_0030_IDENTITY_A => Ok(Arc::new(RwLock::new(el7031_0030::EL7031_0030::new()))),
         EL7041_0052_IDENTITY_A => Ok(Arc::new(RwLock::new(el7041_0052::EL7041_0052::new()))),
+        EL7211_IDENTITY_A | EL7221_IDENTITY_A => Ok(Arc::new(RwLock::new(el72x1::EL72x1::new()))),
         EL2521_IDENTITY_0000_A | EL2521_IDENTITY_0000_B | EL2521_IDENTITY_0024_A => {
             Ok(Arc::new(RwLock::new(EL2521::new())))
         }
@@ -259,15 +276,23 @@ pub fn device_from_subdevice_identity(
 }
 
 /// Array equivalent of [`device_from_subdevice`]
+///
+/// Subdevices without a dedicated driver get a [`GenericDevice`], its PDO mapping has to be
+/// read with [`GenericDevice::read_pdo_mapping`] before the group leaves PRE-OP.
 pub fn devices_from_subdevices<'maindevice, const MAX_SUBDEVICES: usize, const PDI_LEN: usize>(
     group: &mut EthercrabSubDeviceGroupPreoperational<MAX_SUBDEVICES, PDI_LEN>,
     maindevice: &MainDevice,
 ) -> Result<Vec<Arc<RwLock<dyn EthercatDevice>>>, anyhow::Error> {
-    group
+    Ok(group
         .iter(maindevice)
         .map(|subdevice| subdevice.identity())
-        .map(|subdevice_identity| device_from_subdevice_identity(&subdevice_identity))
-        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .map(|subdevice_identity| {
+            device_from_subdevice_identity(&subdevice_identity).unwrap_or_else(|e| {
+                tracing::info!("{}, falling back to GenericDevice", e);
+                Arc::new(RwLock::new(GenericDevice::new()))
+            })
+        })
+        .collect::<Vec<_>>())
 }
 
 /// Casts a `dyn Device` from an array into a specific device type using [`downcast_device`]
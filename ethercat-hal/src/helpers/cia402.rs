@@ -0,0 +1,330 @@
+/// CiA402 (DS402) drive states decoded from the statusword
+///
+/// See IEC 61800-7-201 "Device control" for the state diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cia402State {
+    #[default]
+    NotReadyToSwitchOn,
+    SwitchOnDisabled,
+    ReadyToSwitchOn,
+    SwitchedOn,
+    OperationEnabled,
+    QuickStopActive,
+    FaultReactionActive,
+    Fault,
+}
+
+impl Cia402State {
+    /// Decode the state from bits 0..=3, 5 and 6 of the statusword
+    pub const fn from_statusword(statusword: u16) -> Self {
+        // ready to switch on, switched on, operation enabled, fault, quick stop, switch on disabled
+        let masked = statusword & 0b0110_1111;
+        if masked & 0b0100_1111 == 0b0000_0000 {
+            Self::NotReadyToSwitchOn
+        } else if masked & 0b0100_1111 == 0b0100_0000 {
+            Self::SwitchOnDisabled
+        } else if masked == 0b0010_0001 {
+            Self::ReadyToSwitchOn
+        } else if masked == 0b0010_0011 {
+            Self::SwitchedOn
+        } else if masked == 0b0010_0111 {
+            Self::OperationEnabled
+        } else if masked == 0b0000_0111 {
+            Self::QuickStopActive
+        } else if masked & 0b0100_1111 == 0b0000_1111 {
+            Self::FaultReactionActive
+        } else if masked & 0b0100_1111 == 0b0000_1000 {
+            Self::Fault
+        } else {
+            // undefined bit combination, treat like the power up state
+            Self::NotReadyToSwitchOn
+        }
+    }
+
+    pub const fn is_fault(&self) -> bool {
+        matches!(self, Self::Fault | Self::FaultReactionActive)
+    }
+}
+
+/// CiA402 controlword commands
+///
+/// Only bits 0..=3 and 7 are set, the mode specific bits 4..=6 and 8 stay cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cia402Command {
+    /// Transition 2, 6, 8
+    Shutdown,
+    /// Transition 3
+    SwitchOn,
+    /// Transition 4 (also 3 + 4 in one step)
+    EnableOperation,
+    /// Transition 5
+    DisableOperation,
+    /// Transition 7, 9, 10, 12
+    DisableVoltage,
+    /// Transition 11
+    QuickStop,
+    /// Transition 15, only acts on the rising edge of bit 7
+    FaultReset,
+}
+
+impl Cia402Command {
+    pub const fn controlword(&self) -> u16 {
+        match self {
+            Self::Shutdown => 0b0000_0110,
+            Self::SwitchOn => 0b0000_0111,
+            Self::EnableOperation => 0b0000_1111,
+            Self::DisableOperation => 0b0000_0111,
+            Self::DisableVoltage => 0b0000_0000,
+            Self::QuickStop => 0b0000_0010,
+            Self::FaultReset => 0b1000_0000,
+        }
+    }
+}
+
+/// CiA402 modes of operation (0x6060 / 0x7010:03 on Beckhoff terminals)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cia402Mode {
+    /// Cyclic synchronous position
+    CyclicSynchronousPosition = 8,
+    /// Cyclic synchronous velocity, the default of the EL72x1
+    #[default]
+    CyclicSynchronousVelocity = 9,
+    /// Cyclic synchronous torque
+    CyclicSynchronousTorque = 10,
+}
+
+impl TryFrom<u8> for Cia402Mode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            8 => Ok(Self::CyclicSynchronousPosition),
+            9 => Ok(Self::CyclicSynchronousVelocity),
+            10 => Ok(Self::CyclicSynchronousTorque),
+            _ => Err(anyhow::anyhow!("Invalid value for Cia402Mode: {}", value)),
+        }
+    }
+}
+
+impl From<Cia402Mode> for u8 {
+    fn from(value: Cia402Mode) -> Self {
+        value as Self
+    }
+}
+
+/// Drives the CiA402 state machine towards `OperationEnabled` or `SwitchOnDisabled`
+///
+/// Call [`Cia402StateMachine::next_command`] once per cycle with the current state, the returned
+/// command is written to the controlword. A fault is only acknowledged if [`Cia402StateMachine::reset_fault`]
+/// was requested, the rising edge on bit 7 is generated by sending [`Cia402Command::DisableVoltage`]
+/// for one cycle before [`Cia402Command::FaultReset`].
+#[derive(Debug, Clone, Default)]
+pub struct Cia402StateMachine {
+    /// Target: operation enabled
+    pub enable: bool,
+
+    /// A fault reset was requested and not yet sent
+    fault_reset_pending: bool,
+
+    /// Last command returned by [`Cia402StateMachine::next_command`]
+    last_command: Option<Cia402Command>,
+}
+
+impl Cia402StateMachine {
+    pub const fn new() -> Self {
+        Self {
+            enable: false,
+            fault_reset_pending: false,
+            last_command: None,
+        }
+    }
+
+    /// Request a fault reset, executed once the drive is in [`Cia402State::Fault`]
+    pub const fn reset_fault(&mut self) {
+        self.fault_reset_pending = true;
+    }
+
+    pub const fn fault_reset_pending(&self) -> bool {
+        self.fault_reset_pending
+    }
+
+    pub const fn last_command(&self) -> Option<Cia402Command> {
+        self.last_command
+    }
+
+    /// Next command to reach the target state from `state`
+    pub const fn next_command(&mut self, state: Cia402State) -> Cia402Command {
+        let command = match state {
+            Cia402State::Fault => {
+                if !self.fault_reset_pending {
+                    Cia402Command::DisableVoltage
+                } else if matches!(self.last_command, Some(Cia402Command::FaultReset)) {
+                    // keep bit 7 high until the drive left the fault state
+                    Cia402Command::FaultReset
+                } else if matches!(self.last_command, Some(Cia402Command::DisableVoltage)) {
+                    Cia402Command::FaultReset
+                } else {
+                    // bit 7 needs a rising edge
+                    Cia402Command::DisableVoltage
+                }
+            }
+            Cia402State::FaultReactionActive | Cia402State::NotReadyToSwitchOn => {
+                Cia402Command::DisableVoltage
+            }
+            Cia402State::SwitchOnDisabled => {
+                self.fault_reset_pending = false;
+                if self.enable {
+                    Cia402Command::Shutdown
+                } else {
+                    Cia402Command::DisableVoltage
+                }
+            }
+            Cia402State::ReadyToSwitchOn => {
+                if self.enable {
+                    Cia402Command::EnableOperation
+                } else {
+                    Cia402Command::DisableVoltage
+                }
+            }
+            Cia402State::SwitchedOn => {
+                if self.enable {
+                    Cia402Command::EnableOperation
+                } else {
+                    Cia402Command::Shutdown
+                }
+            }
+            Cia402State::OperationEnabled => {
+                self.fault_reset_pending = false;
+                if self.enable {
+                    Cia402Command::EnableOperation
+                } else {
+                    Cia402Command::Shutdown
+                }
+            }
+            Cia402State::QuickStopActive => Cia402Command::DisableVoltage,
+        };
+        self.last_command = Some(command);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_from_statusword() {
+        assert_eq!(
+            Cia402State::from_statusword(0x0000),
+            Cia402State::NotReadyToSwitchOn
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x0250),
+            Cia402State::SwitchOnDisabled
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x0231),
+            Cia402State::ReadyToSwitchOn
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x0233),
+            Cia402State::SwitchedOn
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x1237),
+            Cia402State::OperationEnabled
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x0217),
+            Cia402State::QuickStopActive
+        );
+        assert_eq!(
+            Cia402State::from_statusword(0x021F),
+            Cia402State::FaultReactionActive
+        );
+        assert_eq!(Cia402State::from_statusword(0x0218), Cia402State::Fault);
+    }
+
+    #[test]
+    fn test_enable_sequence() {
+        let mut sm = Cia402StateMachine::new();
+        assert_eq!(
+            sm.next_command(Cia402State::SwitchOnDisabled),
+            Cia402Command::DisableVoltage
+        );
+
+        sm.enable = true;
+        assert_eq!(
+            sm.next_command(Cia402State::SwitchOnDisabled),
+            Cia402Command::Shutdown
+        );
+        assert_eq!(
+            sm.next_command(Cia402State::ReadyToSwitchOn),
+            Cia402Command::EnableOperation
+        );
+        assert_eq!(
+            sm.next_command(Cia402State::OperationEnabled),
+            Cia402Command::EnableOperation
+        );
+
+        sm.enable = false;
+        assert_eq!(
+            sm.next_command(Cia402State::OperationEnabled),
+            Cia402Command::Shutdown
+        );
+    }
+
+    #[test]
+    fn test_fault_reset_rising_edge() {
+        let mut sm = Cia402StateMachine::new();
+        sm.enable = true;
+
+        // fault without reset request stays in fault
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::DisableVoltage
+        );
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::DisableVoltage
+        );
+
+        sm.reset_fault();
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::FaultReset
+        );
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::FaultReset
+        );
+
+        // drive acknowledged the reset
+        assert_eq!(
+            sm.next_command(Cia402State::SwitchOnDisabled),
+            Cia402Command::Shutdown
+        );
+        assert!(!sm.fault_reset_pending());
+    }
+
+    #[test]
+    fn test_fault_reset_needs_low_bit_first() {
+        let mut sm = Cia402StateMachine::new();
+        sm.enable = true;
+        assert_eq!(
+            sm.next_command(Cia402State::OperationEnabled),
+            Cia402Command::EnableOperation
+        );
+
+        sm.reset_fault();
+        // the last controlword had bit 7 low, but a full cycle with bit 7 low is sent first
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::DisableVoltage
+        );
+        assert_eq!(
+            sm.next_command(Cia402State::Fault),
+            Cia402Command::FaultReset
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod cia402;
 pub mod counter_wrapper_u16_i128;
 pub mod el70xx_velocity_converter;
 pub mod ethercrab_types;
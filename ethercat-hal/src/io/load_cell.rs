@@ -0,0 +1,447 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use smol::lock::RwLock;
+use units::{
+    f64::{Force, Mass},
+    force::{kilogram_force, newton},
+    mass::kilogram,
+};
+
+/// Load Cell (LC) device
+///
+/// Reads the bridge ratio of a strain gauge in mV/V and converts it to a force. The raw value is
+/// low-pass filtered, a value is considered stable if it stayed within a tolerance band for a
+/// configured time. Call [`LoadCell::update`] once per cycle.
+pub struct LoadCell {
+    /// Read the state of the load cell input
+    get_input: Box<dyn Fn() -> LoadCellInput + Send + Sync>,
+
+    /// Conversion from mV/V to force
+    pub calibration: LoadCellCalibration,
+
+    /// Filter and stability detection
+    pub filter: LoadCellFilter,
+
+    /// Subtracted from the gross force
+    tare: Force,
+
+    /// Filtered bridge ratio in mV/V
+    filtered: Option<f64>,
+
+    /// Filtered values within the stability window
+    history: VecDeque<(Instant, f64)>,
+
+    last_update: Option<Instant>,
+    status: Result<(), LoadCellError>,
+}
+
+impl fmt::Debug for LoadCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LoadCell")
+    }
+}
+
+impl LoadCell {
+    pub fn new<PORTS>(device: Arc<RwLock<dyn LoadCellDevice<PORTS>>>, port: PORTS) -> Self
+    where
+        PORTS: Clone + Send + Sync + 'static,
+    {
+        // build sync get closure
+        let get_input = Box::new(move || {
+            let device = device.read_blocking();
+            device.get_input(port.clone())
+        });
+        Self {
+            get_input,
+            calibration: LoadCellCalibration::default(),
+            filter: LoadCellFilter::default(),
+            tare: Force::new::<newton>(0.0),
+            filtered: None,
+            history: VecDeque::new(),
+            last_update: None,
+            status: Err(LoadCellError::NoValue),
+        }
+    }
+
+    /// Read the device and update the filter and stability detection
+    pub fn update(&mut self, now: Instant) {
+        let input = (self.get_input)();
+        self.status = input.check();
+        if self.status.is_err() {
+            // don't filter invalid values, the stability has to be proven again afterwards
+            self.history.clear();
+            return;
+        }
+
+        let dt = self
+            .last_update
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_update = Some(now);
+
+        let filtered = match self.filtered {
+            Some(filtered) => self.filter.apply(filtered, input.bridge_ratio, dt),
+            None => input.bridge_ratio,
+        };
+        self.filtered = Some(filtered);
+
+        self.history.push_back((now, filtered));
+        while let Some((time, _)) = self.history.front() {
+            if now.saturating_duration_since(*time) > self.filter.stable_window {
+                self.history.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Raw bridge ratio in mV/V as read from the device
+    pub fn get_bridge_ratio_raw(&self) -> Result<f64, LoadCellError> {
+        let input = (self.get_input)();
+        input.check()?;
+        Ok(input.bridge_ratio)
+    }
+
+    /// Filtered bridge ratio in mV/V
+    pub fn get_bridge_ratio(&self) -> Result<f64, LoadCellError> {
+        self.status?;
+        self.filtered.ok_or(LoadCellError::NoValue)
+    }
+
+    /// Force without tare
+    pub fn get_gross_force(&self) -> Result<Force, LoadCellError> {
+        Ok(self.calibration.force(self.get_bridge_ratio()?))
+    }
+
+    /// Force with the tare subtracted
+    pub fn get_force(&self) -> Result<Force, LoadCellError> {
+        Ok(self.get_gross_force()? - self.tare)
+    }
+
+    /// Mass equivalent of [`LoadCell::get_force`] under standard gravity
+    pub fn get_mass(&self) -> Result<Mass, LoadCellError> {
+        Ok(Mass::new::<kilogram>(
+            self.get_force()?.get::<kilogram_force>(),
+        ))
+    }
+
+    /// The filtered value stayed within [`LoadCellFilter::stable_tolerance`] for [`LoadCellFilter::stable_window`]
+    pub fn is_stable(&self) -> bool {
+        if self.status.is_err() {
+            return false;
+        }
+        let (Some((first, _)), Some((last, _))) = (self.history.front(), self.history.back())
+        else {
+            return false;
+        };
+        // the history has to cover the whole window
+        if last.saturating_duration_since(*first) + self.filter.max_sample_gap
+            < self.filter.stable_window
+        {
+            return false;
+        }
+        let (min, max) = self
+            .history
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), (_, value)| {
+                (min.min(*value), max.max(*value))
+            });
+        max - min <= self.filter.stable_tolerance
+    }
+
+    /// Use the current gross force as tare
+    pub fn tare(&mut self) -> Result<(), LoadCellError> {
+        self.tare = self.get_gross_force()?;
+        Ok(())
+    }
+
+    pub const fn set_tare(&mut self, tare: Force) {
+        self.tare = tare;
+    }
+
+    pub const fn get_tare(&self) -> Force {
+        self.tare
+    }
+
+    /// Calibration step 1: the load cell carries no load
+    pub fn calibrate_zero(&mut self) -> Result<(), LoadCellError> {
+        self.calibration.zero = self.get_bridge_ratio()?;
+        Ok(())
+    }
+
+    /// Calibration step 2: the load cell carries the known `mass`
+    ///
+    /// Requires [`LoadCell::calibrate_zero`] to be done first.
+    pub fn calibrate_known_weight(&mut self, mass: Mass) -> Result<(), LoadCellError> {
+        let ratio = self.get_bridge_ratio()?;
+        self.calibration = LoadCellCalibration::from_points(
+            (self.calibration.zero, Force::new::<newton>(0.0)),
+            (ratio, Force::new::<kilogram_force>(mass.get::<kilogram>())),
+        )?;
+        Ok(())
+    }
+
+    /// Get the input as read from the device
+    pub fn get_input(&self) -> LoadCellInput {
+        (self.get_input)()
+    }
+}
+
+/// Linear conversion from bridge ratio to force
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadCellCalibration {
+    /// Bridge ratio without load in mV/V
+    pub zero: f64,
+
+    /// Force per mV/V
+    pub sensitivity: Force,
+}
+
+impl Default for LoadCellCalibration {
+    /// 1 N per mV/V without offset, calibrate before use
+    fn default() -> Self {
+        Self {
+            zero: 0.0,
+            sensitivity: Force::new::<newton>(1.0),
+        }
+    }
+}
+
+impl LoadCellCalibration {
+    /// From the data sheet of the load cell
+    ///
+    /// `rated_load` is the capacity, `characteristic_value` the bridge ratio at that load in mV/V.
+    pub fn from_rated(rated_load: Mass, characteristic_value: f64) -> Self {
+        Self {
+            zero: 0.0,
+            sensitivity: Force::new::<kilogram_force>(
+                rated_load.get::<kilogram>() / characteristic_value,
+            ),
+        }
+    }
+
+    /// From two measured points, usually without load and with a known weight
+    pub fn from_points(a: (f64, Force), b: (f64, Force)) -> Result<Self, LoadCellError> {
+        let delta_ratio = b.0 - a.0;
+        if delta_ratio.abs() < f64::EPSILON {
+            return Err(LoadCellError::InvalidCalibration);
+        }
+        let sensitivity = (b.1 - a.1) / delta_ratio;
+        Ok(Self {
+            zero: a.0 - a.1.get::<newton>() / sensitivity.get::<newton>(),
+            sensitivity,
+        })
+    }
+
+    pub fn force(&self, bridge_ratio: f64) -> Force {
+        self.sensitivity * (bridge_ratio - self.zero)
+    }
+}
+
+/// Low pass filter and stability detection settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadCellFilter {
+    /// Time constant of the first order low pass, zero disables filtering
+    pub time_constant: Duration,
+
+    /// Time the filtered value has to stay within [`LoadCellFilter::stable_tolerance`]
+    pub stable_window: Duration,
+
+    /// Allowed peak to peak deviation in mV/V
+    pub stable_tolerance: f64,
+
+    /// Cycle time tolerance when checking if the history covers the stable window
+    pub max_sample_gap: Duration,
+}
+
+impl Default for LoadCellFilter {
+    fn default() -> Self {
+        Self {
+            time_constant: Duration::from_millis(50),
+            stable_window: Duration::from_millis(500),
+            stable_tolerance: 0.001,
+            max_sample_gap: Duration::from_millis(10),
+        }
+    }
+}
+
+impl LoadCellFilter {
+    fn apply(&self, filtered: f64, value: f64, dt: Duration) -> f64 {
+        if self.time_constant.is_zero() {
+            return value;
+        }
+        let alpha = 1.0 - (-dt.as_secs_f64() / self.time_constant.as_secs_f64()).exp();
+        (value - filtered).mul_add(alpha, filtered)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadCellError {
+    /// Bridge ratio below the measuring range
+    Underrange,
+
+    /// Bridge ratio above the measuring range
+    Overrange,
+
+    /// The device marked the value invalid (self calibration, broken wire)
+    DataInvalid,
+
+    /// Error flag of the device
+    Error,
+
+    /// No valid value was read yet
+    NoValue,
+
+    /// Both calibration points have the same bridge ratio
+    InvalidCalibration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadCellInput {
+    /// Bridge ratio in mV/V
+    pub bridge_ratio: f64,
+
+    pub underrange: bool,
+
+    pub overrange: bool,
+
+    pub data_invalid: bool,
+
+    pub error: bool,
+
+    /// Steady state detection of the device, `None` if the device has none
+    pub steady_state: Option<bool>,
+}
+
+impl LoadCellInput {
+    const fn check(&self) -> Result<(), LoadCellError> {
+        if self.error {
+            Err(LoadCellError::Error)
+        } else if self.data_invalid {
+            Err(LoadCellError::DataInvalid)
+        } else if self.overrange {
+            Err(LoadCellError::Overrange)
+        } else if self.underrange {
+            Err(LoadCellError::Underrange)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub trait LoadCellDevice<PORTS>: Send + Sync {
+    fn get_input(&self, port: PORTS) -> LoadCellInput;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    struct DummyLoadCell {
+        input: LoadCellInput,
+    }
+
+    impl LoadCellDevice<()> for DummyLoadCell {
+        fn get_input(&self, _port: ()) -> LoadCellInput {
+            self.input.clone()
+        }
+    }
+
+    fn setup() -> (Arc<RwLock<DummyLoadCell>>, LoadCell) {
+        let device = Arc::new(RwLock::new(DummyLoadCell {
+            input: LoadCellInput::default(),
+        }));
+        let load_cell = LoadCell::new(device.clone(), ());
+        (device, load_cell)
+    }
+
+    #[test]
+    fn test_calibration_from_points() {
+        let calibration = LoadCellCalibration::from_points(
+            (0.1, Force::new::<newton>(0.0)),
+            (1.1, Force::new::<newton>(100.0)),
+        )
+        .unwrap();
+        assert_relative_eq!(calibration.zero, 0.1);
+        assert_relative_eq!(calibration.sensitivity.get::<newton>(), 100.0);
+        assert_relative_eq!(calibration.force(0.6).get::<newton>(), 50.0);
+
+        assert_eq!(
+            LoadCellCalibration::from_points(
+                (0.5, Force::new::<newton>(0.0)),
+                (0.5, Force::new::<newton>(10.0)),
+            ),
+            Err(LoadCellError::InvalidCalibration)
+        );
+
+        let rated = LoadCellCalibration::from_rated(Mass::new::<kilogram>(50.0), 2.0);
+        assert_relative_eq!(rated.force(2.0).get::<kilogram_force>(), 50.0);
+    }
+
+    #[test]
+    fn test_calibrate_and_tare() {
+        let (device, mut load_cell) = setup();
+        load_cell.filter.time_constant = Duration::ZERO;
+        let now = Instant::now();
+
+        assert_eq!(load_cell.get_force(), Err(LoadCellError::NoValue));
+
+        device.write_blocking().input.bridge_ratio = 0.05;
+        load_cell.update(now);
+        load_cell.calibrate_zero().unwrap();
+
+        device.write_blocking().input.bridge_ratio = 1.05;
+        load_cell.update(now);
+        load_cell
+            .calibrate_known_weight(Mass::new::<kilogram>(10.0))
+            .unwrap();
+        assert_relative_eq!(load_cell.get_mass().unwrap().get::<kilogram>(), 10.0);
+
+        load_cell.tare().unwrap();
+        assert_relative_eq!(load_cell.get_force().unwrap().get::<newton>(), 0.0);
+        assert_relative_eq!(
+            load_cell.get_gross_force().unwrap().get::<kilogram_force>(),
+            10.0
+        );
+
+        device.write_blocking().input.overrange = true;
+        load_cell.update(now);
+        assert_eq!(load_cell.get_force(), Err(LoadCellError::Overrange));
+    }
+
+    #[test]
+    fn test_filter_and_stability() {
+        let (device, mut load_cell) = setup();
+        load_cell.filter = LoadCellFilter {
+            time_constant: Duration::from_millis(100),
+            stable_window: Duration::from_millis(100),
+            stable_tolerance: 0.01,
+            max_sample_gap: Duration::from_millis(1),
+        };
+        let start = Instant::now();
+
+        load_cell.update(start);
+        device.write_blocking().input.bridge_ratio = 1.0;
+
+        // after one time constant the filter reached ~63%
+        for ms in 1..=100 {
+            load_cell.update(start + Duration::from_millis(ms));
+        }
+        assert_relative_eq!(
+            load_cell.get_bridge_ratio().unwrap(),
+            1.0 - (-1.0f64).exp(),
+            epsilon = 1e-9
+        );
+        assert!(!load_cell.is_stable());
+
+        for ms in 101..=1500 {
+            load_cell.update(start + Duration::from_millis(ms));
+        }
+        assert!(load_cell.is_stable());
+    }
+}
@@ -4,7 +4,9 @@ pub mod analog_output;
 pub mod digital_input;
 pub mod digital_output;
 pub mod encoder_input;
+pub mod load_cell;
 pub mod pulse_train_output;
 pub mod serial_interface;
+pub mod servo_drive;
 pub mod stepper_velocity_el70x1;
 pub mod temperature_input;
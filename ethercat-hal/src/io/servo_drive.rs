@@ -0,0 +1,196 @@
+use std::{fmt, sync::Arc};
+
+use crate::helpers::cia402::{Cia402Mode, Cia402State};
+use anyhow::Error;
+use smol::lock::RwLock;
+
+/// Servo Drive (SD) device
+///
+/// CiA402 drive in cyclic synchronous position or velocity mode. The device runs the CiA402
+/// state machine every cycle, the machine only sets the target state with [`ServoDrive::set_enabled`]
+/// and acknowledges faults with [`ServoDrive::reset_fault`].
+pub struct ServoDrive {
+    /// Write to the servo drive
+    set_output: Box<dyn Fn(ServoDriveOutput) -> Result<(), Error> + Send + Sync>,
+    /// Read the commanded values of the servo drive
+    get_output: Box<dyn Fn() -> Result<ServoDriveOutput, Error> + Send + Sync>,
+    /// Read the feedback of the servo drive
+    get_input: Box<dyn Fn() -> Result<ServoDriveInput, Error> + Send + Sync>,
+}
+
+impl fmt::Debug for ServoDrive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ServoDrive")
+    }
+}
+
+impl ServoDrive {
+    pub fn new<PORT, DEVICE>(device: Arc<RwLock<DEVICE>>, port: PORT) -> Self
+    where
+        PORT: Clone + Copy + Send + Sync + 'static,
+        DEVICE: ServoDriveDevice<PORT> + Send + Sync + 'static,
+    {
+        // build sync write closure
+        let device1 = device.clone();
+        let set_output = Box::new(move |value: ServoDriveOutput| -> Result<(), Error> {
+            let mut device = smol::block_on(device1.write());
+            device.set_output(port, value)
+        });
+
+        // build sync get closure
+        let device2 = device.clone();
+        let get_output = Box::new(move || -> Result<ServoDriveOutput, Error> {
+            let device = smol::block_on(device2.read());
+            device.get_output(port)
+        });
+
+        // build sync get closure
+        let device3 = device;
+        let get_input = Box::new(move || -> Result<ServoDriveInput, Error> {
+            let device = smol::block_on(device3.read());
+            device.get_input(port)
+        });
+
+        Self {
+            set_output,
+            get_output,
+            get_input,
+        }
+    }
+
+    /// Target state of the drive, `true` for operation enabled
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), Error> {
+        let mut output = (self.get_output)()?;
+        output.enable = enabled;
+        (self.set_output)(output)
+    }
+
+    /// Get the target state of the drive
+    pub fn is_enabled(&self) -> Result<bool, Error> {
+        Ok((self.get_output)()?.enable)
+    }
+
+    /// If the drive reached operation enabled and follows the targets
+    pub fn is_operation_enabled(&self) -> Result<bool, Error> {
+        Ok((self.get_input)()?.state == Cia402State::OperationEnabled)
+    }
+
+    /// Acknowledge a drive fault
+    ///
+    /// The reset is sent once the drive is in the fault state, afterwards the drive is enabled again
+    /// if [`ServoDrive::is_enabled`].
+    pub fn reset_fault(&mut self) -> Result<(), Error> {
+        let mut output = (self.get_output)()?;
+        output.fault_reset = true;
+        (self.set_output)(output)
+    }
+
+    /// Switch between cyclic synchronous position and velocity mode
+    pub fn set_mode(&mut self, mode: Cia402Mode) -> Result<(), Error> {
+        let mut output = (self.get_output)()?;
+        output.mode = mode;
+        (self.set_output)(output)
+    }
+
+    /// Target position in increments (cyclic synchronous position mode)
+    pub fn set_target_position(&mut self, position: u32) -> Result<(), Error> {
+        let mut output = (self.get_output)()?;
+        output.target_position = position;
+        (self.set_output)(output)
+    }
+
+    /// Target velocity (cyclic synchronous velocity mode)
+    pub fn set_target_velocity(&mut self, velocity: i32) -> Result<(), Error> {
+        let mut output = (self.get_output)()?;
+        output.target_velocity = velocity;
+        (self.set_output)(output)
+    }
+
+    /// Actual position in increments
+    pub fn get_position(&self) -> Result<u32, Error> {
+        Ok((self.get_input)()?.position)
+    }
+
+    /// Actual velocity
+    pub fn get_velocity(&self) -> Result<i32, Error> {
+        Ok((self.get_input)()?.velocity)
+    }
+
+    /// Following error in increments, `None` if not mapped
+    pub fn get_following_error(&self) -> Result<Option<i32>, Error> {
+        Ok((self.get_input)()?.following_error)
+    }
+
+    /// Get the full input state (feedback from device)
+    pub fn get_input(&self) -> Result<ServoDriveInput, Error> {
+        (self.get_input)()
+    }
+
+    /// Get the full output state (what we're sending)
+    pub fn get_output(&self) -> Result<ServoDriveOutput, Error> {
+        (self.get_output)()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServoDriveInput {
+    /// Decoded from the CiA402 statusword
+    pub state: Cia402State,
+
+    /// Active mode of operation, `None` if the mode display is not mapped
+    pub mode: Option<Cia402Mode>,
+
+    /// Actual position in increments
+    pub position: u32,
+
+    /// Actual velocity
+    pub velocity: i32,
+
+    /// Actual torque in 1000th of the rated current, `None` if not mapped
+    pub torque: Option<i16>,
+
+    /// Actual following error in increments, `None` if not mapped
+    pub following_error: Option<i32>,
+
+    /// Statusword bit 7
+    pub warning: bool,
+
+    /// Statusword bit 10
+    pub target_reached: bool,
+
+    /// Statusword bit 11
+    pub internal_limit_active: bool,
+
+    /// Statusword bit 12
+    pub drive_follows_command: bool,
+
+    /// Statusword bit 13, following error window exceeded in cyclic synchronous position mode
+    pub following_error_exceeded: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServoDriveOutput {
+    /// Target state, operation enabled or switch on disabled
+    pub enable: bool,
+
+    /// A fault reset is requested
+    pub fault_reset: bool,
+
+    /// Requested mode of operation
+    pub mode: Cia402Mode,
+
+    /// Target position for cyclic synchronous position mode
+    pub target_position: u32,
+
+    /// Target velocity for cyclic synchronous velocity mode
+    pub target_velocity: i32,
+}
+
+pub trait ServoDriveDevice<PORT>: Send + Sync
+where
+    PORT: Clone,
+{
+    fn set_output(&mut self, port: PORT, value: ServoDriveOutput) -> Result<(), Error>;
+    fn get_output(&self, port: PORT) -> Result<ServoDriveOutput, Error>;
+    fn get_input(&self, port: PORT) -> Result<ServoDriveInput, Error>;
+}
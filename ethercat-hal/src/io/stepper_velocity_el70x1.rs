@@ -121,6 +121,30 @@ impl<'device> StepperVelocityEL70x1 {
         output.enable
     }
 
+    /// Acknowledge a driver error, see `reset` of [`crate::pdo::el70x1::StmControl`]
+    ///
+    /// The terminal resets on the rising edge, set it back to `false` in the next cycle.
+    pub fn set_reset(&mut self, reset: bool) {
+        let mut output = (self.get_output)().unwrap();
+
+        output.reset = reset;
+
+        // Write to device
+        (self.set_output)(output).unwrap();
+    }
+
+    /// The driver stage reports a warning (e.g. overtemperature)
+    pub fn is_warning(&self) -> bool {
+        let input = (self.get_input)().unwrap();
+        input.warning
+    }
+
+    /// The driver stage reports an error (see index 0xA010)
+    pub fn is_error(&self) -> bool {
+        let input = (self.get_input)().unwrap();
+        input.error
+    }
+
     /// Get the current position of the stepper
     pub fn get_position(&self) -> i128 {
         let input = (self.get_input)().unwrap();
@@ -38,7 +38,9 @@ impl TemperatureInput {
     /// Get the current temperature in degrees Celsius
     pub fn get_temperature(&self) -> Result<f64, TemperatureInputError> {
         let input = (self.get_input)();
-        if input.overvoltage {
+        if input.open_circuit {
+            Err(TemperatureInputError::OpenCircuit)
+        } else if input.overvoltage {
             Err(TemperatureInputError::OverVoltage)
         } else if input.undervoltage {
             Err(TemperatureInputError::UnderVoltage)
@@ -54,6 +56,9 @@ pub enum TemperatureInputError {
 
     /// Under-voltage error
     UnderVoltage,
+
+    /// Sensor wire broken or not connected
+    OpenCircuit,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +87,9 @@ pub struct TemperatureInputInput {
     /// Error flag
     pub error: bool,
 
+    /// Open circuit (wire break) detected
+    pub open_circuit: bool,
+
     /// if the TxPdo state is valid
     pub txpdo_state: bool,
 
@@ -4,4 +4,5 @@ pub mod devices;
 pub mod helpers;
 pub mod io;
 pub mod pdo;
+pub mod sdo;
 pub mod shared_config;
@@ -0,0 +1,69 @@
+use bitvec::prelude::*;
+use ethercat_hal_derive::PdoObject;
+
+use super::{TxPdoObject, basic::Limit};
+
+/// # `TcInput`
+/// 32 bits / 4 bytes
+///
+/// "TC Inputs" of the EL331x thermocouple terminals.
+#[derive(Debug, Clone, Default, PdoObject, PartialEq)]
+#[pdo_object(bits = 32)]
+pub struct TcInput {
+    /// # 60n0:01
+    /// The measured value is below the measuring range.
+    pub underrange: bool,
+
+    /// # 60n0:02
+    /// The measured value is above the measuring range, also set on an open circuit.
+    pub overrange: bool,
+
+    /// # 60n0:03
+    /// Configurable limit 1
+    pub limit1: Limit,
+
+    /// # 60n0:05
+    /// Configurable limit 2
+    pub limit2: Limit,
+
+    /// # 60n0:07
+    /// Error flag, set together with overrange on an open circuit.
+    pub error: bool,
+
+    /// # 60n0:0F
+    /// The data of the associated TxPDO is invalid.
+    pub txpdo_state: bool,
+
+    /// # 60n0:10
+    /// The TxPDO toggle is toggled by the slave when the data of the associated TxPDO is updated.
+    pub txpdo_toggle: bool,
+
+    /// # 60n0:11
+    /// Temperature in degrees celsius, accurate to 0.1 degrees celsius.
+    pub temperature: f32,
+}
+
+impl TxPdoObject for TcInput {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 1.7
+        self.txpdo_toggle = bits[8 + 7];
+        if !self.txpdo_toggle {
+            return;
+        }
+
+        // Offset 0.0
+        self.underrange = bits[0];
+        // Offset 0.1
+        self.overrange = bits[1];
+        // Offset 0.2
+        self.limit1 = bits[2..4].load_le::<u8>().into();
+        // Offset 0.4
+        self.limit2 = bits[4..6].load_le::<u8>().into();
+        // Offset 0.6
+        self.error = bits[6];
+        // Offset 1.6
+        self.txpdo_state = bits[8 + 6];
+        // Offset 2.0
+        self.temperature = f32::from(bits[16..16 + 16].load_le::<i16>()) / 10.0;
+    }
+}
@@ -0,0 +1,126 @@
+use super::{RxPdoObject, TxPdoObject};
+use bitvec::prelude::*;
+use ethercat_hal_derive::PdoObject;
+
+/// # `RmbStatus`
+/// 16 bits / 2 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 16)]
+pub struct RmbStatus {
+    /// # 6000:01
+    /// The measured value is below the measuring range.
+    pub underrange: bool,
+
+    /// # 6000:02
+    /// The measured value is above the measuring range.
+    pub overrange: bool,
+
+    /// # 6000:04
+    /// The value is invalid, e.g. during the self calibration or a broken wire.
+    pub data_invalid: bool,
+
+    /// # 6000:07
+    /// An error has occurred (see the diagnosis history 0x10F3).
+    pub error: bool,
+
+    /// # 6000:08
+    /// The terminal is running its self calibration.
+    pub calibration_in_progress: bool,
+
+    /// # 6000:09
+    /// The value stayed within the steady state window (0x8000:2A/0x8000:2B).
+    pub steady_state: bool,
+
+    /// # 6000:0E
+    /// The Sync error bit is only required for DC mode. It indicates whether a synchronization error has occurred during the previous cycle.
+    pub sync_error: bool,
+
+    /// # 6000:0F
+    /// The data of the associated TxPDO is invalid.
+    pub txpdo_state: bool,
+
+    /// # 6000:10
+    /// The TxPDO toggle is toggled by the slave when the data of the associated TxPDO is updated.
+    pub txpdo_toggle: bool,
+}
+
+impl TxPdoObject for RmbStatus {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.underrange = bits[0];
+        // Offset 0.1
+        self.overrange = bits[1];
+        // Offset 0.3
+        self.data_invalid = bits[3];
+        // Offset 0.6
+        self.error = bits[6];
+        // Offset 0.7
+        self.calibration_in_progress = bits[7];
+        // Offset 1.0
+        self.steady_state = bits[8];
+        // Offset 1.5
+        self.sync_error = bits[8 + 5];
+        // Offset 1.6
+        self.txpdo_state = bits[8 + 6];
+        // Offset 1.7
+        self.txpdo_toggle = bits[8 + 7];
+    }
+}
+
+/// # `RmbValue`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct RmbValue {
+    /// # 6000:11
+    /// Scaled load value, see the `EL3356` configuration for the scaling.
+    pub value: i32,
+}
+
+impl TxPdoObject for RmbValue {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.value = bits[0..32].load_le();
+    }
+}
+
+/// # `RmbControl`
+/// 16 bits / 2 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 16)]
+pub struct RmbControl {
+    /// # 7000:01
+    /// Start a manual self calibration.
+    pub start_calibration: bool,
+
+    /// # 7000:02
+    /// Suppress the cyclic self calibration.
+    pub disable_calibration: bool,
+
+    /// # 7000:03
+    /// Keep the last value (e.g. during a known disturbance).
+    pub input_freeze: bool,
+
+    /// # 7000:04
+    /// Switch to the fast sample mode 1.
+    pub sample_mode: bool,
+
+    /// # 7000:05
+    /// Tare the terminal with the current value.
+    pub tara: bool,
+}
+
+impl RxPdoObject for RmbControl {
+    fn write(&self, buffer: &mut BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        buffer.set(0, self.start_calibration);
+        // Offset 0.1
+        buffer.set(1, self.disable_calibration);
+        // Offset 0.2
+        buffer.set(2, self.input_freeze);
+        // Offset 0.3
+        buffer.set(3, self.sample_mode);
+        // Offset 0.4
+        buffer.set(4, self.tara);
+    }
+}
@@ -0,0 +1,200 @@
+use super::{RxPdoObject, TxPdoObject};
+use bitvec::prelude::*;
+use ethercat_hal_derive::PdoObject;
+
+/// # `FbPosition`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct FbPosition {
+    /// # 6000:11
+    /// Actual position of the feedback (resolver/encoder) in increments.
+    pub position: u32,
+}
+
+impl TxPdoObject for FbPosition {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.position = bits[0..32].load_le();
+    }
+}
+
+/// # `DrvStatusword`
+/// 16 bits / 2 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 16)]
+pub struct DrvStatusword {
+    /// # 6010:01
+    /// CiA402 statusword, decode with [`crate::helpers::cia402::Cia402State::from_statusword`].
+    pub statusword: u16,
+}
+
+impl DrvStatusword {
+    /// Bit 7: A warning is active (see index 0x10F3 for the diagnosis messages)
+    pub const fn warning(&self) -> bool {
+        self.statusword & (1 << 7) != 0
+    }
+
+    /// Bit 10: Target reached
+    pub const fn target_reached(&self) -> bool {
+        self.statusword & (1 << 10) != 0
+    }
+
+    /// Bit 11: Internal limit (torque, velocity or position) is active
+    pub const fn internal_limit_active(&self) -> bool {
+        self.statusword & (1 << 11) != 0
+    }
+
+    /// Bit 12: In cyclic synchronous modes the drive follows the command value
+    pub const fn drive_follows_command(&self) -> bool {
+        self.statusword & (1 << 12) != 0
+    }
+
+    /// Bit 13: Following error in cyclic synchronous position mode
+    pub const fn following_error(&self) -> bool {
+        self.statusword & (1 << 13) != 0
+    }
+}
+
+impl TxPdoObject for DrvStatusword {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.statusword = bits[0..16].load_le();
+    }
+}
+
+/// # `DrvVelocityActualValue`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct DrvVelocityActualValue {
+    /// # 6010:07
+    /// Actual velocity (unit: see velocity encoder resolution 0x9010:14).
+    pub velocity: i32,
+}
+
+impl TxPdoObject for DrvVelocityActualValue {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.velocity = bits[0..32].load_le();
+    }
+}
+
+/// # `DrvTorqueActualValue`
+/// 16 bits / 2 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 16)]
+pub struct DrvTorqueActualValue {
+    /// # 6010:08
+    /// Actual torque in 1000th of the rated current.
+    pub torque: i16,
+}
+
+impl TxPdoObject for DrvTorqueActualValue {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.torque = bits[0..16].load_le();
+    }
+}
+
+/// # `DrvFollowingErrorActualValue`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct DrvFollowingErrorActualValue {
+    /// # 6010:09
+    /// Difference between target and actual position in increments.
+    pub following_error: i32,
+}
+
+impl TxPdoObject for DrvFollowingErrorActualValue {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.following_error = bits[0..32].load_le();
+    }
+}
+
+/// # `DrvModesOfOperationDisplay`
+/// 8 bits / 1 byte
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 8)]
+pub struct DrvModesOfOperationDisplay {
+    /// # 6010:03
+    /// Currently active mode of operation.
+    pub modes_of_operation_display: u8,
+}
+
+impl TxPdoObject for DrvModesOfOperationDisplay {
+    fn read(&mut self, bits: &BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        self.modes_of_operation_display = bits[0..8].load_le();
+    }
+}
+
+/// # `DrvControlword`
+/// 16 bits / 2 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 16)]
+pub struct DrvControlword {
+    /// # 7010:01
+    /// CiA402 controlword, see [`crate::helpers::cia402::Cia402Command`].
+    pub controlword: u16,
+}
+
+impl RxPdoObject for DrvControlword {
+    fn write(&self, buffer: &mut BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        buffer[0..16].store_le(self.controlword);
+    }
+}
+
+/// # `DrvTargetVelocity`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct DrvTargetVelocity {
+    /// # 7010:06
+    /// Target velocity for cyclic synchronous velocity mode.
+    pub target_velocity: i32,
+}
+
+impl RxPdoObject for DrvTargetVelocity {
+    fn write(&self, buffer: &mut BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        buffer[0..32].store_le(self.target_velocity);
+    }
+}
+
+/// # `DrvTargetPosition`
+/// 32 bits / 4 bytes
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 32)]
+pub struct DrvTargetPosition {
+    /// # 7010:05
+    /// Target position for cyclic synchronous position mode in increments.
+    pub target_position: u32,
+}
+
+impl RxPdoObject for DrvTargetPosition {
+    fn write(&self, buffer: &mut BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        buffer[0..32].store_le(self.target_position);
+    }
+}
+
+/// # `DrvModesOfOperation`
+/// 8 bits / 1 byte
+#[derive(Debug, Clone, Default, PdoObject, PartialEq, Eq)]
+#[pdo_object(bits = 8)]
+pub struct DrvModesOfOperation {
+    /// # 7010:03
+    /// Requested mode of operation.
+    pub modes_of_operation: u8,
+}
+
+impl RxPdoObject for DrvModesOfOperation {
+    fn write(&self, buffer: &mut BitSlice<u8, Lsb0>) {
+        // Offset 0.0
+        buffer[0..8].store_le(self.modes_of_operation);
+    }
+}
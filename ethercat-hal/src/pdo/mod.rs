@@ -2,9 +2,12 @@ pub mod analog_input;
 pub mod basic;
 pub mod el252x;
 pub mod el32xx;
+pub mod el331x;
+pub mod el335x;
 pub mod el40xx;
 pub mod el5152;
 pub mod el70x1;
+pub mod el72x1;
 use crate::coe::Configuration;
 use bitvec::prelude::*;
 
@@ -0,0 +1,436 @@
+use anyhow::anyhow;
+use ethercrab::{SubDevice, SubDeviceRef, SubIndex};
+use std::ops::Deref;
+
+/// Maximum length of a string, byte or complete access upload
+pub const SDO_MAX_UPLOAD_LEN: usize = 512;
+
+/// Maximum length of a download
+///
+/// ethercrab 0.6 only implements expedited downloads and doesn't expose the mailbox for a
+/// normal or segmented download. Writing `u64`, `i64`, `f64` or strings, byte arrays and complete
+/// accesses longer than 4 bytes is therefore rejected by [`SdoRequest::validate`].
+pub const SDO_MAX_DOWNLOAD_LEN: usize = 4;
+
+/// Data type of an SDO upload or download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdoDataType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    /// VISIBLE_STRING, up to [`SDO_MAX_UPLOAD_LEN`] bytes
+    String,
+    /// OCTET_STRING or complete access, up to [`SDO_MAX_UPLOAD_LEN`] bytes
+    Bytes,
+}
+
+impl SdoDataType {
+    /// Parses the lowercase type names used by the REST API (`"u16"`, `"f32"`, `"string"`, ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "bool" => Self::Bool,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "string" => Self::String,
+            "bytes" => Self::Bytes,
+            _ => return None,
+        })
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::String => "string",
+            Self::Bytes => "bytes",
+        }
+    }
+}
+
+/// Typed value of an SDO upload or download
+#[derive(Debug, Clone, PartialEq)]
+pub enum SdoValue {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl SdoValue {
+    pub const fn data_type(&self) -> SdoDataType {
+        match self {
+            Self::Bool(_) => SdoDataType::Bool,
+            Self::U8(_) => SdoDataType::U8,
+            Self::U16(_) => SdoDataType::U16,
+            Self::U32(_) => SdoDataType::U32,
+            Self::U64(_) => SdoDataType::U64,
+            Self::I8(_) => SdoDataType::I8,
+            Self::I16(_) => SdoDataType::I16,
+            Self::I32(_) => SdoDataType::I32,
+            Self::I64(_) => SdoDataType::I64,
+            Self::F32(_) => SdoDataType::F32,
+            Self::F64(_) => SdoDataType::F64,
+            Self::String(_) => SdoDataType::String,
+            Self::Bytes(_) => SdoDataType::Bytes,
+        }
+    }
+
+    /// Decodes little endian SDO data
+    ///
+    /// Numeric types need at least their size in bytes, additional bytes are ignored.
+    pub fn from_bytes(data_type: SdoDataType, bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        fn array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], anyhow::Error> {
+            bytes
+                .get(..N)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| anyhow!("Expected {} bytes, got {}", N, bytes.len()))
+        }
+
+        Ok(match data_type {
+            SdoDataType::Bool => Self::Bool(array::<1>(bytes)?[0] & 1 != 0),
+            SdoDataType::U8 => Self::U8(u8::from_le_bytes(array(bytes)?)),
+            SdoDataType::U16 => Self::U16(u16::from_le_bytes(array(bytes)?)),
+            SdoDataType::U32 => Self::U32(u32::from_le_bytes(array(bytes)?)),
+            SdoDataType::U64 => Self::U64(u64::from_le_bytes(array(bytes)?)),
+            SdoDataType::I8 => Self::I8(i8::from_le_bytes(array(bytes)?)),
+            SdoDataType::I16 => Self::I16(i16::from_le_bytes(array(bytes)?)),
+            SdoDataType::I32 => Self::I32(i32::from_le_bytes(array(bytes)?)),
+            SdoDataType::I64 => Self::I64(i64::from_le_bytes(array(bytes)?)),
+            SdoDataType::F32 => Self::F32(f32::from_le_bytes(array(bytes)?)),
+            SdoDataType::F64 => Self::F64(f64::from_le_bytes(array(bytes)?)),
+            SdoDataType::String => Self::String(
+                // visible strings are often zero padded to their maximum length
+                String::from_utf8_lossy(bytes)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            ),
+            SdoDataType::Bytes => Self::Bytes(bytes.to_vec()),
+        })
+    }
+
+    /// Encodes the value as little endian SDO data
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Bool(v) => vec![u8::from(*v)],
+            Self::U8(v) => v.to_le_bytes().to_vec(),
+            Self::U16(v) => v.to_le_bytes().to_vec(),
+            Self::U32(v) => v.to_le_bytes().to_vec(),
+            Self::U64(v) => v.to_le_bytes().to_vec(),
+            Self::I8(v) => v.to_le_bytes().to_vec(),
+            Self::I16(v) => v.to_le_bytes().to_vec(),
+            Self::I32(v) => v.to_le_bytes().to_vec(),
+            Self::I64(v) => v.to_le_bytes().to_vec(),
+            Self::F32(v) => v.to_le_bytes().to_vec(),
+            Self::F64(v) => v.to_le_bytes().to_vec(),
+            Self::String(v) => v.as_bytes().to_vec(),
+            Self::Bytes(v) => v.clone(),
+        }
+    }
+}
+
+/// An object found while browsing the object dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdoObjectInfo {
+    pub index: u16,
+    /// Raw content of subindex 0
+    pub sub_index_0: Vec<u8>,
+}
+
+impl SdoObjectInfo {
+    /// Highest subindex of a RECORD or ARRAY object
+    ///
+    /// A one byte subindex 0 is the usual "number of entries". It can't be told apart from a
+    /// one byte VAR object without the SDO information service, which ethercrab doesn't
+    /// implement.
+    pub fn max_sub_index(&self) -> Option<u8> {
+        match self.sub_index_0.as_slice() {
+            [n] => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+/// A single CoE access, executed with [`SdoRequest::execute`]
+#[derive(Debug, Clone)]
+pub enum SdoRequest {
+    /// SDO upload of a single subindex or with complete access
+    Upload {
+        index: u16,
+        sub_index: SubIndex,
+        data_type: SdoDataType,
+    },
+    /// Expedited SDO download of up to [`SDO_MAX_DOWNLOAD_LEN`] bytes, larger values are
+    /// not supported
+    Download {
+        index: u16,
+        sub_index: SubIndex,
+        value: SdoValue,
+    },
+    /// Checks if an object exists by uploading its subindex 0
+    Probe { index: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SdoResponse {
+    Upload(SdoValue),
+    Download,
+    /// `None` if the subdevice aborted the upload, usually "object does not exist"
+    Probe(Option<SdoObjectInfo>),
+}
+
+impl SdoRequest {
+    /// Validates the request without touching the bus
+    ///
+    /// With `operational` set, downloads to the PDO mapping and assignment objects are
+    /// rejected because changing them in OP would shift the process image under the running
+    /// devices.
+    pub fn validate(&self, operational: bool) -> Result<(), anyhow::Error> {
+        let Self::Download {
+            index,
+            sub_index,
+            value,
+        } = self
+        else {
+            return Ok(());
+        };
+
+        if operational && is_pdo_configuration_object(*index) {
+            return Err(anyhow!(
+                "[{}::SdoRequest::validate] 0x{:04X} is part of the PDO configuration and can't be written in OP",
+                module_path!(),
+                index
+            ));
+        }
+
+        let len = value.to_bytes().len();
+        if len == 0 || len > SDO_MAX_DOWNLOAD_LEN {
+            return Err(anyhow!(
+                "[{}::SdoRequest::validate] Download to 0x{:04X}:{:?} has {} bytes, only 1 to {} bytes are supported",
+                module_path!(),
+                index,
+                sub_index,
+                len,
+                SDO_MAX_DOWNLOAD_LEN
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates and executes the request on the subdevice
+    ///
+    /// Every request is a single mailbox transfer so the caller can interleave it with
+    /// process data cycles.
+    pub async fn execute<S>(
+        &self,
+        subdevice: &SubDeviceRef<'_, S>,
+        operational: bool,
+    ) -> Result<SdoResponse, anyhow::Error>
+    where
+        S: Deref<Target = SubDevice> + Sync,
+    {
+        self.validate(operational)?;
+        match self {
+            Self::Upload {
+                index,
+                sub_index,
+                data_type,
+            } => {
+                let bytes = sdo_upload_bytes(subdevice, *index, *sub_index).await?;
+                Ok(SdoResponse::Upload(SdoValue::from_bytes(
+                    *data_type, &bytes,
+                )?))
+            }
+            Self::Download {
+                index,
+                sub_index,
+                value,
+            } => {
+                let bytes = value.to_bytes();
+                // ethercrab sends the packed length of the value, so download exactly the
+                // type's width
+                match bytes.as_slice() {
+                    [a] => subdevice.sdo_write(*index, *sub_index, *a).await?,
+                    [a, b] => {
+                        subdevice
+                            .sdo_write(*index, *sub_index, u16::from_le_bytes([*a, *b]))
+                            .await?
+                    }
+                    [a, b, c] => {
+                        subdevice
+                            .sdo_write(*index, *sub_index, [*a, *b, *c])
+                            .await?
+                    }
+                    [a, b, c, d] => {
+                        subdevice
+                            .sdo_write(*index, *sub_index, u32::from_le_bytes([*a, *b, *c, *d]))
+                            .await?
+                    }
+                    _ => unreachable!("download length is checked by validate"),
+                }
+                Ok(SdoResponse::Download)
+            }
+            Self::Probe { index } => {
+                let sub_index_0 = sdo_upload_bytes(subdevice, *index, SubIndex::Index(0))
+                    .await
+                    .ok();
+                Ok(SdoResponse::Probe(sub_index_0.map(|sub_index_0| {
+                    SdoObjectInfo {
+                        index: *index,
+                        sub_index_0,
+                    }
+                })))
+            }
+        }
+    }
+}
+
+/// PDO mapping (0x1600-0x1BFF) and sync manager PDO assignment (0x1C10-0x1C2F) objects
+pub const fn is_pdo_configuration_object(index: u16) -> bool {
+    matches!(index, 0x1600..=0x1BFF | 0x1C10..=0x1C2F)
+}
+
+/// Uploads the raw bytes of an object, expedited, normal or segmented
+async fn sdo_upload_bytes<S>(
+    subdevice: &SubDeviceRef<'_, S>,
+    index: u16,
+    sub_index: SubIndex,
+) -> Result<Vec<u8>, anyhow::Error>
+where
+    S: Deref<Target = SubDevice> + Sync,
+{
+    let bytes = subdevice
+        .sdo_read::<heapless::Vec<u8, SDO_MAX_UPLOAD_LEN>>(index, sub_index)
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_roundtrip() {
+        for value in [
+            SdoValue::Bool(true),
+            SdoValue::U8(0xAB),
+            SdoValue::U16(0xBEEF),
+            SdoValue::U32(0xDEAD_BEEF),
+            SdoValue::U64(u64::MAX - 1),
+            SdoValue::I8(-5),
+            SdoValue::I16(-1234),
+            SdoValue::I32(-123_456),
+            SdoValue::I64(i64::MIN),
+            SdoValue::F32(-0.5),
+            SdoValue::F64(1e-9),
+            SdoValue::String("EL3204".to_string()),
+            SdoValue::Bytes(vec![1, 2, 3]),
+        ] {
+            let bytes = value.to_bytes();
+            assert_eq!(
+                SdoValue::from_bytes(value.data_type(), &bytes).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        // expedited uploads can return more bytes than the type needs
+        assert_eq!(
+            SdoValue::from_bytes(SdoDataType::U16, &[0x34, 0x12, 0, 0]).unwrap(),
+            SdoValue::U16(0x1234)
+        );
+        assert!(SdoValue::from_bytes(SdoDataType::U32, &[0x34, 0x12]).is_err());
+        assert_eq!(
+            SdoValue::from_bytes(SdoDataType::String, b"EK1100\0\0").unwrap(),
+            SdoValue::String("EK1100".to_string())
+        );
+    }
+
+    #[test]
+    fn test_data_type_names() {
+        for data_type in [
+            SdoDataType::Bool,
+            SdoDataType::U64,
+            SdoDataType::I16,
+            SdoDataType::F32,
+            SdoDataType::String,
+            SdoDataType::Bytes,
+        ] {
+            assert_eq!(SdoDataType::from_name(data_type.name()), Some(data_type));
+        }
+        assert_eq!(SdoDataType::from_name("u128"), None);
+    }
+
+    #[test]
+    fn test_validate_download() {
+        let download = |index, value| SdoRequest::Download {
+            index,
+            sub_index: SubIndex::Index(1),
+            value,
+        };
+
+        assert!(download(0x8000, SdoValue::U16(1)).validate(true).is_ok());
+        assert!(download(0x8000, SdoValue::U64(1)).validate(false).is_err());
+        assert!(
+            download(0x8000, SdoValue::String(String::new()))
+                .validate(false)
+                .is_err()
+        );
+        assert!(
+            download(0x1C13, SdoValue::U16(0x1A00))
+                .validate(true)
+                .is_err()
+        );
+        assert!(
+            download(0x1C13, SdoValue::U16(0x1A00))
+                .validate(false)
+                .is_ok()
+        );
+        assert!(download(0x1A00, SdoValue::U32(0)).validate(true).is_err());
+    }
+
+    #[test]
+    fn test_max_sub_index() {
+        let info = |sub_index_0| SdoObjectInfo {
+            index: 0x8000,
+            sub_index_0,
+        };
+        assert_eq!(info(vec![0x11]).max_sub_index(), Some(0x11));
+        assert_eq!(info(vec![0x00, 0x01]).max_sub_index(), None);
+    }
+}
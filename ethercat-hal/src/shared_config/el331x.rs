@@ -0,0 +1,190 @@
+use crate::helpers::ethercrab_types::EthercrabSubDevicePreoperational;
+use crate::shared_config::el30xx::EL30XXPresentation;
+
+/// Base index of the "TC Settings" object of channel 1, channel n is at `0x8000 + (n - 1) * 0x10`
+pub const EL331X_TC_SETTINGS_BASE_INDEX: u16 = 0x8000;
+
+/// Settings of one channel, written to its "TC Settings" object (80n0)
+#[derive(Debug, Clone)]
+pub struct EL331XChannelConfiguration {
+    /// Value presentation (80n0:02)
+    /// - 0: Signed presentation
+    /// - 1: Unsigned presentation
+    /// - 2: Absolute value with MSB as sign
+    pub presentation: EL30XXPresentation,
+
+    /// Enable the filter configured with `filter_settings` (80n0:06, 0x8000:15)
+    pub enable_filter: bool,
+
+    /// Enable limit 1 (80n0:07)
+    pub enable_limit_1: bool,
+
+    /// Enable limit 2 (80n0:08)
+    pub enable_limit_2: bool,
+
+    /// Enable the vendor calibration (80n0:0B)
+    pub enable_vendor_calibration: bool,
+
+    /// Cold junction compensation (80n0:0C)
+    pub cold_junction_compensation: EL331XColdJunctionCompensation,
+
+    /// First limit value for setting the status bits in 0.1°C (80n0:13)
+    pub limit_1: i16,
+
+    /// Second limit value for setting the status bits in 0.1°C (80n0:14)
+    pub limit_2: i16,
+
+    /// Thermocouple element (80n0:19)
+    pub thermocouple_type: EL331XThermocoupleType,
+}
+
+impl EL331XChannelConfiguration {
+    pub async fn write_channel_config<'a>(
+        &self,
+        device: &EthercrabSubDevicePreoperational<'a>,
+        base_index: u16,
+    ) -> Result<(), anyhow::Error> {
+        device
+            .sdo_write(base_index, 0x02, u8::from(self.presentation))
+            .await?;
+        device
+            .sdo_write(base_index, 0x06, self.enable_filter)
+            .await?;
+        device
+            .sdo_write(base_index, 0x07, self.enable_limit_1)
+            .await?;
+        device
+            .sdo_write(base_index, 0x08, self.enable_limit_2)
+            .await?;
+        device
+            .sdo_write(base_index, 0x0B, self.enable_vendor_calibration)
+            .await?;
+        device
+            .sdo_write(base_index, 0x0C, u8::from(self.cold_junction_compensation))
+            .await?;
+        device.sdo_write(base_index, 0x13, self.limit_1).await?;
+        device.sdo_write(base_index, 0x14, self.limit_2).await?;
+        device
+            .sdo_write(base_index, 0x19, u16::from(self.thermocouple_type))
+            .await?;
+        Ok(())
+    }
+}
+
+impl Default for EL331XChannelConfiguration {
+    fn default() -> Self {
+        Self {
+            presentation: EL30XXPresentation::Signed,
+            enable_filter: true,
+            enable_limit_1: false,
+            enable_limit_2: false,
+            enable_vendor_calibration: true,
+            cold_junction_compensation: EL331XColdJunctionCompensation::Internal,
+            limit_1: 0,
+            limit_2: 0,
+            thermocouple_type: EL331XThermocoupleType::K,
+        }
+    }
+}
+
+/// Thermocouple element (80n0:19)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EL331XThermocoupleType {
+    K,
+    J,
+    L,
+    E,
+    T,
+    N,
+    U,
+    B,
+    R,
+    S,
+    C,
+    /// Voltage measurement ±30 mV, 1 µV resolution
+    Voltage30mV,
+    /// Voltage measurement ±60 mV, 2 µV resolution
+    Voltage60mV,
+    /// Voltage measurement ±75 mV, 4 µV resolution
+    Voltage75mV,
+}
+
+impl From<EL331XThermocoupleType> for u16 {
+    fn from(thermocouple_type: EL331XThermocoupleType) -> Self {
+        match thermocouple_type {
+            EL331XThermocoupleType::K => 0,
+            EL331XThermocoupleType::J => 1,
+            EL331XThermocoupleType::L => 2,
+            EL331XThermocoupleType::E => 3,
+            EL331XThermocoupleType::T => 4,
+            EL331XThermocoupleType::N => 5,
+            EL331XThermocoupleType::U => 6,
+            EL331XThermocoupleType::B => 7,
+            EL331XThermocoupleType::R => 8,
+            EL331XThermocoupleType::S => 9,
+            EL331XThermocoupleType::C => 10,
+            EL331XThermocoupleType::Voltage30mV => 100,
+            EL331XThermocoupleType::Voltage60mV => 101,
+            EL331XThermocoupleType::Voltage75mV => 102,
+        }
+    }
+}
+
+/// Cold junction compensation (80n0:0C)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EL331XColdJunctionCompensation {
+    /// Measured with the internal sensor at the terminal contacts
+    Internal,
+    /// No compensation, e.g. if the cold junction is kept at 0°C externally
+    None,
+}
+
+impl From<EL331XColdJunctionCompensation> for u8 {
+    fn from(cold_junction_compensation: EL331XColdJunctionCompensation) -> Self {
+        match cold_junction_compensation {
+            EL331XColdJunctionCompensation::Internal => 0,
+            EL331XColdJunctionCompensation::None => 1,
+        }
+    }
+}
+
+/// Conversion time filter (0x8000:15), applies to all channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EL331XFilterSettings {
+    FIR50Hz,
+    FIR60Hz,
+    FIR100Hz,
+    FIR500Hz,
+    FIR1kHz,
+    FIR2kHz,
+    FIR3750Hz,
+    FIR7500Hz,
+    FIR15kHz,
+    FIR30kHz,
+    FIR5Hz,
+    FIR10Hz,
+}
+
+impl From<EL331XFilterSettings> for u16 {
+    fn from(filter_settings: EL331XFilterSettings) -> Self {
+        match filter_settings {
+            EL331XFilterSettings::FIR50Hz => 0,
+            EL331XFilterSettings::FIR60Hz => 1,
+            EL331XFilterSettings::FIR100Hz => 2,
+            EL331XFilterSettings::FIR500Hz => 3,
+            EL331XFilterSettings::FIR1kHz => 4,
+            EL331XFilterSettings::FIR2kHz => 5,
+            EL331XFilterSettings::FIR3750Hz => 6,
+            EL331XFilterSettings::FIR7500Hz => 7,
+            EL331XFilterSettings::FIR15kHz => 8,
+            EL331XFilterSettings::FIR30kHz => 9,
+            EL331XFilterSettings::FIR5Hz => 10,
+            EL331XFilterSettings::FIR10Hz => 11,
+        }
+    }
+}
+
+/// The EL331x reports an open circuit as overrange together with the error bit
+pub const fn is_open_circuit(overrange: bool, error: bool) -> bool {
+    overrange && error
+}
@@ -12,9 +12,8 @@ impl MachineAct for AnalogInputTestMachine {
                 self.emit_measurement_rate();
             }
             MachineMessage::UnsubscribeNamespace => self.namespace.namespace = None,
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             crate::MachineMessage::ConnectToMachine(_machine_connection) => {}
             MachineMessage::DisconnectMachine(_machine_connection) => {}
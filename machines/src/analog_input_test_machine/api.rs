@@ -6,16 +6,17 @@ use control_core::socketio::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{MachineApi, analog_input_test_machine::AnalogInputTestMachine};
+use crate::{MachineApi, MachineApiSchema, analog_input_test_machine::AnalogInputTestMachine};
 
 #[derive(Debug, Clone)]
 pub struct AnalogInputTestMachineNamespace {
     pub namespace: Option<Namespace>,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub enum MeasurementEvent {
     MeasurementRateHz(f64),
     Measurement(f64, String),
@@ -47,11 +48,17 @@ pub enum AnalogInputTestMachineEvents {
     State(Event<MeasurementEvent>),
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct Mutation {
     measurement_rate_hz: i32,
 }
 
+impl MachineApiSchema for AnalogInputTestMachine {
+    type Mutation = Mutation;
+    type State = ();
+    type LiveValues = ();
+}
+
 impl MachineApi for AnalogInputTestMachine {
     fn api_get_sender(&self) -> smol::channel::Sender<crate::MachineMessage> {
         self.api_sender.clone()
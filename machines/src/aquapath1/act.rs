@@ -39,10 +39,8 @@ impl MachineAct for AquaPathV1 {
                 self.emit_state();
             }
             MachineMessage::UnsubscribeNamespace => self.namespace.namespace = None,
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) =>
                 /*Doesnt connect to any Machine so do nothing*/
@@ -1,18 +1,19 @@
 use super::{AquaPathV1, AquaPathV1Mode};
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::channel::Sender;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     pub front_flow: f64,
     pub back_flow: f64,
@@ -34,7 +35,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// mode state
@@ -51,50 +52,50 @@ impl StateEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct TempStates {
     pub front: TempState,
     pub back: TempState,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct TempState {
     pub temperature: f64,
     pub target_temperature: f64,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct ModeState {
     pub mode: AquaPathV1Mode,
 }
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct FlowStates {
     pub front: FlowState,
     pub back: FlowState,
 }
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct FlowState {
     pub flow: f64,
     pub should_flow: bool,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct FanState {
     pub revolutions: f64,
     pub max_revolutions: f64,
 }
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct FanStates {
     pub front: FanState,
     pub back: FanState,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct ToleranceState {
     pub heating: f64,
     pub cooling: f64,
 }
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct ToleranceStates {
     pub front: ToleranceState,
     pub back: ToleranceState,
@@ -105,8 +106,8 @@ pub enum AquaPathV1Events {
     State(Event<StateEvent>),
 }
 
-#[derive(Deserialize, Serialize)]
-enum Mutation {
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub enum Mutation {
     //Mode
     SetAquaPathMode(AquaPathV1Mode),
 
@@ -160,6 +161,12 @@ impl CacheableEvents<AquaPathV1Events> for AquaPathV1Events {
     }
 }
 
+impl MachineApiSchema for AquaPathV1 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for AquaPathV1 {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
         self.api_sender.clone()
@@ -26,20 +26,26 @@ impl MachineAct for BbmAutomatikV2 {
 
         // Schieber ⟷ Drücker anti-collision interlocks: stop an axis that is
         // travelling while the other is out of its start position.
-        let interlock_triggered =
-            self.enforce_schieber_interlock() | self.enforce_druecker_interlock();
+        let interlock_triggered = self.enforce_interlocks();
         if interlock_triggered {
             self.emit_state();
         }
 
-        // Hardware monitor: watch hardware status, no timing needed
-        let status_changed = self.update_hardware_monitor();
+        // Hardware monitor: watch hardware status and advance homing (reference
+        // switches), no timing needed
+        let status_changed = self.update_hardware_monitor(now);
         if status_changed {
             self.emit_state();
         }
 
-        // Check homing status (reference switches)
-        self.update_homing();
+        // Coordinated multi-axis move
+        let interpolation_changed = self.update_interpolation(now);
+        if interpolation_changed {
+            self.emit_state();
+        }
+
+        // Axis travel and move counts, periodic persistence of the counters
+        self.track_production(now);
 
         // Start a deferred Schieber home once the Drücker is referenced
         // (Drücker-before-Schieber safety rule).
@@ -58,7 +64,7 @@ impl MachineAct for BbmAutomatikV2 {
         }
 
         // Periodic debug log to console (every 1 second when any axis is moving)
-        let any_axis_moving = self.axis_speeds.iter().any(|&s| s != 0);
+        let any_axis_moving = self.axes.iter().any(|a| a.speed_hz() != 0);
         if any_axis_moving {
             let should_log = match self.last_debug_log {
                 Some(last) => now.duration_since(last) > DEBUG_LOG_INTERVAL,
@@ -67,12 +73,14 @@ impl MachineAct for BbmAutomatikV2 {
             if should_log {
                 self.last_debug_log = Some(now);
                 // Log info for moving axes
-                let axis_names = ["MT", "Schieber", "Drücker"];
-                for (i, &speed) in self.axis_speeds.iter().enumerate() {
-                    if speed != 0 {
-                        let pos = self.axes[i].get_position();
-                        tracing::info!("[BBM {}] freq={}Hz pos={}p", axis_names[i], speed, pos);
-                    }
+                for axis in self.axes.iter().filter(|a| a.speed_hz() != 0) {
+                    let pos = axis.pto().get_position();
+                    tracing::info!(
+                        "[BBM {}] freq={}Hz pos={}p",
+                        axis.name(),
+                        axis.speed_hz(),
+                        pos
+                    );
                 }
             }
         }
@@ -88,9 +96,8 @@ impl MachineAct for BbmAutomatikV2 {
             MachineMessage::UnsubscribeNamespace => {
                 self.namespace.namespace = None;
             }
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) => {
                 // Does not connect to other machines; do nothing
@@ -1,17 +1,20 @@
+use super::program::{SequenceProgram, SpeedPreset};
 use super::{AxisTeachPositions, BbmAutomatikV2, TeachSlot};
-use crate::{MachineApi, MachineMessage};
+use crate::production_stats::ProductionStats;
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
 /// State event - contains controllable values (outputs, speeds)
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     pub output_states: [bool; 8],
     pub axis_speeds: [i32; 3],
@@ -36,13 +39,32 @@ pub struct StateEvent {
     /// true when the Drücker is currently blocked (Schieber mid-travel —
     /// neither at start nor at ziel, interlock B).
     pub druecker_interlock_active: bool,
+    /// Ids of the interlock rules currently blocking commands (see
+    /// `bbm_automatik_v2::interlocks`)
+    pub active_interlocks: Vec<String>,
     pub auto_running: bool,
     pub auto_current_set: u32,
     pub auto_current_block: u32,
     pub auto_current_cycle: u32,
     pub auto_total_sets: u32,
+    /// Paused or interrupted, resumable from the last checkpoint. The
+    /// sequence still counts as `auto_running`.
+    pub auto_paused: bool,
+    /// A pause takes effect once the moves in flight are done
+    pub auto_pause_requested: bool,
+    /// Name of the running sequence program
+    pub auto_program: Option<String>,
+    /// A coordinated multi-axis move is running
+    pub interpolating: bool,
     /// Per-axis teach-in positions (persisted to disk)
     pub teach_positions: [AxisTeachPositions; 3],
+    /// Stored auto-sequence programs (persisted to disk)
+    pub sequence_programs: Vec<SequenceProgram>,
+    /// Stored auto-sequence speed presets (persisted to disk)
+    pub speed_presets: Vec<SpeedPreset>,
+    /// Production counters, cycle times, axis usage and downtimes (persisted
+    /// to disk)
+    pub production: ProductionStats,
 }
 
 impl StateEvent {
@@ -52,7 +74,7 @@ impl StateEvent {
 }
 
 /// Live values event - contains sensor readings and positions
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct LiveValuesEvent {
     pub input_states: [bool; 8],
     pub axis_positions: [i32; 3], // Signed to support negative positions
@@ -66,12 +88,19 @@ impl LiveValuesEvent {
 
 /// Events emitted by the machine
 pub enum BbmAutomatikV2Events {
-    State(Event<StateEvent>),
+    State(Box<Event<StateEvent>>),
     LiveValues(Event<LiveValuesEvent>),
 }
 
+/// Target of one axis of [`Mutation::MoveInterpolated`]
+#[derive(Deserialize, Debug, Clone, Copy, JsonSchema)]
+pub struct AxisTarget {
+    pub index: usize,
+    pub position_mm: f32,
+}
+
 /// Mutations (commands from UI to machine)
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(tag = "action", content = "value")]
 pub enum Mutation {
     /// Set a single digital output
@@ -100,6 +129,12 @@ pub enum Mutation {
         delta_mm: f32,
         speed_mm_s: f32,
     },
+    /// Move 2-3 axes together on a straight line, `speed_mm_s` along the
+    /// line. All axes start and arrive together and stop together.
+    MoveInterpolated {
+        targets: Vec<AxisTarget>,
+        speed_mm_s: f32,
+    },
     /// Stop a single axis
     StopAxis { index: usize },
     /// Stop all axes
@@ -120,10 +155,37 @@ pub enum Mutation {
     CancelHoming { index: usize },
     /// Reset all driver alarms
     ResetAlarms,
-    /// Start auto-sequence with speed preset and number of sets
-    StartAutoSequence { speed_preset: String, total_sets: u32 },
-    /// Stop auto-sequence
+    /// Start auto-sequence with speed preset and number of sets. Runs the
+    /// standard program unless `program` names another stored program.
+    StartAutoSequence {
+        speed_preset: String,
+        total_sets: u32,
+        program: Option<String>,
+    },
+    /// Check a stored program against teach positions, soft limits and
+    /// interlocks without starting it. Fails with the first problem found.
+    ValidateAutoSequence {
+        program: String,
+        speed_preset: String,
+        total_sets: u32,
+    },
+    /// Store a sequence program, replacing the one with the same name
+    SaveSequenceProgram { program: SequenceProgram },
+    /// Delete a stored sequence program (not the standard program)
+    DeleteSequenceProgram { name: String },
+    /// Store a speed preset, replacing the one with the same name
+    SaveSpeedPreset { preset: SpeedPreset },
+    /// Stop auto-sequence, also discards a paused one and its checkpoint
     StopAutoSequence,
+    /// Pause the auto-sequence once the moves in flight are done
+    PauseAutoSequence,
+    /// Resume a paused, interrupted or recovered auto-sequence from its last
+    /// checkpoint
+    ResumeAutoSequence,
+    /// Reset the production counters, cycle times and downtimes
+    ResetProductionStats,
+    /// Reset travel and move count of an axis after its maintenance
+    ResetAxisUsage { axis: usize },
     /// Capture the current axis position into the given teach slot
     SaveTeachPosition { axis: usize, slot: TeachSlot },
     /// Clear a teach slot (set back to empty)
@@ -176,7 +238,7 @@ impl NamespaceCacheingLogic<BbmAutomatikV2Events> for BbmAutomatikV2Namespace {
 impl CacheableEvents<BbmAutomatikV2Events> for BbmAutomatikV2Events {
     fn event_value(&self) -> GenericEvent {
         match self {
-            Self::State(event) => event.clone().into(),
+            Self::State(event) => event.as_ref().clone().into(),
             Self::LiveValues(event) => event.clone().into(),
         }
     }
@@ -186,6 +248,12 @@ impl CacheableEvents<BbmAutomatikV2Events> for BbmAutomatikV2Events {
     }
 }
 
+impl MachineApiSchema for BbmAutomatikV2 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for BbmAutomatikV2 {
     fn api_get_sender(&self) -> smol::channel::Sender<MachineMessage> {
         self.api_sender.clone()
@@ -194,13 +262,13 @@ impl MachineApi for BbmAutomatikV2 {
     fn api_mutate(&mut self, request_body: Value) -> Result<(), anyhow::Error> {
         let mutation: Mutation = serde_json::from_value(request_body)?;
         match mutation {
-            Mutation::SetOutput { index, on } => self.set_output(index, on),
-            Mutation::SetAllOutputs { on } => self.set_all_outputs(on),
-            Mutation::SetAxisSpeed { index, speed } => self.set_axis_speed(index, speed),
+            Mutation::SetOutput { index, on } => self.set_output(index, on)?,
+            Mutation::SetAllOutputs { on } => self.set_all_outputs(on)?,
+            Mutation::SetAxisSpeed { index, speed } => self.set_axis_speed(index, speed)?,
             Mutation::SetAxisSpeedMmS { index, speed_mm_s } => {
-                self.set_axis_speed_mm_s(index, speed_mm_s)
+                self.set_axis_speed_mm_s(index, speed_mm_s)?
             }
-            Mutation::SetAxisSpeedRpm { index, rpm } => self.set_axis_speed_rpm(index, rpm),
+            Mutation::SetAxisSpeedRpm { index, rpm } => self.set_axis_speed_rpm(index, rpm)?,
             Mutation::SetAxisAcceleration { index, accel_mm_s2 } => {
                 self.set_axis_acceleration(index, accel_mm_s2)
             }
@@ -208,32 +276,48 @@ impl MachineApi for BbmAutomatikV2 {
                 index,
                 position_mm,
                 speed_mm_s,
-            } => self.move_to_position_mm(index, position_mm, speed_mm_s),
+            } => self.move_to_position_mm(index, position_mm, speed_mm_s)?,
             Mutation::JogRelative {
                 index,
                 delta_mm,
                 speed_mm_s,
-            } => self.jog_relative(index, delta_mm, speed_mm_s),
+            } => self.jog_relative(index, delta_mm, speed_mm_s)?,
+            Mutation::MoveInterpolated {
+                targets,
+                speed_mm_s,
+            } => self.move_interpolated(&targets, speed_mm_s)?,
             Mutation::StopAxis { index } => self.stop_axis(index),
             Mutation::StopAllAxes => self.stop_all_axes(),
-            Mutation::SetBuerstenmotor { on } => self.set_buerstenmotor(on),
-            Mutation::SetRuettelmotor { on } => self.set_ruettelmotor(on),
-            Mutation::SetPneumatik { on } => self.set_pneumatik(on),
-            Mutation::SetLuefter { on } => self.set_luefter(on),
+            Mutation::SetBuerstenmotor { on } => self.set_buerstenmotor(on)?,
+            Mutation::SetRuettelmotor { on } => self.set_ruettelmotor(on)?,
+            Mutation::SetPneumatik { on } => self.set_pneumatik(on)?,
+            Mutation::SetLuefter { on } => self.set_luefter(on)?,
             Mutation::SetAmpel { rot, gelb, gruen } => self.set_ampel(rot, gelb, gruen),
             Mutation::StartHoming { index } => self.start_homing(index),
             Mutation::CancelHoming { index } => self.cancel_homing(index),
             Mutation::ResetAlarms => self.reset_alarms(),
-            Mutation::StartAutoSequence { speed_preset, total_sets } => {
-                self.start_auto_sequence(&speed_preset, total_sets);
+            Mutation::StartAutoSequence {
+                speed_preset,
+                total_sets,
+                program,
+            } => self.start_auto_sequence(program.as_deref(), &speed_preset, total_sets)?,
+            Mutation::ValidateAutoSequence {
+                program,
+                speed_preset,
+                total_sets,
+            } => {
+                self.prepare_auto_sequence(&program, &speed_preset, total_sets)?;
             }
+            Mutation::SaveSequenceProgram { program } => self.save_sequence_program(program)?,
+            Mutation::DeleteSequenceProgram { name } => self.delete_sequence_program(&name)?,
+            Mutation::SaveSpeedPreset { preset } => self.save_speed_preset(preset)?,
             Mutation::StopAutoSequence => self.stop_auto_sequence(),
-            Mutation::SaveTeachPosition { axis, slot } => {
-                self.save_teach_position(axis, slot)
-            }
-            Mutation::ClearTeachPosition { axis, slot } => {
-                self.clear_teach_position(axis, slot)
-            }
+            Mutation::PauseAutoSequence => self.pause_auto_sequence()?,
+            Mutation::ResumeAutoSequence => self.resume_auto_sequence()?,
+            Mutation::ResetProductionStats => self.reset_production_stats(),
+            Mutation::ResetAxisUsage { axis } => self.reset_axis_usage(axis)?,
+            Mutation::SaveTeachPosition { axis, slot } => self.save_teach_position(axis, slot),
+            Mutation::ClearTeachPosition { axis, slot } => self.clear_teach_position(axis, slot),
             Mutation::RenameCustomPosition { axis, slot, name } => {
                 self.rename_custom_teach_position(axis, slot, name)
             }
@@ -241,15 +325,11 @@ impl MachineApi for BbmAutomatikV2 {
                 axis,
                 slot,
                 speed_mm_s,
-            } => self.goto_teach_position(axis, slot, speed_mm_s),
-            Mutation::SetSoftLimitMax { axis, max_mm } => {
-                self.set_soft_limit_max(axis, max_mm)
-            }
-            Mutation::SetSoftLimitMin { axis, min_mm } => {
-                self.set_soft_limit_min(axis, min_mm)
-            }
-            Mutation::TeachSoftLimitMax { axis } => self.teach_soft_limit_max(axis),
-            Mutation::TeachSoftLimitMin { axis } => self.teach_soft_limit_min(axis),
+            } => self.goto_teach_position(axis, slot, speed_mm_s)?,
+            Mutation::SetSoftLimitMax { axis, max_mm } => self.set_soft_limit_max(axis, max_mm)?,
+            Mutation::SetSoftLimitMin { axis, min_mm } => self.set_soft_limit_min(axis, min_mm)?,
+            Mutation::TeachSoftLimitMax { axis } => self.teach_soft_limit_max(axis)?,
+            Mutation::TeachSoftLimitMin { axis } => self.teach_soft_limit_min(axis)?,
         }
         Ok(())
     }
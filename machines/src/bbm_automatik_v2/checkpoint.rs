@@ -0,0 +1,197 @@
+//! Resume points of the auto-sequence
+//!
+//! A [`Checkpoint`] is taken whenever the sequence reaches an op boundary
+//! with every axis at rest and no timed wait in progress. Only those points
+//! are safe to resume from: everything after the last checkpoint is repeated
+//! on resume, which is harmless because moves are absolute, outputs are
+//! levels and waits simply restart. Axis positions at the checkpoint are
+//! recorded so a resume first drives the axes back there (see
+//! [`restore_ops`]), after a pause, an interruption (door, alarm, step loss,
+//! interlock) or a power loss.
+//!
+//! The latest checkpoint is persisted next to the calibration and cleared
+//! when the sequence completes or is stopped.
+
+use super::program::{self, Op, SequenceProgram, SpeedPreset, ValidationContext};
+use super::{AxisTeachPositions, LoopFrame};
+use crate::{AsyncThreadMessage, state_file};
+use serde::{Deserialize, Serialize};
+use smol::channel::Sender;
+
+const FILENAME: &str = "bbm-automatik-v2-checkpoint.json";
+
+/// Axes closer than this to their checkpoint position are not moved on resume
+const POSITION_TOLERANCE_MM: f32 = 0.1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The program as it was started, later edits don't affect a resume
+    pub program: SequenceProgram,
+    pub speed_preset: SpeedPreset,
+    pub total_sets: u32,
+    /// Teach positions snapshotted at start
+    pub teach: [AxisTeachPositions; 3],
+    /// Next op to execute
+    pub pc: usize,
+    pub loops: Vec<LoopFrame>,
+    pub positions_mm: [f32; 3],
+    pub outputs: [bool; 8],
+}
+
+/// Moves from the current positions back to the checkpoint positions, one
+/// axis at a time. Tries every axis order and returns the first one that
+/// passes the soft-limit and interlock validation, or the error of the
+/// first order tried.
+pub fn restore_ops(checkpoint: &Checkpoint, ctx: &ValidationContext) -> anyhow::Result<Vec<Op>> {
+    let to_move: Vec<usize> = (0..checkpoint.positions_mm.len())
+        .filter(|&i| {
+            (ctx.positions_mm[i] - checkpoint.positions_mm[i]).abs() > POSITION_TOLERANCE_MM
+        })
+        .collect();
+
+    let mut first_err = None;
+    for order in orders(&to_move) {
+        let ops: Vec<Op> = order
+            .iter()
+            .flat_map(|&axis| {
+                let mut mask = [false; 3];
+                mask[axis] = true;
+                [
+                    Op::MoveTo {
+                        axis,
+                        position_mm: checkpoint.positions_mm[axis],
+                        speed_mm_s: checkpoint.speed_preset.axis_mm_s(axis),
+                    },
+                    Op::WaitAxes(mask),
+                ]
+            })
+            .collect();
+        match program::validate(&ops, ctx) {
+            Ok(()) => return Ok(ops),
+            Err(e) => {
+                first_err.get_or_insert(e);
+            }
+        }
+    }
+    // orders() yields at least one (possibly empty) order
+    Err(first_err.unwrap())
+}
+
+/// All orderings of `axes`
+fn orders(axes: &[usize]) -> Vec<Vec<usize>> {
+    if axes.is_empty() {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for (i, &first) in axes.iter().enumerate() {
+        let mut rest = axes.to_vec();
+        rest.remove(i);
+        for mut order in orders(&rest) {
+            order.insert(0, first);
+            result.push(order);
+        }
+    }
+    result
+}
+
+/// Load the persisted checkpoint of a sequence that didn't finish
+pub fn load() -> Option<Checkpoint> {
+    let p = state_file::path(FILENAME);
+    let s = std::fs::read_to_string(&p).ok()?;
+    match serde_json::from_str::<Checkpoint>(&s) {
+        Ok(c) => {
+            tracing::info!(
+                "[BbmAutomatikV2] Found auto-sequence checkpoint at {}",
+                p.display()
+            );
+            Some(c)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "[BbmAutomatikV2] Checkpoint at {} is corrupt ({}) - ignoring it",
+                p.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Atomically persist the checkpoint on the main thread, see
+/// [`state_file::run_off_loop`]. Errors are logged, not propagated.
+pub fn save(main_sender: Option<&Sender<AsyncThreadMessage>>, checkpoint: Checkpoint) {
+    state_file::run_off_loop(main_sender, move || {
+        let p = state_file::path(FILENAME);
+        match serde_json::to_string(&checkpoint) {
+            Ok(json) => state_file::write_atomic(&p, &json),
+            Err(e) => tracing::error!("[BbmAutomatikV2] Failed to serialize checkpoint: {}", e),
+        }
+    });
+}
+
+/// Remove the persisted checkpoint, queued after earlier [`save`]s
+pub fn clear(main_sender: Option<&Sender<AsyncThreadMessage>>) {
+    state_file::run_off_loop(main_sender, || {
+        let p = state_file::path(FILENAME);
+        match std::fs::remove_file(&p) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                tracing::error!("[BbmAutomatikV2] Failed to remove {}: {}", p.display(), e);
+            }
+            _ => {}
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bbm_automatik_v2::axes;
+    use crate::bbm_automatik_v2::program::{default_program, default_speed_presets};
+
+    #[test]
+    fn test_restore_order_respects_interlocks() {
+        let teach: [AxisTeachPositions; 3] = Default::default();
+        let checkpoint = Checkpoint {
+            program: default_program(),
+            speed_preset: default_speed_presets().remove(0),
+            total_sets: 1,
+            teach: teach.clone(),
+            pc: 7,
+            loops: Vec::new(),
+            // Schieber at ziel, Drücker extended
+            positions_mm: [150.0, 51.0, 100.0],
+            outputs: [false; 8],
+        };
+        let ctx = ValidationContext {
+            teach: &teach,
+            // Re-homed: everything at zero
+            positions_mm: [0.0; 3],
+            soft_limit_min_mm: [None; 3],
+            soft_limit_max_mm: [None; 3],
+            druecker_start_mm: 60.0,
+            schieber_start_mm: 7.0,
+            schieber_ziel_mm: 51.0,
+        };
+
+        // The Schieber has to reach ziel before the Drücker extends
+        let ops = restore_ops(&checkpoint, &ctx).unwrap();
+        let moved: Vec<usize> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::MoveTo { axis, .. } => Some(*axis),
+                _ => None,
+            })
+            .collect();
+        let schieber = moved.iter().position(|&a| a == axes::SCHIEBER).unwrap();
+        let druecker = moved.iter().position(|&a| a == axes::DRUECKER).unwrap();
+        assert_eq!(moved.len(), 3);
+        assert!(schieber < druecker);
+
+        // Already in place: nothing to do
+        let ctx = ValidationContext {
+            positions_mm: checkpoint.positions_mm,
+            ..ctx
+        };
+        assert!(restore_ops(&checkpoint, &ctx).unwrap().is_empty());
+    }
+}
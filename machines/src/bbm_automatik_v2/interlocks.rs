@@ -0,0 +1,221 @@
+//! Safety rules of the BBM Automatik, evaluated by the
+//! [`InterlockEngine`](crate::interlock::InterlockEngine). The Schieber ⟷
+//! Drücker thresholds follow the teach positions, so the machine rebuilds
+//! the rules whenever those change.
+
+use super::{SCHIEBER_INTERLOCK_TOLERANCE_MM, axes, inputs, outputs};
+use crate::interlock::{Condition, InterlockRule, Reaction, Scope};
+
+/// Door open: no motion, no homing, no brush
+pub const DOOR: &str = "door";
+/// No axis moves until every axis is homed
+pub const HOMING_GATE: &str = "homing_gate";
+/// Interlock A: the Schieber must not move while the Drücker is extended
+pub const SCHIEBER_A: &str = "schieber_a";
+/// Interlock B: the Drücker must not move while the Schieber is mid-travel
+pub const DRUECKER_B: &str = "druecker_b";
+/// The Drücker is homed and retracted before the Schieber homes
+pub const HOMING_ORDER: &str = "homing_order";
+
+/// Positions (mm) the Schieber ⟷ Drücker rules are built from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub druecker_start_mm: f32,
+    pub schieber_start_mm: f32,
+    pub schieber_ziel_mm: f32,
+}
+
+pub fn rules(t: Thresholds) -> Vec<InterlockRule> {
+    let tol = SCHIEBER_INTERLOCK_TOLERANCE_MM;
+    let druecker_retracted = Condition::AxisAtMost {
+        axis: axes::DRUECKER,
+        max_mm: t.druecker_start_mm + tol,
+    };
+
+    vec![
+        // The rotating brush is a hazard on its own, so it is gated like the
+        // axes. The machine additionally switches off Rüttler and Pneumatik
+        // and aborts the auto-sequence when this rule trips.
+        InterlockRule {
+            id: DOOR,
+            description: "Tür offen".to_string(),
+            scopes: vec![
+                Scope::Motion(None),
+                Scope::Homing(None),
+                Scope::Output(outputs::BUERSTENMOTOR),
+            ],
+            armed: Condition::Always,
+            permit: Condition::Input {
+                index: inputs::TUER,
+                high: true,
+            },
+            reaction: Reaction::Stop,
+        },
+        // Homing itself drives the axes inside LinearPtoAxis and is not
+        // gated, so referencing always remains possible. A step loss revokes
+        // an axis' homed flag, which re-arms this gate.
+        InterlockRule {
+            id: HOMING_GATE,
+            description: "nicht alle Achsen referenziert".to_string(),
+            scopes: vec![Scope::Motion(None)],
+            armed: Condition::Always,
+            permit: Condition::All(
+                [axes::MT, axes::SCHIEBER, axes::DRUECKER]
+                    .map(Condition::AxisHomed)
+                    .to_vec(),
+            ),
+            reaction: Reaction::Block,
+        },
+        // The Schieber would shear off the Drücker tips while the Drücker is
+        // extended above its start (e.g. start=150, target=300 ⇒ blocked at
+        // 160). Only armed once the Drücker position is known. Schieber
+        // homing is exempt: it must always reach its own reference.
+        InterlockRule {
+            id: SCHIEBER_A,
+            description: format!(
+                "Drücker über Start/ausgefahren (> {:.1} mm)",
+                t.druecker_start_mm + tol
+            ),
+            scopes: vec![Scope::Motion(Some(axes::SCHIEBER))],
+            armed: Condition::AxisHomed(axes::DRUECKER),
+            permit: druecker_retracted.clone(),
+            reaction: Reaction::Stop,
+        },
+        // The Schieber has two safe positions for the Drücker, at start and
+        // at ziel; mid-travel the Drücker would crash into it.
+        InterlockRule {
+            id: DRUECKER_B,
+            description: format!(
+                "Schieber mittendrin, nicht auf Start ({:.1} mm) oder Ziel ({:.1} mm)",
+                t.schieber_start_mm, t.schieber_ziel_mm
+            ),
+            scopes: vec![Scope::Motion(Some(axes::DRUECKER))],
+            armed: Condition::AxisHomed(axes::SCHIEBER),
+            permit: Condition::Any(vec![
+                Condition::AxisAtMost {
+                    axis: axes::SCHIEBER,
+                    max_mm: t.schieber_start_mm + tol,
+                },
+                Condition::AxisAtLeast {
+                    axis: axes::SCHIEBER,
+                    min_mm: t.schieber_ziel_mm - tol,
+                },
+            ]),
+            reaction: Reaction::Stop,
+        },
+        // During homing the anti-collision rules are bypassed (axes drive
+        // blindly to their sensors), so the Schieber only homes once the
+        // Drücker is referenced and retracted. The machine defers the
+        // Schieber home instead of refusing it.
+        InterlockRule {
+            id: HOMING_ORDER,
+            description: "Drücker muss zuerst referenzieren".to_string(),
+            scopes: vec![Scope::Homing(Some(axes::SCHIEBER))],
+            armed: Condition::Always,
+            permit: Condition::All(vec![
+                Condition::AxisHomed(axes::DRUECKER),
+                !Condition::AxisHoming(axes::DRUECKER),
+                druecker_retracted,
+            ]),
+            reaction: Reaction::Block,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interlock::{AxisSnapshot, Command, InterlockEngine, MachineSnapshot};
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        druecker_start_mm: 60.0,
+        schieber_start_mm: 7.0,
+        schieber_ziel_mm: 51.0,
+    };
+
+    fn snapshot_axes(schieber_mm: f32, druecker_mm: f32) -> [AxisSnapshot; 3] {
+        let axis = |position_mm| AxisSnapshot {
+            position_mm,
+            homed: true,
+            homing: false,
+            moving: false,
+        };
+        [axis(0.0), axis(schieber_mm), axis(druecker_mm)]
+    }
+
+    fn blocked_by(
+        engine: &InterlockEngine,
+        axes: &[AxisSnapshot],
+        command: Command,
+    ) -> Option<&'static str> {
+        let mut inputs = [false; 8];
+        inputs[inputs::TUER] = true;
+        let s = MachineSnapshot {
+            axes,
+            inputs: &inputs,
+            outputs: &[false; 8],
+        };
+        engine.check(command, &s).err().map(|b| b.rule)
+    }
+
+    #[test]
+    fn test_schieber_druecker_interlocks() {
+        let engine = InterlockEngine::new(rules(THRESHOLDS));
+
+        // Drücker at start (within tolerance): Schieber free
+        let axes = snapshot_axes(7.0, 60.4);
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::SCHIEBER)),
+            None
+        );
+        // Drücker extended: Schieber blocked, Drücker itself free
+        let axes = snapshot_axes(7.0, 80.0);
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::SCHIEBER)),
+            Some(SCHIEBER_A)
+        );
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::DRUECKER)),
+            None
+        );
+
+        // Schieber at ziel is safe for the Drücker, mid-travel is not
+        let axes = snapshot_axes(51.0, 60.0);
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::DRUECKER)),
+            None
+        );
+        let axes = snapshot_axes(30.0, 60.0);
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::DRUECKER)),
+            Some(DRUECKER_B)
+        );
+    }
+
+    #[test]
+    fn test_homing_rules() {
+        let engine = InterlockEngine::new(rules(THRESHOLDS));
+        let mut axes = snapshot_axes(30.0, 60.0);
+        axes[axes::DRUECKER].homed = false;
+
+        // Nothing moves before all axes are homed, the Schieber homes last
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Move(axes::MT)),
+            Some(HOMING_GATE)
+        );
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Home(axes::SCHIEBER)),
+            Some(HOMING_ORDER)
+        );
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Home(axes::DRUECKER)),
+            None
+        );
+
+        axes[axes::DRUECKER].homed = true;
+        assert_eq!(
+            blocked_by(&engine, &axes, Command::Home(axes::SCHIEBER)),
+            None
+        );
+    }
+}
@@ -1,17 +1,26 @@
+use crate::bbm_automatik_v2::api::AxisTarget;
 use crate::bbm_automatik_v2::api::{BbmAutomatikV2Events, LiveValuesEvent, StateEvent};
+use crate::interlock::{AxisSnapshot, Blocked, Command, InterlockEngine, MachineSnapshot};
+use crate::linear_interpolation::{InterpolationStatus, LinearInterpolation};
+use crate::linear_pto_axis::{LinearAxisMechanics, LinearPtoAxis};
 use crate::machine_identification::{MachineIdentification, MachineIdentificationUnique};
+use crate::production_stats::{ProductionTracker, Unit};
 use crate::{AsyncThreadMessage, BBM_AUTOMATIK_V2, Machine, MachineMessage, VENDOR_QITECH};
+use anyhow::{anyhow, bail};
 use control_core::socketio::namespace::NamespaceCacheingLogic;
+use control_core_derive::JsonSchema;
 use ethercat_hal::io::digital_input::DigitalInput;
 use ethercat_hal::io::digital_output::DigitalOutput;
-use ethercat_hal::io::pulse_train_output::PulseTrainOutput;
 use serde::{Deserialize, Serialize};
 use smol::channel::{Receiver, Sender};
 use std::time::Instant;
 
 pub mod act;
 pub mod api;
+pub mod checkpoint;
+pub mod interlocks;
 pub mod new;
+pub mod program;
 
 use crate::bbm_automatik_v2::api::BbmAutomatikV2Namespace;
 
@@ -63,7 +72,7 @@ pub mod soft_limits {
     pub const DRUECKER_MAX_MM: f32 = 107.0;
 
     /// Default max position per axis index. `None` means no limit. Used
-    /// to seed `axis_soft_limit_max_mm` on first machine init and as the
+    /// to seed the axes' soft-limit max on first machine init and as the
     /// fallback when a persisted calibration file pre-dates this field.
     pub fn default_max_position_mm(axis: usize) -> Option<f32> {
         match axis {
@@ -93,44 +102,10 @@ pub mod homing {
     pub const RETRACT_DISTANCE_MM: f32 = 2.0;
 }
 
-/// Speed presets for auto-sequence (mm/s)
-pub mod speed_presets {
-    #[derive(Debug, Clone, Copy)]
-    pub struct SpeedPreset {
-        pub mt_mm_s: f32,
-        pub schieber_mm_s: f32,
-        pub druecker_mm_s: f32,
-    }
-    pub const SLOW: SpeedPreset = SpeedPreset {
-        mt_mm_s: 30.0,
-        schieber_mm_s: 40.0,
-        druecker_mm_s: 40.0,
-    };
-    pub const MEDIUM: SpeedPreset = SpeedPreset {
-        mt_mm_s: 60.0,
-        schieber_mm_s: 80.0,
-        druecker_mm_s: 80.0,
-    };
-    pub const FAST: SpeedPreset = SpeedPreset {
-        mt_mm_s: 100.0,
-        schieber_mm_s: 150.0,
-        druecker_mm_s: 150.0,
-    };
-}
-
-/// Auto-sequence tuning constants. The per-axis target POSITIONS are no
-/// longer hardcoded here — they are taken from the user-teached
-/// calibration positions at sequence start (see [`AutoSequenceState`] and
-/// `start_auto_sequence`). Only the timing/structure constants and the
-/// fixed MT advance-per-cycle remain.
+/// Fallback positions for the anti-collision interlocks. The auto-sequence
+/// targets themselves come from the teached calibration positions via the
+/// sequence program (see [`program`]).
 pub mod auto_positions {
-    /// MT moves this far back per fill cycle (fixed, not teachable).
-    pub const MT_ADVANCE_PER_CYCLE: f32 = 10.0;
-    /// Schieber wobble amplitude around its start position (mm).
-    pub const SCHIEBER_WOBBLE: f32 = 1.0;
-    pub const CYCLES_PER_BLOCK: u32 = 19;
-    pub const BLOCKS_PER_SET: u32 = 3;
-
     /// Fallback Drücker start position (mm) used ONLY by the Schieber
     /// anti-collision interlock when the Drücker start has not been
     /// teached yet — so the safety rule is never silently disabled.
@@ -153,65 +128,102 @@ pub mod auto_positions {
 /// beyond start (into the work area) blocks the Schieber.
 pub const SCHIEBER_INTERLOCK_TOLERANCE_MM: f32 = 0.5;
 
-/// Cycle step within one fill cycle
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum AutoCycleStep {
-    /// Step 1a: Wobble schieber out (+wobble from start)
-    WobbleOut,
-    /// Step 1b: Wobble schieber back (-wobble from start)
-    WobbleBack,
-    /// Step 2: Schieber to target (filters fall into magazine)
-    SchieberToTarget,
-    /// Step 3: Drücker pushes hanging filters
-    DrueckerToTarget,
-    /// Step 4a: Drücker returns to start (and MT advances). The Schieber
-    /// MUST NOT move yet — it would shear off the extended Drücker tips.
-    DrueckerReturn,
-    /// Step 4b: Drücker is back at/below start, now the Schieber returns.
-    SchieberReturn,
-    /// Initial positioning: wait for MT + Drücker to reach start before the
-    /// first cycle (the Schieber is positioned by the opening wobble).
-    WaitParallelComplete,
-    /// Final step after all sets: MT drives to its teached Ziel position
-    /// for part removal (Entnahme); sequence finishes once MT arrives.
-    Entnahme,
+/// State file of the production counters, see [`crate::production_stats`]
+pub const PRODUCTION_FILENAME: &str = "bbm-automatik-v2-production.json";
+
+/// Downtime reasons of the production statistics besides the interlock rule
+/// ids (see [`interlocks`])
+pub mod downtime {
+    pub const PAUSE: &str = "pause";
+    pub const DRIVER_ALARM: &str = "driver_alarm";
+    pub const STEP_LOSS: &str = "step_loss";
+    /// A sequence recovered from its checkpoint after a restart
+    pub const POWER_LOSS: &str = "power_loss";
+}
+
+/// Upper bound of auto-sequence ops executed per act() cycle
+const MAX_AUTO_OPS_PER_CYCLE: usize = 64;
+
+/// A loop of the running auto-sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopFrame {
+    /// Index of the [`program::Op::Loop`] that opened the loop
+    pub start: usize,
+    pub count: u32,
+    /// Current iteration, 1-based
+    pub iteration: u32,
+    /// The implicit loop over the sets
+    pub sets: bool,
 }
 
 /// Top-level auto-sequence state
 #[derive(Debug, Clone)]
 pub struct AutoSequenceState {
-    pub speed_preset_name: String,
-    pub speed: speed_presets::SpeedPreset,
+    pub program: program::SequenceProgram,
+    pub speed_preset: program::SpeedPreset,
     pub total_sets: u32,
-    pub current_set: u32,
-    pub current_block: u32,
-    pub current_cycle: u32,
-    pub current_step: AutoCycleStep,
-    pub mt_current_run_pos: f32,
-
-    // Teached target positions (mm), snapshotted at sequence start so a
-    // running sequence is immune to mid-run calibration edits. Resolved
-    // from `teach_positions`; the sequence refuses to start if any is
-    // missing (see `start_auto_sequence`).
-    pub mt_start: f32,
-    pub mt_ziel: f32,
-    pub schieber_start: f32,
-    pub schieber_ziel: f32,
-    pub druecker_start: f32,
-    pub druecker_ziel: f32,
+    /// The compiled program, validated at start
+    pub ops: Vec<program::Op>,
+    /// Index of the next op to execute
+    pub pc: usize,
+    pub loops: Vec<LoopFrame>,
+    /// End of the `Wait` step in progress
+    pub wait_until: Option<Instant>,
+    /// Moves back to the checkpoint positions, executed before `ops` on resume
+    pub restore: Vec<program::Op>,
+
+    /// Pause requested, takes effect at the next resume point
+    pub pause_requested: bool,
+    /// Paused or interrupted, [`BbmAutomatikV2::resume_auto_sequence`]
+    /// continues from `checkpoint`
+    pub paused: bool,
+    /// The latest resume point, `None` until the first one is reached
+    pub checkpoint: Option<checkpoint::Checkpoint>,
+
+    // Teached positions, snapshotted at sequence start so a running
+    // sequence is immune to mid-run calibration edits.
+    pub teach: [AxisTeachPositions; 3],
 }
 
-/// Homing phases
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum HomingPhase {
-    /// Not homing
-    Idle,
-    /// Phase 1: Moving negative until sensor triggers
-    SearchingSensor,
-    /// Phase 2: Retracting 2mm away from sensor
-    Retracting,
-    /// Phase 3: Setting position to 0
-    SettingZero,
+impl AutoSequenceState {
+    /// Progress for the UI as 0-based (set, block, cycle): the set loop and
+    /// the two innermost program loops within it.
+    pub fn progress(&self) -> (u32, u32, u32) {
+        let Some(sets_index) = self.loops.iter().position(|f| f.sets) else {
+            // Before the first set or in the end steps
+            let past_sets = self
+                .ops
+                .iter()
+                .position(|op| matches!(op, program::Op::Loop { sets: true, .. }))
+                .is_some_and(|i| self.pc > i);
+            return (if past_sets { self.total_sets } else { 0 }, 0, 0);
+        };
+        let set = self.loops[sets_index].iteration - 1;
+        let inner = &self.loops[sets_index + 1..];
+        let cycle = inner.last().map_or(0, |f| f.iteration - 1);
+        let block = match inner.len() {
+            0 | 1 => 0,
+            n => inner[n - 2].iteration - 1,
+        };
+        (set, block, cycle)
+    }
+
+    /// The production unit an iteration of the open loop `index` is: the
+    /// sets loop, and within it the innermost and second innermost program
+    /// loops as cycles and blocks, like [`Self::progress`]
+    fn loop_unit(&self, index: usize) -> Option<Unit> {
+        if self.loops[index].sets {
+            return Some(Unit::Set);
+        }
+        let sets_index = self.loops.iter().position(|f| f.sets)?;
+        let level = index.checked_sub(sets_index)?;
+        let depth = program::set_loop_depth(&self.ops);
+        match depth.checked_sub(level) {
+            Some(0) => Some(Unit::Cycle),
+            Some(1) => Some(Unit::Block),
+            _ => None,
+        }
+    }
 }
 
 // ============ Teach / Calibration ============
@@ -219,7 +231,7 @@ pub enum HomingPhase {
 /// A user-saved (teach-in) position with a name. Used for the 2 freely
 /// nameable slots per axis. Start/Ziel are stored as bare `Option<f32>`
 /// because their name is fixed.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NamedTeachPosition {
     pub name: String,
     pub position_mm: f32,
@@ -227,7 +239,7 @@ pub struct NamedTeachPosition {
 
 /// All teach-in positions for one axis. Persisted to disk so calibration
 /// survives reboots.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct AxisTeachPositions {
     #[serde(default)]
     pub start_mm: Option<f32>,
@@ -241,7 +253,7 @@ pub struct AxisTeachPositions {
 
 /// Identifier for a teach slot (Start/Ziel are fixed; Custom1/Custom2 are
 /// freely nameable).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum TeachSlot {
     Start,
     Ziel,
@@ -250,12 +262,11 @@ pub enum TeachSlot {
 }
 
 /// Calibration file load/save. Calibration lives at
-/// `$STATE_DIRECTORY/bbm-automatik-v2-calibration.json` (systemd sets
-/// STATE_DIRECTORY for our service via `StateDirectory=qitech`). On dev
-/// machines without that env var we fall back to the OS temp dir so tests
-/// don't litter `/var/lib/qitech`.
+/// `bbm-automatik-v2-calibration.json` in the state directory, see
+/// [`crate::state_file`].
 pub mod calibration {
     use super::{AxisTeachPositions, soft_limits};
+    use crate::state_file;
     use serde::{Deserialize, Serialize};
     use std::path::PathBuf;
 
@@ -292,14 +303,7 @@ pub mod calibration {
     }
 
     fn path() -> PathBuf {
-        if let Ok(dir) = std::env::var("STATE_DIRECTORY") {
-            return PathBuf::from(dir).join(FILENAME);
-        }
-        if cfg!(target_os = "linux") {
-            PathBuf::from("/var/lib/qitech").join(FILENAME)
-        } else {
-            std::env::temp_dir().join(FILENAME)
-        }
+        state_file::path(FILENAME)
     }
 
     /// Load the persisted calibration. Returns the default-seeded
@@ -342,17 +346,6 @@ pub mod calibration {
         soft_limit_min_mm: &[Option<f32>; 3],
     ) {
         let p = path();
-        if let Some(parent) = p.parent() {
-            if let Err(e) = std::fs::create_dir_all(parent) {
-                tracing::error!(
-                    "[BbmAutomatikV2] Failed to create calibration dir {}: {}",
-                    parent.display(),
-                    e
-                );
-                return;
-            }
-        }
-
         let file = CalibrationFile {
             axes: axes.clone(),
             soft_limit_max_mm: *soft_limit_max_mm,
@@ -366,89 +359,30 @@ pub mod calibration {
             }
         };
 
-        let tmp = p.with_extension("json.tmp");
-        if let Err(e) = std::fs::write(&tmp, &json) {
-            tracing::error!(
-                "[BbmAutomatikV2] Failed to write {}: {}",
-                tmp.display(),
-                e
-            );
-            return;
-        }
-        if let Err(e) = std::fs::rename(&tmp, &p) {
-            tracing::error!(
-                "[BbmAutomatikV2] Failed to commit calibration to {}: {}",
-                p.display(),
-                e
-            );
-        }
+        state_file::write_atomic(&p, &json);
     }
 }
 
-/// Virtual zero offset for the EL2522 hardware position counter.
-///
-/// The EL2522 stores its position counter as a `u32` and Travel Distance
-/// Control compares `target_counter_value` vs the live counter UNSIGNED.
-/// To drive to logically negative positions we initialise the hardware
-/// counter to this offset; logical position is then
-/// `hw_counter - axis_position_offset[i]` (interpreted i32). With both
-/// values comfortably in the lower half of u32, the unsigned compare
-/// picks the physically correct direction every time and TDC brakes
-/// hardware-precisely in both directions.
-///
-/// This is the canonical pattern Beckhoff documents in the EL252x
-/// manual (§6.4.3 "Connection of the EL2522 in the NC", §6.5.1.2 "Travel
-/// Distance Control") — TwinCAT NC owns the signed logical position
-/// internally and writes only positive u32 values to the terminal.
-///
-/// 1_000_000 pulses at 20 pulses/mm = 50_000 mm headroom in either
-/// direction, vastly more than any physical axis on this machine.
-pub const POSITION_OFFSET_PULSES: u32 = 1_000_000;
-
 /// If a Travel Distance Control move ends more than this many pulses away
 /// from its target, the position integrity of the axis is no longer
-/// trustworthy: the axis is flagged (`axis_step_loss`), its `axis_homed`
-/// flag is revoked (which also disables soft limits, since they would be
-/// enforced against a wrong position) and a running auto-sequence is
-/// aborted. The user must re-home the axis to clear the flag.
+/// trustworthy: the axis is flagged ([`LinearPtoAxis::has_step_loss`]),
+/// its homed flag is revoked (which also disables soft limits, since they
+/// would be enforced against a wrong position) and a running auto-sequence
+/// is aborted. The user must re-home the axis to clear the flag.
 /// 20 pulses = 1 mm at 20 pulses/mm.
 pub const STEP_LOSS_INVALIDATE_PULSES: i32 = 20;
 
-/// Mechanical constants for the linear axes
-pub mod mechanics {
-    /// Motor pulses per revolution (default stepper setting)
-    pub const PULSES_PER_REV: u32 = 200;
-    /// Ball screw lead in mm per revolution
-    pub const LEAD_MM: f32 = 10.0;
-    /// Calculated pulses per mm
-    pub const PULSES_PER_MM: f32 = PULSES_PER_REV as f32 / LEAD_MM; // = 20.0
-
-    /// Convert mm/s to frequency (Hz) - for linear axes with ball screw
-    pub fn mm_per_s_to_hz(mm_per_s: f32) -> i32 {
-        (mm_per_s * PULSES_PER_MM) as i32
-    }
-
-    /// Convert frequency (Hz) to mm/s - for linear axes
-    pub fn hz_to_mm_per_s(hz: i32) -> f32 {
-        hz as f32 / PULSES_PER_MM
-    }
-
-    /// Convert position (pulses) to mm
-    pub fn pulses_to_mm(pulses: u32) -> f32 {
-        pulses as f32 / PULSES_PER_MM
-    }
-
-    /// Convert RPM to frequency (Hz) - for rotation axes (no ball screw)
-    /// RPM * 200 pulses/rev / 60 sec/min = Hz
-    pub fn rpm_to_hz(rpm: f32) -> i32 {
-        (rpm * PULSES_PER_REV as f32 / 60.0) as i32
-    }
+/// Jerk limit of the magazine transporter. Its moves follow an S-curve (see
+/// [`crate::pto_trajectory`]) so the loaded magazine doesn't jolt; the other
+/// axes keep Travel Distance Control.
+pub const MT_JERK_MM_S3: f32 = 1000.0;
 
-    /// Convert frequency (Hz) to RPM - for rotation axes
-    pub fn hz_to_rpm(hz: i32) -> f32 {
-        hz as f32 * 60.0 / PULSES_PER_REV as f32
-    }
-}
+/// Mechanical constants for the linear axes: 200 pulses/rev (default
+/// stepper setting) on a 10 mm ball screw = 20 pulses/mm
+pub const MECHANICS: LinearAxisMechanics = LinearAxisMechanics {
+    pulses_per_rev: 200,
+    lead_mm: 10.0,
+};
 
 /// Alarm polarity: CL75t without pull-ups = active HIGH (true = alarm, false/0V = no alarm)
 const ALARM_ACTIVE_LOW: bool = false;
@@ -468,30 +402,11 @@ pub struct BbmAutomatikV2 {
     pub digital_outputs: [DigitalOutput; 8],
     pub output_states: [bool; 8],
 
-    // Pulse Train Outputs (2x EL2522 = 3 channels used)
+    // Linear axes on 2x EL2522 (3 channels used)
     // Axis 0: MT (EL2522 #1, Ch1)
     // Axis 1: Schieber (EL2522 #1, Ch2)
     // Axis 2: Drücker (EL2522 #2, Ch1)
-    pub axes: [PulseTrainOutput; 3],
-    pub axis_speeds: [i32; 3],
-    pub axis_target_speeds: [i32; 3],
-    pub axis_accelerations: [f32; 3],
-    pub axis_target_positions: [i32; 3],
-    pub axis_position_mode: [bool; 3],
-    /// Ignore select_end_counter for N cycles after starting a new move
-    /// (hardware needs time to process go_counter and clear the old signal)
-    pub axis_position_ignore_cycles: [u8; 3],
-
-    // Hardware ramp control
-    pub sdo_write_u16: Option<crate::SdoWriteU16Fn>,
-    pub pto_subdevice_indices: [usize; 2],
-
-    // Homing state
-    pub axis_homing_phase: [HomingPhase; 3],
-    pub axis_homing_retract_target: [u32; 3],
-    /// true once homing Phase 3 (SettingZero) has completed for this axis.
-    /// Soft limits are only enforced after this flag is set.
-    pub axis_homed: [bool; 3],
+    pub axes: [LinearPtoAxis; 3],
 
     /// A Schieber-homing request that was deferred because the Drücker was not
     /// yet referenced+retracted. The Drücker MUST home before the Schieber
@@ -499,38 +414,27 @@ pub struct BbmAutomatikV2 {
     /// could collide). Auto-starts the Schieber home once the Drücker is safe.
     pub schieber_home_pending: bool,
 
-    /// true when a TDC move ended further than
-    /// [`STEP_LOSS_INVALIDATE_PULSES`] from its target. Cleared only by a
-    /// successful re-homing of the axis. While set, `axis_homed` is false.
-    pub axis_step_loss: [bool; 3],
-
-    /// Virtual zero offset per axis (see [`POSITION_OFFSET_PULSES`]).
-    /// Initialised to `POSITION_OFFSET_PULSES` at machine construction so
-    /// negative logical jogs work even before homing; reapplied on Homing
-    /// Phase 3 so logical 0 coincides with the physical home position.
-    pub axis_position_offset: [u32; 3],
-
-    // Driver alarm state (CL75t alarm pins)
-    /// true = alarm active (axis stopped), per axis
-    pub axis_alarm_active: [bool; 3],
-
     // Door interlock
     pub door_interlock_active: bool,
 
+    /// Safety rules, rebuilt when the teach positions change (see
+    /// [`interlocks`])
+    pub interlocks: InterlockEngine,
+
     // Auto-sequence state machine
     pub auto_sequence: Option<AutoSequenceState>,
 
     // Calibration / teach-in positions per axis (persisted to disk)
     pub teach_positions: [AxisTeachPositions; 3],
 
-    /// Per-axis upper soft limit in mm. `None` means no limit. Enforced
-    /// only after homing and outside of a homing run. Editable from the
-    /// calibration UI and persisted alongside `teach_positions`.
-    pub axis_soft_limit_max_mm: [Option<f32>; 3],
-    /// Per-axis lower soft limit in mm. `None` means no limit (default —
-    /// the user often wants to drive into negative territory). Enforced
-    /// the same way as the upper limit.
-    pub axis_soft_limit_min_mm: [Option<f32>; 3],
+    // Auto-sequence programs and speed presets (persisted to disk)
+    pub programs: program::ProgramFile,
+
+    // Production counters and cycle times (persisted to disk)
+    pub production: ProductionTracker,
+
+    // Coordinated multi-axis move (MoveInterpolated)
+    pub interpolation: Option<LinearInterpolation>,
 
     // Debug logging
     pub last_debug_log: Option<Instant>,
@@ -560,35 +464,53 @@ impl BbmAutomatikV2 {
 
     /// Get current state for UI
     pub fn get_state(&self) -> StateEvent {
-        // Convert homing phase to bool for UI (true = any homing phase active)
-        let homing_active = [
-            self.axis_homing_phase[0] != HomingPhase::Idle,
-            self.axis_homing_phase[1] != HomingPhase::Idle,
-            self.axis_homing_phase[2] != HomingPhase::Idle,
-        ];
-
+        let progress = self
+            .auto_sequence
+            .as_ref()
+            .map(|s| s.progress())
+            .unwrap_or((0, 0, 0));
         StateEvent {
             output_states: self.output_states,
-            axis_speeds: self.axis_speeds,
-            axis_target_speeds: self.axis_target_speeds,
-            axis_accelerations: self.axis_accelerations,
-            axis_target_positions: self.axis_target_positions,
-            axis_position_mode: self.axis_position_mode,
-            axis_homing_active: homing_active,
-            axis_homed: self.axis_homed,
-            axis_soft_limit_max: self.axis_soft_limit_max_mm,
-            axis_soft_limit_min: self.axis_soft_limit_min_mm,
-            axis_alarm_active: self.axis_alarm_active,
-            axis_step_loss: self.axis_step_loss,
+            axis_speeds: self.axes.each_ref().map(|a| a.speed_hz()),
+            axis_target_speeds: self.axes.each_ref().map(|a| a.target_speed_hz()),
+            axis_accelerations: self.axes.each_ref().map(|a| a.acceleration_mm_s2()),
+            axis_target_positions: self.axes.each_ref().map(|a| a.target_position_pulses()),
+            axis_position_mode: self.axes.each_ref().map(|a| a.is_position_mode()),
+            // true = any homing phase active
+            axis_homing_active: self.axes.each_ref().map(|a| a.is_homing()),
+            axis_homed: self.axes.each_ref().map(|a| a.is_homed()),
+            axis_soft_limit_max: self.soft_limits_max_mm(),
+            axis_soft_limit_min: self.soft_limits_min_mm(),
+            axis_alarm_active: self.axes.each_ref().map(|a| a.is_alarm_active()),
+            axis_step_loss: self.axes.each_ref().map(|a| a.has_step_loss()),
             door_interlock_active: self.door_interlock_active,
-            schieber_interlock_active: self.schieber_interlock_active(),
-            druecker_interlock_active: self.druecker_interlock_active(),
+            schieber_interlock_active: self.is_interlock_blocking(interlocks::SCHIEBER_A),
+            druecker_interlock_active: self.is_interlock_blocking(interlocks::DRUECKER_B),
+            active_interlocks: self
+                .active_interlocks()
+                .into_iter()
+                .map(String::from)
+                .collect(),
             auto_running: self.auto_sequence.is_some(),
-            auto_current_set: self.auto_sequence.as_ref().map(|s| s.current_set).unwrap_or(0),
-            auto_current_block: self.auto_sequence.as_ref().map(|s| s.current_block).unwrap_or(0),
-            auto_current_cycle: self.auto_sequence.as_ref().map(|s| s.current_cycle).unwrap_or(0),
-            auto_total_sets: self.auto_sequence.as_ref().map(|s| s.total_sets).unwrap_or(0),
+            auto_current_set: progress.0,
+            auto_current_block: progress.1,
+            auto_current_cycle: progress.2,
+            auto_total_sets: self
+                .auto_sequence
+                .as_ref()
+                .map(|s| s.total_sets)
+                .unwrap_or(0),
+            auto_paused: self.auto_sequence.as_ref().is_some_and(|s| s.paused),
+            auto_pause_requested: self
+                .auto_sequence
+                .as_ref()
+                .is_some_and(|s| s.pause_requested),
+            auto_program: self.auto_sequence.as_ref().map(|s| s.program.name.clone()),
+            interpolating: self.interpolation.is_some(),
             teach_positions: self.teach_positions.clone(),
+            sequence_programs: self.programs.programs.clone(),
+            speed_presets: self.programs.speed_presets.clone(),
+            production: self.production.stats().clone(),
         }
     }
 
@@ -612,25 +534,39 @@ impl BbmAutomatikV2 {
         }
     }
 
-    /// Current axis position in logical pulses (signed). With the virtual
-    /// zero offset applied this returns the actual logical position; if
-    /// the offset hasn't been applied yet (offset == 0) the raw counter
-    /// is returned reinterpreted as i32.
+    /// Current axis position in logical pulses (signed), see
+    /// [`LinearPtoAxis::position_pulses`].
     pub fn current_logical_pulses(&self, axis: usize) -> i32 {
-        self.axes[axis]
-            .get_position()
-            .wrapping_sub(self.axis_position_offset[axis]) as i32
+        self.axes[axis].position_pulses()
     }
 
     /// Current axis position in mm (logical, signed).
     pub fn current_logical_mm(&self, axis: usize) -> f32 {
-        self.current_logical_pulses(axis) as f32 / mechanics::PULSES_PER_MM
+        self.axes[axis].position_mm()
+    }
+
+    fn soft_limits_max_mm(&self) -> [Option<f32>; 3] {
+        self.axes.each_ref().map(|a| a.soft_limit_max_mm())
+    }
+
+    fn soft_limits_min_mm(&self) -> [Option<f32>; 3] {
+        self.axes.each_ref().map(|a| a.soft_limit_min_mm())
+    }
+
+    /// Persist teach positions and soft limits
+    fn save_calibration(&self) {
+        calibration::save(
+            &self.teach_positions,
+            &self.soft_limits_max_mm(),
+            &self.soft_limits_min_mm(),
+        );
     }
 
     /// Emit state event to UI
     pub fn emit_state(&mut self) {
         let event = self.get_state().build();
-        self.namespace.emit(BbmAutomatikV2Events::State(event));
+        self.namespace
+            .emit(BbmAutomatikV2Events::State(Box::new(event)));
     }
 
     /// Emit live values to UI
@@ -639,8 +575,18 @@ impl BbmAutomatikV2 {
         self.namespace.emit(BbmAutomatikV2Events::LiveValues(event));
     }
 
-    /// Set a digital output
-    pub fn set_output(&mut self, index: usize, on: bool) {
+    /// Set a digital output. Switching off is always allowed, switching on
+    /// has to pass the interlock rules of the output.
+    pub fn set_output(&mut self, index: usize, on: bool) -> anyhow::Result<()> {
+        if on {
+            self.check_switch_on_allowed(index)?;
+        }
+        self.write_output(index, on);
+        Ok(())
+    }
+
+    /// Writes an output without the interlock check, for switching off
+    fn write_output(&mut self, index: usize, on: bool) {
         if index < self.output_states.len() {
             self.output_states[index] = on;
             self.digital_outputs[index].set(on);
@@ -648,51 +594,52 @@ impl BbmAutomatikV2 {
         }
     }
 
-    /// Set all digital outputs
-    pub fn set_all_outputs(&mut self, on: bool) {
+    /// Set all digital outputs. Switching on is rejected as a whole if any
+    /// output is blocked by an interlock rule.
+    pub fn set_all_outputs(&mut self, on: bool) -> anyhow::Result<()> {
+        if on {
+            for i in 0..self.output_states.len() {
+                self.check_switch_on_allowed(i)?;
+            }
+        }
         for i in 0..self.output_states.len() {
             self.output_states[i] = on;
             self.digital_outputs[i].set(on);
         }
         self.emit_state();
+        Ok(())
     }
 
-    /// Set axis speed (frequency value for PTO)
-    pub fn set_axis_speed(&mut self, index: usize, speed: i32) {
-        // Global homing gate: no movement until all axes are homed.
-        if speed != 0 && !self.all_axes_homed() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Frequenz Achse {} blockiert: nicht alle Achsen referenziert",
-                index
-            );
-            return;
+    /// Rejects switching on output `index` if an interlock rule blocks it.
+    /// The state is emitted anyway so the UI resets its toggle.
+    fn check_switch_on_allowed(&mut self, index: usize) -> anyhow::Result<()> {
+        if let Err(e) = self.check_interlock(Command::SwitchOn(index)) {
+            self.emit_state();
+            bail!("Ausgang {} blockiert: {}", index, e);
+        }
+        Ok(())
+    }
+
+    /// Set axis speed (frequency value for PTO). A zero command (stop) is
+    /// always allowed, any other speed has to pass
+    /// [`Self::check_move_allowed`].
+    pub fn set_axis_speed(&mut self, index: usize, speed: i32) -> anyhow::Result<()> {
+        if speed != 0 {
+            self.check_move_allowed(index)?;
         }
-        if index < self.axis_speeds.len() {
-            self.axis_speeds[index] = speed;
+        if index < self.axes.len() {
             self.axes[index].set_frequency(speed);
             self.emit_state();
         }
+        Ok(())
     }
 
     /// Stop all axes - hardware immediate stop
     pub fn stop_all_axes(&mut self) {
-        for i in 0..self.axis_speeds.len() {
-            // Cancel homing if active
-            if self.axis_homing_phase[i] != HomingPhase::Idle {
-                self.axis_homing_phase[i] = HomingPhase::Idle;
-                tracing::info!("[BbmAutomatikV2] Axis {} homing cancelled by stop_all", i);
-            }
-            self.axis_speeds[i] = 0;
-            self.axis_target_speeds[i] = 0;
-            self.axis_position_mode[i] = false;
-
-            // Hardware: disble_ramp breaks Travel Distance Control
-            let mut output = self.axes[i].get_output();
-            output.disble_ramp = true;
-            output.go_counter = false;
-            output.frequency_value = 0;
-            self.axes[i].set_output(output);
+        for axis in self.axes.iter_mut() {
+            axis.stop();
         }
+        self.interpolation = None;
         // A full stop also cancels a queued Schieber home.
         self.schieber_home_pending = false;
         self.emit_state();
@@ -700,23 +647,8 @@ impl BbmAutomatikV2 {
 
     /// Stop single axis - hardware immediate stop (also cancels homing if active)
     pub fn stop_axis(&mut self, index: usize) {
-        if index < self.axis_speeds.len() {
-            // Cancel homing if active
-            if self.axis_homing_phase[index] != HomingPhase::Idle {
-                self.axis_homing_phase[index] = HomingPhase::Idle;
-                tracing::info!("[BbmAutomatikV2] Axis {} homing cancelled by stop", index);
-            }
-            self.axis_speeds[index] = 0;
-            self.axis_target_speeds[index] = 0;
-            self.axis_position_mode[index] = false;
-
-            // Hardware: disble_ramp breaks Travel Distance Control
-            let mut output = self.axes[index].get_output();
-            output.disble_ramp = true;
-            output.go_counter = false;
-            output.frequency_value = 0;
-            self.axes[index].set_output(output);
-
+        if index < self.axes.len() {
+            self.axes[index].stop();
             self.emit_state();
         }
     }
@@ -726,388 +658,157 @@ impl BbmAutomatikV2 {
     /// Set target axis speed in mm/s (hardware ramp handles transition)
     /// Positive = forward, Negative = backward
     /// For linear axes with ball screw
-    pub fn set_axis_speed_mm_s(&mut self, index: usize, mm_per_s: f32) {
-        // Global homing gate: no movement until all axes are homed. A zero
-        // command (stop) is always allowed.
-        if mm_per_s != 0.0 && !self.all_axes_homed() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Speed Achse {} blockiert: nicht alle Achsen referenziert",
-                index
-            );
-            return;
-        }
-        // Schieber anti-collision interlock: block a non-zero Schieber speed
-        // command while the Drücker is below its start. A zero command
-        // (stop) is always allowed.
-        if index == axes::SCHIEBER && mm_per_s != 0.0 && self.schieber_interlock_active() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Schieber-Speed blockiert (Interlock A): Drücker über Start/ausgefahren ({:.1} mm)",
-                self.current_logical_mm(axes::DRUECKER)
-            );
-            return;
-        }
-        // Drücker interlock (B): block Drücker speed while Schieber not at start.
-        if index == axes::DRUECKER && mm_per_s != 0.0 && self.druecker_interlock_active() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Drücker-Speed blockiert (Interlock B): Schieber nicht auf Start ({:.1} mm)",
-                self.current_logical_mm(axes::SCHIEBER)
-            );
-            return;
+    pub fn set_axis_speed_mm_s(&mut self, index: usize, mm_per_s: f32) -> anyhow::Result<()> {
+        if mm_per_s != 0.0 {
+            self.check_move_allowed(index)?;
         }
-        if index < self.axis_target_speeds.len() {
-            let hz = mechanics::mm_per_s_to_hz(mm_per_s);
-            self.axis_target_speeds[index] = hz;
+        if index < self.axes.len() {
             // Hardware ramp accelerates/brakes automatically to target
+            self.axes[index].set_speed_mm_s(mm_per_s);
             self.emit_state();
         }
+        Ok(())
     }
 
     /// Set target axis speed in RPM (hardware ramp handles transition)
     /// For rotation axes without ball screw
-    pub fn set_axis_speed_rpm(&mut self, index: usize, rpm: f32) {
-        // Global homing gate: no movement until all axes are homed.
-        if rpm != 0.0 && !self.all_axes_homed() {
-            tracing::warn!(
-                "[BbmAutomatikV2] RPM Achse {} blockiert: nicht alle Achsen referenziert",
-                index
-            );
-            return;
+    pub fn set_axis_speed_rpm(&mut self, index: usize, rpm: f32) -> anyhow::Result<()> {
+        if rpm != 0.0 {
+            self.check_move_allowed(index)?;
         }
-        if index < self.axis_target_speeds.len() {
-            let hz = mechanics::rpm_to_hz(rpm);
-            self.axis_target_speeds[index] = hz;
+        if index < self.axes.len() {
+            self.axes[index].set_speed_rpm(rpm);
             self.emit_state();
         }
+        Ok(())
     }
 
     /// Set axis acceleration in mm/s² - writes ramp time constants via SDO
     pub fn set_axis_acceleration(&mut self, index: usize, accel_mm_s2: f32) {
-        if index < self.axis_accelerations.len() {
-            let clamped = accel_mm_s2.clamp(4.0, 500.0);
-            self.axis_accelerations[index] = clamped;
-
-            // Calculate ramp time constants for hardware.
-            // base_freq must match base_frequency_1 in new.rs (5000 Hz).
-            let accel_hz_s = clamped * mechanics::PULSES_PER_MM;
-            let base_freq = 5000.0_f32;
-            let rising_ms = ((base_freq / accel_hz_s) * 1000.0) as u16;
-            // Beckhoff EL252x manual p.139: the falling ramp must be ~10%
-            // steeper than the rising ramp, otherwise Travel Distance
-            // Control reaches the target at full speed instead of via the
-            // slowing-down frequency (imprecise stop / overshoot).
-            let falling_ms = ((rising_ms as f32) * 0.9) as u16;
-
-            // SDO-Write to the correct EL2522
-            // NOTE: SdoWriteU16Fn returns () - errors are handled inside the callback.
-            // If SDO write fails, the hardware keeps the old ramp values.
-            if let Some(sdo_write) = &self.sdo_write_u16 {
-                // Which EL2522? Axis 0,1 = EL2522#1, Axis 2 = EL2522#2
-                let el2522_idx = if index < 2 { 0 } else { 1 };
-                let subdevice_index = self.pto_subdevice_indices[el2522_idx];
-
-                // PTO Base Index: Channel 1 = 0x8000, Channel 2 = 0x8010
-                let pto_base = if index % 2 == 0 { 0x8000u16 } else { 0x8010u16 };
-
-                tracing::debug!(
-                    "[BbmAutomatikV2] SDO write axis {}: ramp rising={}ms falling={}ms (accel={:.0} mm/s²)",
-                    index,
-                    rising_ms,
-                    falling_ms,
-                    clamped
-                );
-
-                // Rising ramp (0x14)
-                sdo_write(subdevice_index, pto_base, 0x14, rising_ms);
-                // Falling ramp (0x15)
-                sdo_write(subdevice_index, pto_base, 0x15, falling_ms);
-            } else {
-                tracing::warn!(
-                    "[BbmAutomatikV2] SDO write not available - acceleration change will not take effect"
-                );
-            }
+        if index < self.axes.len() {
+            self.axes[index].set_acceleration(accel_mm_s2);
             self.emit_state();
         }
     }
 
-    /// Move to a logical target position in mm using hardware Travel
-    /// Distance Control. The hardware ramps up, brakes, and stops
-    /// hardware-precisely at the target in BOTH directions thanks to the
-    /// virtual zero offset (see [`POSITION_OFFSET_PULSES`]): logical
-    /// targets are translated to `logical + offset` in u32 hardware
-    /// space, so the EL2522's unsigned compare always picks the
-    /// physically correct direction.
-    pub fn move_to_position_mm(&mut self, index: usize, position_mm: f32, speed_mm_s: f32) {
+    /// Rejects a move of axis `index` before it starts: unknown axis or a
+    /// blocking interlock rule (door, homing gate, anti-collision).
+    pub fn check_move_allowed(&self, index: usize) -> anyhow::Result<()> {
         if index >= self.axes.len() {
-            return;
-        }
-
-        // Global homing gate: block all movement until every axis is homed.
-        if !self.all_axes_homed() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Bewegung Achse {} blockiert: nicht alle Achsen referenziert",
-                index
-            );
-            return;
-        }
-
-        // Schieber anti-collision interlock (A): refuse any Schieber move while
-        // the Drücker is extended above its start (it would shear the Drücker
-        // tips). Applies to manual AND auto; the auto sequence is choreographed
-        // so this never blocks a legitimate cycle move.
-        if index == axes::SCHIEBER && self.schieber_interlock_active() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Schieber-Move blockiert (Interlock A): Drücker über Start/ausgefahren ({:.1} mm)",
-                self.current_logical_mm(axes::DRUECKER)
-            );
-            return;
-        }
-
-        // Drücker anti-collision interlock (B): refuse any Drücker move while
-        // the Schieber is mid-travel (neither at start nor at ziel — it would
-        // crash into the Schieber). Applies to manual AND auto.
-        if index == axes::DRUECKER && self.druecker_interlock_active() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Drücker-Move blockiert (Interlock B): Schieber mittendrin, nicht auf Start/Ziel ({:.1} mm)",
-                self.current_logical_mm(axes::SCHIEBER)
-            );
-            return;
-        }
-
-        // Soft limits (both bounds) are only enforced after homing and
-        // outside of an active homing run. `None` for either bound means
-        // "no limit" — the user is free to drive past it.
-        let enforce_limits =
-            self.axis_homed[index] && self.axis_homing_phase[index] == HomingPhase::Idle;
-        let clamped_mm = if enforce_limits {
-            let mut v = position_mm;
-            if let Some(max) = self.axis_soft_limit_max_mm[index] {
-                v = v.min(max);
-            }
-            if let Some(min) = self.axis_soft_limit_min_mm[index] {
-                v = v.max(min);
-            }
-            v
-        } else {
-            position_mm
-        };
-
-        if (clamped_mm - position_mm).abs() > 0.1 {
-            tracing::warn!(
-                "[BbmAutomatikV2] Axis {} position clamped: {:.1} mm -> {:.1} mm (soft max)",
-                index,
-                position_mm,
-                clamped_mm
-            );
+            bail!("Unknown axis {}", index);
         }
+        self.check_interlock(Command::Move(index))
+            .map_err(|e| anyhow!("Bewegung {} blockiert: {}", self.axes[index].name(), e))
+    }
 
-        // Round AFTER scaling to pulses so we keep sub-mm precision
-        // (43.5 mm * 20 pulses/mm = exactly 870 pulses). Rounding mm
-        // first would quantize the target to whole millimetres.
-        let target_logical_pulses =
-            (clamped_mm * mechanics::PULSES_PER_MM).round() as i32;
-        let current_logical_pulses = self.current_logical_pulses(index);
-        // Hardware target = logical + offset, computed in i64 to avoid any
-        // i32/u32 ambiguity, then narrowed once we know it fits.
-        let target_hw_u32 = (target_logical_pulses as i64
-            + self.axis_position_offset[index] as i64) as u32;
-        let speed_hz = mechanics::mm_per_s_to_hz(speed_mm_s.abs());
-
-        // Direction in logical space (just for UI sign on axis_speeds).
-        let direction = if target_logical_pulses >= current_logical_pulses {
-            1
-        } else {
-            -1
-        };
-
-        // Activate position mode and remember the logical target so step
-        // loss detection can later compare apples to apples.
-        self.axis_target_positions[index] = target_logical_pulses;
-        self.axis_position_mode[index] = true;
-        // Ignore select_end_counter for 5 cycles (~3.5ms at 700µs cycle)
-        // so hardware has time to process the new go_counter and clear
-        // the stale "target reached" signal from the previous move.
-        self.axis_position_ignore_cycles[index] = 5;
-
-        // Hardware output: go_counter + target position + speed magnitude.
-        // In Travel Distance Control mode the EL2522 picks direction from
-        // the unsigned compare target_counter_value vs counter. With the
-        // virtual offset applied, both values stay comfortably positive
-        // and direction is always physically correct. frequency_value is
-        // magnitude only — the sign is NOT used for direction in TDC.
-        let mut output = self.axes[index].get_output();
-        output.go_counter = true;
-        output.disble_ramp = false;
-        output.frequency_value = speed_hz;
-        output.target_counter_value = target_hw_u32;
-        self.axes[index].set_output(output);
-
-        // Sync software state (signed speed for UI direction indicator).
-        self.axis_target_speeds[index] = speed_hz * direction;
-        self.axis_speeds[index] = speed_hz * direction;
-
+    /// Move to a logical target position in mm using hardware Travel
+    /// Distance Control (S-curve on the transporter), see
+    /// [`LinearPtoAxis::move_to_mm`]. Soft limits
+    /// (both bounds) clamp the target once the axis is homed.
+    pub fn move_to_position_mm(
+        &mut self,
+        index: usize,
+        position_mm: f32,
+        speed_mm_s: f32,
+    ) -> anyhow::Result<()> {
+        self.check_move_allowed(index)?;
+        self.axes[index].move_to_mm(position_mm, speed_mm_s);
         self.emit_state();
-        tracing::info!(
-            "[BbmAutomatikV2] Axis {} moving to {:.3} mm ({} logical pulses, hw target {}) at {:.1} mm/s",
-            index,
-            clamped_mm,
-            target_logical_pulses,
-            target_hw_u32,
-            speed_mm_s
-        );
+        Ok(())
     }
 
     /// Relative jog by `delta_mm`. Now a thin wrapper around
     /// [`Self::move_to_position_mm`] — with the virtual zero offset in
     /// place, TDC handles both directions hardware-precisely. Used by
     /// the +/- JOG buttons.
-    pub fn jog_relative(&mut self, index: usize, delta_mm: f32, speed_mm_s: f32) {
-        if index >= self.axes.len() {
-            return;
-        }
+    pub fn jog_relative(
+        &mut self,
+        index: usize,
+        delta_mm: f32,
+        speed_mm_s: f32,
+    ) -> anyhow::Result<()> {
+        self.check_move_allowed(index)?;
         let target_mm = self.current_logical_mm(index) + delta_mm;
-        self.move_to_position_mm(index, target_mm, speed_mm_s);
-    }
-
-    /// Hardware ramp monitor: watches the EL2522 status flags and pushes
-    /// frequency setpoints to the hardware. Two modes:
-    ///
-    /// - **Position mode** (TDC): wait for `select_end_counter` to flag
-    ///   "target reached", then clear `go_counter` and log step-loss if
-    ///   actual deviated from target.
-    /// - **Speed mode**: used by homing (and by anything else writing
-    ///   `axis_target_speeds` directly). Enforces the upper soft limit
-    ///   when homed, then forwards the target frequency to hardware.
-    pub fn update_hardware_monitor(&mut self) -> bool {
-        let mut changed = false;
-        for i in 0..self.axis_speeds.len() {
-            let input = self.axes[i].get_input();
-
-            // Auto-clear a pending set_counter once hardware confirms.
-            // Both machine init (virtual-zero offset write) and homing
-            // Phase 3 raise set_counter; this single path turns it back
-            // off so the EL2522 doesn't keep clamping the counter to the
-            // set value forever.
-            if input.set_counter_done {
-                let output = self.axes[i].get_output();
-                if output.set_counter {
-                    self.axes[i].clear_set_counter();
-                }
-            }
-
-            // ====== POSITION MODE: Target detection ======
-            if self.axis_position_mode[i] {
-                // Grace period after starting a new move: hardware needs time to
-                // process go_counter and clear the stale select_end_counter signal
-                if self.axis_position_ignore_cycles[i] > 0 {
-                    self.axis_position_ignore_cycles[i] -= 1;
-                } else if input.select_end_counter {
-                    self.axis_speeds[i] = 0;
-                    self.axis_target_speeds[i] = 0;
-                    self.axis_position_mode[i] = false;
-
-                    // Reset go_counter
-                    let mut output = self.axes[i].get_output();
-                    output.go_counter = false;
-                    output.frequency_value = 0;
-                    self.axes[i].set_output(output);
-
-                    changed = true;
-                    let actual_pos = self.current_logical_pulses(i);
-                    let target_pos = self.axis_target_positions[i];
-                    let deviation = (actual_pos - target_pos).abs();
-                    if deviation > STEP_LOSS_INVALIDATE_PULSES {
-                        // Position integrity lost: revoke homing (forces
-                        // re-referencing, also disables the now-unreliable
-                        // soft limits) and abort a running auto-sequence.
-                        tracing::error!(
-                            "[Axis {}] STEP LOSS: target={} actual={} deviation={} pulses ({:.2} mm) - homing revoked, re-reference required",
-                            i,
-                            target_pos,
-                            actual_pos,
-                            deviation,
-                            deviation as f32 / mechanics::PULSES_PER_MM
-                        );
-                        self.axis_step_loss[i] = true;
-                        self.axis_homed[i] = false;
-                        if self.auto_sequence.is_some() {
-                            tracing::error!(
-                                "[BbmAutomatikV2] Auto sequence aborted due to step loss on axis {}",
-                                i
-                            );
-                            self.stop_auto_sequence();
-                        }
-                    } else if deviation > 2 {
-                        tracing::warn!(
-                            "[Axis {}] STEP LOSS DETECTED: target={} actual={} deviation={} pulses ({:.2} mm)",
-                            i,
-                            target_pos,
-                            actual_pos,
-                            deviation,
-                            deviation as f32 / mechanics::PULSES_PER_MM
-                        );
-                    } else {
-                        tracing::info!(
-                            "[Axis {}] Target reached: {} pulses (actual: {}, deviation: {})",
-                            i,
-                            target_pos,
-                            actual_pos,
-                            deviation
-                        );
-                    }
-                }
-            }
+        self.move_to_position_mm(index, target_mm, speed_mm_s)
+    }
 
-            // ====== SPEED MODE: Send target frequency to hardware ======
-            if !self.axis_position_mode[i] {
-                // Soft limits only apply once the axis is homed AND not
-                // currently homing. Each bound is independently optional.
-                let enforce_limits =
-                    self.axis_homed[i] && self.axis_homing_phase[i] == HomingPhase::Idle;
-                if enforce_limits {
-                    let current_mm = self.current_logical_mm(i);
-                    if let Some(max_mm) = self.axis_soft_limit_max_mm[i] {
-                        if current_mm >= max_mm
-                            && self.axis_target_speeds[i] > 0
-                        {
-                            self.axis_target_speeds[i] = 0;
-                            tracing::warn!(
-                                "[BbmAutomatikV2] Axis {} soft max reached at {:.1} mm - stopping",
-                                i,
-                                current_mm
-                            );
-                        }
-                    }
-                    if let Some(min_mm) = self.axis_soft_limit_min_mm[i] {
-                        if current_mm <= min_mm
-                            && self.axis_target_speeds[i] < 0
-                        {
-                            self.axis_target_speeds[i] = 0;
-                            tracing::warn!(
-                                "[BbmAutomatikV2] Axis {} soft min reached at {:.1} mm - stopping",
-                                i,
-                                current_mm
-                            );
-                        }
-                    }
-                }
+    /// Starts a coordinated straight-line move of 2-3 axes, see
+    /// [`LinearInterpolation`]. Every axis must pass its interlock rules and
+    /// the auto sequence must be paused or stopped.
+    pub fn move_interpolated(
+        &mut self,
+        targets: &[AxisTarget],
+        speed_mm_s: f32,
+    ) -> anyhow::Result<()> {
+        if self.interpolation.is_some() {
+            bail!("Interpolierte Bewegung läuft bereits");
+        }
+        if self.auto_sequence.as_ref().is_some_and(|s| !s.paused) {
+            bail!("Interpolierte Bewegung blockiert: Automatik läuft");
+        }
+        for target in targets {
+            self.check_move_allowed(target.index)?;
+        }
+        let targets: Vec<(usize, f32)> = targets.iter().map(|t| (t.index, t.position_mm)).collect();
+        self.interpolation = Some(LinearInterpolation::start(
+            &mut self.axes,
+            &targets,
+            speed_mm_s,
+            MT_JERK_MM_S3,
+            Instant::now(),
+        )?);
+        self.emit_state();
+        Ok(())
+    }
 
-                let target = self.axis_target_speeds[i];
-                if self.axis_speeds[i] != target {
-                    // Send new target speed to hardware
-                    // Hardware ramp accelerates/brakes automatically
-                    let mut output = self.axes[i].get_output();
-                    output.disble_ramp = false;
-                    output.go_counter = false;
-                    output.frequency_value = target;
-                    self.axes[i].set_output(output);
-                    self.axis_speeds[i] = target;
-                    changed = true;
+    /// Streams the running interpolated move, called after
+    /// [`Self::update_hardware_monitor`]. An axis that stopped on its own
+    /// (alarm, interlock) stops the others as well.
+    pub fn update_interpolation(&mut self, now: Instant) -> bool {
+        let Some(interpolation) = self.interpolation.as_mut() else {
+            return false;
+        };
+        match interpolation.update(&mut self.axes, now) {
+            InterpolationStatus::Running => false,
+            InterpolationStatus::Finished { step_loss } => {
+                self.interpolation = None;
+                if step_loss {
+                    // Flagged on the axes and sent with the state, the
+                    // positions have to be referenced again
+                    tracing::error!(
+                        "[BbmAutomatikV2] Schrittverlust in interpolierter Bewegung: {:?}",
+                        self.axes
+                            .iter()
+                            .filter(|a| a.has_step_loss())
+                            .map(|a| a.name())
+                            .collect::<Vec<_>>()
+                    );
                 }
+                true
             }
+            InterpolationStatus::Aborted(reason) => {
+                tracing::warn!(
+                    "[BbmAutomatikV2] Interpolierte Bewegung abgebrochen: {}",
+                    reason
+                );
+                self.interpolation = None;
+                true
+            }
+        }
+    }
 
-            // Status tracking for UI
-            if input.ramp_active {
-                changed = true;
+    /// Hardware ramp monitor, see [`LinearPtoAxis::update`]. Aborts a
+    /// running auto-sequence when an axis loses its position integrity.
+    pub fn update_hardware_monitor(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        for i in 0..self.axes.len() {
+            let update = self.axes[i].update(now);
+            changed |= update.changed;
+            if update.step_loss {
+                self.interrupt_auto_sequence(
+                    downtime::STEP_LOSS,
+                    &format!("step loss on axis {}", i),
+                );
             }
         }
         changed
@@ -1116,23 +817,23 @@ impl BbmAutomatikV2 {
     // ============ Convenience Functions ============
 
     /// Set Bürstenmotor on/off
-    pub fn set_buerstenmotor(&mut self, on: bool) {
-        self.set_output(outputs::BUERSTENMOTOR, on);
+    pub fn set_buerstenmotor(&mut self, on: bool) -> anyhow::Result<()> {
+        self.set_output(outputs::BUERSTENMOTOR, on)
     }
 
     /// Set Rüttelmotor on/off
-    pub fn set_ruettelmotor(&mut self, on: bool) {
-        self.set_output(outputs::RUETTELMOTOR, on);
+    pub fn set_ruettelmotor(&mut self, on: bool) -> anyhow::Result<()> {
+        self.set_output(outputs::RUETTELMOTOR, on)
     }
 
     /// Set Pneumatik valve on/off
-    pub fn set_pneumatik(&mut self, on: bool) {
-        self.set_output(outputs::PNEUMATIK, on);
+    pub fn set_pneumatik(&mut self, on: bool) -> anyhow::Result<()> {
+        self.set_output(outputs::PNEUMATIK, on)
     }
 
     /// Set Schaltschrank-Lüfter on/off
-    pub fn set_luefter(&mut self, on: bool) {
-        self.set_output(outputs::LUEFTER, on);
+    pub fn set_luefter(&mut self, on: bool) -> anyhow::Result<()> {
+        self.set_output(outputs::LUEFTER, on)
     }
 
     /// Set Ampel state
@@ -1156,109 +857,51 @@ impl BbmAutomatikV2 {
     /// Check driver alarm pins and emergency-stop all axes if triggered
     /// Arduino equivalent: checkDriverAlarms() in BBMx22_Automatik_Code.ino v3.2
     pub fn check_driver_alarms(&mut self) -> bool {
-        let alarm_inputs = [
-            (axes::MT, inputs::ALARM_MT),
-            (axes::SCHIEBER, inputs::ALARM_SCHIEBER),
-            (axes::DRUECKER, inputs::ALARM_DRUECKER),
-        ];
-
         let mut any_new_alarm = false;
-
-        for &(axis, input_idx) in &alarm_inputs {
-            let raw = self.digital_inputs[input_idx]
-                .get_value()
-                .unwrap_or(!ALARM_ACTIVE_LOW);
-            let is_alarm = if ALARM_ACTIVE_LOW { !raw } else { raw };
-
-            if is_alarm && !self.axis_alarm_active[axis] {
-                tracing::error!(
-                    "[BbmAutomatikV2] !!! ALARM: Axis {} driver alarm triggered !!!",
-                    axis
-                );
-                self.axis_alarm_active[axis] = true;
-                any_new_alarm = true;
-            }
+        for axis in self.axes.iter_mut() {
+            any_new_alarm |= axis.check_alarm();
         }
 
         if any_new_alarm {
             self.stop_all_axes();
+            self.interrupt_auto_sequence(downtime::DRIVER_ALARM, "driver alarm");
         }
         any_new_alarm
     }
 
     /// Reset all driver alarm states (only if physical alarm pins are inactive)
     pub fn reset_alarms(&mut self) {
-        let had_alarm = self.axis_alarm_active.iter().any(|&a| a);
+        let had_alarm = self.axes.iter().any(|a| a.is_alarm_active());
         if !had_alarm {
             return;
         }
 
         // Check if any physical alarm is still active before resetting
-        let alarm_inputs = [
-            (axes::MT, inputs::ALARM_MT),
-            (axes::SCHIEBER, inputs::ALARM_SCHIEBER),
-            (axes::DRUECKER, inputs::ALARM_DRUECKER),
-        ];
-
-        for &(axis, input_idx) in &alarm_inputs {
-            let raw = self.digital_inputs[input_idx]
-                .get_value()
-                .unwrap_or(!ALARM_ACTIVE_LOW);
-            let still_alarm = if ALARM_ACTIVE_LOW { !raw } else { raw };
-
-            if still_alarm {
-                tracing::warn!(
-                    "[BbmAutomatikV2] Cannot reset alarms - Axis {} alarm still active on hardware",
-                    axis
-                );
-                self.emit_state();
-                return;
-            }
+        if let Some(axis) = self.axes.iter().find(|a| a.alarm_input_active()) {
+            tracing::warn!(
+                "[BbmAutomatikV2] Cannot reset alarms - Axis {} alarm still active on hardware",
+                axis.name()
+            );
+            self.emit_state();
+            return;
         }
 
-        self.axis_alarm_active = [false; 3];
+        for axis in self.axes.iter_mut() {
+            // Cannot fail, the alarm inputs were checked above
+            let _ = axis.reset_alarm();
+        }
         tracing::info!("[BbmAutomatikV2] All alarms reset");
         self.emit_state();
     }
 
-    /// Check if axis is at home position (reference switch triggered)
-    /// Ref switches are NC (normally closed): 24V/true = free, 0V/false = end position reached
-    /// So sensor is triggered (at home) when value is false (signal interrupted)
-    pub fn is_axis_homed(&self, axis: usize) -> bool {
-        match axis {
-            axes::MT => !self.digital_inputs[inputs::REF_MT]
-                .get_value()
-                .unwrap_or(true),
-            axes::SCHIEBER => !self.digital_inputs[inputs::REF_SCHIEBER]
-                .get_value()
-                .unwrap_or(true),
-            axes::DRUECKER => !self.digital_inputs[inputs::REF_DRUECKER]
-                .get_value()
-                .unwrap_or(true),
-            _ => false,
-        }
-    }
-
     // ============ Homing Functions ============
 
-    /// Start homing sequence for an axis
-    /// Sequence: 1) Move negative until sensor, 2) Retract 2mm, 3) Set position to 0
-    /// True when it is safe to home the Schieber: the Drücker is referenced
-    /// (homed), not currently homing, and retracted to/below its start. Until
-    /// then a Schieber-home request is deferred (Drücker-before-Schieber).
-    fn druecker_safe_for_schieber_home(&self) -> bool {
-        self.axis_homed[axes::DRUECKER]
-            && self.axis_homing_phase[axes::DRUECKER] == HomingPhase::Idle
-            && self.current_logical_mm(axes::DRUECKER)
-                <= self.druecker_start_threshold_mm() + SCHIEBER_INTERLOCK_TOLERANCE_MM
-    }
-
     /// Starts a deferred Schieber home once the Drücker has become safe.
     /// Called each act() cycle after update_homing.
     pub fn process_pending_schieber_home(&mut self) {
         if self.schieber_home_pending
-            && self.axis_homing_phase[axes::SCHIEBER] == HomingPhase::Idle
-            && self.druecker_safe_for_schieber_home()
+            && !self.axes[axes::SCHIEBER].is_homing()
+            && !self.is_interlock_blocking(interlocks::HOMING_ORDER)
         {
             self.schieber_home_pending = false;
             tracing::info!(
@@ -1268,168 +911,54 @@ impl BbmAutomatikV2 {
         }
     }
 
+    /// Start homing sequence for an axis, see [`LinearPtoAxis::start_homing`]
     pub fn start_homing(&mut self, index: usize) {
         if index >= self.axes.len() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Cannot home axis {} (invalid axis)",
-                index
-            );
+            tracing::warn!("[BbmAutomatikV2] Cannot home axis {} (invalid axis)", index);
             return;
         }
 
         // If already homing, ignore
-        if self.axis_homing_phase[index] != HomingPhase::Idle {
+        if self.axes[index].is_homing() {
             tracing::warn!("[BbmAutomatikV2] Axis {} already homing", index);
             return;
         }
 
-        // SAFETY: the Drücker MUST home before the Schieber. During homing the
-        // anti-collision interlocks are bypassed (axes drive blindly to their
-        // sensors), so homing the Schieber while the Drücker is not yet
-        // referenced+retracted could crash them. Defer the Schieber home until
-        // the Drücker is safe; it auto-starts via process_pending_schieber_home().
-        if index == axes::SCHIEBER && !self.druecker_safe_for_schieber_home() {
-            self.schieber_home_pending = true;
-            tracing::info!(
-                "[BbmAutomatikV2] Schieber-Homing aufgeschoben: Drücker muss zuerst referenzieren"
-            );
-            self.emit_state();
-            return;
+        // SAFETY: the Drücker MUST home before the Schieber (see
+        // interlocks::HOMING_ORDER). Defer the Schieber home until the
+        // Drücker is safe; it auto-starts via process_pending_schieber_home().
+        match self.check_interlock(Command::Home(index)) {
+            Ok(()) => {}
+            Err(blocked) if blocked.rule == interlocks::HOMING_ORDER => {
+                self.schieber_home_pending = true;
+                tracing::info!(
+                    "[BbmAutomatikV2] Schieber-Homing aufgeschoben: Drücker muss zuerst referenzieren"
+                );
+                self.emit_state();
+                return;
+            }
+            Err(blocked) => {
+                tracing::warn!(
+                    "[BbmAutomatikV2] Homing Achse {} blockiert: {}",
+                    index,
+                    blocked
+                );
+                return;
+            }
         }
 
-        // Start Phase 1: Search for sensor (move negative)
-        self.axis_homing_phase[index] = HomingPhase::SearchingSensor;
-        self.axis_position_mode[index] = false;
-
-        // Set slow homing speed in negative direction
-        let homing_hz = -mechanics::mm_per_s_to_hz(homing::HOMING_SPEED_MM_S);
-        self.axis_target_speeds[index] = homing_hz;
-
+        if let Err(e) = self.axes[index].start_homing() {
+            tracing::warn!("[BbmAutomatikV2] {}", e);
+            return;
+        }
         self.emit_state();
-        tracing::info!(
-            "[BbmAutomatikV2] Axis {} homing Phase 1: Searching sensor at {} Hz ({:.1} mm/s)",
-            index,
-            homing_hz,
-            homing::HOMING_SPEED_MM_S
-        );
     }
 
     /// Cancel homing for an axis
     pub fn cancel_homing(&mut self, index: usize) {
-        if index < self.axes.len() && self.axis_homing_phase[index] != HomingPhase::Idle {
-            self.axis_homing_phase[index] = HomingPhase::Idle;
-            self.stop_axis(index);
-            tracing::info!("[BbmAutomatikV2] Axis {} homing cancelled", index);
-        }
-    }
-
-    /// Update homing state machine
-    /// Called from act() loop
-    pub fn update_homing(&mut self) {
-        for i in 0..self.axes.len() {
-            match self.axis_homing_phase[i] {
-                HomingPhase::Idle => continue,
-
-                HomingPhase::SearchingSensor => {
-                    // Check if reference switch is triggered
-                    if self.is_axis_homed(i) {
-                        // Stop the axis
-                        self.axis_speeds[i] = 0;
-                        self.axis_target_speeds[i] = 0;
-                        let mut output = self.axes[i].get_output();
-                        output.disble_ramp = false;
-                        output.go_counter = false;
-                        output.frequency_value = 0;
-                        self.axes[i].set_output(output);
-
-                        // Calculate retract target in hw-counter space. Hw
-                        // counter never wraps over realistic homing distances
-                        // (RETRACT_DISTANCE_MM is small), so a plain u32 add
-                        // is safe.
-                        let current_hw = self.axes[i].get_position();
-                        let retract_pulses =
-                            (homing::RETRACT_DISTANCE_MM * mechanics::PULSES_PER_MM) as u32;
-                        self.axis_homing_retract_target[i] =
-                            current_hw.wrapping_add(retract_pulses);
-
-                        // Start Phase 2: Retract
-                        self.axis_homing_phase[i] = HomingPhase::Retracting;
-
-                        // Move positive (away from sensor)
-                        let retract_hz = mechanics::mm_per_s_to_hz(homing::HOMING_SPEED_MM_S);
-                        self.axis_target_speeds[i] = retract_hz;
-
-                        tracing::info!(
-                            "[BbmAutomatikV2] Axis {} homing Phase 2: Retracting {:.1}mm (target hw {})",
-                            i,
-                            homing::RETRACT_DISTANCE_MM,
-                            self.axis_homing_retract_target[i]
-                        );
-                        self.emit_state();
-                    }
-                }
-
-                HomingPhase::Retracting => {
-                    // Check if we reached the retract target (compare in
-                    // signed delta space so wraparound doesn't fool us).
-                    let current_hw = self.axes[i].get_position();
-                    let delta = current_hw
-                        .wrapping_sub(self.axis_homing_retract_target[i])
-                        as i32;
-                    if delta >= 0 {
-                        // Stop the axis
-                        self.axis_speeds[i] = 0;
-                        self.axis_target_speeds[i] = 0;
-                        let mut output = self.axes[i].get_output();
-                        output.disble_ramp = false;
-                        output.go_counter = false;
-                        output.frequency_value = 0;
-
-                        // Start Phase 3: Set the hw counter to the virtual
-                        // offset so logical 0 is right here (physical
-                        // sensor + 2 mm retract). axis_position_offset is
-                        // updated eagerly so logical reads are correct
-                        // immediately; the SettingZero phase just waits
-                        // for the hardware to confirm via set_counter_done.
-                        output.set_counter = true;
-                        output.set_counter_value = POSITION_OFFSET_PULSES;
-                        self.axes[i].set_output(output);
-                        self.axis_position_offset[i] = POSITION_OFFSET_PULSES;
-                        self.axis_homing_phase[i] = HomingPhase::SettingZero;
-
-                        tracing::info!(
-                            "[BbmAutomatikV2] Axis {} homing Phase 3: Setting hw counter to offset {} (logical 0)",
-                            i,
-                            POSITION_OFFSET_PULSES
-                        );
-                        self.emit_state();
-                    }
-                }
-
-                HomingPhase::SettingZero => {
-                    // Wait for the hardware to apply the set_counter write.
-                    let input = self.axes[i].get_input();
-                    if input.set_counter_done
-                        || input.counter_value == self.axis_position_offset[i]
-                    {
-                        // Clear the set_counter flag
-                        self.axes[i].clear_set_counter();
-
-                        // Homing complete!
-                        self.axis_homing_phase[i] = HomingPhase::Idle;
-                        // From here on, soft limits apply (position counter is now calibrated).
-                        self.axis_homed[i] = true;
-                        // A fresh reference clears any step-loss flag.
-                        self.axis_step_loss[i] = false;
-
-                        tracing::info!(
-                            "[BbmAutomatikV2] Axis {} homing COMPLETE - logical position is now 0",
-                            i
-                        );
-                        self.emit_state();
-                    }
-                }
-            }
+        if index < self.axes.len() && self.axes[index].is_homing() {
+            self.axes[index].cancel_homing();
+            self.emit_state();
         }
     }
 
@@ -1440,24 +969,24 @@ impl BbmAutomatikV2 {
     /// Pneumatik). Returns true if interlock state changed (for UI update)
     pub fn check_door_interlock(&mut self) -> bool {
         let door_closed = self.are_doors_closed();
-        // The rotating brush is a hazard on its own, so it also arms the
-        // interlock even when no axis is moving.
-        let any_moving = self.axis_speeds.iter().any(|&s| s != 0)
-            || self.auto_sequence.is_some()
-            || self.output_states[outputs::BUERSTENMOTOR];
+        // Anything the door rule gates is running (motion, homing, brush) or
+        // the auto-sequence is running (a paused one keeps still).
+        let any_moving = self.auto_sequence.as_ref().is_some_and(|s| !s.paused)
+            || self
+                .interlock_violations()
+                .iter()
+                .any(|(rule, _)| *rule == interlocks::DOOR);
 
         if !door_closed && any_moving && !self.door_interlock_active {
             tracing::warn!("[BbmAutomatikV2] !!! DOOR OPEN - Emergency stop !!!");
             self.door_interlock_active = true;
             self.stop_all_axes();
-            self.set_buerstenmotor(false);
-            self.set_ruettelmotor(false);
-            self.set_pneumatik(false);
-            // Abort auto sequence if running
-            if self.auto_sequence.is_some() {
-                self.auto_sequence = None;
-                self.set_ampel(true, false, false);
-            }
+            self.write_output(outputs::BUERSTENMOTOR, false);
+            self.write_output(outputs::RUETTELMOTOR, false);
+            self.write_output(outputs::PNEUMATIK, false);
+            // Interrupt the auto sequence, it can be resumed once the door
+            // is closed again
+            self.interrupt_auto_sequence(interlocks::DOOR, "door open");
             return true;
         }
 
@@ -1474,12 +1003,12 @@ impl BbmAutomatikV2 {
 
     /// True only when ALL axes have completed homing. Until then NO axis is
     /// allowed to move (manual jog / move-to-position / speed / auto). Homing
-    /// itself drives the axes via speed mode written directly to
-    /// `axis_target_speeds`, bypassing the guarded movement entry points, so
-    /// referencing always remains possible. A step-loss revokes an axis'
-    /// `axis_homed` flag, which re-arms this gate until it is re-referenced.
+    /// itself drives the axes in speed mode inside [`LinearPtoAxis`],
+    /// bypassing the guarded movement entry points, so referencing always
+    /// remains possible. A step-loss revokes an axis' homed flag, which
+    /// re-arms this gate until it is re-referenced.
     pub fn all_axes_homed(&self) -> bool {
-        self.axis_homed.iter().all(|&h| h)
+        self.axes.iter().all(|a| a.is_homed())
     }
 
     // ============ Schieber ⟷ Drücker Anti-Collision Interlock ============
@@ -1495,60 +1024,6 @@ impl BbmAutomatikV2 {
             .unwrap_or(auto_positions::DRUECKER_START_FALLBACK)
     }
 
-    /// True when Schieber travel must be blocked because the Drücker is
-    /// extended ABOVE its start position (collision risk: the Schieber would
-    /// shear off the Drücker tips — e.g. start=150, target=300 ⇒ blocked at
-    /// 160, free at start/retracted). HARD rule — applies to BOTH manual and
-    /// auto operation. The auto sequence is choreographed so the Schieber is
-    /// only ever commanded while the Drücker is at start; this guard is the
-    /// safety net if that is ever violated. Only armed once the Drücker is
-    /// homed (position known). Schieber HOMING is never gated (see
-    /// [`Self::enforce_schieber_interlock`]).
-    pub fn schieber_interlock_active(&self) -> bool {
-        if !self.axis_homed[axes::DRUECKER] {
-            return false;
-        }
-        let threshold =
-            self.druecker_start_threshold_mm() + SCHIEBER_INTERLOCK_TOLERANCE_MM;
-        self.current_logical_mm(axes::DRUECKER) > threshold
-    }
-
-    /// Active enforcement: if the Schieber is travelling while the Drücker is
-    /// extended above start, stop it immediately. The auto sequence is
-    /// choreographed never to do this, so if it fires DURING a sequence the
-    /// choreography was violated → abort the sequence as well. Schieber homing
-    /// is exempt — it must always be able to reach its own reference. Returns
-    /// true if it intervened.
-    pub fn enforce_schieber_interlock(&mut self) -> bool {
-        if !self.schieber_interlock_active() {
-            return false;
-        }
-        let s = axes::SCHIEBER;
-        // Never interfere with a Schieber homing run.
-        if self.axis_homing_phase[s] != HomingPhase::Idle {
-            return false;
-        }
-        let moving = self.axis_position_mode[s]
-            || self.axis_speeds[s] != 0
-            || self.axis_target_speeds[s] != 0;
-        if !moving {
-            return false;
-        }
-        tracing::warn!(
-            "[BbmAutomatikV2] Schieber-Interlock: Drücker über Start/ausgefahren ({:.1} mm > {:.1} mm) - Schieber gestoppt",
-            self.current_logical_mm(axes::DRUECKER),
-            self.druecker_start_threshold_mm()
-        );
-        self.stop_axis(s);
-        if self.auto_sequence.is_some() {
-            tracing::error!(
-                "[BbmAutomatikV2] Auto-Sequenz abgebrochen: Schieber-Interlock waehrend Automatik ausgeloest (Choreografie verletzt)"
-            );
-            self.stop_auto_sequence();
-        }
-        true
-    }
-
     /// The Schieber start position (mm). Falls back to
     /// [`auto_positions::SCHIEBER_START_FALLBACK`] when not yet teached.
     pub fn schieber_start_threshold_mm(&self) -> f32 {
@@ -1565,54 +1040,104 @@ impl BbmAutomatikV2 {
             .unwrap_or(auto_positions::SCHIEBER_ZIEL_FALLBACK)
     }
 
-    /// Interlock B: true when Drücker travel must be blocked. The Schieber has
-    /// TWO safe positions for the Drücker — at START and at ZIEL (both ends).
-    /// The Drücker may only move while the Schieber sits at one of those ends;
-    /// while the Schieber is MID-TRAVEL (between them) the Drücker would crash
-    /// into it. HARD rule, manual AND auto. Only armed once the Schieber is
-    /// homed. Drücker HOMING is never gated.
-    pub fn druecker_interlock_active(&self) -> bool {
-        if !self.axis_homed[axes::SCHIEBER] {
-            return false;
-        }
-        let s = self.current_logical_mm(axes::SCHIEBER);
-        let tol = SCHIEBER_INTERLOCK_TOLERANCE_MM;
-        // at/below start (retracted) or at/beyond ziel (fully pushed) = safe
-        let near_start = s <= self.schieber_start_threshold_mm() + tol;
-        let near_ziel = s >= self.schieber_ziel_threshold_mm() - tol;
-        // blocked only while the Schieber is neither end (mid-travel)
-        !(near_start || near_ziel)
+    // ============ Interlock Engine ============
+
+    /// Rebuild the interlock rules from the current teach positions
+    pub fn rebuild_interlocks(&mut self) {
+        self.interlocks
+            .set_rules(interlocks::rules(interlocks::Thresholds {
+                druecker_start_mm: self.druecker_start_threshold_mm(),
+                schieber_start_mm: self.schieber_start_threshold_mm(),
+                schieber_ziel_mm: self.schieber_ziel_threshold_mm(),
+            }));
     }
 
-    /// Active enforcement for interlock B: stop the Drücker if it travels
-    /// while the Schieber is away from start. Aborts a running auto-sequence
-    /// if it ever fires (choreography violated). Drücker homing is exempt.
-    pub fn enforce_druecker_interlock(&mut self) -> bool {
-        if !self.druecker_interlock_active() {
-            return false;
-        }
-        let d = axes::DRUECKER;
-        if self.axis_homing_phase[d] != HomingPhase::Idle {
-            return false;
-        }
-        let moving = self.axis_position_mode[d]
-            || self.axis_speeds[d] != 0
-            || self.axis_target_speeds[d] != 0;
-        if !moving {
-            return false;
-        }
-        tracing::warn!(
-            "[BbmAutomatikV2] Drücker-Interlock: Schieber mittendrin, nicht auf Start/Ziel ({:.1} mm) - Drücker gestoppt",
-            self.current_logical_mm(axes::SCHIEBER)
-        );
-        self.stop_axis(d);
-        if self.auto_sequence.is_some() {
-            tracing::error!(
-                "[BbmAutomatikV2] Auto-Sequenz abgebrochen: Drücker-Interlock waehrend Automatik ausgeloest (Choreografie verletzt)"
+    fn interlock_inputs(&self) -> ([AxisSnapshot; 3], [bool; 8]) {
+        let axes = self.axes.each_ref().map(AxisSnapshot::from);
+        let inputs = self
+            .digital_inputs
+            .each_ref()
+            .map(|di| di.get_value().unwrap_or(false));
+        (axes, inputs)
+    }
+
+    /// Checks a command against the interlock rules
+    pub fn check_interlock(&self, command: Command) -> Result<(), Blocked> {
+        let (axes, inputs) = self.interlock_inputs();
+        let snapshot = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        self.interlocks.check(command, &snapshot)
+    }
+
+    /// True when the rule `id` currently blocks its commands
+    pub fn is_interlock_blocking(&self, id: &str) -> bool {
+        self.active_interlocks().contains(&id)
+    }
+
+    /// Ids of the rules currently blocking their commands
+    pub fn active_interlocks(&self) -> Vec<&'static str> {
+        let (axes, inputs) = self.interlock_inputs();
+        let snapshot = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        self.interlocks.blocking_rules(&snapshot).collect()
+    }
+
+    /// Running commands that a rule no longer permits
+    fn interlock_violations(&self) -> Vec<(&'static str, Command)> {
+        let (axes, inputs) = self.interlock_inputs();
+        let snapshot = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        self.interlocks
+            .violations(&snapshot)
+            .iter()
+            .map(|v| (v.rule.id, v.command))
+            .collect()
+    }
+
+    /// Active enforcement of the anti-collision rules: stop an axis that
+    /// travels although a rule no longer permits it. The auto sequence is
+    /// choreographed never to do this, so if a rule fires DURING a sequence
+    /// the choreography was violated → abort the sequence as well. Homing
+    /// runs are exempt, and the door rule is handled by
+    /// [`Self::check_door_interlock`]. Returns true if it intervened.
+    pub fn enforce_interlocks(&mut self) -> bool {
+        let mut intervened = false;
+        for (rule, command) in self.interlock_violations() {
+            if rule == interlocks::DOOR {
+                continue;
+            }
+            let Command::Move(axis) = command else {
+                continue;
+            };
+            tracing::warn!(
+                "[BbmAutomatikV2] Interlock {}: {} gestoppt (Drücker {:.1} mm, Schieber {:.1} mm)",
+                rule,
+                self.axes[axis].name(),
+                self.current_logical_mm(axes::DRUECKER),
+                self.current_logical_mm(axes::SCHIEBER)
             );
-            self.stop_auto_sequence();
+            self.stop_axis(axis);
+            intervened = true;
+            if self.auto_sequence.is_some() {
+                self.interrupt_auto_sequence(
+                    rule,
+                    &format!(
+                        "Interlock {} waehrend Automatik ausgeloest (Choreografie verletzt)",
+                        rule
+                    ),
+                );
+            }
         }
-        true
+        intervened
     }
 
     // ============ Auto-Sequence State Machine ============
@@ -1621,310 +1146,541 @@ impl BbmAutomatikV2 {
     /// Used by the auto-sequence state machine to know when to advance.
     #[inline]
     fn is_axis_moving(&self, index: usize) -> bool {
-        self.axis_position_mode[index]
+        self.axes[index].is_position_mode()
     }
 
-    /// Update auto-sequence state machine (called from act() loop)
-    /// Returns true if state changed (for UI update)
+    /// Update auto-sequence state machine (called from act() loop): executes
+    /// program ops until one has to wait (axes moving, timed wait) or the
+    /// program ends. Records a checkpoint at every resume point and enters
+    /// a requested pause there. Returns true if state changed (for UI update)
     pub fn update_auto_sequence(&mut self) -> bool {
-        let seq = match &self.auto_sequence {
-            Some(s) => s.clone(),
-            None => return false,
-        };
+        if self.auto_sequence.as_ref().is_none_or(|s| s.paused) {
+            return false;
+        }
 
-        match seq.current_step {
-            AutoCycleStep::WobbleOut => {
-                if !self.is_axis_moving(axes::SCHIEBER) {
-                    // Wobble out complete, now wobble back
-                    self.move_to_position_mm(
-                        axes::SCHIEBER,
-                        seq.schieber_start - auto_positions::SCHIEBER_WOBBLE,
-                        seq.speed.schieber_mm_s,
-                    );
-                    self.auto_sequence.as_mut().unwrap().current_step =
-                        AutoCycleStep::WobbleBack;
-                    return true;
-                }
+        let mut changed = false;
+        // Bounded so a loop of instant ops (outputs) can't stall the RT loop;
+        // the rest runs in the next cycle.
+        for _ in 0..MAX_AUTO_OPS_PER_CYCLE {
+            let now = Instant::now();
+            let at_rest = !self.axes.iter().any(|a| a.is_moving());
+            let seq = self.auto_sequence.as_ref().unwrap();
+            let resume_point = at_rest && seq.restore.is_empty() && seq.wait_until.is_none();
+            if resume_point {
+                self.record_checkpoint();
             }
-            AutoCycleStep::WobbleBack => {
-                if !self.is_axis_moving(axes::SCHIEBER) {
-                    // Wobble done, schieber to target
-                    self.move_to_position_mm(
-                        axes::SCHIEBER,
-                        seq.schieber_ziel,
-                        seq.speed.schieber_mm_s,
-                    );
-                    self.auto_sequence.as_mut().unwrap().current_step =
-                        AutoCycleStep::SchieberToTarget;
+            let seq = self.auto_sequence.as_ref().unwrap();
+            if seq.pause_requested {
+                if resume_point {
+                    self.enter_pause();
                     return true;
                 }
+                // Let the moves in flight finish, issue nothing new
+                return changed;
             }
-            AutoCycleStep::SchieberToTarget => {
-                if !self.is_axis_moving(axes::SCHIEBER) {
-                    // Schieber reached Ziel (a SAFE Schieber position for the
-                    // Drücker per interlock B). Now the Drücker pushes — the
-                    // Schieber STAYS at Ziel (it does not have to retract first).
-                    self.move_to_position_mm(
-                        axes::DRUECKER,
-                        seq.druecker_ziel,
-                        seq.speed.druecker_mm_s,
-                    );
-                    self.auto_sequence.as_mut().unwrap().current_step =
-                        AutoCycleStep::DrueckerToTarget;
-                    return true;
-                }
+
+            let op = match seq.restore.first() {
+                Some(op) => op.clone(),
+                None => match seq.ops.get(seq.pc) {
+                    Some(op) => op.clone(),
+                    None => {
+                        tracing::info!(
+                            "[BbmAutomatikV2] Auto sequence COMPLETE - program {}",
+                            seq.program.name
+                        );
+                        self.write_output(outputs::RUETTELMOTOR, false);
+                        self.set_ampel(false, false, true); // Green = done
+                        self.auto_sequence = None;
+                        checkpoint::clear(self.main_sender.as_ref());
+                        self.production.run_completed(now);
+                        return true;
+                    }
+                },
+            };
+            let restoring = !seq.restore.is_empty();
+            if let Some(label) = op.timed_label().filter(|_| !restoring) {
+                let step = format!("{:03} {}", seq.pc, label);
+                self.production.step_started(&seq.program.name, step, now);
             }
-            AutoCycleStep::DrueckerToTarget => {
-                if !self.is_axis_moving(axes::DRUECKER) {
-                    // Drücker pushed → retract it to start (Schieber still at
-                    // Ziel, which B treats as a safe end position). MT advances
-                    // in parallel (gated by neither interlock).
-                    self.move_to_position_mm(
-                        axes::DRUECKER,
-                        seq.druecker_start,
-                        seq.speed.druecker_mm_s,
-                    );
-                    let new_mt_pos =
-                        seq.mt_current_run_pos - auto_positions::MT_ADVANCE_PER_CYCLE;
-                    self.move_to_position_mm(axes::MT, new_mt_pos, seq.speed.mt_mm_s);
-                    let s = self.auto_sequence.as_mut().unwrap();
-                    s.mt_current_run_pos = new_mt_pos;
-                    s.current_step = AutoCycleStep::DrueckerReturn;
-                    return true;
+
+            match op {
+                program::Op::Move {
+                    axis,
+                    target,
+                    speed_mm_s,
+                } => {
+                    let iteration = seq.loops.last().map_or(0, |f| f.iteration);
+                    // Teach slots were checked at start and are snapshotted.
+                    let target_mm = target.resolve(&seq.teach[axis], iteration).unwrap();
+                    if !self.auto_move(axis, target_mm, speed_mm_s) {
+                        return true;
+                    }
                 }
-            }
-            AutoCycleStep::DrueckerReturn => {
-                // Drücker is back at start → now retract the Schieber from Ziel
-                // to start (interlock A allows it: the Drücker is at start).
-                // MT may still be moving in parallel.
-                if !self.is_axis_moving(axes::DRUECKER) {
-                    self.move_to_position_mm(
-                        axes::SCHIEBER,
-                        seq.schieber_start,
-                        seq.speed.schieber_mm_s,
-                    );
-                    self.auto_sequence.as_mut().unwrap().current_step =
-                        AutoCycleStep::SchieberReturn;
-                    return true;
+                program::Op::MoveTo {
+                    axis,
+                    position_mm,
+                    speed_mm_s,
+                } => {
+                    if !self.auto_move(axis, position_mm, speed_mm_s) {
+                        return true;
+                    }
                 }
-            }
-            AutoCycleStep::SchieberReturn => {
-                // Cycle ends once the Schieber is back at start and the parallel
-                // MT advance is done.
-                let schieber_done = !self.is_axis_moving(axes::SCHIEBER);
-                let mt_done = !self.is_axis_moving(axes::MT);
-                if schieber_done && mt_done {
-                    return self.advance_auto_sequence();
+                program::Op::WaitAxes(mask) => {
+                    if (0..mask.len()).any(|i| mask[i] && self.is_axis_moving(i)) {
+                        return changed;
+                    }
                 }
-            }
-            AutoCycleStep::WaitParallelComplete => {
-                // Initial positioning: MT + Drücker drive to start (Schieber
-                // not commanded — see start_auto_sequence). Once both arrive,
-                // begin cycle 1.
-                let druecker_done = !self.is_axis_moving(axes::DRUECKER);
-                let mt_done = !self.is_axis_moving(axes::MT);
-                if druecker_done && mt_done {
-                    return self.advance_auto_sequence();
+                program::Op::Wait(duration) => {
+                    let seq = self.auto_sequence.as_mut().unwrap();
+                    match seq.wait_until {
+                        None => {
+                            seq.wait_until = Some(now + duration);
+                            return changed;
+                        }
+                        Some(until) if now < until => return changed,
+                        Some(_) => seq.wait_until = None,
+                    }
                 }
-            }
-            AutoCycleStep::Entnahme => {
-                // Final removal move: wait for the MT to reach its Ziel,
-                // then finish the sequence (Rüttler off, Ampel green).
-                if !self.is_axis_moving(axes::MT) {
-                    tracing::info!(
-                        "[BbmAutomatikV2] Auto sequence COMPLETE - MT an Entnahme-Position"
-                    );
-                    self.set_ruettelmotor(false);
-                    self.set_ampel(false, false, true); // Green = done
-                    self.auto_sequence = None;
-                    return true;
+                program::Op::SetOutput { output, on } => {
+                    if let Err(e) = self.set_output(output, on) {
+                        tracing::warn!("[BbmAutomatikV2] {}", e);
+                    }
+                }
+                program::Op::Loop { count, sets, .. } => {
+                    let seq = self.auto_sequence.as_mut().unwrap();
+                    seq.loops.push(LoopFrame {
+                        start: seq.pc,
+                        count,
+                        iteration: 1,
+                        sets,
+                    });
+                    if let Some(unit) = seq.loop_unit(seq.loops.len() - 1) {
+                        self.production.unit_started(unit, now);
+                    }
+                }
+                program::Op::EndLoop { start } => {
+                    let seq = self.auto_sequence.as_mut().unwrap();
+                    let unit = seq.loop_unit(seq.loops.len() - 1);
+                    if let Some(unit) = unit {
+                        self.production.unit_completed(unit, now);
+                    }
+                    let frame = seq.loops.last_mut().unwrap();
+                    if frame.iteration < frame.count {
+                        frame.iteration += 1;
+                        seq.pc = start + 1;
+                        if let Some(unit) = unit {
+                            self.production.unit_started(unit, now);
+                        }
+                        changed = true;
+                        continue;
+                    }
+                    seq.loops.pop();
                 }
             }
-        }
-        false
-    }
 
-    /// Advance to next cycle/block/set or finish
-    fn advance_auto_sequence(&mut self) -> bool {
-        let seq = self.auto_sequence.as_mut().unwrap();
-        seq.current_cycle += 1;
-
-        if seq.current_cycle >= auto_positions::CYCLES_PER_BLOCK {
-            // Block complete
-            seq.current_cycle = 0;
-            seq.current_block += 1;
-
-            if seq.current_block >= auto_positions::BLOCKS_PER_SET {
-                // Set complete
-                seq.current_block = 0;
-                seq.current_set += 1;
-
-                if seq.current_set >= seq.total_sets {
-                    // ALL DONE: drive the MT to its teached Ziel for part
-                    // removal (Entnahme), then finish once it arrives.
-                    let mt_ziel = seq.mt_ziel;
-                    let mt_speed = seq.speed.mt_mm_s;
-                    tracing::info!(
-                        "[BbmAutomatikV2] Alle Sets fertig - MT faehrt zur Entnahme-Position {:.1} mm",
-                        mt_ziel
-                    );
-                    self.move_to_position_mm(axes::MT, mt_ziel, mt_speed);
-                    self.auto_sequence.as_mut().unwrap().current_step =
-                        AutoCycleStep::Entnahme;
-                    return true;
-                }
+            let seq = self.auto_sequence.as_mut().unwrap();
+            if restoring {
+                seq.restore.remove(0);
+            } else {
+                seq.pc += 1;
+                self.production.step_completed(now);
             }
-            // New block: reset MT to its teached run-start
-            seq.mt_current_run_pos = seq.mt_start;
+            changed = true;
         }
+        changed
+    }
 
-        self.start_auto_cycle();
+    /// Issues a move of the auto-sequence. The program was validated against
+    /// the interlocks at start; should one trip anyway (axis pushed by hand,
+    /// missed steps) the sequence must not carry on. Returns false if the
+    /// sequence was interrupted.
+    fn auto_move(&mut self, axis: usize, target_mm: f32, speed_mm_s: f32) -> bool {
+        if let Err(blocked) = self.check_interlock(Command::Move(axis)) {
+            let reason = format!("Bewegung {} blockiert: {}", self.axes[axis].name(), blocked);
+            self.interrupt_auto_sequence(blocked.rule, &reason);
+            return false;
+        }
+        self.axes[axis].move_to_mm(target_mm, speed_mm_s);
         true
     }
 
-    /// Start a single fill cycle (wobble -> schieber -> drücker -> return)
-    fn start_auto_cycle(&mut self) {
-        let seq = self.auto_sequence.as_ref().unwrap();
-        let speed = seq.speed;
-        let schieber_start = seq.schieber_start;
+    /// Records (and persists) a checkpoint at the current op unless one was
+    /// already taken there
+    fn record_checkpoint(&mut self) {
+        let positions_mm = self.axes.each_ref().map(|a| a.position_mm());
+        let outputs = self.output_states;
+        let seq = self.auto_sequence.as_mut().unwrap();
+        if seq
+            .checkpoint
+            .as_ref()
+            .is_some_and(|c| c.pc == seq.pc && c.loops == seq.loops)
+        {
+            return;
+        }
+        let checkpoint = checkpoint::Checkpoint {
+            program: seq.program.clone(),
+            speed_preset: seq.speed_preset.clone(),
+            total_sets: seq.total_sets,
+            teach: seq.teach.clone(),
+            pc: seq.pc,
+            loops: seq.loops.clone(),
+            positions_mm,
+            outputs,
+        };
+        seq.checkpoint = Some(checkpoint.clone());
+        checkpoint::save(self.main_sender.as_ref(), checkpoint);
+    }
 
-        // Start wobble: move schieber +wobble from start
-        self.move_to_position_mm(
-            axes::SCHIEBER,
-            schieber_start + auto_positions::SCHIEBER_WOBBLE,
-            speed.schieber_mm_s,
+    /// Pauses at the current resume point: Rüttler off, Ampel gelb+rot
+    fn enter_pause(&mut self) {
+        let seq = self.auto_sequence.as_mut().unwrap();
+        seq.paused = true;
+        seq.pause_requested = false;
+        let (set, block, cycle) = seq.progress();
+        self.write_output(outputs::RUETTELMOTOR, false);
+        self.set_ampel(true, true, false);
+        self.production
+            .downtime_started(downtime::PAUSE, Instant::now());
+        tracing::info!(
+            "[BbmAutomatikV2] Auto sequence paused at set {}, block {}, cycle {}",
+            set + 1,
+            block + 1,
+            cycle + 1
         );
-
-        self.auto_sequence.as_mut().unwrap().current_step = AutoCycleStep::WobbleOut;
     }
 
-    /// Start auto-sequence with given speed preset and number of sets
-    pub fn start_auto_sequence(&mut self, speed_preset: &str, total_sets: u32) {
-        // Safety checks
-        if !self.are_doors_closed() {
-            tracing::warn!("[BbmAutomatikV2] Cannot start: doors not closed");
+    /// Stops all axes and keeps the sequence paused at its last checkpoint
+    /// for a later [`Self::resume_auto_sequence`]. Used for door open,
+    /// driver alarms, step loss and tripped interlocks. Without a checkpoint
+    /// there is nothing to resume and the sequence is discarded. `cause` is
+    /// the downtime reason of the production statistics.
+    pub fn interrupt_auto_sequence(&mut self, cause: &'static str, reason: &str) {
+        let Some(seq) = self.auto_sequence.as_mut() else {
             return;
-        }
-        if self.axis_alarm_active.iter().any(|&a| a) {
-            tracing::warn!("[BbmAutomatikV2] Cannot start: alarm active");
+        };
+        if seq.checkpoint.is_none() {
+            tracing::error!("[BbmAutomatikV2] Auto sequence aborted: {}", reason);
+            self.auto_sequence = None;
+            self.production.run_aborted(Instant::now());
+            self.stop_all_axes();
+            self.write_output(outputs::RUETTELMOTOR, false);
+            self.set_ampel(true, false, false); // Red = stopped
+            self.emit_state();
             return;
         }
-        if self.auto_sequence.is_some() {
-            tracing::warn!("[BbmAutomatikV2] Cannot start: already running");
-            return;
+        seq.paused = true;
+        seq.pause_requested = false;
+        seq.wait_until = None;
+        seq.restore.clear();
+        self.stop_all_axes();
+        self.write_output(outputs::RUETTELMOTOR, false);
+        self.set_ampel(true, false, false); // Red = stopped
+        self.production.downtime_started(cause, Instant::now());
+        tracing::error!(
+            "[BbmAutomatikV2] Auto sequence interrupted: {} - resumable from the last checkpoint",
+            reason
+        );
+        self.emit_state();
+    }
+
+    /// Compiles a program and checks it against the current machine state:
+    /// teach positions, soft limits and the Schieber ⟷ Drücker interlocks
+    /// (see [`program::validate`]).
+    fn compile_checked(
+        &self,
+        program: &program::SequenceProgram,
+        preset: &program::SpeedPreset,
+        total_sets: u32,
+    ) -> anyhow::Result<Vec<program::Op>> {
+        let ops = program.compile(preset, total_sets)?;
+        program::validate(&ops, &self.validation_context(&self.teach_positions))?;
+        Ok(ops)
+    }
+
+    fn validation_context<'a>(
+        &self,
+        teach: &'a [AxisTeachPositions; 3],
+    ) -> program::ValidationContext<'a> {
+        program::ValidationContext {
+            teach,
+            positions_mm: self.axes.each_ref().map(|a| a.position_mm()),
+            soft_limit_min_mm: self.soft_limits_min_mm(),
+            soft_limit_max_mm: self.soft_limits_max_mm(),
+            druecker_start_mm: self.druecker_start_threshold_mm(),
+            schieber_start_mm: self.schieber_start_threshold_mm(),
+            schieber_ziel_mm: self.schieber_ziel_threshold_mm(),
+        }
+    }
+
+    /// Compiles a stored program and checks it against the current machine
+    /// state, see [`Self::compile_checked`].
+    pub fn prepare_auto_sequence(
+        &self,
+        program_name: &str,
+        speed_preset: &str,
+        total_sets: u32,
+    ) -> anyhow::Result<Vec<program::Op>> {
+        let Some(program) = self.programs.program(program_name) else {
+            bail!("Unknown program {}", program_name);
+        };
+        let Some(preset) = self.programs.speed_preset(speed_preset) else {
+            bail!("Unknown speed preset {}", speed_preset);
+        };
+        self.compile_checked(program, preset, total_sets)
+    }
+
+    /// Safety checks shared by start and resume
+    fn check_auto_start_allowed(&self) -> anyhow::Result<()> {
+        if !self.are_doors_closed() {
+            bail!("doors not closed");
+        }
+        if self.axes.iter().any(|a| a.is_alarm_active()) {
+            bail!("alarm active");
         }
         if !self.all_axes_homed() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Cannot start: not all axes homed (referencing required)"
-            );
-            return;
+            bail!("not all axes homed (referencing required)");
         }
-
-        // All target positions come from the teached calibration — the old
-        // hardcoded constants are gone. Refuse to start (rather than fall
-        // back to stale values) if any required position is missing, and
-        // log exactly which ones so the user knows what to teach.
-        let missing = self.missing_auto_teach_positions();
-        if !missing.is_empty() {
-            tracing::warn!(
-                "[BbmAutomatikV2] Cannot start: missing teach positions: {}",
-                missing.join(", ")
-            );
-            return;
+        if self.interpolation.is_some() {
+            bail!("interpolated move running");
         }
-        // Safe to unwrap: missing list is empty.
-        let mt_start = self.teach_positions[axes::MT].start_mm.unwrap();
-        let mt_ziel = self.teach_positions[axes::MT].ziel_mm.unwrap();
-        let schieber_start = self.teach_positions[axes::SCHIEBER].start_mm.unwrap();
-        let schieber_ziel = self.teach_positions[axes::SCHIEBER].ziel_mm.unwrap();
-        let druecker_start = self.teach_positions[axes::DRUECKER].start_mm.unwrap();
-        let druecker_ziel = self.teach_positions[axes::DRUECKER].ziel_mm.unwrap();
-
-        let speed = match speed_preset {
-            "medium" => speed_presets::MEDIUM,
-            "fast" => speed_presets::FAST,
-            _ => speed_presets::SLOW,
-        };
+        Ok(())
+    }
+
+    /// Start auto-sequence running `program_name` (the standard program if
+    /// `None`) with given speed preset and number of sets
+    pub fn start_auto_sequence(
+        &mut self,
+        program_name: Option<&str>,
+        speed_preset: &str,
+        total_sets: u32,
+    ) -> anyhow::Result<()> {
+        if self.auto_sequence.is_some() {
+            bail!("Cannot start: already running (stop a paused sequence first)");
+        }
+        self.check_auto_start_allowed()
+            .map_err(|e| anyhow!("Cannot start: {}", e))?;
+
+        // All target positions come from the teached calibration. Refuse to
+        // start (rather than fall back to stale values) if the program
+        // references a missing position or would run into a soft limit or
+        // interlock.
+        let program_name = program_name.unwrap_or(program::DEFAULT_PROGRAM);
+        let ops = self
+            .prepare_auto_sequence(program_name, speed_preset, total_sets)
+            .map_err(|e| anyhow!("Cannot start: {}", e))?;
+        // Safe to unwrap: looked up by prepare_auto_sequence
+        let program = self.programs.program(program_name).unwrap().clone();
+        let speed_preset = self.programs.speed_preset(speed_preset).unwrap().clone();
 
-        // Initialize sequence with the teached positions snapshotted.
+        tracing::info!(
+            "[BbmAutomatikV2] Auto sequence started: program={}, preset={}, sets={}",
+            program.name,
+            speed_preset.name,
+            total_sets
+        );
         self.auto_sequence = Some(AutoSequenceState {
-            speed_preset_name: speed_preset.to_string(),
-            speed,
+            program,
+            speed_preset,
             total_sets,
-            current_set: 0,
-            current_block: 0,
-            current_cycle: 0,
-            current_step: AutoCycleStep::WaitParallelComplete,
-            mt_current_run_pos: mt_start,
-            mt_start,
-            mt_ziel,
-            schieber_start,
-            schieber_ziel,
-            druecker_start,
-            druecker_ziel,
+            ops,
+            pc: 0,
+            loops: Vec::new(),
+            wait_until: None,
+            restore: Vec::new(),
+            pause_requested: false,
+            paused: false,
+            checkpoint: None,
+            teach: self.teach_positions.clone(),
         });
+        self.production.run_started(Instant::now());
 
         // Start: Rüttler on, Ampel gelb (running)
-        self.set_ruettelmotor(true);
+        if let Err(e) = self.set_ruettelmotor(true) {
+            tracing::warn!("[BbmAutomatikV2] {}", e);
+        }
         self.set_ampel(false, true, false);
+        self.emit_state();
+        Ok(())
+    }
 
-        // Move MT and Drücker to their start positions. The Schieber is
-        // intentionally NOT commanded here: if the Drücker is still extended
-        // (above start), moving the Schieber would shear off its tips. The
-        // WaitParallelComplete step waits for MT + Drücker to reach start;
-        // the Schieber's first move happens in the opening wobble of cycle 1,
-        // by which point the Drücker is at start and it is collision-free.
-        self.move_to_position_mm(axes::MT, mt_start, speed.mt_mm_s);
-        self.move_to_position_mm(axes::DRUECKER, druecker_start, speed.druecker_mm_s);
+    /// Request a pause. The sequence lets the moves in flight finish and
+    /// pauses at the next resume point (all axes at rest).
+    pub fn pause_auto_sequence(&mut self) -> anyhow::Result<()> {
+        let Some(seq) = self.auto_sequence.as_mut() else {
+            bail!("No auto sequence running");
+        };
+        if !seq.paused {
+            seq.pause_requested = true;
+            tracing::info!("[BbmAutomatikV2] Auto sequence pause requested");
+            self.emit_state();
+        }
+        Ok(())
+    }
 
+    /// Resume a paused or interrupted sequence from its last checkpoint:
+    /// the axes first drive back to the checkpoint positions (in an order
+    /// that keeps the interlocks satisfied), outputs are restored and the
+    /// program continues with the op it was at. A pending pause request is
+    /// withdrawn.
+    pub fn resume_auto_sequence(&mut self) -> anyhow::Result<()> {
+        let Some(seq) = self.auto_sequence.as_mut() else {
+            bail!("No auto sequence to resume");
+        };
+        if !seq.paused {
+            seq.pause_requested = false;
+            self.emit_state();
+            return Ok(());
+        }
+        let Some(checkpoint) = seq.checkpoint.clone() else {
+            bail!("Cannot resume: no checkpoint");
+        };
+        self.check_auto_start_allowed()
+            .map_err(|e| anyhow!("Cannot resume: {}", e))?;
+        let restore =
+            checkpoint::restore_ops(&checkpoint, &self.validation_context(&checkpoint.teach))
+                .map_err(|e| anyhow!("Cannot resume: {}", e))?;
+
+        let seq = self.auto_sequence.as_mut().unwrap();
+        seq.pc = checkpoint.pc;
+        seq.loops = checkpoint.loops.clone();
+        seq.wait_until = None;
+        seq.restore = restore;
+        seq.paused = false;
+        let (set, block, cycle) = seq.progress();
+        self.production.run_resumed(Instant::now());
+
+        // Restore the outputs the program had switched; the Ampel belongs to
+        // the machine state.
+        for (i, on) in checkpoint.outputs.into_iter().enumerate() {
+            if ![
+                outputs::AMPEL_ROT,
+                outputs::AMPEL_GELB,
+                outputs::AMPEL_GRUEN,
+            ]
+            .contains(&i)
+                && let Err(e) = self.set_output(i, on)
+            {
+                tracing::warn!("[BbmAutomatikV2] {}", e);
+            }
+        }
+        self.set_ampel(false, true, false);
         tracing::info!(
-            "[BbmAutomatikV2] Auto sequence started: preset={}, sets={}",
-            speed_preset,
-            total_sets
+            "[BbmAutomatikV2] Auto sequence resumed at set {}, block {}, cycle {}",
+            set + 1,
+            block + 1,
+            cycle + 1
         );
         self.emit_state();
+        Ok(())
     }
 
-    /// Names of the teach positions required to run the auto/test sequence
-    /// that are not yet set. Empty = ready to run. Used both as a
-    /// start-guard here and surfaced to the UI so the operator knows what
-    /// to calibrate.
-    pub fn missing_auto_teach_positions(&self) -> Vec<&'static str> {
-        let mut missing = Vec::new();
-        let t = &self.teach_positions;
-        if t[axes::MT].start_mm.is_none() {
-            missing.push("Transporter Start");
-        }
-        if t[axes::MT].ziel_mm.is_none() {
-            missing.push("Transporter Ziel");
-        }
-        if t[axes::SCHIEBER].start_mm.is_none() {
-            missing.push("Schieber Start");
-        }
-        if t[axes::SCHIEBER].ziel_mm.is_none() {
-            missing.push("Schieber Ziel");
-        }
-        if t[axes::DRUECKER].start_mm.is_none() {
-            missing.push("Drücker Start");
-        }
-        if t[axes::DRUECKER].ziel_mm.is_none() {
-            missing.push("Drücker Ziel");
-        }
-        missing
+    /// Rebuilds the sequence of a checkpoint persisted before a power loss,
+    /// paused so the operator can re-home and resume it
+    pub fn recover_auto_sequence(&mut self, checkpoint: checkpoint::Checkpoint) {
+        let ops = match checkpoint
+            .program
+            .compile(&checkpoint.speed_preset, checkpoint.total_sets)
+        {
+            Ok(ops) => ops,
+            Err(e) => {
+                tracing::warn!(
+                    "[BbmAutomatikV2] Discarding auto-sequence checkpoint: {}",
+                    e
+                );
+                checkpoint::clear(self.main_sender.as_ref());
+                return;
+            }
+        };
+        tracing::info!(
+            "[BbmAutomatikV2] Auto sequence {} recovered, paused until resumed",
+            checkpoint.program.name
+        );
+        self.auto_sequence = Some(AutoSequenceState {
+            program: checkpoint.program.clone(),
+            speed_preset: checkpoint.speed_preset.clone(),
+            total_sets: checkpoint.total_sets,
+            ops,
+            pc: checkpoint.pc,
+            loops: checkpoint.loops.clone(),
+            wait_until: None,
+            restore: Vec::new(),
+            pause_requested: false,
+            paused: true,
+            teach: checkpoint.teach.clone(),
+            checkpoint: Some(checkpoint),
+        });
+        self.production
+            .downtime_started(downtime::POWER_LOSS, Instant::now());
     }
 
-    /// Stop auto-sequence and all axes
+    /// Store (insert or replace by name) a sequence program. A running
+    /// sequence keeps the program it was started with.
+    pub fn save_sequence_program(
+        &mut self,
+        program: program::SequenceProgram,
+    ) -> anyhow::Result<()> {
+        self.programs.upsert_program(program)?;
+        self.programs.save();
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Delete a stored sequence program
+    pub fn delete_sequence_program(&mut self, name: &str) -> anyhow::Result<()> {
+        self.programs.remove_program(name)?;
+        self.programs.save();
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Store (insert or replace by name) a speed preset. A running sequence
+    /// keeps the speeds it was started with.
+    pub fn save_speed_preset(&mut self, preset: program::SpeedPreset) -> anyhow::Result<()> {
+        self.programs.upsert_speed_preset(preset)?;
+        self.programs.save();
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Stop auto-sequence and all axes. Discards a paused sequence and its
+    /// checkpoint as well.
     pub fn stop_auto_sequence(&mut self) {
         if self.auto_sequence.is_some() {
             self.auto_sequence = None;
+            checkpoint::clear(self.main_sender.as_ref());
+            self.production.run_aborted(Instant::now());
             self.stop_all_axes();
-            self.set_ruettelmotor(false);
+            self.write_output(outputs::RUETTELMOTOR, false);
             self.set_ampel(true, false, false); // Red = stopped
             tracing::info!("[BbmAutomatikV2] Auto sequence stopped by user");
             self.emit_state();
         }
     }
 
+    /// Feeds the axis states to the production statistics, see
+    /// [`ProductionTracker::track_axes`]
+    pub fn track_production(&mut self, now: Instant) {
+        let axes = self.axes.each_ref().map(AxisSnapshot::from);
+        self.production.track_axes(&axes);
+        self.production.persist_if_due(now);
+    }
+
+    /// Reset the production counters, times and downtimes. Axis usage is
+    /// reset per axis, see [`Self::reset_axis_usage`].
+    pub fn reset_production_stats(&mut self) {
+        self.production.reset();
+        tracing::info!("[BbmAutomatikV2] Production statistics reset");
+        self.emit_state();
+    }
+
+    /// Reset travel and move count of an axis after its maintenance
+    pub fn reset_axis_usage(&mut self, axis: usize) -> anyhow::Result<()> {
+        self.production.reset_axis(axis)?;
+        tracing::info!("[BbmAutomatikV2] Usage of axis {} reset", axis);
+        self.emit_state();
+        Ok(())
+    }
+
     // ============ Teach / Calibration ============
 
     /// Default placeholder name when a custom slot is saved for the first time.
@@ -1974,11 +1730,8 @@ impl BbmAutomatikV2 {
             slot,
             pos_mm
         );
-        calibration::save(
-            &self.teach_positions,
-            &self.axis_soft_limit_max_mm,
-            &self.axis_soft_limit_min_mm,
-        );
+        self.rebuild_interlocks();
+        self.save_calibration();
         self.emit_state();
     }
 
@@ -1995,11 +1748,8 @@ impl BbmAutomatikV2 {
             TeachSlot::Custom2 => t.custom2 = None,
         }
         tracing::info!("[BbmAutomatikV2] Cleared axis {} slot {:?}", axis, slot);
-        calibration::save(
-            &self.teach_positions,
-            &self.axis_soft_limit_max_mm,
-            &self.axis_soft_limit_min_mm,
-        );
+        self.rebuild_interlocks();
+        self.save_calibration();
         self.emit_state();
     }
 
@@ -2048,11 +1798,7 @@ impl BbmAutomatikV2 {
                 return;
             }
         }
-        calibration::save(
-            &self.teach_positions,
-            &self.axis_soft_limit_max_mm,
-            &self.axis_soft_limit_min_mm,
-        );
+        self.save_calibration();
         self.emit_state();
     }
 
@@ -2060,108 +1806,58 @@ impl BbmAutomatikV2 {
     /// the limit; `Some(mm)` clamps subsequent moves to that position.
     /// Persisted alongside teach positions. The new max must stay above
     /// the current min if both are set; otherwise the change is rejected.
-    pub fn set_soft_limit_max(&mut self, axis: usize, max_mm: Option<f32>) {
-        if axis >= self.axis_soft_limit_max_mm.len() {
-            return;
-        }
-        if let Some(v) = max_mm {
-            if !v.is_finite() {
-                tracing::warn!(
-                    "[BbmAutomatikV2] Reject soft-limit max axis {}: non-finite",
-                    axis
-                );
-                return;
-            }
-            if let Some(min) = self.axis_soft_limit_min_mm[axis] {
-                if v <= min {
-                    tracing::warn!(
-                        "[BbmAutomatikV2] Reject soft-limit max axis {}: {:.3} <= current min {:.3}",
-                        axis,
-                        v,
-                        min
-                    );
-                    return;
-                }
-            }
+    pub fn set_soft_limit_max(&mut self, axis: usize, max_mm: Option<f32>) -> anyhow::Result<()> {
+        if axis >= self.axes.len() {
+            bail!("Unknown axis {}", axis);
         }
-        self.axis_soft_limit_max_mm[axis] = max_mm;
-        tracing::info!(
-            "[BbmAutomatikV2] Soft-limit max axis {} = {:?} mm",
-            axis,
-            max_mm
-        );
-        calibration::save(
-            &self.teach_positions,
-            &self.axis_soft_limit_max_mm,
-            &self.axis_soft_limit_min_mm,
-        );
+        self.axes[axis].set_soft_limit_max(max_mm)?;
+        self.save_calibration();
         self.emit_state();
+        Ok(())
     }
 
     /// Set (or clear) the lower soft-limit for an axis. `None` removes
     /// the limit (default — many axes are intentionally unbounded on
     /// the lower side). The new min must stay below the current max if
     /// both are set; otherwise the change is rejected.
-    pub fn set_soft_limit_min(&mut self, axis: usize, min_mm: Option<f32>) {
-        if axis >= self.axis_soft_limit_min_mm.len() {
-            return;
-        }
-        if let Some(v) = min_mm {
-            if !v.is_finite() {
-                tracing::warn!(
-                    "[BbmAutomatikV2] Reject soft-limit min axis {}: non-finite",
-                    axis
-                );
-                return;
-            }
-            if let Some(max) = self.axis_soft_limit_max_mm[axis] {
-                if v >= max {
-                    tracing::warn!(
-                        "[BbmAutomatikV2] Reject soft-limit min axis {}: {:.3} >= current max {:.3}",
-                        axis,
-                        v,
-                        max
-                    );
-                    return;
-                }
-            }
+    pub fn set_soft_limit_min(&mut self, axis: usize, min_mm: Option<f32>) -> anyhow::Result<()> {
+        if axis >= self.axes.len() {
+            bail!("Unknown axis {}", axis);
         }
-        self.axis_soft_limit_min_mm[axis] = min_mm;
-        tracing::info!(
-            "[BbmAutomatikV2] Soft-limit min axis {} = {:?} mm",
-            axis,
-            min_mm
-        );
-        calibration::save(
-            &self.teach_positions,
-            &self.axis_soft_limit_max_mm,
-            &self.axis_soft_limit_min_mm,
-        );
+        self.axes[axis].set_soft_limit_min(min_mm)?;
+        self.save_calibration();
         self.emit_state();
+        Ok(())
     }
 
     /// Teach-in: capture the current axis position as the upper soft-limit.
-    pub fn teach_soft_limit_max(&mut self, axis: usize) {
+    pub fn teach_soft_limit_max(&mut self, axis: usize) -> anyhow::Result<()> {
         if axis >= self.axes.len() {
-            return;
+            bail!("Unknown axis {}", axis);
         }
         let pos_mm = (self.current_logical_mm(axis) * 1000.0).round() / 1000.0;
-        self.set_soft_limit_max(axis, Some(pos_mm));
+        self.set_soft_limit_max(axis, Some(pos_mm))
     }
 
     /// Teach-in: capture the current axis position as the lower soft-limit.
-    pub fn teach_soft_limit_min(&mut self, axis: usize) {
+    pub fn teach_soft_limit_min(&mut self, axis: usize) -> anyhow::Result<()> {
         if axis >= self.axes.len() {
-            return;
+            bail!("Unknown axis {}", axis);
         }
         let pos_mm = (self.current_logical_mm(axis) * 1000.0).round() / 1000.0;
-        self.set_soft_limit_min(axis, Some(pos_mm));
+        self.set_soft_limit_min(axis, Some(pos_mm))
     }
 
-    /// Drive to a saved teach position. No-op if slot is empty.
-    pub fn goto_teach_position(&mut self, axis: usize, slot: TeachSlot, speed_mm_s: f32) {
+    /// Drive to a saved teach position. Rejected if the slot is empty or
+    /// the move is not allowed, see [`Self::check_move_allowed`].
+    pub fn goto_teach_position(
+        &mut self,
+        axis: usize,
+        slot: TeachSlot,
+        speed_mm_s: f32,
+    ) -> anyhow::Result<()> {
         if axis >= self.teach_positions.len() {
-            return;
+            bail!("Unknown axis {}", axis);
         }
         let t = &self.teach_positions[axis];
         let pos = match slot {
@@ -2172,15 +1868,9 @@ impl BbmAutomatikV2 {
         };
         let pos = match pos {
             Some(p) => p,
-            None => {
-                tracing::warn!(
-                    "[BbmAutomatikV2] Cannot go to axis {} slot {:?}: empty",
-                    axis,
-                    slot
-                );
-                return;
-            }
+            None => bail!("Cannot go to axis {} slot {:?}: empty", axis, slot),
         };
+        self.move_to_position_mm(axis, pos, speed_mm_s)?;
         tracing::info!(
             "[BbmAutomatikV2] Goto axis {} slot {:?} -> {:.3} mm at {:.1} mm/s",
             axis,
@@ -2188,6 +1878,362 @@ impl BbmAutomatikV2 {
             pos,
             speed_mm_s
         );
-        self.move_to_position_mm(axis, pos, speed_mm_s);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethercat_hal::io::digital_input::{DigitalInputDevice, DigitalInputInput};
+    use ethercat_hal::io::digital_output::{DigitalOutputDevice, DigitalOutputOutput};
+    use smol::lock::RwLock;
+
+    use super::*;
+    use crate::linear_pto_axis::LinearPtoAxisConfig;
+    use crate::linear_pto_axis::tests::{FakePto, MECHANICS, fake_axis};
+
+    /// The EL1008 and EL2008 of the machine
+    struct FakeIo {
+        inputs: [bool; 8],
+        outputs: [bool; 8],
+    }
+
+    impl DigitalInputDevice<usize> for FakeIo {
+        fn get_input(&self, port: usize) -> Result<DigitalInputInput, anyhow::Error> {
+            Ok(DigitalInputInput {
+                value: self.inputs[port],
+            })
+        }
+    }
+
+    impl DigitalOutputDevice<usize> for FakeIo {
+        fn set_output(&mut self, port: usize, value: DigitalOutputOutput) {
+            self.outputs[port] = value.into();
+        }
+        fn get_output(&self, port: usize) -> DigitalOutputOutput {
+            self.outputs[port].into()
+        }
+    }
+
+    struct Fixture {
+        machine: BbmAutomatikV2,
+        io: Arc<RwLock<FakeIo>>,
+        ptos: [Arc<RwLock<FakePto>>; 3],
+    }
+
+    /// A machine with closed doors and homed axes at zero
+    fn fixture() -> Fixture {
+        let mut inputs = [false; 8];
+        inputs[inputs::TUER] = true;
+        let io = Arc::new(RwLock::new(FakeIo {
+            inputs,
+            outputs: [false; 8],
+        }));
+        let (axes, ptos): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| fake_axis(LinearPtoAxisConfig::new(MECHANICS)))
+            .unzip();
+        let (sender, receiver) = smol::channel::unbounded();
+        let mut machine = BbmAutomatikV2 {
+            api_receiver: receiver,
+            api_sender: sender,
+            machine_identification_unique: MachineIdentificationUnique {
+                machine_identification: MachineIdentification {
+                    vendor: VENDOR_QITECH,
+                    machine: BBM_AUTOMATIK_V2,
+                },
+                serial: 1,
+            },
+            namespace: BbmAutomatikV2Namespace { namespace: None },
+            last_state_emit: Instant::now(),
+            main_sender: None,
+            digital_inputs: std::array::from_fn(|i| DigitalInput::new(io.clone(), i)),
+            digital_outputs: std::array::from_fn(|i| DigitalOutput::new(io.clone(), i)),
+            output_states: [false; 8],
+            axes: axes.try_into().unwrap(),
+            schieber_home_pending: false,
+            door_interlock_active: false,
+            interlocks: InterlockEngine::default(),
+            auto_sequence: None,
+            teach_positions: Default::default(),
+            programs: program::ProgramFile::default(),
+            production: ProductionTracker::new(3),
+            interpolation: None,
+            last_debug_log: None,
+        };
+        machine.rebuild_interlocks();
+        Fixture {
+            machine,
+            io,
+            ptos: ptos.try_into().ok().unwrap(),
+        }
+    }
+
+    impl Fixture {
+        /// Runs `ops` as the auto-sequence
+        fn start(&mut self, ops: Vec<program::Op>) {
+            self.machine.auto_sequence = Some(AutoSequenceState {
+                program: program::default_program(),
+                speed_preset: program::default_speed_presets().remove(0),
+                total_sets: 1,
+                ops,
+                pc: 0,
+                loops: Vec::new(),
+                wait_until: None,
+                restore: Vec::new(),
+                pause_requested: false,
+                paused: false,
+                checkpoint: None,
+                teach: Default::default(),
+            });
+        }
+
+        fn sequence(&self) -> &AutoSequenceState {
+            self.machine.auto_sequence.as_ref().unwrap()
+        }
+
+        fn set_door_closed(&self, closed: bool) {
+            smol::block_on(self.io.write()).inputs[inputs::TUER] = closed;
+        }
+
+        /// Lets the moves in flight arrive at their targets
+        fn finish_moves(&mut self) {
+            for pto in &self.ptos {
+                let mut device = smol::block_on(pto.write());
+                if device.output.go_counter {
+                    device.input.counter_value = device.output.target_counter_value;
+                    device.input.select_end_counter = true;
+                }
+            }
+            for _ in 0..10 {
+                for axis in &mut self.machine.axes {
+                    axis.update(Instant::now());
+                }
+            }
+        }
+
+        /// (rot, gelb, grün) as written to the hardware
+        fn ampel(&self) -> (bool, bool, bool) {
+            let io = smol::block_on(self.io.read());
+            (
+                io.outputs[outputs::AMPEL_ROT],
+                io.outputs[outputs::AMPEL_GELB],
+                io.outputs[outputs::AMPEL_GRUEN],
+            )
+        }
+    }
+
+    fn move_mt(position_mm: f32) -> program::Op {
+        program::Op::MoveTo {
+            axis: axes::MT,
+            position_mm,
+            speed_mm_s: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_pause_waits_for_resume_point() {
+        let mut f = fixture();
+        f.start(vec![
+            move_mt(10.0),
+            program::Op::WaitAxes([true, false, false]),
+            program::Op::SetOutput {
+                output: outputs::PNEUMATIK,
+                on: true,
+            },
+        ]);
+        f.machine.update_auto_sequence();
+        assert!(f.machine.axes[axes::MT].is_moving());
+        assert_eq!(f.sequence().checkpoint.as_ref().unwrap().pc, 0);
+
+        // The move in flight finishes, nothing new is issued
+        f.machine.pause_auto_sequence().unwrap();
+        f.machine.update_auto_sequence();
+        assert!(!f.sequence().paused);
+
+        f.finish_moves();
+        assert!(f.machine.update_auto_sequence());
+        let seq = f.sequence();
+        assert!(seq.paused && !seq.pause_requested);
+        assert_eq!(seq.pc, 1);
+        assert_eq!(seq.checkpoint.as_ref().unwrap().pc, 1);
+        assert_eq!(
+            seq.checkpoint.as_ref().unwrap().positions_mm[axes::MT],
+            10.0
+        );
+        assert!(!f.machine.output_states[outputs::PNEUMATIK]);
+        assert_eq!(f.ampel(), (true, true, false));
+
+        // Paused sequences don't advance
+        assert!(!f.machine.update_auto_sequence());
+        assert_eq!(f.sequence().pc, 1);
+    }
+
+    #[test]
+    fn test_interrupt_without_checkpoint_aborts() {
+        let mut f = fixture();
+        f.start(vec![move_mt(10.0)]);
+        f.machine
+            .interrupt_auto_sequence(downtime::DRIVER_ALARM, "driver alarm");
+        assert!(f.machine.auto_sequence.is_none());
+        assert_eq!(f.ampel(), (true, false, false));
+    }
+
+    #[test]
+    fn test_interrupt_keeps_checkpoint() {
+        let mut f = fixture();
+        f.start(vec![
+            move_mt(10.0),
+            program::Op::WaitAxes([true, false, false]),
+        ]);
+        f.machine.update_auto_sequence();
+        f.machine
+            .interrupt_auto_sequence(downtime::DRIVER_ALARM, "driver alarm");
+        let seq = f.sequence();
+        assert!(seq.paused);
+        assert_eq!(seq.checkpoint.as_ref().unwrap().pc, 0);
+        assert!(!f.machine.axes[axes::MT].is_position_mode());
+        assert_eq!(f.ampel(), (true, false, false));
+    }
+
+    #[test]
+    fn test_resume_restores_outputs_but_not_ampel() {
+        let mut f = fixture();
+        f.start(vec![
+            move_mt(10.0),
+            program::Op::WaitAxes([true, false, false]),
+        ]);
+        f.machine.update_auto_sequence();
+        f.machine
+            .interrupt_auto_sequence(downtime::DRIVER_ALARM, "driver alarm");
+        let seq = f.machine.auto_sequence.as_mut().unwrap();
+        let checkpoint = seq.checkpoint.as_mut().unwrap();
+        checkpoint.outputs[outputs::BUERSTENMOTOR] = true;
+        checkpoint.outputs[outputs::PNEUMATIK] = true;
+        checkpoint.outputs[outputs::AMPEL_GRUEN] = true;
+
+        f.machine.resume_auto_sequence().unwrap();
+        assert!(!f.sequence().paused);
+        let io = smol::block_on(f.io.read());
+        assert!(io.outputs[outputs::BUERSTENMOTOR] && io.outputs[outputs::PNEUMATIK]);
+        drop(io);
+        assert_eq!(f.ampel(), (false, true, false));
+    }
+
+    #[test]
+    fn test_resume_drives_back_to_checkpoint() {
+        let mut f = fixture();
+        f.start(vec![
+            move_mt(10.0),
+            program::Op::WaitAxes([true, false, false]),
+            move_mt(20.0),
+            program::Op::WaitAxes([true, false, false]),
+        ]);
+        f.machine.update_auto_sequence();
+        f.finish_moves();
+        f.machine.update_auto_sequence();
+        f.machine
+            .interrupt_auto_sequence(downtime::DRIVER_ALARM, "driver alarm");
+        let checkpoint = f.sequence().checkpoint.as_ref().unwrap();
+        assert_eq!(checkpoint.pc, 2);
+        assert_eq!(checkpoint.positions_mm[axes::MT], 10.0);
+
+        // Moved by hand while paused
+        f.machine.axes[axes::MT].set_position_mm(25.0).unwrap();
+        f.machine.resume_auto_sequence().unwrap();
+        assert_eq!(f.sequence().restore.len(), 2);
+        f.machine.update_auto_sequence();
+        assert_eq!(
+            f.machine.axes[axes::MT].target_position_pulses(),
+            MECHANICS.mm_to_pulses(10.0)
+        );
+    }
+
+    #[test]
+    fn test_recovered_sequence_resumes_at_checkpoint() {
+        let mut f = fixture();
+        let speed_preset = program::default_speed_presets().remove(0);
+        let speed_mm_s = speed_preset.axis_mm_s(axes::MT);
+        f.machine.recover_auto_sequence(checkpoint::Checkpoint {
+            program: program::default_program(),
+            speed_preset,
+            total_sets: 1,
+            teach: Default::default(),
+            pc: 0,
+            loops: Vec::new(),
+            positions_mm: [10.0, 0.0, 0.0],
+            outputs: [false; 8],
+        });
+        assert!(f.sequence().paused);
+        assert!(!f.machine.update_auto_sequence());
+
+        f.machine.resume_auto_sequence().unwrap();
+        assert_eq!(
+            f.sequence().restore,
+            vec![
+                program::Op::MoveTo {
+                    axis: axes::MT,
+                    position_mm: 10.0,
+                    speed_mm_s,
+                },
+                program::Op::WaitAxes([true, false, false]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tripped_interlock_interrupts_sequence() {
+        let mut f = fixture();
+        f.start(vec![
+            program::Op::SetOutput {
+                output: outputs::RUETTELMOTOR,
+                on: true,
+            },
+            move_mt(10.0),
+        ]);
+        f.set_door_closed(false);
+        assert!(f.machine.update_auto_sequence());
+        let seq = f.sequence();
+        assert!(seq.paused);
+        // The move is not issued, it runs again on resume
+        assert_eq!(seq.checkpoint.as_ref().unwrap().pc, 1);
+        assert!(!f.machine.axes[axes::MT].is_moving());
+        assert!(!f.machine.output_states[outputs::RUETTELMOTOR]);
+        assert_eq!(f.ampel(), (true, false, false));
+
+        // Resumable once the door is closed again
+        assert!(f.machine.resume_auto_sequence().is_err());
+        f.set_door_closed(true);
+        f.machine.resume_auto_sequence().unwrap();
+        assert!(f.machine.output_states[outputs::RUETTELMOTOR]);
+    }
+
+    #[test]
+    fn test_blocked_output_rejects_all_outputs() {
+        let mut f = fixture();
+        f.set_door_closed(false);
+        assert!(f.machine.set_all_outputs(true).is_err());
+        assert_eq!(f.machine.output_states, [false; 8]);
+        assert_eq!(smol::block_on(f.io.read()).outputs, [false; 8]);
+        assert!(f.machine.set_buerstenmotor(true).is_err());
+        f.machine.set_pneumatik(true).unwrap();
+
+        f.set_door_closed(true);
+        f.machine.set_all_outputs(true).unwrap();
+        assert_eq!(f.machine.output_states, [true; 8]);
+    }
+
+    #[test]
+    fn test_blocked_move_is_returned() {
+        let mut f = fixture();
+        f.set_door_closed(false);
+        assert!(f.machine.move_to_position_mm(axes::MT, 10.0, 10.0).is_err());
+        assert!(f.machine.jog_relative(axes::MT, 5.0, 10.0).is_err());
+        assert!(!f.machine.axes[axes::MT].is_moving());
+
+        f.set_door_closed(true);
+        f.machine.move_to_position_mm(axes::MT, 10.0, 10.0).unwrap();
+        assert!(f.machine.axes[axes::MT].is_moving());
     }
 }
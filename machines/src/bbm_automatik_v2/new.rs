@@ -1,7 +1,15 @@
 use crate::bbm_automatik_v2::BbmAutomatikV2;
 use crate::bbm_automatik_v2::api::BbmAutomatikV2Namespace;
-use crate::bbm_automatik_v2::calibration;
 use crate::bbm_automatik_v2::roles;
+use crate::bbm_automatik_v2::{
+    ALARM_ACTIVE_LOW, MECHANICS, MT_JERK_MM_S3, PRODUCTION_FILENAME, STEP_LOSS_INVALIDATE_PULSES,
+};
+use crate::bbm_automatik_v2::{axes, calibration, checkpoint, homing, inputs, program};
+use crate::interlock::InterlockEngine;
+use crate::linear_pto_axis::{
+    AlarmInput, HomingStrategy, LinearPtoAxis, LinearPtoAxisConfig, RampSdo, ReferenceSwitch,
+};
+use crate::production_stats::ProductionTracker;
 use smol::block_on;
 use std::time::Instant;
 
@@ -55,16 +63,17 @@ impl MachineNewTrait for BbmAutomatikV2 {
             .0;
 
             // Create DigitalInput array for 8 inputs
-            let digital_inputs = [
-                DigitalInput::new(el1008.clone(), EL1008Port::DI1),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI2),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI3),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI4),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI5),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI6),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI7),
-                DigitalInput::new(el1008.clone(), EL1008Port::DI8),
+            let di_ports = [
+                EL1008Port::DI1,
+                EL1008Port::DI2,
+                EL1008Port::DI3,
+                EL1008Port::DI4,
+                EL1008Port::DI5,
+                EL1008Port::DI6,
+                EL1008Port::DI7,
+                EL1008Port::DI8,
             ];
+            let digital_inputs = di_ports.map(|port| DigitalInput::new(el1008.clone(), port));
 
             // ========== Digital Outputs (1x EL2008) ==========
             let el2008 = get_ethercat_device::<EL2008>(
@@ -196,15 +205,54 @@ impl MachineNewTrait for BbmAutomatikV2 {
 
             tracing::info!("[BbmAutomatikV2] EL2522 #2 configured: Ch1=Drücker (Ch2=unused)");
 
-            // Create PulseTrainOutput array for 3 axes (Bürste is now a digital output)
-            let axes = [
-                PulseTrainOutput::new(el2522_1.clone(), EL2522Port::PTO1), // MT
-                PulseTrainOutput::new(el2522_1.clone(), EL2522Port::PTO2), // Schieber
-                PulseTrainOutput::new(el2522_2.clone(), EL2522Port::PTO1), // Drücker
+            // Linear axes (Bürste is now a digital output). Axes 0 and 1 are on
+            // EL2522 #1, axis 2 on EL2522 #2.
+            let calibration_state = calibration::load();
+            let axis_inputs = [
+                (inputs::ALARM_MT, inputs::REF_MT),
+                (inputs::ALARM_SCHIEBER, inputs::REF_SCHIEBER),
+                (inputs::ALARM_DRUECKER, inputs::REF_DRUECKER),
             ];
+            let ptos = [
+                (el2522_1.clone(), EL2522Port::PTO1, subdevice_index_1, 0), // MT
+                (el2522_1.clone(), EL2522Port::PTO2, subdevice_index_1, 1), // Schieber
+                (el2522_2.clone(), EL2522Port::PTO1, subdevice_index_2, 0), // Drücker
+            ];
+            let names = ["MT", "Schieber", "Drücker"];
+            let axes = std::array::from_fn(|i| {
+                let (device, port, subdevice_index, channel) = ptos[i].clone();
+                let (alarm_input, ref_input) = axis_inputs[i];
+                let config = LinearPtoAxisConfig {
+                    homing: HomingStrategy::ReferenceSwitch(ReferenceSwitch {
+                        input: DigitalInput::new(el1008.clone(), di_ports[ref_input]),
+                        normally_closed: true,
+                        speed_mm_s: homing::HOMING_SPEED_MM_S,
+                        retract_mm: homing::RETRACT_DISTANCE_MM,
+                    }),
+                    alarm: Some(AlarmInput {
+                        input: DigitalInput::new(el1008.clone(), di_ports[alarm_input]),
+                        active_low: ALARM_ACTIVE_LOW,
+                    }),
+                    ramp_sdo: params.sdo_write_u16.clone().map(|write| RampSdo {
+                        write,
+                        subdevice_index,
+                        channel,
+                    }),
+                    // Beckhoff EL252x manual p.139: the falling ramp must be ~10%
+                    // steeper than the rising ramp, otherwise Travel Distance
+                    // Control reaches the target at full speed instead of via the
+                    // slowing-down frequency (imprecise stop / overshoot).
+                    falling_ramp_factor: 0.9,
+                    soft_limit_max_mm: calibration_state.soft_limit_max_mm[i],
+                    soft_limit_min_mm: calibration_state.soft_limit_min_mm[i],
+                    step_loss_invalidate_pulses: Some(STEP_LOSS_INVALIDATE_PULSES),
+                    s_curve_jerk_mm_s3: (i == axes::MT).then_some(MT_JERK_MM_S3),
+                    ..LinearPtoAxisConfig::new(MECHANICS)
+                };
+                LinearPtoAxis::new(names[i], PulseTrainOutput::new(device, port), config)
+            });
 
             let (sender, receiver) = smol::channel::unbounded();
-            let calibration_state = calibration::load();
             let mut machine = Self {
                 api_receiver: receiver,
                 api_sender: sender,
@@ -218,50 +266,22 @@ impl MachineNewTrait for BbmAutomatikV2 {
                 digital_outputs,
                 output_states: [false; 8],
                 axes,
-                axis_speeds: [0; 3],
-                axis_target_speeds: [0; 3],
-                axis_accelerations: [100.0; 3], // Default: 100 mm/s²
-                axis_target_positions: [0; 3],
-                axis_position_mode: [false; 3],
-                axis_position_ignore_cycles: [0; 3],
-                sdo_write_u16: params.sdo_write_u16.clone(),
-                pto_subdevice_indices: [subdevice_index_1, subdevice_index_2],
-                axis_homing_phase: [
-                    crate::bbm_automatik_v2::HomingPhase::Idle,
-                    crate::bbm_automatik_v2::HomingPhase::Idle,
-                    crate::bbm_automatik_v2::HomingPhase::Idle,
-                ],
-                axis_homing_retract_target: [0; 3],
-                axis_homed: [false; 3],
                 schieber_home_pending: false,
-                axis_step_loss: [false; 3],
-                axis_position_offset: [
-                    crate::bbm_automatik_v2::POSITION_OFFSET_PULSES,
-                    crate::bbm_automatik_v2::POSITION_OFFSET_PULSES,
-                    crate::bbm_automatik_v2::POSITION_OFFSET_PULSES,
-                ],
-                axis_alarm_active: [false; 3],
                 door_interlock_active: false,
+                interlocks: InterlockEngine::default(),
                 auto_sequence: None,
                 teach_positions: calibration_state.axes,
-                axis_soft_limit_max_mm: calibration_state.soft_limit_max_mm,
-                axis_soft_limit_min_mm: calibration_state.soft_limit_min_mm,
+                programs: program::ProgramFile::load(),
+                production: ProductionTracker::load(PRODUCTION_FILENAME, 3),
+                interpolation: None,
                 last_debug_log: None,
             };
 
-            // Queue the virtual-zero offset write to every axis. With
-            // POSITION_OFFSET_PULSES already stored in axis_position_offset
-            // above, logical reads are correct as soon as the hardware
-            // applies this set_counter (next cycle, ~700 µs). The act()
-            // loop clears set_counter after set_counter_done.
-            for i in 0..machine.axes.len() {
-                let mut output = machine.axes[i].get_output();
-                output.set_counter = true;
-                output.set_counter_value =
-                    crate::bbm_automatik_v2::POSITION_OFFSET_PULSES;
-                machine.axes[i].set_output(output);
+            machine.rebuild_interlocks();
+            // A sequence that was running at power loss comes back paused
+            if let Some(checkpoint) = checkpoint::load() {
+                machine.recover_auto_sequence(checkpoint);
             }
-
             machine.emit_state();
             Ok(machine)
         })
@@ -0,0 +1,851 @@
+//! Auto-sequence programs
+//!
+//! A [`SequenceProgram`] is the list of steps the auto-sequence executes: moves to teach
+//! positions, waits, outputs and loops. Programs and speed presets are persisted next to the
+//! calibration and edited from the UI. The built-in "Standard" program is the filter cycle the
+//! machine always ran (wobble, Schieber, Drücker, return, 3 blocks of 19 cycles per set).
+
+use super::{AxisTeachPositions, SCHIEBER_INTERLOCK_TOLERANCE_MM, TeachSlot, axes, outputs};
+use crate::state_file;
+use anyhow::{anyhow, bail};
+use control_core_derive::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const FILENAME: &str = "bbm-automatik-v2-programs.json";
+
+/// Name of the built-in program, used when a start request names none
+pub const DEFAULT_PROGRAM: &str = "Standard";
+
+/// Maximum number of steps per program (all sections)
+const MAX_STEPS: usize = 256;
+/// Maximum nesting of loops, the implicit set loop not counted
+const MAX_LOOP_DEPTH: usize = 4;
+/// Longest accepted wait step
+const MAX_WAIT_MS: u64 = 60 * 60 * 1000;
+const MAX_NAME_LEN: usize = 32;
+
+/// Axis names as used in validation messages
+const AXIS_NAMES: [&str; 3] = ["Transporter", "Schieber", "Drücker"];
+
+/// Defaults of the standard program
+pub mod standard {
+    /// MT moves this far back per fill cycle.
+    pub const MT_ADVANCE_PER_CYCLE: f32 = 10.0;
+    /// Schieber wobble amplitude around its start position (mm).
+    pub const SCHIEBER_WOBBLE: f32 = 1.0;
+    pub const CYCLES_PER_BLOCK: u32 = 19;
+    pub const BLOCKS_PER_SET: u32 = 3;
+}
+
+/// Axis speeds of a named preset (mm/s)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SpeedPreset {
+    pub name: String,
+    pub mt_mm_s: f32,
+    pub schieber_mm_s: f32,
+    pub druecker_mm_s: f32,
+}
+
+impl SpeedPreset {
+    pub fn axis_mm_s(&self, axis: usize) -> f32 {
+        match axis {
+            axes::MT => self.mt_mm_s,
+            axes::SCHIEBER => self.schieber_mm_s,
+            _ => self.druecker_mm_s,
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_name(&self.name)?;
+        for (axis, axis_name) in AXIS_NAMES.iter().enumerate() {
+            let speed = self.axis_mm_s(axis);
+            if !speed.is_finite() || speed <= 0.0 {
+                bail!(
+                    "Speed preset {}: {} speed must be positive, got {}",
+                    self.name,
+                    axis_name,
+                    speed
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn default_speed_presets() -> Vec<SpeedPreset> {
+    let preset = |name: &str, mt_mm_s, schieber_mm_s, druecker_mm_s| SpeedPreset {
+        name: name.to_string(),
+        mt_mm_s,
+        schieber_mm_s,
+        druecker_mm_s,
+    };
+    vec![
+        preset("slow", 30.0, 40.0, 40.0),
+        preset("medium", 60.0, 80.0, 80.0),
+        preset("fast", 100.0, 150.0, 150.0),
+    ]
+}
+
+/// Where a move goes: a teach slot of the moving axis plus offsets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AxisTarget {
+    pub slot: TeachSlot,
+    /// Fixed offset from the teach position (mm)
+    #[serde(default)]
+    pub offset_mm: f32,
+    /// Added once per iteration of the innermost enclosing loop (1-based),
+    /// e.g. -10 moves the Transporter back 10 mm every cycle
+    #[serde(default)]
+    pub offset_per_iteration_mm: f32,
+}
+
+impl AxisTarget {
+    fn teach(slot: TeachSlot, offset_mm: f32) -> Self {
+        Self {
+            slot,
+            offset_mm,
+            offset_per_iteration_mm: 0.0,
+        }
+    }
+
+    fn base_mm(&self, teach: &AxisTeachPositions) -> Option<f32> {
+        match self.slot {
+            TeachSlot::Start => teach.start_mm,
+            TeachSlot::Ziel => teach.ziel_mm,
+            TeachSlot::Custom1 => teach.custom1.as_ref().map(|p| p.position_mm),
+            TeachSlot::Custom2 => teach.custom2.as_ref().map(|p| p.position_mm),
+        }
+    }
+
+    /// Target position in mm, `None` if the teach slot is empty
+    pub fn resolve(&self, teach: &AxisTeachPositions, iteration: u32) -> Option<f32> {
+        self.base_mm(teach)
+            .map(|base| base + self.offset_mm + self.offset_per_iteration_mm * iteration as f32)
+    }
+}
+
+/// One step of a program. Loops are flat: the steps between `Loop` and the
+/// matching `EndLoop` are repeated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value")]
+pub enum ProgramStep {
+    /// Start a move of an axis, does not wait for it to arrive
+    Move {
+        axis: usize,
+        target: AxisTarget,
+        /// Speed in mm/s, the speed preset of the run if not set
+        speed_mm_s: Option<f32>,
+    },
+    /// Wait until the given axes reached their targets
+    WaitAxes {
+        axes: Vec<usize>,
+    },
+    /// Wait a fixed time
+    Wait {
+        duration_ms: u64,
+    },
+    /// Switch a digital output. The Ampel outputs belong to the machine.
+    SetOutput {
+        output: usize,
+        on: bool,
+    },
+    /// Repeat the steps up to the matching `EndLoop` `count` times
+    Loop {
+        count: u32,
+    },
+    EndLoop,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SequenceProgram {
+    pub name: String,
+    /// Run once before the first set
+    #[serde(default)]
+    pub start_steps: Vec<ProgramStep>,
+    /// Run once per set
+    pub set_steps: Vec<ProgramStep>,
+    /// Run once after the last set
+    #[serde(default)]
+    pub end_steps: Vec<ProgramStep>,
+}
+
+/// The filter cycle the machine shipped with
+pub fn default_program() -> SequenceProgram {
+    use ProgramStep::*;
+    use TeachSlot::{Start, Ziel};
+
+    let move_to = |axis, target| Move {
+        axis,
+        target,
+        speed_mm_s: None,
+    };
+    let wait_for = |a: &[usize]| WaitAxes { axes: a.to_vec() };
+    let wobble = standard::SCHIEBER_WOBBLE;
+
+    SequenceProgram {
+        name: DEFAULT_PROGRAM.to_string(),
+        // Move MT and Drücker to their start positions. The Schieber is
+        // intentionally NOT commanded here: if the Drücker is still extended
+        // (above start), moving the Schieber would shear off its tips. Its
+        // first move is the opening wobble of cycle 1, by which point the
+        // Drücker is at start.
+        start_steps: vec![
+            move_to(axes::MT, AxisTarget::teach(Start, 0.0)),
+            move_to(axes::DRUECKER, AxisTarget::teach(Start, 0.0)),
+            wait_for(&[axes::MT, axes::DRUECKER]),
+        ],
+        set_steps: vec![
+            Loop {
+                count: standard::BLOCKS_PER_SET,
+            },
+            Loop {
+                count: standard::CYCLES_PER_BLOCK,
+            },
+            // Wobble the Schieber around its start
+            move_to(axes::SCHIEBER, AxisTarget::teach(Start, wobble)),
+            wait_for(&[axes::SCHIEBER]),
+            move_to(axes::SCHIEBER, AxisTarget::teach(Start, -wobble)),
+            wait_for(&[axes::SCHIEBER]),
+            // Schieber to Ziel: filters fall into the magazine. Ziel is a
+            // safe Schieber position for the Drücker (interlock B).
+            move_to(axes::SCHIEBER, AxisTarget::teach(Ziel, 0.0)),
+            wait_for(&[axes::SCHIEBER]),
+            // Drücker pushes the hanging filters
+            move_to(axes::DRUECKER, AxisTarget::teach(Ziel, 0.0)),
+            wait_for(&[axes::DRUECKER]),
+            // Drücker returns while the MT advances. The Schieber MUST NOT
+            // move yet, it would shear off the extended Drücker tips.
+            move_to(axes::DRUECKER, AxisTarget::teach(Start, 0.0)),
+            move_to(
+                axes::MT,
+                AxisTarget {
+                    slot: Start,
+                    offset_mm: 0.0,
+                    offset_per_iteration_mm: -standard::MT_ADVANCE_PER_CYCLE,
+                },
+            ),
+            wait_for(&[axes::DRUECKER]),
+            // Drücker is back at start, now the Schieber returns
+            move_to(axes::SCHIEBER, AxisTarget::teach(Start, 0.0)),
+            wait_for(&[axes::SCHIEBER, axes::MT]),
+            EndLoop,
+            EndLoop,
+        ],
+        // MT drives to its Ziel for part removal (Entnahme)
+        end_steps: vec![
+            move_to(axes::MT, AxisTarget::teach(Ziel, 0.0)),
+            wait_for(&[axes::MT]),
+        ],
+    }
+}
+
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    if name.trim().is_empty() || name.len() > MAX_NAME_LEN {
+        bail!(
+            "Invalid name {:?}: must be 1 to {} bytes",
+            name,
+            MAX_NAME_LEN
+        );
+    }
+    Ok(())
+}
+
+/// A program step resolved for execution, loops carry their jump targets
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Move {
+        axis: usize,
+        target: AxisTarget,
+        speed_mm_s: f32,
+    },
+    /// Move to an absolute position, used to restore positions on resume
+    MoveTo {
+        axis: usize,
+        position_mm: f32,
+        speed_mm_s: f32,
+    },
+    WaitAxes([bool; 3]),
+    Wait(Duration),
+    SetOutput {
+        output: usize,
+        on: bool,
+    },
+    /// `end` is the index of the matching [`Op::EndLoop`], `sets` marks the
+    /// implicit loop around the set steps
+    Loop {
+        count: u32,
+        end: usize,
+        sets: bool,
+    },
+    EndLoop {
+        start: usize,
+    },
+}
+
+impl Op {
+    /// Name of an op that takes time, for the step-time statistics
+    pub fn timed_label(&self) -> Option<String> {
+        match self {
+            Self::WaitAxes(mask) => {
+                let axes: Vec<&str> = (0..mask.len())
+                    .filter(|&i| mask[i])
+                    .map(|i| AXIS_NAMES[i])
+                    .collect();
+                Some(format!("wait {}", axes.join("+")))
+            }
+            Self::Wait(duration) => Some(format!("dwell {} ms", duration.as_millis())),
+            _ => None,
+        }
+    }
+}
+
+/// Deepest nesting of the program loops inside the sets loop, 0 when the
+/// set steps have no loops
+pub fn set_loop_depth(ops: &[Op]) -> usize {
+    let mut in_sets = false;
+    let mut depth = 0;
+    let mut max = 0;
+    for op in ops {
+        match op {
+            Op::Loop { sets: true, .. } => in_sets = true,
+            Op::Loop { .. } if in_sets => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            Op::EndLoop { .. } if in_sets => match depth {
+                0 => in_sets = false,
+                _ => depth -= 1,
+            },
+            _ => {}
+        }
+    }
+    max
+}
+
+impl SequenceProgram {
+    /// Checks the structure of the program: names, axes, outputs, loops
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_name(&self.name)?;
+        let steps = self.start_steps.len() + self.set_steps.len() + self.end_steps.len();
+        if steps > MAX_STEPS {
+            bail!(
+                "Program {}: {} steps, at most {}",
+                self.name,
+                steps,
+                MAX_STEPS
+            );
+        }
+        if self.set_steps.is_empty() {
+            bail!("Program {}: no set steps", self.name);
+        }
+        let default_preset = &default_speed_presets()[0];
+        self.compile(default_preset, 1).map(|_| ())
+    }
+
+    /// Flattens the program into [`Op`]s with the set steps looped
+    /// `total_sets` times
+    pub fn compile(&self, preset: &SpeedPreset, total_sets: u32) -> anyhow::Result<Vec<Op>> {
+        if total_sets == 0 {
+            bail!("Program {}: total sets must be at least 1", self.name);
+        }
+        let mut ops = Vec::new();
+        self.compile_section("start step", &self.start_steps, preset, &mut ops)?;
+        let sets_start = ops.len();
+        ops.push(Op::Loop {
+            count: total_sets,
+            end: 0,
+            sets: true,
+        });
+        self.compile_section("set step", &self.set_steps, preset, &mut ops)?;
+        let end = ops.len();
+        ops.push(Op::EndLoop { start: sets_start });
+        if let Op::Loop { end: e, .. } = &mut ops[sets_start] {
+            *e = end;
+        }
+        self.compile_section("end step", &self.end_steps, preset, &mut ops)?;
+        Ok(ops)
+    }
+
+    fn compile_section(
+        &self,
+        section: &str,
+        steps: &[ProgramStep],
+        preset: &SpeedPreset,
+        ops: &mut Vec<Op>,
+    ) -> anyhow::Result<()> {
+        let mut open_loops: Vec<usize> = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let err =
+                |msg: String| anyhow!("Program {}, {} {}: {}", self.name, section, i + 1, msg);
+            let op = match step {
+                ProgramStep::Move {
+                    axis,
+                    target,
+                    speed_mm_s,
+                } => {
+                    if *axis >= AXIS_NAMES.len() {
+                        return Err(err(format!("unknown axis {}", axis)));
+                    }
+                    if !target.offset_mm.is_finite() || !target.offset_per_iteration_mm.is_finite()
+                    {
+                        return Err(err("non-finite offset".to_string()));
+                    }
+                    let speed_mm_s = speed_mm_s.unwrap_or(preset.axis_mm_s(*axis));
+                    if !speed_mm_s.is_finite() || speed_mm_s <= 0.0 {
+                        return Err(err(format!("speed must be positive, got {}", speed_mm_s)));
+                    }
+                    Op::Move {
+                        axis: *axis,
+                        target: target.clone(),
+                        speed_mm_s,
+                    }
+                }
+                ProgramStep::WaitAxes { axes } => {
+                    if axes.is_empty() {
+                        return Err(err("no axes to wait for".to_string()));
+                    }
+                    let mut mask = [false; 3];
+                    for &axis in axes {
+                        if axis >= mask.len() {
+                            return Err(err(format!("unknown axis {}", axis)));
+                        }
+                        mask[axis] = true;
+                    }
+                    Op::WaitAxes(mask)
+                }
+                ProgramStep::Wait { duration_ms } => {
+                    if *duration_ms > MAX_WAIT_MS {
+                        return Err(err(format!("wait longer than {} ms", MAX_WAIT_MS)));
+                    }
+                    Op::Wait(Duration::from_millis(*duration_ms))
+                }
+                ProgramStep::SetOutput { output, on } => {
+                    if *output > outputs::LUEFTER {
+                        return Err(err(format!("unknown output {}", output)));
+                    }
+                    if [
+                        outputs::AMPEL_GRUEN,
+                        outputs::AMPEL_GELB,
+                        outputs::AMPEL_ROT,
+                    ]
+                    .contains(output)
+                    {
+                        return Err(err("the Ampel is switched by the machine".to_string()));
+                    }
+                    Op::SetOutput {
+                        output: *output,
+                        on: *on,
+                    }
+                }
+                ProgramStep::Loop { count } => {
+                    if *count == 0 {
+                        return Err(err("loop count must be at least 1".to_string()));
+                    }
+                    if open_loops.len() >= MAX_LOOP_DEPTH {
+                        return Err(err(format!("loops nested deeper than {}", MAX_LOOP_DEPTH)));
+                    }
+                    open_loops.push(ops.len());
+                    Op::Loop {
+                        count: *count,
+                        end: 0,
+                        sets: false,
+                    }
+                }
+                ProgramStep::EndLoop => {
+                    let Some(start) = open_loops.pop() else {
+                        return Err(err("EndLoop without Loop".to_string()));
+                    };
+                    if start + 1 == ops.len() {
+                        return Err(err("empty loop".to_string()));
+                    }
+                    let end = ops.len();
+                    if let Op::Loop { end: e, .. } = &mut ops[start] {
+                        *e = end;
+                    }
+                    Op::EndLoop { start }
+                }
+            };
+            ops.push(op);
+        }
+        if !open_loops.is_empty() {
+            bail!("Program {}: {} Loop without EndLoop", self.name, section);
+        }
+        Ok(())
+    }
+}
+
+/// What a program is checked against before it starts
+#[derive(Debug, Clone)]
+pub struct ValidationContext<'a> {
+    pub teach: &'a [AxisTeachPositions; 3],
+    /// Current logical positions (mm)
+    pub positions_mm: [f32; 3],
+    pub soft_limit_min_mm: [Option<f32>; 3],
+    pub soft_limit_max_mm: [Option<f32>; 3],
+    /// Interlock A: the Schieber must not move while the Drücker is above
+    /// this (plus tolerance)
+    pub druecker_start_mm: f32,
+    /// Interlock B: the Drücker may only move while the Schieber is at/below
+    /// its start or at/beyond its ziel (with tolerance)
+    pub schieber_start_mm: f32,
+    pub schieber_ziel_mm: f32,
+}
+
+/// Range each axis may be in while the program runs: from where it was to
+/// where it was sent until a `WaitAxes` settles it at its target
+#[derive(Debug, Clone, Copy)]
+struct AxisSpan {
+    lo: f32,
+    hi: f32,
+    target: f32,
+}
+
+/// Checks compiled ops before start: all referenced teach slots are set,
+/// every target is within the soft limits and no move can trip the
+/// Schieber ⟷ Drücker interlocks. Loops are checked on their first and
+/// last iteration, where the per-iteration offsets are at their extremes.
+pub fn validate(ops: &[Op], ctx: &ValidationContext) -> anyhow::Result<()> {
+    let mut missing: Vec<String> = Vec::new();
+    let moves = ops.iter().filter_map(|op| match op {
+        Op::Move { axis, target, .. } => Some((*axis, target)),
+        _ => None,
+    });
+    for (axis, target) in moves {
+        if target.base_mm(&ctx.teach[axis]).is_none() {
+            let name = format!(
+                "{} {}",
+                AXIS_NAMES[axis],
+                slot_name(ctx.teach, axis, target.slot)
+            );
+            if !missing.contains(&name) {
+                missing.push(name);
+            }
+        }
+    }
+    if !missing.is_empty() {
+        bail!("Missing teach positions: {}", missing.join(", "));
+    }
+
+    let mut spans = ctx.positions_mm.map(|p| AxisSpan {
+        lo: p,
+        hi: p,
+        target: p,
+    });
+    simulate(ops, 0..ops.len(), 0, ctx, &mut spans)
+}
+
+fn slot_name(teach: &[AxisTeachPositions; 3], axis: usize, slot: TeachSlot) -> String {
+    let t = &teach[axis];
+    match slot {
+        TeachSlot::Start => "Start".to_string(),
+        TeachSlot::Ziel => "Ziel".to_string(),
+        TeachSlot::Custom1 => t
+            .custom1
+            .as_ref()
+            .map_or("Position 1".to_string(), |p| p.name.clone()),
+        TeachSlot::Custom2 => t
+            .custom2
+            .as_ref()
+            .map_or("Position 2".to_string(), |p| p.name.clone()),
+    }
+}
+
+fn simulate(
+    ops: &[Op],
+    range: std::ops::Range<usize>,
+    iteration: u32,
+    ctx: &ValidationContext,
+    spans: &mut [AxisSpan; 3],
+) -> anyhow::Result<()> {
+    let mut pc = range.start;
+    while pc < range.end {
+        match &ops[pc] {
+            Op::Loop { count, end, .. } => {
+                simulate(ops, pc + 1..*end, 1, ctx, spans)?;
+                if *count > 1 {
+                    simulate(ops, pc + 1..*end, *count, ctx, spans)?;
+                }
+                pc = *end + 1;
+                continue;
+            }
+            Op::Move { axis, target, .. } => {
+                // Safe to unwrap: missing slots were rejected above.
+                let target_mm = target.resolve(&ctx.teach[*axis], iteration).unwrap();
+                simulate_move(*axis, target_mm, ctx, spans)?;
+            }
+            Op::MoveTo {
+                axis, position_mm, ..
+            } => simulate_move(*axis, *position_mm, ctx, spans)?,
+            Op::WaitAxes(mask) => {
+                for (span, _) in spans.iter_mut().zip(mask).filter(|(_, wait)| **wait) {
+                    span.lo = span.target;
+                    span.hi = span.target;
+                }
+            }
+            Op::Wait(_) | Op::SetOutput { .. } | Op::EndLoop { .. } => {}
+        }
+        pc += 1;
+    }
+    Ok(())
+}
+
+/// Checks a move and widens the span of the axis to its target
+fn simulate_move(
+    axis: usize,
+    target_mm: f32,
+    ctx: &ValidationContext,
+    spans: &mut [AxisSpan; 3],
+) -> anyhow::Result<()> {
+    check_move(axis, target_mm, ctx, spans)?;
+    let span = &mut spans[axis];
+    span.lo = span.lo.min(target_mm);
+    span.hi = span.hi.max(target_mm);
+    span.target = target_mm;
+    Ok(())
+}
+
+fn check_move(
+    axis: usize,
+    target_mm: f32,
+    ctx: &ValidationContext,
+    spans: &[AxisSpan; 3],
+) -> anyhow::Result<()> {
+    let name = AXIS_NAMES[axis];
+    if let Some(max) = ctx.soft_limit_max_mm[axis].filter(|max| target_mm > *max) {
+        bail!(
+            "{} target {:.1} mm is above the soft limit max {:.1} mm",
+            name,
+            target_mm,
+            max
+        );
+    }
+    if let Some(min) = ctx.soft_limit_min_mm[axis].filter(|min| target_mm < *min) {
+        bail!(
+            "{} target {:.1} mm is below the soft limit min {:.1} mm",
+            name,
+            target_mm,
+            min
+        );
+    }
+
+    let tol = SCHIEBER_INTERLOCK_TOLERANCE_MM;
+    if axis == axes::SCHIEBER {
+        let druecker = spans[axes::DRUECKER];
+        if druecker.hi > ctx.druecker_start_mm + tol {
+            bail!(
+                "Schieber move to {:.1} mm while the Drücker may be extended up to {:.1} mm (interlock A)",
+                target_mm,
+                druecker.hi
+            );
+        }
+    }
+    if axis == axes::DRUECKER {
+        let schieber = spans[axes::SCHIEBER];
+        let at_start = schieber.hi <= ctx.schieber_start_mm + tol;
+        let at_ziel = schieber.lo >= ctx.schieber_ziel_mm - tol;
+        if !(at_start || at_ziel) {
+            bail!(
+                "Drücker move to {:.1} mm while the Schieber may be mid-travel between {:.1} and {:.1} mm (interlock B)",
+                target_mm,
+                schieber.lo,
+                schieber.hi
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Persisted programs and speed presets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramFile {
+    #[serde(default = "default_programs")]
+    pub programs: Vec<SequenceProgram>,
+    #[serde(default = "default_speed_presets")]
+    pub speed_presets: Vec<SpeedPreset>,
+}
+
+fn default_programs() -> Vec<SequenceProgram> {
+    vec![default_program()]
+}
+
+impl Default for ProgramFile {
+    fn default() -> Self {
+        Self {
+            programs: default_programs(),
+            speed_presets: default_speed_presets(),
+        }
+    }
+}
+
+impl ProgramFile {
+    pub fn program(&self, name: &str) -> Option<&SequenceProgram> {
+        self.programs.iter().find(|p| p.name == name)
+    }
+
+    pub fn speed_preset(&self, name: &str) -> Option<&SpeedPreset> {
+        self.speed_presets.iter().find(|p| p.name == name)
+    }
+
+    /// Inserts or replaces (by name) a program
+    pub fn upsert_program(&mut self, program: SequenceProgram) -> anyhow::Result<()> {
+        program.validate()?;
+        match self.programs.iter_mut().find(|p| p.name == program.name) {
+            Some(existing) => *existing = program,
+            None => self.programs.push(program),
+        }
+        Ok(())
+    }
+
+    /// Removes a program, the standard program can't be removed
+    pub fn remove_program(&mut self, name: &str) -> anyhow::Result<()> {
+        if name == DEFAULT_PROGRAM {
+            bail!("Program {} can't be deleted", DEFAULT_PROGRAM);
+        }
+        let len = self.programs.len();
+        self.programs.retain(|p| p.name != name);
+        if self.programs.len() == len {
+            bail!("Unknown program {}", name);
+        }
+        Ok(())
+    }
+
+    /// Inserts or replaces (by name) a speed preset
+    pub fn upsert_speed_preset(&mut self, preset: SpeedPreset) -> anyhow::Result<()> {
+        preset.validate()?;
+        match self
+            .speed_presets
+            .iter_mut()
+            .find(|p| p.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.speed_presets.push(preset),
+        }
+        Ok(())
+    }
+
+    /// Load the persisted programs. Returns the defaults when no file exists
+    /// or it can't be parsed; the standard program is always present.
+    pub fn load() -> Self {
+        let p = state_file::path(FILENAME);
+        let mut file = match std::fs::read_to_string(&p) {
+            Ok(s) => match serde_json::from_str::<ProgramFile>(&s) {
+                Ok(f) => {
+                    tracing::info!("[BbmAutomatikV2] Loaded programs from {}", p.display());
+                    f
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "[BbmAutomatikV2] Program file at {} is corrupt ({}) - starting from defaults",
+                        p.display(),
+                        e
+                    );
+                    Default::default()
+                }
+            },
+            Err(_) => Default::default(),
+        };
+        if file.program(DEFAULT_PROGRAM).is_none() {
+            file.programs.insert(0, default_program());
+        }
+        file
+    }
+
+    /// Atomically persist the programs. Errors are logged, not propagated -
+    /// the in-memory state is still correct.
+    pub fn save(&self) {
+        let p = state_file::path(FILENAME);
+        let json = match serde_json::to_string_pretty(self) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("[BbmAutomatikV2] Failed to serialize programs: {}", e);
+                return;
+            }
+        };
+        state_file::write_atomic(&p, &json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn teach() -> [AxisTeachPositions; 3] {
+        let positions = |start, ziel| AxisTeachPositions {
+            start_mm: Some(start),
+            ziel_mm: Some(ziel),
+            custom1: None,
+            custom2: None,
+        };
+        [
+            positions(200.0, 220.0),
+            positions(7.0, 51.0),
+            positions(60.0, 100.0),
+        ]
+    }
+
+    fn context(teach: &[AxisTeachPositions; 3]) -> ValidationContext<'_> {
+        ValidationContext {
+            teach,
+            positions_mm: [0.0; 3],
+            soft_limit_min_mm: [None; 3],
+            soft_limit_max_mm: [Some(230.0), Some(53.0), Some(107.0)],
+            druecker_start_mm: 60.0,
+            schieber_start_mm: 7.0,
+            schieber_ziel_mm: 51.0,
+        }
+    }
+
+    #[test]
+    fn test_default_program_is_valid() {
+        let program = default_program();
+        program.validate().unwrap();
+        let ops = program.compile(&default_speed_presets()[0], 2).unwrap();
+        let teach = teach();
+        validate(&ops, &context(&teach)).unwrap();
+    }
+
+    #[test]
+    fn test_compile_loops() {
+        let ops = default_program()
+            .compile(&default_speed_presets()[1], 1)
+            .unwrap();
+        // 3 start steps, then the set loop wrapping the two program loops
+        assert_eq!(
+            ops[3],
+            Op::Loop {
+                count: 1,
+                end: ops.len() - 3,
+                sets: true
+            }
+        );
+        assert!(matches!(ops[4], Op::Loop { count: 3, .. }));
+        assert_eq!(ops[ops.len() - 3], Op::EndLoop { start: 3 });
+        // Blocks and cycles
+        assert_eq!(set_loop_depth(&ops), 2);
+
+        let mut program = default_program();
+        program.set_steps.pop();
+        assert!(program.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_soft_limit_and_interlock() {
+        let teach = teach();
+        let mut ctx = context(&teach);
+        let ops = default_program()
+            .compile(&default_speed_presets()[0], 1)
+            .unwrap();
+
+        // MT advances 19 * 10 mm below its start of 200 mm
+        ctx.soft_limit_min_mm[axes::MT] = Some(20.0);
+        let err = validate(&ops, &ctx).unwrap_err().to_string();
+        assert!(err.contains("soft limit min"), "{}", err);
+        ctx.soft_limit_min_mm[axes::MT] = None;
+
+        // Schieber starts mid-travel, the first Drücker move trips interlock B
+        ctx.positions_mm[axes::SCHIEBER] = 30.0;
+        let err = validate(&ops, &ctx).unwrap_err().to_string();
+        assert!(err.contains("interlock B"), "{}", err);
+    }
+}
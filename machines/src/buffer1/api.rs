@@ -1,16 +1,20 @@
 use super::{BufferV1, BufferV1Mode};
-use crate::{MachineApi, MachineMessage, machine_identification::MachineIdentificationUnique};
+use crate::{
+    MachineApi, MachineApiSchema, MachineMessage,
+    machine_identification::MachineIdentificationUnique,
+};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_one_event},
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::channel::Sender;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {}
 
 impl LiveValuesEvent {
@@ -19,7 +23,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     /// mode state
     pub mode_state: ModeState, // connected machine state
@@ -36,27 +40,27 @@ pub enum BufferV1Events {
     State(Event<StateEvent>),
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct ModeState {
     pub mode: BufferV1Mode,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub enum Mode {
     Standby,
     FillingBuffer,
     EmptyingBuffer,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct ConnectedMachineState {
     /// Connected Machine
     pub machine_identification_unique: Option<MachineIdentificationUnique>,
     pub is_available: bool,
 }
 
-#[derive(Deserialize, Serialize)]
-enum Mutation {
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub enum Mutation {
     // Mode
     SetBufferMode(BufferV1Mode),
 
@@ -103,6 +107,12 @@ impl CacheableEvents<Self> for BufferV1Events {
     }
 }
 
+impl MachineApiSchema for BufferV1 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for BufferV1 {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
         self.api_sender.clone()
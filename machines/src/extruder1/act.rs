@@ -57,10 +57,8 @@ impl MachineAct for ExtruderV2 {
                 tracing::info!("extruder1 received subscribe");
             }
             MachineMessage::UnsubscribeNamespace => self.namespace.namespace = None,
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) => {}
             MachineMessage::DisconnectMachine(_machine_connection) =>
@@ -7,14 +7,14 @@ use super::ExtruderV2;
 use crate::{MachineMessage, extruder1::HeatingType};
 
 #[cfg(not(feature = "mock-machine"))]
-use crate::MachineApi;
+use crate::{MachineApi, MachineApiSchema};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
-use control_core_derive::BuildEvent;
+use control_core_derive::{BuildEvent, JsonSchema};
 use serde::{Deserialize, Serialize};
 #[cfg(not(feature = "mock-machine"))]
 use serde_json::Value;
@@ -27,7 +27,7 @@ use units::electric_current::ampere;
 use units::electric_potential::volt;
 use units::frequency::hertz;
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
 pub struct MotorStatusValues {
     pub screw_rpm: f64, // rpm of motor
     pub frequency: f64, // frequency of motor
@@ -51,7 +51,7 @@ impl From<MotorStatus> for MotorStatusValues {
     }
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     /// screw rpm
     pub motor_status: MotorStatusValues,
@@ -85,7 +85,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent)]
+#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// rotation state
@@ -108,33 +108,33 @@ pub struct StateEvent {
     pub pid_settings: PidSettingsStates,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct RotationState {
     pub forward: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct ModeState {
     pub mode: ExtruderV2Mode,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct RegulationState {
     pub uses_rpm: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct PressureState {
     pub target_bar: f64,
     pub wiring_error: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ScrewState {
     pub target_rpm: f64,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct HeatingStates {
     pub nozzle: HeatingState,
     pub front: HeatingState,
@@ -142,19 +142,19 @@ pub struct HeatingStates {
     pub middle: HeatingState,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct HeatingState {
     pub target_temperature: f64,
     pub wiring_error: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ExtruderSettingsState {
     pub pressure_limit: f64,
     pub pressure_limit_enabled: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct InverterStatusState {
     /// RUN (Inverter running)
     pub running: bool,
@@ -176,14 +176,14 @@ pub struct InverterStatusState {
     pub fault_occurence: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct PidSettings {
     pub ki: f64,
     pub kp: f64,
     pub kd: f64,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct TemperaturePidStates {
     pub front: TemperaturePid,
     pub middle: TemperaturePid,
@@ -191,7 +191,7 @@ pub struct TemperaturePidStates {
     pub nozzle: TemperaturePid,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct TemperaturePid {
     pub ki: f64,
     pub kp: f64,
@@ -199,7 +199,7 @@ pub struct TemperaturePid {
     pub zone: String,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct PidSettingsStates {
     pub temperature: TemperaturePidStates,
     pub pressure: PidSettings,
@@ -210,7 +210,7 @@ pub enum ExtruderV2Events {
     State(Event<StateEvent>),
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub enum Mutation {
     /// INVERTER
     /// Frequency Control
@@ -274,6 +274,13 @@ impl CacheableEvents<Self> for ExtruderV2Events {
     }
 }
 
+#[cfg(not(feature = "mock-machine"))]
+impl MachineApiSchema for ExtruderV2 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 #[cfg(not(feature = "mock-machine"))]
 impl MachineApi for ExtruderV2 {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
@@ -8,14 +8,14 @@ use crate::extruder1::{
     mitsubishi_cs80::MotorStatus,
 };
 #[cfg(not(feature = "mock-machine"))]
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
-use control_core_derive::BuildEvent;
+use control_core_derive::{BuildEvent, JsonSchema};
 use serde::{Deserialize, Serialize};
 #[cfg(not(feature = "mock-machine"))]
 use serde_json::Value;
@@ -31,7 +31,7 @@ use units::{
 use super::ExtruderV3;
 use super::ExtruderV3Mode;
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
 pub struct MotorStatusValues {
     pub screw_rpm: f64, // rpm of motor
     pub frequency: f64, // frequency of motor
@@ -55,7 +55,7 @@ impl From<MotorStatus> for MotorStatusValues {
     }
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     /// screw rpm
     pub motor_status: MotorStatusValues,
@@ -89,7 +89,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent)]
+#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// rotation state
@@ -112,7 +112,7 @@ pub struct StateEvent {
     pub pid_settings: PidSettingsStates,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct ModeState {
     pub mode: ExtruderV3Mode,
 }
@@ -122,7 +122,7 @@ pub enum ExtruderV3Events {
     State(Event<StateEvent>),
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub enum Mutation {
     /// INVERTER
     /// Frequency Control
@@ -186,6 +186,13 @@ impl CacheableEvents<Self> for ExtruderV3Events {
     }
 }
 
+#[cfg(not(feature = "mock-machine"))]
+impl MachineApiSchema for ExtruderV3 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 #[cfg(not(feature = "mock-machine"))]
 impl MachineApi for ExtruderV3 {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
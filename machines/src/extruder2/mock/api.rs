@@ -1,9 +1,18 @@
 use super::ExtruderV2;
 use crate::{
-    MachineApi,
-    extruder1::{HeatingType, api::Mutation},
+    MachineApi, MachineApiSchema,
+    extruder1::{
+        HeatingType,
+        api::{LiveValuesEvent, Mutation, StateEvent},
+    },
 };
 
+impl MachineApiSchema for ExtruderV2 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for ExtruderV2 {
     fn api_mutate(&mut self, request_body: serde_json::Value) -> Result<(), anyhow::Error> {
         // there are multiple Modbus Frames that are "prebuilt"
@@ -8,6 +8,7 @@ use smol::channel::Receiver;
 #[cfg(not(feature = "mock-machine"))]
 use smol::channel::Sender;
 
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[cfg(not(feature = "mock-machine"))]
@@ -38,7 +39,7 @@ pub mod mock;
 pub mod new;
 pub mod temperature_controller;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub enum ExtruderV3Mode {
     Standby,
     Heat,
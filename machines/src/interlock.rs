@@ -0,0 +1,394 @@
+//! Declarative interlocks for multi-axis machines
+//!
+//! A machine declares its safety rules as [`InterlockRule`]s: which commands a
+//! rule gates ([`Scope`]), when it is armed and what must hold for those
+//! commands to be permitted ([`Condition`]). The [`InterlockEngine`] evaluates
+//! the rules against a [`MachineSnapshot`], so rules are testable without
+//! hardware:
+//!
+//! - [`InterlockEngine::check`] gates a command before it is issued and names
+//!   the rule that blocked it.
+//! - [`InterlockEngine::violations`] is called every cycle and lists running
+//!   motion or switched-on outputs that a `Stop` rule no longer permits; the
+//!   machine decides how to react (stop the axis, switch off, abort).
+//!
+//! Thresholds are plain values in the rules. Machines whose thresholds follow
+//! calibration data rebuild their rules when that data changes.
+
+use crate::linear_pto_axis::LinearPtoAxis;
+use std::fmt;
+
+/// State of one axis as seen by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AxisSnapshot {
+    pub position_mm: f32,
+    pub homed: bool,
+    pub homing: bool,
+    /// A move is in flight or a speed is commanded (homing included)
+    pub moving: bool,
+}
+
+impl From<&LinearPtoAxis> for AxisSnapshot {
+    fn from(axis: &LinearPtoAxis) -> Self {
+        Self {
+            position_mm: axis.position_mm(),
+            homed: axis.is_homed(),
+            homing: axis.is_homing(),
+            moving: axis.is_moving(),
+        }
+    }
+}
+
+/// Everything a rule can look at
+#[derive(Debug, Clone, Copy)]
+pub struct MachineSnapshot<'a> {
+    pub axes: &'a [AxisSnapshot],
+    pub inputs: &'a [bool],
+    pub outputs: &'a [bool],
+}
+
+/// A predicate over a [`MachineSnapshot`]. Indices that don't exist in the
+/// snapshot evaluate to `false`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Always,
+    AxisHomed(usize),
+    AxisHoming(usize),
+    /// A move is in flight or a speed is commanded
+    AxisMoving(usize),
+    /// Axis position ≤ `max_mm`
+    AxisAtMost {
+        axis: usize,
+        max_mm: f32,
+    },
+    /// Axis position ≥ `min_mm`
+    AxisAtLeast {
+        axis: usize,
+        min_mm: f32,
+    },
+    /// Digital input reads `high`
+    Input {
+        index: usize,
+        high: bool,
+    },
+    /// Digital output is switched `on`
+    Output {
+        index: usize,
+        on: bool,
+    },
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl std::ops::Not for Condition {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl Condition {
+    pub fn holds(&self, s: &MachineSnapshot) -> bool {
+        match self {
+            Self::Always => true,
+            Self::AxisHomed(axis) => s.axes.get(*axis).is_some_and(|a| a.homed),
+            Self::AxisHoming(axis) => s.axes.get(*axis).is_some_and(|a| a.homing),
+            Self::AxisMoving(axis) => s.axes.get(*axis).is_some_and(|a| a.moving),
+            Self::AxisAtMost { axis, max_mm } => {
+                s.axes.get(*axis).is_some_and(|a| a.position_mm <= *max_mm)
+            }
+            Self::AxisAtLeast { axis, min_mm } => {
+                s.axes.get(*axis).is_some_and(|a| a.position_mm >= *min_mm)
+            }
+            Self::Input { index, high } => s.inputs.get(*index) == Some(high),
+            Self::Output { index, on } => s.outputs.get(*index) == Some(on),
+            Self::All(conditions) => conditions.iter().all(|c| c.holds(s)),
+            Self::Any(conditions) => conditions.iter().any(|c| c.holds(s)),
+            Self::Not(condition) => !condition.holds(s),
+        }
+    }
+}
+
+/// A command a machine issues, checked against the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Move, jog or speed command of an axis
+    Move(usize),
+    /// Start a homing run of an axis
+    Home(usize),
+    /// Switch a digital output on
+    SwitchOn(usize),
+}
+
+/// Which commands a rule gates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Commanded motion of one axis, `None` for every axis. Homing runs are
+    /// not motion in this sense, they are covered by [`Scope::Homing`].
+    Motion(Option<usize>),
+    /// Homing runs of one axis, `None` for every axis
+    Homing(Option<usize>),
+    /// Switching on an output
+    Output(usize),
+}
+
+impl Scope {
+    pub fn covers(&self, command: Command) -> bool {
+        match (*self, command) {
+            (Self::Motion(axis), Command::Move(i)) | (Self::Homing(axis), Command::Home(i)) => {
+                axis.is_none_or(|a| a == i)
+            }
+            (Self::Output(output), Command::SwitchOn(i)) => output == i,
+            _ => false,
+        }
+    }
+}
+
+/// What happens when a rule stops permitting a command that is already running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction {
+    /// Only new commands are refused
+    Block,
+    /// New commands are refused and running ones reported as violations
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterlockRule {
+    /// Stable identifier, reported with every block or violation
+    pub id: &'static str,
+    /// Human readable reason shown to the operator
+    pub description: String,
+    pub scopes: Vec<Scope>,
+    /// The rule is only evaluated while this holds, e.g. once the axes it
+    /// refers to are homed and their positions are known
+    pub armed: Condition,
+    /// Gated commands are permitted while this holds
+    pub permit: Condition,
+    pub reaction: Reaction,
+}
+
+impl InterlockRule {
+    /// True when the rule is armed and its permit condition does not hold
+    pub fn is_blocking(&self, s: &MachineSnapshot) -> bool {
+        self.armed.holds(s) && !self.permit.holds(s)
+    }
+}
+
+/// A command refused by a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blocked {
+    pub rule: &'static str,
+    pub description: String,
+    pub command: Command,
+}
+
+impl fmt::Display for Blocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (interlock {})", self.description, self.rule)
+    }
+}
+
+impl std::error::Error for Blocked {}
+
+/// A running command a `Stop` rule no longer permits
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation<'a> {
+    pub rule: &'a InterlockRule,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InterlockEngine {
+    rules: Vec<InterlockRule>,
+}
+
+impl InterlockEngine {
+    pub const fn new(rules: Vec<InterlockRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &[InterlockRule] {
+        &self.rules
+    }
+
+    /// Replace the rule set, e.g. after the thresholds changed
+    pub fn set_rules(&mut self, rules: Vec<InterlockRule>) {
+        self.rules = rules;
+    }
+
+    /// Checks a command against all rules, the first blocking rule wins
+    pub fn check(&self, command: Command, s: &MachineSnapshot) -> Result<(), Blocked> {
+        match self
+            .rules
+            .iter()
+            .find(|r| r.scopes.iter().any(|sc| sc.covers(command)) && r.is_blocking(s))
+        {
+            Some(rule) => Err(Blocked {
+                rule: rule.id,
+                description: rule.description.clone(),
+                command,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Ids of the rules that currently block at least one of their commands
+    pub fn blocking_rules<'a>(
+        &'a self,
+        s: &'a MachineSnapshot,
+    ) -> impl Iterator<Item = &'static str> + 'a {
+        self.rules.iter().filter(|r| r.is_blocking(s)).map(|r| r.id)
+    }
+
+    /// Running commands that a `Stop` rule no longer permits, to be called
+    /// every cycle. Motion of a homing axis counts as [`Command::Home`].
+    pub fn violations<'a>(&'a self, s: &MachineSnapshot) -> Vec<Violation<'a>> {
+        let running = s
+            .axes
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.moving)
+            .map(|(i, a)| {
+                if a.homing {
+                    Command::Home(i)
+                } else {
+                    Command::Move(i)
+                }
+            })
+            .chain(
+                s.outputs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, on)| **on)
+                    .map(|(i, _)| Command::SwitchOn(i)),
+            );
+
+        let mut violations = Vec::new();
+        for command in running {
+            let rule = self.rules.iter().find(|r| {
+                r.reaction == Reaction::Stop
+                    && r.scopes.iter().any(|sc| sc.covers(command))
+                    && r.is_blocking(s)
+            });
+            if let Some(rule) = rule {
+                violations.push(Violation { rule, command });
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(position_mm: f32, moving: bool) -> AxisSnapshot {
+        AxisSnapshot {
+            position_mm,
+            homed: true,
+            homing: false,
+            moving,
+        }
+    }
+
+    fn engine() -> InterlockEngine {
+        InterlockEngine::new(vec![
+            InterlockRule {
+                id: "door",
+                description: "Door open".to_string(),
+                scopes: vec![Scope::Motion(None), Scope::Homing(None), Scope::Output(0)],
+                armed: Condition::Always,
+                permit: Condition::Input {
+                    index: 0,
+                    high: true,
+                },
+                reaction: Reaction::Stop,
+            },
+            InterlockRule {
+                id: "b_below_x",
+                description: "Axis 1 extended".to_string(),
+                scopes: vec![Scope::Motion(Some(0))],
+                armed: Condition::AxisHomed(1),
+                permit: Condition::AxisAtMost {
+                    axis: 1,
+                    max_mm: 10.0,
+                },
+                reaction: Reaction::Stop,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_check_names_blocking_rule() {
+        let engine = engine();
+        let axes = [axis(0.0, false), axis(20.0, false)];
+        let s = MachineSnapshot {
+            axes: &axes,
+            inputs: &[true],
+            outputs: &[false],
+        };
+        let blocked = engine.check(Command::Move(0), &s).unwrap_err();
+        assert_eq!(blocked.rule, "b_below_x");
+        assert!(engine.check(Command::Move(1), &s).is_ok());
+        assert!(engine.check(Command::Home(0), &s).is_ok());
+
+        // Not armed until axis 1 is homed
+        let axes = [
+            axis(0.0, false),
+            AxisSnapshot {
+                homed: false,
+                ..axes[1]
+            },
+        ];
+        let s = MachineSnapshot { axes: &axes, ..s };
+        assert!(engine.check(Command::Move(0), &s).is_ok());
+
+        // Door open blocks everything, including outputs
+        let s = MachineSnapshot {
+            inputs: &[false],
+            ..s
+        };
+        assert_eq!(engine.check(Command::Home(1), &s).unwrap_err().rule, "door");
+        assert_eq!(
+            engine.check(Command::SwitchOn(0), &s).unwrap_err().rule,
+            "door"
+        );
+    }
+
+    #[test]
+    fn test_violations_of_running_commands() {
+        let engine = engine();
+        let axes = [axis(0.0, true), axis(20.0, false)];
+        let s = MachineSnapshot {
+            axes: &axes,
+            inputs: &[true],
+            outputs: &[false],
+        };
+        let violations = engine.violations(&s);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule.id, "b_below_x");
+        assert_eq!(violations[0].command, Command::Move(0));
+
+        // A homing axis is exempt from motion rules
+        let axes = [
+            AxisSnapshot {
+                homing: true,
+                ..axes[0]
+            },
+            axes[1],
+        ];
+        let s = MachineSnapshot { axes: &axes, ..s };
+        assert!(engine.violations(&s).is_empty());
+
+        let s = MachineSnapshot {
+            inputs: &[false],
+            outputs: &[true],
+            ..s
+        };
+        let commands: Vec<_> = engine.violations(&s).iter().map(|v| v.command).collect();
+        assert_eq!(commands, [Command::Home(0), Command::SwitchOn(0)]);
+    }
+}
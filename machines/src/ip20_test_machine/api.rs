@@ -1,16 +1,17 @@
 use super::IP20TestMachine;
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     pub outputs: [bool; 8],
 }
@@ -21,7 +22,7 @@ impl StateEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct LiveValuesEvent {
     pub inputs: [bool; 8],
 }
@@ -37,7 +38,7 @@ pub enum IP20TestMachineEvents {
     LiveValues(Event<LiveValuesEvent>),
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(tag = "action", content = "value")]
 pub enum Mutation {
     SetOutput { index: usize, on: bool },
@@ -72,6 +73,12 @@ impl CacheableEvents<IP20TestMachineEvents> for IP20TestMachineEvents {
     }
 }
 
+impl MachineApiSchema for IP20TestMachine {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for IP20TestMachine {
     fn api_get_sender(&self) -> smol::channel::Sender<MachineMessage> {
         self.api_sender.clone()
@@ -58,9 +58,8 @@ impl MachineAct for LaserMachine {
                     // Already unsubscribed, nothing to do
                 }
             },
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) =>
                 /*Doesnt connect to any Machine so do nothing*/
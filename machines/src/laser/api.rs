@@ -1,4 +1,4 @@
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 
 use super::LaserMachine;
 use control_core::socketio::{
@@ -7,13 +7,13 @@ use control_core::socketio::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
-use control_core_derive::BuildEvent;
+use control_core_derive::{BuildEvent, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     /// diameter measurement in mm
     pub diameter: f64,
@@ -28,7 +28,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone, BuildEvent)]
+#[derive(Serialize, Debug, Clone, BuildEvent, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// laser state
@@ -41,7 +41,7 @@ impl StateEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct LaserState {
     /// higher tolerance in mm
     pub higher_tolerance: f64,
@@ -81,11 +81,11 @@ impl CacheableEvents<Self> for LaserEvents {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 /// All values in the Mutation enum should be positive.
 /// This ensures that the parameters for setting tolerances and target diameter
 /// are valid and meaningful within the context of the LaserMachine's operation.
-enum Mutation {
+pub enum Mutation {
     SetTargetDiameter(f64),
     SetLowerTolerance(f64),
     SetHigherTolerance(f64),
@@ -104,6 +104,12 @@ impl NamespaceCacheingLogic<LaserEvents> for LaserMachineNamespace {
     }
 }
 
+impl MachineApiSchema for LaserMachine {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for LaserMachine {
     fn api_mutate(&mut self, request_body: Value) -> Result<(), anyhow::Error> {
         let mutation: Mutation = serde_json::from_value(request_body)?;
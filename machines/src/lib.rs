@@ -1,6 +1,9 @@
 use anyhow::{Error, Result};
-use control_core::socketio::event::GenericEvent;
-use control_core::socketio::namespace::{CacheableEvents, Namespace, NamespaceCacheingLogic};
+use control_core::schema::JsonSchema;
+use control_core_derive::JsonSchema;
+use control_core::socketio::namespace::{
+    CacheableEvents, Namespace, NamespaceCacheingLogic, SocketQueueItem,
+};
 use ethercat_hal::devices::{
     EthercatDevice, SubDeviceIdentityTuple, downcast_device, subdevice_identity_to_tuple,
 };
@@ -12,7 +15,6 @@ use machine_identification::{
 };
 use serde::Serialize;
 use smol::channel::{Receiver, Sender};
-use socketioxide::extract::SocketRef;
 use std::any::Any;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -24,13 +26,20 @@ pub mod bbm_automatik_v2;
 pub mod buffer1;
 pub mod extruder1;
 pub mod extruder2;
+pub mod interlock;
 pub mod ip20_test_machine;
 pub mod laser;
+pub mod linear_interpolation;
+pub mod linear_pto_axis;
 pub mod machine_identification;
 pub mod mock;
+pub mod production_stats;
+pub mod pto_trajectory;
 pub mod registry;
 pub mod schneidemaschine_v0;
 pub mod serial;
+pub mod state_file;
+pub mod stepper_supervision;
 pub mod test_machine;
 pub mod wago_ai_test_machine;
 pub mod wago_power;
@@ -55,7 +64,7 @@ pub const BBM_AUTOMATIK_V2: u16 = 0x0038;
 use serde_json::Value;
 use smol::lock::RwLock;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct MachineCrossConnectionState {
     machine_identification_unique: Option<MachineIdentificationUnique>,
     is_available: bool,
@@ -70,6 +79,9 @@ pub enum AsyncThreadMessage {
     NoMsg,
     ConnectOneWayRequest(CrossConnection),
     DisconnectMachines(CrossConnection),
+    /// Blocking work a machine hands off to keep it out of its loop, e.g.
+    /// writing a state file. Jobs run one after another in send order.
+    Blocking(Box<dyn FnOnce() + Send + Sync>),
 }
 
 /// Callback type for runtime SDO writes to EtherCAT devices
@@ -98,7 +110,7 @@ pub struct MachineNewParams<
         'machine_new_hardware_etehrcat,
         'machine_new_hardware_serial,
     >,
-    pub socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>,
+    pub socket_queue_tx: Sender<SocketQueueItem>,
     pub main_thread_channel: Option<Sender<AsyncThreadMessage>>,
     pub namespace: Option<Namespace>,
     pub sdo_write_u16: Option<SdoWriteU16Fn>,
@@ -277,6 +289,17 @@ pub trait MachineNewTrait: Machine {
         Self: Sized;
 }
 
+/// Types of the REST and socket.io API of a machine, for the OpenAPI document and to validate
+/// mutations before they are sent to the machine
+pub trait MachineApiSchema {
+    /// Body of a mutation, see [`MachineApi::api_mutate`]
+    type Mutation: JsonSchema;
+    /// `state` of [`MachineValues`], `()` if the machine has none
+    type State: JsonSchema;
+    /// `live_values` of [`MachineValues`], `()` if the machine has none
+    type LiveValues: JsonSchema;
+}
+
 #[derive(Debug)]
 pub struct MachineConnection {
     pub ident: MachineIdentificationUnique,
@@ -302,7 +325,9 @@ pub struct MachineValues {
 pub enum MachineMessage {
     SubscribeNamespace(Namespace),
     UnsubscribeNamespace,
-    HttpApiJsonRequest(serde_json::Value),
+    /// Mutation from the REST API, the result of [`MachineApi::api_mutate`] is sent
+    /// back on the reply channel
+    HttpApiJsonRequest(serde_json::Value, Sender<MutationReply>),
     ConnectToMachine(MachineConnection),
     DisconnectMachine(MachineConnection),
     RequestValues(Sender<MachineValues>),
@@ -314,9 +339,40 @@ pub trait MachineApi {
     fn api_event_namespace(&mut self) -> Option<Namespace>;
 }
 
+/// Outcome of a mutation, the error message if [`MachineApi::api_mutate`] rejected it
+pub type MutationReply = Result<(), String>;
+
+/// Applies a mutation and replies with the outcome
+///
+/// Never blocks the calling loop: the reply channel must have room for one reply, a caller that
+/// gave up waiting has closed it.
+pub fn apply_mutation<M: MachineApi + ?Sized>(
+    machine: &mut M,
+    value: Value,
+    reply: Sender<MutationReply>,
+) {
+    let result = machine.api_mutate(value).map_err(|e| {
+        tracing::warn!("Mutation rejected: {:?}", e);
+        format!("{:#}", e)
+    });
+    let _ = reply.try_send(result);
+    reply.close();
+}
+
 pub trait Machine: MachineAct + MachineApi + Any + Debug + Send + Sync {
     fn get_machine_identification_unique(&self) -> MachineIdentificationUnique;
     fn get_main_sender(&self) -> Option<Sender<AsyncThreadMessage>>;
+
+    /// Signals that can be streamed at the cycle rate, see [`Machine::sample_signals`]
+    fn signal_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Writes the current value of each of [`Machine::signal_names`] into `values`
+    ///
+    /// Called after [`MachineAct::act`] in every cycle while a stream runs, so it must not
+    /// allocate or block. Unavailable values are left as `NaN`.
+    fn sample_signals(&self, _values: &mut [f64]) {}
 }
 
 pub trait AnyGetters: Any {
@@ -532,8 +588,8 @@ where
             MachineMessage::UnsubscribeNamespace => {
                 channel.namespace = None;
             }
-            MachineMessage::HttpApiJsonRequest(value) => {
-                let _ = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) => {
                 // Machine cross-connection not yet implemented
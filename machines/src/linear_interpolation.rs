@@ -0,0 +1,248 @@
+//! Coordinated straight-line moves of several pulse-train axes
+//!
+//! A [`LinearInterpolation`] moves 2–3 [`LinearPtoAxis`] along a straight
+//! line: one S-curve speed profile (see [`crate::pto_trajectory`]) runs over
+//! the path length and every axis streams it scaled by its share of the
+//! move, so all axes start in the same cycle and arrive together. Each cycle
+//! the positions read back from the PTO counters are compared with the line:
+//! a small correction keeps the axes on it, a following error or an axis
+//! that stopped on its own (driver alarm, interlock, stop command) stops all
+//! of them.
+
+use std::time::Instant;
+
+use anyhow::bail;
+
+use crate::linear_pto_axis::LinearPtoAxis;
+use crate::pto_trajectory::{SCurveLimits, SCurveTrajectory};
+
+pub const MAX_INTERPOLATED_AXES: usize = 3;
+
+/// Deviation from the line above which the move is aborted (mm)
+pub const MAX_FOLLOWING_ERROR_MM: f32 = 2.0;
+
+/// Gain of the correction towards the line (mm/s per mm)
+const POSITION_GAIN: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy)]
+struct AxisMove {
+    index: usize,
+    start_mm: f32,
+    delta_mm: f32,
+}
+
+/// Result of [`LinearInterpolation::update`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterpolationStatus {
+    Running,
+    /// All axes are landing their last pulses on the target. `step_loss`
+    /// if an axis already ended off target.
+    Finished {
+        step_loss: bool,
+    },
+    /// All axes were stopped
+    Aborted(String),
+}
+
+#[derive(Debug)]
+pub struct LinearInterpolation {
+    moves: Vec<AxisMove>,
+    path: SCurveTrajectory,
+    length_mm: f32,
+    last_update: Instant,
+}
+
+impl LinearInterpolation {
+    /// Starts the axes of `targets` (axis index, position in mm) together.
+    /// `speed_mm_s` is the speed along the line; the acceleration is the
+    /// lowest of the axes involved. Targets are clamped to the soft limits.
+    pub fn start(
+        axes: &mut [LinearPtoAxis],
+        targets: &[(usize, f32)],
+        speed_mm_s: f32,
+        jerk_mm_s3: f32,
+        now: Instant,
+    ) -> anyhow::Result<Self> {
+        if !(2..=MAX_INTERPOLATED_AXES).contains(&targets.len()) {
+            bail!(
+                "Interpolation needs 2 to {} axes, got {}",
+                MAX_INTERPOLATED_AXES,
+                targets.len()
+            );
+        }
+        let mut moves: Vec<AxisMove> = Vec::with_capacity(targets.len());
+        for &(index, position_mm) in targets {
+            let Some(axis) = axes.get(index) else {
+                bail!("Unknown axis {}", index);
+            };
+            if moves.iter().any(|m| m.index == index) {
+                bail!("Axis {} given twice", axis.name());
+            }
+            if axis.is_moving() || axis.is_homing() {
+                bail!("Axis {} is moving", axis.name());
+            }
+            let start_mm = axis.position_mm();
+            moves.push(AxisMove {
+                index,
+                start_mm,
+                delta_mm: axis.clamp_to_soft_limits(position_mm) - start_mm,
+            });
+        }
+
+        let length_mm = moves.iter().map(|m| m.delta_mm.powi(2)).sum::<f32>().sqrt();
+        if length_mm < 0.01 {
+            bail!("Axes already at the target");
+        }
+        let acceleration_mm_s2 = moves
+            .iter()
+            .map(|m| axes[m.index].acceleration_mm_s2())
+            .fold(f32::INFINITY, f32::min);
+        let limits = SCurveLimits {
+            speed_mm_s: speed_mm_s.abs(),
+            acceleration_mm_s2,
+            jerk_mm_s3,
+        };
+        let path = SCurveTrajectory::new(0.0, length_mm, limits)?;
+
+        for m in &moves {
+            axes[m.index].start_streamed_move(m.start_mm + m.delta_mm);
+        }
+        tracing::info!(
+            "[Interpolation] {} over {:.1} mm at {:.1} mm/s",
+            moves
+                .iter()
+                .map(|m| format!("{} {:+.1} mm", axes[m.index].name(), m.delta_mm))
+                .collect::<Vec<_>>()
+                .join(", "),
+            length_mm,
+            speed_mm_s
+        );
+        Ok(Self {
+            moves,
+            path,
+            length_mm,
+            last_update: now,
+        })
+    }
+
+    /// Indices of the axes being moved
+    pub fn axes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.moves.iter().map(|m| m.index)
+    }
+
+    /// Streams the next setpoint to every axis, called once per cycle after
+    /// the axes' own [`LinearPtoAxis::update`]
+    pub fn update(&mut self, axes: &mut [LinearPtoAxis], now: Instant) -> InterpolationStatus {
+        let dt = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+
+        if let Some(m) = self.moves.iter().find(|m| !axes[m.index].is_streaming()) {
+            let reason = format!("axis {} stopped", axes[m.index].name());
+            return self.abort(axes, reason);
+        }
+
+        // The counters show the pulses sent up to the last cycle
+        let fraction = self.path.position_mm() / self.length_mm;
+        let mut errors = [0.0; MAX_INTERPOLATED_AXES];
+        for (m, error) in self.moves.iter().zip(errors.iter_mut()) {
+            *error = m.start_mm + m.delta_mm * fraction - axes[m.index].position_mm();
+            if error.abs() > MAX_FOLLOWING_ERROR_MM {
+                let reason = format!("axis {} {:.2} mm off the line", axes[m.index].name(), error);
+                return self.abort(axes, reason);
+            }
+        }
+
+        let speed_mm_s = self.path.update(dt);
+        if self.path.is_finished() {
+            let mut step_loss = false;
+            for m in &self.moves {
+                step_loss |= axes[m.index].finish_streamed_move();
+            }
+            tracing::info!("[Interpolation] done");
+            return InterpolationStatus::Finished { step_loss };
+        }
+        for (m, error) in self.moves.iter().zip(errors) {
+            let share = m.delta_mm / self.length_mm;
+            axes[m.index].stream_speed_mm_s(speed_mm_s * share + POSITION_GAIN * error);
+        }
+        InterpolationStatus::Running
+    }
+
+    /// Stops all axes of the move together
+    pub fn stop(&self, axes: &mut [LinearPtoAxis]) {
+        for m in &self.moves {
+            axes[m.index].stop();
+        }
+    }
+
+    fn abort(&self, axes: &mut [LinearPtoAxis], reason: String) -> InterpolationStatus {
+        tracing::warn!("[Interpolation] aborted: {} - stopping all axes", reason);
+        self.stop(axes);
+        InterpolationStatus::Aborted(reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::linear_pto_axis::tests::{MECHANICS, fake_axis};
+    use crate::linear_pto_axis::{LinearPtoAxisConfig, POSITION_OFFSET_PULSES};
+
+    #[test]
+    fn test_axes_arrive_together() {
+        let (a, device_a) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let (b, device_b) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let devices = [device_a, device_b];
+        let mut axes = [a, b];
+        let mut now = Instant::now();
+
+        assert!(LinearInterpolation::start(&mut axes, &[(0, 10.0)], 50.0, 1000.0, now).is_err());
+        let mut interpolation =
+            LinearInterpolation::start(&mut axes, &[(0, 30.0), (1, -40.0)], 50.0, 1000.0, now)
+                .unwrap();
+        assert!(axes.iter().all(LinearPtoAxis::is_position_mode));
+
+        // The fake counters follow the commanded frequencies
+        let mut pulses = [0.0f32; 2];
+        let status = loop {
+            now += Duration::from_millis(1);
+            let status = interpolation.update(&mut axes, now);
+            if status != InterpolationStatus::Running {
+                break status;
+            }
+            for (device, pulses) in devices.iter().zip(pulses.iter_mut()) {
+                let mut device = smol::block_on(device.write());
+                *pulses += device.output.frequency_value as f32 / 1000.0;
+                device.input.counter_value =
+                    (POSITION_OFFSET_PULSES as i64 + *pulses as i64) as u32;
+            }
+            // Both on the line: b travels 4/3 of a, the other way
+            let (pos_a, pos_b) = (axes[0].position_mm(), axes[1].position_mm());
+            assert!((pos_b + pos_a * 4.0 / 3.0).abs() < 0.5);
+        };
+        assert_eq!(status, InterpolationStatus::Finished { step_loss: false });
+        assert!((axes[0].position_mm() - 30.0).abs() < 0.5);
+        assert!((axes[1].position_mm() + 40.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_stopped_axis_stops_all() {
+        let (a, _device_a) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let (b, device_b) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let mut axes = [a, b];
+        let now = Instant::now();
+        let mut interpolation =
+            LinearInterpolation::start(&mut axes, &[(0, 30.0), (1, 40.0)], 50.0, 1000.0, now)
+                .unwrap();
+        interpolation.update(&mut axes, now + Duration::from_millis(1));
+
+        // E.g. a driver alarm stops axis 0
+        axes[0].stop();
+        let status = interpolation.update(&mut axes, now + Duration::from_millis(2));
+        assert!(matches!(status, InterpolationStatus::Aborted(_)));
+        assert!(!axes[1].is_moving());
+        assert_eq!(smol::block_on(device_b.read()).output.frequency_value, 0);
+    }
+}
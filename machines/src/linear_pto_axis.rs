@@ -0,0 +1,1181 @@
+//! Linear axis driven through a pulse train output (EL2522)
+//!
+//! [`LinearPtoAxis`] owns everything a pulse-train linear axis needs on top of the raw
+//! [`PulseTrainOutput`]: unit conversion, the hardware ramp over SDO, Travel Distance Control
+//! or jerk-limited (S-curve) moves, homing on a reference switch, soft limits, the driver alarm
+//! input and step-loss detection. Machines keep their interlocks and sequences and call
+//! [`LinearPtoAxis::update`] once per cycle.
+
+use std::time::Instant;
+
+use anyhow::bail;
+use ethercat_hal::io::digital_input::DigitalInput;
+use ethercat_hal::io::pulse_train_output::PulseTrainOutput;
+
+use crate::SdoWriteU16Fn;
+use crate::pto_trajectory::{SCurveLimits, SCurveTrajectory};
+
+/// Virtual zero offset for the EL2522 hardware position counter.
+///
+/// The EL2522 stores its position counter as a `u32` and Travel Distance
+/// Control compares `target_counter_value` vs the live counter UNSIGNED.
+/// To drive to logically negative positions we initialise the hardware
+/// counter to this offset; logical position is then
+/// `hw_counter - position_offset` (interpreted i32). With both values
+/// comfortably in the lower half of u32, the unsigned compare picks the
+/// physically correct direction every time and TDC brakes
+/// hardware-precisely in both directions.
+///
+/// This is the canonical pattern Beckhoff documents in the EL252x
+/// manual (§6.4.3 "Connection of the EL2522 in the NC", §6.5.1.2 "Travel
+/// Distance Control") — TwinCAT NC owns the signed logical position
+/// internally and writes only positive u32 values to the terminal.
+///
+/// 1_000_000 pulses at 20 pulses/mm = 50_000 mm headroom in either
+/// direction, vastly more than any physical axis.
+pub const POSITION_OFFSET_PULSES: u32 = 1_000_000;
+
+/// Ramp base frequency in Hz, must match `base_frequency_1` of the EL2522
+/// channel configuration.
+pub const RAMP_BASE_FREQUENCY_HZ: f32 = 5000.0;
+
+/// Ignore select_end_counter for N cycles after starting a new move
+/// (~3.5ms at 700µs cycle) so the hardware has time to process the new
+/// go_counter and clear the stale "target reached" signal.
+const POSITION_IGNORE_CYCLES: u8 = 5;
+
+/// Speed of the Travel Distance Control move that lands a streamed move
+/// (S-curve or interpolated) on its exact target pulse
+const LANDING_SPEED_MM_S: f32 = 5.0;
+
+/// Mechanics of a stepper driving a ball screw
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearAxisMechanics {
+    /// Motor pulses per revolution (driver setting)
+    pub pulses_per_rev: u32,
+    /// Ball screw lead in mm per revolution
+    pub lead_mm: f32,
+}
+
+impl LinearAxisMechanics {
+    pub const fn pulses_per_mm(&self) -> f32 {
+        self.pulses_per_rev as f32 / self.lead_mm
+    }
+
+    /// Convert mm/s to frequency (Hz)
+    pub fn mm_per_s_to_hz(&self, mm_per_s: f32) -> i32 {
+        (mm_per_s * self.pulses_per_mm()) as i32
+    }
+
+    /// Convert frequency (Hz) to mm/s
+    pub fn hz_to_mm_per_s(&self, hz: i32) -> f32 {
+        hz as f32 / self.pulses_per_mm()
+    }
+
+    /// Convert a position in mm to pulses, rounded after scaling to keep
+    /// sub-mm precision
+    pub fn mm_to_pulses(&self, mm: f32) -> i32 {
+        (mm * self.pulses_per_mm()).round() as i32
+    }
+
+    /// Convert a position in pulses to mm
+    pub fn pulses_to_mm(&self, pulses: i32) -> f32 {
+        pulses as f32 / self.pulses_per_mm()
+    }
+
+    /// Convert RPM to frequency (Hz), for rotating the motor without ball screw
+    pub fn rpm_to_hz(&self, rpm: f32) -> i32 {
+        (rpm * self.pulses_per_rev as f32 / 60.0) as i32
+    }
+
+    /// Convert frequency (Hz) to RPM
+    pub fn hz_to_rpm(&self, hz: i32) -> f32 {
+        hz as f32 * 60.0 / self.pulses_per_rev as f32
+    }
+}
+
+/// Homing phases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HomingPhase {
+    /// Not homing
+    Idle,
+    /// Phase 1: Moving negative until sensor triggers
+    SearchingSensor,
+    /// Phase 2: Retracting away from sensor
+    Retracting,
+    /// Phase 3: Setting position to 0
+    SettingZero,
+}
+
+/// How an axis finds its zero position
+#[derive(Debug)]
+pub enum HomingStrategy {
+    /// No reference switch: the position at startup is zero and the axis
+    /// counts as homed right away
+    None,
+    /// Drive negative until the reference switch triggers, retract and
+    /// set zero there
+    ReferenceSwitch(ReferenceSwitch),
+}
+
+#[derive(Debug)]
+pub struct ReferenceSwitch {
+    pub input: DigitalInput,
+    /// NC switch: 24V/true = free, 0V/false = end position reached
+    pub normally_closed: bool,
+    /// Homing speed in mm/s (slow for precision)
+    pub speed_mm_s: f32,
+    /// Retract distance after hitting the sensor (mm)
+    pub retract_mm: f32,
+}
+
+/// Alarm output of the stepper driver
+#[derive(Debug)]
+pub struct AlarmInput {
+    pub input: DigitalInput,
+    /// true = alarm when the input is low
+    pub active_low: bool,
+}
+
+/// Where the ramp time constants of the EL2522 channel are written
+pub struct RampSdo {
+    pub write: SdoWriteU16Fn,
+    pub subdevice_index: usize,
+    /// EL2522 channel, 0 or 1
+    pub channel: u8,
+}
+
+pub struct LinearPtoAxisConfig {
+    pub mechanics: LinearAxisMechanics,
+    pub homing: HomingStrategy,
+    pub alarm: Option<AlarmInput>,
+    /// `None` if runtime SDO writes are not available, acceleration changes
+    /// are then ignored
+    pub ramp_sdo: Option<RampSdo>,
+    /// Falling ramp time constant relative to the rising one
+    pub falling_ramp_factor: f32,
+    /// Initial acceleration in mm/s², must match the ramp time constants of
+    /// the channel configuration
+    pub acceleration_mm_s2: f32,
+    pub soft_limit_min_mm: Option<f32>,
+    pub soft_limit_max_mm: Option<f32>,
+    /// Deviation at the end of a move above which step loss is logged
+    pub step_loss_warn_pulses: i32,
+    /// Deviation at the end of a move above which the position is no longer
+    /// trusted: homing is revoked until the axis is homed again. `None`
+    /// only logs.
+    pub step_loss_invalidate_pulses: Option<i32>,
+    /// Jerk limit in mm/s³ for S-curve moves. `Some` makes
+    /// [`LinearPtoAxis::move_to_mm`] stream a jerk-limited trajectory (see
+    /// [`crate::pto_trajectory`]) instead of using Travel Distance Control.
+    pub s_curve_jerk_mm_s3: Option<f32>,
+}
+
+impl LinearPtoAxisConfig {
+    pub const fn new(mechanics: LinearAxisMechanics) -> Self {
+        Self {
+            mechanics,
+            homing: HomingStrategy::None,
+            alarm: None,
+            ramp_sdo: None,
+            falling_ramp_factor: 1.0,
+            acceleration_mm_s2: 100.0,
+            soft_limit_min_mm: None,
+            soft_limit_max_mm: None,
+            step_loss_warn_pulses: 2,
+            step_loss_invalidate_pulses: None,
+            s_curve_jerk_mm_s3: None,
+        }
+    }
+}
+
+/// Result of [`LinearPtoAxis::update`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AxisUpdate {
+    /// Anything the UI shows changed
+    pub changed: bool,
+    /// A move just ended further than `step_loss_invalidate_pulses` from its
+    /// target, homing was revoked
+    pub step_loss: bool,
+}
+
+pub struct LinearPtoAxis {
+    name: &'static str,
+    pto: PulseTrainOutput,
+    mechanics: LinearAxisMechanics,
+    homing: HomingStrategy,
+    alarm: Option<AlarmInput>,
+    ramp_sdo: Option<RampSdo>,
+    falling_ramp_factor: f32,
+    step_loss_warn_pulses: i32,
+    step_loss_invalidate_pulses: Option<i32>,
+    s_curve_jerk_mm_s3: Option<f32>,
+
+    speed_hz: i32,
+    target_speed_hz: i32,
+    acceleration_mm_s2: f32,
+    target_position_pulses: i32,
+    position_mode: bool,
+    position_ignore_cycles: u8,
+    homing_phase: HomingPhase,
+    homing_retract_target: u32,
+    homed: bool,
+    step_loss: bool,
+    position_offset: u32,
+    alarm_active: bool,
+    soft_limit_min_mm: Option<f32>,
+    soft_limit_max_mm: Option<f32>,
+    /// S-curve move in flight, see [`Self::update_trajectory`]
+    trajectory: Option<SCurveTrajectory>,
+    /// Setpoints of the move in flight come from outside, see
+    /// [`Self::start_streamed_move`]
+    streaming: bool,
+    last_update: Instant,
+}
+
+impl std::fmt::Debug for LinearPtoAxis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LinearPtoAxis({})", self.name)
+    }
+}
+
+impl LinearPtoAxis {
+    /// Queues the virtual zero offset write (see [`POSITION_OFFSET_PULSES`]),
+    /// logical reads are correct once the hardware applied it in the next
+    /// cycle.
+    pub fn new(name: &'static str, pto: PulseTrainOutput, config: LinearPtoAxisConfig) -> Self {
+        let mut output = pto.get_output();
+        output.set_counter = true;
+        output.set_counter_value = POSITION_OFFSET_PULSES;
+        pto.set_output(output);
+
+        Self {
+            name,
+            pto,
+            mechanics: config.mechanics,
+            homed: matches!(config.homing, HomingStrategy::None),
+            homing: config.homing,
+            alarm: config.alarm,
+            ramp_sdo: config.ramp_sdo,
+            falling_ramp_factor: config.falling_ramp_factor,
+            step_loss_warn_pulses: config.step_loss_warn_pulses,
+            step_loss_invalidate_pulses: config.step_loss_invalidate_pulses,
+            s_curve_jerk_mm_s3: config.s_curve_jerk_mm_s3,
+            speed_hz: 0,
+            target_speed_hz: 0,
+            acceleration_mm_s2: config.acceleration_mm_s2,
+            target_position_pulses: 0,
+            position_mode: false,
+            position_ignore_cycles: 0,
+            homing_phase: HomingPhase::Idle,
+            homing_retract_target: 0,
+            step_loss: false,
+            position_offset: POSITION_OFFSET_PULSES,
+            alarm_active: false,
+            soft_limit_min_mm: config.soft_limit_min_mm,
+            soft_limit_max_mm: config.soft_limit_max_mm,
+            trajectory: None,
+            streaming: false,
+            last_update: Instant::now(),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub const fn mechanics(&self) -> &LinearAxisMechanics {
+        &self.mechanics
+    }
+
+    /// The raw pulse train output, for diagnostics
+    pub const fn pto(&self) -> &PulseTrainOutput {
+        &self.pto
+    }
+
+    /// Frequency currently sent to the hardware (signed for the direction)
+    pub const fn speed_hz(&self) -> i32 {
+        self.speed_hz
+    }
+
+    pub const fn target_speed_hz(&self) -> i32 {
+        self.target_speed_hz
+    }
+
+    pub const fn acceleration_mm_s2(&self) -> f32 {
+        self.acceleration_mm_s2
+    }
+
+    /// Logical target of the last move in pulses
+    pub const fn target_position_pulses(&self) -> i32 {
+        self.target_position_pulses
+    }
+
+    /// True while a position move (Travel Distance Control or S-curve) is
+    /// in flight
+    pub const fn is_position_mode(&self) -> bool {
+        self.position_mode
+    }
+
+    /// True while a move is in flight or a speed is commanded
+    pub const fn is_moving(&self) -> bool {
+        self.position_mode || self.speed_hz != 0 || self.target_speed_hz != 0
+    }
+
+    pub const fn homing_phase(&self) -> HomingPhase {
+        self.homing_phase
+    }
+
+    pub fn is_homing(&self) -> bool {
+        self.homing_phase != HomingPhase::Idle
+    }
+
+    /// true once homing has completed, revoked on step loss. Soft limits are
+    /// only enforced while this is set.
+    pub const fn is_homed(&self) -> bool {
+        self.homed
+    }
+
+    /// true when a move ended further than `step_loss_invalidate_pulses`
+    /// from its target. Cleared only by homing the axis again.
+    pub const fn has_step_loss(&self) -> bool {
+        self.step_loss
+    }
+
+    /// Latched driver alarm, see [`Self::check_alarm`]
+    pub const fn is_alarm_active(&self) -> bool {
+        self.alarm_active
+    }
+
+    pub const fn soft_limit_min_mm(&self) -> Option<f32> {
+        self.soft_limit_min_mm
+    }
+
+    pub const fn soft_limit_max_mm(&self) -> Option<f32> {
+        self.soft_limit_max_mm
+    }
+
+    /// Hardware counter value in logical pulses
+    pub const fn hw_to_logical_pulses(&self, hw_pulses: u32) -> i32 {
+        hw_pulses.wrapping_sub(self.position_offset) as i32
+    }
+
+    /// Current axis position in logical pulses (signed)
+    pub fn position_pulses(&self) -> i32 {
+        self.hw_to_logical_pulses(self.pto.get_position())
+    }
+
+    /// Current axis position in mm (logical, signed)
+    pub fn position_mm(&self) -> f32 {
+        self.mechanics.pulses_to_mm(self.position_pulses())
+    }
+
+    /// Redefines the current position as `position_mm` without touching the
+    /// hardware counter, for axes without a reference switch such as a
+    /// material feed that restarts at zero after every cut. The hardware
+    /// counter keeps counting; at 20 pulses/mm it stays in the lower half of
+    /// u32 (see [`POSITION_OFFSET_PULSES`]) for over 100 km of travel.
+    pub fn set_position_mm(&mut self, position_mm: f32) -> anyhow::Result<()> {
+        if self.is_moving() || self.is_homing() {
+            bail!("Axis {} must be at rest to set its position", self.name);
+        }
+        let pulses = self.mechanics.mm_to_pulses(position_mm);
+        self.position_offset = self.pto.get_position().wrapping_sub(pulses as u32);
+        Ok(())
+    }
+
+    fn limits_enforced(&self) -> bool {
+        self.homed && self.homing_phase == HomingPhase::Idle
+    }
+
+    /// Set the frequency directly, bypassing soft limits
+    pub fn set_frequency(&mut self, hz: i32) {
+        self.speed_hz = hz;
+        self.pto.set_frequency(hz);
+    }
+
+    /// Set target speed in mm/s, the hardware ramp handles the transition.
+    /// Positive = forward, Negative = backward
+    pub fn set_speed_mm_s(&mut self, mm_per_s: f32) {
+        self.target_speed_hz = self.mechanics.mm_per_s_to_hz(mm_per_s);
+    }
+
+    /// Set target speed in RPM of the motor
+    pub fn set_speed_rpm(&mut self, rpm: f32) {
+        self.target_speed_hz = self.mechanics.rpm_to_hz(rpm);
+    }
+
+    /// Set acceleration in mm/s² - writes ramp time constants via SDO
+    pub fn set_acceleration(&mut self, accel_mm_s2: f32) {
+        let clamped = accel_mm_s2.clamp(4.0, 500.0);
+        self.acceleration_mm_s2 = clamped;
+
+        let accel_hz_s = clamped * self.mechanics.pulses_per_mm();
+        let rising_ms = ((RAMP_BASE_FREQUENCY_HZ / accel_hz_s) * 1000.0) as u16;
+        let falling_ms = ((rising_ms as f32) * self.falling_ramp_factor) as u16;
+
+        // NOTE: SdoWriteU16Fn returns () - errors are handled inside the callback.
+        // If SDO write fails, the hardware keeps the old ramp values.
+        let Some(sdo) = &self.ramp_sdo else {
+            tracing::warn!(
+                "[Axis {}] SDO write not available - acceleration change will not take effect",
+                self.name
+            );
+            return;
+        };
+        // PTO Base Index: Channel 1 = 0x8000, Channel 2 = 0x8010
+        let pto_base = 0x8000u16 + 0x10 * sdo.channel as u16;
+        tracing::debug!(
+            "[Axis {}] SDO write: ramp rising={}ms falling={}ms (accel={:.0} mm/s²)",
+            self.name,
+            rising_ms,
+            falling_ms,
+            clamped
+        );
+        // Rising ramp (0x14)
+        (sdo.write)(sdo.subdevice_index, pto_base, 0x14, rising_ms);
+        // Falling ramp (0x15)
+        (sdo.write)(sdo.subdevice_index, pto_base, 0x15, falling_ms);
+    }
+
+    /// Move to a logical target position in mm using hardware Travel
+    /// Distance Control. The hardware ramps up, brakes, and stops
+    /// hardware-precisely at the target in BOTH directions thanks to the
+    /// virtual zero offset (see [`POSITION_OFFSET_PULSES`]). With
+    /// `s_curve_jerk_mm_s3` configured the move follows a jerk-limited
+    /// trajectory instead. The target is clamped to the soft limits once the
+    /// axis is homed.
+    pub fn move_to_mm(&mut self, position_mm: f32, speed_mm_s: f32) {
+        let clamped_mm = self.clamp_to_soft_limits(position_mm);
+        let target_logical_pulses = self.mechanics.mm_to_pulses(clamped_mm);
+        self.trajectory = None;
+        self.streaming = false;
+        if let Some(jerk_mm_s3) = self.s_curve_jerk_mm_s3 {
+            let limits = SCurveLimits {
+                speed_mm_s: speed_mm_s.abs(),
+                acceleration_mm_s2: self.acceleration_mm_s2,
+                jerk_mm_s3,
+            };
+            match SCurveTrajectory::new(self.position_mm(), clamped_mm, limits) {
+                Ok(trajectory) => {
+                    self.trajectory = Some(trajectory);
+                    self.target_position_pulses = target_logical_pulses;
+                    self.position_mode = true;
+                    tracing::info!(
+                        "[Axis {}] S-curve move to {:.3} mm at {:.1} mm/s (jerk {:.0} mm/s³)",
+                        self.name,
+                        clamped_mm,
+                        speed_mm_s,
+                        jerk_mm_s3
+                    );
+                    return;
+                }
+                Err(e) => tracing::warn!(
+                    "[Axis {}] {} - falling back to Travel Distance Control",
+                    self.name,
+                    e
+                ),
+            }
+        }
+
+        let speed_hz = self.mechanics.mm_per_s_to_hz(speed_mm_s.abs());
+        self.start_tdc_move(target_logical_pulses, speed_hz);
+        tracing::info!(
+            "[Axis {}] moving to {:.3} mm ({} logical pulses) at {:.1} mm/s",
+            self.name,
+            clamped_mm,
+            target_logical_pulses,
+            speed_mm_s
+        );
+    }
+
+    /// Starts a Travel Distance Control move to `target_logical_pulses`
+    fn start_tdc_move(&mut self, target_logical_pulses: i32, speed_hz: i32) {
+        let current_logical_pulses = self.position_pulses();
+        // Hardware target = logical + offset, computed in i64 to avoid any
+        // i32/u32 ambiguity, then narrowed once we know it fits.
+        let target_hw_u32 = (target_logical_pulses as i64 + self.position_offset as i64) as u32;
+
+        // Direction in logical space (just for UI sign on the speed).
+        let direction = if target_logical_pulses >= current_logical_pulses {
+            1
+        } else {
+            -1
+        };
+
+        self.target_position_pulses = target_logical_pulses;
+        self.position_mode = true;
+        self.position_ignore_cycles = POSITION_IGNORE_CYCLES;
+
+        // In Travel Distance Control mode the EL2522 picks direction from
+        // the unsigned compare target_counter_value vs counter.
+        // frequency_value is magnitude only — the sign is NOT used for
+        // direction in TDC.
+        let mut output = self.pto.get_output();
+        output.go_counter = true;
+        output.disble_ramp = false;
+        output.frequency_value = speed_hz;
+        output.target_counter_value = target_hw_u32;
+        self.pto.set_output(output);
+
+        // Signed speed for the UI direction indicator.
+        self.target_speed_hz = speed_hz * direction;
+        self.speed_hz = speed_hz * direction;
+        tracing::debug!("[Axis {}] TDC hw target {}", self.name, target_hw_u32);
+    }
+
+    /// Clamps a target to the soft limits once the axis is homed
+    pub fn clamp_to_soft_limits(&self, position_mm: f32) -> f32 {
+        let clamped_mm = if self.limits_enforced() {
+            let mut v = position_mm;
+            if let Some(max) = self.soft_limit_max_mm {
+                v = v.min(max);
+            }
+            if let Some(min) = self.soft_limit_min_mm {
+                v = v.max(min);
+            }
+            v
+        } else {
+            position_mm
+        };
+
+        if (clamped_mm - position_mm).abs() > 0.1 {
+            tracing::warn!(
+                "[Axis {}] position clamped: {:.1} mm -> {:.1} mm (soft limit)",
+                self.name,
+                position_mm,
+                clamped_mm
+            );
+        }
+        clamped_mm
+    }
+
+    /// Starts a position move whose speed setpoints come from outside, such
+    /// as a [`LinearInterpolation`](crate::linear_interpolation::LinearInterpolation)
+    /// of several axes. `target_mm` must already be clamped. The caller
+    /// streams [`Self::stream_speed_mm_s`] every cycle and ends the move with
+    /// [`Self::finish_streamed_move`]; [`Self::stop`] aborts it.
+    pub fn start_streamed_move(&mut self, target_mm: f32) {
+        self.trajectory = None;
+        self.target_position_pulses = self.mechanics.mm_to_pulses(target_mm);
+        self.position_mode = true;
+        self.streaming = true;
+    }
+
+    /// True while a move started with [`Self::start_streamed_move`] runs
+    pub const fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    /// Sends a speed setpoint with the hardware ramp disabled
+    pub fn stream_speed_mm_s(&mut self, speed_mm_s: f32) {
+        let speed_hz = self.mechanics.mm_per_s_to_hz(speed_mm_s);
+        let mut output = self.pto.get_output();
+        output.disble_ramp = true;
+        output.go_counter = false;
+        output.frequency_value = speed_hz;
+        self.pto.set_output(output);
+        self.speed_hz = speed_hz;
+        self.target_speed_hz = speed_hz;
+    }
+
+    /// Ends a streamed move, see [`Self::land`]. Returns true if the
+    /// position integrity is lost.
+    pub fn finish_streamed_move(&mut self) -> bool {
+        if !self.streaming {
+            return false;
+        }
+        self.land()
+    }
+
+    /// Relative move by `delta_mm` from the current position
+    pub fn jog_relative(&mut self, delta_mm: f32, speed_mm_s: f32) {
+        let target_mm = self.position_mm() + delta_mm;
+        self.move_to_mm(target_mm, speed_mm_s);
+    }
+
+    /// Hardware immediate stop, also cancels homing
+    pub fn stop(&mut self) {
+        if self.homing_phase != HomingPhase::Idle {
+            self.homing_phase = HomingPhase::Idle;
+            tracing::info!("[Axis {}] homing cancelled by stop", self.name);
+        }
+        self.speed_hz = 0;
+        self.target_speed_hz = 0;
+        self.position_mode = false;
+        self.trajectory = None;
+        self.streaming = false;
+
+        // Hardware: disble_ramp breaks Travel Distance Control
+        let mut output = self.pto.get_output();
+        output.disble_ramp = true;
+        output.go_counter = false;
+        output.frequency_value = 0;
+        self.pto.set_output(output);
+    }
+
+    /// Stop on the ramp without leaving speed mode, used between homing phases
+    fn halt(&mut self) {
+        self.speed_hz = 0;
+        self.target_speed_hz = 0;
+        let mut output = self.pto.get_output();
+        output.disble_ramp = false;
+        output.go_counter = false;
+        output.frequency_value = 0;
+        self.pto.set_output(output);
+    }
+
+    // ============ Limits ============
+
+    /// Set (or clear) the upper soft-limit. `None` removes the limit. The
+    /// new max must stay above the current min if both are set.
+    pub fn set_soft_limit_max(&mut self, max_mm: Option<f32>) -> anyhow::Result<()> {
+        if let Some(v) = max_mm {
+            if !v.is_finite() {
+                bail!("Reject soft-limit max axis {}: non-finite", self.name);
+            }
+            if let Some(min) = self.soft_limit_min_mm
+                && v <= min
+            {
+                bail!(
+                    "Reject soft-limit max axis {}: {:.3} <= current min {:.3}",
+                    self.name,
+                    v,
+                    min
+                );
+            }
+        }
+        self.soft_limit_max_mm = max_mm;
+        tracing::info!("[Axis {}] Soft-limit max = {:?} mm", self.name, max_mm);
+        Ok(())
+    }
+
+    /// Set (or clear) the lower soft-limit. `None` removes the limit. The
+    /// new min must stay below the current max if both are set.
+    pub fn set_soft_limit_min(&mut self, min_mm: Option<f32>) -> anyhow::Result<()> {
+        if let Some(v) = min_mm {
+            if !v.is_finite() {
+                bail!("Reject soft-limit min axis {}: non-finite", self.name);
+            }
+            if let Some(max) = self.soft_limit_max_mm
+                && v >= max
+            {
+                bail!(
+                    "Reject soft-limit min axis {}: {:.3} >= current max {:.3}",
+                    self.name,
+                    v,
+                    max
+                );
+            }
+        }
+        self.soft_limit_min_mm = min_mm;
+        tracing::info!("[Axis {}] Soft-limit min = {:?} mm", self.name, min_mm);
+        Ok(())
+    }
+
+    // ============ Alarm ============
+
+    /// true while the alarm input of the driver reports an alarm, a read
+    /// error counts as alarm
+    pub fn alarm_input_active(&self) -> bool {
+        let Some(alarm) = &self.alarm else {
+            return false;
+        };
+        let raw = alarm.input.get_value().unwrap_or(!alarm.active_low);
+        if alarm.active_low { !raw } else { raw }
+    }
+
+    /// Latches a driver alarm and stops the axis. Returns true when the
+    /// alarm newly triggered.
+    pub fn check_alarm(&mut self) -> bool {
+        if self.alarm_active || !self.alarm_input_active() {
+            return false;
+        }
+        tracing::error!("[Axis {}] !!! ALARM: driver alarm triggered !!!", self.name);
+        self.alarm_active = true;
+        self.stop();
+        true
+    }
+
+    /// Clears the latched alarm, fails while the driver still reports it
+    pub fn reset_alarm(&mut self) -> anyhow::Result<()> {
+        if self.alarm_input_active() {
+            bail!("Axis {} alarm still active on hardware", self.name);
+        }
+        self.alarm_active = false;
+        Ok(())
+    }
+
+    // ============ Homing ============
+
+    /// true when the reference switch is reached, false without one
+    pub fn reference_reached(&self) -> bool {
+        let HomingStrategy::ReferenceSwitch(switch) = &self.homing else {
+            return false;
+        };
+        let raw = switch.input.get_value().unwrap_or(switch.normally_closed);
+        if switch.normally_closed { !raw } else { raw }
+    }
+
+    /// Start homing: 1) move negative until the reference switch, 2) retract,
+    /// 3) set the position to 0
+    pub fn start_homing(&mut self) -> anyhow::Result<()> {
+        let HomingStrategy::ReferenceSwitch(switch) = &self.homing else {
+            bail!("Axis {} has no reference switch", self.name);
+        };
+        if self.homing_phase != HomingPhase::Idle {
+            bail!("Axis {} already homing", self.name);
+        }
+
+        // Phase 1: search the sensor at the slow homing speed (negative)
+        let speed_mm_s = switch.speed_mm_s;
+        self.homing_phase = HomingPhase::SearchingSensor;
+        self.position_mode = false;
+        self.trajectory = None;
+        self.streaming = false;
+        self.target_speed_hz = -self.mechanics.mm_per_s_to_hz(speed_mm_s);
+        tracing::info!(
+            "[Axis {}] homing Phase 1: Searching sensor at {} Hz ({:.1} mm/s)",
+            self.name,
+            self.target_speed_hz,
+            speed_mm_s
+        );
+        Ok(())
+    }
+
+    /// Cancel homing, stops the axis
+    pub fn cancel_homing(&mut self) {
+        if self.homing_phase != HomingPhase::Idle {
+            self.stop();
+            tracing::info!("[Axis {}] homing cancelled", self.name);
+        }
+    }
+
+    /// Advances the homing state machine, returns true on a phase change
+    fn update_homing(&mut self) -> bool {
+        let HomingStrategy::ReferenceSwitch(switch) = &self.homing else {
+            return false;
+        };
+        let (speed_mm_s, retract_mm) = (switch.speed_mm_s, switch.retract_mm);
+
+        match self.homing_phase {
+            HomingPhase::Idle => false,
+            HomingPhase::SearchingSensor => {
+                if !self.reference_reached() {
+                    return false;
+                }
+                self.halt();
+
+                // Retract target in hw-counter space. The counter never
+                // wraps over the small retract distance.
+                let retract_pulses = self.mechanics.mm_to_pulses(retract_mm) as u32;
+                self.homing_retract_target = self.pto.get_position().wrapping_add(retract_pulses);
+
+                // Phase 2: move positive, away from the sensor
+                self.homing_phase = HomingPhase::Retracting;
+                self.target_speed_hz = self.mechanics.mm_per_s_to_hz(speed_mm_s);
+                tracing::info!(
+                    "[Axis {}] homing Phase 2: Retracting {:.1}mm (target hw {})",
+                    self.name,
+                    retract_mm,
+                    self.homing_retract_target
+                );
+                true
+            }
+            HomingPhase::Retracting => {
+                // Compare in signed delta space so wraparound doesn't fool us.
+                let delta = self
+                    .pto
+                    .get_position()
+                    .wrapping_sub(self.homing_retract_target) as i32;
+                if delta < 0 {
+                    return false;
+                }
+                self.halt();
+
+                // Phase 3: set the hw counter to the virtual offset so
+                // logical 0 is right here. The offset is updated eagerly so
+                // logical reads are correct immediately; SettingZero just
+                // waits for the hardware to confirm.
+                let mut output = self.pto.get_output();
+                output.set_counter = true;
+                output.set_counter_value = POSITION_OFFSET_PULSES;
+                self.pto.set_output(output);
+                self.position_offset = POSITION_OFFSET_PULSES;
+                self.homing_phase = HomingPhase::SettingZero;
+                tracing::info!(
+                    "[Axis {}] homing Phase 3: Setting hw counter to offset {} (logical 0)",
+                    self.name,
+                    POSITION_OFFSET_PULSES
+                );
+                true
+            }
+            HomingPhase::SettingZero => {
+                let input = self.pto.get_input();
+                if !input.set_counter_done && input.counter_value != self.position_offset {
+                    return false;
+                }
+                self.pto.clear_set_counter();
+
+                self.homing_phase = HomingPhase::Idle;
+                // From here on, soft limits apply (position counter is now calibrated).
+                self.homed = true;
+                // A fresh reference clears any step-loss flag.
+                self.step_loss = false;
+                tracing::info!(
+                    "[Axis {}] homing COMPLETE - logical position is now 0",
+                    self.name
+                );
+                true
+            }
+        }
+    }
+
+    // ============ Cycle ============
+
+    /// Runs once per cycle: watches the EL2522 status flags and pushes
+    /// frequency setpoints to the hardware, then advances homing.
+    ///
+    /// - **Position mode** (TDC): wait for `select_end_counter` to flag
+    ///   "target reached", then clear `go_counter` and check for step loss.
+    /// - **Position mode** (S-curve): stream the next trajectory setpoint,
+    ///   see [`Self::update_trajectory`].
+    /// - **Speed mode**: enforces the soft limits when homed, then forwards
+    ///   the target frequency to hardware.
+    pub fn update(&mut self, now: Instant) -> AxisUpdate {
+        let mut update = AxisUpdate::default();
+        let dt = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+        let input = self.pto.get_input();
+
+        // Auto-clear a pending set_counter once hardware confirms (offset
+        // write at construction and homing Phase 3), so the EL2522 doesn't
+        // keep clamping the counter to the set value forever.
+        if input.set_counter_done && self.pto.get_output().set_counter {
+            self.pto.clear_set_counter();
+        }
+
+        if self.trajectory.is_some() {
+            update = self.update_trajectory(dt);
+        } else if self.position_mode && !self.streaming {
+            if self.position_ignore_cycles > 0 {
+                self.position_ignore_cycles -= 1;
+            } else if input.select_end_counter {
+                self.speed_hz = 0;
+                self.target_speed_hz = 0;
+                self.position_mode = false;
+
+                let mut output = self.pto.get_output();
+                output.go_counter = false;
+                output.frequency_value = 0;
+                self.pto.set_output(output);
+
+                update.changed = true;
+                update.step_loss = self.check_step_loss();
+            }
+        }
+
+        if !self.position_mode {
+            if self.limits_enforced() {
+                let current_mm = self.position_mm();
+                if let Some(max_mm) = self.soft_limit_max_mm
+                    && current_mm >= max_mm
+                    && self.target_speed_hz > 0
+                {
+                    self.target_speed_hz = 0;
+                    tracing::warn!(
+                        "[Axis {}] soft max reached at {:.1} mm - stopping",
+                        self.name,
+                        current_mm
+                    );
+                }
+                if let Some(min_mm) = self.soft_limit_min_mm
+                    && current_mm <= min_mm
+                    && self.target_speed_hz < 0
+                {
+                    self.target_speed_hz = 0;
+                    tracing::warn!(
+                        "[Axis {}] soft min reached at {:.1} mm - stopping",
+                        self.name,
+                        current_mm
+                    );
+                }
+            }
+
+            if self.speed_hz != self.target_speed_hz {
+                // Hardware ramp accelerates/brakes automatically
+                let mut output = self.pto.get_output();
+                output.disble_ramp = false;
+                output.go_counter = false;
+                output.frequency_value = self.target_speed_hz;
+                self.pto.set_output(output);
+                self.speed_hz = self.target_speed_hz;
+                update.changed = true;
+            }
+        }
+
+        if input.ramp_active {
+            update.changed = true;
+        }
+
+        update.changed |= self.update_homing();
+        update
+    }
+
+    /// Streams the next S-curve setpoint, see [`Self::land`] for the end
+    /// of the move
+    fn update_trajectory(&mut self, dt: f64) -> AxisUpdate {
+        let mut update = AxisUpdate {
+            changed: true,
+            ..Default::default()
+        };
+        let Some(trajectory) = &mut self.trajectory else {
+            return update;
+        };
+        let speed_mm_s = trajectory.update(dt);
+        if trajectory.is_finished() {
+            update.step_loss = self.land();
+        } else {
+            self.stream_speed_mm_s(speed_mm_s);
+        }
+        update
+    }
+
+    /// Ends a streamed move once its speed is back at zero: the last pulses
+    /// to the exact target are driven with a slow Travel Distance Control
+    /// move, which then ends through the usual target-reached and step-loss
+    /// checks. Returns true if the position integrity is lost.
+    fn land(&mut self) -> bool {
+        self.trajectory = None;
+        self.streaming = false;
+        let remaining = self.target_position_pulses - self.position_pulses();
+        if remaining == 0 {
+            self.speed_hz = 0;
+            self.target_speed_hz = 0;
+            self.position_mode = false;
+            let mut output = self.pto.get_output();
+            output.disble_ramp = false;
+            output.frequency_value = 0;
+            self.pto.set_output(output);
+            return self.check_step_loss();
+        }
+        tracing::debug!("[Axis {}] landing {} pulses", self.name, remaining);
+        let landing_hz = self.mechanics.mm_per_s_to_hz(LANDING_SPEED_MM_S);
+        self.start_tdc_move(self.target_position_pulses, landing_hz);
+        false
+    }
+
+    /// Compares the end of a move with its target, returns true if the
+    /// position integrity is lost
+    fn check_step_loss(&mut self) -> bool {
+        let actual_pos = self.position_pulses();
+        let target_pos = self.target_position_pulses;
+        let deviation = (actual_pos - target_pos).abs();
+        let deviation_mm = self.mechanics.pulses_to_mm(deviation);
+
+        if self
+            .step_loss_invalidate_pulses
+            .is_some_and(|limit| deviation > limit)
+        {
+            // Position integrity lost: revoke homing (forces re-referencing,
+            // also disables the now-unreliable soft limits).
+            tracing::error!(
+                "[Axis {}] STEP LOSS: target={} actual={} deviation={} pulses ({:.2} mm) - homing revoked, re-reference required",
+                self.name,
+                target_pos,
+                actual_pos,
+                deviation,
+                deviation_mm
+            );
+            self.step_loss = true;
+            self.homed = false;
+            return true;
+        }
+        if deviation > self.step_loss_warn_pulses {
+            tracing::warn!(
+                "[Axis {}] STEP LOSS DETECTED: target={} actual={} deviation={} pulses ({:.2} mm)",
+                self.name,
+                target_pos,
+                actual_pos,
+                deviation,
+                deviation_mm
+            );
+        } else {
+            tracing::info!(
+                "[Axis {}] Target reached: {} pulses (actual: {}, deviation: {})",
+                self.name,
+                target_pos,
+                actual_pos,
+                deviation
+            );
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use ethercat_hal::io::pulse_train_output::{
+        PulseTrainOutputDevice, PulseTrainOutputInput, PulseTrainOutputOutput,
+    };
+    use smol::lock::RwLock;
+
+    use super::*;
+
+    pub(crate) const MECHANICS: LinearAxisMechanics = LinearAxisMechanics {
+        pulses_per_rev: 200,
+        lead_mm: 10.0,
+    };
+
+    pub(crate) struct FakePto {
+        pub(crate) input: PulseTrainOutputInput,
+        pub(crate) output: PulseTrainOutputOutput,
+    }
+
+    impl PulseTrainOutputDevice<()> for FakePto {
+        fn set_output(&mut self, _port: (), value: PulseTrainOutputOutput) {
+            self.output = value;
+        }
+        fn get_output(&self, _port: ()) -> PulseTrainOutputOutput {
+            self.output.clone()
+        }
+        fn get_input(&self, _port: ()) -> PulseTrainOutputInput {
+            self.input.clone()
+        }
+    }
+
+    pub(crate) fn fake_axis(config: LinearPtoAxisConfig) -> (LinearPtoAxis, Arc<RwLock<FakePto>>) {
+        let device = Arc::new(RwLock::new(FakePto {
+            input: PulseTrainOutputInput {
+                select_end_counter: false,
+                ramp_active: false,
+                input_t: false,
+                input_z: false,
+                error: false,
+                sync_error: false,
+                counter_underflow: false,
+                counter_overflow: false,
+                counter_value: POSITION_OFFSET_PULSES,
+                set_counter_done: false,
+            },
+            output: PulseTrainOutputOutput {
+                disble_ramp: false,
+                go_counter: false,
+                frequency_value: 0,
+                target_counter_value: 0,
+                set_counter: false,
+                set_counter_value: 0,
+            },
+        }));
+        let pto = PulseTrainOutput::new(device.clone(), ());
+        (LinearPtoAxis::new("test", pto, config), device)
+    }
+
+    #[test]
+    fn test_mechanics() {
+        assert_eq!(MECHANICS.pulses_per_mm(), 20.0);
+        assert_eq!(MECHANICS.mm_to_pulses(43.5), 870);
+        assert_eq!(MECHANICS.mm_per_s_to_hz(15.0), 300);
+        assert_eq!(MECHANICS.pulses_to_mm(-40), -2.0);
+        assert_eq!(MECHANICS.rpm_to_hz(60.0), 200);
+    }
+
+    #[test]
+    fn test_move_clamped_to_soft_limit() {
+        let (mut axis, device) = fake_axis(LinearPtoAxisConfig {
+            soft_limit_max_mm: Some(50.0),
+            ..LinearPtoAxisConfig::new(MECHANICS)
+        });
+        assert!(smol::block_on(device.read()).output.set_counter);
+
+        axis.move_to_mm(80.0, 10.0);
+        let output = smol::block_on(device.read()).output.clone();
+        assert!(output.go_counter);
+        assert_eq!(output.target_counter_value, POSITION_OFFSET_PULSES + 1000);
+        assert_eq!(output.frequency_value, 200);
+        assert_eq!(axis.target_position_pulses(), 1000);
+
+        axis.move_to_mm(-10.0, 10.0);
+        assert_eq!(axis.target_position_pulses(), -200);
+        assert!(axis.set_soft_limit_min(Some(60.0)).is_err());
+    }
+
+    #[test]
+    fn test_set_position() {
+        let (mut axis, device) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        smol::block_on(device.write()).input.counter_value = POSITION_OFFSET_PULSES + 400;
+        assert_eq!(axis.position_mm(), 20.0);
+
+        axis.set_position_mm(0.0).unwrap();
+        assert_eq!(axis.position_mm(), 0.0);
+        // Moves are relative to the new zero
+        axis.move_to_mm(10.0, 10.0);
+        let output = smol::block_on(device.read()).output.clone();
+        assert_eq!(output.target_counter_value, POSITION_OFFSET_PULSES + 600);
+        assert!(axis.set_position_mm(0.0).is_err());
+    }
+
+    #[test]
+    fn test_s_curve_move() {
+        let (mut axis, device) = fake_axis(LinearPtoAxisConfig {
+            s_curve_jerk_mm_s3: Some(1000.0),
+            ..LinearPtoAxisConfig::new(MECHANICS)
+        });
+        axis.move_to_mm(10.0, 20.0);
+        assert!(axis.is_position_mode() && axis.is_moving());
+        assert!(!smol::block_on(device.read()).output.go_counter);
+
+        // Streams setpoints with the hardware ramp off, rising at first; the
+        // fake counter follows the commanded frequency
+        let mut now = Instant::now();
+        let mut last_hz = 0;
+        let mut pulses = 0.0;
+        for i in 0..5000 {
+            now += std::time::Duration::from_millis(1);
+            axis.update(now);
+            let mut device = smol::block_on(device.write());
+            if device.output.go_counter {
+                break;
+            }
+            assert!(device.output.disble_ramp);
+            let hz = device.output.frequency_value;
+            assert!(i > 100 || hz >= last_hz);
+            last_hz = hz;
+            pulses += hz as f32 / 1000.0;
+            device.input.counter_value = POSITION_OFFSET_PULSES + pulses as u32;
+        }
+
+        // The last pulses are landed with Travel Distance Control
+        let output = smol::block_on(device.read()).output.clone();
+        assert!(output.go_counter && !output.disble_ramp);
+        assert_eq!(output.target_counter_value, POSITION_OFFSET_PULSES + 200);
+        assert!(axis.is_position_mode());
+        assert!((axis.position_mm() - 10.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_step_loss_revokes_homing() {
+        let (mut axis, device) = fake_axis(LinearPtoAxisConfig {
+            step_loss_invalidate_pulses: Some(20),
+            ..LinearPtoAxisConfig::new(MECHANICS)
+        });
+        assert!(axis.is_homed());
+
+        axis.move_to_mm(10.0, 10.0);
+        {
+            let mut device = smol::block_on(device.write());
+            device.input.select_end_counter = true;
+            device.input.counter_value = POSITION_OFFSET_PULSES + 150;
+        }
+        for _ in 0..POSITION_IGNORE_CYCLES {
+            assert!(!axis.update(Instant::now()).step_loss);
+        }
+        let update = axis.update(Instant::now());
+        assert!(update.changed && update.step_loss);
+        assert!(!axis.is_position_mode());
+        assert!(axis.has_step_loss());
+        assert!(!axis.is_homed());
+    }
+}
@@ -5,8 +5,10 @@ use ethercat_hal::devices::wago_modules::ip20_ec_di8_do8::IP20_EC_DI8_DO8_IDENTI
 use serde::Deserialize;
 use serde::Serialize;
 
+use control_core_derive::JsonSchema;
+
 /// Identifies a spacifi machine
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct MachineIdentificationUnique {
     pub machine_identification: MachineIdentification,
     pub serial: u16,
@@ -30,7 +32,7 @@ impl Display for MachineIdentificationUnique {
 }
 
 /// Identifies a machine
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct MachineIdentification {
     pub vendor: u16,
     pub machine: u16,
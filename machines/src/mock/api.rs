@@ -1,4 +1,4 @@
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 
 use super::MockMachine;
 use control_core::socketio::{
@@ -7,20 +7,20 @@ use control_core::socketio::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
-use control_core_derive::BuildEvent;
+use control_core_derive::{BuildEvent, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::channel::Sender;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub enum Mode {
     Standby,
     Running,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     pub amplitude_sum: f64,
     pub amplitude1: f64,
@@ -34,7 +34,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent)]
+#[derive(Serialize, Debug, Clone, PartialEq, BuildEvent, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// sine wave frequencies in millihertz
@@ -45,7 +45,7 @@ pub struct StateEvent {
     pub mode_state: ModeState,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
 pub struct ModeState {
     /// current mode
     pub mode: Mode,
@@ -79,9 +79,9 @@ impl CacheableEvents<Self> for MockEvents {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 /// Mutation for controlling the mock machine
-enum Mutation {
+pub enum Mutation {
     /// Set the frequency of the sine wave in millihertz
     SetFrequency1(f64),
     SetFrequency2(f64),
@@ -102,6 +102,12 @@ impl NamespaceCacheingLogic<MockEvents> for MockMachineNamespace {
     }
 }
 
+impl MachineApiSchema for MockMachine {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for MockMachine {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
         self.api_sender.clone()
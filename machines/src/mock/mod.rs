@@ -50,6 +50,18 @@ impl Machine for MockMachine {
     fn get_main_sender(&self) -> Option<Sender<AsyncThreadMessage>> {
         self.main_sender.clone()
     }
+
+    fn signal_names(&self) -> &'static [&'static str] {
+        &["amplitude_sum", "amplitude1", "amplitude2", "amplitude3"]
+    }
+
+    fn sample_signals(&self, values: &mut [f64]) {
+        let live_values = self.get_live_values();
+        values[0] = live_values.amplitude_sum;
+        values[1] = live_values.amplitude1;
+        values[2] = live_values.amplitude2;
+        values[3] = live_values.amplitude3;
+    }
 }
 
 impl MockMachine {
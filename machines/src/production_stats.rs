@@ -0,0 +1,441 @@
+//! Production counters and cycle-time analytics for pulse-train machines
+//!
+//! A [`ProductionTracker`] keeps the persisted [`ProductionStats`] of a
+//! machine up to date: completed sets, blocks and cycles, good vs aborted
+//! runs, the durations of those units and of the individual program steps,
+//! axis travel and move counts for maintenance intervals and the downtime
+//! per reason. The machine reports what its sequence does; axis usage is
+//! derived from [`AxisSnapshot`]s once per cycle.
+//!
+//! Durations are wall-clock time. A unit or step that is interrupted (pause,
+//! door, alarm, ...) is not timed, the time until the run continues counts
+//! as downtime of that reason instead.
+
+use crate::interlock::AxisSnapshot;
+use crate::state_file;
+use control_core_derive::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Dirty stats are written at most this often, and when a run ends
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Min/max/mean of a duration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DurationStats {
+    pub count: u64,
+    pub total_s: f64,
+    pub min_s: f64,
+    pub max_s: f64,
+    pub last_s: f64,
+}
+
+impl DurationStats {
+    pub fn record(&mut self, duration: Duration) {
+        let s = duration.as_secs_f64();
+        if self.count == 0 {
+            self.min_s = s;
+            self.max_s = s;
+        } else {
+            self.min_s = self.min_s.min(s);
+            self.max_s = self.max_s.max(s);
+        }
+        self.count += 1;
+        self.total_s += s;
+        self.last_s = s;
+    }
+
+    pub fn mean_s(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total_s / self.count as f64)
+    }
+}
+
+/// Usage of one axis since its last maintenance reset. Homing runs are not
+/// counted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AxisUsage {
+    pub travel_mm: f64,
+    pub moves: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DowntimeStats {
+    pub count: u64,
+    pub total_s: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductionCounters {
+    pub sets: u64,
+    pub blocks: u64,
+    pub cycles: u64,
+    /// Runs that completed all their sets
+    pub runs_completed: u64,
+    /// Runs stopped by the operator or discarded after a fault
+    pub runs_aborted: u64,
+}
+
+/// A unit of production, from the innermost to the whole run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Cycle,
+    Block,
+    Set,
+    Run,
+}
+
+/// Persisted analytics of a machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductionStats {
+    /// Unix time (s) of the last counter reset
+    #[serde(default)]
+    pub since_unix_s: u64,
+    #[serde(default)]
+    pub counters: ProductionCounters,
+    #[serde(default)]
+    pub cycle_time: DurationStats,
+    #[serde(default)]
+    pub block_time: DurationStats,
+    #[serde(default)]
+    pub set_time: DurationStats,
+    /// Duration of completed runs, pauses included
+    #[serde(default)]
+    pub run_time: DurationStats,
+    /// Duration per program step, keyed by step. Only steps that take time
+    /// (waiting for axes, dwell) are timed.
+    #[serde(default)]
+    pub step_times: BTreeMap<String, DurationStats>,
+    /// Program the step times belong to, they restart with another program
+    #[serde(default)]
+    pub step_times_program: Option<String>,
+    /// Per axis, reset separately for maintenance
+    #[serde(default)]
+    pub axes: Vec<AxisUsage>,
+    /// Time the machine stood still while a run was pending, per reason
+    #[serde(default)]
+    pub downtime: BTreeMap<String, DowntimeStats>,
+}
+
+impl ProductionStats {
+    fn stats_mut(&mut self, unit: Unit) -> &mut DurationStats {
+        match unit {
+            Unit::Cycle => &mut self.cycle_time,
+            Unit::Block => &mut self.block_time,
+            Unit::Set => &mut self.set_time,
+            Unit::Run => &mut self.run_time,
+        }
+    }
+}
+
+fn unix_now_s() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[derive(Debug)]
+pub struct ProductionTracker {
+    stats: ProductionStats,
+    /// `None` keeps the stats in memory only
+    path: Option<PathBuf>,
+    last_positions_mm: Vec<Option<f32>>,
+    was_moving: Vec<bool>,
+    /// Start of the unit in progress, indexed by [`Unit`]
+    unit_starts: [Option<Instant>; 4],
+    step: Option<(String, Instant)>,
+    downtime: Option<(String, Instant)>,
+    dirty: bool,
+    last_persist: Instant,
+}
+
+impl ProductionTracker {
+    /// In-memory tracker for `axes` axes
+    pub fn new(axes: usize) -> Self {
+        let stats = ProductionStats {
+            since_unix_s: unix_now_s(),
+            axes: vec![AxisUsage::default(); axes],
+            ..Default::default()
+        };
+        Self {
+            stats,
+            path: None,
+            last_positions_mm: vec![None; axes],
+            was_moving: vec![false; axes],
+            unit_starts: [None; 4],
+            step: None,
+            downtime: None,
+            dirty: false,
+            last_persist: Instant::now(),
+        }
+    }
+
+    /// Tracker persisted to the state file `filename` (see
+    /// [`crate::state_file`]). Starts from zero when the file is missing or
+    /// can't be parsed.
+    pub fn load(filename: &str, axes: usize) -> Self {
+        let p = state_file::path(filename);
+        let mut tracker = Self::new(axes);
+        match std::fs::read_to_string(&p) {
+            Ok(s) => match serde_json::from_str::<ProductionStats>(&s) {
+                Ok(mut stats) => {
+                    tracing::info!("[ProductionStats] Loaded from {}", p.display());
+                    stats.axes.resize(axes, AxisUsage::default());
+                    tracker.stats = stats;
+                }
+                Err(e) => tracing::warn!(
+                    "[ProductionStats] File at {} is corrupt ({}) - starting from zero",
+                    p.display(),
+                    e
+                ),
+            },
+            Err(_) => tracing::info!(
+                "[ProductionStats] No file at {} - starting from zero",
+                p.display()
+            ),
+        }
+        tracker.path = Some(p);
+        tracker
+    }
+
+    pub const fn stats(&self) -> &ProductionStats {
+        &self.stats
+    }
+
+    /// Accumulates axis travel and counts moves, to be called every cycle
+    pub fn track_axes(&mut self, axes: &[AxisSnapshot]) {
+        for (i, axis) in axes.iter().enumerate().take(self.stats.axes.len()) {
+            let last = self.last_positions_mm[i].replace(axis.position_mm);
+            let moving = axis.moving && !axis.homing;
+            // Homing re-zeroes the position, so its jumps aren't travel
+            let counted = !axis.homing && (moving || self.was_moving[i]);
+            if let Some(last) = last.filter(|_| counted) {
+                let delta = (axis.position_mm - last).abs() as f64;
+                if delta > 0.0 {
+                    self.stats.axes[i].travel_mm += delta;
+                    self.dirty = true;
+                }
+            }
+            if moving && !self.was_moving[i] {
+                self.stats.axes[i].moves += 1;
+                self.dirty = true;
+            }
+            self.was_moving[i] = moving;
+        }
+    }
+
+    /// A run starts, ending the downtime in progress
+    pub fn run_started(&mut self, now: Instant) {
+        self.end_downtime(now);
+        self.unit_starts = [None; 4];
+        self.unit_started(Unit::Run, now);
+    }
+
+    /// The run continues after a pause or interruption
+    pub fn run_resumed(&mut self, now: Instant) {
+        self.end_downtime(now);
+    }
+
+    pub fn run_completed(&mut self, now: Instant) {
+        self.unit_completed(Unit::Run, now);
+        self.stats.counters.runs_completed += 1;
+        self.persist();
+    }
+
+    /// The run was stopped or discarded, its downtime ends with it
+    pub fn run_aborted(&mut self, now: Instant) {
+        self.end_downtime(now);
+        self.interrupted();
+        self.unit_starts = [None; 4];
+        self.stats.counters.runs_aborted += 1;
+        self.dirty = true;
+        self.persist();
+    }
+
+    /// The run stands still for `reason` until it is resumed or aborted.
+    /// The units and the step in progress are not timed.
+    pub fn downtime_started(&mut self, reason: &str, now: Instant) {
+        self.interrupted();
+        if self.downtime.is_none() {
+            self.downtime = Some((reason.to_string(), now));
+        }
+    }
+
+    fn interrupted(&mut self) {
+        self.step = None;
+        for unit in [Unit::Cycle, Unit::Block, Unit::Set] {
+            self.unit_starts[unit as usize] = None;
+        }
+    }
+
+    fn end_downtime(&mut self, now: Instant) {
+        if let Some((reason, since)) = self.downtime.take() {
+            let entry = self.stats.downtime.entry(reason).or_default();
+            entry.count += 1;
+            entry.total_s += now.duration_since(since).as_secs_f64();
+            self.dirty = true;
+        }
+    }
+
+    pub fn unit_started(&mut self, unit: Unit, now: Instant) {
+        self.unit_starts[unit as usize] = Some(now);
+    }
+
+    /// Counts a completed unit and times it if its start was seen
+    pub fn unit_completed(&mut self, unit: Unit, now: Instant) {
+        let counters = &mut self.stats.counters;
+        match unit {
+            Unit::Cycle => counters.cycles += 1,
+            Unit::Block => counters.blocks += 1,
+            Unit::Set => counters.sets += 1,
+            // Counted as completed or aborted by the caller
+            Unit::Run => {}
+        }
+        if let Some(start) = self.unit_starts[unit as usize].take() {
+            self.stats.stats_mut(unit).record(now.duration_since(start));
+        }
+        self.dirty = true;
+    }
+
+    /// Starts timing `step` of `program` unless it is already timed
+    pub fn step_started(&mut self, program: &str, step: String, now: Instant) {
+        if self.stats.step_times_program.as_deref() != Some(program) {
+            self.stats.step_times.clear();
+            self.stats.step_times_program = Some(program.to_string());
+        }
+        if self.step.as_ref().is_none_or(|(s, _)| *s != step) {
+            self.step = Some((step, now));
+        }
+    }
+
+    pub fn step_completed(&mut self, now: Instant) {
+        if let Some((step, start)) = self.step.take() {
+            self.stats
+                .step_times
+                .entry(step)
+                .or_default()
+                .record(now.duration_since(start));
+            self.dirty = true;
+        }
+    }
+
+    /// Resets everything but the axis usage
+    pub fn reset(&mut self) {
+        self.stats = ProductionStats {
+            since_unix_s: unix_now_s(),
+            axes: std::mem::take(&mut self.stats.axes),
+            ..Default::default()
+        };
+        self.step = None;
+        self.unit_starts = [None; 4];
+        self.downtime = None;
+        self.dirty = true;
+        self.persist();
+    }
+
+    /// Maintenance reset of the usage of one axis
+    pub fn reset_axis(&mut self, axis: usize) -> anyhow::Result<()> {
+        let Some(usage) = self.stats.axes.get_mut(axis) else {
+            anyhow::bail!("Unknown axis {}", axis);
+        };
+        *usage = AxisUsage::default();
+        self.dirty = true;
+        self.persist();
+        Ok(())
+    }
+
+    /// Writes dirty stats every [`PERSIST_INTERVAL`], to be called every
+    /// cycle
+    pub fn persist_if_due(&mut self, now: Instant) {
+        if self.dirty && now.duration_since(self.last_persist) >= PERSIST_INTERVAL {
+            self.persist();
+        }
+    }
+
+    /// Writes dirty stats now. Errors are logged, not propagated.
+    pub fn persist(&mut self) {
+        self.last_persist = Instant::now();
+        if !std::mem::take(&mut self.dirty) {
+            return;
+        }
+        let Some(p) = &self.path else {
+            return;
+        };
+        match serde_json::to_string_pretty(&self.stats) {
+            Ok(json) => state_file::write_atomic(p, &json),
+            Err(e) => tracing::error!("[ProductionStats] Failed to serialize: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(position_mm: f32, moving: bool) -> AxisSnapshot {
+        AxisSnapshot {
+            position_mm,
+            homed: true,
+            homing: false,
+            moving,
+        }
+    }
+
+    #[test]
+    fn test_axis_usage() {
+        let mut tracker = ProductionTracker::new(1);
+        tracker.track_axes(&[axis(5.0, false)]);
+        tracker.track_axes(&[axis(6.0, true)]);
+        tracker.track_axes(&[axis(10.0, true)]);
+        // The last stretch is counted when the axis comes to rest
+        tracker.track_axes(&[axis(9.0, false)]);
+        tracker.track_axes(&[axis(20.0, false)]);
+        assert_eq!(tracker.stats().axes[0].travel_mm, 6.0);
+        assert_eq!(tracker.stats().axes[0].moves, 1);
+
+        // Homing re-zeroes without counting
+        let homing = AxisSnapshot {
+            homing: true,
+            ..axis(0.0, true)
+        };
+        tracker.track_axes(&[homing]);
+        tracker.track_axes(&[axis(0.0, false)]);
+        assert_eq!(tracker.stats().axes[0].travel_mm, 6.0);
+        assert_eq!(tracker.stats().axes[0].moves, 1);
+    }
+
+    #[test]
+    fn test_units_and_downtime() {
+        let t0 = Instant::now();
+        let at = |s| t0 + Duration::from_secs(s);
+        let mut tracker = ProductionTracker::new(0);
+
+        tracker.run_started(at(0));
+        tracker.unit_started(Unit::Cycle, at(0));
+        tracker.step_started("P", "wait".to_string(), at(1));
+        tracker.step_started("P", "wait".to_string(), at(2));
+        tracker.step_completed(at(3));
+        tracker.unit_completed(Unit::Cycle, at(4));
+        tracker.unit_started(Unit::Cycle, at(4));
+
+        // Interrupted mid-cycle: counted once completed, but not timed
+        tracker.downtime_started("door", at(5));
+        tracker.run_resumed(at(15));
+        tracker.unit_completed(Unit::Cycle, at(16));
+        tracker.run_completed(at(20));
+
+        let stats = tracker.stats();
+        assert_eq!(stats.counters.cycles, 2);
+        assert_eq!(stats.cycle_time.count, 1);
+        assert_eq!(stats.cycle_time.last_s, 4.0);
+        assert_eq!(stats.step_times["wait"].total_s, 2.0);
+        assert_eq!(stats.downtime["door"].total_s, 10.0);
+        assert_eq!(stats.run_time.last_s, 20.0);
+        assert_eq!(stats.counters.runs_completed, 1);
+
+        // Step times restart with another program
+        tracker.step_started("Q", "wait".to_string(), at(21));
+        assert!(tracker.stats().step_times.is_empty());
+    }
+}
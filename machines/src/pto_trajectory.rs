@@ -0,0 +1,192 @@
+//! Jerk-limited (S-curve) position moves for pulse train outputs
+//!
+//! Travel Distance Control of the EL2522 ramps linearly: the acceleration
+//! jumps at the start and the end of each ramp, which jolts heavy axes such
+//! as the BBM magazine transporter. A [`SCurveTrajectory`] is streamed as a
+//! frequency setpoint every cycle instead. A [`JerkSpeedController`] drives
+//! the speed towards the travel speed and, once the remaining distance only
+//! just covers its jerk-limited stopping distance, back to zero. See
+//! [`LinearPtoAxis`](crate::linear_pto_axis::LinearPtoAxis) for how the axis
+//! lands on the exact target pulse.
+
+use anyhow::bail;
+use control_core::controllers::second_degree_motion::jerk_speed_controller::JerkSpeedController;
+
+/// Below this the speed counts as stopped (mm/s)
+const STOPPED_SPEED_MM_S: f64 = 1e-3;
+
+/// Longest step a trajectory is advanced by at once, so a stalled loop
+/// doesn't make the setpoint jump
+const MAX_DT_S: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SCurveLimits {
+    pub speed_mm_s: f32,
+    pub acceleration_mm_s2: f32,
+    pub jerk_mm_s3: f32,
+}
+
+#[derive(Debug)]
+pub struct SCurveTrajectory {
+    controller: JerkSpeedController,
+    speed_mm_s: f64,
+    acceleration_mm_s2: f64,
+    jerk_mm_s3: f64,
+    target_mm: f64,
+    /// Integral of the streamed speed: where the pulses sent so far put the
+    /// axis
+    position_mm: f64,
+    /// Latched once the deceleration started
+    braking: bool,
+}
+
+impl SCurveTrajectory {
+    pub fn new(start_mm: f32, target_mm: f32, limits: SCurveLimits) -> anyhow::Result<Self> {
+        let SCurveLimits {
+            speed_mm_s,
+            acceleration_mm_s2,
+            jerk_mm_s3,
+        } = limits;
+        if !(speed_mm_s > 0.0 && acceleration_mm_s2 > 0.0 && jerk_mm_s3 > 0.0) {
+            bail!("S-curve limits must be positive, got {:?}", limits);
+        }
+        let (acceleration, jerk) = (acceleration_mm_s2 as f64, jerk_mm_s3 as f64);
+        // No speed limit: the commanded speed never exceeds it, and clamping
+        // would cut the acceleration off abruptly.
+        let controller = JerkSpeedController::new_simple(None, acceleration, jerk);
+        Ok(Self {
+            controller,
+            speed_mm_s: speed_mm_s as f64,
+            acceleration_mm_s2: acceleration,
+            jerk_mm_s3: jerk,
+            target_mm: target_mm as f64,
+            position_mm: start_mm as f64,
+            braking: false,
+        })
+    }
+
+    pub fn target_mm(&self) -> f32 {
+        self.target_mm as f32
+    }
+
+    /// Position the streamed speed has reached so far
+    pub fn position_mm(&self) -> f32 {
+        self.position_mm as f32
+    }
+
+    /// Advances by `dt` seconds, returns the speed setpoint in mm/s
+    pub fn update(&mut self, dt: f64) -> f32 {
+        let dt = dt.min(MAX_DT_S);
+        let remaining = self.target_mm - self.position_mm;
+        let direction = remaining.signum();
+        // Speed and acceleration in the direction of travel
+        let speed = self.controller.get_speed() * direction;
+        let acceleration = self.controller.get_acceleration() * direction;
+        // Brake one cycle early rather than one cycle late
+        let lookahead = speed.max(0.0) * dt;
+        if !self.braking
+            && remaining.abs() <= self.stopping_distance(speed, acceleration) + lookahead
+        {
+            self.braking = true;
+        }
+        let target_speed = if self.braking {
+            0.0
+        } else {
+            self.speed_mm_s * direction
+        };
+        let speed = self.controller.update(target_speed, dt);
+        self.position_mm += speed * dt;
+        speed as f32
+    }
+
+    /// The deceleration is done, the axis is at (or a few pulses off) the
+    /// target
+    pub fn is_finished(&self) -> bool {
+        let stopped = self.controller.get_speed().abs() < STOPPED_SPEED_MM_S;
+        stopped && (self.braking || (self.target_mm - self.position_mm).abs() < 1e-6)
+    }
+
+    /// Distance to stop from `speed` while accelerating with
+    /// `acceleration`: the acceleration is first ramped back to zero, then
+    /// the speed comes down on an S-curve
+    fn stopping_distance(&self, speed: f64, acceleration: f64) -> f64 {
+        let (a_max, jerk) = (self.acceleration_mm_s2, self.jerk_mm_s3);
+        let (mut distance, mut speed) = (0.0, speed.max(0.0));
+        if acceleration > 0.0 {
+            let t = acceleration / jerk;
+            distance += speed * t + acceleration * t * t / 2.0 - jerk * t.powi(3) / 6.0;
+            speed += acceleration * t / 2.0;
+        }
+        distance
+            + if speed >= a_max * a_max / jerk {
+                // Trapezoidal deceleration: jerk, constant, jerk
+                speed * (speed / a_max + a_max / jerk) / 2.0
+            } else {
+                // Triangular deceleration, a_max is never reached
+                speed * (speed / jerk).sqrt()
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CYCLE_S: f64 = 0.0007;
+
+    #[test]
+    fn test_s_curve_reaches_target() {
+        for (start, target, max_speed, accel, jerk) in [
+            (0.0, 500.0, 200.0, 100.0, 1000.0),
+            (0.0, 20.0, 200.0, 100.0, 1000.0),
+            (5.0, 6.0, 100.0, 100.0, 1000.0),
+            (100.0, -150.0, 150.0, 100.0, 400.0),
+        ] {
+            let limits = SCurveLimits {
+                speed_mm_s: max_speed,
+                acceleration_mm_s2: accel,
+                jerk_mm_s3: jerk,
+            };
+            let mut trajectory = SCurveTrajectory::new(start, target, limits).unwrap();
+            let (mut last_speed, mut t) = (0.0f32, 0.0);
+            while !trajectory.is_finished() {
+                let speed = trajectory.update(CYCLE_S);
+                assert!(speed.abs() <= max_speed + 0.5);
+                // Acceleration within its limit (plus rounding at the ramp ends)
+                assert!(((speed - last_speed) / CYCLE_S as f32).abs() < accel * 1.2);
+                last_speed = speed;
+                t += CYCLE_S;
+                assert!(t < 10.0, "{} -> {} did not finish", start, target);
+            }
+            // The axis lands the last pulses with Travel Distance Control
+            assert!((trajectory.position_mm() - target).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_acceleration_ramps_up() {
+        let limits = SCurveLimits {
+            speed_mm_s: 100.0,
+            acceleration_mm_s2: 100.0,
+            jerk_mm_s3: 1000.0,
+        };
+        let mut trajectory = SCurveTrajectory::new(0.0, 100.0, limits).unwrap();
+        // After 10 ms the jerk limit allows 10 mm/s², the speed is 0.05 mm/s
+        let mut speed = 0.0;
+        for _ in 0..10 {
+            speed = trajectory.update(0.001);
+        }
+        assert!((speed - 0.05).abs() < 0.01);
+        assert!(
+            SCurveTrajectory::new(
+                0.0,
+                1.0,
+                SCurveLimits {
+                    jerk_mm_s3: 0.0,
+                    ..limits
+                }
+            )
+            .is_err()
+        );
+    }
+}
@@ -10,7 +10,8 @@ use crate::{
 };
 
 use crate::{
-    Machine, MachineNewParams, MachineNewTrait, machine_identification::MachineIdentification,
+    Machine, MachineApiSchema, MachineNewParams, MachineNewTrait,
+    machine_identification::MachineIdentification,
 };
 
 #[cfg(not(feature = "mock-machine"))]
@@ -26,13 +27,24 @@ use crate::test_machine::TestMachine;
 use lazy_static::lazy_static;
 
 use anyhow::Error;
+use control_core::schema::JsonSchema;
+use serde_json::Value;
 use std::{any::TypeId, collections::HashMap};
 
 pub type MachineNewClosure =
     Box<dyn Fn(&MachineNewParams) -> Result<Box<dyn Machine>, Error> + Send + Sync>;
 
+/// JSON Schemas of the [`MachineApiSchema`] types of a machine
+#[derive(Debug, Clone)]
+pub struct MachineSchemas {
+    pub mutation: Value,
+    pub state: Value,
+    pub live_values: Value,
+}
+
 pub struct MachineRegistry {
     type_map: HashMap<TypeId, (MachineIdentification, MachineNewClosure)>,
+    schemas: HashMap<MachineIdentification, MachineSchemas>,
 }
 
 impl Default for MachineRegistry {
@@ -45,13 +57,22 @@ impl MachineRegistry {
     pub fn new() -> Self {
         Self {
             type_map: HashMap::new(),
+            schemas: HashMap::new(),
         }
     }
 
-    pub fn register<T: MachineNewTrait + 'static>(
+    pub fn register<T: MachineNewTrait + MachineApiSchema + 'static>(
         &mut self,
         machine_identficiation: MachineIdentification,
     ) {
+        self.schemas.insert(
+            machine_identficiation.clone(),
+            MachineSchemas {
+                mutation: T::Mutation::json_schema(),
+                state: T::State::json_schema(),
+                live_values: T::LiveValues::json_schema(),
+            },
+        );
         self.type_map.insert(
             TypeId::of::<T>(),
             (
@@ -94,6 +115,20 @@ impl MachineRegistry {
         // call machine new function by reference
         (machine_new_closure)(machine_new_params)
     }
+
+    pub fn schemas(
+        &self,
+        machine_identification: &MachineIdentification,
+    ) -> Option<&MachineSchemas> {
+        self.schemas.get(machine_identification)
+    }
+
+    /// Schemas of all registered machines, sorted by machine identification
+    pub fn all_schemas(&self) -> Vec<(&MachineIdentification, &MachineSchemas)> {
+        let mut schemas: Vec<_> = self.schemas.iter().collect();
+        schemas.sort_by_key(|(id, _)| (id.vendor, id.machine));
+        schemas
+    }
 }
 
 lazy_static! {
@@ -13,11 +13,20 @@ impl MachineAct for SchneidemaschineV0 {
         }
 
         // Hardware monitor: watch hardware status, no timing needed
-        let status_changed = self.update_hardware_monitor();
+        let status_changed = self.update_hardware_monitor(now);
         if status_changed {
             self.emit_state();
         }
 
+        // Feed ⟷ knife interlocks, then the cut job step
+        let interlock_tripped = self.enforce_interlocks();
+        if self.update_cut_jobs(now) || interlock_tripped {
+            self.emit_state();
+        }
+
+        // Axis travel and move counts, periodic persistence of the counters
+        self.track_production(now);
+
         // Emit state and live values at ~30 Hz
         if now.duration_since(self.last_state_emit) > Duration::from_secs_f64(1.0 / 30.0) {
             self.emit_live_values();
@@ -25,7 +34,7 @@ impl MachineAct for SchneidemaschineV0 {
         }
 
         // Periodic debug log to console (every 1 second when any axis is moving)
-        let any_axis_moving = self.axis_speeds.iter().any(|&s| s != 0);
+        let any_axis_moving = self.axes.iter().any(|a| a.speed_hz() != 0);
         if any_axis_moving {
             let should_log = match self.last_debug_log {
                 Some(last) => now.duration_since(last) > DEBUG_LOG_INTERVAL,
@@ -34,8 +43,8 @@ impl MachineAct for SchneidemaschineV0 {
             if should_log {
                 self.last_debug_log = Some(now);
                 // Log info for the moving axis
-                for (i, &speed) in self.axis_speeds.iter().enumerate() {
-                    if speed != 0 {
+                for (i, axis) in self.axes.iter().enumerate() {
+                    if axis.speed_hz() != 0 {
                         let pto_info = self.get_debug_pto(i);
                         tracing::info!(
                             "[Achse{}] freq={}Hz ({:.1}mm/s) pos={}p ({:.1}mm) ramp={} err={}",
@@ -63,9 +72,8 @@ impl MachineAct for SchneidemaschineV0 {
             MachineMessage::UnsubscribeNamespace => {
                 self.namespace.namespace = None;
             }
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(_machine_connection) => {
                 // Does not connect to other machines; do nothing
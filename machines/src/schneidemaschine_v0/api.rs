@@ -1,17 +1,20 @@
 use super::SchneidemaschineV0;
-use crate::{MachineApi, MachineMessage};
+use super::cut_job::{CutJob, CutJobSpec, CutPhase, JobState};
+use crate::production_stats::ProductionStats;
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
 /// State event - contains controllable values (outputs, speeds)
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     pub output_states: [bool; 8],
     pub axis_speeds: [i32; 2],
@@ -19,6 +22,22 @@ pub struct StateEvent {
     pub axis_accelerations: [f32; 2],
     pub axis_target_positions: [i32; 2],
     pub axis_position_mode: [bool; 2],
+    /// Knife confirmed at its top position
+    pub knife_up: bool,
+    /// Knife confirmed through the material
+    pub knife_down: bool,
+    /// Ids of the interlock rules currently blocking a command
+    pub active_interlocks: Vec<String>,
+    /// A reference cut has put the material edge at the knife
+    pub referenced: bool,
+    pub job_state: JobState,
+    pub cut_phase: CutPhase,
+    /// Fault that stopped the jobs, cleared by `ResetCutFault`
+    pub cut_fault: Option<String>,
+    /// Queued jobs, the first one runs
+    pub cut_jobs: Vec<CutJob>,
+    /// Production counters and axis usage (persisted to disk)
+    pub production: ProductionStats,
 }
 
 impl StateEvent {
@@ -28,10 +47,10 @@ impl StateEvent {
 }
 
 /// Live values event - contains sensor readings and positions
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct LiveValuesEvent {
     pub input_states: [bool; 8],
-    pub axis_positions: [u32; 2],
+    pub axis_positions: [i32; 2],
 }
 
 impl LiveValuesEvent {
@@ -41,7 +60,7 @@ impl LiveValuesEvent {
 }
 
 /// Debug event for PTO channel - comprehensive EtherCAT status
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct DebugPtoEvent {
     pub channel: u8,
     // Output (what we send to the device)
@@ -74,13 +93,13 @@ impl DebugPtoEvent {
 
 /// Events emitted by the machine
 pub enum SchneidemaschineV0Events {
-    State(Event<StateEvent>),
+    State(Box<Event<StateEvent>>),
     LiveValues(Event<LiveValuesEvent>),
     DebugPto(Event<DebugPtoEvent>),
 }
 
 /// Mutations (commands from UI to machine)
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(tag = "action", content = "value")]
 pub enum Mutation {
     /// Set a single digital output
@@ -101,6 +120,26 @@ pub enum Mutation {
     },
     /// Stop all axes
     StopAllAxes,
+    /// Cut once and restart the feed position at the new edge
+    ReferenceCut,
+    /// Queue a cut job
+    QueueCutJob { job: CutJobSpec },
+    /// Remove a queued job
+    RemoveCutJob { id: u32 },
+    /// Remove all jobs but the running one
+    ClearCutJobs,
+    /// Start or continue the queued jobs
+    StartCutJobs,
+    /// Pause after the piece in progress
+    PauseCutJobs,
+    /// Stop immediately
+    StopCutJobs,
+    /// Clear the cut fault
+    ResetCutFault,
+    /// Reset the production counters, cycle times and downtimes
+    ResetProductionStats,
+    /// Reset travel and move count of an axis after its maintenance
+    ResetAxisUsage { axis: usize },
     /// Request debug info for a PTO channel (emits DebugPtoEvent)
     DebugPto { index: usize },
     /// Log all debug info to server console
@@ -125,7 +164,7 @@ impl NamespaceCacheingLogic<SchneidemaschineV0Events> for SchneidemaschineV0Name
 impl CacheableEvents<SchneidemaschineV0Events> for SchneidemaschineV0Events {
     fn event_value(&self) -> GenericEvent {
         match self {
-            Self::State(event) => event.clone().into(),
+            Self::State(event) => event.as_ref().clone().into(),
             Self::LiveValues(event) => event.clone().into(),
             Self::DebugPto(event) => event.clone().into(),
         }
@@ -136,6 +175,12 @@ impl CacheableEvents<SchneidemaschineV0Events> for SchneidemaschineV0Events {
     }
 }
 
+impl MachineApiSchema for SchneidemaschineV0 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for SchneidemaschineV0 {
     fn api_get_sender(&self) -> smol::channel::Sender<MachineMessage> {
         self.api_sender.clone()
@@ -144,10 +189,26 @@ impl MachineApi for SchneidemaschineV0 {
     fn api_mutate(&mut self, request_body: Value) -> Result<(), anyhow::Error> {
         let mutation: Mutation = serde_json::from_value(request_body)?;
         match mutation {
-            Mutation::SetOutput { index, on } => self.set_output(index, on),
-            Mutation::SetAllOutputs { on } => self.set_all_outputs(on),
-            Mutation::SetAxisSpeed { index, speed } => self.set_axis_speed(index, speed),
+            Mutation::SetOutput { index, on } => {
+                self.check_output_allowed(index, on)?;
+                self.set_output(index, on)
+            }
+            Mutation::SetAllOutputs { on } => {
+                for index in 0..self.output_states.len() {
+                    self.check_output_allowed(index, on)?;
+                }
+                self.set_all_outputs(on)
+            }
+            Mutation::SetAxisSpeed { index, speed } => {
+                if speed != 0 {
+                    self.check_move_allowed(index)?;
+                }
+                self.set_axis_speed(index, speed)
+            }
             Mutation::SetAxisSpeedMmS { index, speed_mm_s } => {
+                if speed_mm_s != 0.0 {
+                    self.check_move_allowed(index)?;
+                }
                 self.set_axis_speed_mm_s(index, speed_mm_s)
             }
             Mutation::SetAxisAcceleration { index, accel_mm_s2 } => {
@@ -157,8 +218,21 @@ impl MachineApi for SchneidemaschineV0 {
                 index,
                 position_mm,
                 speed_mm_s,
-            } => self.move_to_position_mm(index, position_mm, speed_mm_s),
+            } => {
+                self.check_move_allowed(index)?;
+                self.move_to_position_mm(index, position_mm, speed_mm_s)
+            }
             Mutation::StopAllAxes => self.stop_all_axes(),
+            Mutation::ReferenceCut => self.start_reference_cut()?,
+            Mutation::QueueCutJob { job } => self.queue_cut_job(job)?,
+            Mutation::RemoveCutJob { id } => self.remove_cut_job(id)?,
+            Mutation::ClearCutJobs => self.clear_cut_jobs(),
+            Mutation::StartCutJobs => self.start_cut_jobs()?,
+            Mutation::PauseCutJobs => self.pause_cut_jobs(),
+            Mutation::StopCutJobs => self.stop_cut_jobs(),
+            Mutation::ResetCutFault => self.reset_cut_fault()?,
+            Mutation::ResetProductionStats => self.reset_production_stats(),
+            Mutation::ResetAxisUsage { axis } => self.reset_axis_usage(axis)?,
             Mutation::DebugPto { index } => self.emit_debug_pto(index),
             Mutation::DebugLogAll => self.log_debug_all(),
         }
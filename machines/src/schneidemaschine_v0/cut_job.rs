@@ -0,0 +1,213 @@
+//! Length-based cut jobs
+//!
+//! A [`CutJob`] cuts `quantity` pieces of `length_mm`: the feed pushes the
+//! material `length_mm` past the knife, the knife comes down and goes back
+//! up, the feed position restarts at zero. Pieces are optionally grouped
+//! into batches of `batch_size`, the machine pauses after each batch so the
+//! operator can take it out. Jobs are queued and run in order; a job leaves
+//! the queue once all its pieces are cut.
+
+use anyhow::bail;
+use control_core_derive::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Feed speed when a job names none
+pub const DEFAULT_FEED_SPEED_MM_S: f32 = 100.0;
+pub const MIN_CUT_LENGTH_MM: f32 = 1.0;
+pub const MAX_CUT_LENGTH_MM: f32 = 10_000.0;
+pub const MAX_FEED_SPEED_MM_S: f32 = 500.0;
+pub const MAX_QUANTITY: u32 = 100_000;
+pub const MAX_QUEUED_JOBS: usize = 64;
+
+/// A job as requested by the operator
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
+pub struct CutJobSpec {
+    pub length_mm: f32,
+    pub quantity: u32,
+    /// Pause after this many pieces, `None` runs the job through
+    pub batch_size: Option<u32>,
+    /// Defaults to [`DEFAULT_FEED_SPEED_MM_S`]
+    pub speed_mm_s: Option<f32>,
+}
+
+/// A queued job
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct CutJob {
+    pub id: u32,
+    pub length_mm: f32,
+    pub quantity: u32,
+    pub batch_size: Option<u32>,
+    pub speed_mm_s: f32,
+    /// Pieces cut so far
+    pub cut: u32,
+}
+
+impl CutJob {
+    pub const fn is_done(&self) -> bool {
+        self.cut >= self.quantity
+    }
+
+    /// True when the last piece cut completed a batch
+    pub fn batch_complete(&self) -> bool {
+        self.cut > 0 && self.batch_size.is_some_and(|n| self.cut.is_multiple_of(n))
+    }
+}
+
+/// What the job mode is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum JobState {
+    Idle,
+    Running,
+    /// Pieces are cut until the current one is done, then the jobs pause
+    Pausing,
+    /// Paused by the operator or after a batch, the current job continues
+    /// on start
+    Paused,
+}
+
+/// Step of the piece (or reference cut) in progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum CutPhase {
+    Idle,
+    Feeding,
+    KnifeDown,
+    KnifeDwell,
+    KnifeUp,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CutJobQueue {
+    jobs: Vec<CutJob>,
+    next_id: u32,
+}
+
+impl CutJobQueue {
+    pub fn jobs(&self) -> &[CutJob] {
+        &self.jobs
+    }
+
+    /// The job that runs next (or is running)
+    pub fn current(&self) -> Option<&CutJob> {
+        self.jobs.first()
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut CutJob> {
+        self.jobs.first_mut()
+    }
+
+    /// Validates and queues a job, returns its id
+    pub fn push(&mut self, spec: CutJobSpec) -> anyhow::Result<u32> {
+        if self.jobs.len() >= MAX_QUEUED_JOBS {
+            bail!("Job queue full ({} jobs)", MAX_QUEUED_JOBS);
+        }
+        if !(MIN_CUT_LENGTH_MM..=MAX_CUT_LENGTH_MM).contains(&spec.length_mm) {
+            bail!(
+                "Cut length must be {} to {} mm, got {}",
+                MIN_CUT_LENGTH_MM,
+                MAX_CUT_LENGTH_MM,
+                spec.length_mm
+            );
+        }
+        if !(1..=MAX_QUANTITY).contains(&spec.quantity) {
+            bail!(
+                "Quantity must be 1 to {}, got {}",
+                MAX_QUANTITY,
+                spec.quantity
+            );
+        }
+        if spec.batch_size == Some(0) {
+            bail!("Batch size must be at least 1");
+        }
+        let speed_mm_s = spec.speed_mm_s.unwrap_or(DEFAULT_FEED_SPEED_MM_S);
+        if !(speed_mm_s > 0.0 && speed_mm_s <= MAX_FEED_SPEED_MM_S) {
+            bail!(
+                "Feed speed must be above 0 and at most {} mm/s, got {}",
+                MAX_FEED_SPEED_MM_S,
+                speed_mm_s
+            );
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(CutJob {
+            id,
+            length_mm: spec.length_mm,
+            quantity: spec.quantity,
+            batch_size: spec.batch_size,
+            speed_mm_s,
+            cut: 0,
+        });
+        Ok(id)
+    }
+
+    /// Removes a job. The current job can only be removed while the jobs
+    /// are not running (`running`).
+    pub fn remove(&mut self, id: u32, running: bool) -> anyhow::Result<()> {
+        let Some(index) = self.jobs.iter().position(|j| j.id == id) else {
+            bail!("Unknown job {}", id);
+        };
+        if index == 0 && running {
+            bail!("Job {} is running", id);
+        }
+        self.jobs.remove(index);
+        Ok(())
+    }
+
+    /// Removes all jobs but the current one if `running`
+    pub fn clear(&mut self, running: bool) {
+        self.jobs.truncate(if running { 1 } else { 0 });
+    }
+
+    /// Removes the current job once it is done, returns true if it was
+    pub fn pop_done(&mut self) -> bool {
+        if self.current().is_some_and(CutJob::is_done) {
+            self.jobs.remove(0);
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(length_mm: f32, quantity: u32) -> CutJobSpec {
+        CutJobSpec {
+            length_mm,
+            quantity,
+            batch_size: Some(2),
+            speed_mm_s: None,
+        }
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut queue = CutJobQueue::default();
+        assert!(queue.push(spec(0.5, 3)).is_err());
+        assert!(queue.push(spec(100.0, 0)).is_err());
+        let first = queue.push(spec(100.0, 3)).unwrap();
+        let second = queue.push(spec(250.0, 1)).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(queue.current().unwrap().speed_mm_s, DEFAULT_FEED_SPEED_MM_S);
+
+        // Batches of 2 out of 3 pieces
+        let job = queue.current_mut().unwrap();
+        job.cut = 1;
+        assert!(!job.batch_complete());
+        job.cut = 2;
+        assert!(job.batch_complete() && !job.is_done());
+        assert!(!queue.pop_done());
+        queue.current_mut().unwrap().cut = 3;
+        assert!(queue.pop_done());
+        assert_eq!(queue.current().unwrap().id, second);
+
+        // The running job stays
+        assert!(queue.remove(second, true).is_err());
+        queue.push(spec(10.0, 1)).unwrap();
+        queue.clear(true);
+        assert_eq!(queue.jobs().len(), 1);
+        queue.remove(second, false).unwrap();
+        assert!(queue.current().is_none());
+    }
+}
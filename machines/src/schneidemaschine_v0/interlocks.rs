@@ -0,0 +1,96 @@
+//! Safety rules of the Schneidemaschine, evaluated by the
+//! [`InterlockEngine`](crate::interlock::InterlockEngine). The feed and the
+//! knife exclude each other: material moving under a closing knife tears or
+//! jams.
+
+use super::{axes, inputs, outputs};
+use crate::interlock::{Condition, InterlockRule, Reaction, Scope};
+
+/// The feed only moves with the knife up (confirmed) and its valve off
+pub const KNIFE_UP: &str = "knife_up";
+/// The knife only comes down with the feed at rest
+pub const FEED_AT_REST: &str = "feed_at_rest";
+
+pub fn rules() -> Vec<InterlockRule> {
+    vec![
+        InterlockRule {
+            id: KNIFE_UP,
+            description: "Messer nicht oben".to_string(),
+            scopes: vec![Scope::Motion(Some(axes::FEED))],
+            armed: Condition::Always,
+            permit: Condition::All(vec![
+                Condition::Input {
+                    index: inputs::MESSER_OBEN,
+                    high: true,
+                },
+                Condition::Output {
+                    index: outputs::MESSER,
+                    on: false,
+                },
+            ]),
+            reaction: Reaction::Stop,
+        },
+        InterlockRule {
+            id: FEED_AT_REST,
+            description: "Vorschub läuft".to_string(),
+            scopes: vec![Scope::Output(outputs::MESSER)],
+            armed: Condition::Always,
+            permit: !Condition::AxisMoving(axes::FEED),
+            reaction: Reaction::Stop,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interlock::{AxisSnapshot, Command, InterlockEngine, MachineSnapshot};
+
+    #[test]
+    fn test_feed_and_knife_exclude_each_other() {
+        let engine = InterlockEngine::new(rules());
+        let mut axes = [AxisSnapshot::default(); 2];
+        let mut inputs = [false; 8];
+        inputs[inputs::MESSER_OBEN] = true;
+        let mut outputs = [false; 8];
+        let check = |command, axes: &[AxisSnapshot], inputs: &[bool], outputs: &[bool]| {
+            let s = MachineSnapshot {
+                axes,
+                inputs,
+                outputs,
+            };
+            engine.check(command, &s).err().map(|b| b.rule)
+        };
+
+        assert_eq!(
+            check(Command::Move(axes::FEED), &axes, &inputs, &outputs),
+            None
+        );
+        assert_eq!(
+            check(Command::SwitchOn(outputs::MESSER), &axes, &inputs, &outputs),
+            None
+        );
+
+        // Knife valve on: no feed
+        outputs[outputs::MESSER] = true;
+        assert_eq!(
+            check(Command::Move(axes::FEED), &axes, &inputs, &outputs),
+            Some(KNIFE_UP)
+        );
+
+        // Feed moving: knife stays up
+        outputs[outputs::MESSER] = false;
+        axes[axes::FEED].moving = true;
+        assert_eq!(
+            check(Command::SwitchOn(outputs::MESSER), &axes, &inputs, &outputs),
+            Some(FEED_AT_REST)
+        );
+
+        // Knife not confirmed up: no feed
+        inputs[inputs::MESSER_OBEN] = false;
+        assert_eq!(
+            check(Command::Move(axes::FEED), &axes, &inputs, &outputs),
+            Some(KNIFE_UP)
+        );
+    }
+}
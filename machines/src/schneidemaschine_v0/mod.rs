@@ -1,17 +1,23 @@
+use crate::interlock::{AxisSnapshot, Blocked, Command, InterlockEngine, MachineSnapshot};
+use crate::linear_pto_axis::{LinearAxisMechanics, LinearPtoAxis};
 use crate::machine_identification::{MachineIdentification, MachineIdentificationUnique};
+use crate::production_stats::{ProductionTracker, Unit};
 use crate::schneidemaschine_v0::api::{
     DebugPtoEvent, LiveValuesEvent, SchneidemaschineV0Events, StateEvent,
 };
 use crate::{AsyncThreadMessage, Machine, MachineMessage, SCHNEIDEMASCHINE_V0, VENDOR_QITECH};
+use anyhow::{anyhow, bail};
 use control_core::socketio::namespace::NamespaceCacheingLogic;
+use cut_job::{CutJobQueue, CutJobSpec, CutPhase, JobState};
 use ethercat_hal::io::digital_input::DigitalInput;
 use ethercat_hal::io::digital_output::DigitalOutput;
-use ethercat_hal::io::pulse_train_output::PulseTrainOutput;
 use smol::channel::{Receiver, Sender};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub mod act;
 pub mod api;
+pub mod cut_job;
+pub mod interlocks;
 pub mod new;
 
 use crate::schneidemaschine_v0::api::SchneidemaschineV0Namespace;
@@ -23,31 +29,53 @@ pub mod roles {
     pub const PTO: u16 = 3; // EL2522
 }
 
-/// Mechanical constants for the linear axis
-pub mod mechanics {
-    /// Motor pulses per revolution (CL57T setting)
-    pub const PULSES_PER_REV: u32 = 200;
-    /// Ball screw lead in mm per revolution
-    pub const LEAD_MM: f32 = 10.0;
-    /// Calculated pulses per mm
-    pub const PULSES_PER_MM: f32 = PULSES_PER_REV as f32 / LEAD_MM; // = 20.0
+/// Axis indices. Only EL2522 channel 2 is configured, channel 1 is unused.
+pub mod axes {
+    /// Roller feed pushing the material past the knife
+    pub const FEED: usize = 1;
+}
 
-    /// Convert mm/s to frequency (Hz)
-    pub fn mm_per_s_to_hz(mm_per_s: f32) -> i32 {
-        (mm_per_s * PULSES_PER_MM) as i32
-    }
+/// Digital input indices (EL1008)
+pub mod inputs {
+    /// Knife at its top position (24V = reached)
+    pub const MESSER_OBEN: usize = 0;
+    /// Knife through the material (24V = reached)
+    pub const MESSER_UNTEN: usize = 1;
+}
 
-    /// Convert frequency (Hz) to mm/s
-    pub fn hz_to_mm_per_s(hz: i32) -> f32 {
-        hz as f32 / PULSES_PER_MM
-    }
+/// Digital output indices (EL2008)
+pub mod outputs {
+    /// Knife valve, on = knife comes down
+    pub const MESSER: usize = 0;
+}
 
-    /// Convert position (pulses) to mm
-    pub fn pulses_to_mm(pulses: u32) -> f32 {
-        pulses as f32 / PULSES_PER_MM
-    }
+/// Longest time the knife may take to confirm its top or bottom position
+pub const KNIFE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time the knife stays down after confirming the bottom position
+pub const KNIFE_DWELL: Duration = Duration::from_millis(100);
+
+/// A feed ending further than this from its target is reported as a fault
+/// instead of cutting a piece of the wrong length
+pub const LENGTH_TOLERANCE_MM: f32 = 0.5;
+
+/// Downtime reasons of the production statistics
+pub mod downtime {
+    pub const PAUSE: &str = "pause";
+    pub const BATCH_COMPLETE: &str = "batch_complete";
+    pub const FAULT: &str = "fault";
 }
 
+/// Mechanical constants for the linear axis: 200 pulses/rev (CL57T
+/// setting) on a 10 mm ball screw = 20 pulses/mm
+pub const MECHANICS: LinearAxisMechanics = LinearAxisMechanics {
+    pulses_per_rev: 200,
+    lead_mm: 10.0,
+};
+
+/// State file of the production counters, see [`crate::production_stats`]
+pub const PRODUCTION_FILENAME: &str = "schneidemaschine-v0-production.json";
+
 pub struct SchneidemaschineV0 {
     pub api_receiver: Receiver<MachineMessage>,
     pub api_sender: Sender<MachineMessage>,
@@ -63,18 +91,27 @@ pub struct SchneidemaschineV0 {
     pub digital_outputs: [DigitalOutput; 8],
     pub output_states: [bool; 8],
 
-    // Pulse Train Outputs (1x EL2522 = 2 channels)
-    pub axes: [PulseTrainOutput; 2],
-    pub axis_speeds: [i32; 2],
-    pub axis_target_speeds: [i32; 2],
-    pub axis_accelerations: [f32; 2],
-    pub axis_target_positions: [i32; 2],
-    pub axis_position_mode: [bool; 2],
-    pub axis_position_ignore_cycles: [u8; 2],
+    // Linear axes (1x EL2522 = 2 channels)
+    pub axes: [LinearPtoAxis; 2],
+
+    /// Feed ⟷ knife rules, see [`interlocks`]
+    pub interlocks: InterlockEngine,
 
-    // Hardware ramp control
-    pub sdo_write_u16: Option<crate::SdoWriteU16Fn>,
-    pub pto_subdevice_index: usize,
+    // Cut jobs
+    pub cut_jobs: CutJobQueue,
+    pub job_state: JobState,
+    pub cut_phase: CutPhase,
+    pub phase_since: Instant,
+    /// The cut in progress is the reference cut
+    pub reference_cut: bool,
+    /// The material edge is at the knife (feed position 0) after a
+    /// reference cut; revoked by faults and aborted cuts
+    pub referenced: bool,
+    /// Stops the jobs until reset
+    pub cut_fault: Option<String>,
+
+    // Production counters and axis usage (persisted to disk)
+    pub production: ProductionTracker,
 
     // Debug logging
     pub last_debug_log: Option<Instant>,
@@ -106,11 +143,24 @@ impl SchneidemaschineV0 {
     pub fn get_state(&self) -> StateEvent {
         StateEvent {
             output_states: self.output_states,
-            axis_speeds: self.axis_speeds,
-            axis_target_speeds: self.axis_target_speeds,
-            axis_accelerations: self.axis_accelerations,
-            axis_target_positions: self.axis_target_positions,
-            axis_position_mode: self.axis_position_mode,
+            axis_speeds: self.axes.each_ref().map(|a| a.speed_hz()),
+            axis_target_speeds: self.axes.each_ref().map(|a| a.target_speed_hz()),
+            axis_accelerations: self.axes.each_ref().map(|a| a.acceleration_mm_s2()),
+            axis_target_positions: self.axes.each_ref().map(|a| a.target_position_pulses()),
+            axis_position_mode: self.axes.each_ref().map(|a| a.is_position_mode()),
+            knife_up: self.input(inputs::MESSER_OBEN),
+            knife_down: self.input(inputs::MESSER_UNTEN),
+            active_interlocks: self
+                .active_interlocks()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            referenced: self.referenced,
+            job_state: self.job_state,
+            cut_phase: self.cut_phase,
+            cut_fault: self.cut_fault.clone(),
+            cut_jobs: self.cut_jobs.jobs().to_vec(),
+            production: self.production.stats().clone(),
         }
     }
 
@@ -122,22 +172,18 @@ impl SchneidemaschineV0 {
             input_states[i] = di.get_value().unwrap_or(false);
         }
 
-        // Read axis positions from PTO feedback
-        let mut positions = [0u32; 2];
-        for (i, axis) in self.axes.iter().enumerate() {
-            positions[i] = axis.get_position();
-        }
-
         LiveValuesEvent {
             input_states,
-            axis_positions: positions,
+            // Logical positions in pulses (signed)
+            axis_positions: self.axes.each_ref().map(|a| a.position_pulses()),
         }
     }
 
     /// Emit state event to UI
     pub fn emit_state(&mut self) {
         let event = self.get_state().build();
-        self.namespace.emit(SchneidemaschineV0Events::State(event));
+        self.namespace
+            .emit(SchneidemaschineV0Events::State(Box::new(event)));
     }
 
     /// Emit live values to UI
@@ -167,42 +213,25 @@ impl SchneidemaschineV0 {
 
     /// Set axis speed (frequency value for PTO)
     pub fn set_axis_speed(&mut self, index: usize, speed: i32) {
-        if index < self.axis_speeds.len() {
-            self.axis_speeds[index] = speed;
+        if index < self.axes.len() {
             self.axes[index].set_frequency(speed);
             self.emit_state();
         }
     }
 
-    /// Stop all axes - hardware immediate stop
+    /// Stop all axes - hardware immediate stop. Also stops the cut jobs.
     pub fn stop_all_axes(&mut self) {
-        for i in 0..self.axis_speeds.len() {
-            self.axis_speeds[i] = 0;
-            self.axis_target_speeds[i] = 0;
-            self.axis_position_mode[i] = false;
-
-            let mut output = self.axes[i].get_output();
-            output.disble_ramp = true;
-            output.go_counter = false;
-            output.frequency_value = 0;
-            self.axes[i].set_output(output);
+        self.stop_cut_jobs();
+        for axis in self.axes.iter_mut() {
+            axis.stop();
         }
         self.emit_state();
     }
 
     /// Stop single axis - hardware immediate stop
     pub fn stop_axis(&mut self, index: usize) {
-        if index < self.axis_speeds.len() {
-            self.axis_speeds[index] = 0;
-            self.axis_target_speeds[index] = 0;
-            self.axis_position_mode[index] = false;
-
-            let mut output = self.axes[index].get_output();
-            output.disble_ramp = true;
-            output.go_counter = false;
-            output.frequency_value = 0;
-            self.axes[index].set_output(output);
-
+        if index < self.axes.len() {
+            self.axes[index].stop();
             self.emit_state();
         }
     }
@@ -212,48 +241,17 @@ impl SchneidemaschineV0 {
     /// Set target axis speed in mm/s (hardware ramp handles transition)
     /// Positive = forward, Negative = backward
     pub fn set_axis_speed_mm_s(&mut self, index: usize, mm_per_s: f32) {
-        if index < self.axis_target_speeds.len() {
-            let hz = mechanics::mm_per_s_to_hz(mm_per_s);
-            self.axis_target_speeds[index] = hz;
+        if index < self.axes.len() {
             // Hardware ramp accelerates/brakes automatically to target
+            self.axes[index].set_speed_mm_s(mm_per_s);
             self.emit_state();
         }
     }
 
     /// Set axis acceleration in mm/s² - writes ramp time constants via SDO
     pub fn set_axis_acceleration(&mut self, index: usize, accel_mm_s2: f32) {
-        if index < self.axis_accelerations.len() {
-            let clamped = accel_mm_s2.clamp(4.0, 500.0);
-            self.axis_accelerations[index] = clamped;
-
-            // Calculate ramp time constants for hardware
-            let accel_hz_s = clamped * mechanics::PULSES_PER_MM;
-            let base_freq = 5000.0_f32;
-            let rising_ms = ((base_freq / accel_hz_s) * 1000.0) as u16;
-            let falling_ms = rising_ms; // Same as rising to avoid step loss from aggressive braking
-
-            // SDO-Write to EL2522
-            // NOTE: SdoWriteU16Fn returns () - errors are handled inside the callback.
-            if let Some(sdo_write) = &self.sdo_write_u16 {
-                let subdevice_index = self.pto_subdevice_index;
-                // PTO Base Index: Channel 1 = 0x8000, Channel 2 = 0x8010
-                let pto_base = if index == 0 { 0x8000u16 } else { 0x8010u16 };
-
-                tracing::debug!(
-                    "[SchneidemaschineV0] SDO write axis {}: ramp rising={}ms falling={}ms (accel={:.0} mm/s²)",
-                    index,
-                    rising_ms,
-                    falling_ms,
-                    clamped
-                );
-
-                sdo_write(subdevice_index, pto_base, 0x14, rising_ms);
-                sdo_write(subdevice_index, pto_base, 0x15, falling_ms);
-            } else {
-                tracing::warn!(
-                    "[SchneidemaschineV0] SDO write not available - acceleration change will not take effect"
-                );
-            }
+        if index < self.axes.len() {
+            self.axes[index].set_acceleration(accel_mm_s2);
             self.emit_state();
         }
     }
@@ -261,109 +259,439 @@ impl SchneidemaschineV0 {
     /// Move to a target position in mm using hardware Travel Distance Control
     pub fn move_to_position_mm(&mut self, index: usize, position_mm: f32, speed_mm_s: f32) {
         if index < self.axes.len() {
-            let target_pulses = (position_mm.round() * mechanics::PULSES_PER_MM) as i32;
-            let speed_hz = mechanics::mm_per_s_to_hz(speed_mm_s.abs());
-
-            let current_pulses = self.axes[index].get_position() as i32;
-            let direction = if target_pulses > current_pulses {
-                1
-            } else {
-                -1
-            };
-
-            self.axis_target_positions[index] = target_pulses;
-            self.axis_position_mode[index] = true;
-            self.axis_position_ignore_cycles[index] = 5;
-
-            // In Travel Distance Control mode, the EL2522 hardware automatically
-            // determines direction by comparing target_counter_value vs current position.
-            // frequency_value must be POSITIVE (magnitude only) - sign is NOT used for direction.
-            let mut output = self.axes[index].get_output();
-            output.go_counter = true;
-            output.disble_ramp = false;
-            output.frequency_value = speed_hz;
-            output.target_counter_value = target_pulses as u32;
-            self.axes[index].set_output(output);
-
-            // Keep sign for UI display
-            self.axis_target_speeds[index] = speed_hz * direction;
-            self.axis_speeds[index] = speed_hz * direction;
-
+            self.axes[index].move_to_mm(position_mm, speed_mm_s);
             self.emit_state();
-            tracing::info!(
-                "[SchneidemaschineV0] Axis {} moving to {:.0} mm ({} pulses) at {:.1} mm/s",
-                index,
-                position_mm.round(),
-                target_pulses,
-                speed_mm_s
-            );
         }
     }
 
-    /// Hardware ramp monitor: watches hardware status, does not set speeds
-    pub fn update_hardware_monitor(&mut self) -> bool {
+    /// Hardware ramp monitor, see [`LinearPtoAxis::update`]
+    pub fn update_hardware_monitor(&mut self, now: Instant) -> bool {
         let mut changed = false;
-        for i in 0..self.axis_speeds.len() {
-            let input = self.axes[i].get_input();
-
-            // Position mode: target detection
-            if self.axis_position_mode[i] {
-                if self.axis_position_ignore_cycles[i] > 0 {
-                    self.axis_position_ignore_cycles[i] -= 1;
-                } else if input.select_end_counter {
-                    self.axis_speeds[i] = 0;
-                    self.axis_target_speeds[i] = 0;
-                    self.axis_position_mode[i] = false;
-
-                    let mut output = self.axes[i].get_output();
-                    output.go_counter = false;
-                    output.frequency_value = 0;
-                    self.axes[i].set_output(output);
-
-                    changed = true;
-                    let actual_pos = self.axes[i].get_position() as i32;
-                    let target_pos = self.axis_target_positions[i];
-                    let deviation = (actual_pos - target_pos).abs();
-                    if deviation > 2 {
-                        tracing::warn!(
-                            "[Axis {}] STEP LOSS DETECTED: target={} actual={} deviation={} pulses ({:.2} mm)",
-                            i,
-                            target_pos,
-                            actual_pos,
-                            deviation,
-                            deviation as f32 / mechanics::PULSES_PER_MM
-                        );
-                    } else {
-                        tracing::info!(
-                            "[Axis {}] Target reached: {} pulses (actual: {}, deviation: {})",
-                            i,
-                            target_pos,
-                            actual_pos,
-                            deviation
-                        );
-                    }
-                }
+        for axis in self.axes.iter_mut() {
+            changed |= axis.update(now).changed;
+        }
+        changed
+    }
+
+    /// Feeds the axis states to the production statistics, see
+    /// [`ProductionTracker::track_axes`]
+    pub fn track_production(&mut self, now: Instant) {
+        let axes = self.axes.each_ref().map(AxisSnapshot::from);
+        self.production.track_axes(&axes);
+        self.production.persist_if_due(now);
+    }
+
+    /// Reset the production counters, times and downtimes
+    pub fn reset_production_stats(&mut self) {
+        self.production.reset();
+        tracing::info!("[SchneidemaschineV0] Production statistics reset");
+        self.emit_state();
+    }
+
+    /// Reset travel and move count of an axis after its maintenance
+    pub fn reset_axis_usage(&mut self, axis: usize) -> anyhow::Result<()> {
+        self.production.reset_axis(axis)?;
+        tracing::info!("[SchneidemaschineV0] Usage of axis {} reset", axis);
+        self.emit_state();
+        Ok(())
+    }
+
+    // ============ Interlocks ============
+
+    fn input(&self, index: usize) -> bool {
+        self.digital_inputs[index].get_value().unwrap_or(false)
+    }
+
+    fn interlock_inputs(&self) -> ([AxisSnapshot; 2], [bool; 8]) {
+        let axes = self.axes.each_ref().map(AxisSnapshot::from);
+        let inputs = std::array::from_fn(|i| self.input(i));
+        (axes, inputs)
+    }
+
+    /// Checks a command against the feed ⟷ knife rules
+    pub fn check_interlock(&self, command: Command) -> Result<(), Blocked> {
+        let (axes, inputs) = self.interlock_inputs();
+        let s = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        self.interlocks.check(command, &s)
+    }
+
+    /// Ids of the rules currently blocking commands
+    pub fn active_interlocks(&self) -> Vec<&'static str> {
+        let (axes, inputs) = self.interlock_inputs();
+        let s = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        self.interlocks.blocking_rules(&s).collect()
+    }
+
+    /// Stops the feed when it moves although a rule no longer permits it
+    /// (knife left its top position) or the knife valve is on while the
+    /// feed moves. A running job faults. Returns true if it intervened.
+    pub fn enforce_interlocks(&mut self) -> bool {
+        let (axes, inputs) = self.interlock_inputs();
+        let s = MachineSnapshot {
+            axes: &axes,
+            inputs: &inputs,
+            outputs: &self.output_states,
+        };
+        let Some(rule) = self.interlocks.violations(&s).first().map(|v| v.rule.id) else {
+            return false;
+        };
+        tracing::warn!("[SchneidemaschineV0] Interlock {}: Vorschub gestoppt", rule);
+        self.axes[axes::FEED].stop();
+        if self.cut_phase != CutPhase::Idle || self.job_state != JobState::Idle {
+            self.fault_cut_jobs(format!("Interlock {} ausgelöst", rule));
+        }
+        true
+    }
+
+    /// Manual commands are refused while a piece is being cut; a paused job
+    /// allows them, the next piece is fed relative to the last cut anyway
+    fn check_manual_allowed(&self) -> anyhow::Result<()> {
+        if self.cut_phase != CutPhase::Idle
+            || matches!(self.job_state, JobState::Running | JobState::Pausing)
+        {
+            bail!("Cut job running");
+        }
+        Ok(())
+    }
+
+    /// Rejects a manual move of axis `index`, see [`Self::check_interlock`]
+    pub fn check_move_allowed(&self, index: usize) -> anyhow::Result<()> {
+        if index >= self.axes.len() {
+            bail!("Unknown axis {}", index);
+        }
+        self.check_manual_allowed()?;
+        self.check_interlock(Command::Move(index))
+            .map_err(|e| anyhow!("Bewegung {} blockiert: {}", self.axes[index].name(), e))
+    }
+
+    /// Rejects manually switching on output `index`
+    pub fn check_output_allowed(&self, index: usize, on: bool) -> anyhow::Result<()> {
+        if index >= self.output_states.len() {
+            bail!("Unknown output {}", index);
+        }
+        self.check_manual_allowed()?;
+        if on {
+            self.check_interlock(Command::SwitchOn(index))
+                .map_err(|e| anyhow!("Ausgang {} blockiert: {}", index, e))?;
+        }
+        Ok(())
+    }
+
+    // ============ Cut Jobs ============
+
+    /// Queue a cut job, see [`CutJobQueue::push`]
+    pub fn queue_cut_job(&mut self, spec: CutJobSpec) -> anyhow::Result<()> {
+        let id = self.cut_jobs.push(spec)?;
+        tracing::info!("[SchneidemaschineV0] Cut job {} queued", id);
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Remove a queued job, the running one only while paused
+    pub fn remove_cut_job(&mut self, id: u32) -> anyhow::Result<()> {
+        self.cut_jobs.remove(id, self.job_state != JobState::Idle)?;
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Remove all jobs but a running or paused one
+    pub fn clear_cut_jobs(&mut self) {
+        self.cut_jobs.clear(self.job_state != JobState::Idle);
+        self.emit_state();
+    }
+
+    /// Checks shared by the reference cut and the job start
+    fn check_cut_allowed(&self) -> anyhow::Result<()> {
+        if let Some(fault) = &self.cut_fault {
+            bail!("fault active ({}), reset first", fault);
+        }
+        if self.cut_phase != CutPhase::Idle {
+            bail!("cut in progress");
+        }
+        if self.axes[axes::FEED].is_moving() {
+            bail!("feed moving");
+        }
+        if !self.input(inputs::MESSER_OBEN) {
+            bail!("knife not confirmed up");
+        }
+        Ok(())
+    }
+
+    /// Homing of the feed: cut once so the material edge is at the knife and
+    /// restart the feed position at zero
+    pub fn start_reference_cut(&mut self) -> anyhow::Result<()> {
+        if self.job_state != JobState::Idle {
+            bail!("Cannot reference: jobs not idle");
+        }
+        self.check_cut_allowed()
+            .map_err(|e| anyhow!("Cannot reference: {}", e))?;
+        tracing::info!("[SchneidemaschineV0] Reference cut");
+        self.reference_cut = true;
+        self.referenced = false;
+        self.start_knife(Instant::now());
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Start or continue the queued jobs
+    pub fn start_cut_jobs(&mut self) -> anyhow::Result<()> {
+        match self.job_state {
+            JobState::Running => return Ok(()),
+            JobState::Pausing => {
+                self.job_state = JobState::Running;
+                self.emit_state();
+                return Ok(());
             }
+            JobState::Idle | JobState::Paused => {}
+        }
+        self.check_cut_allowed()
+            .map_err(|e| anyhow!("Cannot start: {}", e))?;
+        if !self.referenced {
+            bail!("Cannot start: reference cut required");
+        }
+        if self.cut_jobs.current().is_none() {
+            bail!("Cannot start: no jobs queued");
+        }
+        let now = Instant::now();
+        if self.job_state == JobState::Idle {
+            self.production.run_started(now);
+        } else {
+            self.production.run_resumed(now);
+        }
+        self.job_state = JobState::Running;
+        tracing::info!("[SchneidemaschineV0] Cut jobs started");
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Pause once the piece in progress is cut
+    pub fn pause_cut_jobs(&mut self) {
+        if self.job_state == JobState::Running {
+            self.job_state = JobState::Pausing;
+            tracing::info!("[SchneidemaschineV0] Cut jobs pause requested");
+            self.emit_state();
+        }
+    }
+
+    /// Stop immediately: feed stopped, knife valve off. A piece that was
+    /// being cut leaves the material in an unknown state, a new reference
+    /// cut is required then. The current job keeps its count.
+    pub fn stop_cut_jobs(&mut self) {
+        if self.job_state == JobState::Idle && self.cut_phase == CutPhase::Idle {
+            return;
+        }
+        self.halt_cutting();
+        if self.job_state != JobState::Idle {
+            self.production.run_aborted(Instant::now());
+        }
+        self.job_state = JobState::Idle;
+        tracing::info!("[SchneidemaschineV0] Cut jobs stopped");
+        self.emit_state();
+    }
 
-            // JOG mode: send speed directly to hardware
-            if !self.axis_position_mode[i] {
-                let target = self.axis_target_speeds[i];
-                if self.axis_speeds[i] != target {
-                    let mut output = self.axes[i].get_output();
-                    output.disble_ramp = false;
-                    output.go_counter = false;
-                    output.frequency_value = target;
-                    self.axes[i].set_output(output);
-                    self.axis_speeds[i] = target;
-                    changed = true;
+    /// Clear a fault once the knife is back up
+    pub fn reset_cut_fault(&mut self) -> anyhow::Result<()> {
+        if self.cut_fault.is_none() {
+            return Ok(());
+        }
+        if !self.input(inputs::MESSER_OBEN) {
+            bail!("Cannot reset: knife not confirmed up");
+        }
+        self.cut_fault = None;
+        tracing::info!("[SchneidemaschineV0] Cut fault reset");
+        self.emit_state();
+        Ok(())
+    }
+
+    /// Feed stopped, knife valve off, the cut in progress dropped
+    fn halt_cutting(&mut self) {
+        self.axes[axes::FEED].stop();
+        self.set_knife(false);
+        if self.cut_phase != CutPhase::Idle {
+            self.referenced = false;
+        }
+        self.cut_phase = CutPhase::Idle;
+        self.reference_cut = false;
+    }
+
+    /// Stops cutting and pauses the jobs until the fault is reset
+    fn fault_cut_jobs(&mut self, reason: String) {
+        tracing::error!("[SchneidemaschineV0] Cut fault: {}", reason);
+        self.halt_cutting();
+        if self.job_state != JobState::Idle {
+            self.job_state = JobState::Paused;
+            self.production
+                .downtime_started(downtime::FAULT, Instant::now());
+        }
+        self.cut_fault = Some(reason);
+        self.emit_state();
+    }
+
+    fn set_knife(&mut self, down: bool) {
+        self.output_states[outputs::MESSER] = down;
+        self.digital_outputs[outputs::MESSER].set(down);
+    }
+
+    fn set_phase(&mut self, phase: CutPhase, now: Instant) {
+        self.cut_phase = phase;
+        self.phase_since = now;
+    }
+
+    fn start_knife(&mut self, now: Instant) {
+        if let Err(blocked) = self.check_interlock(Command::SwitchOn(outputs::MESSER)) {
+            self.fault_cut_jobs(blocked.to_string());
+            return;
+        }
+        self.set_knife(true);
+        self.set_phase(CutPhase::KnifeDown, now);
+    }
+
+    /// Advances the cut jobs (called from the act() loop): feed the piece,
+    /// knife down, dwell, knife up, count. Returns true if the state changed
+    pub fn update_cut_jobs(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.phase_since);
+        match self.cut_phase {
+            CutPhase::Idle => self.start_next_piece(now),
+            CutPhase::Feeding => {
+                if self.axes[axes::FEED].is_moving() {
+                    return false;
+                }
+                let Some(job) = self.cut_jobs.current() else {
+                    return false;
+                };
+                let deviation = self.axes[axes::FEED].position_mm() - job.length_mm;
+                if deviation.abs() > LENGTH_TOLERANCE_MM {
+                    self.fault_cut_jobs(format!(
+                        "Vorschub {:.2} mm neben dem Ziel (Schrittverlust?)",
+                        deviation
+                    ));
+                    return true;
+                }
+                self.start_knife(now);
+                true
+            }
+            CutPhase::KnifeDown => {
+                if self.input(inputs::MESSER_UNTEN) {
+                    self.set_phase(CutPhase::KnifeDwell, now);
+                    return true;
+                }
+                if elapsed > KNIFE_TIMEOUT {
+                    self.fault_cut_jobs("Messer unten nicht bestätigt".to_string());
+                    return true;
+                }
+                false
+            }
+            CutPhase::KnifeDwell => {
+                if elapsed < KNIFE_DWELL {
+                    return false;
                 }
+                self.set_knife(false);
+                self.set_phase(CutPhase::KnifeUp, now);
+                true
             }
+            CutPhase::KnifeUp => {
+                if self.input(inputs::MESSER_OBEN) && !self.input(inputs::MESSER_UNTEN) {
+                    self.finish_cut(now);
+                    return true;
+                }
+                if elapsed > KNIFE_TIMEOUT {
+                    self.fault_cut_jobs("Messer oben nicht bestätigt".to_string());
+                    return true;
+                }
+                false
+            }
+        }
+    }
 
-            if input.ramp_active {
-                changed = true;
+    /// Feeds the next piece of the current job, or ends or pauses the jobs
+    fn start_next_piece(&mut self, now: Instant) -> bool {
+        match self.job_state {
+            JobState::Idle | JobState::Paused => return false,
+            JobState::Pausing => {
+                self.job_state = JobState::Paused;
+                self.production.downtime_started(downtime::PAUSE, now);
+                tracing::info!("[SchneidemaschineV0] Cut jobs paused");
+                return true;
             }
+            JobState::Running => {}
+        }
+        let Some(job) = self.cut_jobs.current() else {
+            self.job_state = JobState::Idle;
+            self.production.run_completed(now);
+            tracing::info!("[SchneidemaschineV0] All cut jobs done");
+            return true;
+        };
+        let (length_mm, speed_mm_s) = (job.length_mm, job.speed_mm_s);
+        if job.cut == 0 {
+            self.production.unit_started(Unit::Set, now);
+        }
+        if job.batch_size.is_some() && (job.cut == 0 || job.batch_complete()) {
+            self.production.unit_started(Unit::Block, now);
+        }
+        if let Err(blocked) = self.check_interlock(Command::Move(axes::FEED)) {
+            self.fault_cut_jobs(blocked.to_string());
+            return true;
+        }
+        self.production.unit_started(Unit::Cycle, now);
+        self.axes[axes::FEED].move_to_mm(length_mm, speed_mm_s);
+        self.set_phase(CutPhase::Feeding, now);
+        true
+    }
+
+    /// The knife is back up: restart the feed position at the new edge and
+    /// count the piece
+    fn finish_cut(&mut self, now: Instant) {
+        self.set_phase(CutPhase::Idle, now);
+        if let Err(e) = self.axes[axes::FEED].set_position_mm(0.0) {
+            self.fault_cut_jobs(e.to_string());
+            return;
+        }
+        if self.reference_cut {
+            self.reference_cut = false;
+            self.referenced = true;
+            tracing::info!("[SchneidemaschineV0] Reference cut done");
+            return;
+        }
+
+        let Some(job) = self.cut_jobs.current_mut() else {
+            return;
+        };
+        job.cut += 1;
+        let (id, cut, quantity) = (job.id, job.cut, job.quantity);
+        let batch_complete = job.batch_complete();
+        let done = job.is_done();
+        self.production.unit_completed(Unit::Cycle, now);
+        if batch_complete || (done && job.batch_size.is_some()) {
+            self.production.unit_completed(Unit::Block, now);
+        }
+        if done {
+            self.production.unit_completed(Unit::Set, now);
+            self.cut_jobs.pop_done();
+            tracing::info!(
+                "[SchneidemaschineV0] Cut job {} done: {} pieces",
+                id,
+                quantity
+            );
+        } else if batch_complete && self.job_state == JobState::Running {
+            // The operator takes the batch out and starts again
+            self.job_state = JobState::Paused;
+            self.production
+                .downtime_started(downtime::BATCH_COMPLETE, now);
+            tracing::info!(
+                "[SchneidemaschineV0] Cut job {}: batch complete at {}/{} pieces",
+                id,
+                cut,
+                quantity
+            );
         }
-        changed
     }
 
     // ============ Debug Functions ============
@@ -375,22 +703,23 @@ impl SchneidemaschineV0 {
         }
 
         let axis = &self.axes[index];
-        let input = axis.get_input();
-        let output = axis.get_output();
+        let input = axis.pto().get_input();
+        let output = axis.pto().get_output();
+        let to_mm = |hw_pulses| MECHANICS.pulses_to_mm(axis.hw_to_logical_pulses(hw_pulses));
 
         DebugPtoEvent {
             channel: index as u8,
             // Output (what we're sending)
             frequency_setpoint_hz: output.frequency_value,
-            frequency_setpoint_mm_s: mechanics::hz_to_mm_per_s(output.frequency_value),
+            frequency_setpoint_mm_s: MECHANICS.hz_to_mm_per_s(output.frequency_value),
             target_position_pulses: output.target_counter_value,
-            target_position_mm: mechanics::pulses_to_mm(output.target_counter_value),
+            target_position_mm: to_mm(output.target_counter_value),
             disable_ramp: output.disble_ramp,
             set_counter_request: output.set_counter,
             set_counter_value: output.set_counter_value,
             // Input (feedback from device)
             actual_position_pulses: input.counter_value,
-            actual_position_mm: mechanics::pulses_to_mm(input.counter_value),
+            actual_position_mm: to_mm(input.counter_value),
             ramp_active: input.ramp_active,
             error: input.error,
             sync_error: input.sync_error,
@@ -447,3 +776,262 @@ impl SchneidemaschineV0 {
         tracing::info!("===============================================");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethercat_hal::io::digital_input::{DigitalInputDevice, DigitalInputInput};
+    use ethercat_hal::io::digital_output::{DigitalOutputDevice, DigitalOutputOutput};
+    use smol::lock::RwLock;
+
+    use super::*;
+    use crate::linear_pto_axis::LinearPtoAxisConfig;
+    use crate::linear_pto_axis::tests::{FakePto, fake_axis};
+
+    /// The EL1008 and EL2008 of the machine
+    struct FakeIo {
+        inputs: [bool; 8],
+        outputs: [bool; 8],
+    }
+
+    impl DigitalInputDevice<usize> for FakeIo {
+        fn get_input(&self, port: usize) -> Result<DigitalInputInput, anyhow::Error> {
+            Ok(DigitalInputInput {
+                value: self.inputs[port],
+            })
+        }
+    }
+
+    impl DigitalOutputDevice<usize> for FakeIo {
+        fn set_output(&mut self, port: usize, value: DigitalOutputOutput) {
+            self.outputs[port] = value.into();
+        }
+        fn get_output(&self, port: usize) -> DigitalOutputOutput {
+            self.outputs[port].into()
+        }
+    }
+
+    struct Fixture {
+        machine: SchneidemaschineV0,
+        io: Arc<RwLock<FakeIo>>,
+        feed: Arc<RwLock<FakePto>>,
+    }
+
+    /// A machine with the knife up and nothing queued
+    fn fixture() -> Fixture {
+        let mut inputs = [false; 8];
+        inputs[inputs::MESSER_OBEN] = true;
+        let io = Arc::new(RwLock::new(FakeIo {
+            inputs,
+            outputs: [false; 8],
+        }));
+        let (unused, _) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let (feed_axis, feed) = fake_axis(LinearPtoAxisConfig::new(MECHANICS));
+        let (sender, receiver) = smol::channel::unbounded();
+        let machine = SchneidemaschineV0 {
+            api_receiver: receiver,
+            api_sender: sender,
+            machine_identification_unique: MachineIdentificationUnique {
+                machine_identification: MachineIdentification {
+                    vendor: VENDOR_QITECH,
+                    machine: SCHNEIDEMASCHINE_V0,
+                },
+                serial: 1,
+            },
+            namespace: SchneidemaschineV0Namespace { namespace: None },
+            last_state_emit: Instant::now(),
+            main_sender: None,
+            digital_inputs: std::array::from_fn(|i| DigitalInput::new(io.clone(), i)),
+            digital_outputs: std::array::from_fn(|i| DigitalOutput::new(io.clone(), i)),
+            output_states: [false; 8],
+            axes: [unused, feed_axis],
+            interlocks: InterlockEngine::new(interlocks::rules()),
+            cut_jobs: CutJobQueue::default(),
+            job_state: JobState::Idle,
+            cut_phase: CutPhase::Idle,
+            phase_since: Instant::now(),
+            reference_cut: false,
+            referenced: false,
+            cut_fault: None,
+            production: ProductionTracker::new(2),
+            last_debug_log: None,
+        };
+        Fixture { machine, io, feed }
+    }
+
+    impl Fixture {
+        /// Sets the knife position switches
+        fn set_knife_position(&self, up: bool, down: bool) {
+            let mut io = smol::block_on(self.io.write());
+            io.inputs[inputs::MESSER_OBEN] = up;
+            io.inputs[inputs::MESSER_UNTEN] = down;
+        }
+
+        fn knife_valve(&self) -> bool {
+            smol::block_on(self.io.read()).outputs[outputs::MESSER]
+        }
+
+        /// Lets the feed arrive at its target
+        fn finish_feed(&mut self) {
+            {
+                let mut device = smol::block_on(self.feed.write());
+                device.input.counter_value = device.output.target_counter_value;
+                device.input.select_end_counter = true;
+            }
+            for _ in 0..10 {
+                self.machine.update_hardware_monitor(Instant::now());
+            }
+            smol::block_on(self.feed.write()).input.select_end_counter = false;
+        }
+
+        /// Runs the knife of the cut in progress down and back up
+        fn cut(&mut self) {
+            assert_eq!(self.machine.cut_phase, CutPhase::KnifeDown);
+            let now = Instant::now();
+            self.set_knife_position(false, true);
+            self.machine.update_cut_jobs(now);
+            self.machine.update_cut_jobs(now + KNIFE_DWELL);
+            assert!(!self.knife_valve());
+            self.set_knife_position(true, false);
+            self.machine.update_cut_jobs(now + KNIFE_DWELL);
+            assert_eq!(self.machine.cut_phase, CutPhase::Idle);
+        }
+
+        /// Reference cut, then `spec` queued and started
+        fn start_job(&mut self, spec: CutJobSpec) {
+            self.machine.start_reference_cut().unwrap();
+            self.cut();
+            assert!(self.machine.referenced);
+            self.machine.queue_cut_job(spec).unwrap();
+            self.machine.start_cut_jobs().unwrap();
+        }
+    }
+
+    fn spec(quantity: u32) -> CutJobSpec {
+        CutJobSpec {
+            length_mm: 100.0,
+            quantity,
+            batch_size: None,
+            speed_mm_s: None,
+        }
+    }
+
+    #[test]
+    fn test_cuts_job() {
+        let mut f = fixture();
+        f.start_job(spec(2));
+        for _ in 0..2 {
+            f.machine.update_cut_jobs(Instant::now());
+            assert_eq!(f.machine.cut_phase, CutPhase::Feeding);
+            f.finish_feed();
+            f.machine.update_cut_jobs(Instant::now());
+            assert!(f.knife_valve());
+            f.cut();
+        }
+        assert!(f.machine.cut_jobs.current().is_none());
+        f.machine.update_cut_jobs(Instant::now());
+        assert_eq!(f.machine.job_state, JobState::Idle);
+    }
+
+    #[test]
+    fn test_knife_timeout_faults() {
+        let mut f = fixture();
+        f.machine.start_reference_cut().unwrap();
+        assert!(f.knife_valve());
+
+        // Knife never confirms the bottom position
+        let now = Instant::now();
+        assert!(!f.machine.update_cut_jobs(now));
+        assert!(f.machine.update_cut_jobs(now + KNIFE_TIMEOUT * 2));
+        assert!(f.machine.cut_fault.is_some());
+        assert!(!f.knife_valve());
+        assert_eq!(f.machine.cut_phase, CutPhase::Idle);
+        assert!(!f.machine.referenced);
+        assert!(f.machine.start_reference_cut().is_err());
+
+        f.machine.reset_cut_fault().unwrap();
+        f.machine.start_reference_cut().unwrap();
+    }
+
+    #[test]
+    fn test_knife_up_timeout_pauses_job() {
+        let mut f = fixture();
+        f.start_job(spec(2));
+        f.machine.update_cut_jobs(Instant::now());
+        f.finish_feed();
+        f.machine.update_cut_jobs(Instant::now());
+
+        // The knife comes down but stays stuck there
+        let now = Instant::now();
+        f.set_knife_position(false, true);
+        f.machine.update_cut_jobs(now);
+        f.machine.update_cut_jobs(now + KNIFE_DWELL);
+        assert_eq!(f.machine.cut_phase, CutPhase::KnifeUp);
+        f.machine
+            .update_cut_jobs(now + KNIFE_DWELL + KNIFE_TIMEOUT * 2);
+        assert!(f.machine.cut_fault.is_some());
+        assert_eq!(f.machine.job_state, JobState::Paused);
+        assert_eq!(f.machine.cut_jobs.current().unwrap().cut, 0);
+
+        // Reset only once the knife is up again
+        assert!(f.machine.reset_cut_fault().is_err());
+        f.set_knife_position(true, false);
+        f.machine.reset_cut_fault().unwrap();
+        assert!(f.machine.start_cut_jobs().is_err());
+    }
+
+    #[test]
+    fn test_knife_leaving_top_aborts_feed() {
+        let mut f = fixture();
+        f.start_job(spec(2));
+        f.machine.update_cut_jobs(Instant::now());
+        assert!(f.machine.axes[axes::FEED].is_moving());
+
+        f.set_knife_position(false, false);
+        assert!(f.machine.enforce_interlocks());
+        assert!(!f.machine.axes[axes::FEED].is_moving());
+        assert_eq!(f.machine.job_state, JobState::Paused);
+        assert!(!f.machine.referenced);
+        assert!(f.machine.cut_fault.is_some());
+    }
+
+    #[test]
+    fn test_stop_drops_cut_in_progress() {
+        let mut f = fixture();
+        f.start_job(spec(2));
+        f.machine.update_cut_jobs(Instant::now());
+        f.finish_feed();
+        f.machine.update_cut_jobs(Instant::now());
+        assert_eq!(f.machine.cut_phase, CutPhase::KnifeDown);
+
+        f.machine.stop_cut_jobs();
+        assert_eq!(f.machine.job_state, JobState::Idle);
+        assert_eq!(f.machine.cut_phase, CutPhase::Idle);
+        assert!(!f.knife_valve());
+        assert!(!f.machine.referenced);
+        assert!(f.machine.cut_fault.is_none());
+        // The job stays queued with its count
+        assert_eq!(f.machine.cut_jobs.current().unwrap().cut, 0);
+        assert!(f.machine.start_cut_jobs().is_err());
+    }
+
+    #[test]
+    fn test_rejects_commands_while_running() {
+        let mut f = fixture();
+        f.start_job(spec(2));
+        assert!(f.machine.start_reference_cut().is_err());
+        assert!(f.machine.check_move_allowed(axes::FEED).is_err());
+        assert!(
+            f.machine
+                .check_output_allowed(outputs::MESSER, true)
+                .is_err()
+        );
+        let id = f.machine.cut_jobs.current().unwrap().id;
+        assert!(f.machine.remove_cut_job(id).is_err());
+
+        // Starting again keeps the job running
+        f.machine.start_cut_jobs().unwrap();
+        assert_eq!(f.machine.job_state, JobState::Running);
+    }
+}
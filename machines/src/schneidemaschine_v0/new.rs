@@ -1,6 +1,11 @@
+use crate::interlock::InterlockEngine;
+use crate::linear_pto_axis::{LinearPtoAxis, LinearPtoAxisConfig, RampSdo};
+use crate::production_stats::ProductionTracker;
 use crate::schneidemaschine_v0::SchneidemaschineV0;
 use crate::schneidemaschine_v0::api::SchneidemaschineV0Namespace;
-use crate::schneidemaschine_v0::roles;
+use crate::schneidemaschine_v0::cut_job::{CutJobQueue, CutPhase, JobState};
+use crate::schneidemaschine_v0::interlocks;
+use crate::schneidemaschine_v0::{MECHANICS, PRODUCTION_FILENAME, roles};
 use smol::block_on;
 use std::time::Instant;
 
@@ -128,11 +133,25 @@ impl MachineNewTrait for SchneidemaschineV0 {
                 "[SchneidemaschineV0] EL2522 configured: Channel 2 = PulseDirection mode, base_freq=5000Hz, hardware ramp enabled"
             );
 
-            // Create PulseTrainOutput array for 2 axes
-            let axes = [
-                PulseTrainOutput::new(el2522.clone(), EL2522Port::PTO1),
-                PulseTrainOutput::new(el2522.clone(), EL2522Port::PTO2),
-            ];
+            // Linear axes without reference switch: the position at startup is
+            // zero. Falling ramp same as rising to avoid step loss from
+            // aggressive braking.
+            let names = ["Achse 1", "Achse 2"];
+            let ports = [EL2522Port::PTO1, EL2522Port::PTO2];
+            let axes = std::array::from_fn(|i| {
+                LinearPtoAxis::new(
+                    names[i],
+                    PulseTrainOutput::new(el2522.clone(), ports[i]),
+                    LinearPtoAxisConfig {
+                        ramp_sdo: params.sdo_write_u16.clone().map(|write| RampSdo {
+                            write,
+                            subdevice_index: pto_subdevice_index,
+                            channel: i as u8,
+                        }),
+                        ..LinearPtoAxisConfig::new(MECHANICS)
+                    },
+                )
+            });
 
             let (sender, receiver) = smol::channel::unbounded();
             let mut machine = Self {
@@ -148,14 +167,15 @@ impl MachineNewTrait for SchneidemaschineV0 {
                 digital_outputs,
                 output_states: [false; 8],
                 axes,
-                axis_speeds: [0; 2],
-                axis_target_speeds: [0; 2],
-                axis_accelerations: [100.0; 2], // Default: 100 mm/s²
-                axis_target_positions: [0; 2],
-                axis_position_mode: [false; 2],
-                axis_position_ignore_cycles: [0; 2],
-                sdo_write_u16: params.sdo_write_u16.clone(),
-                pto_subdevice_index,
+                production: ProductionTracker::load(PRODUCTION_FILENAME, 2),
+                interlocks: InterlockEngine::new(interlocks::rules()),
+                cut_jobs: CutJobQueue::default(),
+                job_state: JobState::Idle,
+                cut_phase: CutPhase::Idle,
+                phase_since: Instant::now(),
+                reference_cut: false,
+                referenced: false,
+                cut_fault: None,
                 last_debug_log: None,
             };
 
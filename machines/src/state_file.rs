@@ -0,0 +1,91 @@
+//! Persisted machine state (calibration, programs, counters). State files
+//! live in the directory the server passes to [`set_directory`] (its
+//! `state.directory`). Without one release builds on Linux use
+//! `/var/lib/qitech`, debug builds (dev machines, tests) fall back to the OS
+//! temp dir so they don't litter `/var/lib/qitech`.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use smol::channel::Sender;
+
+use crate::AsyncThreadMessage;
+
+static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the directory of all state files, call it before creating machines.
+/// Only the first call has an effect.
+pub fn set_directory(directory: PathBuf) {
+    if DIRECTORY.set(directory).is_err() {
+        tracing::warn!("[StateFile] State directory already set");
+    }
+}
+
+/// Path of a state file
+pub fn path(filename: &str) -> PathBuf {
+    if let Some(dir) = DIRECTORY.get() {
+        return dir.join(filename);
+    }
+    if cfg!(all(target_os = "linux", not(debug_assertions))) {
+        PathBuf::from("/var/lib/qitech").join(filename)
+    } else {
+        std::env::temp_dir().join(filename)
+    }
+}
+
+/// Write `json` to `p` via a temp file and rename, so a crash never leaves a
+/// half-written file behind. The temp file and the directory are synced so
+/// the new content survives a power loss. Errors are logged.
+pub fn write_atomic(p: &Path, json: &str) {
+    if let Some(parent) = p.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        tracing::error!(
+            "[StateFile] Failed to create state dir {}: {}",
+            parent.display(),
+            e
+        );
+        return;
+    }
+
+    let tmp = p.with_extension("json.tmp");
+    if let Err(e) = write_synced(&tmp, json) {
+        tracing::error!("[StateFile] Failed to write {}: {}", tmp.display(), e);
+        return;
+    }
+    if let Err(e) = std::fs::rename(&tmp, p) {
+        tracing::error!("[StateFile] Failed to commit {}: {}", p.display(), e);
+        return;
+    }
+    // The rename is only durable once the directory entry is synced
+    if let Some(parent) = p.parent()
+        && let Err(e) = File::open(parent).and_then(|dir| dir.sync_all())
+    {
+        tracing::error!("[StateFile] Failed to sync {}: {}", parent.display(), e);
+    }
+}
+
+fn write_synced(p: &Path, json: &str) -> std::io::Result<()> {
+    let mut file = File::create(p)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()
+}
+
+/// Runs `job` on the main thread so file IO stays out of the machine loop.
+/// Without a main thread (tests, failed send) it runs right away.
+pub fn run_off_loop(
+    main_sender: Option<&Sender<AsyncThreadMessage>>,
+    job: impl FnOnce() + Send + Sync + 'static,
+) {
+    let Some(sender) = main_sender else {
+        return job();
+    };
+    if let Err(e) = sender.try_send(AsyncThreadMessage::Blocking(Box::new(job))) {
+        tracing::warn!("[StateFile] Main thread unavailable, writing in place");
+        if let AsyncThreadMessage::Blocking(job) = e.into_inner() {
+            job();
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Stall and following-error supervision for EL70x1 steppers
+//!
+//! In velocity mode the machines only command a speed and never look at what
+//! the terminal did with it. A [`StepperSupervisor`] integrates the commanded
+//! speed to the position the step counter should show and compares it with
+//! the counter every cycle. A difference beyond the limit means the terminal
+//! stopped generating steps (driver stage switched off, short, undervoltage)
+//! and raises [`StepperFault::FollowingError`]. The error bit of the driver
+//! stage raises [`StepperFault::DriverError`] right away. Faults are latched
+//! until [`StepperSupervisor::reset`].
+//!
+//! Without an encoder the counter counts generated steps, a motor stalled by
+//! load is only caught if the terminal reports it.
+
+use std::time::Instant;
+
+use control_core_derive::JsonSchema;
+use ethercat_hal::io::stepper_velocity_el70x1::StepperVelocityEL70x1;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, PartialEq, JsonSchema)]
+pub enum StepperFault {
+    /// The driver stage reports an error
+    DriverError,
+    /// Counter this many full steps behind (positive) or ahead of the
+    /// commanded speed
+    FollowingError { steps: f64 },
+}
+
+impl std::fmt::Display for StepperFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DriverError => write!(f, "driver error"),
+            Self::FollowingError { steps } => write!(f, "following error of {:.0} steps", steps),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StepperSupervisor {
+    /// Counter increments per full step
+    microsteps: u8,
+    /// Largest following error (full steps)
+    max_following_error: f64,
+    /// Where the counter should be (microsteps)
+    expected_position: f64,
+    /// Speed commanded since the last update (full steps/s)
+    last_speed: f64,
+    last_update: Option<Instant>,
+    fault: Option<StepperFault>,
+    warning: bool,
+    /// The reset bit is set and goes back to `false` next cycle
+    resetting: bool,
+}
+
+impl StepperSupervisor {
+    pub const fn new(microsteps: u8, max_following_error: f64) -> Self {
+        Self {
+            microsteps,
+            max_following_error,
+            expected_position: 0.0,
+            last_speed: 0.0,
+            last_update: None,
+            fault: None,
+            warning: false,
+            resetting: false,
+        }
+    }
+
+    pub const fn fault(&self) -> Option<&StepperFault> {
+        self.fault.as_ref()
+    }
+
+    /// Warning bit of the driver stage, e.g. overtemperature
+    pub const fn is_warning(&self) -> bool {
+        self.warning
+    }
+
+    /// Clears the latched fault and acknowledges the error of the driver
+    /// stage
+    pub fn reset(&mut self, stepper: &mut StepperVelocityEL70x1) {
+        self.fault = None;
+        self.last_update = None;
+        stepper.set_reset(true);
+        self.resetting = true;
+    }
+
+    /// Checks the stepper, called once per cycle after its speed was set.
+    /// Returns a newly raised fault.
+    pub fn update(
+        &mut self,
+        stepper: &mut StepperVelocityEL70x1,
+        now: Instant,
+    ) -> Option<StepperFault> {
+        if self.resetting {
+            stepper.set_reset(false);
+            self.resetting = false;
+        }
+        self.warning = stepper.is_warning();
+
+        let dt = self
+            .last_update
+            .replace(now)
+            .map(|last| now.duration_since(last).as_secs_f64());
+        let position = stepper.get_position() as f64;
+        let speed = stepper.get_speed() as f64;
+        let last_speed = std::mem::replace(&mut self.last_speed, speed);
+
+        // Standing still, disabled or already faulted: nothing to follow.
+        // The counter may also have been set, e.g. by homing.
+        let Some(dt) = dt.filter(|_| stepper.is_enabled() && self.fault.is_none()) else {
+            self.expected_position = position;
+            return None;
+        };
+        if stepper.is_error() {
+            return self.raise(StepperFault::DriverError);
+        }
+        if last_speed == 0.0 {
+            self.expected_position = position;
+            return None;
+        }
+
+        self.expected_position += last_speed * self.microsteps as f64 * dt;
+        let error = (self.expected_position - position) / self.microsteps as f64;
+        if error.abs() > self.max_following_error {
+            return self.raise(StepperFault::FollowingError { steps: error });
+        }
+        None
+    }
+
+    fn raise(&mut self, fault: StepperFault) -> Option<StepperFault> {
+        self.fault = Some(fault.clone());
+        Some(fault)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ethercat_hal::io::stepper_velocity_el70x1::{
+        StepperVelocityEL70x1Device, StepperVelocityEL70x1Input, StepperVelocityEL70x1Output,
+    };
+    use ethercat_hal::shared_config::el70x1::EL70x1SpeedRange;
+    use smol::lock::RwLock;
+
+    use super::*;
+
+    struct FakeStepper {
+        input: StepperVelocityEL70x1Input,
+        output: StepperVelocityEL70x1Output,
+    }
+
+    impl StepperVelocityEL70x1Device<()> for FakeStepper {
+        fn set_output(
+            &mut self,
+            _port: (),
+            value: StepperVelocityEL70x1Output,
+        ) -> Result<(), anyhow::Error> {
+            self.output = value;
+            Ok(())
+        }
+        fn get_input(&self, _port: ()) -> Result<StepperVelocityEL70x1Input, anyhow::Error> {
+            Ok(self.input.clone())
+        }
+        fn get_output(&self, _port: ()) -> Result<StepperVelocityEL70x1Output, anyhow::Error> {
+            Ok(self.output.clone())
+        }
+        fn get_speed_range(&self, _port: ()) -> EL70x1SpeedRange {
+            EL70x1SpeedRange::Steps1000
+        }
+    }
+
+    fn fake_stepper() -> (StepperVelocityEL70x1, Arc<RwLock<FakeStepper>>) {
+        let device = Arc::new(RwLock::new(FakeStepper {
+            input: StepperVelocityEL70x1Input {
+                counter_value: 0,
+                ready_to_enable: true,
+                ready: true,
+                warning: false,
+                error: false,
+                moving_positive: false,
+                moving_negative: false,
+                torque_reduced: false,
+            },
+            output: StepperVelocityEL70x1Output {
+                velocity: 0,
+                enable: true,
+                reduce_torque: false,
+                reset: false,
+                set_counter: None,
+            },
+        }));
+        (StepperVelocityEL70x1::new(device.clone(), ()), device)
+    }
+
+    #[test]
+    fn test_following_error() {
+        let (mut stepper, device) = fake_stepper();
+        let mut supervisor = StepperSupervisor::new(64, 50.0);
+        let mut now = Instant::now();
+        stepper.set_speed(500.0).unwrap();
+        let speed = stepper.get_speed() as i128;
+
+        // The counter follows the commanded speed
+        for i in 0..1000 {
+            assert_eq!(supervisor.update(&mut stepper, now), None);
+            smol::block_on(device.write()).input.counter_value = speed * 64 * i / 1000;
+            now += Duration::from_millis(1);
+        }
+
+        // The counter stops: the fault is raised once the error exceeds
+        // 50 steps, after 100 ms at 500 steps/s
+        let mut faulted_after = None;
+        for i in 0..200 {
+            if let Some(fault) = supervisor.update(&mut stepper, now) {
+                assert!(matches!(fault, StepperFault::FollowingError { steps } if steps > 50.0));
+                faulted_after = Some(i);
+                break;
+            }
+            now += Duration::from_millis(1);
+        }
+        assert!((95..=105).contains(&faulted_after.unwrap()));
+        assert!(supervisor.fault().is_some());
+
+        // Latched until reset, which pulses the reset bit of the terminal
+        assert_eq!(supervisor.update(&mut stepper, now), None);
+        supervisor.reset(&mut stepper);
+        assert!(smol::block_on(device.read()).output.reset);
+        assert_eq!(supervisor.update(&mut stepper, now), None);
+        assert!(!smol::block_on(device.read()).output.reset);
+        assert!(supervisor.fault().is_none());
+    }
+
+    #[test]
+    fn test_driver_error() {
+        let (mut stepper, device) = fake_stepper();
+        let mut supervisor = StepperSupervisor::new(64, 50.0);
+        let now = Instant::now();
+        supervisor.update(&mut stepper, now);
+        smol::block_on(device.write()).input.error = true;
+        smol::block_on(device.write()).input.warning = true;
+        assert_eq!(
+            supervisor.update(&mut stepper, now + Duration::from_millis(1)),
+            Some(StepperFault::DriverError)
+        );
+        assert!(supervisor.is_warning());
+
+        // A disabled stepper is not supervised
+        let mut supervisor = StepperSupervisor::new(64, 50.0);
+        smol::block_on(device.write()).output.enable = false;
+        supervisor.update(&mut stepper, now);
+        assert_eq!(
+            supervisor.update(&mut stepper, now + Duration::from_millis(1)),
+            None
+        );
+    }
+}
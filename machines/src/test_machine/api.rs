@@ -1,16 +1,17 @@
 use super::TestMachine;
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use control_core::socketio::{
     event::{Event, GenericEvent},
     namespace::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub struct StateEvent {
     pub led_on: [bool; 4],
 }
@@ -25,7 +26,7 @@ pub enum TestMachineEvents {
     State(Event<StateEvent>),
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(tag = "action", content = "value")]
 pub enum Mutation {
     SetLed { index: usize, on: bool },
@@ -59,6 +60,12 @@ impl CacheableEvents<TestMachineEvents> for TestMachineEvents {
     }
 }
 
+impl MachineApiSchema for TestMachine {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = ();
+}
+
 impl MachineApi for TestMachine {
     fn api_get_sender(&self) -> smol::channel::Sender<MachineMessage> {
         self.api_sender.clone()
@@ -6,16 +6,17 @@ use control_core::socketio::{
         CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_first_and_last_event,
     },
 };
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{MachineApi, wago_ai_test_machine::WagoAiTestMachine};
+use crate::{MachineApi, MachineApiSchema, wago_ai_test_machine::WagoAiTestMachine};
 
 #[derive(Debug, Clone)]
 pub struct WagoAiTestMachineNamespace {
     pub namespace: Option<Namespace>,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, JsonSchema)]
 pub enum AnalogInputsEvent {
     MeasurementRateHz(f64),
     AnalogInputs(f64, f64, f64, f64, String),
@@ -48,11 +49,17 @@ pub enum WagoAiTestMachineEvents {
     State(Event<AnalogInputsEvent>),
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct Mutation {
     measurement_rate_hz: i32,
 }
 
+impl MachineApiSchema for WagoAiTestMachine {
+    type Mutation = Mutation;
+    type State = ();
+    type LiveValues = ();
+}
+
 impl MachineApi for WagoAiTestMachine {
     fn api_get_sender(&self) -> smol::channel::Sender<crate::MachineMessage> {
         self.api_sender.clone()
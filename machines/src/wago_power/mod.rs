@@ -1,5 +1,5 @@
 use crate::{
-    MACHINE_WAGO_POWER_V1, MachineChannel, MachineWithChannel, VENDOR_QITECH,
+    MACHINE_WAGO_POWER_V1, MachineApiSchema, MachineChannel, MachineWithChannel, VENDOR_QITECH,
     machine_identification::MachineIdentification,
 };
 use anyhow::Result;
@@ -10,7 +10,7 @@ use control_core::socketio::{
         cache_first_and_last_event,
     },
 };
-use control_core_derive::BuildEvent;
+use control_core_derive::{BuildEvent, JsonSchema};
 use serde::*;
 use std::time::{Duration, Instant};
 
@@ -33,7 +33,7 @@ const MODBUS_DC_OFF: u16 = 0;
 const MODBUS_DC_ON: u16 = 1;
 const MODBUS_HICCUP_POWER: u16 = 1 << 8;
 
-#[derive(Serialize, Debug, Clone, BuildEvent)]
+#[derive(Serialize, Debug, Clone, BuildEvent, JsonSchema)]
 pub struct LiveValues {
     voltage: f64,
     current: f64,
@@ -49,7 +49,7 @@ impl CacheableEvents<Self> for LiveValues {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub enum Mode {
     Off,
     On24V,
@@ -64,7 +64,7 @@ impl Mode {
     }
 }
 
-#[derive(Serialize, Debug, Clone, BuildEvent)]
+#[derive(Serialize, Debug, Clone, BuildEvent, JsonSchema)]
 pub struct State {
     mode: Mode,
     is_default_state: bool,
@@ -80,7 +80,7 @@ impl CacheableEvents<Self> for State {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub enum Mutation {
     SetMode(Mode),
 }
@@ -194,6 +194,12 @@ impl WagoPower {
     };
 }
 
+impl MachineApiSchema for WagoPower {
+    type Mutation = Mutation;
+    type State = State;
+    type LiveValues = LiveValues;
+}
+
 impl MachineWithChannel for WagoPower {
     type State = State;
     type LiveValues = LiveValues;
@@ -22,6 +22,11 @@ impl MachineAct for Winder2 {
         // sync the traverse speed
         self.sync_traverse_speed();
 
+        // stall and following-error supervision, holds on a fault
+        if self.supervise_steppers(now) {
+            self.emit_state();
+        }
+
         // automatically stops or pulls after N Meters if enabled
         self.stop_or_pull_spool(now);
 
@@ -43,9 +48,8 @@ impl MachineAct for Winder2 {
                 self.emit_state();
             }
             MachineMessage::UnsubscribeNamespace => self.namespace.namespace = None,
-            MachineMessage::HttpApiJsonRequest(value) => {
-                use crate::MachineApi;
-                let _res = self.api_mutate(value);
+            MachineMessage::HttpApiJsonRequest(value, reply) => {
+                crate::apply_mutation(self, value, reply);
             }
             MachineMessage::ConnectToMachine(machine_connection) => {
                 if self.connected_machines.len() >= self.max_connected_machines {
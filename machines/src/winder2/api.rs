@@ -10,7 +10,7 @@ mod winder2_imports {
         },
     };
 
-    pub use control_core_derive::BuildEvent;
+    pub use control_core_derive::{BuildEvent, JsonSchema};
     pub use serde::{Deserialize, Serialize};
     pub use serde_json::Value;
     pub use smol::lock::Mutex;
@@ -33,7 +33,7 @@ mod winder2_imports {
         },
     };
 
-    pub use control_core_derive::BuildEvent;
+    pub use control_core_derive::{BuildEvent, JsonSchema};
     pub use serde::{Deserialize, Serialize};
     pub use serde_json::Value;
     pub use smol::lock::Mutex;
@@ -48,11 +48,12 @@ mod winder2_imports {
 use smol::channel::Sender;
 pub use winder2_imports::*;
 
+use crate::stepper_supervision::StepperFault;
 #[cfg(not(feature = "mock-machine"))]
-use crate::{MachineApi, MachineMessage};
+use crate::{MachineApi, MachineApiSchema, MachineMessage};
 use crate::{MachineCrossConnectionState, machine_identification::MachineIdentificationUnique};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
 pub enum Mode {
     #[default]
     Standby,
@@ -83,7 +84,7 @@ impl From<Mode> for Winder2Mode {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub enum Mutation {
     // Traverse
     /// Position in mm from home point
@@ -132,6 +133,10 @@ pub enum Mutation {
     // Mode
     SetMode(Mode),
 
+    // Steppers
+    /// Clear latched stepper faults and acknowledge driver errors
+    ResetStepperFaults,
+
     // Connected Machine
     SetConnectedMachine(MachineIdentificationUnique),
 
@@ -139,7 +144,7 @@ pub enum Mutation {
     DisconnectMachine(MachineIdentificationUnique),
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct LiveValuesEvent {
     /// traverse position in mm
     pub traverse_position: Option<f64>,
@@ -159,7 +164,7 @@ impl LiveValuesEvent {
     }
 }
 
-#[derive(Serialize, Debug, Clone, BuildEvent)]
+#[derive(Serialize, Debug, Clone, BuildEvent, JsonSchema)]
 pub struct StateEvent {
     pub is_default_state: bool,
     /// traverse state
@@ -174,11 +179,13 @@ pub struct StateEvent {
     pub tension_arm_state: TensionArmState,
     /// spool speed controller state
     pub spool_speed_controller_state: SpoolSpeedControllerState,
+    /// stepper faults and warnings
+    pub stepper_state: StepperState,
     /// Is a Machine Connected?
     pub connected_machine_state: MachineCrossConnectionState,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct TraverseState {
     /// min position in mm
     pub limit_inner: f64,
@@ -212,7 +219,7 @@ pub struct TraverseState {
     pub can_go_home: bool,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct PullerState {
     /// regulation type
     pub regulation: PullerRegulationMode,
@@ -226,7 +233,7 @@ pub struct PullerState {
     pub gear_ratio: GearRatio,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, JsonSchema)]
 pub enum SpoolAutomaticActionMode {
     #[default]
     NoAction,
@@ -234,13 +241,13 @@ pub enum SpoolAutomaticActionMode {
     Hold,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct SpoolAutomaticActionState {
     pub spool_required_meters: f64,
     pub spool_automatic_action_mode: SpoolAutomaticActionMode,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct ModeState {
     /// mode
     pub mode: Mode,
@@ -248,13 +255,13 @@ pub struct ModeState {
     pub can_wind: bool,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct TensionArmState {
     /// is zeroed
     pub zeroed: bool,
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
 pub struct SpoolSpeedControllerState {
     /// regulation mode
     pub regulation_mode: super::spool_speed_controller::SpoolSpeedControllerType,
@@ -276,6 +283,22 @@ pub struct SpoolSpeedControllerState {
     pub forward: bool,
 }
 
+#[derive(Serialize, Debug, Clone, Default, JsonSchema)]
+pub struct StepperState {
+    /// latched traverse fault, cleared by `ResetStepperFaults`
+    pub traverse_fault: Option<StepperFault>,
+    /// latched puller fault
+    pub puller_fault: Option<StepperFault>,
+    /// latched spool fault
+    pub spool_fault: Option<StepperFault>,
+    /// traverse driver warning (e.g. overtemperature)
+    pub traverse_warning: bool,
+    /// puller driver warning
+    pub puller_warning: bool,
+    /// spool driver warning
+    pub spool_warning: bool,
+}
+
 pub enum Winder2Events {
     LiveValues(Event<LiveValuesEvent>),
     State(Event<StateEvent>),
@@ -315,6 +338,13 @@ impl CacheableEvents<Self> for Winder2Events {
     }
 }
 
+#[cfg(not(feature = "mock-machine"))]
+impl MachineApiSchema for Winder2 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 #[cfg(not(feature = "mock-machine"))]
 impl MachineApi for Winder2 {
     fn api_get_sender(&self) -> Sender<MachineMessage> {
@@ -367,6 +397,7 @@ impl MachineApi for Winder2 {
             Mutation::SetSpoolAutomaticAction(mode) => self.set_spool_automatic_mode(mode),
             Mutation::ResetSpoolProgress => self.stop_or_pull_spool_reset(Instant::now()),
             Mutation::ZeroTensionArmAngle => self.tension_arm_zero(),
+            Mutation::ResetStepperFaults => self.reset_stepper_faults(),
             Mutation::SetConnectedMachine(machine_identification_unique) => {
                 let main_sender = match &self.main_sender {
                     Some(sender) => sender,
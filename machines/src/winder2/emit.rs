@@ -5,8 +5,8 @@ mod winder2_imports {
     pub use crate::buffer1::BufferV1;
     pub use api::{
         LiveValuesEvent, ModeState, PullerState, SpoolAutomaticActionMode,
-        SpoolAutomaticActionState, SpoolSpeedControllerState, StateEvent, TensionArmState,
-        TraverseState, Winder2Events,
+        SpoolAutomaticActionState, SpoolSpeedControllerState, StateEvent, StepperState,
+        TensionArmState, TraverseState, Winder2Events,
     };
     pub use control_core::socketio::event::BuildEvent;
     pub use control_core::socketio::namespace::NamespaceCacheingLogic;
@@ -81,7 +81,12 @@ impl Winder2 {
     }
     /// Implement Mode
     pub fn set_mode(&mut self, mode: &Winder2Mode) {
-        let should_update = *mode != Winder2Mode::Wind || self.can_wind();
+        let should_update = match mode {
+            Winder2Mode::Wind => self.can_wind(),
+            // A faulted stepper must be reset first
+            Winder2Mode::Pull => !self.has_stepper_fault(),
+            Winder2Mode::Standby | Winder2Mode::Hold => true,
+        };
 
         if should_update {
             // all transitions are allowed
@@ -302,6 +307,14 @@ impl Winder2 {
                 spool_required_meters: self.spool_automatic_action.target_length.get::<meter>(),
                 spool_automatic_action_mode: self.spool_automatic_action.mode.clone(),
             },
+            stepper_state: StepperState {
+                traverse_fault: self.traverse_supervisor.fault().cloned(),
+                puller_fault: self.puller_supervisor.fault().cloned(),
+                spool_fault: self.spool_supervisor.fault().cloned(),
+                traverse_warning: self.traverse_supervisor.is_warning(),
+                puller_warning: self.puller_supervisor.is_warning(),
+                spool_warning: self.spool_supervisor.is_warning(),
+            },
             connected_machine_state: cross_conn,
         }
     }
@@ -381,6 +394,14 @@ impl Winder2 {
         self.emit_state();
     }
 
+    /// Implement Stepper Supervision
+    pub fn reset_stepper_faults(&mut self) {
+        self.traverse_supervisor.reset(&mut self.traverse);
+        self.puller_supervisor.reset(&mut self.puller);
+        self.spool_supervisor.reset(&mut self.spool);
+        self.emit_state();
+    }
+
     /// Implement Tension Arm
     pub fn tension_arm_zero(&mut self) {
         self.tension_arm.zero();
@@ -1,8 +1,17 @@
 use super::Winder2;
-use crate::{MachineApi, winder2::api::Mutation};
+use crate::{
+    MachineApi, MachineApiSchema,
+    winder2::api::{LiveValuesEvent, Mutation, StateEvent},
+};
 use serde_json::Value;
 use std::time::Instant;
 
+impl MachineApiSchema for Winder2 {
+    type Mutation = Mutation;
+    type State = StateEvent;
+    type LiveValues = LiveValuesEvent;
+}
+
 impl MachineApi for Winder2 {
     fn api_mutate(&mut self, request_body: Value) -> Result<(), anyhow::Error> {
         let mutation: Mutation = serde_json::from_value(request_body)?;
@@ -48,6 +57,7 @@ impl MachineApi for Winder2 {
             Mutation::SetSpoolAutomaticAction(mode) => self.set_spool_automatic_mode(mode),
             Mutation::ResetSpoolProgress => self.stop_or_pull_spool_reset(Instant::now()),
             Mutation::ZeroTensionArmAngle => self.tension_arm_zero(),
+            Mutation::ResetStepperFaults => self.reset_stepper_faults(),
             Mutation::SetConnectedMachine(machine_identification_unique) => {
                 self.set_connected_buffer(machine_identification_unique)
             }
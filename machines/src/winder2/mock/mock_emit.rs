@@ -4,7 +4,9 @@ use super::Winder2;
 use crate::machine_identification::{MachineIdentification, MachineIdentificationUnique};
 use crate::winder2::Winder2Mode;
 use crate::winder2::api::LiveValuesEvent;
-use crate::winder2::api::{ModeState, SpoolAutomaticActionMode, StateEvent, Winder2Events};
+use crate::winder2::api::{
+    ModeState, SpoolAutomaticActionMode, StateEvent, StepperState, Winder2Events,
+};
 use crate::winder2::puller_speed_controller::{GearRatio, PullerRegulationMode};
 use crate::winder2::spool_speed_controller::SpoolSpeedControllerType;
 use crate::{MACHINE_WINDER_V1, VENDOR_QITECH};
@@ -106,6 +108,7 @@ impl Winder2 {
             mode_state: self.mode_state.clone(),
             tension_arm_state: self.tension_arm_state.clone(),
             spool_speed_controller_state: self.spool_speed_controller_state.clone(),
+            stepper_state: self.stepper_state.clone(),
             connected_machine_state: cross_conn,
         }
     }
@@ -128,6 +131,12 @@ impl Winder2 {
         self.emit_state();
     }
 
+    /// Implement Stepper Supervision
+    pub fn reset_stepper_faults(&mut self) {
+        self.stepper_state = StepperState::default();
+        self.emit_state();
+    }
+
     /// Implement Tension Arm
     pub fn tension_arm_zero(&mut self) {
         self.tension_arm_state.zeroed = true;
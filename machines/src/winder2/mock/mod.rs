@@ -6,8 +6,8 @@ pub mod mock_emit;
 pub mod new;
 
 use super::api::{
-    ModeState, PullerState, SpoolAutomaticActionState, SpoolSpeedControllerState, TensionArmState,
-    TraverseState, Winder2Namespace,
+    ModeState, PullerState, SpoolAutomaticActionState, SpoolSpeedControllerState, StepperState,
+    TensionArmState, TraverseState, Winder2Namespace,
 };
 use crate::{
     AsyncThreadMessage, Machine, MachineConnection, MachineMessage,
@@ -33,6 +33,8 @@ pub struct Winder2 {
     pub tension_arm_state: TensionArmState,
     /// spool speed controller state
     pub spool_speed_controller_state: SpoolSpeedControllerState,
+    /// stepper faults and warnings
+    pub stepper_state: StepperState,
 
     /// Receive from Api or MainThread
     api_receiver: Receiver<MachineMessage>,
@@ -1,7 +1,7 @@
 use crate::{
     MachineNewParams, MachineNewTrait,
     winder2::api::{
-        ModeState, PullerState, SpoolAutomaticActionState, SpoolSpeedControllerState,
+        ModeState, PullerState, SpoolAutomaticActionState, SpoolSpeedControllerState, StepperState,
         TensionArmState, TraverseState, Winder2Namespace,
     },
 };
@@ -47,6 +47,7 @@ impl MachineNewTrait for Winder2 {
             mode_state: ModeState::default(),
             tension_arm_state: TensionArmState::default(),
             spool_speed_controller_state: SpoolSpeedControllerState::default(),
+            stepper_state: StepperState::default(),
             connected_machines: vec![],
         };
 
@@ -40,6 +40,7 @@ mod winder2_imports {
     pub use std::{fmt::Debug, sync::Weak, time::Instant};
 
     pub use crate::buffer1::BufferV1;
+    pub use crate::stepper_supervision::StepperSupervisor;
     pub use crate::{AsyncThreadMessage, Machine};
     pub use units::ConstZero;
     pub use units::f64::Length;
@@ -82,6 +83,25 @@ impl Machine for Winder2 {
     fn get_main_sender(&self) -> Option<Sender<AsyncThreadMessage>> {
         self.main_sender.clone()
     }
+
+    fn signal_names(&self) -> &'static [&'static str] {
+        &[
+            "traverse_position",
+            "puller_speed",
+            "spool_rpm",
+            "tension_arm_angle",
+            "spool_progress",
+        ]
+    }
+
+    fn sample_signals(&self, values: &mut [f64]) {
+        let live_values = self.get_live_values();
+        values[0] = live_values.traverse_position.unwrap_or(f64::NAN);
+        values[1] = live_values.puller_speed;
+        values[2] = live_values.spool_rpm;
+        values[3] = live_values.tension_arm_angle;
+        values[4] = live_values.spool_progress;
+    }
 }
 
 #[cfg(not(feature = "mock-machine"))]
@@ -104,6 +124,11 @@ pub struct Winder2 {
     pub traverse_controller: TraverseController,
     pub traverse_end_stop: DigitalInput,
 
+    // stepper stall and following-error supervision
+    pub traverse_supervisor: StepperSupervisor,
+    pub puller_supervisor: StepperSupervisor,
+    pub spool_supervisor: StepperSupervisor,
+
     // socketio
     namespace: Winder2Namespace,
     last_measurement_emit: Instant,
@@ -148,16 +173,69 @@ impl Winder2 {
         self.traverse_controller.update_speed(
             &mut self.traverse,
             &self.traverse_end_stop,
+            &self.spool,
             self.spool_speed_controller.get_speed(),
         )
     }
 
     /// Can wind capability check
     pub const fn can_wind(&self) -> bool {
-        // Check if tension arm is zeroed and traverse is homed
+        // Check if tension arm is zeroed, traverse is homed and no stepper faulted
         self.tension_arm.zeroed
             && self.traverse_controller.is_homed()
             && !self.traverse_controller.is_going_home()
+            && !self.has_stepper_fault()
+    }
+
+    /// A stepper fault is latched, see [`Self::supervise_steppers`]
+    pub const fn has_stepper_fault(&self) -> bool {
+        self.traverse_supervisor.fault().is_some()
+            || self.puller_supervisor.fault().is_some()
+            || self.spool_supervisor.fault().is_some()
+    }
+
+    /// Compares the commanded with the counted steps and reads the driver
+    /// diagnostics of all steppers, called after their speeds are synced. A
+    /// fault stops traverse, puller and spool in Hold until
+    /// [`Self::reset_stepper_faults`]. Returns if the state changed.
+    pub fn supervise_steppers(&mut self, now: Instant) -> bool {
+        let warnings = self.stepper_warnings();
+        let faults = [
+            (
+                "traverse",
+                self.traverse_supervisor.update(&mut self.traverse, now),
+            ),
+            (
+                "puller",
+                self.puller_supervisor.update(&mut self.puller, now),
+            ),
+            ("spool", self.spool_supervisor.update(&mut self.spool, now)),
+        ];
+        let mut faulted = false;
+        for (name, fault) in faults {
+            if let Some(fault) = fault {
+                tracing::error!("[Winder2] {} stepper: {} - holding", name, fault);
+                faulted = true;
+            }
+        }
+        if faulted {
+            if matches!(self.mode, Winder2Mode::Pull | Winder2Mode::Wind) {
+                self.set_mode(&Winder2Mode::Hold);
+            }
+            // Hold homes the traverse, stay put instead. A faulted traverse
+            // lost its position.
+            let position_lost = self.traverse_supervisor.fault().is_some();
+            self.traverse_controller.halt(position_lost);
+        }
+        faulted || warnings != self.stepper_warnings()
+    }
+
+    const fn stepper_warnings(&self) -> [bool; 3] {
+        [
+            self.traverse_supervisor.is_warning(),
+            self.puller_supervisor.is_warning(),
+            self.spool_supervisor.is_warning(),
+        ]
     }
 
     /// Can go to inner limit capability check
@@ -3,6 +3,7 @@ mod winder2_imports {
     pub use super::super::api::Winder2Namespace;
     pub use super::super::tension_arm::TensionArm;
     pub use super::super::{Winder2, Winder2Mode};
+    pub use crate::stepper_supervision::StepperSupervisor;
     pub use crate::winder2::puller_speed_controller::PullerSpeedController;
     pub use crate::winder2::spool_speed_controller::SpoolSpeedController;
     pub use crate::winder2::traverse_controller::TraverseController;
@@ -52,6 +53,11 @@ pub use winder2_imports::*;
 #[cfg(not(feature = "mock-machine"))]
 use crate::get_ethercat_device;
 
+/// Commanded and counted steps may differ by one motor revolution before a
+/// stepper faults (full steps)
+#[cfg(not(feature = "mock-machine"))]
+const MAX_FOLLOWING_ERROR_STEPS: f64 = 200.0;
+
 #[cfg(not(feature = "mock-machine"))]
 impl MachineNewTrait for Winder2 {
     fn new<'maindevice>(params: &MachineNewParams) -> Result<Self, Error> {
@@ -235,8 +241,12 @@ impl MachineNewTrait for Winder2 {
                 traverse_controller: TraverseController::new(
                     Length::new::<millimeter>(22.0), // Default inner limit
                     Length::new::<millimeter>(92.0), // Default outer limit
-                    64,                              // Microsteps
+                    64,                              // Traverse microsteps
+                    64,                              // Spool microsteps
                 ),
+                traverse_supervisor: StepperSupervisor::new(64, MAX_FOLLOWING_ERROR_STEPS),
+                puller_supervisor: StepperSupervisor::new(64, MAX_FOLLOWING_ERROR_STEPS),
+                spool_supervisor: StepperSupervisor::new(64, MAX_FOLLOWING_ERROR_STEPS),
                 emitted_default_state: false,
                 spool_automatic_action: super::SpoolAutomaticAction {
                     progress: Length::ZERO,
@@ -6,11 +6,12 @@ use crate::winder2::{
 use control_core::controllers::second_degree_motion::acceleration_position_controller::MotionControllerError;
 
 use super::tension_arm::TensionArm;
+use control_core_derive::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use units::f64::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub enum SpoolSpeedControllerType {
     #[default]
     Adaptive,
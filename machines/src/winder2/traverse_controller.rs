@@ -1,15 +1,24 @@
 use std::time::Instant;
 
+use control_core::controllers::electronic_gear::{CamTable, ElectronicGear, GearProfile};
+use control_core::converters::angular_step_converter::AngularStepConverter;
 use control_core::converters::linear_step_converter::LinearStepConverter;
 use ethercat_hal::io::{
     digital_input::DigitalInput, stepper_velocity_el70x1::StepperVelocityEL70x1,
 };
 use units::ConstZero;
+use units::angle::revolution;
 use units::angular_velocity::revolution_per_second;
 use units::f64::{AngularVelocity, Length, Velocity};
 use units::length::millimeter;
 use units::velocity::millimeter_per_second;
 
+/// Correction speed of the traverse per mm behind its cam position (1/s)
+const GEAR_POSITION_GAIN: f64 = 10.0;
+
+/// Largest correction speed of the traverse (mm/s)
+const GEAR_MAX_CORRECTION_MM_S: f64 = 10.0;
+
 #[derive(Debug)]
 pub struct TraverseController {
     enabled: bool,
@@ -21,6 +30,13 @@ pub struct TraverseController {
     state: State,
     fullstep_converter: LinearStepConverter,
     microstep_converter: LinearStepConverter,
+    /// Couples the traverse position to the spool revolutions while
+    /// traversing, see [`Self::traverse_cam`]
+    gear: ElectronicGear,
+    spool_microstep_converter: AngularStepConverter,
+    /// Revolutions the spool turned, in either direction
+    spool_revolutions: f64,
+    last_spool_steps: Option<i128>,
     // A sticky flag if the [`State`] changed (not the sub states)
     // Needed to send state updates to the UI
     did_change_state: bool,
@@ -62,12 +78,12 @@ pub enum TraversingState {
 
     /// Like [`State::GoingIn`] but
     /// - will go into [`State::GoingOut`] after reaching the inner limit
-    /// - speed is synced to spool speed
+    /// - position is geared to the spool revolutions
     TraversingIn,
 
     /// Like [`State::GoingOut`] but
     /// - will go into [`State::GoingIn`] after reaching the outer limit
-    /// - speed is synced to spool speed
+    /// - position is geared to the spool revolutions
     TraversingOut,
 }
 
@@ -97,7 +113,14 @@ pub enum HomingState {
 }
 
 impl TraverseController {
-    pub fn new(limit_inner: Length, limit_outer: Length, microsteps: u8) -> Self {
+    /// `microsteps` of the traverse stepper, `spool_microsteps` of the spool
+    /// stepper the traverse is geared to
+    pub fn new(
+        limit_inner: Length,
+        limit_outer: Length,
+        microsteps: u8,
+        spool_microsteps: u8,
+    ) -> Self {
         Self {
             enabled: false,
             position: Length::ZERO,
@@ -115,6 +138,14 @@ impl TraverseController {
                 200 * microsteps as i16,
                 Length::new::<millimeter>(35.0),
             ),
+            gear: ElectronicGear::new(
+                GearProfile::Ratio(0.0),
+                GEAR_POSITION_GAIN,
+                GEAR_MAX_CORRECTION_MM_S,
+            ),
+            spool_microstep_converter: AngularStepConverter::new(200 * spool_microsteps as i16),
+            spool_revolutions: 0.0,
+            last_spool_steps: None,
         }
     }
 }
@@ -127,18 +158,22 @@ impl TraverseController {
 
     pub fn set_limit_inner(&mut self, limit: Length) {
         self.limit_inner = limit;
+        self.regear();
     }
 
     pub fn set_limit_outer(&mut self, limit: Length) {
         self.limit_outer = limit;
+        self.regear();
     }
 
     pub fn set_step_size(&mut self, step_size: Length) {
         self.step_size = step_size;
+        self.regear();
     }
 
     pub fn set_padding(&mut self, padding: Length) {
         self.padding = padding;
+        self.regear();
     }
 
     pub fn get_limit_inner(&self) -> Length {
@@ -180,18 +215,32 @@ impl TraverseController {
 impl TraverseController {
     pub const fn goto_limit_inner(&mut self) {
         self.state = State::GoingIn;
+        self.gear.disengage();
     }
 
     pub const fn goto_limit_outer(&mut self) {
         self.state = State::GoingOut;
+        self.gear.disengage();
     }
 
     pub const fn goto_home(&mut self) {
         self.state = State::Homing(HomingState::Initialize);
+        self.gear.disengage();
     }
 
     pub const fn start_traversing(&mut self) {
         self.state = State::Traversing(TraversingState::GoingOut);
+        self.gear.disengage();
+    }
+
+    /// Stops any movement. With `position_lost` the traverse has to be
+    /// homed again.
+    pub const fn halt(&mut self, position_lost: bool) {
+        self.state = match position_lost || !self.is_homed() {
+            true => State::NotHomed,
+            false => State::Idle,
+        };
+        self.gear.disengage();
     }
 
     pub const fn is_homed(&self) -> bool {
@@ -257,6 +306,82 @@ impl TraverseController {
         self.position = self.microstep_converter.steps_to_distance(steps as f64);
     }
 
+    /// Accumulates the spool revolutions, the master position of the gear.
+    /// Counted in either direction so the lay continues when the spool
+    /// direction is flipped.
+    pub fn sync_spool_position(&mut self, spool: &StepperVelocityEL70x1) {
+        let steps = spool.get_position();
+        if let Some(last) = self.last_spool_steps {
+            let revolutions = self
+                .spool_microstep_converter
+                .steps_to_angle((steps - last) as f64)
+                .get::<revolution>();
+            self.spool_revolutions += revolutions.abs();
+        }
+        self.last_spool_steps = Some(steps);
+    }
+
+    /// Traverse turning points: outer and inner limit within the padding
+    fn traverse_range(&self) -> (f64, f64) {
+        (
+            (self.limit_outer - self.padding).get::<millimeter>(),
+            (self.limit_inner + self.padding).get::<millimeter>(),
+        )
+    }
+
+    /// One traverse cycle as cam over the spool revolutions: starting at the
+    /// outer turning point, in to the inner one and back out, one step size
+    /// per revolution. `None` if the padding leaves no room to traverse.
+    fn traverse_cam(&self) -> Option<CamTable> {
+        let (outer, inner) = self.traverse_range();
+        let stroke = outer - inner;
+        let revolutions = stroke / self.step_size.get::<millimeter>();
+        if !(stroke > 0.0 && revolutions.is_finite() && revolutions > 0.0) {
+            return None;
+        }
+        CamTable::new(vec![
+            (0.0, 0.0),
+            (revolutions, -stroke),
+            (2.0 * revolutions, 0.0),
+        ])
+        .ok()
+    }
+
+    /// Whether the engaged cam is in its inward half
+    fn cam_going_in(&self) -> Option<bool> {
+        let GearProfile::Cam(cam) = self.gear.profile() else {
+            return None;
+        };
+        let phase = self.gear.phase(self.spool_revolutions)?;
+        Some(phase.rem_euclid(cam.period()) < cam.period() / 2.0)
+    }
+
+    /// Engages the gear with the cam phase of the current position and
+    /// direction: the traverse continues its lay without a jump, also after
+    /// the limits, padding or step size changed.
+    fn regear(&mut self) {
+        let direction_in = match &self.state {
+            State::Traversing(TraversingState::TraversingIn) => true,
+            State::Traversing(TraversingState::TraversingOut) => false,
+            _ => return,
+        };
+        let Some(cam) = self.traverse_cam() else {
+            tracing::warn!("[TraverseController] No room to traverse, holding position");
+            self.gear.disengage();
+            return;
+        };
+        let (outer, inner) = self.traverse_range();
+        let half = cam.period() / 2.0;
+        let step_size = self.step_size.get::<millimeter>();
+        let position = self.position.get::<millimeter>();
+        let phase = match direction_in {
+            true => ((outer - position) / step_size).clamp(0.0, half),
+            false => half + ((position - inner) / step_size).clamp(0.0, half),
+        };
+        self.gear.set_profile(GearProfile::Cam(cam));
+        self.gear.engage(self.spool_revolutions, phase, outer);
+    }
+
     /// Update the [`did_change_state`] flag
     /// Only considers the major state not the sub states
     const fn update_did_change_state(&mut self, old_state: &State) -> bool {
@@ -365,22 +490,18 @@ impl TraverseController {
                 TraversingState::GoingOut => {
                     // If outer limit is reached
                     if self.position >= self.limit_outer - self.padding {
-                        // Turn around
+                        // Turn around and lock the lay to the spool
                         self.state = State::Traversing(TraversingState::TraversingIn);
+                        self.regear();
                     }
                 }
-                TraversingState::TraversingIn => {
-                    // If inner limit is reached
-                    if self.position <= self.limit_inner + self.padding {
-                        // Turn around
-                        self.state = State::Traversing(TraversingState::TraversingOut);
-                    }
-                }
-                TraversingState::TraversingOut => {
-                    // If outer limit is reached
-                    if self.position >= self.limit_outer - self.padding {
-                        // Turn around
-                        self.state = State::Traversing(TraversingState::TraversingIn);
+                // The cam turns around, follow its direction
+                TraversingState::TraversingIn | TraversingState::TraversingOut => {
+                    if let Some(going_in) = self.cam_going_in() {
+                        self.state = match going_in {
+                            true => State::Traversing(TraversingState::TraversingIn),
+                            false => State::Traversing(TraversingState::TraversingOut),
+                        };
                     }
                 }
             },
@@ -451,14 +572,16 @@ impl TraverseController {
                         Velocity::new::<millimeter_per_second>(100.0),
                     )
                 }
-                TraversingState::TraversingIn => self.speed_to_position(
-                    self.limit_inner + self.padding - Length::new::<millimeter>(0.01),
-                    Self::calculate_traverse_speed(spool_speed, self.step_size),
-                ),
-                TraversingState::TraversingOut => self.speed_to_position(
-                    self.limit_outer - self.padding + Length::new::<millimeter>(0.01),
-                    Self::calculate_traverse_speed(spool_speed, self.step_size),
-                ),
+                TraversingState::TraversingIn | TraversingState::TraversingOut => {
+                    // Phase-locked to the spool revolutions; stands still if
+                    // the gear could not be engaged
+                    let speed = self.gear.update(
+                        self.spool_revolutions,
+                        spool_speed.get::<revolution_per_second>().abs(),
+                        self.position.get::<millimeter>(),
+                    );
+                    Velocity::new::<millimeter_per_second>(speed.unwrap_or(0.0))
+                }
             },
         }
     }
@@ -487,8 +610,10 @@ impl TraverseController {
         &mut self,
         traverse: &mut StepperVelocityEL70x1,
         traverse_end_stop: &DigitalInput,
+        spool: &StepperVelocityEL70x1,
         spool_speed: AngularVelocity,
     ) {
+        self.sync_spool_position(spool);
         let speed = self.get_speed(traverse, traverse_end_stop, spool_speed);
         let steps_per_second = self.fullstep_converter.velocity_to_steps(speed);
         // ignore if we can't set speed
@@ -514,4 +639,43 @@ mod tests {
             epsilon = f64::EPSILON
         );
     }
+
+    #[test]
+    fn test_regear_keeps_position() {
+        let mut controller = TraverseController::new(
+            Length::new::<millimeter>(22.0),
+            Length::new::<millimeter>(92.0),
+            64,
+            64,
+        );
+        // Stroke from 91.12 mm to 22.88 mm, one cycle in 78 revolutions
+        let cam = controller.traverse_cam().unwrap();
+        assert_relative_eq!(cam.period(), 2.0 * 68.24 / 1.75, epsilon = 1e-9);
+
+        controller.state = State::Traversing(TraversingState::TraversingOut);
+        controller.position = Length::new::<millimeter>(50.0);
+        controller.spool_revolutions = 120.0;
+        controller.regear();
+        let target = controller.gear.slave_target(120.0).unwrap();
+        assert_relative_eq!(target, 50.0, epsilon = 1e-9);
+        assert_eq!(controller.cam_going_in(), Some(false));
+
+        // A new step size changes the lay from here on, without a jump
+        controller.set_step_size(Length::new::<millimeter>(3.5));
+        assert_relative_eq!(
+            controller.gear.slave_target(120.0).unwrap(),
+            50.0,
+            epsilon = 1e-9
+        );
+        // One revolution later the traverse moved one step size out
+        assert_relative_eq!(
+            controller.gear.slave_target(121.0).unwrap(),
+            53.5,
+            epsilon = 1e-9
+        );
+
+        // No room left between the paddings: the traverse holds
+        controller.set_padding(Length::new::<millimeter>(40.0));
+        assert!(!controller.gear.is_engaged());
+    }
 }
@@ -1,11 +1,16 @@
+use crate::audit::{self, AuditLog};
+use crate::auth::AuthState;
 use crate::ethercat::config::{MAX_SUBDEVICES, PDI_LEN};
+use crate::r#loop::MachineExecutor;
 use crate::rest::handlers::write_machine_device_identification::MachineDeviceInfoRequest;
 use crate::socketio::main_namespace::MainNamespaceEvents;
 use crate::socketio::main_namespace::machines_event::{MachineObj, MachinesEventBuilder};
 use crate::socketio::namespaces::Namespaces;
+use crate::socketio::subscription::SocketSubscriptions;
 use anyhow::{Result, bail};
-use control_core::socketio::event::GenericEvent;
+use control_core::socketio::namespace::SocketQueueItem;
 use ethercat_hal::devices::EthercatDevice;
+use ethercat_hal::sdo::{SdoRequest, SdoResponse};
 use ethercrab::SubDeviceRef;
 use ethercrab::{MainDevice, SubDeviceGroup, subdevice_group::Op};
 use machines::machine_identification::{DeviceIdentification, MachineIdentificationUnique};
@@ -15,7 +20,6 @@ use serde::{Deserialize, Serialize};
 use smol::channel::{Receiver, Sender};
 use smol::lock::{Mutex, RwLock};
 use socketioxide::SocketIo;
-use socketioxide::extract::SocketRef;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
@@ -23,8 +27,9 @@ use std::sync::Arc;
 pub struct SocketioSetup {
     pub socketio: RwLock<Option<SocketIo>>,
     pub namespaces: RwLock<Namespaces>,
-    pub socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>,
-    pub socket_queue_rx: Receiver<(SocketRef, Arc<GenericEvent>)>,
+    pub socket_queue_tx: Sender<SocketQueueItem>,
+    pub socket_queue_rx: Receiver<SocketQueueItem>,
+    pub subscriptions: Arc<SocketSubscriptions>,
 }
 
 pub struct SerialSetup {
@@ -50,6 +55,12 @@ pub enum HotThreadMessage {
         subindex: u8,
         value: u16,
     },
+    /// Single typed CoE access for the SDO browser, answered on `reply`
+    Sdo {
+        subdevice_index: usize,
+        request: SdoRequest,
+        reply: Sender<Result<SdoResponse, String>>,
+    },
 }
 
 use crate::AsyncThreadMessage;
@@ -88,8 +99,12 @@ pub struct SharedState {
     pub api_machines: Mutex<HashMap<MachineIdentificationUnique, Sender<MachineMessage>>>,
     pub current_machines_meta: Mutex<Vec<MachineObj>>,
     pub rt_machine_creation_channel: Sender<HotThreadMessage>,
+    /// Channel of the non-RT executor, `None` if all machines run in the RT loop
+    pub non_rt_machine_creation_channel: Option<Sender<HotThreadMessage>>,
     pub main_channel: Sender<AsyncThreadMessage>,
     pub ethercat_meta_data: RwLock<Vec<EtherCatDeviceMetaData>>,
+    pub auth: AuthState,
+    pub audit_log: AuditLog,
 }
 
 impl fmt::Debug for EthercatSetup {
@@ -155,6 +170,19 @@ impl SharedState {
         bail!("Unknown machine!")
     }
 
+    /// Channel of the loop executing machines of the given kind
+    ///
+    /// Falls back to the RT loop if non-RT machines are not isolated.
+    pub const fn machine_creation_channel(
+        &self,
+        executor: MachineExecutor,
+    ) -> &Sender<HotThreadMessage> {
+        match (executor, &self.non_rt_machine_creation_channel) {
+            (MachineExecutor::NonRealTime, Some(channel)) => channel,
+            _ => &self.rt_machine_creation_channel,
+        }
+    }
+
     /// Removes a machine by its unique identifier
     pub async fn remove_machine(&self, machine_id: &MachineIdentificationUnique) {
         let mut current_machines = self.current_machines_meta.lock().await;
@@ -208,7 +236,7 @@ impl SharedState {
         });
     }
 
-    pub async fn add_machines(&self, machines: Vec<Box<dyn Machine>>) {
+    pub async fn add_machines(&self, machines: Vec<Box<dyn Machine>>, executor: MachineExecutor) {
         let mut api_machines = self.api_machines.lock().await;
         for machine in machines.iter() {
             api_machines.insert(
@@ -227,16 +255,17 @@ impl SharedState {
             .collect();
         self.add_machines_if_not_exists(objs).await;
 
-        self.rt_machine_creation_channel
+        self.machine_creation_channel(executor)
             .send(HotThreadMessage::AddMachines(machines))
             .await
-            .expect("Could not send to real-time channel");
+            .expect("Could not send to machine loop channel");
 
         self.send_machines_event().await;
     }
 
     pub fn new(
         sender: Sender<HotThreadMessage>,
+        non_rt_sender: Option<Sender<HotThreadMessage>>,
         main_async_channel: Sender<AsyncThreadMessage>,
     ) -> Self {
         let (socket_queue_tx, socket_queue_rx) = smol::channel::unbounded();
@@ -248,10 +277,14 @@ impl SharedState {
                 namespaces: RwLock::new(Namespaces::new(socket_queue_tx.clone())),
                 socket_queue_tx,
                 socket_queue_rx,
+                subscriptions: Arc::new(SocketSubscriptions::default()),
             },
             api_machines: Mutex::new(HashMap::new()),
             rt_machine_creation_channel: sender,
+            non_rt_machine_creation_channel: non_rt_sender,
             main_channel: main_async_channel,
+            auth: AuthState::from_config(),
+            audit_log: AuditLog::new(audit::path()),
         }
     }
 }
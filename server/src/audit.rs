@@ -0,0 +1,461 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use machines::MachineMessage;
+use machines::machine_identification::MachineIdentificationUnique;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app_state::SharedState;
+use crate::auth::sessions::Session;
+use crate::config::config;
+
+const FILENAME: &str = "audit.jsonl";
+
+/// Bytes [`AuditLog::query`] reads at most, from the end of the log
+pub const MAX_QUERY_SCAN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Read size of [`AuditLog::query`]
+const QUERY_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// Location of the audit log, next to the other persisted state
+pub fn path() -> PathBuf {
+    let state = &config().state;
+    state.file(state.audit_file.as_ref(), FILENAME)
+}
+
+/// How a mutation ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "message", rename_all = "snake_case")]
+pub enum AuditResult {
+    /// Handed to the machine without waiting for the outcome, written by earlier versions
+    Sent,
+    /// Applied by the machine
+    Applied,
+    /// Rejected by the machine, e.g. a soft-limit violation
+    Rejected(String),
+    /// The machine didn't answer within `machines.mutation_timeout_ms`
+    Timeout,
+    /// Rejected by the permission check
+    Denied(String),
+    /// Doesn't match the mutation schema of the machine
+    Invalid(String),
+    /// Failed before or while reaching the machine
+    Error(String),
+}
+
+/// One mutation, serialized as one line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix time in milliseconds
+    pub timestamp_ms: u128,
+    pub username: String,
+    /// API the mutation came in through, e.g. `rest_v1`
+    pub source: String,
+    pub remote_addr: Option<String>,
+    pub machine_identification_unique: MachineIdentificationUnique,
+    pub mutation: Value,
+    pub result: AuditResult,
+    /// Machine state right before and after the mutation was applied
+    pub state_before: Option<Value>,
+    pub state_after: Option<Value>,
+}
+
+/// Filter for [`AuditLog::query`], all conditions are optional
+#[derive(Debug, Default, Clone)]
+pub struct AuditQuery {
+    pub machine_identification_unique: Option<MachineIdentificationUnique>,
+    pub from_ms: Option<u128>,
+    pub to_ms: Option<u128>,
+    /// Return at most the newest `limit` entries
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.machine_identification_unique
+            .as_ref()
+            .is_none_or(|id| *id == entry.machine_identification_unique)
+            && self.from_ms.is_none_or(|from| entry.timestamp_ms >= from)
+            && self.to_ms.is_none_or(|to| entry.timestamp_ms <= to)
+    }
+}
+
+/// Append-only log of all machine mutations as JSON lines
+///
+/// The file is only ever opened in append mode, entries are never changed or removed by the
+/// server. Rotation and retention are left to the operating system.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLog {
+    pub const fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = self.file.lock().expect("Audit lock poisoned");
+        if file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        let result = file
+            .as_mut()
+            .expect("File opened above")
+            .write_all(line.as_bytes());
+        if result.is_err() {
+            // reopen on the next entry, e.g. after the file was rotated away
+            *file = None;
+        }
+        drop(file);
+        Ok(result?)
+    }
+
+    /// Reads the newest entries matching `query`, oldest first
+    ///
+    /// Blocking, the file is read backwards from its end until `limit` entries are found, an
+    /// entry older than `from_ms` is reached or [`MAX_QUERY_SCAN_BYTES`] were read.
+    pub fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditEntry>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let end = file.metadata()?.len();
+        let stop = end.saturating_sub(MAX_QUERY_SCAN_BYTES);
+
+        let mut entries = vec![];
+        // unprocessed bytes from `pos` on, ends before the lines already processed
+        let mut buffer = vec![];
+        let mut pos = end;
+        loop {
+            while let Some(newline) = buffer.iter().rposition(|&b| b == b'\n') {
+                let line = buffer.split_off(newline + 1);
+                buffer.truncate(newline);
+                if !collect_line(&line, query, &mut entries) {
+                    entries.reverse();
+                    return Ok(entries);
+                }
+            }
+            if pos == stop {
+                // the first line of the file, a cut off line otherwise
+                if pos == 0 {
+                    collect_line(&buffer, query, &mut entries);
+                } else {
+                    tracing::warn!(
+                        "Audit query stopped after the newest {} bytes",
+                        MAX_QUERY_SCAN_BYTES
+                    );
+                }
+                break;
+            }
+
+            let start = pos.saturating_sub(QUERY_CHUNK_BYTES).max(stop);
+            let mut chunk = vec![0; (pos - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.append(&mut buffer);
+            buffer = chunk;
+            pos = start;
+        }
+
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+/// Adds the entry in `line` to `entries` if it matches, `false` once the query is satisfied
+fn collect_line(line: &[u8], query: &AuditQuery, entries: &mut Vec<AuditEntry>) -> bool {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return true;
+    }
+    let entry = match serde_json::from_slice::<AuditEntry>(line) {
+        Ok(entry) => entry,
+        // a partially written last line after a power loss
+        Err(e) => {
+            tracing::warn!("Skipping unreadable audit entry: {}", e);
+            return true;
+        }
+    };
+    // entries are appended in time order, everything before is older
+    if query.from_ms.is_some_and(|from| entry.timestamp_ms < from) {
+        return false;
+    }
+    if query.matches(&entry) {
+        entries.push(entry);
+    }
+    query.limit.is_none_or(|limit| entries.len() < limit)
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+/// `None` if `future` doesn't finish within `timeout`
+async fn timeout<T>(future: impl Future<Output = T>, duration: Duration) -> Option<T> {
+    smol::future::or(async { Some(future.await) }, async {
+        smol::Timer::after(duration).await;
+        None
+    })
+    .await
+}
+
+fn mutation_timeout() -> Duration {
+    Duration::from_millis(config().machines.mutation_timeout_ms)
+}
+
+/// Snapshot of the machine state, `None` if the machine doesn't answer
+async fn request_state(app_state: &SharedState, id: &MachineIdentificationUnique) -> Option<Value> {
+    let (sender, receiver) = smol::channel::bounded(1);
+    app_state
+        .message_machine(id, MachineMessage::RequestValues(sender))
+        .await
+        .ok()?;
+    timeout(receiver.recv(), mutation_timeout())
+        .await?
+        .ok()
+        .map(|values| values.state)
+}
+
+/// Where a mutation came from
+#[derive(Debug, Clone)]
+pub struct MutationOrigin<'a> {
+    pub session: &'a Session,
+    pub source: &'static str,
+    pub remote_addr: Option<SocketAddr>,
+}
+
+/// What the machine made of a mutation
+#[derive(Debug, Clone)]
+pub struct MutationOutcome {
+    /// [`AuditResult::Applied`], [`AuditResult::Rejected`] or [`AuditResult::Timeout`]
+    pub result: AuditResult,
+    /// Machine state after the mutation, `None` if the machine doesn't answer
+    pub state: Option<Value>,
+}
+
+/// Sends a mutation to a machine, waits for the outcome and records it in the audit log
+///
+/// The machine handles its messages in order, so state requests before and after the mutation
+/// frame exactly this mutation. Fails if the machine is unknown.
+pub async fn send_audited_mutation(
+    app_state: &SharedState,
+    origin: &MutationOrigin<'_>,
+    id: &MachineIdentificationUnique,
+    mutation: Value,
+) -> anyhow::Result<MutationOutcome> {
+    let state_before = request_state(app_state, id).await;
+    let (reply_sender, reply_receiver) = smol::channel::bounded(1);
+    let sent = app_state
+        .message_machine(
+            id,
+            MachineMessage::HttpApiJsonRequest(mutation.clone(), reply_sender),
+        )
+        .await;
+    if let Err(e) = sent {
+        record(
+            app_state,
+            origin,
+            id,
+            mutation,
+            AuditResult::Error(e.to_string()),
+            state_before,
+            None,
+        );
+        return Err(e);
+    }
+
+    let result = match timeout(reply_receiver.recv(), mutation_timeout()).await {
+        Some(Ok(Ok(()))) => AuditResult::Applied,
+        Some(Ok(Err(e))) => AuditResult::Rejected(e),
+        // the machine was removed before it handled the mutation
+        Some(Err(_)) => AuditResult::Error("Machine disconnected".to_string()),
+        None => AuditResult::Timeout,
+    };
+    let state = match result {
+        AuditResult::Applied | AuditResult::Rejected(_) => request_state(app_state, id).await,
+        _ => None,
+    };
+
+    record(
+        app_state,
+        origin,
+        id,
+        mutation,
+        result.clone(),
+        state_before,
+        state.clone(),
+    );
+    Ok(MutationOutcome { result, state })
+}
+
+/// Records a mutation that was not sent, e.g. because it was denied
+pub fn record_rejected_mutation(
+    app_state: &SharedState,
+    origin: &MutationOrigin<'_>,
+    id: &MachineIdentificationUnique,
+    mutation: Value,
+    result: AuditResult,
+) {
+    record(app_state, origin, id, mutation, result, None, None);
+}
+
+fn record(
+    app_state: &SharedState,
+    origin: &MutationOrigin<'_>,
+    id: &MachineIdentificationUnique,
+    mutation: Value,
+    result: AuditResult,
+    state_before: Option<Value>,
+    state_after: Option<Value>,
+) {
+    let entry = AuditEntry {
+        timestamp_ms: now_ms(),
+        username: origin.session.username.clone(),
+        source: origin.source.to_string(),
+        remote_addr: origin.remote_addr.map(|addr| addr.ip().to_string()),
+        machine_identification_unique: id.clone(),
+        mutation,
+        result,
+        state_before,
+        state_after,
+    };
+    // the mutation is already applied, a failing audit log must not fail the request
+    if let Err(e) = app_state.audit_log.append(&entry) {
+        tracing::error!("Failed to write audit entry {:?}: {:?}", entry, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use machines::machine_identification::MachineIdentification;
+
+    fn entry(machine: u16, timestamp_ms: u128) -> AuditEntry {
+        AuditEntry {
+            timestamp_ms,
+            username: "operator".to_string(),
+            source: "rest_v2".to_string(),
+            remote_addr: Some("10.10.10.20".to_string()),
+            machine_identification_unique: MachineIdentificationUnique {
+                machine_identification: MachineIdentification { vendor: 1, machine },
+                serial: 1,
+            },
+            mutation: serde_json::json!({ "action": "StopAllAxes" }),
+            result: AuditResult::Applied,
+            state_before: None,
+            state_after: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let path = std::env::temp_dir().join(format!("audit-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::new(path.clone());
+
+        for (machine, timestamp_ms) in [(7, 100), (8, 200), (7, 300), (7, 400)] {
+            log.append(&entry(machine, timestamp_ms)).unwrap();
+        }
+
+        let mock = entry(7, 0).machine_identification_unique;
+        let query = AuditQuery {
+            machine_identification_unique: Some(mock),
+            from_ms: Some(150),
+            ..Default::default()
+        };
+        let timestamps: Vec<u128> = log
+            .query(&query)
+            .unwrap()
+            .iter()
+            .map(|e| e.timestamp_ms)
+            .collect();
+        assert_eq!(timestamps, vec![300, 400]);
+
+        let query = AuditQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&query).unwrap()[0].timestamp_ms, 400);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_query_reads_backwards() {
+        let path = std::env::temp_dir().join(format!("audit-test-{}-2.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::new(path.clone());
+
+        // spans several read chunks
+        for timestamp_ms in 0..2000 {
+            log.append(&entry(7, timestamp_ms)).unwrap();
+        }
+        // cut off by a power loss
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"timestamp_ms\":20")
+            .unwrap();
+
+        let timestamps = |query: AuditQuery| -> Vec<u128> {
+            log.query(&query)
+                .unwrap()
+                .iter()
+                .map(|e| e.timestamp_ms)
+                .collect()
+        };
+        assert_eq!(
+            timestamps(AuditQuery {
+                limit: Some(3),
+                ..Default::default()
+            }),
+            vec![1997, 1998, 1999]
+        );
+        assert_eq!(
+            timestamps(AuditQuery {
+                from_ms: Some(10),
+                to_ms: Some(12),
+                ..Default::default()
+            }),
+            vec![10, 11, 12]
+        );
+        assert_eq!(timestamps(AuditQuery::default()).len(), 2000);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_timeout() {
+        smol::block_on(async {
+            let ready = timeout(async { 1 }, Duration::from_millis(100)).await;
+            assert_eq!(ready, Some(1));
+            let pending = timeout(smol::future::pending::<u8>(), Duration::from_millis(10)).await;
+            assert_eq!(pending, None);
+        });
+    }
+}
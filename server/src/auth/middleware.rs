@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use machines::machine_identification::MachineIdentification;
+use serde::Deserialize;
+use serde_json::Value;
+use socketioxide::extract::{SocketRef, TryData};
+
+use super::Role;
+use super::permissions::required_role_for_mutation;
+use super::sessions::Session;
+use crate::app_state::SharedState;
+use crate::rest::response::{ApiError, forbidden, unauthorized};
+
+/// Token of an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Checks that `session` has at least `role`
+pub fn authorize(session: &Session, role: Role) -> Result<(), ApiError> {
+    if session.role >= role {
+        Ok(())
+    } else {
+        Err(forbidden(format!(
+            "Role {} required, {} has role {}",
+            role, session.username, session.role
+        )))
+    }
+}
+
+/// Checks the per-mutation permissions, see [`required_role_for_mutation`]
+pub fn authorize_mutation(
+    session: &Session,
+    machine_identification: &MachineIdentification,
+    mutation: &Value,
+) -> Result<(), ApiError> {
+    authorize(
+        session,
+        required_role_for_mutation(machine_identification, mutation),
+    )
+}
+
+/// Route layer rejecting requests without at least the given role
+///
+/// Use with `axum::middleware::from_fn_with_state((app_state, role), require_role)`. The
+/// [`Session`] is added to the request extensions for handlers with finer grained checks.
+pub async fn require_role(
+    State((app_state, role)): State<(Arc<SharedState>, Role)>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(session) = app_state.auth.authenticate(bearer_token(request.headers())) else {
+        return unauthorized("Login required").into_response();
+    };
+    if let Err(e) = authorize(&session, role) {
+        return e.into_response();
+    }
+    request.extensions_mut().insert(session);
+    next.run(request).await
+}
+
+/// Auth payload of the socket.io handshake, `io(url, { auth: { token } })`
+#[derive(Debug, Deserialize)]
+pub struct SocketAuth {
+    token: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct SocketAuthError;
+
+impl std::fmt::Display for SocketAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unauthorized")
+    }
+}
+
+/// Connect middleware for socket.io namespaces, requires [`Role::Viewer`]
+///
+/// The token is read from the handshake auth payload or from the `Authorization` header.
+pub fn socketio_auth(
+    app_state: Arc<SharedState>,
+) -> impl Fn(SocketRef, TryData<SocketAuth>) -> Result<(), SocketAuthError> + Clone + Send + Sync + 'static
+{
+    move |socket: SocketRef, TryData(auth): TryData<SocketAuth>| {
+        let token = auth.ok().and_then(|auth| auth.token);
+        let headers = &socket.req_parts().headers;
+        let token = token.as_deref().or_else(|| bearer_token(headers));
+
+        match app_state.auth.authenticate(token) {
+            Some(session) if session.role >= Role::Viewer => Ok(()),
+            _ => {
+                tracing::info!(
+                    "Rejected unauthorized socket socket={:?} namespace={}",
+                    socket.id,
+                    socket.ns()
+                );
+                Err(SocketAuthError)
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use self::sessions::{Session, SessionStore};
+use self::users::UserStore;
+use crate::config::config;
+
+pub mod middleware;
+pub mod permissions;
+pub mod sessions;
+pub mod users;
+
+/// Role of a user, every role includes the permissions of the roles before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can see machines, live values and metrics
+    Viewer,
+    /// Can run machines
+    Operator,
+    /// Can change calibration, limits, raw outputs and device configuration
+    Maintenance,
+    /// Can manage users
+    Admin,
+}
+
+impl Role {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Maintenance => "maintenance",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(Self::Viewer),
+            "operator" => Ok(Self::Operator),
+            "maintenance" => Ok(Self::Maintenance),
+            "admin" => Ok(Self::Admin),
+            _ => Err(anyhow::anyhow!("Unknown role {s}")),
+        }
+    }
+}
+
+/// Users and sessions of the API
+///
+/// Requests without a valid token get the anonymous role, viewer by default for read-only access
+/// without login. Set `auth.anonymous_role` to `none` to require a login for everything, or to
+/// `operator` to allow mutations without login on a trusted network.
+pub struct AuthState {
+    pub users: UserStore,
+    pub sessions: SessionStore,
+    pub anonymous_role: Option<Role>,
+}
+
+impl AuthState {
+    pub fn new(users: UserStore, anonymous_role: Option<Role>) -> Self {
+        Self {
+            users,
+            sessions: SessionStore::default(),
+            anonymous_role,
+        }
+    }
+
+    pub fn from_config() -> Self {
+        Self::new(
+            UserStore::load_or_init(users::path()),
+            config().auth.anonymous_role,
+        )
+    }
+
+    /// Resolves a bearer token to its session, falls back to the anonymous role
+    pub fn authenticate(&self, token: Option<&str>) -> Option<Session> {
+        token
+            .and_then(|token| self.sessions.get(token))
+            .or_else(|| self.anonymous_role.map(Session::anonymous))
+    }
+}
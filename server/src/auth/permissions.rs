@@ -0,0 +1,184 @@
+use machines::machine_identification::MachineIdentification;
+use machines::{
+    BBM_AUTOMATIK_V2, MACHINE_EXTRUDER_V1, MACHINE_EXTRUDER_V2, MACHINE_WINDER_V1,
+    SCHNEIDEMASCHINE_V0,
+};
+use serde_json::Value;
+
+use super::Role;
+
+/// Role required for any mutation not listed in [`MAINTENANCE_MUTATIONS`]
+pub const DEFAULT_MUTATION_ROLE: Role = Role::Operator;
+
+/// Mutations reserved for [`Role::Maintenance`], by machine and variant name
+///
+/// Raw outputs, calibration, limits and controller tuning can damage the machine or the
+/// product.
+const MAINTENANCE_MUTATIONS: &[(u16, &[&str])] = &[
+    (
+        BBM_AUTOMATIK_V2,
+        &[
+            "SetOutput",
+            "SetAllOutputs",
+            "SaveTeachPosition",
+            "ClearTeachPosition",
+            "RenameCustomPosition",
+            "SetSoftLimitMax",
+            "SetSoftLimitMin",
+            "TeachSoftLimitMax",
+            "TeachSoftLimitMin",
+        ],
+    ),
+    (
+        SCHNEIDEMASCHINE_V0,
+        &["SetOutput", "SetAllOutputs", "DebugPto", "DebugLogAll"],
+    ),
+    (MACHINE_EXTRUDER_V1, EXTRUDER_MAINTENANCE_MUTATIONS),
+    (MACHINE_EXTRUDER_V2, EXTRUDER_MAINTENANCE_MUTATIONS),
+    (MACHINE_WINDER_V1, &["ZeroTensionArmAngle"]),
+];
+
+const EXTRUDER_MAINTENANCE_MUTATIONS: &[&str] = &[
+    "SetPressurePidSettings",
+    "SetTemperaturePidSettings",
+    "SetExtruderPressureLimit",
+    "SetExtruderPressureLimitIsEnabled",
+];
+
+/// Role required to send `mutation` to a machine
+///
+/// Unknown shapes fall back to [`DEFAULT_MUTATION_ROLE`] and are rejected by the machine
+/// itself.
+pub fn required_role_for_mutation(
+    machine_identification: &MachineIdentification,
+    mutation: &Value,
+) -> Role {
+    let Some(action) = mutation_name(mutation) else {
+        return DEFAULT_MUTATION_ROLE;
+    };
+    let maintenance = MAINTENANCE_MUTATIONS.iter().any(|(machine, actions)| {
+        *machine == machine_identification.machine && actions.contains(&action)
+    });
+    if maintenance {
+        Role::Maintenance
+    } else {
+        DEFAULT_MUTATION_ROLE
+    }
+}
+
+/// Variant name of a serialized mutation enum
+///
+/// - adjacently tagged: `{ "action": "SetOutput", "value": ... }`
+/// - externally tagged: `{ "SetPressurePidSettings": ... }`
+/// - unit variant: `"ZeroTensionArmAngle"`
+fn mutation_name(mutation: &Value) -> Option<&str> {
+    match mutation {
+        Value::String(name) => Some(name),
+        Value::Object(fields) => match fields.get("action") {
+            Some(action) => action.as_str(),
+            None if fields.len() == 1 => fields.keys().next().map(String::as_str),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use machines::VENDOR_QITECH;
+    use serde_json::json;
+
+    fn machine(machine: u16) -> MachineIdentification {
+        MachineIdentification {
+            vendor: VENDOR_QITECH,
+            machine,
+        }
+    }
+
+    #[test]
+    fn test_required_role_for_mutation() {
+        let bbm = machine(BBM_AUTOMATIK_V2);
+        let winder = machine(MACHINE_WINDER_V1);
+
+        let soft_limit =
+            json!({ "action": "SetSoftLimitMax", "value": { "axis": 0, "max_mm": 10.0 } });
+        assert_eq!(
+            required_role_for_mutation(&bbm, &soft_limit),
+            Role::Maintenance
+        );
+        // only listed for the BBM
+        assert_eq!(
+            required_role_for_mutation(&winder, &soft_limit),
+            Role::Operator
+        );
+        assert_eq!(
+            required_role_for_mutation(&bbm, &json!({ "action": "StopAllAxes" })),
+            Role::Operator
+        );
+        assert_eq!(
+            required_role_for_mutation(&bbm, &json!("garbage")),
+            Role::Operator
+        );
+    }
+
+    #[test]
+    fn test_schneidemaschine_mutations() {
+        let schneidemaschine = machine(SCHNEIDEMASCHINE_V0);
+        assert_eq!(
+            required_role_for_mutation(
+                &schneidemaschine,
+                &json!({ "action": "SetOutput", "value": { "index": 0, "on": true } })
+            ),
+            Role::Maintenance
+        );
+        assert_eq!(
+            required_role_for_mutation(&schneidemaschine, &json!({ "action": "StartCutJobs" })),
+            Role::Operator
+        );
+    }
+
+    #[test]
+    fn test_extruder_mutations() {
+        for extruder in [machine(MACHINE_EXTRUDER_V1), machine(MACHINE_EXTRUDER_V2)] {
+            let pid = json!({ "SetPressurePidSettings": { "kp": 1.0, "ki": 0.0, "kd": 0.0 } });
+            assert_eq!(
+                required_role_for_mutation(&extruder, &pid),
+                Role::Maintenance
+            );
+            assert_eq!(
+                required_role_for_mutation(
+                    &extruder,
+                    &json!({ "SetExtruderPressureLimitIsEnabled": false })
+                ),
+                Role::Maintenance
+            );
+            assert_eq!(
+                required_role_for_mutation(&extruder, &json!({ "SetInverterTargetRpm": 10.0 })),
+                Role::Operator
+            );
+        }
+    }
+
+    #[test]
+    fn test_winder_mutations() {
+        let winder = machine(MACHINE_WINDER_V1);
+        assert_eq!(
+            required_role_for_mutation(&winder, &json!("ZeroTensionArmAngle")),
+            Role::Maintenance
+        );
+        assert_eq!(
+            required_role_for_mutation(&winder, &json!({ "SetMode": "Pull" })),
+            Role::Operator
+        );
+        assert_eq!(
+            required_role_for_mutation(&winder, &json!("GotoTraverseHome")),
+            Role::Operator
+        );
+        // not a single variant
+        assert_eq!(
+            required_role_for_mutation(&winder, &json!({ "A": 1, "ZeroTensionArmAngle": 2 })),
+            Role::Operator
+        );
+    }
+}
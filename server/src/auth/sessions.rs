@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::Rng;
+
+use super::Role;
+
+/// Sessions expire after a shift, the UI has to log in again afterwards
+pub const SESSION_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub username: String,
+    pub role: Role,
+    pub expires_at: Option<Instant>,
+}
+
+impl Session {
+    pub const ANONYMOUS_USERNAME: &'static str = "anonymous";
+
+    pub fn anonymous(role: Role) -> Self {
+        Self {
+            username: Self::ANONYMOUS_USERNAME.to_string(),
+            role,
+            expires_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// In-memory login sessions, keyed by their bearer token
+///
+/// Sessions don't survive a restart of the server. Uses a blocking mutex because the socket.io
+/// connect middleware is synchronous, the lock is never held across an await.
+#[derive(Debug, Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    /// Creates a session and returns its token
+    pub fn create(&self, username: &str, role: Role) -> (String, Session) {
+        let token = URL_SAFE_NO_PAD.encode(rand::rng().random::<[u8; 32]>());
+        let session = Session {
+            username: username.to_string(),
+            role,
+            expires_at: Some(Instant::now() + SESSION_TTL),
+        };
+
+        let mut sessions = self.sessions.lock().expect("Session lock poisoned");
+        // drop expired sessions so the map doesn't grow forever
+        let now = Instant::now();
+        sessions.retain(|_, session| !session.is_expired(now));
+        sessions.insert(token.clone(), session.clone());
+        drop(sessions);
+        (token, session)
+    }
+
+    pub fn get(&self, token: &str) -> Option<Session> {
+        self.sessions
+            .lock()
+            .expect("Session lock poisoned")
+            .get(token)
+            .filter(|session| !session.is_expired(Instant::now()))
+            .cloned()
+    }
+
+    pub fn revoke(&self, token: &str) -> bool {
+        let mut sessions = self.sessions.lock().expect("Session lock poisoned");
+        sessions.remove(token).is_some()
+    }
+
+    /// Ends all sessions of a user, e.g. after the user was deleted or its role changed
+    pub fn revoke_user(&self, username: &str) {
+        let mut sessions = self.sessions.lock().expect("Session lock poisoned");
+        sessions.retain(|_, session| session.username != username);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions() {
+        let store = SessionStore::default();
+        let (token, _) = store.create("alice", Role::Operator);
+        let (other, _) = store.create("bob", Role::Viewer);
+        assert_ne!(token, other);
+        assert_eq!(store.get(&token).map(|s| s.role), Some(Role::Operator));
+
+        assert!(store.revoke(&token));
+        assert!(store.get(&token).is_none());
+
+        store.revoke_user("bob");
+        assert!(store.get(&other).is_none());
+        assert!(store.get("invalid").is_none());
+    }
+}
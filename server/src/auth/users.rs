@@ -0,0 +1,302 @@
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail};
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::Role;
+use crate::config::config;
+
+const FILENAME: &str = "users.json";
+
+/// Username of the account created on first start
+pub const INITIAL_ADMIN_USERNAME: &str = "admin";
+
+/// Holds the password of the initial account, next to the user database
+const INITIAL_PASSWORD_FILENAME: &str = "initial-admin-password";
+
+/// Location of the user database, next to the other persisted machine state
+pub fn path() -> PathBuf {
+    let state = &config().state;
+    state.file(state.users_file.as_ref(), FILENAME)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub username: String,
+    pub role: Role,
+    /// Argon2id PHC string, see [`hash_password`]
+    pub password_hash: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsersFile {
+    users: Vec<User>,
+}
+
+/// Public view of a user without the password hash
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct UserInfo {
+    pub username: String,
+    pub role: Role,
+}
+
+/// User accounts, persisted as JSON
+#[derive(Debug)]
+pub struct UserStore {
+    /// `None` keeps the users in memory only
+    path: Option<PathBuf>,
+    users: Mutex<Vec<User>>,
+}
+
+impl UserStore {
+    pub const fn in_memory(users: Vec<User>) -> Self {
+        Self {
+            path: None,
+            users: Mutex::new(users),
+        }
+    }
+
+    /// Loads the users from `path`
+    ///
+    /// If there is no file yet an `admin` account with a random password is created. The
+    /// password is written to `initial-admin-password` next to `path`, readable by the owner
+    /// only, and should be changed right after commissioning.
+    pub fn load_or_init(path: PathBuf) -> Self {
+        let users = match std::fs::read_to_string(&path) {
+            Ok(s) => match serde_json::from_str::<UsersFile>(&s) {
+                Ok(file) => {
+                    tracing::info!("Loaded {} users from {}", file.users.len(), path.display());
+                    file.users
+                }
+                Err(e) => {
+                    // don't overwrite a file we can't parse, nobody can log in until it's fixed
+                    tracing::error!("User file at {} is corrupt: {}", path.display(), e);
+                    return Self {
+                        path: None,
+                        users: Mutex::new(vec![]),
+                    };
+                }
+            },
+            Err(_) => vec![],
+        };
+
+        let password_path = path.with_file_name(INITIAL_PASSWORD_FILENAME);
+        let store = Self {
+            path: Some(path),
+            users: Mutex::new(users),
+        };
+
+        if store.list().is_empty() {
+            let password = URL_SAFE_NO_PAD.encode(rand::rng().random::<[u8; 12]>());
+            // the password file first, an account nobody knows the password of is useless
+            let created = write_initial_password(&password_path, &password)
+                .and_then(|()| store.upsert(INITIAL_ADMIN_USERNAME, &password, Role::Admin));
+            match created {
+                Ok(()) => tracing::warn!(
+                    "Created initial user username={}, the password is in {}, change it after login",
+                    INITIAL_ADMIN_USERNAME,
+                    password_path.display()
+                ),
+                Err(e) => tracing::error!("Failed to create initial user: {:?}", e),
+            }
+        }
+
+        store
+    }
+
+    /// Checks the credentials and returns the role of the user
+    pub fn verify(&self, username: &str, password: &str) -> Option<Role> {
+        // hash outside of the lock
+        let user = self
+            .users
+            .lock()
+            .expect("User lock poisoned")
+            .iter()
+            .find(|user| user.username == username)
+            .cloned()?;
+        verify_password(password, &user.password_hash).then_some(user.role)
+    }
+
+    pub fn list(&self) -> Vec<UserInfo> {
+        self.users
+            .lock()
+            .expect("User lock poisoned")
+            .iter()
+            .map(|user| UserInfo {
+                username: user.username.clone(),
+                role: user.role,
+            })
+            .collect()
+    }
+
+    /// Creates a user or replaces password and role of an existing one
+    pub fn upsert(&self, username: &str, password: &str, role: Role) -> anyhow::Result<()> {
+        if username.is_empty() || username == super::sessions::Session::ANONYMOUS_USERNAME {
+            bail!("Invalid username {username:?}");
+        }
+        if password.len() < 8 {
+            bail!("Password must have at least 8 characters");
+        }
+
+        let user = User {
+            username: username.to_string(),
+            role,
+            password_hash: hash_password(password),
+        };
+        let mut users = self.users.lock().expect("User lock poisoned");
+        let mut updated = users.clone();
+        match updated.iter_mut().find(|u| u.username == username) {
+            Some(existing) => *existing = user,
+            None => updated.push(user),
+        }
+        ensure_admin_left(&updated)?;
+        self.save(&updated)?;
+        *users = updated;
+        drop(users);
+        Ok(())
+    }
+
+    pub fn remove(&self, username: &str) -> anyhow::Result<()> {
+        let mut users = self.users.lock().expect("User lock poisoned");
+        let updated: Vec<User> = users
+            .iter()
+            .filter(|user| user.username != username)
+            .cloned()
+            .collect();
+        if updated.len() == users.len() {
+            bail!("Unknown user {username}");
+        }
+        ensure_admin_left(&updated)?;
+        self.save(&updated)?;
+        *users = updated;
+        drop(users);
+        Ok(())
+    }
+
+    /// Atomically writes the users to disk
+    fn save(&self, users: &[User]) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&UsersFile {
+            users: users.to_vec(),
+        })?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+fn ensure_admin_left(users: &[User]) -> anyhow::Result<()> {
+    if !users.iter().any(|user| user.role == Role::Admin) {
+        bail!("At least one admin is required");
+    }
+    Ok(())
+}
+
+/// Writes the password of the initial account, readable by the server user only
+fn write_initial_password(path: &Path, password: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+    writeln!(file, "{}", password)?;
+    Ok(())
+}
+
+/// Argon2id with the default parameters, as PHC string including salt and parameters
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::encode_b64(&rand::rng().random::<[u8; 16]>())
+        .expect("16 bytes are a valid salt");
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Argon2 with default parameters accepts any password")
+        .to_string()
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    // the parameters are taken from the hash
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_hash() {
+        let hash = hash_password("correct horse");
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "plain"));
+        // salted
+        assert_ne!(hash, hash_password("correct horse"));
+    }
+
+    #[test]
+    fn test_user_store() {
+        let store = UserStore::in_memory(vec![]);
+        // the first user has to be an admin
+        assert!(store.upsert("op", "operator1", Role::Operator).is_err());
+        store.upsert("admin", "administrator", Role::Admin).unwrap();
+        store.upsert("op", "operator1", Role::Operator).unwrap();
+        assert!(store.upsert("short", "1234", Role::Viewer).is_err());
+
+        assert_eq!(store.verify("op", "operator1"), Some(Role::Operator));
+        assert_eq!(store.verify("op", "operator2"), None);
+        assert_eq!(store.verify("nobody", "operator1"), None);
+
+        // the last admin can't be removed or demoted
+        assert!(store.remove("admin").is_err());
+        assert!(
+            store
+                .upsert("admin", "administrator", Role::Viewer)
+                .is_err()
+        );
+        store.remove("op").unwrap();
+        assert_eq!(store.list().len(), 1);
+    }
+
+    #[test]
+    fn test_initial_admin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("users-test-{}", std::process::id()));
+        let store = UserStore::load_or_init(dir.join(FILENAME));
+        let password_path = dir.join(INITIAL_PASSWORD_FILENAME);
+        let password = std::fs::read_to_string(&password_path).unwrap();
+        let mode = std::fs::metadata(&password_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            store.verify(INITIAL_ADMIN_USERNAME, password.trim()),
+            Some(Role::Admin)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+
+use super::toml::parse_value;
+
+/// Shortcuts for common `--set` overrides, `(flag, config key, help)`
+const SHORTCUTS: [(&str, &str, &str); 6] = [
+    ("port", "network.port", "Port of the REST API and socket.io"),
+    ("bind", "network.bind_address", "Address to listen on"),
+    ("web-dir", "network.web_dir", "Directory of the UI bundle"),
+    (
+        "ethercat-interface",
+        "ethercat.interface",
+        "EtherCAT network interface, skips the discovery",
+    ),
+    (
+        "cycle-target-us",
+        "rt.cycle_target_us",
+        "RT loop cycle in µs",
+    ),
+    ("log-filter", "logging.filter", "RUST_LOG style log filter"),
+];
+
+/// Parsed command line
+#[derive(Debug, Clone, Default)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    pub check_config: bool,
+    /// `(config key, value)` in the order they are applied
+    pub overrides: Vec<(String, toml_edit::Value)>,
+}
+
+fn command() -> Command {
+    let command = Command::new("server")
+        .about("QiTech control server")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .short('c')
+                .value_name("PATH")
+                .env("QITECH_CONFIG")
+                .value_parser(value_parser!(PathBuf))
+                .help("TOML config file, defaults to /etc/qitech/server.toml if it exists"),
+        )
+        .arg(
+            Arg::new("check-config")
+                .long("check-config")
+                .action(ArgAction::SetTrue)
+                .help("Validate the configuration, print the effective config and exit"),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .short('s')
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append)
+                .help("Override a config value, e.g. `--set rt.core=3`"),
+        );
+
+    SHORTCUTS
+        .iter()
+        .fold(command, |command, (flag, key, help)| {
+            command.arg(
+                Arg::new(*flag)
+                    .long(*flag)
+                    .value_name("VALUE")
+                    .help(format!("{} (`{}`)", help, key)),
+            )
+        })
+}
+
+impl Cli {
+    pub fn parse() -> Self {
+        let matches = command().get_matches();
+        Self::from_matches(&matches).unwrap_or_else(|e| {
+            eprintln!("error: {:#}", e);
+            std::process::exit(2);
+        })
+    }
+
+    fn from_matches(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let mut overrides = vec![];
+        for set in matches.get_many::<String>("set").into_iter().flatten() {
+            let (key, value) = set
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected `--set KEY=VALUE`, got `{}`", set))?;
+            overrides.push((key.trim().to_string(), parse_value(value.trim())));
+        }
+        // shortcuts win over `--set`
+        for (flag, key, _) in SHORTCUTS {
+            if let Some(value) = matches.get_one::<String>(flag) {
+                overrides.push((key.to_string(), parse_value(value)));
+            }
+        }
+
+        Ok(Self {
+            config: matches.get_one::<PathBuf>("config").cloned(),
+            check_config: matches.get_flag("check-config"),
+            overrides,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli() {
+        let matches = command()
+            .try_get_matches_from([
+                "server",
+                "--config",
+                "server.toml",
+                "--set",
+                "rt.core=3",
+                "--port",
+                "8080",
+                "--ethercat-interface",
+                "enp1s0",
+                "--check-config",
+            ])
+            .unwrap();
+        let cli = Cli::from_matches(&matches).unwrap();
+
+        assert_eq!(cli.config, Some(PathBuf::from("server.toml")));
+        assert!(cli.check_config);
+        let overrides: Vec<(String, String)> = cli
+            .overrides
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect();
+        assert_eq!(
+            overrides,
+            vec![
+                ("rt.core".to_string(), "3".to_string()),
+                ("network.port".to_string(), "8080".to_string()),
+                ("ethercat.interface".to_string(), "\"enp1s0\"".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use toml_edit::Document;
+use tracing_subscriber::EnvFilter;
+
+use crate::auth::Role;
+
+pub mod cli;
+pub mod toml;
+
+/// Used when neither `--config` nor `QITECH_CONFIG` is given and the file exists
+pub const DEFAULT_CONFIG_PATH: &str = "/etc/qitech/server.toml";
+
+/// Configuration of the server
+///
+/// Loaded from a TOML file, then overridden by the environment variables the server used before
+/// the file existed and finally by the command line, see [`load`]. Every field has a default, an
+/// empty file is a valid configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub network: NetworkConfig,
+    pub socketio: SocketioConfig,
+    pub rt: RtConfig,
+    pub ethercat: EthercatConfig,
+    pub logging: LoggingConfig,
+    pub metrics: MetricsConfig,
+    pub state: StateConfig,
+    pub auth: AuthConfig,
+    pub machines: MachinesConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub bind_address: IpAddr,
+    /// REST API, socket.io and the UI bundle
+    pub port: u16,
+    /// Vite build of the UI, served with an SPA fallback to `index.html`
+    pub web_dir: Option<PathBuf>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 3001,
+            web_dir: None,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub const fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SocketioConfig {
+    /// Events waiting per socket before events that can be dropped are dropped
+    ///
+    /// State and live values are coalesced and don't count towards the limit.
+    pub socket_queue_capacity: usize,
+    /// Events waiting per socket before the socket is disconnected
+    ///
+    /// Bounds the queue of a client that doesn't read at all, including the cached events that
+    /// are never dropped. The client reconnects and gets the cache again.
+    pub socket_queue_max_len: usize,
+    /// Wait before retrying a socket whose send buffer is full
+    pub retry_interval_ms: u64,
+    /// Sockets subscribed to deltas get the full state at least this often
+    pub delta_snapshot_interval_ms: u64,
+}
+
+impl Default for SocketioConfig {
+    fn default() -> Self {
+        Self {
+            socket_queue_capacity: 64,
+            socket_queue_max_len: 8192,
+            retry_interval_ms: 10,
+            delta_snapshot_interval_ms: 10_000,
+        }
+    }
+}
+
+impl SocketioConfig {
+    pub const fn retry_interval(&self) -> Duration {
+        Duration::from_millis(self.retry_interval_ms)
+    }
+
+    pub const fn delta_snapshot_interval(&self) -> Duration {
+        Duration::from_millis(self.delta_snapshot_interval_ms)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RtConfig {
+    /// Cycle of the RT loop
+    pub cycle_target_us: u64,
+    /// Pin the RT loop to `core`
+    pub pin_core: bool,
+    pub core: usize,
+    /// `SCHED_FIFO` for the RT loop, always off in development builds
+    pub realtime_priority: bool,
+    /// Run serial and Modbus TCP machines on a separate normal priority thread
+    pub isolate_non_rt_machines: bool,
+    pub non_rt_cycle_target_us: u64,
+}
+
+impl Default for RtConfig {
+    fn default() -> Self {
+        Self {
+            cycle_target_us: 700,
+            pin_core: true,
+            core: 2,
+            realtime_priority: true,
+            isolate_non_rt_machines: false,
+            non_rt_cycle_target_us: 5_000,
+        }
+    }
+}
+
+impl RtConfig {
+    pub const fn cycle_target(&self) -> Duration {
+        Duration::from_micros(self.cycle_target_us)
+    }
+
+    pub const fn non_rt_cycle_target(&self) -> Duration {
+        Duration::from_micros(self.non_rt_cycle_target_us)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EthercatConfig {
+    /// Network interface of the EtherCAT bus, discovered if unset
+    pub interface: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// `RUST_LOG` style filter, e.g. `info,ethercrab=warn`
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Runtime metrics are appended here once per `sample_interval_ms`
+    pub csv_path: PathBuf,
+    pub sample_interval_ms: u64,
+    /// Static bearer token for Prometheus scrapers on `/metrics`
+    pub token: Option<String>,
+    /// Live value fields exported on `/metrics`, all numeric fields if unset
+    pub live_values: Option<Vec<String>>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            csv_path: PathBuf::from("runtime_metrics.csv"),
+            sample_interval_ms: 1000,
+            token: None,
+            live_values: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StateConfig {
+    /// Persisted state like calibrations, users and the audit log
+    pub directory: Option<PathBuf>,
+    pub users_file: Option<PathBuf>,
+    pub audit_file: Option<PathBuf>,
+}
+
+impl StateConfig {
+    /// Configured directory, `/var/lib/qitech` on Linux otherwise
+    pub fn directory(&self) -> PathBuf {
+        match &self.directory {
+            Some(directory) => directory.clone(),
+            None if cfg!(target_os = "linux") => PathBuf::from("/var/lib/qitech"),
+            None => std::env::temp_dir(),
+        }
+    }
+
+    pub fn file(&self, path: Option<&PathBuf>, filename: &str) -> PathBuf {
+        path.cloned()
+            .unwrap_or_else(|| self.directory().join(filename))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Role of requests without login, `"none"` requires a login for everything
+    ///
+    /// Viewer by default, so nobody can mutate a machine without login. `"operator"` has to be
+    /// set explicitly for a UI that doesn't log in.
+    #[serde(with = "anonymous_role")]
+    pub anonymous_role: Option<Role>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            anonymous_role: Some(Role::Viewer),
+        }
+    }
+}
+
+/// `Option<Role>` as a role name or `"none"`, TOML has no null
+mod anonymous_role {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::auth::Role;
+
+    pub fn serialize<S: Serializer>(role: &Option<Role>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(role.map_or("none", |role| role.name()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Role>, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "none" => Ok(None),
+            name => name.parse().map(Some).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MachinesConfig {
+    /// `act` budget of machines in the RT loop
+    pub act_budget_us: u64,
+    /// `act` budget of machines on the non-RT executor
+    pub non_rt_act_budget_us: u64,
+    /// `act` budgets by machine slug, e.g. `bbm_automatik_v2 = 300`
+    pub act_budgets_us: HashMap<String, u64>,
+    /// How long REST and socket.io callers wait for a machine to apply a mutation
+    pub mutation_timeout_ms: u64,
+}
+
+impl Default for MachinesConfig {
+    fn default() -> Self {
+        Self {
+            act_budget_us: 200,
+            non_rt_act_budget_us: 2_000,
+            act_budgets_us: HashMap::new(),
+            mutation_timeout_ms: 1_000,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Checks the values serde can't, reports all problems at once
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+
+        if self.network.port == 0 {
+            errors.push("network.port must not be 0".to_string());
+        }
+        if self.socketio.socket_queue_capacity == 0 {
+            errors.push("socketio.socket_queue_capacity must not be 0".to_string());
+        }
+        if self.socketio.socket_queue_max_len < self.socketio.socket_queue_capacity {
+            errors.push(
+                "socketio.socket_queue_max_len must not be below socket_queue_capacity".to_string(),
+            );
+        }
+        if self.socketio.retry_interval_ms == 0 {
+            errors.push("socketio.retry_interval_ms must not be 0".to_string());
+        }
+        if self.socketio.delta_snapshot_interval_ms == 0 {
+            errors.push("socketio.delta_snapshot_interval_ms must not be 0".to_string());
+        }
+        if !(50..=100_000).contains(&self.rt.cycle_target_us) {
+            errors.push(format!(
+                "rt.cycle_target_us must be between 50 and 100000, is {}",
+                self.rt.cycle_target_us
+            ));
+        }
+        if !(1_000..=1_000_000).contains(&self.rt.non_rt_cycle_target_us) {
+            errors.push(format!(
+                "rt.non_rt_cycle_target_us must be between 1000 and 1000000, is {}",
+                self.rt.non_rt_cycle_target_us
+            ));
+        }
+        if self
+            .ethercat
+            .interface
+            .as_ref()
+            .is_some_and(String::is_empty)
+        {
+            errors.push("ethercat.interface must not be empty".to_string());
+        }
+        if let Some(filter) = &self.logging.filter {
+            if let Err(e) = EnvFilter::try_new(filter) {
+                errors.push(format!("logging.filter is invalid: {}", e));
+            }
+        }
+        if self.metrics.sample_interval_ms == 0 {
+            errors.push("metrics.sample_interval_ms must not be 0".to_string());
+        }
+        if self.metrics.token.as_ref().is_some_and(String::is_empty) {
+            errors.push("metrics.token must not be empty".to_string());
+        }
+        let budgets = std::iter::once(("act_budget_us", self.machines.act_budget_us))
+            .chain(std::iter::once((
+                "non_rt_act_budget_us",
+                self.machines.non_rt_act_budget_us,
+            )))
+            .chain(
+                self.machines
+                    .act_budgets_us
+                    .iter()
+                    .map(|(slug, us)| (slug.as_str(), *us)),
+            );
+        for (name, us) in budgets {
+            if us == 0 {
+                errors.push(format!("machines budget {} must not be 0", name));
+            }
+        }
+        if self.machines.mutation_timeout_ms == 0 {
+            errors.push("machines.mutation_timeout_ms must not be 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            bail!("Invalid configuration:\n  {}", errors.join("\n  "))
+        }
+    }
+
+    /// Problems the server starts with anyway, e.g. on a development machine
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        if let Some(web_dir) = &self.network.web_dir {
+            if !web_dir.join("index.html").is_file() {
+                warnings.push(format!(
+                    "network.web_dir {} has no index.html, the UI is not served",
+                    web_dir.display()
+                ));
+            }
+        }
+        if self.rt.pin_core {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            if self.rt.core >= cores {
+                warnings.push(format!(
+                    "rt.core {} does not exist, this system has {} cores",
+                    self.rt.core, cores
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+/// Where the configuration came from, for `--check-config` and the startup log
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigSources {
+    pub file: Option<PathBuf>,
+    pub env: Vec<&'static str>,
+    pub overrides: Vec<String>,
+}
+
+/// Reads the config file, applies the environment and the command line overrides and validates
+/// the result
+///
+/// `path` is only optional for the default location, an explicitly given file has to exist.
+pub fn load(
+    path: Option<&Path>,
+    overrides: &[(String, toml_edit::Value)],
+) -> anyhow::Result<(ServerConfig, ConfigSources)> {
+    let mut sources = ConfigSources::default();
+    let mut document = match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            sources.file = Some(path.to_path_buf());
+            text.parse::<Document>()
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        }
+        None if Path::new(DEFAULT_CONFIG_PATH).is_file() => {
+            return load(Some(Path::new(DEFAULT_CONFIG_PATH)), overrides);
+        }
+        None => Document::new(),
+    };
+
+    for (key, name, value) in env_overrides() {
+        if !toml::contains(&document, key) {
+            toml::set(&mut document, key, value)?;
+            sources.env.push(name);
+        }
+    }
+    for (key, value) in overrides {
+        toml::set(&mut document, key, value.clone())?;
+        sources.overrides.push(key.clone());
+    }
+
+    let config: ServerConfig = toml_edit::de::from_document(document).map_err(|e| {
+        anyhow!(
+            "Invalid configuration: {}",
+            e.to_string().trim().replace('\n', " ")
+        )
+    })?;
+    config.validate()?;
+    Ok((config, sources))
+}
+
+/// Environment variables predating the config file, used for the keys the file doesn't set
+fn env_overrides() -> Vec<(&'static str, &'static str, toml_edit::Value)> {
+    const STRINGS: [(&str, &str); 7] = [
+        ("network.web_dir", "QITECH_WEB_DIR"),
+        ("logging.filter", "RUST_LOG"),
+        ("state.directory", "STATE_DIRECTORY"),
+        ("state.users_file", "QITECH_USERS_FILE"),
+        ("state.audit_file", "QITECH_AUDIT_FILE"),
+        ("metrics.token", "QITECH_METRICS_TOKEN"),
+        ("auth.anonymous_role", "QITECH_AUTH_ANONYMOUS_ROLE"),
+    ];
+
+    let mut overrides = vec![];
+    for (key, name) in STRINGS {
+        if let Ok(value) = std::env::var(name) {
+            overrides.push((key, name, value.as_str().into()));
+        }
+    }
+    if let Ok(value) = std::env::var("QITECH_METRICS_LIVE_VALUES") {
+        let fields: toml_edit::Array = value
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect();
+        overrides.push((
+            "metrics.live_values",
+            "QITECH_METRICS_LIVE_VALUES",
+            fields.into(),
+        ));
+    }
+    overrides
+}
+
+/// Output of `--check-config`: the sources, warnings and the effective config as TOML
+pub fn check_report(config: &ServerConfig, sources: &ConfigSources) -> anyhow::Result<String> {
+    let mut report = String::from("# Configuration is valid\n");
+    match &sources.file {
+        Some(file) => report.push_str(&format!("# file: {}\n", file.display())),
+        None => report.push_str("# file: none, using defaults\n"),
+    }
+    if !sources.env.is_empty() {
+        report.push_str(&format!("# environment: {}\n", sources.env.join(", ")));
+    }
+    if !sources.overrides.is_empty() {
+        report.push_str(&format!(
+            "# command line: {}\n",
+            sources.overrides.join(", ")
+        ));
+    }
+    for warning in config.warnings() {
+        report.push_str(&format!("# warning: {}\n", warning));
+    }
+
+    let mut printed = config.clone();
+    if printed.metrics.token.is_some() {
+        printed.metrics.token = Some("<hidden>".to_string());
+    }
+    report.push('\n');
+    report.push_str(&toml_edit::ser::to_string_pretty(&printed)?);
+    Ok(report)
+}
+
+static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
+
+/// Sets the config returned by [`config`], only the first call has an effect
+pub fn init(config: ServerConfig) {
+    if CONFIG.set(config).is_err() {
+        tracing::warn!("Config already initialized");
+    }
+}
+
+/// Config of the running server, the defaults before [`init`] (e.g. in tests)
+pub fn config() -> &'static ServerConfig {
+    CONFIG.get_or_init(ServerConfig::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn load_str(text: &str, overrides: &[(&str, &str)]) -> anyhow::Result<ServerConfig> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "server-config-test-{}-{}.toml",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, text)?;
+        let overrides: Vec<_> = overrides
+            .iter()
+            .map(|(key, value)| (key.to_string(), toml::parse_value(value)))
+            .collect();
+        let result = load(Some(&path), &overrides).map(|(config, _)| config);
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn test_load_config() {
+        let config = load_str(
+            r#"
+            [network]
+            port = 8080
+
+            [rt]
+            cycle_target_us = 500
+            pin_core = false
+
+            [machines]
+            act_budgets_us = { bbm_automatik_v2 = 300 }
+            "#,
+            &[
+                ("network.port", "9090"),
+                ("ethercat.interface", "enp1s0"),
+                ("machines.act_budgets_us.laser_v1", "5000"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.network.port, 9090);
+        assert_eq!(config.rt.cycle_target(), Duration::from_micros(500));
+        assert_eq!(config.ethercat.interface.as_deref(), Some("enp1s0"));
+        assert_eq!(config.machines.act_budgets_us["bbm_automatik_v2"], 300);
+        assert_eq!(config.machines.act_budgets_us["laser_v1"], 5000);
+        assert_eq!(config.metrics, MetricsConfig::default());
+        assert_eq!(config.auth.anonymous_role, Some(Role::Viewer));
+
+        // round trip of the effective config
+        let printed = toml_edit::ser::to_string_pretty(&config).unwrap();
+        let reloaded: ServerConfig = toml_edit::de::from_str(&printed).unwrap();
+        assert_eq!(reloaded, config);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(load_str("[network]\nprot = 3001\n", &[]).is_err());
+        assert!(load_str("[rt]\ncycle_target_us = 10\n", &[]).is_err());
+        assert!(load_str("[auth]\nanonymous_role = \"guest\"\n", &[]).is_err());
+        assert_eq!(
+            load_str("[auth]\nanonymous_role = \"operator\"\n", &[])
+                .unwrap()
+                .auth
+                .anonymous_role,
+            Some(Role::Operator)
+        );
+        assert_eq!(
+            load_str("[auth]\nanonymous_role = \"none\"\n", &[])
+                .unwrap()
+                .auth
+                .anonymous_role,
+            None
+        );
+        assert!(load_str("", &[("network", "1")]).is_err());
+    }
+
+    #[test]
+    fn test_env_only_fills_unset_keys() {
+        let document: Document = "[state]\ndirectory = \"/srv/qitech\"\n".parse().unwrap();
+        assert!(toml::contains(&document, "state.directory"));
+        assert!(!toml::contains(&document, "state.users_file"));
+        assert!(!toml::contains(&document, "metrics.token"));
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{anyhow, bail};
+use toml_edit::{Document, Item, Value};
+
+/// Parses the value of a `--set key=value` override
+///
+/// Anything that isn't valid TOML is taken as a string, so paths and interface names don't need
+/// quotes.
+pub fn parse_value(raw: &str) -> Value {
+    raw.parse::<Value>()
+        .map(|value| value.decorated("", ""))
+        .unwrap_or_else(|_| raw.into())
+}
+
+/// Whether a dotted `path`, e.g. `network.port`, is set in the document
+pub fn contains(document: &Document, path: &str) -> bool {
+    let mut item = document.as_item();
+    for key in path.split('.').map(str::trim) {
+        match item.get(key) {
+            Some(next) => item = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Sets a dotted `path`, e.g. `network.port`, creating missing tables
+pub fn set(document: &mut Document, path: &str, value: Value) -> anyhow::Result<()> {
+    let mut keys: Vec<&str> = path.split('.').map(str::trim).collect();
+    if keys.iter().any(|key| key.is_empty()) {
+        bail!("Invalid config key `{}`", path);
+    }
+    let last = keys.pop().expect("split yields at least one key");
+
+    let mut table = document.as_table_mut();
+    for key in keys {
+        let item = table.entry(key).or_insert_with(toml_edit::table);
+        if item.is_inline_table() {
+            // `a = { b = 1 }` in the file and `--set a.c=2`
+            *item = std::mem::take(item)
+                .into_table()
+                .map_or(Item::None, Item::Table);
+        }
+        table = item
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("`{}` in `{}` is not a table", key, path))?;
+    }
+    table.insert(last, Item::Value(value));
+    Ok(())
+}
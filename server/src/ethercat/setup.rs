@@ -11,13 +11,14 @@ use control_core::socketio::namespace::NamespaceCacheingLogic;
 #[cfg(all(target_os = "linux", not(feature = "development-build")))]
 use control_core::{irq_handling::set_irq_affinity, realtime::set_realtime_priority};
 use ethercat_hal::debugging::diagnosis_history::get_most_recent_diagnosis_message;
-use ethercat_hal::devices::devices_from_subdevices;
+use ethercat_hal::devices::generic::GenericDevice;
 use ethercat_hal::devices::wago_750_354::{
     WAGO_750_354_PRODUCT_ID, WAGO_750_354_VENDOR_ID, Wago750_354,
 };
 use ethercat_hal::devices::wago_modules::ip20_ec_di8_do8::{
     IP20_EC_DI8_DO8_PRODUCT_ID, IP20_EC_DI8_DO8_VENDOR_ID, IP20EcDi8Do8,
 };
+use ethercat_hal::devices::{EthercatDeviceUsed, devices_from_subdevices, downcast_device};
 
 use crate::utils::{start_dnsmasq, stop_dnsmasq};
 use ethercrab::std::ethercat_now;
@@ -29,7 +30,6 @@ use machines::machine_identification::{
 use machines::registry::{MACHINE_REGISTRY, MachineRegistry};
 use machines::{Machine, MachineNewHardware, MachineNewHardwareEthercat, MachineNewParams};
 use smol::channel::Sender;
-use socketioxide::extract::SocketRef;
 use std::{sync::Arc, time::Duration};
 
 /// Structure to hold the result of grouping devices by identification
@@ -112,7 +112,7 @@ pub async fn set_ethercat_devices<const MAX_SUBDEVICES: usize, const MAX_PDI: us
     machine_registry: &MachineRegistry,
     hardware: &MachineNewHardwareEthercat<'_, '_, '_>,
     shared_state: Arc<SharedState>,
-    socket_queue_tx: Sender<(SocketRef, Arc<control_core::socketio::event::GenericEvent>)>,
+    socket_queue_tx: Sender<control_core::socketio::namespace::SocketQueueItem>,
 ) -> Result<(), anyhow::Error> {
     let device_grouping_result = group_devices_by_identification(device_identifications);
     let machine_new_hardware = MachineNewHardware::Ethercat(hardware);
@@ -295,6 +295,28 @@ pub async fn setup_loop(
         devices_from_subdevices::<MAX_SUBDEVICES, PDI_LEN>(&mut group_preop, &maindevice)?;
     let subdevices = group_preop.iter(&maindevice).collect::<Vec<_>>();
 
+    // read the PDO mapping of subdevices without a dedicated driver while still in PRE-OP
+    for (device, subdevice) in devices.iter().zip(&subdevices) {
+        let Ok(generic) = downcast_device::<GenericDevice>(device.clone()).await else {
+            continue;
+        };
+        let res = generic
+            .write()
+            .await
+            .read_pdo_mapping(subdevice, &maindevice)
+            .await;
+        match res {
+            // copy the process image every cycle, even if no machine uses the device
+            Ok(_) => generic.write().await.set_used(true),
+            Err(e) => tracing::warn!(
+                "[{}::setup_loop] Failed to read PDO mapping of {}: {:?}",
+                module_path!(),
+                subdevice.name(),
+                e
+            ),
+        }
+    }
+
     // extract device identifications
     let device_identifications = read_device_identifications(&subdevices, &maindevice)
         .await
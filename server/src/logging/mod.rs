@@ -8,14 +8,15 @@ pub mod journald;
 
 /// Initialize the basic tracing system (without OpenTelemetry if enabled)
 /// OpenTelemetry layer is deferred until async runtime is available
-pub fn init_tracing() {
-    // First try to get filter from env, then use default
-    // Use RUST_LOG env var to control logging, e.g.:
+pub fn init_tracing(filter: Option<&str>) {
+    // First try the configured filter (`logging.filter` or RUST_LOG), then use default, e.g.:
     // RUST_LOG=info,h2=error,tower=error,tonic=error,hyper=error,opentelemetry_otlp=error
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-        // Set very strict filters for the noisy OpenTelemetry components
-        EnvFilter::new(
-            "info,\
+    let env_filter = filter
+        .and_then(|filter| EnvFilter::try_new(filter).ok())
+        .unwrap_or_else(|| {
+            // Set very strict filters for the noisy OpenTelemetry components
+            EnvFilter::new(
+                "info,\
              tower_http=debug,\
              axum=debug,\
              ethercrab=info,\
@@ -24,8 +25,8 @@ pub fn init_tracing() {
              tonic=error,\
              hyper=error,\
              opentelemetry_otlp=error",
-        )
-    });
+            )
+        });
 
     let subscriber = tracing_subscriber::registry().with(env_filter);
 
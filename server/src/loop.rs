@@ -1,21 +1,34 @@
 use crate::app_state::{EthercatSetup, HotThreadMessage};
+use crate::config::config;
 use crate::performance_metrics::EthercatPerformanceMetrics;
 use bitvec::prelude::*;
 use control_core::realtime::set_core_affinity;
 #[cfg(not(feature = "development-build"))]
 use control_core::realtime::set_realtime_priority;
+use ethercat_hal::sdo::{SdoRequest, SdoResponse};
 use machines::Machine;
-use machines::machine_identification::write_machine_device_identification;
-use smol::channel::Receiver;
+use machines::machine_identification::{
+    MachineIdentificationUnique, write_machine_device_identification,
+};
+use serde::{Deserialize, Serialize};
+use smol::channel::{Receiver, Sender};
 use spin_sleep::SpinSleeper;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 
 use crate::metrics::jitter::record_machines_loop_jitter;
 use crate::metrics::preemption::set_rt_loop_tid;
+use crate::metrics::rt_loop::{MachineActMetrics, rt_loop_metrics};
+use crate::streaming::StreamSampler;
 pub struct RtLoopInputs<'a> {
     pub machines: &'a mut Vec<Box<dyn Machine>>,
-    pub ethercat_setup: Option<Box<EthercatSetup>>,
+    /// `act` timing of each machine, same order as `machines`
+    pub machine_act_metrics: Vec<Arc<MachineActMetrics>>,
+    /// Signal streams of the machines in this loop
+    pub stream_sampler: StreamSampler,
+    pub ethercat_setup: Option<Arc<EthercatSetup>>,
     pub ethercat_perf_metrics: Option<&'a mut EthercatPerformanceMetrics>,
     pub sleeper: SpinSleeper,
     pub cycle_target: Duration,
@@ -30,6 +43,26 @@ pub struct RtLoopInputs<'a> {
     pub healthy_working_counter: Option<u16>,
 }
 
+/// Where a machine's `act` is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MachineExecutor {
+    /// The RT loop, together with the EtherCAT tx/rx
+    RealTime,
+    /// A normal priority thread for machines behind serial or Modbus TCP, see
+    /// [`start_non_rt_loop_thread`]
+    NonRealTime,
+}
+
+impl MachineExecutor {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::RealTime => "real_time",
+            Self::NonRealTime => "non_real_time",
+        }
+    }
+}
+
 /// After this many consecutive degraded cycles the loop errors out and the
 /// process exits (systemd restarts it cleanly — same pattern as the
 /// EtherCAT init timeout). At a 300 µs cycle target this is ~30 ms, well
@@ -52,7 +85,10 @@ pub fn start_loop_thread(
                 SpinSleeper::new(3_333_333) // frequency in Hz ~ 1 / 300µs, Basically specifies the accuracy of our sleep
                     .with_spin_strategy(spin_sleep::SpinStrategy::YieldThread);
 
-            let _ = set_core_affinity(2);
+            let rt_config = &config().rt;
+            if rt_config.pin_core {
+                let _ = set_core_affinity(rt_config.core);
+            }
 
             // Get thread ID in a platform-specific way
             #[cfg(target_os = "linux")]
@@ -71,7 +107,12 @@ pub fn start_loop_thread(
             set_rt_loop_tid(tid);
 
             #[cfg(not(feature = "development-build"))]
-            if let Err(e) = set_realtime_priority() {
+            if !rt_config.realtime_priority {
+                tracing::warn!(
+                    "[{}::init_loop] Real-time priority disabled by configuration",
+                    module_path!()
+                );
+            } else if let Err(e) = set_realtime_priority() {
                 tracing::error!(
                     "[{}::init_loop] Failed to set thread to real-time priority \n{:?}",
                     module_path!(),
@@ -84,11 +125,21 @@ pub fn start_loop_thread(
                 );
             }
 
+            let sdo_sender = match start_sdo_thread() {
+                Ok(sender) => sender,
+                Err(e) => {
+                    tracing::error!("Failed to start SDO thread {:?}", e);
+                    std::process::exit(1);
+                }
+            };
+
             let mut ethercat_perf = EthercatPerformanceMetrics::new();
             let mut machines: Vec<Box<dyn Machine>> = vec![];
             let mut last_iter_start: Option<Instant> = None;
             let mut rt_loop_inputs = RtLoopInputs {
                 machines: &mut machines,
+                machine_act_metrics: vec![],
+                stream_sampler: StreamSampler::new(),
                 ethercat_setup: None,
                 sleeper,
                 cycle_target,
@@ -107,7 +158,7 @@ pub fn start_loop_thread(
                     HotThreadMessage::NoMsg => {}
                     HotThreadMessage::AddEtherCatSetup(ethercat_setup) => {
                         println!("EthercatSetup: {:?}", ethercat_setup.devices);
-                        rt_loop_inputs.ethercat_setup = Some(Box::new(ethercat_setup));
+                        rt_loop_inputs.ethercat_setup = Some(Arc::new(ethercat_setup));
                         // Fresh bus -> fresh health baseline
                         rt_loop_inputs.degraded_cycles = 0;
                         rt_loop_inputs.healthy_working_counter = None;
@@ -129,9 +180,11 @@ pub fn start_loop_thread(
                         }
                     }
                     HotThreadMessage::DeleteMachine(unique_id) => {
-                        rt_loop_inputs
-                            .machines
-                            .retain(|m| m.get_machine_identification_unique() != unique_id);
+                        delete_machine(
+                            rt_loop_inputs.machines,
+                            &mut rt_loop_inputs.machine_act_metrics,
+                            &unique_id,
+                        );
                     }
                     HotThreadMessage::SdoWriteU16 {
                         subdevice_index,
@@ -149,18 +202,37 @@ pub fn start_loop_thread(
                             }
                         }
                     }
-                    HotThreadMessage::AddMachines(machine_vec) => {
-                        tracing::info!("received machines{:?}", machine_vec);
-                        for new_machine in machine_vec {
-                            let id = new_machine.get_machine_identification_unique();
-                            if !rt_loop_inputs
-                                .machines
-                                .iter()
-                                .any(|m| m.get_machine_identification_unique() == id)
-                            {
-                                rt_loop_inputs.machines.push(new_machine);
+                    HotThreadMessage::Sdo {
+                        subdevice_index,
+                        request,
+                        reply,
+                    } => match &rt_loop_inputs.ethercat_setup {
+                        Some(ethercat_setup) => {
+                            let job = SdoJob {
+                                ethercat_setup: ethercat_setup.clone(),
+                                subdevice_index,
+                                request,
+                                reply,
+                            };
+                            if let Err(e) = sdo_sender.try_send(job) {
+                                let _ = e
+                                    .into_inner()
+                                    .reply
+                                    .try_send(Err("SDO queue is full".to_string()));
                             }
                         }
+                        None => {
+                            let _ = reply.try_send(Err("No EtherCAT setup".to_string()));
+                        }
+                    },
+                    HotThreadMessage::AddMachines(machine_vec) => {
+                        tracing::info!("received machines{:?}", machine_vec);
+                        add_machines(
+                            rt_loop_inputs.machines,
+                            &mut rt_loop_inputs.machine_act_metrics,
+                            machine_vec,
+                            MachineExecutor::RealTime,
+                        );
                     }
                 }
                 let iter_start = Instant::now();
@@ -169,6 +241,7 @@ pub fn start_loop_thread(
                         let jitter_ns = period.as_nanos() as i128
                             - rt_loop_inputs.cycle_target.as_nanos() as i128;
                         record_machines_loop_jitter(jitter_ns);
+                        rt_loop_metrics().cycle_time.record(period);
                     }
                 }
                 last_iter_start = Some(iter_start);
@@ -195,6 +268,115 @@ pub fn start_loop_thread(
     return res;
 }
 
+/// Requests waiting for the SDO thread, further requests are rejected
+const SDO_QUEUE_LEN: usize = 16;
+
+/// SDO browser request handed from the RT loop to the SDO thread
+struct SdoJob {
+    ethercat_setup: Arc<EthercatSetup>,
+    subdevice_index: usize,
+    request: SdoRequest,
+    reply: Sender<Result<SdoResponse, String>>,
+}
+
+/// Executes SDO browser requests next to the RT loop
+///
+/// A mailbox transfer spans several cycles, so the loop only queues the request here and keeps
+/// exchanging process data. The frames go through the same TX/RX thread as the process data.
+fn start_sdo_thread() -> Result<Sender<SdoJob>, std::io::Error> {
+    let (sender, receiver) = smol::channel::bounded::<SdoJob>(SDO_QUEUE_LEN);
+    std::thread::Builder::new()
+        .name("sdo".to_owned())
+        .spawn(move || {
+            while let Ok(job) = receiver.recv_blocking() {
+                let res = job
+                    .ethercat_setup
+                    .group
+                    .subdevice(&job.ethercat_setup.maindevice, job.subdevice_index)
+                    .map_err(|e| format!("{:?}", e))
+                    .and_then(|subdevice| {
+                        smol::block_on(job.request.execute(&subdevice, true))
+                            .map_err(|e| format!("{:?}", e))
+                    });
+                let _ = job.reply.try_send(res);
+            }
+        })?;
+    Ok(sender)
+}
+
+/// Adds machines that are not executed yet, `machine_act_metrics` stays parallel to `machines`
+fn add_machines(
+    machines: &mut Vec<Box<dyn Machine>>,
+    machine_act_metrics: &mut Vec<Arc<MachineActMetrics>>,
+    new_machines: Vec<Box<dyn Machine>>,
+    executor: MachineExecutor,
+) {
+    for new_machine in new_machines {
+        let id = new_machine.get_machine_identification_unique();
+        if !machines
+            .iter()
+            .any(|m| m.get_machine_identification_unique() == id)
+        {
+            machine_act_metrics.push(rt_loop_metrics().register_machine(id, executor));
+            machines.push(new_machine);
+        }
+    }
+}
+
+fn delete_machine(
+    machines: &mut Vec<Box<dyn Machine>>,
+    machine_act_metrics: &mut Vec<Arc<MachineActMetrics>>,
+    id: &MachineIdentificationUnique,
+) {
+    machines.retain(|m| m.get_machine_identification_unique() != *id);
+    machine_act_metrics.retain(|m| m.machine_identification_unique != *id);
+    rt_loop_metrics().unregister_machine(id);
+}
+
+/// Executes machines without real-time requirements, e.g. behind serial or Modbus TCP
+///
+/// A blocking serial read or a slow network there can't delay the EtherCAT cycle. The thread
+/// keeps the normal scheduling priority and is not pinned to the RT core. Only
+/// [`HotThreadMessage::AddMachines`] and [`HotThreadMessage::DeleteMachine`] are handled.
+pub fn start_non_rt_loop_thread(
+    receiver: Receiver<HotThreadMessage>,
+    cycle_target: Duration,
+) -> Result<std::thread::JoinHandle<()>, std::io::Error> {
+    std::thread::Builder::new()
+        .name("loop-non-rt".to_owned())
+        .spawn(move || {
+            let mut machines: Vec<Box<dyn Machine>> = vec![];
+            let mut machine_act_metrics: Vec<Arc<MachineActMetrics>> = vec![];
+            let mut stream_sampler = StreamSampler::new();
+            loop {
+                while let Ok(msg) = receiver.try_recv() {
+                    match msg {
+                        HotThreadMessage::AddMachines(machine_vec) => {
+                            tracing::info!("received non-RT machines{:?}", machine_vec);
+                            add_machines(
+                                &mut machines,
+                                &mut machine_act_metrics,
+                                machine_vec,
+                                MachineExecutor::NonRealTime,
+                            );
+                        }
+                        HotThreadMessage::DeleteMachine(unique_id) => {
+                            delete_machine(&mut machines, &mut machine_act_metrics, &unique_id);
+                        }
+                        HotThreadMessage::NoMsg => {}
+                        _ => tracing::warn!("Non-RT loop ignores EtherCAT messages"),
+                    }
+                }
+
+                let cycle_start = Instant::now();
+                execute_machines(&mut machines, &machine_act_metrics, &mut stream_sampler);
+                if let Some(remaining) = cycle_target.checked_sub(cycle_start.elapsed()) {
+                    std::thread::sleep(remaining);
+                }
+            }
+        })
+}
+
 /// Bus health snapshot of one tx/rx cycle, evaluated in `loop_once`.
 pub struct BusHealth {
     pub all_op: bool,
@@ -209,10 +391,12 @@ pub async fn copy_ethercat_inputs(
     // - copy inputs to devices
     let mut bus_health = None;
     if let Some(ethercat_setup) = ethercat_setup {
+        let txrx_start = Instant::now();
         let response = ethercat_setup
             .group
             .tx_rx(&ethercat_setup.maindevice)
             .await?;
+        rt_loop_metrics().txrx_time.record(txrx_start.elapsed());
         bus_health = Some(BusHealth {
             all_op: response.all_op(),
             working_counter: response.working_counter,
@@ -308,11 +492,18 @@ pub async fn copy_ethercat_outputs(
     Ok(())
 }
 
-pub fn execute_machines(machines: &mut Vec<Box<dyn Machine>>) {
+pub fn execute_machines(
+    machines: &mut Vec<Box<dyn Machine>>,
+    machine_act_metrics: &[Arc<MachineActMetrics>],
+    stream_sampler: &mut StreamSampler,
+) {
     let now = Instant::now();
-    for machine in machines.iter_mut() {
+    for (machine, metrics) in machines.iter_mut().zip(machine_act_metrics) {
+        let act_start = Instant::now();
         machine.act(now);
+        metrics.record_act(act_start.elapsed());
     }
+    stream_sampler.sample(machines, now);
 }
 // No more logging in loop_once
 pub fn loop_once<'maindevice>(inputs: &mut RtLoopInputs<'_>) -> Result<(), anyhow::Error> {
@@ -342,10 +533,18 @@ pub fn loop_once<'maindevice>(inputs: &mut RtLoopInputs<'_>) -> Result<(), anyho
                     if health.working_counter > baseline {
                         inputs.healthy_working_counter = Some(health.working_counter);
                     }
+                    let metrics = rt_loop_metrics();
+                    metrics.set_working_counter(
+                        health.working_counter,
+                        inputs.healthy_working_counter.unwrap_or(baseline),
+                    );
                     let healthy = health.all_op && health.working_counter >= baseline;
                     if healthy {
                         inputs.degraded_cycles = 0;
                     } else {
+                        metrics
+                            .degraded_cycles_total
+                            .fetch_add(1, Ordering::Relaxed);
                         inputs.degraded_cycles += 1;
                         if inputs.degraded_cycles >= MAX_DEGRADED_CYCLES {
                             return Err(anyhow::anyhow!(
@@ -365,7 +564,11 @@ pub fn loop_once<'maindevice>(inputs: &mut RtLoopInputs<'_>) -> Result<(), anyho
         };
     }
 
-    execute_machines(&mut inputs.machines);
+    execute_machines(
+        inputs.machines,
+        &inputs.machine_act_metrics,
+        &mut inputs.stream_sampler,
+    );
 
     if inputs.ethercat_setup.is_some() && inputs.ethercat_perf_metrics.is_some() {
         let res = smol::block_on(copy_ethercat_outputs(inputs.ethercat_setup.as_deref()));
@@ -377,6 +580,10 @@ pub fn loop_once<'maindevice>(inputs: &mut RtLoopInputs<'_>) -> Result<(), anyho
         };
     }
 
+    let metrics = rt_loop_metrics();
+    metrics.busy_time.record(loop_once_start.elapsed());
+    metrics.cycles_total.fetch_add(1, Ordering::Relaxed);
+
     if inputs.ethercat_setup.is_some() {
         // spin_sleep so we have a cycle time of ~300us
         // This does push usage to 100% if completely busy, but provides much better accuracy then thread sleep or async sleep
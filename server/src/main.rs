@@ -1,4 +1,5 @@
 use crate::{
+    metrics::act_budget::spawn_act_budget_supervisor,
     metrics::collector::{RuntimeMetricsConfig, spawn_runtime_metrics_sampler},
     socketio::main_namespace::machines_event::MachineObj,
 };
@@ -12,15 +13,16 @@ use machines::{
     },
     registry::{MACHINE_REGISTRY, MachineRegistry},
     serial::{devices::laser::Laser, init::SerialDetection},
-    winder2::api::GenericEvent,
 };
 #[cfg(feature = "development-build")]
 use std::sync::atomic::{AtomicBool, Ordering};
 use utils::start_dnsmasq;
 
 use app_state::{HotThreadMessage, SharedState};
+use config::cli::Cli;
+use control_core::socketio::namespace::SocketQueueItem;
 use ethercat::ethercat_discovery_info::send_ethercat_discovering;
-use r#loop::start_loop_thread;
+use r#loop::{MachineExecutor, start_loop_thread, start_non_rt_loop_thread};
 use metrics::io::set_ethercat_iface;
 use panic::init_panic_handling;
 use rest::init::start_api_thread;
@@ -30,7 +32,6 @@ use smol::{
     future,
     lock::RwLock,
 };
-use socketioxide::extract::SocketRef;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 #[cfg(feature = "mock-machine")]
@@ -49,6 +50,9 @@ use crate::{
 pub mod mock_init;
 
 pub mod app_state;
+pub mod audit;
+pub mod auth;
+pub mod config;
 pub mod ethercat;
 pub mod logging;
 pub mod r#loop;
@@ -58,6 +62,7 @@ pub mod panic;
 pub mod performance_metrics;
 pub mod rest;
 pub mod socketio;
+pub mod streaming;
 pub mod utils;
 
 pub async fn send_empty_machines_event(shared_state: Arc<SharedState>) {
@@ -70,7 +75,7 @@ pub async fn add_serial_device(
     device_identification: &DeviceIdentification,
     device: Arc<RwLock<dyn SerialDevice>>,
     machine_registry: &MachineRegistry,
-    socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>,
+    socket_queue_tx: Sender<SocketQueueItem>,
 ) {
     tracing::info!("add_serial_device");
     let hardware = MachineNewHardwareSerial { device };
@@ -116,7 +121,7 @@ pub async fn add_serial_device(
         .insert(machine_identification, machine.api_get_sender());
 
     let _ = shared_state
-        .rt_machine_creation_channel
+        .machine_creation_channel(MachineExecutor::NonRealTime)
         .send(HotThreadMessage::AddMachines(vec![machine]))
         .await;
     shared_state.clone().send_machines_event().await;
@@ -137,7 +142,10 @@ pub async fn start_interface_discovery(
     app_state: Arc<SharedState>,
     sender: Sender<HotThreadMessage>,
 ) {
-    let interface = find_ethercat_interface().await;
+    let interface = match &config::config().ethercat.interface {
+        Some(interface) => interface.clone(),
+        None => find_ethercat_interface().await,
+    };
     tracing::info!("Inferface found {}, setting up EtherCAT loop", interface);
     set_ethercat_iface(interface.clone());
     let res = setup_loop(&interface, app_state.clone()).await;
@@ -214,8 +222,7 @@ pub async fn handle_serial_device_hotplug(
         app_state.clone().remove_machine(&unique_ident).await;
 
         let _ = app_state
-            .clone()
-            .rt_machine_creation_channel
+            .machine_creation_channel(MachineExecutor::NonRealTime)
             .send(HotThreadMessage::DeleteMachine(unique_ident))
             .await;
 
@@ -227,6 +234,7 @@ async fn handle_async_requests(recv: Receiver<AsyncThreadMessage>, shared_state:
     while let Ok(message) = recv.recv().await {
         match message {
             AsyncThreadMessage::NoMsg => (),
+            AsyncThreadMessage::Blocking(job) => smol::unblock(job).await,
             AsyncThreadMessage::ConnectOneWayRequest(cross_connection) => {
                 let api_machines_guard = shared_state.api_machines.lock().await;
                 // The Src Connection is from the machine that recvs the request to connect
@@ -322,8 +330,42 @@ fn setup_ctrlc_handler() -> Arc<AtomicBool> {
 static ALLOC: dhat::Alloc = dhat::Alloc;
 
 fn main() {
-    logging::init_tracing();
+    let cli = Cli::parse();
+    let (config, sources) = match config::load(cli.config.as_deref(), &cli.overrides) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+    };
+    if cli.check_config {
+        match config::check_report(&config, &sources) {
+            Ok(report) => print!("{}", report),
+            Err(e) => {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(directory) = &config.state.directory {
+        machines::state_file::set_directory(directory.clone());
+    }
+    config::init(config);
+    let config = config::config();
+
+    logging::init_tracing(config.logging.filter.as_deref());
     tracing::info!("Tracing initialized successfully");
+    tracing::info!(
+        "Configuration loaded from {:?}, environment {:?}, command line {:?}",
+        sources.file,
+        sources.env,
+        sources.overrides
+    );
+    for warning in config.warnings() {
+        tracing::warn!("{}", warning);
+    }
     init_panic_handling();
 
     #[cfg(feature = "heap-profile")]
@@ -332,22 +374,29 @@ fn main() {
     #[cfg(feature = "development-build")]
     let running = setup_ctrlc_handler();
 
-    const CYCLE_TARGET_TIME: Duration = Duration::from_micros(700);
-
     // for the "hot thread"
     let (sender, receiver) = smol::channel::unbounded();
     let (main_sender, main_receiver) = smol::channel::unbounded();
-    let shared_state = SharedState::new(sender.clone(), main_sender);
+    // serial and Modbus TCP machines optionally run outside the RT loop
+    let non_rt_sender = config.rt.isolate_non_rt_machines.then(|| {
+        let (non_rt_sender, non_rt_receiver) = smol::channel::unbounded();
+        start_non_rt_loop_thread(non_rt_receiver, config.rt.non_rt_cycle_target())
+            .expect("Failed to start non-RT loop thread");
+        non_rt_sender
+    });
+    let shared_state = SharedState::new(sender.clone(), non_rt_sender, main_sender);
     let app_state = Arc::new(shared_state);
-    let _loop_thread = start_loop_thread(receiver, CYCLE_TARGET_TIME);
+    let _loop_thread = start_loop_thread(receiver, config.rt.cycle_target());
     let _ = start_api_thread(app_state.clone());
 
     spawn_runtime_metrics_sampler(RuntimeMetricsConfig {
-        csv_path: "runtime_metrics.csv".to_string(),
-        interval: Duration::from_secs(1),
-        ethercat_iface: None,
+        csv_path: config.metrics.csv_path.to_string_lossy().into_owned(),
+        interval: Duration::from_millis(config.metrics.sample_interval_ms),
+        ethercat_iface: config.ethercat.interface.clone(),
     });
 
+    spawn_act_budget_supervisor(app_state.clone());
+
     let mut socketio_task = smol::spawn(start_socketio_queue(app_state.clone()));
     let mut serial_task = smol::spawn(start_serial_discovery(app_state.clone()));
     let mut async_machine_task = smol::spawn(handle_async_requests(
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use control_core::socketio::namespace::NamespaceCacheingLogic;
+use machines::machine_identification::MachineIdentificationUnique;
+use smol::Timer;
+
+use crate::app_state::SharedState;
+use crate::config::config;
+use crate::r#loop::MachineExecutor;
+use crate::metrics::rt_loop::rt_loop_metrics;
+use crate::socketio::main_namespace::MainNamespaceEvents;
+use crate::socketio::main_namespace::machine_act_budget_event::{
+    MachineActBudgetEventBuilder, MachineActBudgetObj,
+};
+
+/// How often overruns are checked, logged and sent to the main namespace
+const SUPERVISION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Budget of a newly added machine, from `[machines]` in the config
+pub fn act_budget(id: &MachineIdentificationUnique, executor: MachineExecutor) -> Duration {
+    let machines = &config().machines;
+    let us = machines
+        .act_budgets_us
+        .get(&id.machine_identification.slug())
+        .copied()
+        .unwrap_or(match executor {
+            MachineExecutor::RealTime => machines.act_budget_us,
+            MachineExecutor::NonRealTime => machines.non_rt_act_budget_us,
+        });
+    Duration::from_micros(us)
+}
+
+/// Watches the `act` overrun counters, logs new overruns and sends them to the main namespace
+///
+/// Runs outside the RT loop, which must not log.
+pub fn spawn_act_budget_supervisor(app_state: Arc<SharedState>) {
+    smol::spawn(async move {
+        let mut last_overruns: HashMap<MachineIdentificationUnique, u64> = HashMap::new();
+        loop {
+            Timer::after(SUPERVISION_INTERVAL).await;
+
+            let machines = rt_loop_metrics().machines();
+            let mut changed = false;
+            let objs: Vec<MachineActBudgetObj> = machines
+                .iter()
+                .map(|machine| {
+                    let id = &machine.machine_identification_unique;
+                    let overruns = machine.overruns_total.load(Ordering::Relaxed);
+                    let max_act = machine.take_window_max();
+                    let last = last_overruns.insert(id.clone(), overruns).unwrap_or(0);
+                    if overruns > last {
+                        changed = true;
+                        tracing::warn!(
+                            "{} {} overran its act budget of {:?} {} times in the last {:?}, longest act {:?}",
+                            id.machine_identification.slug(),
+                            id.serial,
+                            machine.budget,
+                            overruns - last,
+                            SUPERVISION_INTERVAL,
+                            max_act
+                        );
+                    }
+                    MachineActBudgetObj {
+                        machine_identification_unique: id.clone(),
+                        executor: machine.executor,
+                        budget_ns: u64::try_from(machine.budget.as_nanos()).unwrap_or(u64::MAX),
+                        max_act_ns: u64::try_from(max_act.as_nanos()).unwrap_or(u64::MAX),
+                        overruns,
+                    }
+                })
+                .collect();
+            last_overruns.retain(|id, _| {
+                machines
+                    .iter()
+                    .any(|m| m.machine_identification_unique == *id)
+            });
+
+            if changed {
+                let event = MachineActBudgetEventBuilder().build(objs);
+                app_state
+                    .socketio_setup
+                    .namespaces
+                    .write()
+                    .await
+                    .main_namespace
+                    .emit(MainNamespaceEvents::MachineActBudgetEvent(event));
+            }
+        }
+    })
+    .detach();
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Most buckets a [`Histogram`] can have, without the implicit `+Inf` bucket
+pub const MAX_BUCKETS: usize = 16;
+
+/// Lock-free histogram of durations
+///
+/// Recording is a handful of relaxed atomic adds and never allocates, so it can be used from the
+/// RT loop. Readers may see a sample in `count` but not yet in its bucket, which is fine for
+/// monitoring.
+#[derive(Debug)]
+pub struct Histogram {
+    /// Upper bounds of the buckets in nanoseconds, ascending
+    bounds_ns: &'static [u64],
+    /// Non-cumulative counts, the last used slot is the `+Inf` bucket
+    buckets: [AtomicU64; MAX_BUCKETS + 1],
+    sum_ns: AtomicU64,
+    count: AtomicU64,
+}
+
+/// Consistent-enough copy of a [`Histogram`] for export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    /// `(upper bound in ns, cumulative count)`, the `+Inf` bucket is `count`
+    pub buckets: Vec<(u64, u64)>,
+    pub sum_ns: u64,
+    pub count: u64,
+}
+
+impl Histogram {
+    /// `bounds_ns` has to be ascending and at most [`MAX_BUCKETS`] long
+    pub const fn new(bounds_ns: &'static [u64]) -> Self {
+        assert!(bounds_ns.len() <= MAX_BUCKETS);
+        Self {
+            bounds_ns,
+            buckets: [const { AtomicU64::new(0) }; MAX_BUCKETS + 1],
+            sum_ns: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, duration: Duration) {
+        let ns = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = self
+            .bounds_ns
+            .iter()
+            .position(|bound| ns <= *bound)
+            .unwrap_or(self.bounds_ns.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(ns, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds_ns
+            .iter()
+            .zip(self.buckets.iter())
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (*bound, cumulative)
+            })
+            .collect();
+        let inf = self.buckets[self.bounds_ns.len()].load(Ordering::Relaxed);
+        HistogramSnapshot {
+            buckets,
+            sum_ns: self.sum_ns.load(Ordering::Relaxed),
+            count: cumulative + inf,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        static BOUNDS: [u64; 3] = [100, 1_000, 10_000];
+        let histogram = Histogram::new(&BOUNDS);
+        histogram.record(Duration::from_nanos(50));
+        histogram.record(Duration::from_nanos(100));
+        histogram.record(Duration::from_nanos(5_000));
+        histogram.record(Duration::from_secs(1));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets, vec![(100, 2), (1_000, 2), (10_000, 3)]);
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.sum_ns, 1_000_005_150);
+    }
+}
@@ -1,7 +1,12 @@
+pub mod act_budget;
 pub mod collector;
 pub mod csv_writer;
+pub mod histogram;
 pub mod io;
 pub mod jitter;
+pub mod openmetrics;
 pub mod preemption;
 pub mod process;
+pub mod rt_loop;
+pub mod socketio;
 pub mod state;
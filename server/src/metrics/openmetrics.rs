@@ -0,0 +1,209 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::metrics::histogram::HistogramSnapshot;
+
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Writer for the OpenMetrics text exposition format
+///
+/// Every family has to be announced with [`Self::family`] before its samples are written.
+/// Durations are exported in seconds as the format recommends.
+#[derive(Debug, Default)]
+pub struct OpenMetricsWriter {
+    out: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricType {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Histogram => "histogram",
+        }
+    }
+}
+
+impl OpenMetricsWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn family(&mut self, name: &str, metric_type: MetricType, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, metric_type.name());
+        let _ = writeln!(self.out, "# HELP {} {}", name, escape(help));
+    }
+
+    /// Writes one sample, counters need the `_total` suffix in `name`
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        write_labels(&mut self.out, labels, None);
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    /// Single counter family without labels
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.family(name, MetricType::Counter, help);
+        self.sample(&format!("{name}_total"), &[], value as f64);
+    }
+
+    /// Single gauge family without labels
+    pub fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, MetricType::Gauge, help);
+        self.sample(name, &[], value);
+    }
+
+    /// Writes the buckets, sum and count of a duration histogram in seconds
+    pub fn histogram(&mut self, name: &str, labels: &[(&str, &str)], snapshot: &HistogramSnapshot) {
+        for (bound_ns, count) in &snapshot.buckets {
+            let le = format_value(*bound_ns as f64 / 1e9);
+            self.out.push_str(name);
+            self.out.push_str("_bucket");
+            write_labels(&mut self.out, labels, Some(&le));
+            let _ = writeln!(self.out, " {}", count);
+        }
+        self.out.push_str(name);
+        self.out.push_str("_bucket");
+        write_labels(&mut self.out, labels, Some("+Inf"));
+        let _ = writeln!(self.out, " {}", snapshot.count);
+
+        self.sample(&format!("{name}_sum"), labels, snapshot.sum_ns as f64 / 1e9);
+        self.sample(&format!("{name}_count"), labels, snapshot.count as f64);
+    }
+
+    pub fn finish(mut self) -> String {
+        self.out.push_str("# EOF\n");
+        self.out
+    }
+}
+
+fn write_labels(out: &mut String, labels: &[(&str, &str)], le: Option<&str>) {
+    if labels.is_empty() && le.is_none() {
+        return;
+    }
+    out.push('{');
+    let le = le.map(|le| ("le", le));
+    for (i, (name, value)) in labels.iter().copied().chain(le).enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}=\"{}\"", name, escape(value));
+    }
+    out.push('}');
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Collects the numeric and boolean leaves of a machine's live values as `(field, value)`
+///
+/// Nested fields are joined with `.`, e.g. `temperatures.nozzle`. Strings, nulls and arrays are
+/// skipped since they have no meaningful gauge value.
+pub fn numeric_fields(value: &Value) -> Vec<(String, f64)> {
+    let mut fields = vec![];
+    collect_numeric_fields(String::new(), value, &mut fields);
+    fields
+}
+
+fn collect_numeric_fields(path: String, value: &Value, fields: &mut Vec<(String, f64)>) {
+    match value {
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                fields.push((path, n));
+            }
+        }
+        Value::Bool(b) => fields.push((path, f64::from(u8::from(*b)))),
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_numeric_fields(path, value, fields);
+            }
+        }
+        Value::Null | Value::String(_) | Value::Array(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::histogram::Histogram;
+    use std::time::Duration;
+
+    #[test]
+    fn test_histogram_encoding() {
+        static BOUNDS: [u64; 2] = [500_000, 1_000_000];
+        let histogram = Histogram::new(&BOUNDS);
+        histogram.record(Duration::from_micros(300));
+        histogram.record(Duration::from_millis(2));
+
+        let mut writer = OpenMetricsWriter::new();
+        writer.family("loop_cycle_seconds", MetricType::Histogram, "Cycle time");
+        writer.histogram(
+            "loop_cycle_seconds",
+            &[("machine", "a\"b")],
+            &histogram.snapshot(),
+        );
+        writer.counter("loop_cycles", "Cycles", 2);
+
+        assert_eq!(
+            writer.finish(),
+            "# TYPE loop_cycle_seconds histogram\n\
+             # HELP loop_cycle_seconds Cycle time\n\
+             loop_cycle_seconds_bucket{machine=\"a\\\"b\",le=\"0.0005\"} 1\n\
+             loop_cycle_seconds_bucket{machine=\"a\\\"b\",le=\"0.001\"} 1\n\
+             loop_cycle_seconds_bucket{machine=\"a\\\"b\",le=\"+Inf\"} 2\n\
+             loop_cycle_seconds_sum{machine=\"a\\\"b\"} 0.0023\n\
+             loop_cycle_seconds_count{machine=\"a\\\"b\"} 2\n\
+             # TYPE loop_cycles counter\n\
+             # HELP loop_cycles Cycles\n\
+             loop_cycles_total 2\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn test_numeric_fields() {
+        let live_values = serde_json::json!({
+            "speed": 1.5,
+            "enabled": true,
+            "mode": "Standby",
+            "temperatures": { "nozzle": 210, "front": null },
+        });
+        let mut fields = numeric_fields(&live_values);
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            fields,
+            vec![
+                ("enabled".to_string(), 1.0),
+                ("speed".to_string(), 1.5),
+                ("temperatures.nozzle".to_string(), 210.0),
+            ]
+        );
+    }
+}
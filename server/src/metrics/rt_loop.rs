@@ -0,0 +1,158 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use machines::machine_identification::MachineIdentificationUnique;
+
+use crate::r#loop::MachineExecutor;
+use crate::metrics::act_budget::act_budget;
+use crate::metrics::histogram::Histogram;
+
+const US: u64 = 1_000;
+const MS: u64 = 1_000_000;
+
+/// Start to start period of the RT loop, around the cycle target
+static CYCLE_BOUNDS_NS: [u64; 12] = [
+    100 * US,
+    200 * US,
+    300 * US,
+    400 * US,
+    500 * US,
+    700 * US,
+    MS,
+    MS + 500 * US,
+    2 * MS,
+    3 * MS,
+    5 * MS,
+    10 * MS,
+];
+
+/// Busy time of one loop iteration and the EtherCAT tx/rx round trip
+static BUSY_BOUNDS_NS: [u64; 12] = [
+    10 * US,
+    25 * US,
+    50 * US,
+    75 * US,
+    100 * US,
+    150 * US,
+    200 * US,
+    300 * US,
+    500 * US,
+    MS,
+    2 * MS,
+    5 * MS,
+];
+
+/// Duration of one `Machine::act`
+static ACT_BOUNDS_NS: [u64; 12] = [
+    US,
+    2 * US,
+    5 * US,
+    10 * US,
+    25 * US,
+    50 * US,
+    100 * US,
+    200 * US,
+    300 * US,
+    500 * US,
+    MS,
+    5 * MS,
+];
+
+/// Metrics written by the RT loop, read by the `/metrics` endpoint
+pub struct RtLoopMetrics {
+    pub cycle_time: Histogram,
+    pub busy_time: Histogram,
+    pub txrx_time: Histogram,
+    pub cycles_total: AtomicU64,
+    /// Cycles in which not all subdevices were in OP or the working counter was too low
+    pub degraded_cycles_total: AtomicU64,
+    pub working_counter: AtomicU64,
+    pub healthy_working_counter: AtomicU64,
+    machines: Mutex<Vec<Arc<MachineActMetrics>>>,
+}
+
+/// Execution time of one machine in the RT loop or the non-RT executor
+pub struct MachineActMetrics {
+    pub machine_identification_unique: MachineIdentificationUnique,
+    pub executor: MachineExecutor,
+    /// An `act` taking longer than this counts as overrun
+    pub budget: Duration,
+    pub act_time: Histogram,
+    pub overruns_total: AtomicU64,
+    /// Longest `act` since the last [`Self::take_window_max`]
+    window_max_ns: AtomicU64,
+}
+
+impl MachineActMetrics {
+    pub fn record_act(&self, duration: Duration) {
+        self.act_time.record(duration);
+        let ns = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.window_max_ns.fetch_max(ns, Ordering::Relaxed);
+        if duration > self.budget {
+            self.overruns_total.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Longest `act` since the previous call
+    pub fn take_window_max(&self) -> Duration {
+        Duration::from_nanos(self.window_max_ns.swap(0, Ordering::Relaxed))
+    }
+}
+
+static RT_LOOP_METRICS: RtLoopMetrics = RtLoopMetrics {
+    cycle_time: Histogram::new(&CYCLE_BOUNDS_NS),
+    busy_time: Histogram::new(&BUSY_BOUNDS_NS),
+    txrx_time: Histogram::new(&BUSY_BOUNDS_NS),
+    cycles_total: AtomicU64::new(0),
+    degraded_cycles_total: AtomicU64::new(0),
+    working_counter: AtomicU64::new(0),
+    healthy_working_counter: AtomicU64::new(0),
+    machines: Mutex::new(vec![]),
+};
+
+pub fn rt_loop_metrics() -> &'static RtLoopMetrics {
+    &RT_LOOP_METRICS
+}
+
+impl RtLoopMetrics {
+    /// Called when a machine is added to the loop, not in the hot path
+    pub fn register_machine(
+        &self,
+        id: MachineIdentificationUnique,
+        executor: MachineExecutor,
+    ) -> Arc<MachineActMetrics> {
+        let metrics = Arc::new(MachineActMetrics {
+            budget: act_budget(&id, executor),
+            machine_identification_unique: id,
+            executor,
+            act_time: Histogram::new(&ACT_BOUNDS_NS),
+            overruns_total: AtomicU64::new(0),
+            window_max_ns: AtomicU64::new(0),
+        });
+        let mut machines = self.machines.lock().expect("Metrics lock poisoned");
+        machines
+            .retain(|m| m.machine_identification_unique != metrics.machine_identification_unique);
+        machines.push(metrics.clone());
+        drop(machines);
+        metrics
+    }
+
+    pub fn unregister_machine(&self, id: &MachineIdentificationUnique) {
+        self.machines
+            .lock()
+            .expect("Metrics lock poisoned")
+            .retain(|m| m.machine_identification_unique != *id);
+    }
+
+    pub fn machines(&self) -> Vec<Arc<MachineActMetrics>> {
+        self.machines.lock().expect("Metrics lock poisoned").clone()
+    }
+
+    pub fn set_working_counter(&self, working_counter: u16, healthy_working_counter: u16) {
+        self.working_counter
+            .store(u64::from(working_counter), Ordering::Relaxed);
+        self.healthy_working_counter
+            .store(u64::from(healthy_working_counter), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Metrics of the socket.io fan-out, read by the `/metrics` endpoint
+pub struct SocketioMetrics {
+    pub sent_total: AtomicU64,
+    /// Sends retried because the send buffer of the socket was full
+    pub retries_total: AtomicU64,
+    /// Events waiting in the per socket queues
+    pub queued: AtomicU64,
+    /// Sockets disconnected because their queue reached `socket_queue_max_len`
+    pub overflow_disconnects_total: AtomicU64,
+    events: Mutex<BTreeMap<String, EventCounters>>,
+}
+
+/// Counters of one event name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventCounters {
+    /// Replaced by a newer event before it was sent
+    pub coalesced: u64,
+    /// Not sent because the socket queue was full
+    pub dropped: u64,
+}
+
+static SOCKETIO_METRICS: SocketioMetrics = SocketioMetrics {
+    sent_total: AtomicU64::new(0),
+    retries_total: AtomicU64::new(0),
+    queued: AtomicU64::new(0),
+    overflow_disconnects_total: AtomicU64::new(0),
+    events: Mutex::new(BTreeMap::new()),
+};
+
+pub fn socketio_metrics() -> &'static SocketioMetrics {
+    &SOCKETIO_METRICS
+}
+
+impl SocketioMetrics {
+    pub fn record_coalesced(&self, event: &str) {
+        self.update(event, |counters| counters.coalesced += 1);
+    }
+
+    pub fn record_dropped(&self, event: &str) {
+        self.update(event, |counters| counters.dropped += 1);
+    }
+
+    fn update(&self, event: &str, f: impl FnOnce(&mut EventCounters)) {
+        let mut events = self.events.lock().expect("Metrics lock poisoned");
+        match events.get_mut(event) {
+            Some(counters) => f(counters),
+            None => f(events.entry(event.to_string()).or_default()),
+        }
+    }
+
+    /// Counters by event name, only names that were coalesced or dropped at least once
+    pub fn events(&self) -> Vec<(String, EventCounters)> {
+        self.events
+            .lock()
+            .expect("Metrics lock poisoned")
+            .iter()
+            .map(|(name, counters)| (name.clone(), *counters))
+            .collect()
+    }
+
+    pub fn add_queued(&self, n: u64) {
+        self.queued.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn sub_queued(&self, n: u64) {
+        self.queued.fetch_sub(n, Ordering::Relaxed);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::app_state::SharedState;
+use crate::r#loop::MachineExecutor;
 use machines::{
     Machine, MachineChannel, machine_identification::MachineIdentificationUnique,
     wago_power::WagoPower,
@@ -46,7 +47,9 @@ pub async fn start_modbus_tcp_discovery(shared_state: Arc<SharedState>) {
             .join_all()
             .await;
 
-        shared_state.add_machines(machines).await;
+        shared_state
+            .add_machines(machines, MachineExecutor::NonRealTime)
+            .await;
         return;
     }
 }
@@ -65,5 +68,7 @@ pub async fn start_modbus_tcp_discovery(shared_state: Arc<SharedState>) {
 
     let machines: Vec<Box<dyn Machine>> = vec![Box::new(power)];
 
-    shared_state.add_machines(machines).await;
+    shared_state
+        .add_machines(machines, MachineExecutor::NonRealTime)
+        .await;
 }
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::{Query, State};
+use axum::routing::get;
+use machines::machine_identification::{MachineIdentification, MachineIdentificationUnique};
+use serde::{Deserialize, Serialize};
+
+use crate::app_state::SharedState;
+use crate::audit::{AuditEntry, AuditQuery};
+use crate::rest::response::*;
+
+/// Entries returned when no `limit` is given
+const DEFAULT_LIMIT: usize = 1000;
+
+#[derive(Deserialize, Debug)]
+struct AuditQueryParams {
+    vendor: Option<u16>,
+    machine: Option<u16>,
+    serial: Option<u16>,
+    /// Unix time in milliseconds, inclusive
+    from: Option<u64>,
+    /// Unix time in milliseconds, inclusive
+    to: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+struct GetAuditResponse {
+    entries: Vec<AuditEntry>,
+}
+
+async fn get_audit_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Query(params): Query<AuditQueryParams>,
+) -> Result<GetAuditResponse> {
+    let machine_identification_unique = match (params.vendor, params.machine, params.serial) {
+        (Some(vendor), Some(machine), Some(serial)) => Some(MachineIdentificationUnique {
+            machine_identification: MachineIdentification { vendor, machine },
+            serial,
+        }),
+        (None, None, None) => None,
+        _ => {
+            return Err(bad_request(
+                "`vendor`, `machine` and `serial` must be given together",
+            ));
+        }
+    };
+
+    let query = AuditQuery {
+        machine_identification_unique,
+        from_ms: params.from.map(u128::from),
+        to_ms: params.to.map(u128::from),
+        limit: Some(params.limit.unwrap_or(DEFAULT_LIMIT)),
+    };
+    // reading the file blocks
+    let entries = tokio::task::spawn_blocking(move || shared_state.audit_log.query(&query))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?;
+
+    json(GetAuditResponse { entries })
+}
+
+/// Audit log of machine mutations
+///
+/// `GET /?vendor=1&machine=56&serial=1&from=<unix ms>&to=<unix ms>&limit=100` returns the
+/// newest matching entries, oldest first.
+pub fn audit_router() -> Router<Arc<SharedState>> {
+    Router::new().route("/", get(get_audit_handler))
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::middleware::from_fn_with_state;
+use axum::routing::{delete, get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::app_state::SharedState;
+use crate::auth::Role;
+use crate::auth::middleware::{bearer_token, require_role};
+use crate::auth::sessions::{SESSION_TTL, Session};
+use crate::auth::users::UserInfo;
+use crate::rest::response::*;
+
+#[derive(Deserialize, Debug)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize, Debug)]
+struct LoginResponse {
+    /// Send as `Authorization: Bearer <token>` or as socket.io auth `{ token }`
+    token: String,
+    username: String,
+    role: Role,
+    expires_in_s: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct MeResponse {
+    username: String,
+    role: Role,
+}
+
+#[derive(Serialize, Debug)]
+struct GetUsersResponse {
+    users: Vec<UserInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PutUserRequest {
+    password: String,
+    role: Role,
+}
+
+async fn post_login_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Json(body): Json<LoginRequest>,
+) -> Result<LoginResponse> {
+    let Some(role) = shared_state
+        .auth
+        .users
+        .verify(&body.username, &body.password)
+    else {
+        tracing::warn!("Failed login username={}", body.username);
+        return Err(unauthorized("Invalid username or password"));
+    };
+
+    let (token, session) = shared_state.auth.sessions.create(&body.username, role);
+    tracing::info!("Login username={} role={}", session.username, session.role);
+
+    json(LoginResponse {
+        token,
+        username: session.username,
+        role: session.role,
+        expires_in_s: SESSION_TTL.as_secs(),
+    })
+}
+
+async fn post_logout_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    headers: HeaderMap,
+) -> Result<()> {
+    if let Some(token) = bearer_token(&headers) {
+        shared_state.auth.sessions.revoke(token);
+    }
+    json(())
+}
+
+async fn get_me_handler(Extension(session): Extension<Session>) -> Result<MeResponse> {
+    json(MeResponse {
+        username: session.username,
+        role: session.role,
+    })
+}
+
+async fn get_users_handler(
+    State(shared_state): State<Arc<SharedState>>,
+) -> Result<GetUsersResponse> {
+    json(GetUsersResponse {
+        users: shared_state.auth.users.list(),
+    })
+}
+
+async fn put_user_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Extension(session): Extension<Session>,
+    Path(username): Path<String>,
+    Json(body): Json<PutUserRequest>,
+) -> Result<()> {
+    shared_state
+        .auth
+        .users
+        .upsert(&username, &body.password, body.role)
+        .map_err(bad_request)?;
+    // existing sessions keep their old role otherwise
+    shared_state.auth.sessions.revoke_user(&username);
+    tracing::info!(
+        "User updated username={} role={} by={}",
+        username,
+        body.role,
+        session.username
+    );
+    json(())
+}
+
+async fn delete_user_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Extension(session): Extension<Session>,
+    Path(username): Path<String>,
+) -> Result<()> {
+    shared_state
+        .auth
+        .users
+        .remove(&username)
+        .map_err(bad_request)?;
+    shared_state.auth.sessions.revoke_user(&username);
+    tracing::info!("User deleted username={} by={}", username, session.username);
+    json(())
+}
+
+/// Login and user management
+///
+/// - `POST /login` with `{ "username": "...", "password": "..." }` returns a token
+/// - `POST /logout` ends the session of the bearer token
+/// - `GET /me` returns the user of the session
+/// - `GET /users`, `PUT /users/{username}` with `{ "password": "...", "role": "operator" }` and
+///   `DELETE /users/{username}` manage users, admin only
+pub fn auth_router(app_state: &Arc<SharedState>) -> Router<Arc<SharedState>> {
+    let viewer = from_fn_with_state((app_state.clone(), Role::Viewer), require_role);
+    let admin = from_fn_with_state((app_state.clone(), Role::Admin), require_role);
+
+    Router::new()
+        .route("/login", post(post_login_handler))
+        .route("/logout", post(post_logout_handler))
+        .merge(
+            Router::new()
+                .route("/me", get(get_me_handler))
+                .route_layer(viewer),
+        )
+        .merge(
+            Router::new()
+                .route("/users", get(get_users_handler))
+                .route(
+                    "/users/{username}",
+                    delete(delete_user_handler).put(put_user_handler),
+                )
+                .route_layer(admin),
+        )
+}
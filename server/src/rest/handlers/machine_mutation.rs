@@ -2,67 +2,113 @@ use super::mutation::MachineMutationBody;
 use super::mutation::MutationResponse;
 use crate::{
     app_state::SharedState,
+    audit::{
+        AuditResult, MutationOrigin, MutationOutcome, record_rejected_mutation,
+        send_audited_mutation,
+    },
+    auth::{middleware::authorize_mutation, sessions::Session},
+    rest::openapi::validate_mutation,
     rest::util::{ResponseUtil, ResponseUtilError},
 };
-use axum::{Json, body::Body, extract::State, http::Response};
+use axum::{
+    Extension, Json,
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::Response,
+    response::IntoResponse,
+};
 use serde_json::Value;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 #[axum::debug_handler]
 pub async fn post_machine_mutate(
     State(app_state): State<Arc<SharedState>>,
+    Extension(session): Extension<Session>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
     Json(body): Json<MachineMutationBody<Value>>,
 ) -> Response<Body> {
-    let result = _post_machine_mutate(State(app_state), Json(body)).await;
-    match result {
-        Ok(_) => ResponseUtil::ok(MutationResponse::success()),
-        Err(e) => ResponseUtilError::Error(e).into(),
+    let origin = MutationOrigin {
+        session: &session,
+        source: "rest_v1",
+        remote_addr: Some(remote_addr),
+    };
+
+    if let Err(e) = authorize_mutation(
+        &session,
+        &body.machine_identification_unique.machine_identification,
+        &body.data,
+    ) {
+        record_rejected_mutation(
+            &app_state,
+            &origin,
+            &body.machine_identification_unique,
+            body.data,
+            AuditResult::Denied(format!("Role {} insufficient", session.role)),
+        );
+        return e.into_response();
+    }
+
+    if let Err(e) = validate_mutation(
+        &body.machine_identification_unique.machine_identification,
+        &body.data,
+    ) {
+        record_rejected_mutation(
+            &app_state,
+            &origin,
+            &body.machine_identification_unique,
+            body.data,
+            AuditResult::Invalid(e.to_string()),
+        );
+        return ResponseUtilError::BadRequest(e).into();
+    }
+
+    let outcome = match _post_machine_mutate(&app_state, &origin, body).await {
+        Ok(outcome) => outcome,
+        Err(e) => return ResponseUtilError::Error(e).into(),
+    };
+    match outcome.result {
+        AuditResult::Applied => ResponseUtil::ok(MutationResponse {
+            state: outcome.state,
+            ..MutationResponse::success()
+        }),
+        AuditResult::Rejected(e) => ResponseUtil::bad_request(&e),
+        AuditResult::Timeout => {
+            ResponseUtilError::Timeout(anyhow::anyhow!("Machine didn't apply the mutation in time"))
+                .into()
+        }
+        result => ResponseUtilError::Error(anyhow::anyhow!("Mutation failed: {:?}", result)).into(),
     }
 }
 
 async fn _post_machine_mutate(
-    State(app_state): State<Arc<SharedState>>,
-    Json(body): Json<MachineMutationBody<Value>>,
-) -> Result<(), anyhow::Error> {
+    app_state: &SharedState,
+    origin: &MutationOrigin<'_>,
+    body: MachineMutationBody<Value>,
+) -> Result<MutationOutcome, anyhow::Error> {
     tracing::info!(
-        "Mutating machine machine={} data={:?}",
+        "Mutating machine machine={} data={:?} user={}",
         body.machine_identification_unique,
         body.data,
+        origin.session.username,
     );
 
     let span = tracing::info_span!("machine_mutate", machine = %body.machine_identification_unique);
     let _span = span.enter();
 
-    match app_state
-        .api_machines
-        .lock()
-        .await
-        .get(&body.machine_identification_unique)
-    {
-        Some(sender) => {
-            let res = sender
-                .clone()
-                .send(machines::MachineMessage::HttpApiJsonRequest(
-                    body.data.clone(),
-                ))
-                .await;
-            match res {
-                Ok(_) => (),
-                Err(e) => tracing::error!(
-                    "[{}::_post_machine_mutate] Sending MachineMessage::HttpApiJsonRequest to {} failed {}",
-                    module_path!(),
-                    body.machine_identification_unique,
-                    e
-                ),
-            };
-            Ok(())
-        }
-
-        None => Err(anyhow::anyhow!(
+    send_audited_mutation(
+        app_state,
+        origin,
+        &body.machine_identification_unique,
+        body.data,
+    )
+    .await
+    .map_err(|e| {
+        anyhow::anyhow!(
             "[{}::_post_machine_mutate] Machine api_mutate error {} {}",
             module_path!(),
-            "No Machine found with id: ",
-            body.machine_identification_unique
-        )),
-    }
+            body.machine_identification_unique,
+            e
+        )
+    })
 }
@@ -1,11 +1,27 @@
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
+use axum::extract::State;
+use axum::http::{HeaderMap, header};
+use axum::response::{IntoResponse, Response};
 use axum::{Json, Router, routing::get};
+use machines::MachineMessage;
 use serde::Serialize;
 
 use crate::SharedState;
+use crate::auth::Role;
+use crate::auth::middleware::{authorize, bearer_token};
+use crate::config::config;
+use crate::metrics::openmetrics::{self, MetricType, OpenMetricsWriter, numeric_fields};
 use crate::metrics::process::ProcessMetrics;
+use crate::metrics::rt_loop::rt_loop_metrics;
+use crate::metrics::socketio::socketio_metrics;
 use crate::metrics::state::get_latest_runtime_sample;
+use crate::rest::response::unauthorized;
+
+/// Machines that don't answer within this time are left out of a scrape
+const LIVE_VALUES_TIMEOUT: Duration = Duration::from_millis(200);
 
 /// Process-level metrics exposed over the REST API.
 ///
@@ -88,3 +104,257 @@ pub fn metrics_router() -> Router<Arc<SharedState>> {
         .route("/process/metrics", get(get_process_metrics))
         .route("/runtime/latest", get(get_runtime_metrics_latest))
 }
+
+/// Static token for scrapers, which can't log in, see [`get_openmetrics`]
+fn scrape_token_matches(token: Option<&str>) -> bool {
+    let Some(expected) = &config().metrics.token else {
+        return false;
+    };
+    let Some(token) = token else {
+        return false;
+    };
+    // compare in constant time
+    !expected.is_empty()
+        && token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn write_socketio_metrics(writer: &mut OpenMetricsWriter) {
+    let metrics = socketio_metrics();
+    writer.gauge(
+        "qitech_socketio_socket_queued_events",
+        "Events waiting in the per socket queues",
+        metrics.queued.load(Ordering::Relaxed) as f64,
+    );
+    writer.counter(
+        "qitech_socketio_events_sent",
+        "Events sent to socket.io clients",
+        metrics.sent_total.load(Ordering::Relaxed),
+    );
+    writer.counter(
+        "qitech_socketio_send_retries",
+        "Sends retried because the send buffer of a socket was full",
+        metrics.retries_total.load(Ordering::Relaxed),
+    );
+    writer.counter(
+        "qitech_socketio_overflow_disconnects",
+        "Sockets disconnected because their queue reached socket_queue_max_len",
+        metrics.overflow_disconnects_total.load(Ordering::Relaxed),
+    );
+
+    let events = metrics.events();
+    writer.family(
+        "qitech_socketio_events_coalesced",
+        MetricType::Counter,
+        "Events replaced by a newer event before they were sent",
+    );
+    for (event, counters) in &events {
+        writer.sample(
+            "qitech_socketio_events_coalesced_total",
+            &[("event", event)],
+            counters.coalesced as f64,
+        );
+    }
+    writer.family(
+        "qitech_socketio_events_dropped",
+        MetricType::Counter,
+        "Events dropped because the socket queue was full",
+    );
+    for (event, counters) in &events {
+        writer.sample(
+            "qitech_socketio_events_dropped_total",
+            &[("event", event)],
+            counters.dropped as f64,
+        );
+    }
+}
+
+async fn write_machine_live_values(shared_state: &SharedState, writer: &mut OpenMetricsWriter) {
+    // all numeric fields if unset
+    let allowlist = &config().metrics.live_values;
+    let machines: Vec<_> = shared_state
+        .api_machines
+        .lock()
+        .await
+        .iter()
+        .map(|(id, sender)| (id.clone(), sender.clone()))
+        .collect();
+
+    writer.family(
+        "qitech_machine_live_value",
+        MetricType::Gauge,
+        "Numeric live values of the machines, booleans as 0 or 1",
+    );
+    for (id, sender) in machines {
+        let (values_tx, values_rx) = smol::channel::bounded(1);
+        if sender
+            .try_send(MachineMessage::RequestValues(values_tx))
+            .is_err()
+        {
+            continue;
+        }
+        let values = smol::future::or(async { values_rx.recv().await.ok() }, async {
+            smol::Timer::after(LIVE_VALUES_TIMEOUT).await;
+            None
+        })
+        .await;
+        let Some(values) = values else {
+            continue;
+        };
+
+        let machine = id.machine_identification.slug();
+        let serial = id.serial.to_string();
+        for (field, value) in numeric_fields(&values.live_values) {
+            if allowlist
+                .as_ref()
+                .is_some_and(|allowlist| !allowlist.contains(&field))
+            {
+                continue;
+            }
+            writer.sample(
+                "qitech_machine_live_value",
+                &[
+                    ("machine", &machine),
+                    ("serial", &serial),
+                    ("field", &field),
+                ],
+                value,
+            );
+        }
+    }
+}
+
+/// Prometheus/OpenMetrics scrape endpoint
+///
+/// Accepts a session with at least the viewer role or the static `metrics.token` as bearer
+/// token.
+pub async fn get_openmetrics(
+    State(shared_state): State<Arc<SharedState>>,
+    headers: HeaderMap,
+) -> Response {
+    let token = bearer_token(&headers);
+    if !scrape_token_matches(token) {
+        let Some(session) = shared_state.auth.authenticate(token) else {
+            return unauthorized("Login or metrics token required").into_response();
+        };
+        if let Err(e) = authorize(&session, Role::Viewer) {
+            return e.into_response();
+        }
+    }
+
+    let rt = rt_loop_metrics();
+    let mut writer = OpenMetricsWriter::new();
+
+    writer.family(
+        "qitech_loop_cycle_seconds",
+        MetricType::Histogram,
+        "Start to start period of the real-time loop",
+    );
+    writer.histogram("qitech_loop_cycle_seconds", &[], &rt.cycle_time.snapshot());
+    writer.family(
+        "qitech_loop_busy_seconds",
+        MetricType::Histogram,
+        "Time spent in one loop iteration before sleeping",
+    );
+    writer.histogram("qitech_loop_busy_seconds", &[], &rt.busy_time.snapshot());
+    writer.family(
+        "qitech_ethercat_txrx_seconds",
+        MetricType::Histogram,
+        "EtherCAT process data tx/rx round trip",
+    );
+    writer.histogram(
+        "qitech_ethercat_txrx_seconds",
+        &[],
+        &rt.txrx_time.snapshot(),
+    );
+    writer.counter(
+        "qitech_loop_cycles",
+        "Iterations of the real-time loop",
+        rt.cycles_total.load(Ordering::Relaxed),
+    );
+    writer.counter(
+        "qitech_ethercat_degraded_cycles",
+        "Cycles with a subdevice not in OP or a working counter below the healthy baseline",
+        rt.degraded_cycles_total.load(Ordering::Relaxed),
+    );
+    writer.gauge(
+        "qitech_ethercat_working_counter",
+        "Working counter of the last cycle",
+        rt.working_counter.load(Ordering::Relaxed) as f64,
+    );
+    writer.gauge(
+        "qitech_ethercat_healthy_working_counter",
+        "Working counter baseline of a healthy bus",
+        rt.healthy_working_counter.load(Ordering::Relaxed) as f64,
+    );
+
+    writer.family(
+        "qitech_machine_act_seconds",
+        MetricType::Histogram,
+        "Duration of one act call per machine",
+    );
+    let machines = rt.machines();
+    for machine in &machines {
+        let id = &machine.machine_identification_unique;
+        let slug = id.machine_identification.slug();
+        let serial = id.serial.to_string();
+        writer.histogram(
+            "qitech_machine_act_seconds",
+            &[
+                ("machine", &slug),
+                ("serial", &serial),
+                ("executor", machine.executor.name()),
+            ],
+            &machine.act_time.snapshot(),
+        );
+    }
+    writer.family(
+        "qitech_machine_act_budget_seconds",
+        MetricType::Gauge,
+        "Budget of one act call per machine",
+    );
+    for machine in &machines {
+        let id = &machine.machine_identification_unique;
+        let slug = id.machine_identification.slug();
+        let serial = id.serial.to_string();
+        writer.sample(
+            "qitech_machine_act_budget_seconds",
+            &[("machine", &slug), ("serial", &serial)],
+            machine.budget.as_secs_f64(),
+        );
+    }
+    writer.family(
+        "qitech_machine_act_overruns",
+        MetricType::Counter,
+        "Act calls that took longer than the budget",
+    );
+    for machine in &machines {
+        let id = &machine.machine_identification_unique;
+        let slug = id.machine_identification.slug();
+        let serial = id.serial.to_string();
+        writer.sample(
+            "qitech_machine_act_overruns_total",
+            &[("machine", &slug), ("serial", &serial)],
+            machine.overruns_total.load(Ordering::Relaxed) as f64,
+        );
+    }
+
+    writer.gauge(
+        "qitech_socketio_queue_depth",
+        "Events waiting to be sent to socket.io clients",
+        shared_state.socketio_setup.socket_queue_tx.len() as f64,
+    );
+    write_socketio_metrics(&mut writer);
+
+    write_machine_live_values(&shared_state, &mut writer).await;
+
+    (
+        [(header::CONTENT_TYPE, openmetrics::CONTENT_TYPE)],
+        writer.finish(),
+    )
+        .into_response()
+}
@@ -1,4 +1,7 @@
+pub mod audit;
+pub mod auth;
 pub mod machine_mutation;
 pub mod metrics;
 pub mod mutation;
+pub mod sdo;
 pub mod write_machine_device_identification;
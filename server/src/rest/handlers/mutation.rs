@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use serde::Serialize;
+use serde_json::Value;
 
 use machines::machine_identification::MachineIdentificationUnique;
 
@@ -8,6 +9,9 @@ use machines::machine_identification::MachineIdentificationUnique;
 pub struct MutationResponse {
     pub success: bool,
     pub error: Option<String>,
+    /// Machine state after a machine mutation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<Value>,
 }
 
 impl MutationResponse {
@@ -15,12 +19,14 @@ impl MutationResponse {
         Self {
             success: true,
             error: None,
+            state: None,
         }
     }
     pub const fn error(error: String) -> Self {
         Self {
             success: false,
             error: Some(error),
+            state: None,
         }
     }
 }
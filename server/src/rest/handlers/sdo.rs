@@ -0,0 +1,303 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use ethercat_hal::sdo::{SdoDataType, SdoRequest, SdoResponse, SdoValue};
+use ethercrab::SubIndex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app_state::{HotThreadMessage, SharedState};
+use crate::rest::response::*;
+
+/// Upper bound of objects probed by a single browse request
+///
+/// Every probe is one mailbox round trip, one area of 16 channels (e.g. `0x8000..=0x80FF`)
+/// keeps a request well below a second and doesn't hold up other SDO requests.
+const MAX_PROBES_PER_REQUEST: usize = 0x100;
+
+#[derive(Deserialize, Debug)]
+struct ObjectsQuery {
+    /// First index to probe, hex (`0x8000`) or decimal
+    from: String,
+    /// Last index to probe, inclusive
+    to: String,
+}
+
+#[derive(Serialize, Debug)]
+struct SdoObjectResponse {
+    index: String,
+    /// Highest subindex if subindex 0 looks like an entry count
+    max_sub_index: Option<u8>,
+    sub_index_0: Vec<u8>,
+}
+
+#[derive(Serialize, Debug)]
+struct GetObjectsResponse {
+    objects: Vec<SdoObjectResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReadQuery {
+    /// `bool`, `u8`..`u64`, `i8`..`i64`, `f32`, `f64`, `string` or `bytes`
+    data_type: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct WriteBody {
+    data_type: String,
+    value: Value,
+}
+
+#[derive(Serialize, Debug)]
+struct SdoValueResponse {
+    index: String,
+    sub_index: String,
+    data_type: String,
+    value: Value,
+}
+
+/// Enumerates the object dictionary by probing subindex 0 of every index in the range
+async fn get_objects_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Path(subdevice_index): Path<usize>,
+    Query(query): Query<ObjectsQuery>,
+) -> Result<GetObjectsResponse> {
+    let range: RangeInclusive<u16> = parse_u16(&query.from)?..=parse_u16(&query.to)?;
+    let probes = range.clone().count();
+    if probes > MAX_PROBES_PER_REQUEST {
+        return Err(bad_request(format!(
+            "Range contains {probes} objects, at most {MAX_PROBES_PER_REQUEST} are allowed"
+        )));
+    }
+
+    let mut objects = vec![];
+    for index in range {
+        let request = SdoRequest::Probe { index };
+        if let SdoResponse::Probe(Some(info)) =
+            send_sdo_request(&shared_state, subdevice_index, request).await?
+        {
+            objects.push(SdoObjectResponse {
+                index: format!("0x{:04X}", info.index),
+                max_sub_index: info.max_sub_index(),
+                sub_index_0: info.sub_index_0,
+            });
+        }
+    }
+
+    json(GetObjectsResponse { objects })
+}
+
+async fn get_value_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Path((subdevice_index, index, sub_index)): Path<(usize, String, String)>,
+    Query(query): Query<ReadQuery>,
+) -> Result<SdoValueResponse> {
+    let data_type = parse_data_type(&query.data_type)?;
+    let request = SdoRequest::Upload {
+        index: parse_u16(&index)?,
+        sub_index: parse_sub_index(&sub_index)?,
+        data_type,
+    };
+
+    match send_sdo_request(&shared_state, subdevice_index, request).await? {
+        SdoResponse::Upload(value) => json(SdoValueResponse {
+            index,
+            sub_index,
+            data_type: data_type.name().to_string(),
+            value: value_to_json(value),
+        }),
+        response => Err(internal_error(format!("Unexpected response {response:?}"))),
+    }
+}
+
+async fn put_value_handler(
+    State(shared_state): State<Arc<SharedState>>,
+    Path((subdevice_index, index, sub_index)): Path<(usize, String, String)>,
+    Json(body): Json<WriteBody>,
+) -> Result<()> {
+    let data_type = parse_data_type(&body.data_type)?;
+    let request = SdoRequest::Download {
+        index: parse_u16(&index)?,
+        sub_index: parse_sub_index(&sub_index)?,
+        value: value_from_json(data_type, &body.value).map_err(bad_request)?,
+    };
+    tracing::info!("SDO download subdevice={} {:?}", subdevice_index, request);
+
+    send_sdo_request(&shared_state, subdevice_index, request).await?;
+    json(())
+}
+
+/// Hands the request to the RT loop, which queues it for the SDO thread
+async fn send_sdo_request(
+    shared_state: &SharedState,
+    subdevice_index: usize,
+    request: SdoRequest,
+) -> std::result::Result<SdoResponse, ApiError> {
+    // validate here too, so invalid requests don't occupy a loop cycle
+    request.validate(true).map_err(bad_request)?;
+
+    let (reply, receiver) = smol::channel::bounded(1);
+    shared_state
+        .rt_machine_creation_channel
+        .send(HotThreadMessage::Sdo {
+            subdevice_index,
+            request,
+            reply,
+        })
+        .await
+        .map_err(internal_error)?;
+
+    receiver
+        .recv()
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)
+}
+
+fn parse_u16(s: &str) -> std::result::Result<u16, ApiError> {
+    s.strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .map_or_else(|| s.parse(), |hex| u16::from_str_radix(hex, 16))
+        .map_err(|e| bad_request(format!("Invalid index {s}: {e}")))
+}
+
+/// Parses a subindex, `complete` selects complete access
+fn parse_sub_index(s: &str) -> std::result::Result<SubIndex, ApiError> {
+    if s == "complete" {
+        return Ok(SubIndex::Complete);
+    }
+    let sub_index = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .map_or_else(|| s.parse(), |hex| u8::from_str_radix(hex, 16))
+        .map_err(|e| bad_request(format!("Invalid subindex {s}: {e}")))?;
+    Ok(SubIndex::Index(sub_index))
+}
+
+fn parse_data_type(s: &str) -> std::result::Result<SdoDataType, ApiError> {
+    SdoDataType::from_name(s).ok_or_else(|| bad_request(format!("Unknown data type {s}")))
+}
+
+fn value_to_json(value: SdoValue) -> Value {
+    match value {
+        SdoValue::Bool(v) => Value::from(v),
+        SdoValue::U8(v) => Value::from(v),
+        SdoValue::U16(v) => Value::from(v),
+        SdoValue::U32(v) => Value::from(v),
+        SdoValue::U64(v) => Value::from(v),
+        SdoValue::I8(v) => Value::from(v),
+        SdoValue::I16(v) => Value::from(v),
+        SdoValue::I32(v) => Value::from(v),
+        SdoValue::I64(v) => Value::from(v),
+        SdoValue::F32(v) => Value::from(v),
+        SdoValue::F64(v) => Value::from(v),
+        SdoValue::String(v) => Value::from(v),
+        SdoValue::Bytes(v) => Value::from(v),
+    }
+}
+
+fn value_from_json(data_type: SdoDataType, value: &Value) -> anyhow::Result<SdoValue> {
+    fn int<T: TryFrom<i128>>(value: &Value) -> anyhow::Result<T> {
+        let int = value
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| value.as_u64().map(i128::from))
+            .ok_or_else(|| anyhow::anyhow!("Expected an integer, got {value}"))?;
+        T::try_from(int).map_err(|_| anyhow::anyhow!("{int} is out of range"))
+    }
+    let float = || {
+        value
+            .as_f64()
+            .ok_or_else(|| anyhow::anyhow!("Expected a number, got {value}"))
+    };
+
+    Ok(match data_type {
+        SdoDataType::Bool => SdoValue::Bool(
+            value
+                .as_bool()
+                .ok_or_else(|| anyhow::anyhow!("Expected a bool, got {value}"))?,
+        ),
+        SdoDataType::U8 => SdoValue::U8(int(value)?),
+        SdoDataType::U16 => SdoValue::U16(int(value)?),
+        SdoDataType::U32 => SdoValue::U32(int(value)?),
+        SdoDataType::U64 => SdoValue::U64(int(value)?),
+        SdoDataType::I8 => SdoValue::I8(int(value)?),
+        SdoDataType::I16 => SdoValue::I16(int(value)?),
+        SdoDataType::I32 => SdoValue::I32(int(value)?),
+        SdoDataType::I64 => SdoValue::I64(int(value)?),
+        SdoDataType::F32 => SdoValue::F32(float()? as f32),
+        SdoDataType::F64 => SdoValue::F64(float()?),
+        SdoDataType::String => SdoValue::String(
+            value
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Expected a string, got {value}"))?
+                .to_string(),
+        ),
+        SdoDataType::Bytes => SdoValue::Bytes(serde_json::from_value(value.clone())?),
+    })
+}
+
+/// CoE object dictionary browser for the subdevice at `subdevice_index`
+///
+/// - `GET /{subdevice_index}/objects?from=0x8000&to=0x80FF` lists existing objects, at most
+///   [`MAX_PROBES_PER_REQUEST`] indices per request
+/// - `GET /{subdevice_index}/{index}/{sub_index}?data_type=u16` uploads a value
+/// - `PUT /{subdevice_index}/{index}/{sub_index}` with `{ "data_type": "u16", "value": 1 }`
+///   downloads a value
+///
+/// `sub_index` can be `complete` for complete access. Downloads are limited to
+/// [`ethercat_hal::sdo::SDO_MAX_DOWNLOAD_LEN`] bytes, `u64`, `i64`, `f64` and longer strings
+/// or byte arrays can only be uploaded.
+pub fn sdo_router() -> Router<Arc<SharedState>> {
+    Router::new()
+        .route("/{subdevice_index}/objects", get(get_objects_handler))
+        .route(
+            "/{subdevice_index}/{index}/{sub_index}",
+            get(get_value_handler).put(put_value_handler),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_addresses() {
+        assert_eq!(parse_u16("0x8010").ok(), Some(0x8010));
+        assert_eq!(parse_u16("4096").ok(), Some(0x1000));
+        assert!(parse_u16("0x10000").is_err());
+        assert!(matches!(parse_sub_index("0x11"), Ok(SubIndex::Index(0x11))));
+        assert!(matches!(
+            parse_sub_index("complete"),
+            Ok(SubIndex::Complete)
+        ));
+        assert!(parse_sub_index("256").is_err());
+    }
+
+    #[test]
+    fn test_value_from_json() {
+        assert_eq!(
+            value_from_json(SdoDataType::U16, &json!(500)).unwrap(),
+            SdoValue::U16(500)
+        );
+        assert_eq!(
+            value_from_json(SdoDataType::I8, &json!(-3)).unwrap(),
+            SdoValue::I8(-3)
+        );
+        assert!(value_from_json(SdoDataType::U8, &json!(256)).is_err());
+        assert!(value_from_json(SdoDataType::U8, &json!(-1)).is_err());
+        assert!(value_from_json(SdoDataType::Bool, &json!(1)).is_err());
+        assert_eq!(
+            value_from_json(SdoDataType::Bytes, &json!([1, 2])).unwrap(),
+            SdoValue::Bytes(vec![1, 2])
+        );
+        assert_eq!(
+            value_to_json(SdoValue::String("EL3204".to_string())),
+            json!("EL3204")
+        );
+    }
+}
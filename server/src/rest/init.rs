@@ -1,7 +1,8 @@
 use anyhow::Result;
 use axum::http::{HeaderValue, header};
-use axum::routing::post;
-use std::path::PathBuf;
+use axum::middleware::from_fn_with_state;
+use axum::routing::{get, post};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::thread;
 use tower_http::cors::CorsLayer;
@@ -13,10 +14,16 @@ use tracing::Level;
 use super::handlers::machine_mutation::post_machine_mutate;
 use super::handlers::write_machine_device_identification::post_write_machine_device_identification;
 use crate::app_state::SharedState;
+use crate::auth::Role;
+use crate::auth::middleware::require_role;
+use crate::config::config;
 use crate::rest::rest_api::rest_api_router;
 use crate::socketio::init::init_socketio;
 
-use crate::rest::handlers::metrics::metrics_router;
+use crate::rest::handlers::audit::audit_router;
+use crate::rest::handlers::auth::auth_router;
+use crate::rest::handlers::metrics::{get_openmetrics, metrics_router};
+use crate::rest::handlers::sdo::sdo_router;
 
 async fn init_api(app_state: Arc<SharedState>) -> Result<()> {
     let cors = CorsLayer::permissive();
@@ -27,29 +34,49 @@ async fn init_api(app_state: Arc<SharedState>) -> Result<()> {
         .on_request(DefaultOnRequest::new().level(Level::TRACE))
         .on_response(DefaultOnResponse::new().level(Level::TRACE));
 
+    // Mutation handlers check the per-mutation role on top of the route role
+    let viewer = from_fn_with_state((app_state.clone(), Role::Viewer), require_role);
+    let maintenance = from_fn_with_state((app_state.clone(), Role::Maintenance), require_role);
+
     let mut app = axum::Router::new()
         .route(
             "/api/v1/write_machine_device_identification",
-            post(post_write_machine_device_identification),
+            post(post_write_machine_device_identification).route_layer(maintenance.clone()),
+        )
+        .route(
+            "/api/v1/machine/mutate",
+            post(post_machine_mutate).route_layer(viewer.clone()),
+        )
+        .nest("/api/v1/auth", auth_router(&app_state))
+        .nest(
+            "/api/v1/audit",
+            audit_router().route_layer(maintenance.clone()),
         )
-        .route("/api/v1/machine/mutate", post(post_machine_mutate))
-        .nest("/api/v1/metrics", metrics_router())
-        .nest("/api/v2", rest_api_router());
+        .nest(
+            "/api/v1/metrics",
+            metrics_router().route_layer(viewer.clone()),
+        )
+        // authenticates itself, scrapers use a static token instead of a session
+        .route("/metrics", get(get_openmetrics))
+        .nest(
+            "/api/v1/ethercat/sdo",
+            sdo_router().route_layer(maintenance),
+        )
+        .nest("/api/v2", rest_api_router().route_layer(viewer));
 
-    // Serve the React UI bundle for browser/tablet access when QITECH_WEB_DIR
-    // points at a directory containing the Vite build output. SPA fallback
+    // Serve the React UI bundle for browser/tablet access when `network.web_dir`
+    // (or QITECH_WEB_DIR) points at the Vite build output. SPA fallback
     // routes unknown paths to index.html so client-side routing keeps working.
-    if let Ok(web_dir) = std::env::var("QITECH_WEB_DIR") {
-        let dir = PathBuf::from(&web_dir);
+    if let Some(dir) = &config().network.web_dir {
         let index = dir.join("index.html");
         if index.is_file() {
-            tracing::info!("Serving UI bundle from {}", web_dir);
-            let serve_dir = ServeDir::new(&dir).fallback(ServeFile::new(&index));
+            tracing::info!("Serving UI bundle from {}", dir.display());
+            let serve_dir = ServeDir::new(dir).fallback(ServeFile::new(&index));
             app = app.fallback_service(serve_dir);
         } else {
             tracing::warn!(
-                "QITECH_WEB_DIR={} has no index.html, skipping UI serving",
-                web_dir
+                "Web dir {} has no index.html, skipping UI serving",
+                dir.display()
             );
         }
     }
@@ -74,15 +101,20 @@ async fn init_api(app_state: Arc<SharedState>) -> Result<()> {
         .layer(no_cache)
         .with_state(app_state.clone());
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3001")
+    let addr = config().network.socket_addr();
+    let listener = tokio::net::TcpListener::bind(addr)
         .await
-        .expect("Failed to bind to port 3001");
+        .unwrap_or_else(|e| panic!("Failed to bind to {}: {}", addr, e));
 
-    tracing::info!("HTTP server running on 0.0.0.0:3001");
+    tracing::info!("HTTP server running on {}", addr);
 
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| anyhow::anyhow!("Server error: {}", e))
+    // the peer address is recorded in the audit log
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("Server error: {}", e))
 }
 
 /// Starts the API server in its own thread with a single-threaded Tokio runtime
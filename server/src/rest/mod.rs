@@ -1,5 +1,6 @@
 pub mod handlers;
 pub mod init;
+pub mod openapi;
 pub mod response;
 pub mod rest_api;
 pub mod util;
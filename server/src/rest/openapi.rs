@@ -0,0 +1,247 @@
+use axum::Json;
+use control_core::schema::validate::validate;
+use machines::machine_identification::MachineIdentification;
+use machines::registry::{MACHINE_REGISTRY, MachineSchemas};
+use serde_json::{Map, Value, json};
+
+use crate::rest::rest_api::REST_MACHINES;
+
+/// Checks a mutation against the schema of the machine before it is sent to the machine
+///
+/// Machines without a registered schema pass, sending to them fails later.
+pub fn validate_mutation(id: &MachineIdentification, mutation: &Value) -> anyhow::Result<()> {
+    let Some(schemas) = MACHINE_REGISTRY.schemas(id) else {
+        return Ok(());
+    };
+    validate(&schemas.mutation, mutation).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        anyhow::anyhow!("Invalid mutation for {}: {}", id.slug(), errors.join("; "))
+    })
+}
+
+pub async fn get_openapi() -> Json<Value> {
+    Json(openapi_document())
+}
+
+fn component_ref(slug: &str, name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}.{}", slug, name) })
+}
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": {
+            "type": "object",
+            "additionalProperties": { "type": "string" },
+        } } },
+    })
+}
+
+fn serial_parameter() -> Value {
+    json!({
+        "name": "serial",
+        "in": "path",
+        "required": true,
+        "schema": { "type": "integer", "minimum": 1, "maximum": u16::MAX },
+    })
+}
+
+fn machine_paths(slug: &str, paths: &mut Map<String, Value>) {
+    paths.insert(
+        format!("/api/v2/machine/{}/{{serial}}", slug),
+        json!({
+            "parameters": [serial_parameter()],
+            "get": {
+                "summary": format!("Current state and live values of a {}", slug),
+                "responses": {
+                    "200": {
+                        "description": "Current values",
+                        "content": { "application/json": { "schema": {
+                            "type": "object",
+                            "properties": {
+                                "machine": { "$ref": "#/components/schemas/Machine" },
+                                "state": component_ref(slug, "State"),
+                                "live_values": component_ref(slug, "LiveValues"),
+                            },
+                            "required": ["machine", "state", "live_values"],
+                        } } },
+                    },
+                    "404": error_response("Unknown machine"),
+                },
+            },
+            "post": {
+                "summary": format!("Send mutations to a {}", slug),
+                "description": "All mutations are validated and authorized before the first one is sent. The mutations are sent one after another, each once the machine applied the previous one.",
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": {
+                        "type": "array",
+                        "items": component_ref(slug, "Mutation"),
+                    } } },
+                },
+                "responses": {
+                    "200": {
+                        "description": "All mutations applied",
+                        "content": { "application/json": { "schema": {
+                            "type": "object",
+                            "properties": {
+                                "state": { "anyOf": [component_ref(slug, "State"), { "type": "null" }] },
+                            },
+                        } } },
+                    },
+                    "400": error_response("A mutation doesn't match the schema or the machine rejected it"),
+                    "403": error_response("The role of the session is insufficient"),
+                    "404": error_response("Unknown machine"),
+                    "504": error_response("The machine didn't apply a mutation in time"),
+                },
+            },
+        }),
+    );
+    paths.insert(
+        format!("/api/v2/machine/{}/{{serial}}/stream", slug),
+        json!({
+            "parameters": [serial_parameter()],
+            "get": {
+                "summary": "Stream signals at the cycle rate, see docs/rest-api.md for the format",
+                "parameters": [
+                    { "name": "signals", "in": "query", "required": true, "schema": { "type": "string" } },
+                    { "name": "duration_ms", "in": "query", "required": true, "schema": { "type": "integer", "minimum": 1 } },
+                    { "name": "decimation", "in": "query", "schema": { "type": "integer", "minimum": 1 } },
+                ],
+                "responses": {
+                    "200": {
+                        "description": "Binary stream",
+                        "content": { "application/octet-stream": {} },
+                    },
+                    "400": error_response("Unknown signal or invalid parameters"),
+                    "404": error_response("Unknown machine"),
+                },
+            },
+        }),
+    );
+    paths.insert(
+        format!("/api/v2/machine/{}/schema", slug),
+        json!({
+            "get": {
+                "summary": format!("JSON Schemas of the mutations, state and live values of a {}", slug),
+                "responses": { "200": {
+                    "description": "JSON Schemas",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "mutation": { "type": "object" },
+                            "state": { "type": "object" },
+                            "live_values": { "type": "object" },
+                        },
+                    } } },
+                } },
+            },
+        }),
+    );
+}
+
+fn machine_components(slug: &str, schemas: &MachineSchemas, components: &mut Map<String, Value>) {
+    components.insert(format!("{}.Mutation", slug), schemas.mutation.clone());
+    components.insert(format!("{}.State", slug), schemas.state.clone());
+    components.insert(format!("{}.LiveValues", slug), schemas.live_values.clone());
+}
+
+/// OpenAPI 3.1 document of `/api/v2`, generated from the machine schemas
+pub fn openapi_document() -> Value {
+    let mut paths = Map::new();
+    let mut components = Map::new();
+    components.insert(
+        "Machine".to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "legacy_id": { "type": "object" },
+                "serial": { "type": "integer" },
+                "vendor": { "type": "string" },
+                "slug": { "type": "string" },
+                "error": { "type": ["string", "null"] },
+            },
+        }),
+    );
+
+    paths.insert(
+        "/api/v2/machine".to_string(),
+        json!({
+            "get": {
+                "summary": "All machines",
+                "responses": { "200": {
+                    "description": "Machines",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "machines": { "type": "array", "items": { "$ref": "#/components/schemas/Machine" } },
+                        },
+                    } } },
+                } },
+            },
+        }),
+    );
+
+    for id in REST_MACHINES {
+        let Some(schemas) = MACHINE_REGISTRY.schemas(&id) else {
+            continue;
+        };
+        let slug = id.slug();
+        machine_paths(&slug, &mut paths);
+        machine_components(&slug, schemas, &mut components);
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "QiTech Control",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "security": [{ "bearer": [] }],
+        "paths": paths,
+        "components": {
+            "schemas": components,
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use machines::winder2::Winder2;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_mutation() {
+        let id = Winder2::MACHINE_IDENTIFICATION;
+        assert!(validate_mutation(&id, &json!({ "SetMode": "Pull" })).is_ok());
+        assert!(validate_mutation(&id, &json!("GotoTraverseHome")).is_ok());
+
+        let error = validate_mutation(&id, &json!({ "SetTraverseLimitOuter": "far" }))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Invalid mutation for winder_v1: /SetTraverseLimitOuter: expected number, found string"
+        );
+        let error = validate_mutation(&id, &json!({ "SetMode": "Run" }))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Invalid mutation for winder_v1: /SetMode: expected one of \"Standby\", \"Hold\", \"Pull\", \"Wind\", found \"Run\""
+        );
+    }
+
+    #[test]
+    fn test_openapi_document() {
+        let document = openapi_document();
+        let schemas = &document["components"]["schemas"];
+        assert_eq!(schemas["winder_v1.Mutation"]["title"], "Mutation");
+        assert!(schemas["winder_v1.State"]["properties"].is_object());
+        assert!(document["paths"]["/api/v2/machine/winder_v1/{serial}"]["post"].is_object());
+    }
+}
@@ -3,16 +3,24 @@ use serde_json::json;
 
 pub enum ApiError {
     ErrBadRequest(String),
+    ErrUnauthorized(String),
+    ErrForbidden(String),
     ErrNotFound(String),
     ErrInternal(String),
+    ErrTimeout(String),
 }
 
 impl axum::response::IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let json = match self {
             Self::ErrBadRequest(ref e) => serde_json::to_string(&json!({ "error_bad_request": e })),
+            Self::ErrUnauthorized(ref e) => {
+                serde_json::to_string(&json!({ "error_unauthorized": e }))
+            }
+            Self::ErrForbidden(ref e) => serde_json::to_string(&json!({ "error_forbidden": e })),
             Self::ErrNotFound(ref e) => serde_json::to_string(&json!({ "error_not_found": e })),
             Self::ErrInternal(ref e) => serde_json::to_string(&json!({ "error_internal": e })),
+            Self::ErrTimeout(ref e) => serde_json::to_string(&json!({ "error_timeout": e })),
         };
 
         let body = match json {
@@ -22,8 +30,11 @@ impl axum::response::IntoResponse for ApiError {
 
         let status = match self {
             Self::ErrBadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::ErrUnauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::ErrForbidden(_) => StatusCode::FORBIDDEN,
             Self::ErrNotFound(_) => StatusCode::NOT_FOUND,
             Self::ErrInternal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ErrTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
         };
 
         axum::response::Response::builder()
@@ -44,6 +55,14 @@ pub fn bad_request<E: ToString>(e: E) -> ApiError {
     ApiError::ErrBadRequest(e.to_string())
 }
 
+pub fn unauthorized<E: ToString>(e: E) -> ApiError {
+    ApiError::ErrUnauthorized(e.to_string())
+}
+
+pub fn forbidden<E: ToString>(e: E) -> ApiError {
+    ApiError::ErrForbidden(e.to_string())
+}
+
 pub fn not_found<E: ToString>(e: E) -> ApiError {
     ApiError::ErrNotFound(e.to_string())
 }
@@ -51,3 +70,7 @@ pub fn not_found<E: ToString>(e: E) -> ApiError {
 pub fn internal_error<E: ToString>(e: E) -> ApiError {
     ApiError::ErrInternal(e.to_string())
 }
+
+pub fn timeout<E: ToString>(e: E) -> ApiError {
+    ApiError::ErrTimeout(e.to_string())
+}
@@ -1,6 +1,11 @@
 use std::sync::Arc;
 
-use axum::extract::{Path, State};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router, debug_handler};
 use machines::MachineMessage;
@@ -11,13 +16,20 @@ use machines::ip20_test_machine::IP20TestMachine;
 use machines::laser::LaserMachine;
 use machines::machine_identification::{MachineIdentification, MachineIdentificationUnique};
 use machines::mock::MockMachine;
+use machines::registry::MACHINE_REGISTRY;
 use machines::test_machine::TestMachine;
 use machines::wago_power::WagoPower;
 use machines::winder2::Winder2;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 
 use crate::app_state::SharedState;
+use crate::audit::{AuditResult, MutationOrigin, record_rejected_mutation, send_audited_mutation};
+use crate::auth::middleware::authorize_mutation;
+use crate::auth::sessions::Session;
+use crate::rest::openapi::{get_openapi, validate_mutation};
 use crate::rest::response::*;
+use crate::streaming::start_stream;
 
 #[derive(Serialize, Debug, PartialEq)]
 struct MachineResponce {
@@ -118,26 +130,167 @@ async fn get_machine_handler(
 
 type PostMachineRequest = Vec<serde_json::Value>;
 
+#[derive(Serialize, Debug, PartialEq)]
+struct PostMachineResponce {
+    /// State after the last mutation, `None` if the machine didn't answer
+    state: Option<serde_json::Value>,
+}
+
 #[debug_handler]
 async fn post_machine_handler(
     Extension(id): Extension<MachineIdentification>,
+    Extension(session): Extension<Session>,
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
     State(shared_state): State<Arc<SharedState>>,
     Path(serial): Path<u16>,
     Json(request): Json<PostMachineRequest>,
-) -> Result<()> {
-    let id = MachineIdentificationUnique {
-        machine_identification: id,
+) -> Result<PostMachineResponce> {
+    let origin = MutationOrigin {
+        session: &session,
+        source: "rest_v2",
+        remote_addr: Some(remote_addr),
+    };
+    let unique_id = MachineIdentificationUnique {
+        machine_identification: id.clone(),
         serial,
     };
 
-    for value in request {
-        shared_state
-            .message_machine(&id, MachineMessage::HttpApiJsonRequest(value))
+    // check role and schema of all mutations before sending any
+    for value in &request {
+        if let Err(e) = authorize_mutation(&session, &id, value) {
+            record_rejected_mutation(
+                &shared_state,
+                &origin,
+                &unique_id,
+                value.clone(),
+                AuditResult::Denied(format!("Role {} insufficient", session.role)),
+            );
+            return Err(e);
+        }
+        if let Err(e) = validate_mutation(&id, value) {
+            record_rejected_mutation(
+                &shared_state,
+                &origin,
+                &unique_id,
+                value.clone(),
+                AuditResult::Invalid(e.to_string()),
+            );
+            return Err(bad_request(e));
+        }
+    }
+
+    // stop at the first mutation the machine doesn't apply, the error names its index and
+    // the earlier ones stay applied
+    let mut state = None;
+    for (index, value) in request.into_iter().enumerate() {
+        let outcome = send_audited_mutation(&shared_state, &origin, &unique_id, value)
             .await
             .map_err(not_found)?;
+        match outcome.result {
+            AuditResult::Applied => state = outcome.state,
+            AuditResult::Rejected(e) => {
+                return Err(bad_request(format!(
+                    "Mutation {} rejected, earlier mutations stay applied: {}",
+                    index, e
+                )));
+            }
+            AuditResult::Timeout => {
+                return Err(timeout(format!(
+                    "Mutation {} not applied within the timeout, earlier mutations stay applied",
+                    index
+                )));
+            }
+            result => {
+                return Err(internal_error(format!(
+                    "Mutation {} failed, earlier mutations stay applied: {:?}",
+                    index, result
+                )));
+            }
+        }
     }
 
-    json(())
+    json(PostMachineResponce { state })
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamQuery {
+    /// Comma separated signal names
+    signals: String,
+    duration_ms: u64,
+    #[serde(default = "default_decimation")]
+    decimation: u32,
+}
+
+const fn default_decimation() -> u32 {
+    1
+}
+
+/// Streams the selected signals at the cycle rate as `application/octet-stream`
+///
+/// See [`crate::streaming`] for the format.
+#[debug_handler]
+async fn stream_machine_handler(
+    Extension(id): Extension<MachineIdentification>,
+    State(shared_state): State<Arc<SharedState>>,
+    Path(serial): Path<u16>,
+    Query(query): Query<StreamQuery>,
+) -> std::result::Result<Response, ApiError> {
+    let id = MachineIdentificationUnique {
+        machine_identification: id,
+        serial,
+    };
+    if !shared_state.api_machines.lock().await.contains_key(&id) {
+        return Err(not_found("Unknown machine!"));
+    }
+
+    let signals = query
+        .signals
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    let stream = start_stream(
+        id,
+        signals,
+        query.decimation,
+        Duration::from_millis(query.duration_ms),
+    )
+    .map_err(bad_request)?;
+    stream.wait_running().await.map_err(bad_request)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        Body::from_stream(stream.into_body()),
+    )
+        .into_response())
+}
+
+/// Machines with routes under `/api/v2/machine/<slug>`
+pub const REST_MACHINES: [MachineIdentification; 9] = [
+    LaserMachine::MACHINE_IDENTIFICATION,
+    Winder2::MACHINE_IDENTIFICATION,
+    MockMachine::MACHINE_IDENTIFICATION,
+    ExtruderV2::MACHINE_IDENTIFICATION,
+    AquaPathV1::MACHINE_IDENTIFICATION,
+    TestMachine::MACHINE_IDENTIFICATION,
+    WagoPower::MACHINE_IDENTIFICATION,
+    IP20TestMachine::MACHINE_IDENTIFICATION,
+    AnalogInputTestMachine::MACHINE_IDENTIFICATION,
+];
+
+#[debug_handler]
+async fn get_machine_schema_handler(
+    Extension(id): Extension<MachineIdentification>,
+) -> Result<serde_json::Value> {
+    let schemas = MACHINE_REGISTRY
+        .schemas(&id)
+        .ok_or_else(|| not_found("Machine is not available in this build"))?;
+    json(serde_json::json!({
+        "mutation": schemas.mutation,
+        "state": schemas.state,
+        "live_values": schemas.live_values,
+    }))
 }
 
 fn make_machine_router(id: MachineIdentification) -> Router<Arc<SharedState>> {
@@ -146,21 +299,19 @@ fn make_machine_router(id: MachineIdentification) -> Router<Arc<SharedState>> {
     Router::new()
         .route(&path, get(get_machine_handler))
         .route(&path, post(post_machine_handler))
+        .route(&format!("{path}/stream"), get(stream_machine_handler))
+        .route(
+            &format!("/machine/{slug}/schema"),
+            get(get_machine_schema_handler),
+        )
         .layer(Extension(id))
 }
 
 pub fn rest_api_router() -> Router<Arc<SharedState>> {
-    Router::new()
-        .route("/machine", get(get_machines_handler))
-        .merge(make_machine_router(LaserMachine::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(Winder2::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(MockMachine::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(ExtruderV2::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(AquaPathV1::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(TestMachine::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(WagoPower::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(IP20TestMachine::MACHINE_IDENTIFICATION))
-        .merge(make_machine_router(
-            AnalogInputTestMachine::MACHINE_IDENTIFICATION,
-        ))
+    REST_MACHINES.into_iter().fold(
+        Router::new()
+            .route("/machine", get(get_machines_handler))
+            .route("/openapi.json", get(get_openapi)),
+        |router, id| router.merge(make_machine_router(id)),
+    )
 }
@@ -41,6 +41,36 @@ impl ResponseUtil {
             .unwrap()
     }
 
+    pub fn bad_request(message: &str) -> Response<Body> {
+        let json = match serde_json::to_string(&json!({ "error": message })) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize bad request message: {}", e);
+                return Self::error("Failed to serialize bad request message");
+            }
+        };
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("Content-Type", "application/json")
+            .body(Body::from(json))
+            .unwrap()
+    }
+
+    pub fn gateway_timeout(message: &str) -> Response<Body> {
+        let json = match serde_json::to_string(&json!({ "error": message })) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize timeout message: {}", e);
+                return Self::error("Failed to serialize timeout message");
+            }
+        };
+        Response::builder()
+            .status(StatusCode::GATEWAY_TIMEOUT)
+            .header("Content-Type", "application/json")
+            .body(Body::from(json))
+            .unwrap()
+    }
+
     pub fn not_found(message: &str) -> Response<Body> {
         let json = match serde_json::to_string(&json!({ "error": message })) {
             Ok(json) => json,
@@ -60,6 +90,8 @@ impl ResponseUtil {
 pub enum ResponseUtilError {
     Error(anyhow::Error),
     NotFound(anyhow::Error),
+    BadRequest(anyhow::Error),
+    Timeout(anyhow::Error),
 }
 
 impl From<ResponseUtilError> for Response<Body> {
@@ -67,6 +99,8 @@ impl From<ResponseUtilError> for Response<Body> {
         match error {
             ResponseUtilError::Error(e) => ResponseUtil::error(&e.to_string()),
             ResponseUtilError::NotFound(e) => ResponseUtil::not_found(&e.to_string()),
+            ResponseUtilError::BadRequest(e) => ResponseUtil::bad_request(&e.to_string()),
+            ResponseUtilError::Timeout(e) => ResponseUtil::gateway_timeout(&e.to_string()),
         }
     }
 }
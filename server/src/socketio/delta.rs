@@ -0,0 +1,107 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// One operation of a JSON patch (RFC 6902), only the operations [`diff`] produces
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Operations that turn `old` into `new`
+///
+/// Objects are compared key by key, arrays element by element if their length didn't change,
+/// otherwise they are replaced as a whole.
+pub fn diff(old: &Value, new: &Value) -> Vec<PatchOperation> {
+    let mut operations = vec![];
+    diff_at(String::new(), old, new, &mut operations);
+    operations
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, operations: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_objects(&path, old, new, operations),
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                diff_at(format!("{}/{}", path, index), old, new, operations);
+            }
+        }
+        (old, new) if old != new => operations.push(PatchOperation::Replace {
+            path,
+            value: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_objects(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    operations: &mut Vec<PatchOperation>,
+) {
+    for (key, old_value) in old {
+        let path = format!("{}/{}", path, escape(key));
+        match new.get(key) {
+            Some(new_value) => diff_at(path, old_value, new_value, operations),
+            None => operations.push(PatchOperation::Remove { path }),
+        }
+    }
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            operations.push(PatchOperation::Add {
+                path: format!("{}/{}", path, escape(key)),
+                value: new_value.clone(),
+            });
+        }
+    }
+}
+
+/// JSON pointer escaping of a key (RFC 6901)
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = json!({
+            "mode": "Standby",
+            "speed": { "target": 1.0, "max": 5.0 },
+            "axes": [1, 2, 3],
+            "names": ["a"],
+            "removed": true,
+            "a/b": 1,
+        });
+        let new = json!({
+            "mode": "Running",
+            "speed": { "target": 1.0, "max": 5.0 },
+            "axes": [1, 4, 3],
+            "names": ["a", "b"],
+            "added": null,
+            "a/b": 2,
+        });
+
+        let patch = serde_json::to_value(diff(&old, &new)).unwrap();
+        assert_eq!(
+            patch,
+            json!([
+                { "op": "replace", "path": "/a~1b", "value": 2 },
+                { "op": "replace", "path": "/axes/1", "value": 4 },
+                { "op": "replace", "path": "/mode", "value": "Running" },
+                { "op": "replace", "path": "/names", "value": ["a", "b"] },
+                { "op": "remove", "path": "/removed" },
+                { "op": "add", "path": "/added", "value": null },
+            ])
+        );
+
+        assert!(diff(&new, &new).is_empty());
+    }
+}
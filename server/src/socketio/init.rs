@@ -1,7 +1,10 @@
 use super::namespace_id::NamespaceId;
+use super::subscription::Subscription;
 use crate::app_state::SharedState;
+use crate::auth::middleware::socketio_auth;
 use socketioxide::ParserConfig;
-use socketioxide::extract::SocketRef;
+use socketioxide::extract::{SocketRef, TryData};
+use socketioxide::handler::ConnectHandler;
 use socketioxide::layer::SocketIoLayer;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -17,18 +20,23 @@ pub async fn init_socketio(app_state: Arc<SharedState>) -> SocketIoLayer {
     let app_state_main = app_state.clone();
 
     // set the on connect handler for main namespace
-    io.ns("/main", move |socket: SocketRef| {
-        handle_socket_connection(socket, app_state_main.clone());
-    });
+    io.ns(
+        "/main",
+        (move |socket: SocketRef| {
+            handle_socket_connection(socket, app_state_main.clone());
+        })
+        .with(socketio_auth(app_state.clone())),
+    );
 
     // Clone app_state for the second handler
     let app_state_machine = app_state.clone();
 
     if let Err(err) = io.dyn_ns(
         "/machine/{vendor}/{machine}/{serial}",
-        move |socket: SocketRef| {
+        (move |socket: SocketRef| {
             handle_socket_connection(socket, app_state_machine.clone());
-        },
+        })
+        .with(socketio_auth(app_state.clone())),
     ) {
         tracing::error!("Failed to detect machine namespace: {}", err);
     }
@@ -52,6 +60,9 @@ fn handle_socket_connection(socket: SocketRef, app_state: Arc<SharedState>) {
     // Setup disconnection handler
     setup_disconnection(socket.clone(), namespace_id.clone(), app_state.clone());
 
+    // Setup subscribe handler
+    setup_subscription(socket.clone(), namespace_id.clone(), app_state.clone());
+
     // Setup connection
     setup_connection(socket, namespace_id, app_state);
 }
@@ -60,6 +71,7 @@ fn setup_disconnection(socket: SocketRef, namespace_id: NamespaceId, app_state:
     socket.on_disconnect(move |socket: SocketRef| {
         let namespace_id = namespace_id.clone();
         let app_state = app_state.clone();
+        app_state.socketio_setup.subscriptions.remove(&socket);
 
         // Spawn async task to avoid blocking
         smol::spawn(async move {
@@ -104,6 +116,49 @@ fn setup_disconnection(socket: SocketRef, namespace_id: NamespaceId, app_state:
     });
 }
 
+/// Handles the `subscribe` message, which limits the events and fields a socket receives
+///
+/// The cached events are sent again, so the client starts from a full state.
+fn setup_subscription(socket: SocketRef, namespace_id: NamespaceId, app_state: Arc<SharedState>) {
+    socket.on(
+        "subscribe",
+        move |socket: SocketRef, TryData(subscription): TryData<Subscription>| {
+            let subscription = match subscription {
+                Ok(subscription) => subscription,
+                Err(err) => {
+                    tracing::warn!(
+                        "Invalid subscription socket={:?} namespace={} error={}",
+                        socket.id,
+                        namespace_id,
+                        err
+                    );
+                    return;
+                }
+            };
+            tracing::info!(
+                "Socket subscribed socket={:?} namespace={} subscription={:?}",
+                socket.id,
+                namespace_id,
+                subscription
+            );
+            app_state
+                .socketio_setup
+                .subscriptions
+                .set(&socket, subscription);
+
+            let namespace_id = namespace_id.clone();
+            let app_state = app_state.clone();
+            smol::spawn(async move {
+                let mut namespaces_guard = app_state.socketio_setup.namespaces.write().await;
+                if let Ok(namespace) = namespaces_guard.apply_mut(namespace_id).await {
+                    namespace.reemit(socket);
+                }
+            })
+            .detach();
+        },
+    );
+}
+
 fn setup_connection(socket: SocketRef, namespace_id: NamespaceId, app_state: Arc<SharedState>) {
     let socket_clone = socket.clone();
     let namespace_id_clone = namespace_id.clone();
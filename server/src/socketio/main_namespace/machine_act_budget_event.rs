@@ -0,0 +1,32 @@
+use control_core::socketio::event::Event;
+use machines::machine_identification::MachineIdentificationUnique;
+use serde::{Deserialize, Serialize};
+
+use crate::r#loop::MachineExecutor;
+
+/// Machines whose `act` took longer than their budget
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MachineActBudgetEvent {
+    pub machines: Vec<MachineActBudgetObj>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MachineActBudgetObj {
+    pub machine_identification_unique: MachineIdentificationUnique,
+    pub executor: MachineExecutor,
+    pub budget_ns: u64,
+    /// Longest `act` in the last supervision interval
+    pub max_act_ns: u64,
+    /// Overruns since the machine was added
+    pub overruns: u64,
+}
+
+pub struct MachineActBudgetEventBuilder();
+
+impl MachineActBudgetEventBuilder {
+    const NAME: &'static str = "MachineActBudgetEvent";
+
+    pub fn build(&self, machines: Vec<MachineActBudgetObj>) -> Event<MachineActBudgetEvent> {
+        Event::new(Self::NAME, MachineActBudgetEvent { machines })
+    }
+}
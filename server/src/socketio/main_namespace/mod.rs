@@ -2,17 +2,21 @@ use std::sync::Arc;
 
 use control_core::socketio::{
     event::{Event, GenericEvent},
-    namespace::{CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, cache_one_event},
+    namespace::{
+        CacheFn, CacheableEvents, Namespace, NamespaceCacheingLogic, SocketQueueItem,
+        cache_one_event,
+    },
 };
 use ethercat_devices_event::EthercatDevicesEvent;
 use ethercat_interface_discovery_event::EthercatInterfaceDiscoveryEvent;
+use machine_act_budget_event::MachineActBudgetEvent;
 use machines_event::MachinesEvent;
 use smol::channel::Sender;
-use socketioxide::extract::SocketRef;
 use tracing::instrument;
 
 pub mod ethercat_devices_event;
 pub mod ethercat_interface_discovery_event;
+pub mod machine_act_budget_event;
 pub mod machines_event;
 
 pub struct MainRoom {
@@ -20,7 +24,7 @@ pub struct MainRoom {
 }
 
 impl MainRoom {
-    pub fn new(socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>) -> Self {
+    pub fn new(socket_queue_tx: Sender<SocketQueueItem>) -> Self {
         Self {
             namespace: Namespace::new(socket_queue_tx),
         }
@@ -44,6 +48,7 @@ pub enum MainNamespaceEvents {
     MachinesEvent(Event<MachinesEvent>),
     EthercatDevicesEvent(Event<EthercatDevicesEvent>),
     EthercatInterfaceDiscoveryEvent(Event<EthercatInterfaceDiscoveryEvent>),
+    MachineActBudgetEvent(Event<MachineActBudgetEvent>),
 }
 
 impl CacheableEvents<Self> for MainNamespaceEvents {
@@ -52,6 +57,7 @@ impl CacheableEvents<Self> for MainNamespaceEvents {
             Self::EthercatDevicesEvent(event) => event.into(),
             Self::EthercatInterfaceDiscoveryEvent(event) => event.into(),
             Self::MachinesEvent(event) => event.into(),
+            Self::MachineActBudgetEvent(event) => event.into(),
         }
     }
 
@@ -60,6 +66,7 @@ impl CacheableEvents<Self> for MainNamespaceEvents {
             Self::EthercatDevicesEvent(_) => cache_one_event(),
             Self::EthercatInterfaceDiscoveryEvent(_) => cache_one_event(),
             Self::MachinesEvent(_) => cache_one_event(),
+            Self::MachineActBudgetEvent(_) => cache_one_event(),
         }
     }
 }
@@ -1,5 +1,7 @@
+pub mod delta;
 pub mod init;
 pub mod main_namespace;
 pub mod namespace_id;
 pub mod namespaces;
 pub mod queue;
+pub mod subscription;
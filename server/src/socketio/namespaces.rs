@@ -1,8 +1,7 @@
 use super::{main_namespace::MainRoom, namespace_id::NamespaceId};
-use control_core::socketio::event::GenericEvent;
+use control_core::socketio::namespace::SocketQueueItem;
 use smol::channel::Sender;
-use socketioxide::extract::SocketRef;
-use std::{collections::HashMap, sync::Arc};
+use std::collections::HashMap;
 
 pub struct Namespaces {
     pub main_namespace: MainRoom,
@@ -27,7 +26,7 @@ impl Namespaces {
         }
     }
 
-    pub fn new(socket_queue_tx: Sender<(SocketRef, Arc<GenericEvent>)>) -> Self {
+    pub fn new(socket_queue_tx: Sender<SocketQueueItem>) -> Self {
         Self {
             main_namespace: MainRoom::new(socket_queue_tx),
             machine_namespaces: HashMap::new(),
@@ -1,74 +1,392 @@
 use crate::app_state::SharedState;
-use std::{sync::Arc, time::Instant};
+use crate::config::config;
+use crate::metrics::socketio::socketio_metrics;
+use crate::socketio::subscription::{
+    DeltaEncoder, EncodedEvent, SocketSubscriptions, Subscription,
+};
+use control_core::socketio::event::GenericEvent;
+use serde::Serialize;
+use smol::Timer;
+use smol::channel::{Receiver, Sender};
+use socketioxide::extract::SocketRef;
+use socketioxide::socket::Sid;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, info, instrument, trace};
 
-/// Send a single event with retry logic
+/// How an event is queued for a socket that can't keep up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPolicy {
+    /// A newer event with the same name replaces the queued one, the client only needs the latest
+    /// snapshot
+    Coalesce,
+    /// Dropped if the socket queue is full
+    DropWhenFull,
+    /// Always queued, up to the hard limit of the socket queue
+    Keep,
+}
+
+impl DeliveryPolicy {
+    pub fn for_event(name: &str, replay: bool) -> Self {
+        if replay {
+            // the cache is bounded and the client needs all of it, e.g. for graphs
+            return Self::Keep;
+        }
+        match name {
+            "StateEvent"
+            | "LiveValuesEvent"
+            | "MachinesEvent"
+            | "EthercatDevicesEvent"
+            | "EthercatInterfaceDiscoveryEvent"
+            | "MachineActBudgetEvent" => Self::Coalesce,
+            _ => Self::DropWhenFull,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueuedEvent {
+    event: Arc<GenericEvent>,
+    policy: DeliveryPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PushResult {
+    Queued,
+    Coalesced,
+    Dropped,
+    /// The queue reached its hard limit, the socket has to be disconnected
+    Overflow,
+}
+
+/// Events waiting for one socket, sent by its own writer task
+///
+/// A slow socket only fills its own queue, the fan-out never waits for it.
+#[derive(Debug)]
+struct SocketOutbox {
+    socket: SocketRef,
+    events: Mutex<VecDeque<QueuedEvent>>,
+    capacity: usize,
+    max_len: usize,
+    /// Wakes the writer, holds at most one pending wake up
+    wake_tx: Sender<()>,
+}
+
+impl SocketOutbox {
+    fn push(&self, event: Arc<GenericEvent>, policy: DeliveryPolicy) -> PushResult {
+        let result = {
+            let mut events = self.events.lock().expect("Outbox lock poisoned");
+            push_event(&mut events, self.capacity, self.max_len, event, policy)
+        };
+        if result == PushResult::Queued {
+            // a full channel means the writer is already woken
+            let _ = self.wake_tx.try_send(());
+        }
+        result
+    }
+
+    fn pop(&self) -> Option<QueuedEvent> {
+        self.events
+            .lock()
+            .expect("Outbox lock poisoned")
+            .pop_front()
+    }
+
+    /// Removes all events, returns how many were queued
+    fn clear(&self) -> usize {
+        let mut events = self.events.lock().expect("Outbox lock poisoned");
+        let len = events.len();
+        events.clear();
+        len
+    }
+
+    /// Stops the writer and disconnects the socket, the client reconnects and gets the cached
+    /// events again
+    fn disconnect(&self) {
+        self.wake_tx.close();
+        socketio_metrics().sub_queued(self.clear() as u64);
+        let _ = self.socket.clone().disconnect();
+    }
+
+    /// The writer stopped because the socket disconnected
+    fn is_closed(&self) -> bool {
+        self.wake_tx.is_closed()
+    }
+}
+
+fn push_event(
+    events: &mut VecDeque<QueuedEvent>,
+    capacity: usize,
+    max_len: usize,
+    event: Arc<GenericEvent>,
+    policy: DeliveryPolicy,
+) -> PushResult {
+    match policy {
+        DeliveryPolicy::Coalesce => {
+            if let Some(queued) = events
+                .iter_mut()
+                .find(|q| q.policy == DeliveryPolicy::Coalesce && q.event.name == event.name)
+            {
+                // keep the position, events of other names queued after it stay in order
+                queued.event = event;
+                return PushResult::Coalesced;
+            }
+        }
+        DeliveryPolicy::DropWhenFull => {
+            if events.len() >= capacity {
+                return PushResult::Dropped;
+            }
+        }
+        DeliveryPolicy::Keep => {}
+    }
+    if events.len() >= max_len {
+        return PushResult::Overflow;
+    }
+    events.push_back(QueuedEvent { event, policy });
+    PushResult::Queued
+}
+
+/// Send a single event, waits while the send buffer of the socket is full
+///
+/// Returns `false` if the socket is gone.
 #[instrument(skip_all)]
-async fn send_event_with_retry(
-    socket: &socketioxide::extract::SocketRef,
-    event: &Arc<control_core::socketio::event::GenericEvent>,
-) {
-    // retry loop for each event
+async fn send_event<T: Serialize + Sync + ?Sized>(
+    socket: &SocketRef,
+    name: &str,
+    event: &T,
+    retry_interval: Duration,
+) -> bool {
     loop {
-        // check if socket is still connected
         if !socket.connected() {
             trace!(
                 socket_id = ?socket.id,
-                event = %event.name,
+                event = %name,
                 "Socket disconnected, skipping event"
             );
-            break; // Exit the loop if the socket is not connected
+            return false;
         }
 
-        match socket.emit("event", event.as_ref()) {
+        match socket.emit("event", event) {
             Ok(_) => {
                 trace!(
                     socket_id = ?socket.id,
-                    event = %event.name,
-                    timestamp = event.ts,
+                    event = %name,
                     "Successfully emitted event"
                 );
-                break; // Successfully emitted, exit loop
+                socketio_metrics()
+                    .sent_total
+                    .fetch_add(1, Ordering::Relaxed);
+                return true;
             }
-            Err(e) => match e {
-                socketioxide::SendError::Serialize(serialize_error) => {
-                    trace!(
-                        socket_id = ?socket.id,
-                        event = %event.name,
-                        error = %serialize_error,
-                        "Serialization error, skipping event"
-                    );
-                    break; // no reason in retrying serialization errors
-                }
-                socketioxide::SendError::Socket(socket_error) => match socket_error {
-                    socketioxide::SocketError::InternalChannelFull => {
-                        continue; // Retry sending the event
-                    }
-                    socketioxide::SocketError::Closed => {
+            Err(socketioxide::SendError::Serialize(serialize_error)) => {
+                trace!(
+                    socket_id = ?socket.id,
+                    event = %name,
+                    error = %serialize_error,
+                    "Serialization error, skipping event"
+                );
+                return true; // no reason in retrying serialization errors
+            }
+            Err(socketioxide::SendError::Socket(
+                socketioxide::SocketError::InternalChannelFull,
+            )) => {
+                // newer events for this socket are coalesced or dropped meanwhile
+                socketio_metrics()
+                    .retries_total
+                    .fetch_add(1, Ordering::Relaxed);
+                Timer::after(retry_interval).await;
+            }
+            Err(socketioxide::SendError::Socket(socketioxide::SocketError::Closed)) => {
+                trace!(
+                    socket_id = ?socket.id,
+                    event = %name,
+                    "Socket closed, skipping event"
+                );
+                return false;
+            }
+        }
+    }
+}
+
+/// Settings shared by all socket writers
+#[derive(Debug, Clone)]
+struct WriterSettings {
+    capacity: usize,
+    max_len: usize,
+    retry_interval: Duration,
+    delta_snapshot_interval: Duration,
+    subscriptions: Arc<SocketSubscriptions>,
+}
+
+async fn socket_writer(outbox: Arc<SocketOutbox>, wake_rx: Receiver<()>, settings: WriterSettings) {
+    let socket = &outbox.socket;
+    let mut encoder = DeltaEncoder::new(settings.delta_snapshot_interval);
+    let mut subscription: Option<Arc<Subscription>> = None;
+
+    while wake_rx.recv().await.is_ok() {
+        while let Some(queued) = outbox.pop() {
+            socketio_metrics().sub_queued(1);
+            let event = &queued.event;
+
+            let current = settings.subscriptions.get(socket);
+            if !ptr_eq(&current, &subscription) {
+                // the client gets the cached events again after subscribing
+                encoder.reset();
+                subscription = current;
+            }
+            let encoded = match &subscription {
+                Some(subscription) => match encoder.encode(subscription, event) {
+                    Ok(encoded) => encoded,
+                    Err(e) => {
                         trace!(
                             socket_id = ?socket.id,
                             event = %event.name,
-                            "Socket closed, skipping event"
+                            error = %e,
+                            "Serialization error, skipping event"
                         );
-                        break; // Socket is closed, no point in retrying
+                        continue;
                     }
                 },
-            },
+                None => EncodedEvent::Original,
+            };
+
+            let sent = match encoded {
+                EncodedEvent::Original => {
+                    send_event(socket, &event.name, event.as_ref(), settings.retry_interval).await
+                }
+                EncodedEvent::Json(json) => {
+                    send_event(socket, &event.name, &json, settings.retry_interval).await
+                }
+                EncodedEvent::Skip => true,
+            };
+            if !sent {
+                return;
+            }
+        }
+    }
+}
+
+fn ptr_eq(a: &Option<Arc<Subscription>>, b: &Option<Arc<Subscription>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Outboxes by socket, a client has one socket per namespace with the same [`Sid`]
+#[derive(Debug)]
+struct Outboxes {
+    sockets: HashMap<Sid, Vec<Arc<SocketOutbox>>>,
+    settings: WriterSettings,
+}
+
+impl Outboxes {
+    fn get_or_spawn(&mut self, socket: &SocketRef) -> &Arc<SocketOutbox> {
+        let outboxes = self.sockets.entry(socket.id).or_default();
+        // the socket may have reconnected to the namespace in the same session
+        retain_open(outboxes);
+        if let Some(index) = outboxes.iter().position(|o| o.socket.ns() == socket.ns()) {
+            return &outboxes[index];
         }
+
+        let (wake_tx, wake_rx) = smol::channel::bounded(1);
+        let outbox = Arc::new(SocketOutbox {
+            socket: socket.clone(),
+            events: Mutex::new(VecDeque::new()),
+            capacity: self.settings.capacity,
+            max_len: self.settings.max_len,
+            wake_tx,
+        });
+        smol::spawn(socket_writer(
+            outbox.clone(),
+            wake_rx,
+            self.settings.clone(),
+        ))
+        .detach();
+        outboxes.push(outbox);
+        let index = outboxes.len() - 1;
+        &outboxes[index]
+    }
+
+    /// Removes the outboxes of disconnected sockets
+    fn prune(&mut self) {
+        self.sockets.retain(|_, outboxes| {
+            retain_open(outboxes);
+            !outboxes.is_empty()
+        });
     }
 }
 
+fn retain_open(outboxes: &mut Vec<Arc<SocketOutbox>>) {
+    outboxes.retain(|outbox| {
+        let closed = outbox.is_closed() || !outbox.socket.connected();
+        if closed {
+            // stops a writer that is waiting for events
+            outbox.wake_tx.close();
+            socketio_metrics().sub_queued(outbox.clear() as u64);
+        }
+        !closed
+    });
+}
+
+/// Distributes the events of all namespaces to the per socket queues
 pub async fn socketio_queue_worker(app_state: &SharedState) {
     tracing::info!("SocketIO global queue listener started");
+    let socketio_config = &config().socketio;
+    let subscriptions = app_state.socketio_setup.subscriptions.clone();
+    let mut outboxes = Outboxes {
+        sockets: HashMap::new(),
+        settings: WriterSettings {
+            capacity: socketio_config.socket_queue_capacity,
+            max_len: socketio_config.socket_queue_max_len,
+            retry_interval: socketio_config.retry_interval(),
+            delta_snapshot_interval: socketio_config.delta_snapshot_interval(),
+            subscriptions: subscriptions.clone(),
+        },
+    };
     let mut event_count = 0;
     let mut batch_start = Instant::now();
 
-    while let Ok((socket, event)) = app_state.socketio_setup.socket_queue_rx.recv().await {
+    while let Ok(item) = app_state.socketio_setup.socket_queue_rx.recv().await {
         event_count += 1;
 
-        send_event_with_retry(&socket, &event).await;
+        let wanted = subscriptions
+            .get(&item.socket)
+            .is_none_or(|subscription| subscription.wants(&item.event.name));
+        if wanted && item.socket.connected() {
+            let policy = DeliveryPolicy::for_event(&item.event.name, item.replay);
+            let outbox = outboxes.get_or_spawn(&item.socket);
+            match outbox.push(item.event.clone(), policy) {
+                PushResult::Queued => socketio_metrics().add_queued(1),
+                PushResult::Coalesced => socketio_metrics().record_coalesced(&item.event.name),
+                PushResult::Dropped => {
+                    trace!(
+                        socket_id = ?item.socket.id,
+                        event = %item.event.name,
+                        "Socket queue full, dropping event"
+                    );
+                    socketio_metrics().record_dropped(&item.event.name);
+                }
+                PushResult::Overflow => {
+                    tracing::warn!(
+                        "[{}::socketio_queue_worker] Socket {} queued {} events, disconnecting",
+                        module_path!(),
+                        item.socket.id,
+                        socketio_config.socket_queue_max_len
+                    );
+                    socketio_metrics()
+                        .overflow_disconnects_total
+                        .fetch_add(1, Ordering::Relaxed);
+                    outbox.disconnect();
+                }
+            }
+        }
 
         if batch_start.elapsed().as_secs() >= 5 {
+            outboxes.prune();
             let elapsed = batch_start.elapsed();
             if event_count > 0 {
                 debug!(
@@ -95,3 +413,119 @@ pub async fn start_socketio_queue(app_state: Arc<SharedState>) {
         tracing::error!("Restarting SocketIO...");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, ts: u64) -> Arc<GenericEvent> {
+        Arc::new(GenericEvent {
+            name: name.to_string(),
+            data: Box::new(ts),
+            ts,
+        })
+    }
+
+    fn queued(events: &VecDeque<QueuedEvent>) -> Vec<(&str, u64)> {
+        events
+            .iter()
+            .map(|q| (q.event.name.as_str(), q.event.ts))
+            .collect()
+    }
+
+    #[test]
+    fn test_push_event() {
+        let mut events = VecDeque::new();
+        let push = |events: &mut VecDeque<QueuedEvent>, name: &str, ts: u64, replay: bool| {
+            push_event(
+                events,
+                2,
+                5,
+                event(name, ts),
+                DeliveryPolicy::for_event(name, replay),
+            )
+        };
+
+        // replays are kept even if they have the same name
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 0, true),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 1, true),
+            PushResult::Queued
+        );
+        // the first live emit is queued, later ones replace it
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 2, false),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "StateEvent", 3, false),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 4, false),
+            PushResult::Coalesced
+        );
+        // queue is over capacity, droppable events are dropped
+        assert_eq!(
+            push(&mut events, "DebugPtoEvent", 5, false),
+            PushResult::Dropped
+        );
+
+        assert_eq!(
+            queued(&events),
+            vec![
+                ("LiveValuesEvent", 0),
+                ("LiveValuesEvent", 1),
+                ("LiveValuesEvent", 4),
+                ("StateEvent", 3),
+            ]
+        );
+
+        events.clear();
+        assert_eq!(
+            push(&mut events, "DebugPtoEvent", 6, false),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "DebugPtoEvent", 7, false),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "DebugPtoEvent", 8, false),
+            PushResult::Dropped
+        );
+        assert_eq!(
+            queued(&events),
+            vec![("DebugPtoEvent", 6), ("DebugPtoEvent", 7)]
+        );
+
+        // replays and new coalesced names overflow at the hard limit, coalescing still works
+        events.clear();
+        for ts in 0..4 {
+            assert_eq!(
+                push(&mut events, "LiveValuesEvent", ts, true),
+                PushResult::Queued
+            );
+        }
+        assert_eq!(
+            push(&mut events, "StateEvent", 4, false),
+            PushResult::Queued
+        );
+        assert_eq!(
+            push(&mut events, "StateEvent", 5, false),
+            PushResult::Coalesced
+        );
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 6, true),
+            PushResult::Overflow
+        );
+        assert_eq!(
+            push(&mut events, "LiveValuesEvent", 7, false),
+            PushResult::Overflow
+        );
+        assert_eq!(events.len(), 5);
+    }
+}
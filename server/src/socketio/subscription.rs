@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use control_core::socketio::event::GenericEvent;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use socketioxide::extract::SocketRef;
+use socketioxide::socket::Sid;
+
+use super::delta::diff;
+
+/// Events sent as patches against the last sent event when [`Subscription::delta`] is set
+const DELTA_EVENTS: [&str; 1] = ["StateEvent"];
+
+/// What a socket receives from its namespace, sent by the client as `subscribe` message
+///
+/// Sockets without a subscription receive every event in full.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Subscription {
+    /// Event names, all events if unset
+    pub events: Option<Vec<String>>,
+    /// Fields by event name as dotted paths, e.g. `{"LiveValuesEvent": ["temperatures.nozzle"]}`
+    pub fields: HashMap<String, Vec<String>>,
+    /// Send a `StateEvent` as `StateEventPatch` with JSON patch operations against the last one
+    pub delta: bool,
+}
+
+impl Subscription {
+    pub fn wants(&self, event: &str) -> bool {
+        self.events
+            .as_ref()
+            .is_none_or(|events| events.iter().any(|e| e == event))
+    }
+}
+
+/// Event as it is sent to one socket
+#[derive(Debug)]
+pub enum EncodedEvent {
+    /// Nothing to change, the event is sent as it is
+    Original,
+    Json(Value),
+    /// Nothing changed since the last event
+    Skip,
+}
+
+/// Last sent value of the delta events of one socket
+#[derive(Debug)]
+pub struct DeltaEncoder {
+    snapshot_interval: Duration,
+    /// Last sent data and when it was last sent in full, by event name
+    last: HashMap<String, (Value, Instant)>,
+}
+
+impl DeltaEncoder {
+    pub fn new(snapshot_interval: Duration) -> Self {
+        Self {
+            snapshot_interval,
+            last: HashMap::new(),
+        }
+    }
+
+    /// Forget the sent events, the next ones are sent in full
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+
+    pub fn encode(
+        &mut self,
+        subscription: &Subscription,
+        event: &GenericEvent,
+    ) -> serde_json::Result<EncodedEvent> {
+        let fields = subscription.fields.get(&event.name);
+        let delta = subscription.delta && DELTA_EVENTS.contains(&event.name.as_str());
+        if fields.is_none() && !delta {
+            return Ok(EncodedEvent::Original);
+        }
+
+        let mut data = serde_json::to_value(&event.data)?;
+        if let Some(fields) = fields {
+            data = project(&data, fields);
+        }
+        if !delta {
+            return Ok(EncodedEvent::Json(event_json(&event.name, data, event.ts)));
+        }
+
+        let now = Instant::now();
+        if let Some((last, last_full)) = self.last.get_mut(&event.name) {
+            if now.duration_since(*last_full) < self.snapshot_interval {
+                let patch = diff(last, &data);
+                if patch.is_empty() {
+                    return Ok(EncodedEvent::Skip);
+                }
+                *last = data;
+                let name = format!("{}Patch", event.name);
+                return Ok(EncodedEvent::Json(event_json(
+                    &name,
+                    serde_json::to_value(patch)?,
+                    event.ts,
+                )));
+            }
+        }
+
+        // periodic full snapshot, the client recovers if it missed a patch
+        self.last.insert(event.name.clone(), (data.clone(), now));
+        Ok(EncodedEvent::Json(event_json(&event.name, data, event.ts)))
+    }
+}
+
+fn event_json(name: &str, data: Value, ts: u64) -> Value {
+    json!({ "name": name, "data": data, "ts": ts })
+}
+
+/// Copy of `value` with only the dotted `paths`, missing paths are left out
+pub fn project(value: &Value, paths: &[String]) -> Value {
+    let mut projected = Value::Object(Map::new());
+    for path in paths {
+        let keys: Vec<&str> = path.split('.').collect();
+        let Some(field) = keys
+            .iter()
+            .try_fold(value, |value, key| value.as_object()?.get(*key))
+        else {
+            continue;
+        };
+
+        let mut target = &mut projected;
+        for key in &keys[..keys.len() - 1] {
+            target = target
+                .as_object_mut()
+                .expect("only objects are inserted on the path")
+                .entry(*key)
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !target.is_object() {
+                // a parent path was selected as well, it already contains this field
+                break;
+            }
+        }
+        if let Some(target) = target.as_object_mut() {
+            target.insert(keys[keys.len() - 1].to_string(), field.clone());
+        }
+    }
+    projected
+}
+
+/// Subscriptions of all sockets, by session and namespace
+#[derive(Debug, Default)]
+pub struct SocketSubscriptions {
+    sockets: RwLock<HashMap<Sid, HashMap<String, Arc<Subscription>>>>,
+}
+
+impl SocketSubscriptions {
+    pub fn get(&self, socket: &SocketRef) -> Option<Arc<Subscription>> {
+        self.sockets
+            .read()
+            .expect("Subscriptions lock poisoned")
+            .get(&socket.id)?
+            .get(socket.ns())
+            .cloned()
+    }
+
+    pub fn set(&self, socket: &SocketRef, subscription: Subscription) {
+        self.sockets
+            .write()
+            .expect("Subscriptions lock poisoned")
+            .entry(socket.id)
+            .or_default()
+            .insert(socket.ns().to_string(), Arc::new(subscription));
+    }
+
+    pub fn remove(&self, socket: &SocketRef) {
+        let mut sockets = self.sockets.write().expect("Subscriptions lock poisoned");
+        if let Some(namespaces) = sockets.get_mut(&socket.id) {
+            namespaces.remove(socket.ns());
+            if namespaces.is_empty() {
+                sockets.remove(&socket.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_event(mode: &str, speed: f64, ts: u64) -> GenericEvent {
+        GenericEvent {
+            name: "StateEvent".to_string(),
+            data: Box::new(json!({ "mode": mode, "speed": { "target": speed, "max": 5.0 } })),
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_project() {
+        let value = json!({
+            "temperatures": { "nozzle": 200.0, "front": 190.0 },
+            "pressure": 80.0,
+            "rpm": 10.0,
+        });
+        let paths = [
+            "temperatures.nozzle".to_string(),
+            "rpm".to_string(),
+            "missing.field".to_string(),
+        ];
+        assert_eq!(
+            project(&value, &paths),
+            json!({ "temperatures": { "nozzle": 200.0 }, "rpm": 10.0 })
+        );
+    }
+
+    #[test]
+    fn test_delta_encoder() {
+        let subscription = Subscription {
+            delta: true,
+            fields: HashMap::from([("StateEvent".to_string(), vec!["speed".to_string()])]),
+            ..Default::default()
+        };
+        let mut encoder = DeltaEncoder::new(Duration::from_secs(60));
+
+        // first state in full, projected to the subscribed fields
+        let EncodedEvent::Json(full) = encoder
+            .encode(&subscription, &state_event("Standby", 1.0, 1))
+            .unwrap()
+        else {
+            panic!("expected full event");
+        };
+        assert_eq!(
+            full,
+            json!({ "name": "StateEvent", "data": { "speed": { "target": 1.0, "max": 5.0 } }, "ts": 1 })
+        );
+
+        // unsubscribed field changed
+        assert!(matches!(
+            encoder
+                .encode(&subscription, &state_event("Running", 1.0, 2))
+                .unwrap(),
+            EncodedEvent::Skip
+        ));
+
+        let EncodedEvent::Json(patch) = encoder
+            .encode(&subscription, &state_event("Running", 2.0, 3))
+            .unwrap()
+        else {
+            panic!("expected patch");
+        };
+        assert_eq!(
+            patch,
+            json!({
+                "name": "StateEventPatch",
+                "data": [{ "op": "replace", "path": "/speed/target", "value": 2.0 }],
+                "ts": 3
+            })
+        );
+
+        // other events are unchanged
+        let live_values = GenericEvent {
+            name: "LiveValuesEvent".to_string(),
+            data: Box::new(json!({ "speed": 1.0 })),
+            ts: 4,
+        };
+        assert!(matches!(
+            encoder.encode(&subscription, &live_values).unwrap(),
+            EncodedEvent::Original
+        ));
+        assert!(subscription.wants("LiveValuesEvent"));
+
+        encoder.reset();
+        assert!(matches!(
+            encoder
+                .encode(&subscription, &state_event("Running", 2.0, 5))
+                .unwrap(),
+            EncodedEvent::Json(_)
+        ));
+    }
+}
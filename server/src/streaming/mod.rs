@@ -0,0 +1,415 @@
+//! High-rate streaming of machine signals, like a software oscilloscope
+//!
+//! A client starts a [`SignalStream`] for one machine. The loop that executes the machine samples
+//! the selected [`Machine::signal_names`] after `act` in every cycle into a [`FrameRing`], the
+//! REST handler drains the ring and sends the frames in the binary format of [`encode_header`]
+//! and [`encode_frames`] until the duration is over.
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use machines::Machine;
+use machines::machine_identification::MachineIdentificationUnique;
+use smol::Timer;
+use smol::stream::{Stream, StreamExt};
+
+use ring::FrameRing;
+
+pub mod ring;
+
+/// Longest stream a client can request
+pub const MAX_DURATION: Duration = Duration::from_secs(60);
+pub const MAX_SIGNALS: usize = 32;
+/// Streams running at the same time, each one costs time in the loop
+pub const MAX_STREAMS: usize = 4;
+/// Frames buffered per stream, about 5 s at a 300 µs cycle
+const RING_CAPACITY: usize = 16_384;
+/// How long a loop has to pick up a new stream
+const START_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often the ring is drained while the stream runs
+const DRAIN_INTERVAL: Duration = Duration::from_millis(20);
+
+pub const MAGIC: &[u8; 8] = b"QTSTREAM";
+pub const VERSION: u16 = 1;
+const RECORD_FRAME: u8 = 0;
+const RECORD_DROPPED: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StreamStatus {
+    /// Not picked up by a loop yet
+    Pending = 0,
+    Running = 1,
+    /// Duration is over or the stream was stopped
+    Finished = 2,
+    /// The machine doesn't have a requested signal, see [`SignalStream::error`]
+    Failed = 3,
+}
+
+impl StreamStatus {
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Running,
+            2 => Self::Finished,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// One stream of selected signals of one machine
+pub struct SignalStream {
+    pub machine: MachineIdentificationUnique,
+    pub signals: Vec<String>,
+    /// Sample every n-th cycle
+    pub decimation: u32,
+    pub duration: Duration,
+    /// A frame is the time since the start in ns followed by the signal values as `f64` bits
+    ring: FrameRing,
+    status: AtomicU8,
+    error: OnceLock<String>,
+    /// Set when the client went away
+    stopped: AtomicBool,
+}
+
+impl SignalStream {
+    pub fn status(&self) -> StreamStatus {
+        StreamStatus::from_u8(self.status.load(Ordering::Acquire))
+    }
+
+    fn set_status(&self, status: StreamStatus) {
+        self.status.store(status as u8, Ordering::Release);
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.get().map(String::as_str)
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub const fn ring(&self) -> &FrameRing {
+        &self.ring
+    }
+}
+
+/// Streams requested by clients, picked up by the loops on the next cycle
+struct StreamRegistry {
+    streams: Mutex<Vec<Arc<SignalStream>>>,
+    /// Changed on every start and removal, the loops only lock the registry if it changed
+    generation: AtomicU64,
+}
+
+static STREAMS: StreamRegistry = StreamRegistry {
+    streams: Mutex::new(vec![]),
+    generation: AtomicU64::new(0),
+};
+
+/// Starts sampling `signals` of `machine`, removed again when the returned guard is dropped
+pub fn start_stream(
+    machine: MachineIdentificationUnique,
+    signals: Vec<String>,
+    decimation: u32,
+    duration: Duration,
+) -> anyhow::Result<StreamGuard> {
+    if signals.is_empty() || signals.len() > MAX_SIGNALS {
+        anyhow::bail!("Select between 1 and {} signals", MAX_SIGNALS);
+    }
+    if decimation == 0 {
+        anyhow::bail!("Decimation must be at least 1");
+    }
+    if duration.is_zero() || duration > MAX_DURATION {
+        anyhow::bail!("Duration must be between 1 ms and {:?}", MAX_DURATION);
+    }
+
+    let stream = Arc::new(SignalStream {
+        machine,
+        ring: FrameRing::new(1 + signals.len(), RING_CAPACITY),
+        signals,
+        decimation,
+        duration,
+        status: AtomicU8::new(StreamStatus::Pending as u8),
+        error: OnceLock::new(),
+        stopped: AtomicBool::new(false),
+    });
+
+    let mut streams = STREAMS.streams.lock().expect("Stream registry poisoned");
+    if streams.len() >= MAX_STREAMS {
+        anyhow::bail!("{} streams are running already", MAX_STREAMS);
+    }
+    streams.push(stream.clone());
+    STREAMS.generation.fetch_add(1, Ordering::Release);
+    drop(streams);
+
+    Ok(StreamGuard(stream))
+}
+
+/// Removes the stream from the registry when dropped, e.g. when the client disconnects
+pub struct StreamGuard(Arc<SignalStream>);
+
+impl std::ops::Deref for StreamGuard {
+    type Target = SignalStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl StreamGuard {
+    /// Waits until a loop samples the stream
+    pub async fn wait_running(&self) -> anyhow::Result<()> {
+        let start = Instant::now();
+        loop {
+            match self.status() {
+                StreamStatus::Pending if start.elapsed() < START_TIMEOUT => {
+                    Timer::after(Duration::from_millis(1)).await;
+                }
+                StreamStatus::Pending => {
+                    anyhow::bail!("Machine is not executed by any loop")
+                }
+                StreamStatus::Running | StreamStatus::Finished => return Ok(()),
+                StreamStatus::Failed => {
+                    anyhow::bail!("{}", self.error().unwrap_or("Stream failed"))
+                }
+            }
+        }
+    }
+
+    /// The header followed by the frames until the stream finished
+    ///
+    /// Dropping the body, e.g. when the client disconnects, stops the stream.
+    pub fn into_body(self) -> impl Stream<Item = Result<Vec<u8>, Infallible>> + Send + 'static {
+        let header = encode_header(&self.signals);
+        smol::stream::once(Ok(header)).chain(smol::stream::unfold(
+            (self, vec![]),
+            |(stream, mut frames)| async move {
+                loop {
+                    let finished = stream.status() != StreamStatus::Running;
+                    frames.clear();
+                    stream.ring.drain_into(&mut frames);
+                    let dropped = stream.ring.take_dropped();
+                    if frames.is_empty() && dropped == 0 {
+                        if finished {
+                            return None;
+                        }
+                        Timer::after(DRAIN_INTERVAL).await;
+                        continue;
+                    }
+
+                    let width = stream.ring.width();
+                    let mut out = Vec::with_capacity(9 + frames.len() / width * (1 + 8 * width));
+                    encode_frames(&frames, width, dropped, &mut out);
+                    return Some((Ok(out), (stream, frames)));
+                }
+            },
+        ))
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.stop();
+        STREAMS
+            .streams
+            .lock()
+            .expect("Stream registry poisoned")
+            .retain(|s| !Arc::ptr_eq(s, &self.0));
+        STREAMS.generation.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// A stream being sampled by one loop
+struct ActiveStream {
+    stream: Arc<SignalStream>,
+    /// Index into the machine's signals for each selected signal
+    indices: Vec<usize>,
+    start: Instant,
+    cycle: u64,
+}
+
+/// Samples the streams of the machines executed by one loop
+///
+/// Lives in the loop thread. Without streams a cycle costs one atomic load.
+pub struct StreamSampler {
+    generation: u64,
+    streams: Vec<ActiveStream>,
+    /// All signals of the sampled machine
+    values: Vec<f64>,
+    frame: Vec<u64>,
+}
+
+impl Default for StreamSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamSampler {
+    pub const fn new() -> Self {
+        Self {
+            generation: 0,
+            streams: vec![],
+            values: vec![],
+            frame: vec![],
+        }
+    }
+
+    /// Called after all machines acted in a cycle
+    pub fn sample(&mut self, machines: &[Box<dyn Machine>], now: Instant) {
+        let generation = STREAMS.generation.load(Ordering::Acquire);
+        if generation != self.generation {
+            self.refresh(machines, generation);
+        }
+
+        for active in &mut self.streams {
+            let stream = &active.stream;
+            if stream.status() != StreamStatus::Running {
+                continue;
+            }
+            let elapsed = now.saturating_duration_since(active.start);
+            if stream.stopped.load(Ordering::Relaxed) || elapsed > stream.duration {
+                stream.set_status(StreamStatus::Finished);
+                continue;
+            }
+            active.cycle += 1;
+            if (active.cycle - 1) % u64::from(stream.decimation) != 0 {
+                continue;
+            }
+            let Some(machine) = machines
+                .iter()
+                .find(|m| m.get_machine_identification_unique() == stream.machine)
+            else {
+                // deleted while streaming
+                stream.set_status(StreamStatus::Finished);
+                continue;
+            };
+
+            self.values.clear();
+            self.values.resize(machine.signal_names().len(), f64::NAN);
+            machine.sample_signals(&mut self.values);
+
+            self.frame.clear();
+            self.frame
+                .push(u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX));
+            self.frame
+                .extend(active.indices.iter().map(|i| self.values[*i].to_bits()));
+            stream.ring.push(&self.frame);
+        }
+    }
+
+    /// Picks up new streams for machines of this loop and forgets removed ones
+    ///
+    /// Skipped if a client holds the registry lock, the loop must not wait.
+    fn refresh(&mut self, machines: &[Box<dyn Machine>], generation: u64) {
+        let Ok(streams) = STREAMS.streams.try_lock() else {
+            return;
+        };
+        self.generation = generation;
+        self.streams
+            .retain(|active| streams.iter().any(|s| Arc::ptr_eq(s, &active.stream)));
+
+        for stream in streams.iter() {
+            if stream.status() != StreamStatus::Pending
+                || self
+                    .streams
+                    .iter()
+                    .any(|active| Arc::ptr_eq(&active.stream, stream))
+            {
+                continue;
+            }
+            let Some(machine) = machines
+                .iter()
+                .find(|m| m.get_machine_identification_unique() == stream.machine)
+            else {
+                // executed by the other loop
+                continue;
+            };
+
+            let names = machine.signal_names();
+            let indices: Result<Vec<usize>, &String> = stream
+                .signals
+                .iter()
+                .map(|signal| names.iter().position(|n| n == signal).ok_or(signal))
+                .collect();
+            match indices {
+                Ok(indices) => {
+                    stream.set_status(StreamStatus::Running);
+                    self.streams.push(ActiveStream {
+                        stream: stream.clone(),
+                        indices,
+                        start: Instant::now(),
+                        cycle: 0,
+                    });
+                }
+                Err(signal) => {
+                    let _ = stream.error.set(format!(
+                        "Unknown signal `{}`, available: {}",
+                        signal,
+                        names.join(", ")
+                    ));
+                    stream.set_status(StreamStatus::Failed);
+                }
+            }
+        }
+        self.frame.reserve(1 + MAX_SIGNALS);
+    }
+}
+
+/// Start of a stream: magic, version, number of signals and the length prefixed signal names
+///
+/// All integers are little endian.
+pub fn encode_header(signals: &[String]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + signals.iter().map(|s| 2 + s.len()).sum::<usize>());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&(signals.len() as u16).to_le_bytes());
+    for signal in signals {
+        out.extend_from_slice(&(signal.len() as u16).to_le_bytes());
+        out.extend_from_slice(signal.as_bytes());
+    }
+    out
+}
+
+/// Records after the header
+///
+/// - `0`, time since the start in ns as `u64`, one `f64` per signal: a frame
+/// - `1`, number of frames as `u64`: frames dropped because the client didn't keep up
+pub fn encode_frames(frames: &[u64], width: usize, dropped: u64, out: &mut Vec<u8>) {
+    if dropped > 0 {
+        out.push(RECORD_DROPPED);
+        out.extend_from_slice(&dropped.to_le_bytes());
+    }
+    for frame in frames.chunks_exact(width) {
+        out.push(RECORD_FRAME);
+        for word in frame {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let header = encode_header(&["speed".to_string()]);
+        assert_eq!(&header[..8], MAGIC);
+        assert_eq!(
+            &header[8..],
+            &[1, 0, 1, 0, 5, 0, b's', b'p', b'e', b'e', b'd']
+        );
+
+        let mut out = vec![];
+        encode_frames(&[300_000, 2.5f64.to_bits()], 2, 3, &mut out);
+        let mut expected = vec![RECORD_DROPPED];
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.push(RECORD_FRAME);
+        expected.extend_from_slice(&300_000u64.to_le_bytes());
+        expected.extend_from_slice(&2.5f64.to_le_bytes());
+        assert_eq!(out, expected);
+    }
+}
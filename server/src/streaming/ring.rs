@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Single producer, single consumer ring of frames with a fixed number of `u64` words
+///
+/// The producer is the RT loop and never waits: a frame that doesn't fit is dropped and counted.
+pub struct FrameRing {
+    width: usize,
+    capacity: u64,
+    slots: Box<[AtomicU64]>,
+    /// Frames written, only the producer stores
+    head: AtomicU64,
+    /// Frames read, only the consumer stores
+    tail: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl FrameRing {
+    pub fn new(width: usize, capacity: usize) -> Self {
+        Self {
+            width,
+            capacity: capacity as u64,
+            slots: (0..width * capacity).map(|_| AtomicU64::new(0)).collect(),
+            head: AtomicU64::new(0),
+            tail: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Called by the producer only, returns `false` if the frame was dropped
+    pub fn push(&self, frame: &[u64]) -> bool {
+        debug_assert_eq!(frame.len(), self.width);
+        let head = self.head.load(Ordering::Relaxed);
+        if head - self.tail.load(Ordering::Acquire) >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        let start = (head % self.capacity) as usize * self.width;
+        for (slot, word) in self.slots[start..start + self.width].iter().zip(frame) {
+            slot.store(*word, Ordering::Relaxed);
+        }
+        self.head.store(head + 1, Ordering::Release);
+        true
+    }
+
+    /// Called by the consumer only, appends all available frames to `out`
+    ///
+    /// Returns the number of frames read.
+    pub fn drain_into(&self, out: &mut Vec<u64>) -> u64 {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        for index in tail..head {
+            let start = (index % self.capacity) as usize * self.width;
+            out.extend(
+                self.slots[start..start + self.width]
+                    .iter()
+                    .map(|slot| slot.load(Ordering::Relaxed)),
+            );
+        }
+        self.tail.store(head, Ordering::Release);
+        head - tail
+    }
+
+    /// Frames dropped because the consumer didn't keep up, resets the counter
+    pub fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_ring() {
+        let ring = FrameRing::new(2, 3);
+        let mut out = vec![];
+
+        assert!(ring.push(&[1, 10]));
+        assert!(ring.push(&[2, 20]));
+        assert_eq!(ring.drain_into(&mut out), 2);
+        assert_eq!(out, vec![1, 10, 2, 20]);
+
+        // wraps around, the fourth frame doesn't fit
+        out.clear();
+        assert!(ring.push(&[3, 30]));
+        assert!(ring.push(&[4, 40]));
+        assert!(ring.push(&[5, 50]));
+        assert!(!ring.push(&[6, 60]));
+        assert_eq!(ring.take_dropped(), 1);
+        assert_eq!(ring.take_dropped(), 0);
+        assert_eq!(ring.drain_into(&mut out), 3);
+        assert_eq!(out, vec![3, 30, 4, 40, 5, 50]);
+
+        out.clear();
+        assert_eq!(ring.drain_into(&mut out), 0);
+        assert!(out.is_empty());
+    }
+}
@@ -37,6 +37,7 @@ system! {
         angular_velocity::AngularVelocity,
         electric_current::ElectricCurrent,
         electric_potential::ElectricPotential,
+        force::Force,
         frequency::Frequency,
         jerk::Jerk,
         length::Length,